@@ -0,0 +1,307 @@
+//! Throughput of the standard invoker on representative workloads, in
+//! millions of gas per second.
+//!
+//! Run with `cargo bench --bench workloads`, optionally followed by the
+//! names of the workloads to run.
+
+use std::time::{Duration, Instant};
+
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+	interpreter::{asm::Assembler, opcode::Opcode},
+	standard::{transact_with_gas, Config, Etable, EtableResolver, Invoker, TransactArgs},
+};
+use primitive_types::{H160, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const CONTRACT: H160 = H160::repeat_byte(0x22);
+const GAS_LIMIT: u64 = 30_000_000;
+
+/// Minimum time spent running a workload, after a first warm-up run.
+const MEASUREMENT_TIME: Duration = Duration::from_secs(2);
+/// Minimum number of measured runs of a workload.
+const MIN_RUNS: u32 = 5;
+
+struct Workload {
+	name: &'static str,
+	code: Assembler,
+}
+
+/// Token transfers from the caller to a new address per iteration, as in
+/// ERC-20: two balance updates in a mapping at slot 0 and a `Transfer` log.
+fn erc20_transfer_loop() -> Workload {
+	let transfer_topic = U256::from_str_radix(
+		"ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+		16,
+	)
+	.unwrap();
+
+	let code = Assembler::new()
+		.push(1000)
+		.label("loop")
+		// balances[caller] -= 1
+		.op(Opcode::CALLER)
+		.push(0)
+		.op(Opcode::MSTORE)
+		.push(0)
+		.push(32)
+		.op(Opcode::MSTORE)
+		.push(64)
+		.push(0)
+		.op(Opcode::SHA3)
+		.op(Opcode::DUP1)
+		.op(Opcode::SLOAD)
+		.push(1)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SUB)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SSTORE)
+		// balances[counter] += 1
+		.op(Opcode::DUP1)
+		.push(0)
+		.op(Opcode::MSTORE)
+		.push(64)
+		.push(0)
+		.op(Opcode::SHA3)
+		.op(Opcode::DUP1)
+		.op(Opcode::SLOAD)
+		.push(1)
+		.op(Opcode::ADD)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SSTORE)
+		// Transfer(caller, counter, amount)
+		.push(1)
+		.push(0)
+		.op(Opcode::MSTORE)
+		.op(Opcode::DUP1)
+		.op(Opcode::CALLER)
+		.push(transfer_topic)
+		.push(32)
+		.push(0)
+		.op(Opcode::LOG3)
+		// counter -= 1
+		.push(1)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SUB)
+		.op(Opcode::DUP1)
+		.jumpi("loop")
+		.op(Opcode::STOP);
+
+	Workload {
+		name: "erc20_transfer_loop",
+		code,
+	}
+}
+
+/// Hash of the previous hash, in memory.
+fn keccak_loop() -> Workload {
+	let code = Assembler::new()
+		.push(200_000)
+		.label("loop")
+		.push(32)
+		.push(0)
+		.op(Opcode::SHA3)
+		.push(0)
+		.op(Opcode::MSTORE)
+		.push(1)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SUB)
+		.op(Opcode::DUP1)
+		.jumpi("loop")
+		.op(Opcode::STOP);
+
+	Workload {
+		name: "keccak_loop",
+		code,
+	}
+}
+
+/// Copy of 32 KiB within memory, expanded to 64 KiB by the first copy.
+fn memory_copy_loop() -> Workload {
+	let code = Assembler::new()
+		.push(5000)
+		.label("loop")
+		.push(32 * 1024)
+		.push(0)
+		.push(32 * 1024)
+		.op(Opcode::MCOPY)
+		.push(1)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SUB)
+		.op(Opcode::DUP1)
+		.jumpi("loop")
+		.op(Opcode::STOP);
+
+	Workload {
+		name: "memory_copy_loop",
+		code,
+	}
+}
+
+/// Calls expanding their memory to 256 KiB with a single byte write, as in
+/// `stMemoryStressTest`, so that the expansions dominate.
+fn memory_stress_loop() -> Workload {
+	let code = Assembler::new()
+		.op(Opcode::CALLDATASIZE)
+		.jumpi("expand")
+		.push(150)
+		.label("loop")
+		// Call itself with one byte of call data.
+		.push(0)
+		.push(0)
+		.push(1)
+		.push(0)
+		.push(0)
+		.op(Opcode::ADDRESS)
+		.op(Opcode::GAS)
+		.op(Opcode::CALL)
+		.op(Opcode::POP)
+		.push(1)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SUB)
+		.op(Opcode::DUP1)
+		.jumpi("loop")
+		.op(Opcode::STOP)
+		.label("expand")
+		.push(1)
+		.push(256 * 1024 - 1)
+		.op(Opcode::MSTORE8)
+		.op(Opcode::STOP);
+
+	Workload {
+		name: "memory_stress_loop",
+		code,
+	}
+}
+
+/// Modular arithmetic on 256-bit words: a square modulo a prime, an
+/// addition and a cube.
+fn arithmetic_loop() -> Workload {
+	let prime = U256::from(2).pow(U256::from(255)) - 19;
+
+	let code = Assembler::new()
+		.push(7)
+		.push(200_000)
+		.label("loop")
+		.op(Opcode::SWAP1)
+		// x = x * x % prime + counter
+		.push(prime)
+		.op(Opcode::DUP2)
+		.op(Opcode::DUP3)
+		.op(Opcode::MULMOD)
+		.op(Opcode::SWAP1)
+		.op(Opcode::POP)
+		.op(Opcode::DUP2)
+		.op(Opcode::ADD)
+		// x ^= x ** 3
+		.push(3)
+		.op(Opcode::DUP2)
+		.op(Opcode::EXP)
+		.op(Opcode::XOR)
+		.op(Opcode::SWAP1)
+		// counter -= 1
+		.push(1)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SUB)
+		.op(Opcode::DUP1)
+		.jumpi("loop")
+		.op(Opcode::STOP);
+
+	Workload {
+		name: "arithmetic_loop",
+		code,
+	}
+}
+
+/// Gas used by a call to the workload, and the time it took.
+fn run(config: &Config, backend: &InMemoryBackend) -> (u64, Duration) {
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let resolver = EtableResolver::new(config, &(), &etable);
+	let invoker = Invoker::new(config, &resolver);
+	let args = TransactArgs::Call {
+		caller: CALLER,
+		address: CONTRACT,
+		value: U256::zero(),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(GAS_LIMIT),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	};
+
+	let mut overlay = backend.overlay();
+	let start = Instant::now();
+	let (result, gas) =
+		transact_with_gas(args, None, &mut overlay, &invoker).expect("transaction is valid");
+	let elapsed = start.elapsed();
+	result.expect("workload succeeds");
+
+	(gas.used.as_u64(), elapsed)
+}
+
+fn main() {
+	// Arguments other than flags, such as the `--bench` given by cargo,
+	// select the workloads to run.
+	let names = std::env::args()
+		.skip(1)
+		.filter(|arg| !arg.starts_with("--"))
+		.collect::<Vec<_>>();
+
+	let config = Config::cancun();
+	let workloads = [
+		erc20_transfer_loop(),
+		keccak_loop(),
+		memory_copy_loop(),
+		memory_stress_loop(),
+		arithmetic_loop(),
+	];
+
+	for workload in workloads {
+		if !names.is_empty() && !names.iter().any(|name| workload.name.contains(name)) {
+			continue;
+		}
+
+		let backend = InMemoryBackend::from_genesis(
+			InMemoryEnvironment {
+				block_gas_limit: U256::from(GAS_LIMIT),
+				..Default::default()
+			},
+			[
+				(CALLER, InMemoryAccount::default()),
+				(
+					CONTRACT,
+					InMemoryAccount {
+						code: workload.code.assemble().expect("workload assembles"),
+						..Default::default()
+					},
+				),
+			]
+			.into(),
+		);
+
+		let (gas, _) = run(&config, &backend);
+		let mut runs = 0;
+		let mut total = Duration::ZERO;
+		while runs < MIN_RUNS || total < MEASUREMENT_TIME {
+			total += run(&config, &backend).1;
+			runs += 1;
+		}
+
+		let per_run = total / runs;
+		println!(
+			"{:<24}{:>12} gas{:>12.3?}/run{:>10.1} Mgas/s",
+			workload.name,
+			gas,
+			per_run,
+			gas as f64 / per_run.as_secs_f64() / 1e6,
+		);
+	}
+}
@@ -0,0 +1,196 @@
+/*
+ * C interface of the EVM.
+ *
+ * Addresses are 20 bytes, and words 32 bytes in big-endian. The host
+ * provides its state through a table of callbacks, and reads the output, gas
+ * used, logs and state diff of each execution from an opaque result, to
+ * apply the changes itself.
+ */
+
+#ifndef EVM_H
+#define EVM_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Callback reading a word of an account, such as its balance. */
+typedef void (*EvmAccountWordFn)(void *context, const uint8_t *address, uint8_t *out);
+
+/*
+ * Table of callbacks reading the state of the host. A null callback reads as
+ * zero, empty code or a missing account.
+ */
+typedef struct EvmHost {
+	/* Opaque pointer passed to every callback. */
+	void *context;
+	/* Write the balance of `address` to `out`. */
+	EvmAccountWordFn balance;
+	/* Write the nonce of `address` to `out`. */
+	EvmAccountWordFn nonce;
+	/*
+	 * Copy at most `len` bytes of the code of `address` to `out`, and return
+	 * the size of the code. Called with a null `out` to get the size.
+	 */
+	size_t (*code)(void *context, const uint8_t *address, uint8_t *out, size_t len);
+	/* Write the value of the storage of `address` at `key` to `out`. */
+	void (*storage)(void *context, const uint8_t *address, const uint8_t *key, uint8_t *out);
+	/* Whether `address` exists. */
+	bool (*exists)(void *context, const uint8_t *address);
+	/*
+	 * Write the hash of the block `number` to `out`. Only called for the 256
+	 * blocks before the current one.
+	 */
+	void (*block_hash)(void *context, const uint8_t *number, uint8_t *out);
+} EvmHost;
+
+/* Block environment of an execution. */
+typedef struct EvmEnvironment {
+	uint8_t block_number[32];
+	uint8_t block_coinbase[20];
+	uint8_t block_timestamp[32];
+	uint8_t block_difficulty[32];
+	/* Whether the block has a randomness (`prevRandao`), after the Merge. */
+	bool has_block_randomness;
+	uint8_t block_randomness[32];
+	uint8_t block_gas_limit[32];
+	uint8_t block_base_fee_per_gas[32];
+	/* Block excess blob gas (see EIP-4844). */
+	uint8_t block_excess_blob_gas[32];
+	uint8_t chain_id[32];
+} EvmEnvironment;
+
+/* A transaction. A null `to` makes it a create, with `data` as init code. */
+typedef struct EvmTransaction {
+	uint8_t caller[20];
+	/* Target of a call, 20 bytes, or null for a create. */
+	const uint8_t *to;
+	uint8_t value[32];
+	/* Call data or init code. */
+	const uint8_t *data;
+	size_t data_len;
+	/* Whether `nonce` is checked. Otherwise the nonce of the sender is used. */
+	bool has_nonce;
+	uint64_t nonce;
+	uint64_t gas_limit;
+	uint8_t gas_price[32];
+} EvmTransaction;
+
+/* How an execution ended. */
+typedef enum EvmStatus {
+	/* The execution succeeded. */
+	EVM_SUCCEED = 0,
+	/* The execution reverted. */
+	EVM_REVERT = 1,
+	/* The execution failed with an EVM exception, such as out of gas. */
+	EVM_ERROR = 2,
+	/* The execution aborted with a fatal error, or on the instruction limit. */
+	EVM_FATAL = 3,
+	/* The transaction is invalid, and was not executed. */
+	EVM_INVALID = 4,
+} EvmStatus;
+
+/* A log emitted by an execution. Its pointers are valid as long as the result. */
+typedef struct EvmLog {
+	uint8_t address[20];
+	/* Topics, each of 32 bytes. */
+	const uint8_t *topics;
+	size_t topic_count;
+	const uint8_t *data;
+	size_t data_len;
+} EvmLog;
+
+/*
+ * Changes of an account made by an execution. Its pointers are valid as long
+ * as the result.
+ */
+typedef struct EvmAccountDiff {
+	uint8_t address[20];
+	/* Whether the account was removed. The other fields are then unset. */
+	bool removed;
+	bool balance_changed;
+	/* Balance after the execution. */
+	uint8_t balance[32];
+	bool nonce_changed;
+	/* Nonce after the execution. */
+	uint8_t nonce[32];
+	bool code_changed;
+	/* Code after the execution. */
+	const uint8_t *code;
+	size_t code_len;
+	/* Number of changed storage slots, read with `evm_result_storage`. */
+	size_t storage_count;
+} EvmAccountDiff;
+
+typedef struct EvmBackend EvmBackend;
+typedef struct EvmResult EvmResult;
+
+/*
+ * Create a backend reading the state through the callbacks of `host`, in the
+ * block `environment`. Both are copied. Returns null if either is null.
+ */
+EvmBackend *evm_backend_new(const EvmHost *host, const EvmEnvironment *environment);
+
+/* Free a backend. */
+void evm_backend_free(EvmBackend *backend);
+
+/*
+ * Execute a transaction on the state of `backend`, with the rules of `fork`,
+ * such as "cancun". The backend is not modified: the changes are in the
+ * state diff of the result.
+ *
+ * Returns null if an argument is null, the fork is unknown or the execution
+ * panicked. Otherwise the result is freed with `evm_result_free`.
+ */
+EvmResult *evm_transact(const EvmBackend *backend, const char *fork,
+                        const EvmTransaction *transaction);
+
+/* Status of a result. */
+EvmStatus evm_result_status(const EvmResult *result);
+
+/*
+ * Output of a call, empty for a create or a failed execution. Its length is
+ * written to `len`.
+ */
+const uint8_t *evm_result_output(const EvmResult *result, size_t *len);
+
+/* Write the address created by a create to `out`. Whether there is one. */
+bool evm_result_created(const EvmResult *result, uint8_t *out);
+
+/* Gas charged, after refunds, as in the receipt. Zero for an invalid transaction. */
+uint64_t evm_result_gas_used(const EvmResult *result);
+
+/* Description of the error of a failed execution, or null. */
+const char *evm_result_error(const EvmResult *result);
+
+/* Number of logs. */
+size_t evm_result_log_count(const EvmResult *result);
+
+/* Write the log at `index` to `out`. Whether there is one. */
+bool evm_result_log(const EvmResult *result, size_t index, EvmLog *out);
+
+/* Number of changed accounts. */
+size_t evm_result_account_count(const EvmResult *result);
+
+/* Write the changes of the account at `index` to `out`. Whether there is one. */
+bool evm_result_account(const EvmResult *result, size_t index, EvmAccountDiff *out);
+
+/*
+ * Write the key and the new value of the changed storage slot at `slot` of
+ * the account at `account` to `key` and `value`. Whether there is one.
+ */
+bool evm_result_storage(const EvmResult *result, size_t account, size_t slot,
+                        uint8_t *key, uint8_t *value);
+
+/* Free a result, and the memory its pointers point to. */
+void evm_result_free(EvmResult *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* EVM_H */
@@ -0,0 +1,215 @@
+use core::ffi::c_void;
+use std::ptr;
+
+use evm::backend::{RuntimeBaseBackend, RuntimeEnvironment};
+use primitive_types::{H160, H256, U256};
+
+/// Callback reading a word of an account, such as its balance.
+pub type EvmAccountWordFn =
+	unsafe extern "C" fn(context: *mut c_void, address: *const u8, out: *mut u8);
+
+/// Table of callbacks reading the state of the host.
+///
+/// Addresses are 20 bytes, and words 32 bytes in big-endian. A null callback
+/// reads as zero, empty code or a missing account.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EvmHost {
+	/// Opaque pointer passed to every callback.
+	pub context: *mut c_void,
+	/// Write the balance of `address` to `out`.
+	pub balance: Option<EvmAccountWordFn>,
+	/// Write the nonce of `address` to `out`.
+	pub nonce: Option<EvmAccountWordFn>,
+	/// Copy at most `len` bytes of the code of `address` to `out`, and return
+	/// the size of the code. Called with a null `out` to get the size.
+	pub code: Option<
+		unsafe extern "C" fn(
+			context: *mut c_void,
+			address: *const u8,
+			out: *mut u8,
+			len: usize,
+		) -> usize,
+	>,
+	/// Write the value of the storage of `address` at `key` to `out`.
+	pub storage: Option<
+		unsafe extern "C" fn(
+			context: *mut c_void,
+			address: *const u8,
+			key: *const u8,
+			out: *mut u8,
+		),
+	>,
+	/// Whether `address` exists.
+	pub exists: Option<unsafe extern "C" fn(context: *mut c_void, address: *const u8) -> bool>,
+	/// Write the hash of the block `number` to `out`. Only called for the 256
+	/// blocks before the current one.
+	pub block_hash:
+		Option<unsafe extern "C" fn(context: *mut c_void, number: *const u8, out: *mut u8)>,
+}
+
+/// Block environment of an execution.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EvmEnvironment {
+	/// Block number.
+	pub block_number: [u8; 32],
+	/// Block coinbase.
+	pub block_coinbase: [u8; 20],
+	/// Block timestamp.
+	pub block_timestamp: [u8; 32],
+	/// Block difficulty.
+	pub block_difficulty: [u8; 32],
+	/// Whether the block has a randomness, after the Merge.
+	pub has_block_randomness: bool,
+	/// Block randomness (`prevRandao`).
+	pub block_randomness: [u8; 32],
+	/// Block gas limit.
+	pub block_gas_limit: [u8; 32],
+	/// Block base fee.
+	pub block_base_fee_per_gas: [u8; 32],
+	/// Block excess blob gas (see EIP-4844).
+	pub block_excess_blob_gas: [u8; 32],
+	/// Chain ID.
+	pub chain_id: [u8; 32],
+}
+
+/// A read-only backend calling back into the host.
+pub struct EvmBackend {
+	host: EvmHost,
+	environment: EvmEnvironment,
+}
+
+impl EvmBackend {
+	pub(crate) fn new(host: EvmHost, environment: EvmEnvironment) -> Self {
+		Self { host, environment }
+	}
+
+	fn account_word(&self, callback: Option<EvmAccountWordFn>, address: H160) -> U256 {
+		let mut out = [0u8; 32];
+		if let Some(callback) = callback {
+			// SAFETY: the host guarantees its callbacks are sound to call with
+			// its context and buffers of the documented sizes.
+			unsafe { callback(self.host.context, address.as_ptr(), out.as_mut_ptr()) };
+		}
+		U256::from_big_endian(&out)
+	}
+}
+
+impl RuntimeEnvironment for EvmBackend {
+	fn block_hash(&self, number: U256) -> H256 {
+		let current = self.block_number();
+		if number >= current || current - number > U256::from(256) {
+			return H256::default();
+		}
+
+		let mut out = H256::default();
+		if let Some(block_hash) = self.host.block_hash {
+			let mut word = [0u8; 32];
+			number.to_big_endian(&mut word);
+			// SAFETY: as in `account_word`.
+			unsafe { block_hash(self.host.context, word.as_ptr(), out.as_mut_ptr()) };
+		}
+		out
+	}
+
+	fn block_number(&self) -> U256 {
+		U256::from_big_endian(&self.environment.block_number)
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		H160(self.environment.block_coinbase)
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		U256::from_big_endian(&self.environment.block_timestamp)
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		U256::from_big_endian(&self.environment.block_difficulty)
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		self.environment
+			.has_block_randomness
+			.then_some(H256(self.environment.block_randomness))
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		U256::from_big_endian(&self.environment.block_gas_limit)
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		U256::from_big_endian(&self.environment.block_base_fee_per_gas)
+	}
+
+	fn block_excess_blob_gas(&self) -> U256 {
+		U256::from_big_endian(&self.environment.block_excess_blob_gas)
+	}
+
+	fn chain_id(&self) -> U256 {
+		U256::from_big_endian(&self.environment.chain_id)
+	}
+}
+
+impl RuntimeBaseBackend for EvmBackend {
+	fn balance(&self, address: H160) -> U256 {
+		self.account_word(self.host.balance, address)
+	}
+
+	fn code_size(&self, address: H160) -> U256 {
+		match self.host.code {
+			// SAFETY: as in `account_word`.
+			Some(code) => {
+				U256::from(unsafe { code(self.host.context, address.as_ptr(), ptr::null_mut(), 0) })
+			}
+			None => U256::zero(),
+		}
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		let code = match self.host.code {
+			Some(code) => code,
+			None => return Vec::new(),
+		};
+
+		// SAFETY: as in `account_word`.
+		let len = unsafe { code(self.host.context, address.as_ptr(), ptr::null_mut(), 0) };
+		let mut out = vec![0u8; len];
+		let copied = unsafe { code(self.host.context, address.as_ptr(), out.as_mut_ptr(), len) };
+		out.truncate(copied);
+		out
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		let mut out = H256::default();
+		if let Some(storage) = self.host.storage {
+			// SAFETY: as in `account_word`.
+			unsafe {
+				storage(
+					self.host.context,
+					address.as_ptr(),
+					index.as_ptr(),
+					out.as_mut_ptr(),
+				)
+			};
+		}
+		out
+	}
+
+	fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+		H256::default()
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		match self.host.exists {
+			// SAFETY: as in `account_word`.
+			Some(exists) => unsafe { exists(self.host.context, address.as_ptr()) },
+			None => false,
+		}
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		self.account_word(self.host.nonce, address)
+	}
+}
@@ -0,0 +1,346 @@
+//! C interface of the EVM, to embed it in clients written in other languages.
+//!
+//! The host provides its state through a table of callbacks, and reads the
+//! output, gas used, logs and state diff of each execution from an opaque
+//! result, to apply the changes itself. The declarations are in
+//! `include/evm.h`.
+
+mod backend;
+mod result;
+
+use std::{
+	ffi::{c_char, CStr},
+	panic::{self, AssertUnwindSafe},
+	ptr, slice,
+};
+
+use evm::standard::{transact_with_gas, Config, Etable, EtableResolver, Invoker, TransactArgs};
+use evm_precompile::StandardPrecompileSet;
+use primitive_types::{H160, U256};
+
+pub use crate::{
+	backend::{EvmAccountWordFn, EvmBackend, EvmEnvironment, EvmHost},
+	result::{EvmAccountDiff, EvmLog, EvmResult, EvmStatus},
+};
+
+/// A transaction. A null `to` makes it a create, with `data` as init code.
+#[repr(C)]
+pub struct EvmTransaction {
+	/// Sender.
+	pub caller: [u8; 20],
+	/// Target of a call, 20 bytes, or null for a create.
+	pub to: *const u8,
+	/// Value.
+	pub value: [u8; 32],
+	/// Call data or init code.
+	pub data: *const u8,
+	/// Length of the data.
+	pub data_len: usize,
+	/// Whether `nonce` is checked. Otherwise the nonce of the sender is used.
+	pub has_nonce: bool,
+	/// Nonce.
+	pub nonce: u64,
+	/// Gas limit.
+	pub gas_limit: u64,
+	/// Gas price.
+	pub gas_price: [u8; 32],
+}
+
+/// Create a backend reading the state through the callbacks of `host`, in the
+/// block `environment`. Both are copied. Returns null if either is null.
+///
+/// # Safety
+///
+/// `host` and `environment` must be null or valid, and the callbacks of
+/// `host` sound to call with its context for as long as the backend lives.
+#[no_mangle]
+pub unsafe extern "C" fn evm_backend_new(
+	host: *const EvmHost,
+	environment: *const EvmEnvironment,
+) -> *mut EvmBackend {
+	match (host.as_ref(), environment.as_ref()) {
+		(Some(host), Some(environment)) => {
+			Box::into_raw(Box::new(EvmBackend::new(*host, *environment)))
+		}
+		_ => ptr::null_mut(),
+	}
+}
+
+/// Free a backend.
+///
+/// # Safety
+///
+/// `backend` must be null or returned by [evm_backend_new], and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn evm_backend_free(backend: *mut EvmBackend) {
+	if !backend.is_null() {
+		drop(Box::from_raw(backend));
+	}
+}
+
+/// Execute a transaction on the state of `backend`, with the rules of
+/// `fork`, such as `"cancun"`. The backend is not modified: the changes are
+/// in the state diff of the result.
+///
+/// Returns null if an argument is null, the fork is unknown or the execution
+/// panicked. Otherwise the result is freed with [evm_result_free].
+///
+/// # Safety
+///
+/// `backend` must be null or valid, `fork` null or a nul-terminated string,
+/// and `transaction` null or valid, with `data_len` bytes at `data` and 20
+/// at a non-null `to`.
+#[no_mangle]
+pub unsafe extern "C" fn evm_transact(
+	backend: *const EvmBackend,
+	fork: *const c_char,
+	transaction: *const EvmTransaction,
+) -> *mut EvmResult {
+	let (backend, transaction) = match (backend.as_ref(), transaction.as_ref()) {
+		(Some(backend), Some(transaction)) if !fork.is_null() => (backend, transaction),
+		_ => return ptr::null_mut(),
+	};
+	let config = match CStr::from_ptr(fork)
+		.to_str()
+		.ok()
+		.and_then(Config::from_fork_name)
+	{
+		Some(config) => config,
+		None => return ptr::null_mut(),
+	};
+	let args = transact_args(transaction);
+
+	let result = panic::catch_unwind(AssertUnwindSafe(|| {
+		let etable = (
+			Etable::single(evm::standard::eval_gasometer),
+			Etable::runtime(),
+		);
+		let precompiles = StandardPrecompileSet::new(&config);
+		let resolver = EtableResolver::new(&config, &precompiles, &etable);
+		let invoker = Invoker::new(&config, &resolver);
+
+		let mut overlay = evm::backend::OverlayedBackend::new(backend, Default::default());
+		match transact_with_gas(args, None, &mut overlay, &invoker) {
+			Ok((result, gas)) => {
+				let state_diff = overlay.state_diff();
+				let logs = overlay.deconstruct().1.logs;
+				EvmResult::executed(result, gas.effective, logs, state_diff)
+			}
+			Err(err) => EvmResult::invalid(err),
+		}
+	}));
+
+	match result {
+		Ok(result) => Box::into_raw(Box::new(result)),
+		Err(_) => ptr::null_mut(),
+	}
+}
+
+unsafe fn transact_args(transaction: &EvmTransaction) -> TransactArgs {
+	let data = if transaction.data.is_null() {
+		Vec::new()
+	} else {
+		slice::from_raw_parts(transaction.data, transaction.data_len).to_vec()
+	};
+	let caller = H160(transaction.caller);
+	let value = U256::from_big_endian(&transaction.value);
+	let nonce = transaction
+		.has_nonce
+		.then_some(U256::from(transaction.nonce));
+	let gas_limit = U256::from(transaction.gas_limit);
+	let gas_price = U256::from_big_endian(&transaction.gas_price);
+
+	if transaction.to.is_null() {
+		TransactArgs::Create {
+			caller,
+			value,
+			init_code: data,
+			salt: None,
+			nonce,
+			fee_payer: None,
+			gas_limit,
+			gas_price,
+			max_priority_fee_per_gas: None,
+			access_list: Vec::new(),
+			overrides: Default::default(),
+		}
+	} else {
+		TransactArgs::Call {
+			caller,
+			address: H160::from_slice(slice::from_raw_parts(transaction.to, 20)),
+			value,
+			data,
+			nonce,
+			fee_payer: None,
+			gas_limit,
+			gas_price,
+			max_priority_fee_per_gas: None,
+			access_list: Vec::new(),
+			blob_hashes: Vec::new(),
+			max_fee_per_blob_gas: None,
+			overrides: Default::default(),
+		}
+	}
+}
+
+/// Status of a result.
+///
+/// # Safety
+///
+/// `result` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn evm_result_status(result: *const EvmResult) -> EvmStatus {
+	(*result).status
+}
+
+/// Output of a call, empty for a create or a failed execution. Its length is
+/// written to `len`.
+///
+/// # Safety
+///
+/// `result` and `len` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn evm_result_output(result: *const EvmResult, len: *mut usize) -> *const u8 {
+	let output = &(*result).output;
+	*len = output.len();
+	output.as_ptr()
+}
+
+/// Write the address created by a create to `out`, 20 bytes. Whether there
+/// is one.
+///
+/// # Safety
+///
+/// `result` and `out` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn evm_result_created(result: *const EvmResult, out: *mut u8) -> bool {
+	match (*result).created {
+		Some(address) => {
+			ptr::copy_nonoverlapping(address.as_ptr(), out, 20);
+			true
+		}
+		None => false,
+	}
+}
+
+/// Gas charged, after refunds, as in the receipt. Zero for an invalid
+/// transaction.
+///
+/// # Safety
+///
+/// `result` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn evm_result_gas_used(result: *const EvmResult) -> u64 {
+	(*result).gas_used
+}
+
+/// Description of the error of a failed execution, or null.
+///
+/// # Safety
+///
+/// `result` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn evm_result_error(result: *const EvmResult) -> *const c_char {
+	match &(*result).error {
+		Some(error) => error.as_ptr(),
+		None => ptr::null(),
+	}
+}
+
+/// Number of logs.
+///
+/// # Safety
+///
+/// `result` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn evm_result_log_count(result: *const EvmResult) -> usize {
+	(*result).log_count()
+}
+
+/// Write the log at `index` to `out`. Whether there is one.
+///
+/// # Safety
+///
+/// `result` and `out` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn evm_result_log(
+	result: *const EvmResult,
+	index: usize,
+	out: *mut EvmLog,
+) -> bool {
+	match (*result).log(index) {
+		Some(log) => {
+			out.write(log);
+			true
+		}
+		None => false,
+	}
+}
+
+/// Number of changed accounts.
+///
+/// # Safety
+///
+/// `result` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn evm_result_account_count(result: *const EvmResult) -> usize {
+	(*result).account_count()
+}
+
+/// Write the changes of the account at `index` to `out`. Whether there is
+/// one.
+///
+/// # Safety
+///
+/// `result` and `out` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn evm_result_account(
+	result: *const EvmResult,
+	index: usize,
+	out: *mut EvmAccountDiff,
+) -> bool {
+	match (*result).account(index) {
+		Some(account) => {
+			out.write(account);
+			true
+		}
+		None => false,
+	}
+}
+
+/// Write the key and the new value of the changed storage slot at `slot` of
+/// the account at `account` to `key` and `value`, 32 bytes each. Whether
+/// there is one.
+///
+/// # Safety
+///
+/// `result`, `key` and `value` must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn evm_result_storage(
+	result: *const EvmResult,
+	account: usize,
+	slot: usize,
+	key: *mut u8,
+	value: *mut u8,
+) -> bool {
+	match (*result).storage(account, slot) {
+		Some((slot_key, slot_value)) => {
+			ptr::copy_nonoverlapping(slot_key.as_ptr(), key, 32);
+			ptr::copy_nonoverlapping(slot_value.as_ptr(), value, 32);
+			true
+		}
+		None => false,
+	}
+}
+
+/// Free a result, and the memory its pointers point to.
+///
+/// # Safety
+///
+/// `result` must be null or returned by [evm_transact], and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn evm_result_free(result: *mut EvmResult) {
+	if !result.is_null() {
+		drop(Box::from_raw(result));
+	}
+}
@@ -0,0 +1,218 @@
+use std::ffi::CString;
+
+use evm::{
+	backend::{Delta, StateDiff},
+	interpreter::{error::ExitError, runtime::Log},
+	standard::TransactValue,
+};
+use primitive_types::{H160, U256};
+
+/// How an execution ended.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EvmStatus {
+	/// The execution succeeded.
+	Succeed = 0,
+	/// The execution reverted.
+	Revert = 1,
+	/// The execution failed with an EVM exception, such as out of gas.
+	Error = 2,
+	/// The execution aborted with a fatal error, or on the instruction
+	/// limit.
+	Fatal = 3,
+	/// The transaction is invalid, and was not executed.
+	Invalid = 4,
+}
+
+/// A log emitted by an execution. Its pointers are valid as long as the
+/// result.
+#[repr(C)]
+pub struct EvmLog {
+	/// Address of the account emitting the log.
+	pub address: [u8; 20],
+	/// Topics, each of 32 bytes.
+	pub topics: *const u8,
+	/// Number of topics.
+	pub topic_count: usize,
+	/// Data.
+	pub data: *const u8,
+	/// Length of the data.
+	pub data_len: usize,
+}
+
+/// Changes of an account made by an execution. Its pointers are valid as
+/// long as the result.
+#[repr(C)]
+pub struct EvmAccountDiff {
+	/// Address of the account.
+	pub address: [u8; 20],
+	/// Whether the account was removed. The other fields are then unset.
+	pub removed: bool,
+	/// Whether the balance changed.
+	pub balance_changed: bool,
+	/// Balance after the execution.
+	pub balance: [u8; 32],
+	/// Whether the nonce changed.
+	pub nonce_changed: bool,
+	/// Nonce after the execution.
+	pub nonce: [u8; 32],
+	/// Whether the code changed.
+	pub code_changed: bool,
+	/// Code after the execution.
+	pub code: *const u8,
+	/// Length of the code.
+	pub code_len: usize,
+	/// Number of changed storage slots, read with `evm_result_storage`.
+	pub storage_count: usize,
+}
+
+struct Account {
+	address: H160,
+	removed: bool,
+	balance: Option<U256>,
+	nonce: Option<U256>,
+	code: Option<Vec<u8>>,
+	storage: Vec<([u8; 32], [u8; 32])>,
+}
+
+/// Outcome of an execution.
+pub struct EvmResult {
+	pub(crate) status: EvmStatus,
+	pub(crate) output: Vec<u8>,
+	pub(crate) created: Option<H160>,
+	pub(crate) gas_used: u64,
+	pub(crate) error: Option<CString>,
+	logs: Vec<(Log, Vec<u8>)>,
+	accounts: Vec<Account>,
+}
+
+/// The value after a change, if any.
+fn after<T>(delta: Delta<T>) -> Option<T> {
+	match delta {
+		Delta::Unchanged | Delta::Removed(_) => None,
+		Delta::Added(value) | Delta::Changed { to: value, .. } => Some(value),
+	}
+}
+
+fn word(value: U256) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	value.to_big_endian(&mut word);
+	word
+}
+
+impl EvmResult {
+	/// Result of a transaction that was not executed.
+	pub(crate) fn invalid(err: ExitError) -> Self {
+		Self {
+			status: EvmStatus::Invalid,
+			output: Vec::new(),
+			created: None,
+			gas_used: 0,
+			error: CString::new(format!("{:?}", err)).ok(),
+			logs: Vec::new(),
+			accounts: Vec::new(),
+		}
+	}
+
+	/// Result of an executed transaction.
+	pub(crate) fn executed(
+		result: Result<TransactValue, ExitError>,
+		gas_used: U256,
+		logs: Vec<Log>,
+		state_diff: StateDiff,
+	) -> Self {
+		let (status, output, created, error) = match result {
+			Ok(TransactValue::Call { retval, .. }) => (EvmStatus::Succeed, retval, None, None),
+			Ok(TransactValue::Create { address, .. }) => {
+				(EvmStatus::Succeed, Vec::new(), Some(address), None)
+			}
+			Err(err) => {
+				let status = match err {
+					ExitError::Reverted => EvmStatus::Revert,
+					ExitError::Exception(_) => EvmStatus::Error,
+					ExitError::Fatal(_) | ExitError::InstructionLimit => EvmStatus::Fatal,
+				};
+				(
+					status,
+					Vec::new(),
+					None,
+					CString::new(format!("{:?}", err)).ok(),
+				)
+			}
+		};
+
+		let logs = logs
+			.into_iter()
+			.map(|log| {
+				let topics = log.topics.iter().flat_map(|topic| topic.0).collect();
+				(log, topics)
+			})
+			.collect();
+		let accounts = state_diff
+			.accounts
+			.into_iter()
+			.map(|(address, diff)| Account {
+				address,
+				removed: matches!(diff.balance, Delta::Removed(_)),
+				balance: after(diff.balance),
+				nonce: after(diff.nonce),
+				code: after(diff.code),
+				storage: diff
+					.storage
+					.into_iter()
+					.filter_map(|(key, delta)| after(delta).map(|value| (key.0, value.0)))
+					.collect(),
+			})
+			.collect();
+
+		Self {
+			status,
+			output,
+			created,
+			gas_used: gas_used.low_u64(),
+			error,
+			logs,
+			accounts,
+		}
+	}
+
+	pub(crate) fn log_count(&self) -> usize {
+		self.logs.len()
+	}
+
+	pub(crate) fn log(&self, index: usize) -> Option<EvmLog> {
+		self.logs.get(index).map(|(log, topics)| EvmLog {
+			address: log.address.0,
+			topics: topics.as_ptr(),
+			topic_count: log.topics.len(),
+			data: log.data.as_ptr(),
+			data_len: log.data.len(),
+		})
+	}
+
+	pub(crate) fn account_count(&self) -> usize {
+		self.accounts.len()
+	}
+
+	pub(crate) fn account(&self, index: usize) -> Option<EvmAccountDiff> {
+		self.accounts.get(index).map(|account| {
+			let code = account.code.as_deref().unwrap_or_default();
+			EvmAccountDiff {
+				address: account.address.0,
+				removed: account.removed,
+				balance_changed: account.balance.is_some(),
+				balance: word(account.balance.unwrap_or_default()),
+				nonce_changed: account.nonce.is_some(),
+				nonce: word(account.nonce.unwrap_or_default()),
+				code_changed: account.code.is_some(),
+				code: code.as_ptr(),
+				code_len: code.len(),
+				storage_count: account.storage.len(),
+			}
+		})
+	}
+
+	pub(crate) fn storage(&self, account: usize, slot: usize) -> Option<&([u8; 32], [u8; 32])> {
+		self.accounts.get(account)?.storage.get(slot)
+	}
+}
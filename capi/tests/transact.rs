@@ -0,0 +1,222 @@
+use core::ffi::c_void;
+use std::{collections::BTreeMap, ffi::CStr, ptr, slice};
+
+use evm_capi::*;
+use primitive_types::{H160, H256, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const CONTRACT: H160 = H160::repeat_byte(0x22);
+const CANCUN: &[u8] = b"cancun\0";
+
+#[derive(Default)]
+struct State {
+	balances: BTreeMap<H160, U256>,
+	nonces: BTreeMap<H160, U256>,
+	codes: BTreeMap<H160, Vec<u8>>,
+}
+
+unsafe fn state<'a>(context: *mut c_void) -> &'a State {
+	&*(context as *const State)
+}
+
+unsafe fn address(address: *const u8) -> H160 {
+	H160::from_slice(slice::from_raw_parts(address, 20))
+}
+
+unsafe fn write_word(value: U256, out: *mut u8) {
+	value.to_big_endian(slice::from_raw_parts_mut(out, 32));
+}
+
+unsafe extern "C" fn balance(context: *mut c_void, account: *const u8, out: *mut u8) {
+	let value = state(context).balances.get(&address(account)).copied();
+	write_word(value.unwrap_or_default(), out);
+}
+
+unsafe extern "C" fn nonce(context: *mut c_void, account: *const u8, out: *mut u8) {
+	let value = state(context).nonces.get(&address(account)).copied();
+	write_word(value.unwrap_or_default(), out);
+}
+
+unsafe extern "C" fn code(
+	context: *mut c_void,
+	account: *const u8,
+	out: *mut u8,
+	len: usize,
+) -> usize {
+	let code = state(context)
+		.codes
+		.get(&address(account))
+		.cloned()
+		.unwrap_or_default();
+	if !out.is_null() {
+		ptr::copy_nonoverlapping(code.as_ptr(), out, code.len().min(len));
+	}
+	code.len()
+}
+
+unsafe extern "C" fn exists(context: *mut c_void, account: *const u8) -> bool {
+	let state = state(context);
+	let account = address(account);
+	state.balances.contains_key(&account) || state.codes.contains_key(&account)
+}
+
+fn host(state: &State) -> EvmHost {
+	EvmHost {
+		context: state as *const State as *mut c_void,
+		balance: Some(balance),
+		nonce: Some(nonce),
+		code: Some(code),
+		storage: None,
+		exists: Some(exists),
+		block_hash: None,
+	}
+}
+
+fn environment() -> EvmEnvironment {
+	let mut block_gas_limit = [0u8; 32];
+	U256::from(30_000_000).to_big_endian(&mut block_gas_limit);
+	EvmEnvironment {
+		block_number: [0; 32],
+		block_coinbase: [0; 20],
+		block_timestamp: [0; 32],
+		block_difficulty: [0; 32],
+		has_block_randomness: true,
+		block_randomness: [0; 32],
+		block_gas_limit,
+		block_base_fee_per_gas: [0; 32],
+		block_excess_blob_gas: [0; 32],
+		chain_id: [0; 32],
+	}
+}
+
+fn transaction(to: Option<&H160>, data: &[u8]) -> EvmTransaction {
+	EvmTransaction {
+		caller: CALLER.0,
+		to: to.map_or(ptr::null(), |to| to.as_ptr()),
+		value: [0; 32],
+		data: data.as_ptr(),
+		data_len: data.len(),
+		has_nonce: false,
+		nonce: 0,
+		gas_limit: 100_000,
+		gas_price: [0; 32],
+	}
+}
+
+fn state_with_contract() -> State {
+	let mut state = State::default();
+	state.balances.insert(CALLER, U256::from(1_000_000_000));
+	// Store 42 at slot 1, log it and return it.
+	state.codes.insert(
+		CONTRACT,
+		hex::decode("602a600155602a60005260206000a060206000f3").unwrap(),
+	);
+	state
+}
+
+#[test]
+fn call_returns_output_logs_and_state_diff() {
+	let state = state_with_contract();
+	let host = host(&state);
+	let environment = environment();
+
+	unsafe {
+		let backend = evm_backend_new(&host, &environment);
+		assert!(!backend.is_null());
+		let transaction = transaction(Some(&CONTRACT), &[]);
+		let result = evm_transact(backend, CANCUN.as_ptr().cast(), &transaction);
+		assert!(!result.is_null());
+
+		assert_eq!(evm_result_status(result), EvmStatus::Succeed);
+		assert!(evm_result_error(result).is_null());
+		let mut len = 0;
+		let output = evm_result_output(result, &mut len);
+		assert_eq!(
+			U256::from_big_endian(slice::from_raw_parts(output, len)),
+			U256::from(42)
+		);
+		assert!(evm_result_gas_used(result) > 21_000);
+
+		assert_eq!(evm_result_log_count(result), 1);
+		let mut log = std::mem::zeroed::<EvmLog>();
+		assert!(evm_result_log(result, 0, &mut log));
+		assert_eq!(H160(log.address), CONTRACT);
+		assert_eq!(log.topic_count, 0);
+		assert_eq!(slice::from_raw_parts(log.data, log.data_len).len(), 32);
+		assert!(!evm_result_log(result, 1, &mut log));
+
+		let mut contract = None;
+		for index in 0..evm_result_account_count(result) {
+			let mut account = std::mem::zeroed::<EvmAccountDiff>();
+			assert!(evm_result_account(result, index, &mut account));
+			if H160(account.address) == CONTRACT {
+				contract = Some((index, account));
+			}
+		}
+		let (index, contract) = contract.expect("contract storage changed");
+		assert!(!contract.removed);
+		assert_eq!(contract.storage_count, 1);
+		let (mut key, mut value) = ([0u8; 32], [0u8; 32]);
+		assert!(evm_result_storage(
+			result,
+			index,
+			0,
+			key.as_mut_ptr(),
+			value.as_mut_ptr()
+		));
+		assert_eq!(H256(key), H256::from_low_u64_be(1));
+		assert_eq!(H256(value), H256::from_low_u64_be(42));
+
+		evm_result_free(result);
+		evm_backend_free(backend);
+	}
+}
+
+#[test]
+fn create_returns_address() {
+	let state = state_with_contract();
+	let host = host(&state);
+	let environment = environment();
+
+	unsafe {
+		let backend = evm_backend_new(&host, &environment);
+		// Deploy an empty contract.
+		let init_code = hex::decode("60006000f3").unwrap();
+		let transaction = transaction(None, &init_code);
+		let result = evm_transact(backend, CANCUN.as_ptr().cast(), &transaction);
+
+		assert_eq!(evm_result_status(result), EvmStatus::Succeed);
+		let mut created = [0u8; 20];
+		assert!(evm_result_created(result, created.as_mut_ptr()));
+		assert_ne!(H160(created), H160::zero());
+
+		evm_result_free(result);
+		evm_backend_free(backend);
+	}
+}
+
+#[test]
+fn invalid_transaction_and_unknown_fork() {
+	let state = state_with_contract();
+	let host = host(&state);
+	let environment = environment();
+
+	unsafe {
+		let backend = evm_backend_new(&host, &environment);
+		let mut transaction = transaction(Some(&CONTRACT), &[]);
+		transaction.has_nonce = true;
+		transaction.nonce = 5;
+		let result = evm_transact(backend, CANCUN.as_ptr().cast(), &transaction);
+
+		assert_eq!(evm_result_status(result), EvmStatus::Invalid);
+		assert_eq!(evm_result_gas_used(result), 0);
+		assert_eq!(evm_result_account_count(result), 0);
+		assert!(!CStr::from_ptr(evm_result_error(result))
+			.to_bytes()
+			.is_empty());
+		evm_result_free(result);
+
+		assert!(evm_transact(backend, b"byzantium\0".as_ptr().cast(), &transaction).is_null());
+		evm_backend_free(backend);
+	}
+}
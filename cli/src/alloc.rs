@@ -0,0 +1,88 @@
+//! Accounts in the format of the `alloc` of a geth genesis file: an object
+//! of accounts by address, with `balance`, `nonce`, `code` and `storage`
+//! fields, all optional.
+
+use std::{collections::BTreeMap, fs};
+
+use evm::backend::InMemoryAccount;
+use primitive_types::{H160, H256};
+use serde_json::{json, Map, Value};
+
+use crate::{error::Error, parse};
+
+/// Load the accounts of an alloc file.
+pub fn load(path: &str) -> Result<BTreeMap<H160, InMemoryAccount>, Error> {
+	let alloc: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+	let alloc = alloc
+		.as_object()
+		.ok_or_else(|| Error::Alloc("not an object".into()))?;
+
+	let mut state = BTreeMap::new();
+	for (address, fields) in alloc {
+		let address = parse::address(address).map_err(Error::Alloc)?;
+		let mut account = InMemoryAccount::default();
+		if let Some(balance) = fields.get("balance") {
+			account.balance = parse::u256(string(balance)?).map_err(Error::Alloc)?;
+		}
+		if let Some(nonce) = fields.get("nonce") {
+			account.nonce = parse::u256(string(nonce)?).map_err(Error::Alloc)?;
+		}
+		if let Some(code) = fields.get("code") {
+			account.code = parse::bytes(string(code)?).map_err(Error::Alloc)?.0;
+		}
+		if let Some(storage) = fields.get("storage") {
+			let storage = storage.as_object().ok_or_else(|| {
+				Error::Alloc(format!("storage of {:?} is not an object", address))
+			})?;
+			for (key, value) in storage {
+				let key = parse::word(key).map_err(Error::Alloc)?;
+				let value = parse::word(string(value)?).map_err(Error::Alloc)?;
+				if value != H256::zero() {
+					account.storage.insert(key, value);
+				}
+			}
+		}
+		state.insert(address, account);
+	}
+
+	Ok(state)
+}
+
+/// Accounts as an alloc, with the quantities in hex and the empty code and
+/// storage left out, as geth writes them.
+pub fn to_json(state: &BTreeMap<H160, InMemoryAccount>) -> Value {
+	let mut alloc = Map::new();
+	for (address, account) in state {
+		let mut fields = Map::new();
+		fields.insert("balance".into(), json!(format!("{:#x}", account.balance)));
+		fields.insert("nonce".into(), json!(format!("{:#x}", account.nonce)));
+		if !account.code.is_empty() {
+			fields.insert(
+				"code".into(),
+				json!(format!("0x{}", hex::encode(&account.code))),
+			);
+		}
+		if !account.storage.is_empty() {
+			let storage = account
+				.storage
+				.iter()
+				.map(|(key, value)| (format!("{:?}", key), json!(format!("{:?}", value))))
+				.collect::<Map<_, _>>();
+			fields.insert("storage".into(), Value::Object(storage));
+		}
+		alloc.insert(format!("{:?}", address), Value::Object(fields));
+	}
+	Value::Object(alloc)
+}
+
+/// Write the accounts to an alloc file.
+pub fn dump(path: &str, state: &BTreeMap<H160, InMemoryAccount>) -> Result<(), Error> {
+	fs::write(path, serde_json::to_string_pretty(&to_json(state))?)?;
+	Ok(())
+}
+
+fn string(value: &Value) -> Result<&str, Error> {
+	value
+		.as_str()
+		.ok_or_else(|| Error::Alloc(format!("{} is not a string", value)))
+}
@@ -0,0 +1,357 @@
+//! `bench`: execute bytecode, or a built-in workload, repeatedly and report
+//! its throughput, its allocations and the opcodes it spends its time in.
+
+use std::{
+	alloc::{GlobalAlloc, Layout, System},
+	sync::atomic::{AtomicU64, Ordering},
+	time::{Duration, Instant},
+};
+
+use clap::Args;
+use evm::{
+	backend::InMemoryBackend,
+	interpreter::{asm::Assembler, error::ExitError, opcode::Opcode},
+	standard::{
+		transact_with_gas, Config, Etable, EtableResolver, Invoker, TransactArgs, TransactValue,
+	},
+};
+use evm_precompile::StandardPrecompileSet;
+use evm_tracer::{GasProfiler, TracedBackend, TracingEtable, TracingInvoker};
+use primitive_types::U256;
+
+use crate::{
+	error::Error,
+	run::{self, RunArgs},
+};
+
+/// The system allocator, counting the allocations of the whole binary.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+		ALLOCATED.fetch_add(layout.size() as u64, Ordering::Relaxed);
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		System.dealloc(ptr, layout)
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+		ALLOCATED.fetch_add(new_size as u64, Ordering::Relaxed);
+		System.realloc(ptr, layout, new_size)
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[derive(Args)]
+pub struct BenchArgs {
+	#[command(flatten)]
+	run: RunArgs,
+
+	/// Built-in workload to run instead of `--code`.
+	#[arg(long, value_parser = WORKLOADS.map(|(name, _)| name), conflicts_with = "code")]
+	workload: Option<String>,
+
+	/// Number of measured runs, after a warm-up run.
+	#[arg(long, default_value = "10")]
+	runs: u32,
+
+	/// Number of opcodes listed as hot spots.
+	#[arg(long, default_value = "10")]
+	top: usize,
+}
+
+/// Name of a built-in workload, and its code.
+type Workload = (&'static str, fn() -> Assembler);
+
+/// Built-in workloads, the same as those of `cargo bench --bench workloads`.
+const WORKLOADS: [Workload; 5] = [
+	("erc20_transfer_loop", erc20_transfer_loop),
+	("keccak_loop", keccak_loop),
+	("memory_copy_loop", memory_copy_loop),
+	("memory_stress_loop", memory_stress_loop),
+	("arithmetic_loop", arithmetic_loop),
+];
+
+pub fn bench(args: BenchArgs) -> Result<(), Error> {
+	let run_args = match &args.workload {
+		Some(name) => {
+			let (_, workload) = WORKLOADS
+				.iter()
+				.find(|(workload, _)| workload == name)
+				.expect("checked by clap");
+			let code = workload()
+				.assemble()
+				.map_err(|_| Error::Bench("workload does not assemble"))?;
+			args.run
+				.with_code_and_input(code, args.run.input().to_vec())
+		}
+		None if args.run.code().is_some() => args.run,
+		None => return Err(Error::Bench("--code or --workload is required")),
+	};
+	let (config, backend, transact_args) = run::prepare(run_args)?;
+
+	let (result, gas, _) = execute(&config, &backend, transact_args.clone())?;
+	run::print_result(result);
+
+	let mut total = Duration::ZERO;
+	let mut fastest = Duration::MAX;
+	let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+	let allocated = ALLOCATED.load(Ordering::Relaxed);
+	for _ in 0..args.runs {
+		let (_, _, elapsed) = execute(&config, &backend, transact_args.clone())?;
+		total += elapsed;
+		fastest = fastest.min(elapsed);
+	}
+	let runs = args.runs.max(1);
+	let allocations = (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / u64::from(runs);
+	let allocated = (ALLOCATED.load(Ordering::Relaxed) - allocated) / u64::from(runs);
+
+	let per_run = total / runs;
+	println!("gas executed: {}", gas);
+	println!(
+		"time per run: {:.3?} (fastest {:.3?}, {} runs)",
+		per_run, fastest, args.runs
+	);
+	println!(
+		"throughput: {:.1} Mgas/s",
+		gas as f64 / per_run.as_secs_f64() / 1e6
+	);
+	println!("allocations per run: {} ({} bytes)", allocations, allocated);
+
+	// Profiling slows the execution down, so it gets a run of its own.
+	let profiler = profile(&config, &backend, transact_args);
+	let mut opcodes = profiler.opcodes().collect::<Vec<_>>();
+	opcodes.sort_by(|(_, a), (_, b)| b.time.cmp(&a.time));
+	let profiled = opcodes
+		.iter()
+		.map(|(_, profile)| profile.time)
+		.sum::<Duration>();
+	println!("hot spots:");
+	println!(
+		"  {:<16}{:>12}{:>14}{:>14}{:>8}",
+		"opcode", "count", "gas", "time", "time%"
+	);
+	for (opcode, profile) in opcodes.iter().take(args.top) {
+		println!(
+			"  {:<16}{:>12}{:>14}{:>14.3?}{:>7.1}%",
+			opcode.to_string(),
+			profile.count,
+			profile.gas,
+			profile.time,
+			profile.time.as_secs_f64() / profiled.as_secs_f64().max(f64::MIN_POSITIVE) * 100.0
+		);
+	}
+
+	Ok(())
+}
+
+/// Result of an execution, the gas executed and the time it took.
+type Execution = (Result<TransactValue, ExitError>, u64, Duration);
+
+/// Execute the transaction on a fresh overlay.
+fn execute(
+	config: &Config,
+	backend: &InMemoryBackend,
+	args: TransactArgs,
+) -> Result<Execution, Error> {
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let precompiles = StandardPrecompileSet::new(config);
+	let resolver = EtableResolver::new(config, &precompiles, &etable);
+	let invoker = Invoker::new(config, &resolver);
+
+	let mut overlay = backend.overlay();
+	let start = Instant::now();
+	let (result, gas) =
+		transact_with_gas(args, None, &mut overlay, &invoker).map_err(Error::InvalidTransaction)?;
+	let elapsed = start.elapsed();
+
+	Ok((result, gas.used.low_u64(), elapsed))
+}
+
+/// Execute the transaction with a gas profiler.
+fn profile(config: &Config, backend: &InMemoryBackend, args: TransactArgs) -> GasProfiler {
+	let etable = TracingEtable::new((
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	));
+	let precompiles = StandardPrecompileSet::new(config);
+	let resolver = EtableResolver::new(config, &precompiles, &etable);
+	let invoker = TracingInvoker::new(Invoker::new(config, &resolver));
+
+	let mut traced = TracedBackend::new(backend.overlay(), GasProfiler::new());
+	let _ = evm::transact(args, None, &mut traced, &invoker);
+	traced.into_parts().1
+}
+
+/// Token transfers from the caller to a new address per iteration, as in
+/// ERC-20: two balance updates in a mapping at slot 0 and a `Transfer` log.
+fn erc20_transfer_loop() -> Assembler {
+	let transfer_topic = U256::from_str_radix(
+		"ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+		16,
+	)
+	.expect("valid topic");
+
+	Assembler::new()
+		.push(1000)
+		.label("loop")
+		// balances[caller] -= 1
+		.op(Opcode::CALLER)
+		.push(0)
+		.op(Opcode::MSTORE)
+		.push(0)
+		.push(32)
+		.op(Opcode::MSTORE)
+		.push(64)
+		.push(0)
+		.op(Opcode::SHA3)
+		.op(Opcode::DUP1)
+		.op(Opcode::SLOAD)
+		.push(1)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SUB)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SSTORE)
+		// balances[counter] += 1
+		.op(Opcode::DUP1)
+		.push(0)
+		.op(Opcode::MSTORE)
+		.push(64)
+		.push(0)
+		.op(Opcode::SHA3)
+		.op(Opcode::DUP1)
+		.op(Opcode::SLOAD)
+		.push(1)
+		.op(Opcode::ADD)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SSTORE)
+		// Transfer(caller, counter, amount)
+		.push(1)
+		.push(0)
+		.op(Opcode::MSTORE)
+		.op(Opcode::DUP1)
+		.op(Opcode::CALLER)
+		.push(transfer_topic)
+		.push(32)
+		.push(0)
+		.op(Opcode::LOG3)
+		// counter -= 1
+		.push(1)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SUB)
+		.op(Opcode::DUP1)
+		.jumpi("loop")
+		.op(Opcode::STOP)
+}
+
+/// Hash of the previous hash, in memory.
+fn keccak_loop() -> Assembler {
+	Assembler::new()
+		.push(200_000)
+		.label("loop")
+		.push(32)
+		.push(0)
+		.op(Opcode::SHA3)
+		.push(0)
+		.op(Opcode::MSTORE)
+		.push(1)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SUB)
+		.op(Opcode::DUP1)
+		.jumpi("loop")
+		.op(Opcode::STOP)
+}
+
+/// Copy of 32 KiB within memory, expanded to 64 KiB by the first copy.
+fn memory_copy_loop() -> Assembler {
+	Assembler::new()
+		.push(5000)
+		.label("loop")
+		.push(32 * 1024)
+		.push(0)
+		.push(32 * 1024)
+		.op(Opcode::MCOPY)
+		.push(1)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SUB)
+		.op(Opcode::DUP1)
+		.jumpi("loop")
+		.op(Opcode::STOP)
+}
+
+/// Calls expanding their memory to 256 KiB with a single byte write, as in
+/// `stMemoryStressTest`, so that the expansions dominate.
+fn memory_stress_loop() -> Assembler {
+	Assembler::new()
+		.op(Opcode::CALLDATASIZE)
+		.jumpi("expand")
+		.push(150)
+		.label("loop")
+		// Call itself with one byte of call data.
+		.push(0)
+		.push(0)
+		.push(1)
+		.push(0)
+		.push(0)
+		.op(Opcode::ADDRESS)
+		.op(Opcode::GAS)
+		.op(Opcode::CALL)
+		.op(Opcode::POP)
+		.push(1)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SUB)
+		.op(Opcode::DUP1)
+		.jumpi("loop")
+		.op(Opcode::STOP)
+		.label("expand")
+		.push(1)
+		.push(256 * 1024 - 1)
+		.op(Opcode::MSTORE8)
+		.op(Opcode::STOP)
+}
+
+/// Modular arithmetic on 256-bit words: a square modulo a prime, an
+/// addition and a cube.
+fn arithmetic_loop() -> Assembler {
+	let prime = U256::from(2).pow(U256::from(255)) - 19;
+
+	Assembler::new()
+		.push(7)
+		.push(200_000)
+		.label("loop")
+		.op(Opcode::SWAP1)
+		// x = x * x % prime + counter
+		.push(prime)
+		.op(Opcode::DUP2)
+		.op(Opcode::DUP3)
+		.op(Opcode::MULMOD)
+		.op(Opcode::SWAP1)
+		.op(Opcode::POP)
+		.op(Opcode::DUP2)
+		.op(Opcode::ADD)
+		// x ^= x ** 3
+		.push(3)
+		.op(Opcode::DUP2)
+		.op(Opcode::EXP)
+		.op(Opcode::XOR)
+		.op(Opcode::SWAP1)
+		// counter -= 1
+		.push(1)
+		.op(Opcode::SWAP1)
+		.op(Opcode::SUB)
+		.op(Opcode::DUP1)
+		.jumpi("loop")
+		.op(Opcode::STOP)
+}
@@ -0,0 +1,9 @@
+use evm::standard::Config;
+
+use crate::error::Error;
+
+/// Config of a fork, by case-insensitive name. `paris` is accepted for the
+/// Merge.
+pub fn fork_config(name: &str) -> Result<Config, Error> {
+	Config::from_fork_name(name).ok_or_else(|| Error::UnknownFork(name.to_string()))
+}
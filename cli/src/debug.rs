@@ -0,0 +1,208 @@
+//! `debug`: step through a transaction interactively, with the commands read
+//! from the standard input.
+
+use std::io::{self, BufRead, Write};
+
+use evm::{
+	interpreter::{error::Capture, opcode::Opcode, runtime::GasState},
+	standard::{Etable, EtableResolver, Invoker},
+	Breakpoint, Debugger,
+};
+use evm_precompile::StandardPrecompileSet;
+use primitive_types::U256;
+
+use crate::{
+	error::Error,
+	parse,
+	run::{self, RunArgs},
+};
+
+const HELP: &str = "\
+commands:
+  s, step              execute the next opcode, stepping into calls
+  n, next              execute the next opcode, stepping over calls
+  c, continue          run until a breakpoint is hit
+  b, break <target>    add a breakpoint on `pc <position>`, `op <opcode>`
+                       or `addr <address>`
+  d, delete <target>   remove a breakpoint
+  breakpoints          list the breakpoints
+  stack                print the stack, from the top
+  mem <offset> <len>   print a range of the memory
+  sload <key>          print a storage value of the current account
+  where                print where the transaction is paused
+  h, help              print this help
+  q, quit              abort the transaction
+An empty line repeats the last command.";
+
+pub fn debug(args: RunArgs) -> Result<(), Error> {
+	let (config, backend, transact_args) = run::prepare(args)?;
+
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let precompiles = StandardPrecompileSet::new(&config);
+	let resolver = EtableResolver::new(&config, &precompiles, &etable);
+	let invoker = Invoker::new(&config, &resolver);
+
+	let mut overlay = backend.overlay();
+	let mut debugger =
+		Debugger::new(transact_args, &invoker, &mut overlay).map_err(Error::InvalidTransaction)?;
+
+	println!("type `help` for the commands");
+	let mut outcome = debugger.step().map(|()| None);
+	let mut lines = io::stdin().lock().lines();
+	let mut last = String::new();
+	let result = 'repl: loop {
+		match outcome {
+			Ok(breakpoint) => {
+				if let Some(breakpoint) = breakpoint {
+					println!("breakpoint {}", describe(&breakpoint));
+				}
+			}
+			Err(Capture::Exit(result)) => break Some(result),
+			Err(Capture::Trap(_)) => {
+				println!("interrupted");
+				break None;
+			}
+		}
+		print_where(&debugger);
+
+		outcome = loop {
+			print!("(evm) ");
+			io::stdout().flush()?;
+			let line = match lines.next() {
+				Some(line) => line?,
+				None => "quit".to_string(),
+			};
+			if !line.trim().is_empty() {
+				last = line;
+			}
+			let words = last.split_whitespace().collect::<Vec<_>>();
+
+			match words.as_slice() {
+				["s"] | ["step"] => break debugger.step().map(|()| None),
+				["n"] | ["next"] => break debugger.step_over(),
+				["c"] | ["continue"] => break debugger.resume().map(Some),
+				["b", target @ ..] | ["break", target @ ..] => match breakpoint(target) {
+					Ok(breakpoint) => debugger.add_breakpoint(breakpoint),
+					Err(err) => println!("{}", err),
+				},
+				["d", target @ ..] | ["delete", target @ ..] => match breakpoint(target) {
+					Ok(breakpoint) => {
+						if !debugger.remove_breakpoint(&breakpoint) {
+							println!("no such breakpoint");
+						}
+					}
+					Err(err) => println!("{}", err),
+				},
+				["breakpoints"] => {
+					for breakpoint in debugger.breakpoints() {
+						println!("  {}", describe(breakpoint));
+					}
+				}
+				["stack"] => {
+					if let Some(machine) = debugger.machine() {
+						for (index, value) in machine.stack.data().iter().rev().enumerate() {
+							println!("  {}: {:#x}", index, U256::from_big_endian(&value[..]));
+						}
+					}
+				}
+				["mem", offset, len] => match (parse::u64(offset), parse::u64(len)) {
+					(Ok(offset), Ok(len)) => {
+						if let Some(machine) = debugger.machine() {
+							let memory = machine.memory.get(offset as usize, len as usize);
+							for (index, chunk) in memory.chunks(32).enumerate() {
+								println!(
+									"  {:#06x}: {}",
+									offset as usize + index * 32,
+									hex::encode(chunk)
+								);
+							}
+						}
+					}
+					(Err(err), _) | (_, Err(err)) => println!("{}", err),
+				},
+				["sload", key] => match parse::word(key) {
+					Ok(key) => {
+						if let Some(value) = debugger.storage(key) {
+							println!("  {:?}", value);
+						}
+					}
+					Err(err) => println!("{}", err),
+				},
+				["where"] => print_where(&debugger),
+				["h"] | ["help"] => println!("{}", HELP),
+				["q"] | ["quit"] => break 'repl None,
+				_ => println!("unknown command, type `help` for the commands"),
+			}
+		};
+	};
+	drop(debugger);
+
+	match result {
+		Some(result) => {
+			run::print_result(result);
+			let state_diff = overlay.state_diff();
+			run::print_logs(&overlay.deconstruct().1.logs);
+			run::print_state_diff(&state_diff);
+		}
+		None => println!("aborted"),
+	}
+
+	Ok(())
+}
+
+fn print_where<H, Tr, I>(debugger: &Debugger<'_, '_, H, Tr, I>)
+where
+	I: evm::Invoker<H, Tr>,
+	I::Interpreter: evm::interpreter::StepInterpreter<H, Tr>,
+	I::State: AsRef<evm::interpreter::runtime::RuntimeState> + GasState,
+{
+	if let (Some(depth), Some(address), Some(position), Some(machine)) = (
+		debugger.depth(),
+		debugger.address(),
+		debugger.position(),
+		debugger.machine(),
+	) {
+		let opcode = match debugger.opcode() {
+			Some(opcode) => opcode.to_string(),
+			None => "end of code".to_string(),
+		};
+		println!(
+			"depth {} {:?} pc {:#x}: {} (gas left {})",
+			depth,
+			address,
+			position,
+			opcode,
+			machine.state.gas()
+		);
+	}
+}
+
+fn breakpoint(target: &[&str]) -> Result<Breakpoint, String> {
+	match target {
+		["pc", position] => Ok(Breakpoint::Pc(parse::u64(position)? as usize)),
+		["op", opcode] => (0..=u8::MAX)
+			.map(Opcode)
+			.find(|op| op.to_string().eq_ignore_ascii_case(opcode))
+			.or_else(|| {
+				parse::u64(opcode)
+					.ok()
+					.filter(|value| *value <= u64::from(u8::MAX))
+					.map(|value| Opcode(value as u8))
+			})
+			.map(Breakpoint::Opcode)
+			.ok_or_else(|| format!("unknown opcode {}", opcode)),
+		["addr", address] => Ok(Breakpoint::Address(parse::address(address)?)),
+		_ => Err("expected `pc <position>`, `op <opcode>` or `addr <address>`".into()),
+	}
+}
+
+fn describe(breakpoint: &Breakpoint) -> String {
+	match breakpoint {
+		Breakpoint::Pc(position) => format!("pc {:#x}", position),
+		Breakpoint::Opcode(opcode) => format!("op {}", opcode),
+		Breakpoint::Address(address) => format!("addr {:?}", address),
+	}
+}
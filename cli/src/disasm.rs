@@ -0,0 +1,128 @@
+//! `disasm`: disassemble bytecode, given in hex or fetched from a node.
+
+use std::collections::BTreeSet;
+
+use clap::Args;
+use evm::interpreter::{
+	disasm::{disassemble, Instruction},
+	opcode::Opcode,
+};
+use primitive_types::H160;
+
+use crate::{
+	error::Error,
+	parse::{self, Bytes},
+	rpc,
+};
+
+#[derive(Args)]
+pub struct DisasmArgs {
+	/// Code to disassemble, in hex.
+	#[arg(value_parser = parse::bytes, required_unless_present = "rpc", conflicts_with = "rpc")]
+	code: Option<Bytes>,
+
+	/// JSON-RPC endpoint to fetch the code of `--address` from.
+	#[arg(long, requires = "address")]
+	rpc: Option<String>,
+
+	/// Account whose code is fetched.
+	#[arg(long, value_parser = parse::address, requires = "rpc")]
+	address: Option<H160>,
+
+	/// Block at which the code is fetched, a number or a tag.
+	#[arg(long, value_parser = rpc::block, default_value = "latest")]
+	block: String,
+}
+
+pub fn disasm(args: DisasmArgs) -> Result<(), Error> {
+	let code = match (args.code, args.rpc, args.address) {
+		(Some(code), _, _) => code.0,
+		(None, Some(url), Some(address)) => {
+			rpc::code(&rpc::Http::new(&url)?, address, &args.block)?
+		}
+		_ => unreachable!("checked by clap"),
+	};
+
+	print!("{}", listing(&code));
+	Ok(())
+}
+
+/// Listing of code: an instruction per line with its offset, basic blocks
+/// separated by blank lines, and jump destinations annotated.
+pub fn listing(code: &[u8]) -> String {
+	let instructions = disassemble(code);
+	let eof = instructions.is_eof();
+	let sections = instructions.sections().to_vec();
+	let instructions = instructions.collect::<Vec<_>>();
+	let jumpdests = instructions
+		.iter()
+		.filter(|instruction| !eof && instruction.opcode == Opcode::JUMPDEST)
+		.map(|instruction| instruction.offset)
+		.collect::<BTreeSet<_>>();
+
+	let mut listing = String::new();
+	let mut block_start = true;
+	for (index, instruction) in instructions.iter().enumerate() {
+		if let Some(section) = sections
+			.iter()
+			.position(|section| section.start == instruction.offset)
+			.filter(|_| eof)
+		{
+			if !block_start {
+				listing.push('\n');
+			}
+			listing.push_str(&format!("; code section {}\n", section));
+		} else if instruction.opcode == Opcode::JUMPDEST && !block_start {
+			listing.push('\n');
+		}
+
+		let mut line = format!("{:06x}: {}", instruction.offset, instruction);
+		if jumpdests.contains(&instruction.offset) {
+			line.push_str("  ; jump destination");
+		} else if let Some(target) = static_jump(&instructions, index) {
+			if jumpdests.contains(&target) {
+				line.push_str(&format!("  ; -> {:06x}", target));
+			} else {
+				line.push_str("  ; -> invalid jump destination");
+			}
+		}
+		listing.push_str(&line);
+		listing.push('\n');
+
+		block_start = ends_block(instruction.opcode, eof);
+		if block_start && index + 1 < instructions.len() {
+			listing.push('\n');
+		}
+	}
+
+	listing
+}
+
+/// Target of a push immediately followed by `JUMP` or `JUMPI`.
+fn static_jump(instructions: &[Instruction], index: usize) -> Option<usize> {
+	let push = instructions.get(index)?;
+	push.opcode.is_push()?;
+	let next = instructions.get(index + 1)?;
+	if next.opcode != Opcode::JUMP && next.opcode != Opcode::JUMPI {
+		return None;
+	}
+	if push.immediate.len() > 8 {
+		return Some(usize::MAX);
+	}
+	Some(
+		push.immediate
+			.iter()
+			.fold(0, |target, byte| (target << 8) | *byte as usize),
+	)
+}
+
+/// Whether the opcode ends a basic block.
+fn ends_block(opcode: Opcode, eof: bool) -> bool {
+	match opcode.0 {
+		// STOP, JUMP, JUMPI, RETURN, REVERT, INVALID, SELFDESTRUCT.
+		0x00 | 0x56 | 0x57 | 0xf3 | 0xfd | 0xfe | 0xff => true,
+		// RJUMP, RJUMPI, RJUMPV, RETF, JUMPF, RETURNCONTRACT.
+		0xe0 | 0xe1 | 0xe2 | 0xe4 | 0xe5 | 0xee => eof,
+		_ => false,
+	}
+}
@@ -0,0 +1,200 @@
+//! `eof`: inspect EOF containers.
+
+use clap::{Args, Subcommand};
+use evm::interpreter::{
+	disasm::disassemble,
+	eof::{validate, ContainerKind, EofHeader, EofType},
+};
+
+use crate::{
+	config::fork_config,
+	error::Error,
+	parse::{self, Bytes},
+};
+
+/// Forks whose rules include EOF, as in the EOF tests.
+const EOF_FORKS: [&str; 2] = ["prague", "osaka"];
+
+#[derive(Args)]
+pub struct EofArgs {
+	#[command(subcommand)]
+	command: EofCommand,
+}
+
+#[derive(Subcommand)]
+enum EofCommand {
+	/// Validate a container, and print its sections and where it fails.
+	Validate(ValidateArgs),
+}
+
+#[derive(Args)]
+struct ValidateArgs {
+	/// Container, in hex.
+	#[arg(value_parser = parse::bytes)]
+	code: Bytes,
+
+	/// Fork whose rules are applied.
+	#[arg(long, default_value = "osaka")]
+	fork: String,
+
+	/// Validate the container as init code rather than runtime code.
+	#[arg(long)]
+	initcode: bool,
+}
+
+pub fn eof(args: EofArgs) -> Result<(), Error> {
+	match args.command {
+		EofCommand::Validate(args) => validate_container(args),
+	}
+}
+
+fn validate_container(args: ValidateArgs) -> Result<(), Error> {
+	// Only the container rules of the EOF forks are applied, so their
+	// incomplete execution presets are not needed.
+	if !EOF_FORKS.contains(&args.fork.to_ascii_lowercase().as_str()) {
+		fork_config(&args.fork)?;
+		return Err(Error::Eof(format!("{} does not support EOF", args.fork)));
+	}
+	let code = &args.code.0;
+	let kind = if args.initcode {
+		ContainerKind::Initcode
+	} else {
+		ContainerKind::Runtime
+	};
+
+	// The header of an invalid container often parses, and shows where its
+	// failure is.
+	let result = validate(code, kind);
+	if let Ok(header) = result.clone().or_else(|_| EofHeader::parse(code)) {
+		print_layout(code, &header, 0, "");
+	}
+
+	match result {
+		Ok(_) => {
+			println!("valid {:?} container", kind);
+			Ok(())
+		}
+		Err(err) => Err(Error::Eof(format!(
+			"invalid container: {}, {}",
+			err,
+			locate(code, err.offset)
+		))),
+	}
+}
+
+/// Print the sections of a container at `base` in the outermost one.
+fn print_layout(code: &[u8], header: &EofHeader, base: usize, indent: &str) {
+	let range = |start: usize, end: usize| format!("{:#06x}..{:#06x}", base + start, base + end);
+	let types_start = header.code_sections[0].start - header.types.len() * 4;
+
+	println!("{}header: {}", indent, range(0, types_start));
+	println!(
+		"{}types: {}",
+		indent,
+		range(types_start, header.code_sections[0].start)
+	);
+	for (index, (section, ty)) in header.code_sections.iter().zip(&header.types).enumerate() {
+		println!(
+			"{}code section {}: {} ({} bytes), {}",
+			indent,
+			index,
+			range(section.start, section.end),
+			section.len(),
+			describe_type(ty)
+		);
+	}
+	for (index, section) in header.container_sections.iter().enumerate() {
+		println!(
+			"{}container section {}: {} ({} bytes)",
+			indent,
+			index,
+			range(section.start, section.end),
+			section.len()
+		);
+		if let Some(Ok(subheader)) = code.get(section.clone()).map(EofHeader::parse) {
+			print_layout(
+				&code[section.clone()],
+				&subheader,
+				base + section.start,
+				&format!("{}  ", indent),
+			);
+		}
+	}
+	let present = header.data.end.min(code.len()) - header.data.start;
+	if present < header.data.len() {
+		println!(
+			"{}data: {} ({} bytes declared, {} present)",
+			indent,
+			range(header.data.start, header.data.end),
+			header.data.len(),
+			present
+		);
+	} else {
+		println!(
+			"{}data: {} ({} bytes)",
+			indent,
+			range(header.data.start, header.data.end),
+			header.data.len()
+		);
+	}
+}
+
+fn describe_type(ty: &EofType) -> String {
+	let outputs = if ty.is_returning() {
+		ty.outputs.to_string()
+	} else {
+		"non-returning".to_string()
+	};
+	format!(
+		"inputs {}, outputs {}, max stack increase {}",
+		ty.inputs, outputs, ty.max_stack_increase
+	)
+}
+
+/// Where an offset of a container is: in its header, types, data, or at an
+/// instruction of a code section, looking into the subcontainers.
+fn locate(code: &[u8], offset: usize) -> String {
+	let header = match EofHeader::parse(code) {
+		Ok(header) => header,
+		Err(_) => return "in the header".into(),
+	};
+
+	if let Some(index) = header
+		.code_sections
+		.iter()
+		.position(|section| section.contains(&offset))
+	{
+		let instruction = disassemble(code)
+			.take_while(|instruction| instruction.offset <= offset)
+			.last();
+		return match instruction {
+			Some(instruction) => format!(
+				"in code section {} at {} (offset {} of the section)",
+				index,
+				instruction,
+				instruction.offset - header.code_sections[index].start
+			),
+			None => format!("in code section {}", index),
+		};
+	}
+	if let Some((index, section)) = header
+		.container_sections
+		.iter()
+		.enumerate()
+		.find(|(_, section)| section.contains(&offset))
+	{
+		return format!(
+			"in container section {}, {}",
+			index,
+			locate(&code[section.clone()], offset - section.start)
+		);
+	}
+	if header.data.contains(&offset) || offset >= code.len() {
+		return "in the data section".into();
+	}
+	if offset >= header.code_sections[0].start - header.types.len() * 4 {
+		"in the types section".into()
+	} else {
+		"in the header".into()
+	}
+}
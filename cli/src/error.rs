@@ -0,0 +1,30 @@
+use evm::{backend::fork::ForkError, interpreter::error::ExitError};
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("io error: {0}")]
+	IO(#[from] std::io::Error),
+	#[error("json error: {0}")]
+	JSON(#[from] serde_json::Error),
+	#[error("unknown fork {0}, expected one of: {forks}", forks = evm::standard::Config::FORK_NAMES.join(", "))]
+	UnknownFork(String),
+	#[error("invalid alloc: {0}")]
+	Alloc(String),
+	#[error("invalid transaction: {0:?}")]
+	InvalidTransaction(ExitError),
+	#[error("execution failed: {0:?}")]
+	Execution(ExitError),
+	#[error("{0}")]
+	Rpc(#[from] ForkError),
+	#[error("cannot estimate gas: {0}")]
+	Estimate(&'static str),
+	#[error("cannot benchmark: {0}")]
+	Bench(&'static str),
+	#[error("{0}")]
+	Eof(String),
+	#[error("cannot minimize: {0}")]
+	Minimize(&'static str),
+	#[error("cannot replay: {0}")]
+	Replay(String),
+}
@@ -0,0 +1,245 @@
+//! `estimate`: estimate the gas of a transaction, and its access list, on
+//! top of a chain forked over JSON-RPC.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use clap::Args;
+use evm::{
+	backend::{fork::ForkBackend, OverlayedBackend},
+	interpreter::{error::ExitError, utils::u256_to_h256},
+	standard::{
+		transact_with_gas, Config, Etable, EtableResolver, Invoker, TransactArgs, TransactGas,
+		TransactOverrides, TransactValue,
+	},
+};
+use evm_precompile::StandardPrecompileSet;
+use primitive_types::{H160, H256, U256};
+
+use crate::{
+	config::fork_config,
+	error::Error,
+	parse::{self, Bytes},
+	rpc::{self, Http},
+};
+
+#[derive(Args)]
+pub struct EstimateArgs {
+	/// JSON-RPC endpoint of the chain to fork.
+	#[arg(long)]
+	rpc: String,
+
+	/// Block to fork at, the latest one by default.
+	#[arg(long, value_parser = parse::u256)]
+	block: Option<U256>,
+
+	/// Fork whose rules are applied.
+	#[arg(long, default_value = "cancun")]
+	fork: String,
+
+	/// Sender of the transaction.
+	#[arg(long, value_parser = parse::address)]
+	from: H160,
+
+	/// Account called. Without it, `--data` is run as init code.
+	#[arg(long, value_parser = parse::address)]
+	to: Option<H160>,
+
+	/// Call data, or init code, in hex.
+	#[arg(long, value_parser = parse::bytes, default_value = "")]
+	data: Bytes,
+
+	/// Value transferred, in wei.
+	#[arg(long, value_parser = parse::u256, default_value = "0")]
+	value: U256,
+
+	/// Upper bound of the estimate, the block gas limit by default.
+	#[arg(long, value_parser = parse::u64)]
+	gas: Option<u64>,
+}
+
+/// Executions of the transaction of [EstimateArgs] with different gas
+/// limits and access lists, on fresh overlays of the fork.
+struct Estimator<'a> {
+	args: &'a EstimateArgs,
+	config: Config,
+	fork: ForkBackend<Http>,
+}
+
+/// Outcome of an execution.
+struct Execution {
+	result: Result<TransactValue, ExitError>,
+	gas: TransactGas,
+	accessed: BTreeSet<(H160, Option<H256>)>,
+}
+
+impl<'a> Estimator<'a> {
+	/// Execute with the gas limit and the access list. `None` if the
+	/// transaction is invalid with them.
+	fn execute(
+		&self,
+		gas_limit: U256,
+		access_list: Vec<(H160, Vec<H256>)>,
+	) -> Result<Option<Execution>, Error> {
+		let args = &self.args;
+		let overrides = TransactOverrides {
+			skip_base_fee_check: true,
+			..Default::default()
+		};
+		let transact_args = match args.to {
+			Some(address) => TransactArgs::Call {
+				caller: args.from,
+				address,
+				value: args.value,
+				data: args.data.0.clone(),
+				nonce: None,
+				fee_payer: None,
+				gas_limit,
+				gas_price: U256::zero(),
+				max_priority_fee_per_gas: None,
+				access_list,
+				blob_hashes: Vec::new(),
+				max_fee_per_blob_gas: None,
+				overrides,
+			},
+			None => TransactArgs::Create {
+				caller: args.from,
+				value: args.value,
+				init_code: args.data.0.clone(),
+				salt: None,
+				nonce: None,
+				fee_payer: None,
+				gas_limit,
+				gas_price: U256::zero(),
+				max_priority_fee_per_gas: None,
+				access_list,
+				overrides,
+			},
+		};
+
+		let etable = (
+			Etable::single(evm::standard::eval_gasometer),
+			Etable::runtime(),
+		);
+		let precompiles = StandardPrecompileSet::new(&self.config);
+		let resolver = EtableResolver::new(&self.config, &precompiles, &etable);
+		let invoker = Invoker::new(&self.config, &resolver);
+
+		let mut overlay = OverlayedBackend::new(&self.fork, precompiles_accessed());
+		let outcome = transact_with_gas(transact_args, None, &mut overlay, &invoker);
+		if let Some(err) = self.fork.take_error() {
+			return Err(err.into());
+		}
+
+		Ok(outcome.ok().map(|(result, gas)| Execution {
+			result,
+			gas,
+			accessed: overlay.accessed().clone(),
+		}))
+	}
+
+	/// Whether the transaction succeeds with the gas limit.
+	fn succeeds(&self, gas_limit: U256) -> Result<bool, Error> {
+		Ok(matches!(
+			self.execute(gas_limit, Vec::new())?,
+			Some(Execution { result: Ok(_), .. })
+		))
+	}
+}
+
+pub fn estimate(args: EstimateArgs) -> Result<(), Error> {
+	let config = fork_config(&args.fork)?;
+	let transport = Http::new(&args.rpc)?;
+	let block = match args.block {
+		Some(block) => block,
+		None => rpc::block_number(&transport)?,
+	};
+	let fork = ForkBackend::new(transport, block)?;
+	let cap = args
+		.gas
+		.map(U256::from)
+		.unwrap_or(fork.environment().block_gas_limit);
+	let estimator = Estimator {
+		args: &args,
+		config,
+		fork,
+	};
+
+	let execution = estimator
+		.execute(cap, Vec::new())?
+		.ok_or(Error::Estimate("transaction invalid at the gas cap"))?;
+	if let Err(err) = &execution.result {
+		return Err(Error::Execution(err.clone()));
+	}
+
+	// No gas limit below the gas executed succeeds, and the refunds and the
+	// gas kept by calls (see EIP-150) make the limit needed higher.
+	let mut low = execution.gas.used.saturating_sub(U256::one());
+	let mut high = cap;
+	while low + 1 < high {
+		let mid = (low + high) / 2;
+		if estimator.succeeds(mid)? {
+			high = mid;
+		} else {
+			low = mid;
+		}
+	}
+
+	let access_list = access_list(&execution, &estimator);
+	let with_access_list = estimator
+		.execute(cap, access_list.clone())?
+		.ok_or(Error::Estimate("transaction invalid with its access list"))?;
+
+	println!("block: {}", block);
+	println!("gas estimate: {}", high);
+	println!("access list:");
+	for (address, keys) in &access_list {
+		println!("  {:?}", address);
+		for key in keys {
+			println!("    {:?}", key);
+		}
+	}
+	println!(
+		"gas used with the access list: {} (without: {})",
+		with_access_list.gas.effective, execution.gas.effective
+	);
+
+	Ok(())
+}
+
+/// Access list of the accesses of an execution. The sender, the account
+/// called or created, the coinbase and the precompiles are warm anyway, and
+/// only listed for their storage keys.
+fn access_list(execution: &Execution, estimator: &Estimator) -> Vec<(H160, Vec<H256>)> {
+	let args = estimator.args;
+	let mut warm = precompiles_accessed()
+		.into_iter()
+		.map(|(address, _)| address)
+		.collect::<BTreeSet<_>>();
+	warm.insert(args.from);
+	warm.extend(args.to);
+	if let Ok(TransactValue::Create { address, .. }) = &execution.result {
+		warm.insert(*address);
+	}
+	if estimator.config.warm_coinbase_address {
+		warm.insert(estimator.fork.environment().block_coinbase);
+	}
+
+	let mut list = BTreeMap::<H160, Vec<H256>>::new();
+	for (address, key) in &execution.accessed {
+		match key {
+			Some(key) => list.entry(*address).or_default().push(*key),
+			None if !warm.contains(address) => {
+				list.entry(*address).or_default();
+			}
+			None => (),
+		}
+	}
+	list.into_iter().collect()
+}
+
+/// Precompiles, accessed before a transaction starts.
+fn precompiles_accessed() -> BTreeSet<(H160, Option<H256>)> {
+	(1..10)
+		.map(|i| (u256_to_h256(U256::from(i)).into(), None))
+		.collect()
+}
@@ -0,0 +1,76 @@
+mod alloc;
+mod bench;
+mod config;
+mod debug;
+mod disasm;
+mod eof;
+mod error;
+mod estimate;
+mod minimize;
+mod parse;
+mod replay;
+mod rpc;
+mod run;
+mod statetest;
+
+use std::process;
+
+use clap::{Parser, Subcommand};
+
+use crate::error::Error;
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+	/// Run bytecode, or call an account of a pre state, and print the exit
+	/// reason, output, gas used, logs and state changes.
+	Run(run::RunCommandArgs),
+	/// Disassemble bytecode, given in hex or fetched with `--rpc`.
+	Disasm(disasm::DisasmArgs),
+	/// Run the tests of a state test fixture, optionally tracing them.
+	Statetest(statetest::StatetestArgs),
+	/// Estimate the gas of a transaction, and its access list, on a chain
+	/// forked with `--rpc`.
+	Estimate(estimate::EstimateArgs),
+	/// Step through bytecode, or a call to an account of a pre state,
+	/// interactively.
+	Debug(run::RunArgs),
+	/// Re-execute a transaction of a chain reached with `--rpc`, print its
+	/// trace, and compare the outcome with its receipt.
+	Replay(replay::ReplayArgs),
+	/// Shrink the code and input of a failing execution, such as a fuzzing
+	/// finding, while it keeps failing the same way.
+	Minimize(minimize::MinimizeArgs),
+	/// Execute bytecode, or a built-in workload, repeatedly and report its
+	/// throughput, allocations and hot spots.
+	Bench(bench::BenchArgs),
+	/// Inspect EOF containers.
+	Eof(eof::EofArgs),
+}
+
+fn execute(cli: Cli) -> Result<(), Error> {
+	match cli.command {
+		Command::Run(args) => run::run(args),
+		Command::Disasm(args) => disasm::disasm(args),
+		Command::Statetest(args) => statetest::statetest(args),
+		Command::Estimate(args) => estimate::estimate(args),
+		Command::Debug(args) => debug::debug(args),
+		Command::Replay(args) => replay::replay(args),
+		Command::Minimize(args) => minimize::minimize(args),
+		Command::Bench(args) => bench::bench(args),
+		Command::Eof(args) => eof::eof(args),
+	}
+}
+
+fn main() {
+	if let Err(err) = execute(Cli::parse()) {
+		eprintln!("error: {}", err);
+		process::exit(1);
+	}
+}
@@ -0,0 +1,207 @@
+//! `minimize`: shrink the code and input of a failing execution, such as a
+//! fuzzing finding, while it keeps failing the same way.
+
+use std::{
+	any::Any,
+	fs,
+	panic::{self, AssertUnwindSafe},
+	time::{Duration, Instant},
+};
+
+use clap::Args;
+use evm::{
+	interpreter::{disasm::disassemble, error::ExitError},
+	standard::{transact_with_gas, Etable, EtableResolver, Invoker},
+};
+use evm_precompile::StandardPrecompileSet;
+use serde_json::json;
+
+use crate::{
+	error::Error,
+	run::{self, RunArgs},
+};
+
+#[derive(Args)]
+pub struct MinimizeArgs {
+	#[command(flatten)]
+	run: RunArgs,
+
+	/// Also count executions slower than this many milliseconds as failing.
+	#[arg(long)]
+	slower_than: Option<u64>,
+
+	/// File the reproducer is written to, as JSON with its `code` and
+	/// `input`.
+	#[arg(long)]
+	out: Option<String>,
+}
+
+/// How an execution fails.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Failure {
+	/// The execution panicked, with the message if any.
+	Panic(Option<String>),
+	/// The transaction is invalid, or its execution failed, including with
+	/// a fatal error for a violated invariant.
+	Error(ExitError),
+	/// The execution took longer than `--slower-than`.
+	Slow,
+}
+
+pub fn minimize(args: MinimizeArgs) -> Result<(), Error> {
+	let mut code = args
+		.run
+		.code()
+		.ok_or(Error::Minimize("--code is required"))?
+		.to_vec();
+	let mut input = args.run.input().to_vec();
+	let slower_than = args.slower_than.map(Duration::from_millis);
+
+	// Panics are expected, and their messages part of the failure.
+	let hook = panic::take_hook();
+	panic::set_hook(Box::new(|_| {}));
+	let result = reduce(&args.run, slower_than, &mut code, &mut input);
+	panic::set_hook(hook);
+	let (failure, executions) = result?;
+
+	println!("failure: {:?}", failure);
+	println!(
+		"minimized in {} executions: code {} -> {} bytes, input {} -> {} bytes",
+		executions,
+		args.run.code().unwrap_or_default().len(),
+		code.len(),
+		args.run.input().len(),
+		input.len()
+	);
+	println!("code: 0x{}", hex::encode(&code));
+	println!("input: 0x{}", hex::encode(&input));
+	if let Some(path) = &args.out {
+		let reproducer = json!({
+			"code": format!("0x{}", hex::encode(&code)),
+			"input": format!("0x{}", hex::encode(&input)),
+		});
+		fs::write(path, serde_json::to_string_pretty(&reproducer)?)?;
+	}
+
+	Ok(())
+}
+
+/// Shrink the code and the input until no removal keeps the failure, and
+/// return the failure and the number of executions.
+fn reduce(
+	args: &RunArgs,
+	slower_than: Option<Duration>,
+	code: &mut Vec<u8>,
+	input: &mut Vec<u8>,
+) -> Result<(Failure, usize), Error> {
+	let failure =
+		observe(args, slower_than)?.ok_or(Error::Minimize("the execution does not fail"))?;
+	let mut executions = 1;
+	let mut fails = |code: &[u8], input: &[u8]| {
+		executions += 1;
+		let args = args.with_code_and_input(code.to_vec(), input.to_vec());
+		Ok(observe(&args, slower_than)?.as_ref() == Some(&failure))
+	};
+
+	// Removing instructions from EOF code breaks its container.
+	let eof = disassemble(code).is_eof();
+	loop {
+		let mut shrunk = false;
+		if !eof {
+			let mut instructions = instructions(code);
+			shrunk |= shrink(&mut instructions, |instructions| {
+				fails(&instructions.concat(), input)
+			})?;
+			*code = instructions.concat();
+		}
+		shrunk |= shrink(input, |input| fails(code, input))?;
+		for index in 0..input.len() {
+			if input[index] != 0 {
+				let mut zeroed = input.clone();
+				zeroed[index] = 0;
+				if fails(code, &zeroed)? {
+					*input = zeroed;
+					shrunk = true;
+				}
+			}
+		}
+		if !shrunk {
+			break;
+		}
+	}
+
+	Ok((failure, executions))
+}
+
+/// Execute the transaction of the arguments, and return how it fails.
+fn observe(args: &RunArgs, slower_than: Option<Duration>) -> Result<Option<Failure>, Error> {
+	let (config, backend, transact_args) = run::prepare(args.clone())?;
+
+	let start = Instant::now();
+	let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+		let etable = (
+			Etable::single(evm::standard::eval_gasometer),
+			Etable::runtime(),
+		);
+		let precompiles = StandardPrecompileSet::new(&config);
+		let resolver = EtableResolver::new(&config, &precompiles, &etable);
+		let invoker = Invoker::new(&config, &resolver);
+
+		let mut overlay = backend.overlay();
+		transact_with_gas(transact_args, None, &mut overlay, &invoker)
+			.and_then(|(result, _)| result.map(|_| ()))
+	}));
+	let elapsed = start.elapsed();
+
+	Ok(match outcome {
+		Err(payload) => Some(Failure::Panic(message(payload))),
+		Ok(_) if slower_than.map_or(false, |limit| elapsed > limit) => Some(Failure::Slow),
+		Ok(Err(err)) => Some(Failure::Error(err)),
+		Ok(Ok(())) => None,
+	})
+}
+
+fn message(payload: Box<dyn Any + Send>) -> Option<String> {
+	match payload.downcast::<String>() {
+		Ok(message) => Some(*message),
+		Err(payload) => payload
+			.downcast_ref::<&str>()
+			.map(|message| message.to_string()),
+	}
+}
+
+/// Legacy code split into its instructions, each with its push data.
+fn instructions(code: &[u8]) -> Vec<Vec<u8>> {
+	disassemble(code)
+		.map(|instruction| {
+			let end = instruction.offset + 1 + instruction.immediate.len();
+			code[instruction.offset..end].to_vec()
+		})
+		.collect()
+}
+
+/// Remove chunks of items for as long as the failure is kept without them,
+/// halving the size of the chunks down to a single item. Whether any item
+/// was removed.
+fn shrink<T: Clone>(
+	items: &mut Vec<T>,
+	mut fails: impl FnMut(&[T]) -> Result<bool, Error>,
+) -> Result<bool, Error> {
+	let mut shrunk = false;
+	let mut size = (items.len() + 1) / 2;
+	while size > 0 {
+		let mut start = 0;
+		while start < items.len() {
+			let end = (start + size).min(items.len());
+			let candidate = [&items[..start], &items[end..]].concat();
+			if fails(&candidate)? {
+				*items = candidate;
+				shrunk = true;
+			} else {
+				start = end;
+			}
+		}
+		size /= 2;
+	}
+	Ok(shrunk)
+}
@@ -0,0 +1,50 @@
+//! Parsers of command line values and of the strings of JSON files.
+
+use primitive_types::{H160, H256, U256};
+
+/// Parse a number, in hexadecimal with a `0x` prefix or in decimal.
+pub fn u256(value: &str) -> Result<U256, String> {
+	match value.strip_prefix("0x") {
+		Some(hex) if hex.is_empty() => Ok(U256::zero()),
+		Some(hex) => U256::from_str_radix(hex, 16).map_err(|err| err.to_string()),
+		None => U256::from_dec_str(value).map_err(|err| err.to_string()),
+	}
+}
+
+/// Parse a number that fits in 64 bits.
+pub fn u64(value: &str) -> Result<u64, String> {
+	let value = u256(value)?;
+	if value > U256::from(u64::MAX) {
+		return Err(format!("{} does not fit in 64 bits", value));
+	}
+	Ok(value.as_u64())
+}
+
+/// Parse a 32-byte word given as a number, such as a storage key.
+pub fn word(value: &str) -> Result<H256, String> {
+	let value = u256(value)?;
+	let mut word = H256::zero();
+	value.to_big_endian(&mut word.0);
+	Ok(word)
+}
+
+/// Parse a hex address, with or without `0x` prefix.
+pub fn address(value: &str) -> Result<H160, String> {
+	let bytes = bytes(value)?.0;
+	if bytes.len() != 20 {
+		return Err(format!("{} is not a 20-byte address", value));
+	}
+	Ok(H160::from_slice(&bytes))
+}
+
+/// Bytes given in hex.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Bytes(pub Vec<u8>);
+
+/// Parse hex bytes, with or without `0x` prefix.
+pub fn bytes(value: &str) -> Result<Bytes, String> {
+	let value = value.trim();
+	hex::decode(value.strip_prefix("0x").unwrap_or(value))
+		.map(Bytes)
+		.map_err(|err| err.to_string())
+}
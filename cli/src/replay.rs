@@ -0,0 +1,341 @@
+//! `replay`: re-execute a transaction of a chain on top of the state forked
+//! over JSON-RPC, trace it, and compare the outcome with its receipt.
+
+use clap::{Args, ValueEnum};
+use evm::{
+	backend::{
+		fork::{ForkBackend, RpcTransport},
+		OverlayedBackend,
+	},
+	interpreter::runtime::Log,
+	standard::{execute_block, Config, Etable, EtableResolver, Invoker, TransactArgs},
+};
+use evm_precompile::StandardPrecompileSet;
+use evm_tracer::{
+	CallTracer, CallTracerConfig, StructLogger, StructLoggerConfig, TracedBackend, TracingEtable,
+	TracingInvoker,
+};
+use primitive_types::{H160, H256, U256};
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+
+use crate::{config::fork_config, error::Error, parse, rpc::Http};
+
+#[derive(Args)]
+pub struct ReplayArgs {
+	/// Hash of the transaction.
+	#[arg(value_parser = parse::word)]
+	hash: H256,
+
+	/// JSON-RPC endpoint of the chain.
+	#[arg(long)]
+	rpc: String,
+
+	/// Fork whose rules are applied. It is not derived from the block.
+	#[arg(long, default_value = "cancun")]
+	fork: String,
+
+	/// Trace printed.
+	#[arg(long, value_enum, default_value_t = Tracer::Call)]
+	tracer: Tracer,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Tracer {
+	/// Tree of the calls, as geth's `callTracer`.
+	Call,
+	/// Opcodes evaluated, as geth's default struct logger.
+	Struct,
+}
+
+/// A transaction, as returned by `eth_getTransactionByHash` or in a block.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcTransaction {
+	hash: H256,
+	block_number: Option<U256>,
+	transaction_index: Option<U256>,
+	from: H160,
+	to: Option<H160>,
+	#[serde(deserialize_with = "bytes")]
+	input: Vec<u8>,
+	value: U256,
+	gas: U256,
+	nonce: U256,
+	gas_price: Option<U256>,
+	max_fee_per_gas: Option<U256>,
+	max_priority_fee_per_gas: Option<U256>,
+	#[serde(default)]
+	access_list: Vec<RpcAccess>,
+	#[serde(default)]
+	blob_versioned_hashes: Vec<H256>,
+	max_fee_per_blob_gas: Option<U256>,
+	authorization_list: Option<Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcAccess {
+	address: H160,
+	storage_keys: Vec<H256>,
+}
+
+#[derive(Deserialize)]
+struct RpcBlock {
+	transactions: Vec<RpcTransaction>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcReceipt {
+	status: Option<U256>,
+	gas_used: U256,
+	logs: Vec<RpcLog>,
+}
+
+#[derive(Deserialize)]
+struct RpcLog {
+	address: H160,
+	topics: Vec<H256>,
+	#[serde(deserialize_with = "bytes")]
+	data: Vec<u8>,
+}
+
+fn bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+	let value = String::deserialize(deserializer)?;
+	parse::bytes(&value)
+		.map(|bytes| bytes.0)
+		.map_err(serde::de::Error::custom)
+}
+
+fn decode<T: for<'de> Deserialize<'de>>(value: Value) -> Result<T, Error> {
+	serde_json::from_value(value).map_err(|err| Error::Replay(err.to_string()))
+}
+
+impl RpcTransaction {
+	fn transact_args(&self) -> Result<TransactArgs, Error> {
+		if self.authorization_list.is_some() {
+			return Err(Error::Replay(format!(
+				"transaction {:?} has an unsupported type",
+				self.hash
+			)));
+		}
+		// Dynamic fee transactions pay at most their fee cap, and legacy ones
+		// their gas price.
+		let gas_price = self.max_fee_per_gas.or(self.gas_price).unwrap_or_default();
+		let access_list = self
+			.access_list
+			.iter()
+			.map(|access| (access.address, access.storage_keys.clone()))
+			.collect();
+
+		Ok(match self.to {
+			Some(address) => TransactArgs::Call {
+				caller: self.from,
+				address,
+				value: self.value,
+				data: self.input.clone(),
+				nonce: Some(self.nonce),
+				fee_payer: None,
+				gas_limit: self.gas,
+				gas_price,
+				max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+				access_list,
+				blob_hashes: self.blob_versioned_hashes.clone(),
+				max_fee_per_blob_gas: self.max_fee_per_blob_gas,
+				overrides: Default::default(),
+			},
+			None => TransactArgs::Create {
+				caller: self.from,
+				value: self.value,
+				init_code: self.input.clone(),
+				salt: None,
+				nonce: Some(self.nonce),
+				fee_payer: None,
+				gas_limit: self.gas,
+				gas_price,
+				max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+				access_list,
+				overrides: Default::default(),
+			},
+		})
+	}
+}
+
+pub fn replay(args: ReplayArgs) -> Result<(), Error> {
+	let config = fork_config(&args.fork)?;
+	let transport = Http::new(&args.rpc)?;
+	let transaction: Option<RpcTransaction> =
+		decode(transport.request("eth_getTransactionByHash", json!([args.hash]))?)?;
+	let transaction = transaction
+		.ok_or_else(|| Error::Replay(format!("transaction {:?} not found", args.hash)))?;
+	let (number, index) = match (transaction.block_number, transaction.transaction_index) {
+		(Some(number), Some(index)) if !number.is_zero() => (number, index.as_usize()),
+		_ => {
+			return Err(Error::Replay(format!(
+				"transaction {:?} is not in a block",
+				args.hash
+			)))
+		}
+	};
+	let block: RpcBlock = decode(transport.request(
+		"eth_getBlockByNumber",
+		json!([format!("{:#x}", number), true]),
+	)?)?;
+	let receipt: RpcReceipt =
+		decode(transport.request("eth_getTransactionReceipt", json!([args.hash]))?)?;
+
+	// The state is the one after the parent block, and the environment the
+	// one of the block of the transaction.
+	let environment = ForkBackend::new(Http::new(&args.rpc)?, number)?
+		.environment()
+		.clone();
+	let mut fork = ForkBackend::new(transport, number - 1)?;
+	*fork.environment_mut() = environment;
+
+	let (before, target) = match block.transactions.get(..=index) {
+		Some([before @ .., target]) if target.hash == args.hash => (before, target),
+		_ => {
+			return Err(Error::Replay(format!(
+				"transaction {:?} is not at index {} of block {}",
+				args.hash, index, number
+			)))
+		}
+	};
+	let before = before
+		.iter()
+		.map(RpcTransaction::transact_args)
+		.collect::<Result<Vec<_>, _>>()?;
+	let target = target.transact_args()?;
+
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let precompiles = StandardPrecompileSet::new(&config);
+	let resolver = EtableResolver::new(&config, &precompiles, &etable);
+	let invoker = Invoker::new(&config, &resolver);
+
+	// Changes of the transactions before the replayed one accumulate in an
+	// overlay of the fork.
+	let state = OverlayedBackend::new(&fork, Default::default());
+	let (state, result) = execute_block(before, None, state, &invoker);
+	if let Some(err) = fork.take_error() {
+		return Err(err.into());
+	}
+	result.map_err(|err| Error::Replay(format!("in block {}: {}", number, err)))?;
+
+	let trace = trace(&config, &precompiles, &state, target.clone(), args.tracer)?;
+	if let Some(err) = fork.take_error() {
+		return Err(err.into());
+	}
+	println!("{}", serde_json::to_string_pretty(&trace)?);
+
+	let (_, result) = execute_block([target], None, state, &invoker);
+	if let Some(err) = fork.take_error() {
+		return Err(err.into());
+	}
+	let local = result
+		.map_err(|err| Error::Replay(err.to_string()))?
+		.pop()
+		.expect("one receipt per transaction");
+
+	println!("receipt:");
+	let status = receipt.status.map(|status| !status.is_zero());
+	if status.map_or(true, |status| status == local.status) {
+		println!("  status: {}", status_name(local.status));
+	} else {
+		println!(
+			"  status: {} on chain, {} locally",
+			status.map_or("unknown", status_name),
+			status_name(local.status)
+		);
+	}
+	if receipt.gas_used == local.gas_used {
+		println!("  gas used: {}", local.gas_used);
+	} else {
+		println!(
+			"  gas used: {} on chain, {} locally",
+			receipt.gas_used, local.gas_used
+		);
+	}
+	match receipt
+		.logs
+		.iter()
+		.zip(&local.logs)
+		.position(|(expected, log)| !log_matches(expected, log))
+	{
+		None if receipt.logs.len() == local.logs.len() => {
+			println!("  logs: {}", local.logs.len())
+		}
+		None => println!(
+			"  logs: {} on chain, {} locally",
+			receipt.logs.len(),
+			local.logs.len()
+		),
+		Some(position) => println!(
+			"  logs: {} on chain, {} locally, differing from log {}",
+			receipt.logs.len(),
+			local.logs.len(),
+			position
+		),
+	}
+
+	Ok(())
+}
+
+/// Trace the transaction on a fresh overlay of the state.
+fn trace<'config>(
+	config: &'config Config,
+	precompiles: &StandardPrecompileSet<'config>,
+	state: &OverlayedBackend<&ForkBackend<Http>>,
+	args: TransactArgs,
+	tracer: Tracer,
+) -> Result<Value, Error> {
+	let overlay = OverlayedBackend::new(state, Default::default());
+
+	Ok(match tracer {
+		// The call tracer does not look at the opcodes evaluated.
+		Tracer::Call => {
+			let etable = (
+				Etable::single(evm::standard::eval_gasometer),
+				Etable::runtime(),
+			);
+			let resolver = EtableResolver::new(config, precompiles, &etable);
+			let invoker = TracingInvoker::new(Invoker::new(config, &resolver));
+			let mut traced = TracedBackend::new(
+				overlay,
+				CallTracer::new(CallTracerConfig {
+					only_top_call: false,
+					with_log: true,
+				}),
+			);
+			let _ = evm::transact(args, None, &mut traced, &invoker);
+			serde_json::to_value(traced.into_parts().1.into_result())?
+		}
+		Tracer::Struct => {
+			let etable = TracingEtable::new((
+				Etable::single(evm::standard::eval_gasometer),
+				Etable::runtime(),
+			));
+			let resolver = EtableResolver::new(config, precompiles, &etable);
+			let invoker = TracingInvoker::new(Invoker::new(config, &resolver));
+			let mut traced =
+				TracedBackend::new(overlay, StructLogger::new(StructLoggerConfig::default()));
+			let _ = evm::transact(args, None, &mut traced, &invoker);
+			serde_json::to_value(traced.into_parts().1.into_result())?
+		}
+	})
+}
+
+fn status_name(status: bool) -> &'static str {
+	if status {
+		"success"
+	} else {
+		"failure"
+	}
+}
+
+fn log_matches(expected: &RpcLog, log: &Log) -> bool {
+	expected.address == log.address && expected.topics == log.topics && expected.data == log.data
+}
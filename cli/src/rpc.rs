@@ -0,0 +1,177 @@
+//! Minimal JSON-RPC transport over plain HTTP, for the subcommands taking
+//! `--rpc`. Use a local node, or a proxy, for `https://` endpoints.
+
+use std::{
+	cell::Cell,
+	io::{BufRead, BufReader, Read, Write},
+	net::TcpStream,
+};
+
+use evm::backend::fork::{ForkError, RpcTransport};
+use primitive_types::{H160, U256};
+use serde_json::{json, Value};
+
+use crate::parse;
+
+/// JSON-RPC over HTTP/1.1, with a connection per request.
+pub struct Http {
+	host: String,
+	port: u16,
+	path: String,
+	id: Cell<u64>,
+}
+
+impl Http {
+	/// Transport to an `http://host[:port][/path]` endpoint.
+	pub fn new(url: &str) -> Result<Self, ForkError> {
+		let rest = url
+			.strip_prefix("http://")
+			.ok_or_else(|| ForkError::Transport(format!("{} is not an http:// url", url)))?;
+		let (authority, path) = match rest.find('/') {
+			Some(index) => (&rest[..index], &rest[index..]),
+			None => (rest, "/"),
+		};
+		let (host, port) = match authority.rsplit_once(':') {
+			Some((host, port)) => (
+				host,
+				port.parse()
+					.map_err(|_| ForkError::Transport(format!("invalid port in {}", url)))?,
+			),
+			None => (authority, 80),
+		};
+
+		Ok(Self {
+			host: host.to_string(),
+			port,
+			path: path.to_string(),
+			id: Cell::new(0),
+		})
+	}
+
+	fn post(&self, body: &[u8]) -> std::io::Result<(u16, Vec<u8>)> {
+		let mut stream = TcpStream::connect((self.host.as_str(), self.port))?;
+		write!(
+			stream,
+			"POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+			self.path,
+			self.host,
+			body.len()
+		)?;
+		stream.write_all(body)?;
+
+		let mut reader = BufReader::new(stream);
+		let mut line = String::new();
+		reader.read_line(&mut line)?;
+		let status = line
+			.split_whitespace()
+			.nth(1)
+			.and_then(|status| status.parse().ok())
+			.ok_or_else(|| invalid_data(format!("invalid status line {:?}", line)))?;
+
+		let mut length = None;
+		let mut chunked = false;
+		loop {
+			line.clear();
+			reader.read_line(&mut line)?;
+			let header = line.trim_end();
+			if header.is_empty() {
+				break;
+			}
+			if let Some((name, value)) = header.split_once(':') {
+				let value = value.trim();
+				if name.eq_ignore_ascii_case("content-length") {
+					length = value.parse().ok();
+				} else if name.eq_ignore_ascii_case("transfer-encoding") {
+					chunked = value.eq_ignore_ascii_case("chunked");
+				}
+			}
+		}
+
+		let mut body = Vec::new();
+		if chunked {
+			loop {
+				line.clear();
+				reader.read_line(&mut line)?;
+				let size = line.trim_end().split(';').next().unwrap_or_default();
+				let size = usize::from_str_radix(size, 16)
+					.map_err(|_| invalid_data(format!("invalid chunk size {:?}", line)))?;
+				if size == 0 {
+					break;
+				}
+				let start = body.len();
+				body.resize(start + size, 0);
+				reader.read_exact(&mut body[start..])?;
+				line.clear();
+				reader.read_line(&mut line)?;
+			}
+		} else if let Some(length) = length {
+			body.resize(length, 0);
+			reader.read_exact(&mut body)?;
+		} else {
+			reader.read_to_end(&mut body)?;
+		}
+
+		Ok((status, body))
+	}
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+	std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+impl RpcTransport for Http {
+	fn request(&self, method: &str, params: Value) -> Result<Value, ForkError> {
+		let id = self.id.get() + 1;
+		self.id.set(id);
+		let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+
+		let (status, body) = self
+			.post(request.to_string().as_bytes())
+			.map_err(|err| ForkError::Transport(err.to_string()))?;
+		let mut response: Value = serde_json::from_slice(&body)
+			.map_err(|err| ForkError::Transport(format!("http status {}: {}", status, err)))?;
+
+		if let Some(error) = response.get("error") {
+			return Err(ForkError::Rpc(
+				error
+					.get("message")
+					.and_then(Value::as_str)
+					.map_or_else(|| error.to_string(), str::to_string),
+			));
+		}
+		match response.get_mut("result") {
+			Some(result) => Ok(result.take()),
+			None => Err(ForkError::Decode(format!("no result in {}", response))),
+		}
+	}
+}
+
+/// Code of an account at a block, a number or a tag such as `latest`.
+pub fn code(
+	transport: &dyn RpcTransport,
+	address: H160,
+	block: &str,
+) -> Result<Vec<u8>, ForkError> {
+	let code = transport.request("eth_getCode", json!([address, block]))?;
+	let code = code
+		.as_str()
+		.ok_or_else(|| ForkError::Decode(format!("code {} is not a string", code)))?;
+	parse::bytes(code)
+		.map(|code| code.0)
+		.map_err(ForkError::Decode)
+}
+
+/// Parse a block given as a number or as a tag such as `latest`, into a
+/// JSON-RPC block parameter.
+pub fn block(value: &str) -> Result<String, String> {
+	match value {
+		"latest" | "earliest" | "pending" | "safe" | "finalized" => Ok(value.to_string()),
+		_ => parse::u256(value).map(|number| format!("{:#x}", number)),
+	}
+}
+
+/// Number of the latest block.
+pub fn block_number(transport: &dyn RpcTransport) -> Result<U256, ForkError> {
+	serde_json::from_value(transport.request("eth_blockNumber", json!([]))?)
+		.map_err(|err| ForkError::Decode(err.to_string()))
+}
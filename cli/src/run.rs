@@ -0,0 +1,254 @@
+//! `run`: execute bytecode, or a call to an account of the pre state, and
+//! print the outcome.
+
+use std::{collections::BTreeMap, fmt::Debug};
+
+use clap::Args;
+use evm::{
+	backend::{Delta, InMemoryAccount, InMemoryBackend, InMemoryEnvironment, StateDiff},
+	interpreter::{error::ExitError, runtime::Log},
+	standard::{
+		transact_with_gas, Config, Etable, EtableResolver, Invoker, TransactArgs, TransactValue,
+	},
+};
+use evm_precompile::StandardPrecompileSet;
+use primitive_types::{H160, U256};
+
+use crate::{
+	alloc,
+	config::fork_config,
+	error::Error,
+	parse::{self, Bytes},
+};
+
+#[derive(Args, Clone)]
+pub struct RunArgs {
+	/// Code to run, in hex. It is deployed at the `--to` address, or run as
+	/// init code with `--create`. Without it, the code of the `--to`
+	/// account of the pre state is run.
+	#[arg(long, value_parser = parse::bytes)]
+	code: Option<Bytes>,
+
+	/// Call data, or constructor arguments appended to the init code, in
+	/// hex.
+	#[arg(long, value_parser = parse::bytes, default_value = "")]
+	input: Bytes,
+
+	/// Value transferred, in wei.
+	#[arg(long, value_parser = parse::u256, default_value = "0")]
+	value: U256,
+
+	/// Fork whose rules are applied.
+	#[arg(long, default_value = "cancun")]
+	fork: String,
+
+	/// Gas limit of the transaction.
+	#[arg(long, value_parser = parse::u64, default_value = "30000000")]
+	gas: u64,
+
+	/// Sender of the transaction.
+	#[arg(long, value_parser = parse::address, default_value = "0x1000000000000000000000000000000000000000")]
+	from: H160,
+
+	/// Account called.
+	#[arg(long, value_parser = parse::address, default_value = "0x2000000000000000000000000000000000000000")]
+	to: H160,
+
+	/// Run `--code` as the init code of a create transaction.
+	#[arg(long, default_value_t = false, requires = "code")]
+	create: bool,
+
+	/// Pre state, as the `alloc` of a geth genesis file.
+	#[arg(long)]
+	prestate: Option<String>,
+}
+
+impl RunArgs {
+	/// Code given with `--code`.
+	pub fn code(&self) -> Option<&[u8]> {
+		self.code.as_ref().map(|code| &code.0[..])
+	}
+
+	/// Call data, or constructor arguments, given with `--input`.
+	pub fn input(&self) -> &[u8] {
+		&self.input.0
+	}
+
+	/// The same arguments, with another code and input.
+	pub fn with_code_and_input(&self, code: Vec<u8>, input: Vec<u8>) -> Self {
+		Self {
+			code: Some(Bytes(code)),
+			input: Bytes(input),
+			..self.clone()
+		}
+	}
+}
+
+#[derive(Args)]
+pub struct RunCommandArgs {
+	#[command(flatten)]
+	run: RunArgs,
+
+	/// Write the post state to the file, as an alloc.
+	#[arg(long)]
+	dump_state: Option<String>,
+}
+
+/// Block environment of transactions run by the CLI.
+pub fn environment() -> InMemoryEnvironment {
+	InMemoryEnvironment {
+		block_number: U256::one(),
+		block_gas_limit: U256::from(u64::MAX),
+		chain_id: U256::one(),
+		..Default::default()
+	}
+}
+
+/// Config, pre state and transaction given by the arguments.
+pub fn prepare(args: RunArgs) -> Result<(Config, InMemoryBackend, TransactArgs), Error> {
+	let config = fork_config(&args.fork)?;
+
+	let mut state = match &args.prestate {
+		Some(path) => alloc::load(path)?,
+		None => BTreeMap::new(),
+	};
+	if let (Some(code), false) = (&args.code, args.create) {
+		state.entry(args.to).or_default().code = code.0.clone();
+	}
+	// A sender missing from the pre state is given the value it transfers.
+	state.entry(args.from).or_insert_with(|| InMemoryAccount {
+		balance: args.value,
+		..Default::default()
+	});
+	let backend = InMemoryBackend {
+		environment: environment(),
+		state,
+	};
+
+	let transact_args = if args.create {
+		TransactArgs::Create {
+			caller: args.from,
+			value: args.value,
+			init_code: [args.code.unwrap_or_default().0, args.input.0].concat(),
+			salt: None,
+			nonce: None,
+			fee_payer: None,
+			gas_limit: U256::from(args.gas),
+			gas_price: U256::zero(),
+			max_priority_fee_per_gas: None,
+			access_list: Vec::new(),
+			overrides: Default::default(),
+		}
+	} else {
+		TransactArgs::Call {
+			caller: args.from,
+			address: args.to,
+			value: args.value,
+			data: args.input.0,
+			nonce: None,
+			fee_payer: None,
+			gas_limit: U256::from(args.gas),
+			gas_price: U256::zero(),
+			max_priority_fee_per_gas: None,
+			access_list: Vec::new(),
+			blob_hashes: Vec::new(),
+			max_fee_per_blob_gas: None,
+			overrides: Default::default(),
+		}
+	};
+
+	Ok((config, backend, transact_args))
+}
+
+pub fn run(args: RunCommandArgs) -> Result<(), Error> {
+	let (config, backend, transact_args) = prepare(args.run)?;
+
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let precompiles = StandardPrecompileSet::new(&config);
+	let resolver = EtableResolver::new(&config, &precompiles, &etable);
+	let invoker = Invoker::new(&config, &resolver);
+
+	let mut overlay = backend.overlay();
+	let (result, gas) = transact_with_gas(transact_args, None, &mut overlay, &invoker)
+		.map_err(Error::InvalidTransaction)?;
+	let state_diff = overlay.state_diff();
+	let changeset = overlay.deconstruct().1;
+
+	print_result(result);
+	println!(
+		"gas used: {} (executed {}, refunded {})",
+		gas.effective, gas.used, gas.refunded
+	);
+	print_logs(&changeset.logs);
+	print_state_diff(&state_diff);
+
+	if let Some(path) = &args.dump_state {
+		let mut post = backend;
+		post.apply_overlayed(&changeset);
+		alloc::dump(path, &post.state)?;
+	}
+
+	Ok(())
+}
+
+pub fn print_result(result: Result<TransactValue, ExitError>) {
+	match result {
+		Ok(TransactValue::Call { succeed, retval }) => {
+			println!("exit: {:?}", succeed);
+			println!("output: 0x{}", hex::encode(retval));
+		}
+		Ok(TransactValue::Create { succeed, address }) => {
+			println!("exit: {:?}", succeed);
+			println!("created: {:?}", address);
+		}
+		Err(err) => println!("exit: {:?}", err),
+	}
+}
+
+pub fn print_logs(logs: &[Log]) {
+	if logs.is_empty() {
+		return;
+	}
+	println!("logs:");
+	for log in logs {
+		println!("  {:?}", log.address);
+		for topic in &log.topics {
+			println!("    topic: {:?}", topic);
+		}
+		println!("    data: 0x{}", hex::encode(&log.data));
+	}
+}
+
+pub fn print_state_diff(state_diff: &StateDiff) {
+	if state_diff.accounts.is_empty() {
+		return;
+	}
+	println!("state changes:");
+	for (address, account) in &state_diff.accounts {
+		println!("  {:?}", address);
+		print_delta("balance", &account.balance);
+		print_delta("nonce", &account.nonce);
+		match &account.code {
+			Delta::Unchanged => (),
+			Delta::Added(code) | Delta::Changed { to: code, .. } => {
+				println!("    code: 0x{}", hex::encode(code))
+			}
+			Delta::Removed(_) => println!("    code: removed"),
+		}
+		for (key, value) in &account.storage {
+			print_delta(&format!("storage[{:?}]", key), value);
+		}
+	}
+}
+
+fn print_delta<T: Debug>(name: &str, delta: &Delta<T>) {
+	match delta {
+		Delta::Unchanged => (),
+		Delta::Added(value) => println!("    {}: {:?}", name, value),
+		Delta::Removed(value) => println!("    {}: {:?} -> removed", name, value),
+		Delta::Changed { from, to } => println!("    {}: {:?} -> {:?}", name, from, to),
+	}
+}
@@ -0,0 +1,185 @@
+//! `statetest`: run the tests of a state test fixture, with the output of
+//! geth's `evm statetest`, so that differential fuzzing tools can drive the
+//! CLI as one of their clients.
+
+use std::{
+	collections::BTreeMap,
+	fs::{self, File},
+	io::{self, BufReader, Write},
+};
+
+use clap::Args;
+use evm::{
+	backend::{InMemoryBackend, OverlayedBackend},
+	standard::{Etable, EtableResolver, Invoker},
+};
+use evm_precompile::StandardPrecompileSet;
+use evm_tracer::{JsonLogger, JsonLoggerConfig, TracedBackend, TracingEtable, TracingInvoker};
+use jsontests::{
+	filter::TestFilter,
+	hash::state_root,
+	run,
+	types::{TestData, TestExpectException, TestMulti},
+};
+use primitive_types::H256;
+use serde_json::{json, Map, Value};
+
+use crate::{alloc, error::Error};
+
+#[derive(Args)]
+pub struct StatetestArgs {
+	/// State test fixture.
+	file: String,
+
+	/// Only run the tests whose name matches the pattern.
+	#[arg(long)]
+	run: Option<String>,
+
+	/// Only run the tests of the fork.
+	#[arg(long)]
+	fork: Option<String>,
+
+	/// Only run the post state of the given index.
+	#[arg(long)]
+	index: Option<usize>,
+
+	/// Write an EIP-3155 JSON trace of each transaction to stderr, followed
+	/// by its state root.
+	#[arg(long = "trace.json", alias = "json")]
+	trace: bool,
+
+	/// Capture the memory in the traces.
+	#[arg(long = "trace.memory")]
+	trace_memory: bool,
+
+	/// Do not capture the stack in the traces.
+	#[arg(long = "trace.nostack")]
+	trace_nostack: bool,
+
+	/// Capture the return data in the traces.
+	#[arg(long = "trace.returndata")]
+	trace_returndata: bool,
+
+	/// Write the post state of each test to the file, as an object of
+	/// allocs by `<name>/<fork>/<index>`.
+	#[arg(long)]
+	dump_state: Option<String>,
+}
+
+pub fn statetest(args: StatetestArgs) -> Result<(), Error> {
+	let tests: BTreeMap<String, TestMulti> =
+		serde_json::from_reader(BufReader::new(File::open(&args.file)?))?;
+	let filter = TestFilter {
+		name: args.run.clone(),
+		fork: args.fork.clone(),
+		case_index: args.index,
+	};
+	let trace = if args.trace {
+		Some(JsonLoggerConfig {
+			enable_memory: args.trace_memory,
+			disable_stack: args.trace_nostack,
+			enable_return_data: args.trace_returndata,
+		})
+	} else {
+		None
+	};
+
+	let mut results = Vec::new();
+	let mut dumps = Map::new();
+	for (name, test) in tests {
+		for test in test.tests() {
+			let fork = format!("{:?}", test.fork);
+			if !filter.matches(&name, &fork, Some(test.index)) {
+				continue;
+			}
+
+			let mut result = json!({ "name": name, "pass": false, "fork": fork });
+			match execute(&test, trace) {
+				Ok((root, post)) => {
+					if args.dump_state.is_some() {
+						dumps.insert(
+							format!("{}/{}/{}", name, fork, test.index),
+							alloc::to_json(&post.state),
+						);
+					}
+					result["stateRoot"] = json!(root);
+					if test.post.expect_exception.is_none() && root != test.post.hash {
+						result["error"] = json!(format!(
+							"post state root mismatch: got {:?}, want {:?}",
+							root, test.post.hash
+						));
+					} else {
+						result["pass"] = json!(true);
+					}
+				}
+				Err(err) => result["error"] = json!(err),
+			}
+			results.push(result);
+		}
+	}
+
+	println!("{}", serde_json::to_string_pretty(&Value::Array(results))?);
+	if let Some(path) = &args.dump_state {
+		fs::write(path, serde_json::to_string_pretty(&Value::Object(dumps))?)?;
+	}
+	Ok(())
+}
+
+/// Run the transaction of a test, writing its trace to stderr if enabled,
+/// and return the post state and its root. An invalid transaction leaves the
+/// state unchanged, and fails the test unless it is expected.
+fn execute(
+	test: &TestData,
+	trace: Option<JsonLoggerConfig>,
+) -> Result<(H256, InMemoryBackend), String> {
+	let config = run::fork_config(test.fork).ok_or("unsupported fork")?;
+	if test.transaction.has_authorization_list {
+		return Err("unsupported transaction type".into());
+	}
+
+	let backend = InMemoryBackend {
+		environment: run::test_environment(test, &config),
+		state: run::in_memory_state(test.pre.clone()),
+	};
+
+	let etable = TracingEtable::new((
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	));
+	let precompiles = StandardPrecompileSet::new(&config);
+	let resolver = EtableResolver::new(&config, &precompiles, &etable);
+	let invoker = TracingInvoker::new(Invoker::new(&config, &resolver));
+
+	// Traces are discarded, rather than not collected, when disabled.
+	let writer: Box<dyn Write> = match trace {
+		Some(_) => Box::new(io::stderr()),
+		None => Box::new(io::sink()),
+	};
+	let mut traced = TracedBackend::new(
+		OverlayedBackend::new(&backend, run::initial_accessed()),
+		JsonLogger::new(writer, trace.unwrap_or_default()),
+	);
+	let result = evm::transact(
+		run::test_transact_args(test),
+		Some(4),
+		&mut traced,
+		&invoker,
+	);
+	let (overlay, logger) = traced.into_parts();
+	logger.into_inner().map_err(|err| err.to_string())?;
+
+	let mut post = backend.clone();
+	post.apply_overlayed(&overlay.deconstruct().1);
+	let root = state_root(&post);
+	if trace.is_some() {
+		eprintln!("{}", json!({ "stateRoot": root }));
+	}
+
+	match (result, &test.post.expect_exception) {
+		(Ok(_), Some(TestExpectException::TR_TypeNotSupported)) | (Err(_), Some(_)) => {
+			Ok((root, post))
+		}
+		(Ok(_), Some(_)) => Err("expected an exception".into()),
+		(_, None) => Ok((root, post)),
+	}
+}
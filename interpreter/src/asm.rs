@@ -0,0 +1,128 @@
+//! Bytecode assembler, to write readable code in tests.
+//!
+//! ```
+//! use evm_interpreter::asm::Assembler;
+//!
+//! // Loop forever.
+//! let code = Assembler::new().label("start").jump("start").assemble();
+//! assert_eq!(code.unwrap(), [0x5b, 0x61, 0x00, 0x00, 0x56]);
+//! ```
+
+use alloc::{string::String, vec::Vec};
+
+use primitive_types::U256;
+
+use crate::opcode::Opcode;
+
+/// Error of [Assembler::assemble].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AsmError {
+	/// A label is used but never defined.
+	UndefinedLabel(String),
+	/// A label is defined twice.
+	DuplicateLabel(String),
+	/// A label is at an offset that does not fit in a `PUSH2`.
+	LabelOutOfRange(String),
+}
+
+/// Bytecode builder. Labels are `JUMPDEST`s, pushed as `PUSH2` of their
+/// offset, which is resolved when assembling.
+#[derive(Clone, Debug, Default)]
+pub struct Assembler {
+	code: Vec<u8>,
+	labels: Vec<(String, usize)>,
+	references: Vec<(String, usize)>,
+}
+
+impl Assembler {
+	/// Create an empty assembler.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Append an opcode.
+	#[must_use]
+	pub fn op(mut self, opcode: Opcode) -> Self {
+		self.code.push(opcode.0);
+		self
+	}
+
+	/// Append raw bytes.
+	#[must_use]
+	pub fn raw(mut self, bytes: &[u8]) -> Self {
+		self.code.extend_from_slice(bytes);
+		self
+	}
+
+	/// Push a value, with the shortest push opcode: `PUSH0` for zero.
+	#[must_use]
+	pub fn push<V: Into<U256>>(mut self, value: V) -> Self {
+		let value = value.into();
+		let len = (value.bits() + 7) / 8;
+
+		let mut bytes = [0u8; 32];
+		value.to_big_endian(&mut bytes);
+		self.code.push(Opcode::PUSH0.0 + len as u8);
+		self.code.extend_from_slice(&bytes[(32 - len)..]);
+		self
+	}
+
+	/// Define a label at the current offset, with a `JUMPDEST`.
+	#[must_use]
+	pub fn label(mut self, label: &str) -> Self {
+		self.labels.push((label.into(), self.code.len()));
+		self.op(Opcode::JUMPDEST)
+	}
+
+	/// Push the offset of a label.
+	#[must_use]
+	pub fn push_label(mut self, label: &str) -> Self {
+		self.code.push(Opcode::PUSH2.0);
+		self.references.push((label.into(), self.code.len()));
+		self.code.extend_from_slice(&[0, 0]);
+		self
+	}
+
+	/// Jump to a label.
+	#[must_use]
+	pub fn jump(self, label: &str) -> Self {
+		self.push_label(label).op(Opcode::JUMP)
+	}
+
+	/// Jump to a label if the top of the stack is not zero.
+	#[must_use]
+	pub fn jumpi(self, label: &str) -> Self {
+		self.push_label(label).op(Opcode::JUMPI)
+	}
+
+	/// Current offset in the code.
+	#[must_use]
+	pub fn offset(&self) -> usize {
+		self.code.len()
+	}
+
+	/// Resolve labels and return the code.
+	pub fn assemble(mut self) -> Result<Vec<u8>, AsmError> {
+		for (i, (label, _)) in self.labels.iter().enumerate() {
+			if self.labels[..i].iter().any(|(other, _)| other == label) {
+				return Err(AsmError::DuplicateLabel(label.clone()));
+			}
+		}
+
+		for (label, position) in &self.references {
+			let offset = self
+				.labels
+				.iter()
+				.find(|(other, _)| other == label)
+				.map(|(_, offset)| *offset)
+				.ok_or_else(|| AsmError::UndefinedLabel(label.clone()))?;
+			let offset =
+				u16::try_from(offset).map_err(|_| AsmError::LabelOutOfRange(label.clone()))?;
+
+			self.code[*position..(*position + 2)].copy_from_slice(&offset.to_be_bytes());
+		}
+
+		Ok(self.code)
+	}
+}
@@ -0,0 +1,160 @@
+//! Bytecode disassembler.
+//!
+//! [disassemble] iterates over the instructions of legacy code, or of the
+//! code sections of an [EOF](https://eips.ethereum.org/EIPS/eip-3540)
+//! container, with their immediate bytes.
+
+use alloc::vec::Vec;
+use core::{fmt, ops::Range};
+
+use crate::{eof::EofHeader, opcode::Opcode};
+
+/// A disassembled instruction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Instruction<'code> {
+	/// Offset of the opcode in the code.
+	pub offset: usize,
+	/// Opcode.
+	pub opcode: Opcode,
+	/// Immediate bytes following the opcode, such as push data. Truncated
+	/// if the code ends before them.
+	pub immediate: &'code [u8],
+}
+
+impl<'code> fmt::Display for Instruction<'code> {
+	/// Standard mnemonic of the opcode, followed by its immediate bytes in
+	/// hex, if any.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.opcode)?;
+
+		if !self.immediate.is_empty() {
+			write!(f, " 0x")?;
+			for byte in self.immediate {
+				write!(f, "{:02x}", byte)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Iterator over the instructions of code, returned by [disassemble].
+#[derive(Clone, Debug)]
+pub struct Instructions<'code> {
+	code: &'code [u8],
+	sections: Vec<Range<usize>>,
+	eof: bool,
+	section: usize,
+	position: usize,
+}
+
+impl<'code> Instructions<'code> {
+	/// Whether the code is an EOF container.
+	#[must_use]
+	pub const fn is_eof(&self) -> bool {
+		self.eof
+	}
+
+	/// Ranges of the code sections in the code. The whole code for legacy
+	/// code.
+	#[must_use]
+	pub fn sections(&self) -> &[Range<usize>] {
+		&self.sections
+	}
+}
+
+impl<'code> Iterator for Instructions<'code> {
+	type Item = Instruction<'code>;
+
+	fn next(&mut self) -> Option<Instruction<'code>> {
+		loop {
+			let section = self.sections.get(self.section)?.clone();
+			if self.position < section.start {
+				self.position = section.start;
+			}
+			if self.position >= section.end {
+				self.section += 1;
+				continue;
+			}
+
+			let offset = self.position;
+			let opcode = Opcode(self.code[offset]);
+			let len = immediate_len(opcode, &self.code[(offset + 1)..section.end], self.eof);
+			let end = (offset + 1).saturating_add(len).min(section.end);
+
+			self.position = end;
+			return Some(Instruction {
+				offset,
+				opcode,
+				immediate: &self.code[(offset + 1)..end],
+			});
+		}
+	}
+}
+
+/// Disassemble code. EOF containers, starting with `0xef00`, are
+/// disassembled section by section with EOF immediates. Other code, or a
+/// container whose header is malformed, is disassembled as legacy code.
+#[must_use]
+pub fn disassemble(code: &[u8]) -> Instructions<'_> {
+	let (sections, eof) = match EofHeader::parse(code) {
+		Ok(header) => (header.code_sections, true),
+		Err(_) => (alloc::vec![0..code.len()], false),
+	};
+
+	Instructions {
+		code,
+		sections,
+		eof,
+		section: 0,
+		position: 0,
+	}
+}
+
+/// Number of immediate bytes of `opcode`, given the code following it.
+pub(crate) fn immediate_len(opcode: Opcode, rest: &[u8], eof: bool) -> usize {
+	if let Some(n) = opcode.is_push() {
+		return n as usize;
+	}
+	if !eof {
+		return 0;
+	}
+
+	match opcode.0 {
+		// DUPN, SWAPN, EXCHANGE, EOFCREATE, RETURNCONTRACT.
+		0xe6 | 0xe7 | 0xe8 | 0xec | 0xee => 1,
+		// DATALOADN, RJUMP, RJUMPI, CALLF, JUMPF.
+		0xd1 | 0xe0 | 0xe1 | 0xe3 | 0xe5 => 2,
+		// RJUMPV: the maximum index, then a relative offset per index.
+		0xe2 => rest
+			.first()
+			.map_or(1, |max_index| 1 + (*max_index as usize + 1) * 2),
+		_ => 0,
+	}
+}
+
+/// Mnemonics of EOF opcodes, not defined in legacy code.
+pub(crate) const fn eof_name(opcode: Opcode) -> Option<&'static str> {
+	Some(match opcode.0 {
+		0xd0 => "DATALOAD",
+		0xd1 => "DATALOADN",
+		0xd2 => "DATASIZE",
+		0xd3 => "DATACOPY",
+		0xe0 => "RJUMP",
+		0xe1 => "RJUMPI",
+		0xe2 => "RJUMPV",
+		0xe3 => "CALLF",
+		0xe4 => "RETF",
+		0xe5 => "JUMPF",
+		0xe6 => "DUPN",
+		0xe7 => "SWAPN",
+		0xe8 => "EXCHANGE",
+		0xec => "EOFCREATE",
+		0xee => "RETURNCONTRACT",
+		0xf7 => "RETURNDATALOAD",
+		0xf8 => "EXTCALL",
+		0xf9 => "EXTDELEGATECALL",
+		0xfb => "EXTSTATICCALL",
+		_ => return None,
+	})
+}
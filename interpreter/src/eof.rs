@@ -0,0 +1,834 @@
+//! EOF container validation.
+//!
+//! [validate] checks an [EOF](https://eips.ethereum.org/EIPS/eip-3540)
+//! container before it is deployed or executed: its header and section
+//! sizes, the instructions of its code sections
+//! ([EIP-3670](https://eips.ethereum.org/EIPS/eip-3670)), their relative
+//! jumps ([EIP-4200](https://eips.ethereum.org/EIPS/eip-4200)), calls
+//! between sections ([EIP-4750](https://eips.ethereum.org/EIPS/eip-4750)),
+//! stack heights ([EIP-5450](https://eips.ethereum.org/EIPS/eip-5450)) and,
+//! recursively, its subcontainers
+//! ([EIP-7620](https://eips.ethereum.org/EIPS/eip-7620)).
+
+use alloc::{vec, vec::Vec};
+use core::{fmt, ops::Range};
+
+use crate::{
+	disasm::{eof_name, immediate_len},
+	opcode::Opcode,
+};
+
+/// First bytes of an EOF container.
+pub const MAGIC: [u8; 2] = [0xef, 0x00];
+/// Supported EOF version.
+pub const VERSION: u8 = 0x01;
+
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_CONTAINER: u8 = 0x03;
+const KIND_DATA: u8 = 0xff;
+
+const MAX_CODE_SECTIONS: usize = 1024;
+const MAX_CONTAINER_SECTIONS: usize = 256;
+const MAX_CONTAINER_SIZE: usize = 0xc000;
+const MAX_STACK_HEIGHT: usize = 1024;
+const MAX_STACK_INCREASE: u16 = 0x03ff;
+
+const DATALOADN: u8 = 0xd1;
+const RJUMP: u8 = 0xe0;
+const RJUMPI: u8 = 0xe1;
+const RJUMPV: u8 = 0xe2;
+const CALLF: u8 = 0xe3;
+const RETF: u8 = 0xe4;
+const JUMPF: u8 = 0xe5;
+const DUPN: u8 = 0xe6;
+const SWAPN: u8 = 0xe7;
+const EXCHANGE: u8 = 0xe8;
+const EOFCREATE: u8 = 0xec;
+const RETURNCODE: u8 = 0xee;
+
+/// Kind of validation failure, see [EofError].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EofErrorKind {
+	InvalidMagic,
+	InvalidVersion,
+	MissingTypeHeader,
+	MissingCodeHeader,
+	MissingDataSection,
+	MissingTerminator,
+	IncompleteSectionNumber,
+	IncompleteSectionSize,
+	ZeroSectionSize,
+	TooManyCodeSections,
+	TooManyContainers,
+	InvalidTypeSectionSize,
+	InvalidSectionBodiesSize,
+	TopLevelContainerTruncated,
+	EofcreateWithTruncatedContainer,
+	ContainerSizeAboveLimit,
+	InvalidFirstSectionType,
+	InputsOutputsNumAboveLimit,
+	MaxStackIncreaseAboveLimit,
+	UndefinedInstruction,
+	TruncatedInstruction,
+	MissingStopOpcode,
+	InvalidRjumpDestination,
+	InvalidCodeSectionIndex,
+	InvalidDataloadnIndex,
+	InvalidContainerSectionIndex,
+	CallfToNonReturning,
+	JumpfDestinationIncompatibleOutputs,
+	InvalidNonReturningFlag,
+	StackUnderflow,
+	StackOverflow,
+	StackHigherThanOutputs,
+	StackHeightMismatch,
+	InvalidMaxStackIncrease,
+	UnreachableInstructions,
+	UnreachableCodeSections,
+	OrphanSubcontainer,
+	IncompatibleContainerKind,
+}
+
+impl EofErrorKind {
+	const ALL: [EofErrorKind; 38] = [
+		Self::InvalidMagic,
+		Self::InvalidVersion,
+		Self::MissingTypeHeader,
+		Self::MissingCodeHeader,
+		Self::MissingDataSection,
+		Self::MissingTerminator,
+		Self::IncompleteSectionNumber,
+		Self::IncompleteSectionSize,
+		Self::ZeroSectionSize,
+		Self::TooManyCodeSections,
+		Self::TooManyContainers,
+		Self::InvalidTypeSectionSize,
+		Self::InvalidSectionBodiesSize,
+		Self::TopLevelContainerTruncated,
+		Self::EofcreateWithTruncatedContainer,
+		Self::ContainerSizeAboveLimit,
+		Self::InvalidFirstSectionType,
+		Self::InputsOutputsNumAboveLimit,
+		Self::MaxStackIncreaseAboveLimit,
+		Self::UndefinedInstruction,
+		Self::TruncatedInstruction,
+		Self::MissingStopOpcode,
+		Self::InvalidRjumpDestination,
+		Self::InvalidCodeSectionIndex,
+		Self::InvalidDataloadnIndex,
+		Self::InvalidContainerSectionIndex,
+		Self::CallfToNonReturning,
+		Self::JumpfDestinationIncompatibleOutputs,
+		Self::InvalidNonReturningFlag,
+		Self::StackUnderflow,
+		Self::StackOverflow,
+		Self::StackHigherThanOutputs,
+		Self::StackHeightMismatch,
+		Self::InvalidMaxStackIncrease,
+		Self::UnreachableInstructions,
+		Self::UnreachableCodeSections,
+		Self::OrphanSubcontainer,
+		Self::IncompatibleContainerKind,
+	];
+
+	/// Name of the kind, as the `EOFException` of execution-spec-tests.
+	#[must_use]
+	pub const fn name(&self) -> &'static str {
+		match self {
+			Self::InvalidMagic => "INVALID_MAGIC",
+			Self::InvalidVersion => "INVALID_VERSION",
+			Self::MissingTypeHeader => "MISSING_TYPE_HEADER",
+			Self::MissingCodeHeader => "MISSING_CODE_HEADER",
+			Self::MissingDataSection => "MISSING_DATA_SECTION",
+			Self::MissingTerminator => "MISSING_TERMINATOR",
+			Self::IncompleteSectionNumber => "INCOMPLETE_SECTION_NUMBER",
+			Self::IncompleteSectionSize => "INCOMPLETE_SECTION_SIZE",
+			Self::ZeroSectionSize => "ZERO_SECTION_SIZE",
+			Self::TooManyCodeSections => "TOO_MANY_CODE_SECTIONS",
+			Self::TooManyContainers => "TOO_MANY_CONTAINERS",
+			Self::InvalidTypeSectionSize => "INVALID_TYPE_SECTION_SIZE",
+			Self::InvalidSectionBodiesSize => "INVALID_SECTION_BODIES_SIZE",
+			Self::TopLevelContainerTruncated => "TOPLEVEL_CONTAINER_TRUNCATED",
+			Self::EofcreateWithTruncatedContainer => "EOFCREATE_WITH_TRUNCATED_CONTAINER",
+			Self::ContainerSizeAboveLimit => "CONTAINER_SIZE_ABOVE_LIMIT",
+			Self::InvalidFirstSectionType => "INVALID_FIRST_SECTION_TYPE",
+			Self::InputsOutputsNumAboveLimit => "INPUTS_OUTPUTS_NUM_ABOVE_LIMIT",
+			Self::MaxStackIncreaseAboveLimit => "MAX_STACK_INCREASE_ABOVE_LIMIT",
+			Self::UndefinedInstruction => "UNDEFINED_INSTRUCTION",
+			Self::TruncatedInstruction => "TRUNCATED_INSTRUCTION",
+			Self::MissingStopOpcode => "MISSING_STOP_OPCODE",
+			Self::InvalidRjumpDestination => "INVALID_RJUMP_DESTINATION",
+			Self::InvalidCodeSectionIndex => "INVALID_CODE_SECTION_INDEX",
+			Self::InvalidDataloadnIndex => "INVALID_DATALOADN_INDEX",
+			Self::InvalidContainerSectionIndex => "INVALID_CONTAINER_SECTION_INDEX",
+			Self::CallfToNonReturning => "CALLF_TO_NON_RETURNING",
+			Self::JumpfDestinationIncompatibleOutputs => "JUMPF_DESTINATION_INCOMPATIBLE_OUTPUTS",
+			Self::InvalidNonReturningFlag => "INVALID_NON_RETURNING_FLAG",
+			Self::StackUnderflow => "STACK_UNDERFLOW",
+			Self::StackOverflow => "STACK_OVERFLOW",
+			Self::StackHigherThanOutputs => "STACK_HIGHER_THAN_OUTPUTS",
+			Self::StackHeightMismatch => "STACK_HEIGHT_MISMATCH",
+			Self::InvalidMaxStackIncrease => "INVALID_MAX_STACK_INCREASE",
+			Self::UnreachableInstructions => "UNREACHABLE_INSTRUCTIONS",
+			Self::UnreachableCodeSections => "UNREACHABLE_CODE_SECTIONS",
+			Self::OrphanSubcontainer => "ORPHAN_SUBCONTAINER",
+			Self::IncompatibleContainerKind => "INCOMPATIBLE_CONTAINER_KIND",
+		}
+	}
+
+	/// Kind of the given [EofErrorKind::name].
+	#[must_use]
+	pub fn from_name(name: &str) -> Option<Self> {
+		Self::ALL.iter().copied().find(|kind| kind.name() == name)
+	}
+}
+
+impl fmt::Display for EofErrorKind {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.name())
+	}
+}
+
+/// Validation failure of an EOF container.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EofError {
+	/// Kind of the failure.
+	pub kind: EofErrorKind,
+	/// Offset in the container of the byte or instruction that fails, in
+	/// the outermost container for failures of subcontainers.
+	pub offset: usize,
+}
+
+impl fmt::Display for EofError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{} at offset {}", self.kind, self.offset)
+	}
+}
+
+impl EofError {
+	const fn new(kind: EofErrorKind, offset: usize) -> Self {
+		Self { kind, offset }
+	}
+}
+
+/// How a container is used, which restricts the instructions it can end
+/// with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContainerKind {
+	/// Code of an account, which cannot use `RETURNCODE`.
+	Runtime,
+	/// Code of a creation, which cannot use `STOP` or `RETURN`.
+	Initcode,
+}
+
+impl Default for ContainerKind {
+	fn default() -> Self {
+		Self::Runtime
+	}
+}
+
+/// Type of a code section.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EofType {
+	/// Number of stack items the section takes.
+	pub inputs: u8,
+	/// Number of stack items the section returns, or
+	/// [EofType::NON_RETURNING].
+	pub outputs: u8,
+	/// Highest stack height reached by the section, above its inputs.
+	pub max_stack_increase: u16,
+}
+
+impl EofType {
+	/// Outputs of a section that never returns to its caller.
+	pub const NON_RETURNING: u8 = 0x80;
+
+	/// Whether the section returns to its caller.
+	#[must_use]
+	pub const fn is_returning(&self) -> bool {
+		self.outputs != Self::NON_RETURNING
+	}
+}
+
+/// Layout of an EOF container, given by its header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EofHeader {
+	/// Types of the code sections.
+	pub types: Vec<EofType>,
+	/// Ranges of the code sections in the container.
+	pub code_sections: Vec<Range<usize>>,
+	/// Ranges of the subcontainers in the container.
+	pub container_sections: Vec<Range<usize>>,
+	/// Range of the data section in the container, as declared. It ends
+	/// after the container if the data is truncated.
+	pub data: Range<usize>,
+}
+
+struct Reader<'code> {
+	code: &'code [u8],
+	position: usize,
+}
+
+impl<'code> Reader<'code> {
+	fn bytes(&mut self, n: usize) -> Option<&'code [u8]> {
+		let ret = self.code.get(self.position..(self.position + n))?;
+		self.position += n;
+		Some(ret)
+	}
+
+	fn u8(&mut self) -> Option<u8> {
+		self.bytes(1).map(|b| b[0])
+	}
+
+	fn u16(&mut self) -> Option<usize> {
+		self.bytes(2)
+			.map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+	}
+
+	fn u32(&mut self) -> Option<usize> {
+		self.bytes(4)
+			.map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+	}
+
+	fn error(&self, kind: EofErrorKind) -> EofError {
+		EofError::new(kind, self.position)
+	}
+
+	/// Read a kind byte, which must be `kind`.
+	fn kind(&mut self, kind: u8, missing: EofErrorKind) -> Result<(), EofError> {
+		let error = self.error(missing);
+		match self.u8() {
+			Some(value) if value == kind => Ok(()),
+			_ => Err(error),
+		}
+	}
+
+	/// Read a number of sections, which must be in `1..=max`.
+	fn section_number(&mut self, max: usize, too_many: EofErrorKind) -> Result<usize, EofError> {
+		let error = self.error(EofErrorKind::IncompleteSectionNumber);
+		match self.u16() {
+			None => Err(error),
+			Some(0) => Err(EofError::new(EofErrorKind::ZeroSectionSize, error.offset)),
+			Some(number) if number > max => Err(EofError::new(too_many, error.offset)),
+			Some(number) => Ok(number),
+		}
+	}
+
+	/// Read the non-zero sizes of `number` sections.
+	fn section_sizes(
+		&mut self,
+		number: usize,
+		read: fn(&mut Self) -> Option<usize>,
+	) -> Result<Vec<usize>, EofError> {
+		let mut sizes = Vec::with_capacity(number);
+		for _ in 0..number {
+			let error = self.error(EofErrorKind::IncompleteSectionSize);
+			match read(self) {
+				None => return Err(error),
+				Some(0) => return Err(EofError::new(EofErrorKind::ZeroSectionSize, error.offset)),
+				Some(size) => sizes.push(size),
+			}
+		}
+		Ok(sizes)
+	}
+}
+
+impl EofHeader {
+	/// Parse the header of an EOF container, and check that the sections
+	/// it declares fit the container. Only the data section can be
+	/// truncated.
+	pub fn parse(code: &[u8]) -> Result<Self, EofError> {
+		let mut reader = Reader { code, position: 0 };
+		if reader.bytes(2) != Some(&MAGIC[..]) {
+			return Err(EofError::new(EofErrorKind::InvalidMagic, 0));
+		}
+		if reader.u8() != Some(VERSION) {
+			return Err(EofError::new(EofErrorKind::InvalidVersion, 2));
+		}
+
+		reader.kind(KIND_TYPES, EofErrorKind::MissingTypeHeader)?;
+		let types_offset = reader.position;
+		let types_size = reader.u16().ok_or(EofError::new(
+			EofErrorKind::IncompleteSectionSize,
+			types_offset,
+		))?;
+
+		reader.kind(KIND_CODE, EofErrorKind::MissingCodeHeader)?;
+		let code_number =
+			reader.section_number(MAX_CODE_SECTIONS, EofErrorKind::TooManyCodeSections)?;
+		let code_sizes = reader.section_sizes(code_number, Reader::u16)?;
+
+		let mut container_sizes = Vec::new();
+		if reader.code.get(reader.position) == Some(&KIND_CONTAINER) {
+			reader.position += 1;
+			let container_number =
+				reader.section_number(MAX_CONTAINER_SECTIONS, EofErrorKind::TooManyContainers)?;
+			container_sizes = reader.section_sizes(container_number, Reader::u32)?;
+		}
+
+		reader.kind(KIND_DATA, EofErrorKind::MissingDataSection)?;
+		let data_size = reader
+			.u16()
+			.ok_or(reader.error(EofErrorKind::IncompleteSectionSize))?;
+		reader.kind(0x00, EofErrorKind::MissingTerminator)?;
+
+		if types_size != code_number * 4 {
+			return Err(EofError::new(
+				EofErrorKind::InvalidTypeSectionSize,
+				types_offset,
+			));
+		}
+
+		let mut start = reader.position;
+		let mut section = |size: usize| {
+			let range = start..(start + size);
+			start += size;
+			range
+		};
+		let types_range = section(types_size);
+		let code_sections = code_sizes.into_iter().map(&mut section).collect::<Vec<_>>();
+		let container_sections = container_sizes
+			.into_iter()
+			.map(&mut section)
+			.collect::<Vec<_>>();
+		let data = section(data_size);
+
+		if data.start > code.len() || data.end < code.len() {
+			return Err(EofError::new(
+				EofErrorKind::InvalidSectionBodiesSize,
+				reader.position,
+			));
+		}
+
+		let types = code[types_range]
+			.chunks(4)
+			.map(|chunk| EofType {
+				inputs: chunk[0],
+				outputs: chunk[1],
+				max_stack_increase: u16::from_be_bytes([chunk[2], chunk[3]]),
+			})
+			.collect();
+
+		Ok(Self {
+			types,
+			code_sections,
+			container_sections,
+			data,
+		})
+	}
+
+	/// Offset of the type of a code section.
+	fn type_offset(&self, section: usize) -> usize {
+		self.code_sections[0].start - self.types.len() * 4 + section * 4
+	}
+}
+
+/// Validate an EOF container of the given kind, returning its header.
+pub fn validate(code: &[u8], kind: ContainerKind) -> Result<EofHeader, EofError> {
+	validate_container(code, kind, Some(EofErrorKind::TopLevelContainerTruncated))
+}
+
+/// Validate a container. Its data can only be truncated if `truncated` is
+/// `None`, that is for the subcontainers deployed by `RETURNCODE`.
+fn validate_container(
+	code: &[u8],
+	kind: ContainerKind,
+	truncated: Option<EofErrorKind>,
+) -> Result<EofHeader, EofError> {
+	if code.len() > MAX_CONTAINER_SIZE {
+		return Err(EofError::new(EofErrorKind::ContainerSizeAboveLimit, 0));
+	}
+
+	let header = EofHeader::parse(code)?;
+	if header.data.end > code.len() {
+		if let Some(truncated) = truncated {
+			return Err(EofError::new(truncated, code.len()));
+		}
+	}
+
+	for (index, ty) in header.types.iter().enumerate() {
+		let offset = header.type_offset(index);
+		if index == 0 && (ty.inputs != 0 || ty.is_returning()) {
+			return Err(EofError::new(EofErrorKind::InvalidFirstSectionType, offset));
+		}
+		if ty.inputs > 0x7f || ty.outputs > EofType::NON_RETURNING {
+			return Err(EofError::new(
+				EofErrorKind::InputsOutputsNumAboveLimit,
+				offset,
+			));
+		}
+		if ty.max_stack_increase > MAX_STACK_INCREASE {
+			return Err(EofError::new(
+				EofErrorKind::MaxStackIncreaseAboveLimit,
+				offset + 2,
+			));
+		}
+	}
+
+	let mut sections = Vec::with_capacity(header.code_sections.len());
+	for index in 0..header.code_sections.len() {
+		sections.push(validate_section(code, &header, index, kind)?);
+	}
+
+	// Code sections must be reachable from the first one, through `CALLF`
+	// and `JUMPF`.
+	let mut reached = vec![false; sections.len()];
+	let mut pending = vec![0];
+	reached[0] = true;
+	while let Some(index) = pending.pop() {
+		for callee in &sections[index].callees {
+			if !reached[*callee] {
+				reached[*callee] = true;
+				pending.push(*callee);
+			}
+		}
+	}
+	if let Some(index) = reached.iter().position(|reached| !reached) {
+		return Err(EofError::new(
+			EofErrorKind::UnreachableCodeSections,
+			header.code_sections[index].start,
+		));
+	}
+
+	for (index, section) in sections.iter().enumerate() {
+		if header.types[index].is_returning() && !section.returns {
+			return Err(EofError::new(
+				EofErrorKind::InvalidNonReturningFlag,
+				header.type_offset(index),
+			));
+		}
+	}
+
+	for (index, range) in header.container_sections.iter().enumerate() {
+		let created = sections
+			.iter()
+			.any(|section| section.created.contains(&index));
+		let returned = sections
+			.iter()
+			.any(|section| section.returned.contains(&index));
+		let (kind, truncated) = match (created, returned) {
+			(true, false) => (
+				ContainerKind::Initcode,
+				Some(EofErrorKind::EofcreateWithTruncatedContainer),
+			),
+			(false, true) => (ContainerKind::Runtime, None),
+			(true, true) => {
+				return Err(EofError::new(
+					EofErrorKind::IncompatibleContainerKind,
+					range.start,
+				))
+			}
+			(false, false) => {
+				return Err(EofError::new(EofErrorKind::OrphanSubcontainer, range.start))
+			}
+		};
+
+		validate_container(&code[range.clone()], kind, truncated)
+			.map_err(|err| EofError::new(err.kind, range.start + err.offset))?;
+	}
+
+	Ok(header)
+}
+
+/// References of a code section to other sections and subcontainers.
+struct SectionReferences {
+	/// Sections called or jumped to.
+	callees: Vec<usize>,
+	/// Whether the section returns, with `RETF` or by jumping to a
+	/// returning section.
+	returns: bool,
+	/// Subcontainers created with `EOFCREATE`.
+	created: Vec<usize>,
+	/// Subcontainers deployed with `RETURNCODE`.
+	returned: Vec<usize>,
+}
+
+struct Instruction {
+	offset: usize,
+	opcode: u8,
+	immediate: Range<usize>,
+}
+
+impl Instruction {
+	fn u16(&self, code: &[u8], at: usize) -> usize {
+		u16::from_be_bytes([
+			code[self.immediate.start + at],
+			code[self.immediate.start + at + 1],
+		]) as usize
+	}
+
+	/// Destinations of a relative jump.
+	fn jump_targets(&self, code: &[u8]) -> Vec<isize> {
+		let end = self.immediate.end as isize;
+		let relative = |at: usize| self.u16(code, at) as u16 as i16 as isize;
+		match self.opcode {
+			RJUMP | RJUMPI => vec![end + relative(0)],
+			RJUMPV => (0..=(code[self.immediate.start] as usize))
+				.map(|index| end + relative(1 + index * 2))
+				.collect(),
+			_ => Vec::new(),
+		}
+	}
+
+	/// Whether execution does not continue with the next instruction.
+	fn terminates(&self) -> bool {
+		matches!(
+			self.opcode,
+			0x00 | 0xf3 | 0xfd | 0xfe | RETF | JUMPF | RETURNCODE | RJUMP
+		)
+	}
+}
+
+/// Whether an opcode is defined in EOF code.
+fn defined(opcode: u8) -> bool {
+	if eof_name(Opcode(opcode)).is_some() {
+		return true;
+	}
+
+	match opcode {
+		// JUMP, JUMPI, PC, GAS, code introspection, legacy calls and
+		// creations, SELFDESTRUCT and AUTH.
+		0x38 | 0x39 | 0x3b | 0x3c | 0x3f | 0x56..=0x58 | 0x5a => false,
+		0xf0..=0xf2 | 0xf4..=0xf6 | 0xfa | 0xff => false,
+		_ => Opcode(opcode).stack_io().is_some(),
+	}
+}
+
+/// Number of stack items an instruction pops and pushes.
+fn stack_io(instruction: &Instruction, code: &[u8], types: &[EofType]) -> (usize, usize) {
+	let immediate = code.get(instruction.immediate.start).copied().unwrap_or(0) as usize;
+	match instruction.opcode {
+		0xd0 | 0xf7 => (1, 1),
+		DATALOADN | 0xd2 => (0, 1),
+		0xd3 => (3, 0),
+		RJUMP => (0, 0),
+		RJUMPI | RJUMPV => (1, 0),
+		CALLF => {
+			let ty = types[instruction.u16(code, 0)];
+			(ty.inputs as usize, ty.outputs as usize)
+		}
+		DUPN => (immediate + 1, immediate + 2),
+		SWAPN => (immediate + 2, immediate + 2),
+		EXCHANGE => {
+			let n = (immediate >> 4) + 1 + (immediate & 0x0f) + 1 + 1;
+			(n, n)
+		}
+		EOFCREATE | 0xf8 => (4, 1),
+		0xf9 | 0xfb => (3, 1),
+		RETURNCODE => (2, 0),
+		opcode => Opcode(opcode).stack_io().unwrap_or((0, 0)),
+	}
+}
+
+/// Validate the instructions and stack heights of a code section.
+fn validate_section(
+	code: &[u8],
+	header: &EofHeader,
+	index: usize,
+	kind: ContainerKind,
+) -> Result<SectionReferences, EofError> {
+	let section = header.code_sections[index].clone();
+	let ty = header.types[index];
+	let mut references = SectionReferences {
+		callees: Vec::new(),
+		returns: false,
+		created: Vec::new(),
+		returned: Vec::new(),
+	};
+
+	// Decode instructions, with the index of the instruction starting at
+	// each offset of the section.
+	let mut instructions = Vec::new();
+	let mut starts = vec![None; section.len()];
+	let mut position = section.start;
+	while position < section.end {
+		let opcode = code[position];
+		if !defined(opcode) {
+			return Err(EofError::new(EofErrorKind::UndefinedInstruction, position));
+		}
+
+		let len = immediate_len(Opcode(opcode), &code[(position + 1)..section.end], true);
+		if position + 1 + len > section.end {
+			return Err(EofError::new(EofErrorKind::TruncatedInstruction, position));
+		}
+
+		starts[position - section.start] = Some(instructions.len());
+		instructions.push(Instruction {
+			offset: position,
+			opcode,
+			immediate: (position + 1)..(position + 1 + len),
+		});
+		position += 1 + len;
+	}
+
+	match instructions.last() {
+		Some(last) if last.terminates() => (),
+		_ => {
+			return Err(EofError::new(
+				EofErrorKind::MissingStopOpcode,
+				section.end - 1,
+			))
+		}
+	}
+
+	let target_index = |target: isize| -> Option<usize> {
+		if target < section.start as isize {
+			return None;
+		}
+		starts
+			.get(target as usize - section.start)
+			.copied()
+			.flatten()
+	};
+
+	for instruction in &instructions {
+		let error = |kind| Err(EofError::new(kind, instruction.offset));
+		for target in instruction.jump_targets(code) {
+			if target_index(target).is_none() {
+				return error(EofErrorKind::InvalidRjumpDestination);
+			}
+		}
+
+		match instruction.opcode {
+			CALLF | JUMPF => {
+				let callee = instruction.u16(code, 0);
+				let callee_ty = match header.types.get(callee) {
+					Some(callee_ty) => *callee_ty,
+					None => return error(EofErrorKind::InvalidCodeSectionIndex),
+				};
+				if instruction.opcode == CALLF && !callee_ty.is_returning() {
+					return error(EofErrorKind::CallfToNonReturning);
+				}
+				if instruction.opcode == JUMPF && callee_ty.is_returning() {
+					if !ty.is_returning() {
+						return error(EofErrorKind::InvalidNonReturningFlag);
+					}
+					if callee_ty.outputs > ty.outputs {
+						return error(EofErrorKind::JumpfDestinationIncompatibleOutputs);
+					}
+					references.returns = true;
+				}
+				references.callees.push(callee);
+			}
+			RETF => {
+				if !ty.is_returning() {
+					return error(EofErrorKind::InvalidNonReturningFlag);
+				}
+				references.returns = true;
+			}
+			DATALOADN => {
+				if instruction.u16(code, 0) + 32 > header.data.len() {
+					return error(EofErrorKind::InvalidDataloadnIndex);
+				}
+			}
+			EOFCREATE | RETURNCODE => {
+				let container = code[instruction.immediate.start] as usize;
+				if container >= header.container_sections.len() {
+					return error(EofErrorKind::InvalidContainerSectionIndex);
+				}
+				if instruction.opcode == EOFCREATE {
+					references.created.push(container);
+				} else if kind == ContainerKind::Runtime {
+					return error(EofErrorKind::IncompatibleContainerKind);
+				} else {
+					references.returned.push(container);
+				}
+			}
+			0x00 | 0xf3 if kind == ContainerKind::Initcode => {
+				return error(EofErrorKind::IncompatibleContainerKind);
+			}
+			_ => (),
+		}
+	}
+
+	// Stack heights, as ranges of the heights over all paths. Instructions
+	// are only reached forward, and backward jumps must keep the height.
+	let inputs = ty.inputs as usize;
+	let mut heights: Vec<Option<(usize, usize)>> = vec![None; instructions.len()];
+	heights[0] = Some((inputs, inputs));
+	let mut max_height = inputs;
+	for (i, instruction) in instructions.iter().enumerate() {
+		let error = |kind| Err(EofError::new(kind, instruction.offset));
+		let (min, max) = match heights[i] {
+			Some(height) => height,
+			None => return error(EofErrorKind::UnreachableInstructions),
+		};
+
+		if matches!(instruction.opcode, CALLF | JUMPF) {
+			let callee_ty = header.types[instruction.u16(code, 0)];
+			if max + callee_ty.max_stack_increase as usize > MAX_STACK_HEIGHT {
+				return error(EofErrorKind::StackOverflow);
+			}
+		}
+
+		let required = match instruction.opcode {
+			RETF => Some(ty.outputs as usize),
+			JUMPF => {
+				let callee_ty = header.types[instruction.u16(code, 0)];
+				if callee_ty.is_returning() {
+					Some(
+						ty.outputs as usize + callee_ty.inputs as usize
+							- callee_ty.outputs as usize,
+					)
+				} else {
+					if min < callee_ty.inputs as usize {
+						return error(EofErrorKind::StackUnderflow);
+					}
+					None
+				}
+			}
+			_ => None,
+		};
+		if let Some(required) = required {
+			if max > required {
+				return error(EofErrorKind::StackHigherThanOutputs);
+			}
+			if min < required {
+				return error(EofErrorKind::StackUnderflow);
+			}
+			continue;
+		}
+
+		let (pops, pushes) = stack_io(instruction, code, &header.types);
+		if min < pops {
+			return error(EofErrorKind::StackUnderflow);
+		}
+		let next = (min - pops + pushes, max - pops + pushes);
+		max_height = max_height.max(next.1);
+		if next.1 > MAX_STACK_HEIGHT {
+			return error(EofErrorKind::StackOverflow);
+		}
+
+		let mut successors = instruction
+			.jump_targets(code)
+			.into_iter()
+			.filter_map(target_index)
+			.collect::<Vec<_>>();
+		if !instruction.terminates() {
+			successors.push(i + 1);
+		}
+		for successor in successors {
+			if successor <= i {
+				if heights[successor] != Some(next) {
+					return error(EofErrorKind::StackHeightMismatch);
+				}
+				continue;
+			}
+
+			heights[successor] = Some(match heights[successor] {
+				Some((other_min, other_max)) => (other_min.min(next.0), other_max.max(next.1)),
+				None => next,
+			});
+		}
+	}
+
+	if max_height != inputs + ty.max_stack_increase as usize {
+		return Err(EofError::new(
+			EofErrorKind::InvalidMaxStackIncrease,
+			header.type_offset(index) + 2,
+		));
+	}
+
+	Ok(references)
+}
@@ -21,6 +21,9 @@ pub enum ExitError {
 	/// Machine encountered an error that is not supposed to be normal EVM
 	/// errors, such as requiring too much memory to execute.
 	Fatal(ExitFatal),
+	/// Execution reached the instruction limit set by the embedder. Like fatal
+	/// errors, this aborts the whole call stack.
+	InstructionLimit,
 }
 
 impl From<ExitError> for ExitResult {
@@ -38,6 +41,7 @@ impl fmt::Display for ExitError {
 			Self::Exception(_) => f.write_str("EVM exit exception"),
 			Self::Reverted => f.write_str("EVM internal revert"),
 			Self::Fatal(_) => f.write_str("EVM fatal error"),
+			Self::InstructionLimit => f.write_str("EVM instruction limit reached"),
 		}
 	}
 }
@@ -126,6 +130,47 @@ pub enum ExitException {
 	#[cfg_attr(feature = "scale", codec(index = 14))]
 	MaxNonce,
 
+	/// `AUTHCALL` without a valid authorization ([EIP-3074](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-3074.md)).
+	#[cfg_attr(feature = "scale", codec(index = 16))]
+	Unauthorized,
+
+	/// Transaction sender has code ([EIP-3607](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-3607.md)).
+	#[cfg_attr(feature = "scale", codec(index = 17))]
+	SenderHasCode,
+
+	/// Transaction gas price is below the block base fee ([EIP-1559](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-1559.md)).
+	#[cfg_attr(feature = "scale", codec(index = 18))]
+	GasPriceBelowBaseFee,
+
+	/// Transaction priority fee is above its max fee ([EIP-1559](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-1559.md)).
+	#[cfg_attr(feature = "scale", codec(index = 19))]
+	PriorityFeeAboveMaxFee,
+
+	/// Transaction max fee per blob gas is below the block blob base fee ([EIP-4844](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-4844.md)).
+	#[cfg_attr(feature = "scale", codec(index = 20))]
+	BlobGasPriceBelowBaseFee,
+
+	/// Transaction blob hashes are missing, of an unknown version, or not enabled ([EIP-4844](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-4844.md)).
+	#[cfg_attr(feature = "scale", codec(index = 21))]
+	InvalidBlobHashes,
+
+	/// Transaction has more blobs than allowed per transaction ([EIP-4844](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-4844.md)).
+	#[cfg_attr(feature = "scale", codec(index = 25))]
+	TooManyBlobs,
+
+	/// Transaction nonce is missing or does not match the nonce of the sender.
+	#[cfg_attr(feature = "scale", codec(index = 22))]
+	InvalidNonce,
+
+	/// Call re-entering a contract on the call path, rejected by the invoker.
+	#[cfg_attr(feature = "scale", codec(index = 23))]
+	Reentrancy,
+
+	/// Sub-call returning more data than the return buffer limit of the
+	/// calling frame.
+	#[cfg_attr(feature = "scale", codec(index = 24))]
+	ReturnDataLimit,
+
 	/// Other normal errors.
 	#[cfg_attr(feature = "scale", codec(index = 13))]
 	Other(Cow<'static, str>),
@@ -161,8 +206,13 @@ pub enum ExitFatal {
 	AlreadyExited,
 	/// Unfinished execution.
 	Unfinished,
+	/// The memory of the live frames of the transaction exceeds its memory
+	/// budget.
+	#[cfg_attr(feature = "scale", codec(index = 6))]
+	MemoryBudgetExceeded,
 
 	/// Other fatal errors.
+	#[cfg_attr(feature = "scale", codec(index = 5))]
 	Other(Cow<'static, str>),
 }
 
@@ -7,14 +7,14 @@ use core::{
 };
 
 use primitive_types::{H160, H256, U256};
-use sha3::{Digest, Keccak256};
 
 use crate::{
 	error::{ExitError, ExitException, ExitResult},
 	interpreter::Interpreter,
-	machine::{Machine, Memory},
+	machine::{Machine, Memory, MemoryStorage},
 	runtime::{Context, RuntimeBackend, RuntimeState, Transfer},
-	utils::{h256_to_u256, u256_to_usize},
+	utils::{h256_to_u256, keccak256, keccak256_parts, u256_to_usize},
+	Shared,
 };
 
 pub trait TrapConstruct<T> {
@@ -27,6 +27,7 @@ pub trait TrapConsume<T> {
 	fn consume(self) -> Result<T, Self::Rest>;
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum CallCreateTrap {
 	Create,
 	Create2,
@@ -34,6 +35,8 @@ pub enum CallCreateTrap {
 	CallCode,
 	DelegateCall,
 	StaticCall,
+	#[cfg(feature = "eip3074")]
+	AuthCall,
 }
 
 impl TrapConstruct<CallCreateTrap> for CallCreateTrap {
@@ -51,6 +54,7 @@ impl TrapConsume<CallCreateTrap> for CallCreateTrap {
 }
 
 /// Combined call create trap data.
+#[derive(Clone, Debug)]
 pub enum CallCreateTrapData {
 	/// A call trap data.
 	Call(CallTrapData),
@@ -67,9 +71,9 @@ impl CallCreateTrapData {
 		}
 	}
 
-	pub fn new_from<S: AsRef<RuntimeState> + AsMut<RuntimeState>>(
+	pub fn new_from<S: AsRef<RuntimeState> + AsMut<RuntimeState>, M: MemoryStorage>(
 		opcode: CallCreateTrap,
-		machine: &mut Machine<S>,
+		machine: &mut Machine<S, M>,
 	) -> Result<Self, ExitError> {
 		match opcode {
 			CallCreateTrap::Create => Ok(Self::Create(CreateTrapData::new_create_from(machine)?)),
@@ -90,13 +94,55 @@ impl CallCreateTrapData {
 				CallScheme::StaticCall,
 				machine,
 			)?)),
+			#[cfg(feature = "eip3074")]
+			CallCreateTrap::AuthCall => Ok(Self::Call(CallTrapData::new_from(
+				CallScheme::AuthCall,
+				machine,
+			)?)),
+		}
+	}
+
+	/// Trap data of `opcode`, read from the stack and memory of the trapped
+	/// machine without consuming them, unlike [Self::new_from]. This gives
+	/// embedders and instrumentation the target, value, scheme, input and gas
+	/// of a trap before it is handled.
+	pub fn peek_from<S: AsRef<RuntimeState>, M: MemoryStorage>(
+		opcode: CallCreateTrap,
+		machine: &Machine<S, M>,
+	) -> Result<Self, ExitError> {
+		match opcode {
+			CallCreateTrap::Create => Ok(Self::Create(CreateTrapData::peek_create_from(machine)?)),
+			CallCreateTrap::Create2 => {
+				Ok(Self::Create(CreateTrapData::peek_create2_from(machine)?))
+			}
+			CallCreateTrap::Call => Ok(Self::Call(CallTrapData::peek_from(
+				CallScheme::Call,
+				machine,
+			)?)),
+			CallCreateTrap::CallCode => Ok(Self::Call(CallTrapData::peek_from(
+				CallScheme::CallCode,
+				machine,
+			)?)),
+			CallCreateTrap::DelegateCall => Ok(Self::Call(CallTrapData::peek_from(
+				CallScheme::DelegateCall,
+				machine,
+			)?)),
+			CallCreateTrap::StaticCall => Ok(Self::Call(CallTrapData::peek_from(
+				CallScheme::StaticCall,
+				machine,
+			)?)),
+			#[cfg(feature = "eip3074")]
+			CallCreateTrap::AuthCall => Ok(Self::Call(CallTrapData::peek_from(
+				CallScheme::AuthCall,
+				machine,
+			)?)),
 		}
 	}
 
 	pub fn code<H: RuntimeBackend>(&self, handler: &H) -> Vec<u8> {
 		match self {
 			Self::Call(trap) => handler.code(trap.target),
-			Self::Create(trap) => trap.code.clone(),
+			Self::Create(trap) => trap.code.to_vec(),
 		}
 	}
 }
@@ -112,13 +158,17 @@ pub enum CallScheme {
 	DelegateCall,
 	/// `STATICCALL`
 	StaticCall,
+	/// `AUTHCALL`
+	#[cfg(feature = "eip3074")]
+	AuthCall,
 }
 
 #[derive(Clone, Debug)]
 pub struct CallTrapData {
+	pub scheme: CallScheme,
 	pub target: H160,
 	pub transfer: Option<Transfer>,
-	pub input: Vec<u8>,
+	pub input: Shared<Vec<u8>>,
 	pub gas: U256,
 	pub is_static: bool,
 	pub out_offset: U256,
@@ -127,11 +177,13 @@ pub struct CallTrapData {
 }
 
 impl CallTrapData {
+	/// Trap data of the given parameters, and the end of the memory range
+	/// they cover.
 	#[allow(clippy::too_many_arguments)]
-	fn new_from_params<S: AsRef<RuntimeState> + AsMut<RuntimeState>>(
+	fn new_from_params<S: AsRef<RuntimeState>, M: MemoryStorage>(
 		scheme: CallScheme,
-		memory: &mut Memory,
-		state: &mut S,
+		memory: &Memory<M>,
+		state: &S,
 		gas: &H256,
 		to: &H256,
 		value: Option<&H256>,
@@ -139,7 +191,7 @@ impl CallTrapData {
 		in_len: &H256,
 		out_offset: &H256,
 		out_len: &H256,
-	) -> Result<((), Self), ExitError> {
+	) -> Result<(Self, U256), ExitError> {
 		let gas = h256_to_u256(*gas);
 		let value = value.map_or(U256::zero(), |v| h256_to_u256(*v));
 		let in_offset = h256_to_u256(*in_offset);
@@ -160,11 +212,11 @@ impl CallTrapData {
 			Some((u256_to_usize(in_offset)?, u256_to_usize(in_len)?))
 		};
 
-		memory.resize_end(max(in_end, out_end))?;
-
-		let input = in_offset_len
-			.map(|(in_offset, in_len)| memory.get(in_offset, in_len))
-			.unwrap_or(Vec::new());
+		let input = Shared::new(
+			in_offset_len
+				.map(|(in_offset, in_len)| memory.get(in_offset, in_len))
+				.unwrap_or(Vec::new()),
+		);
 
 		let context = match scheme {
 			CallScheme::Call | CallScheme::StaticCall => Context {
@@ -182,29 +234,40 @@ impl CallTrapData {
 				caller: state.as_ref().context.caller,
 				apparent_value: state.as_ref().context.apparent_value,
 			},
+			#[cfg(feature = "eip3074")]
+			CallScheme::AuthCall => Context {
+				address: (*to).into(),
+				caller: state
+					.as_ref()
+					.authorized
+					.ok_or(ExitException::Unauthorized)?,
+				apparent_value: value,
+			},
 		};
 
-		let transfer = if scheme == CallScheme::Call {
-			Some(Transfer {
+		let transfer = match scheme {
+			CallScheme::Call => Some(Transfer {
 				source: state.as_ref().context.address,
 				target: (*to).into(),
 				value,
-			})
-		} else if scheme == CallScheme::CallCode {
-			Some(Transfer {
+			}),
+			CallScheme::CallCode => Some(Transfer {
 				source: state.as_ref().context.address,
 				target: state.as_ref().context.address,
 				value,
-			})
-		} else {
-			None
+			}),
+			#[cfg(feature = "eip3074")]
+			CallScheme::AuthCall => Some(Transfer {
+				source: context.caller,
+				target: (*to).into(),
+				value,
+			}),
+			CallScheme::DelegateCall | CallScheme::StaticCall => None,
 		};
 
-		state.as_mut().retbuf = Vec::new();
-
 		Ok((
-			(),
 			Self {
+				scheme,
 				target: (*to).into(),
 				transfer,
 				input,
@@ -214,21 +277,34 @@ impl CallTrapData {
 				out_offset,
 				out_len,
 			},
+			max(in_end, out_end),
 		))
 	}
 
-	pub fn new_from<S: AsRef<RuntimeState> + AsMut<RuntimeState>>(
+	pub fn new_from<S: AsRef<RuntimeState> + AsMut<RuntimeState>, M: MemoryStorage>(
 		scheme: CallScheme,
-		machine: &mut Machine<S>,
+		machine: &mut Machine<S, M>,
 	) -> Result<Self, ExitError> {
 		let stack = &mut machine.stack;
 		let memory = &mut machine.memory;
 		let state = &mut machine.state;
 
 		match scheme {
-			CallScheme::Call | CallScheme::CallCode => stack.perform_pop7_push0(
+			CallScheme::DelegateCall | CallScheme::StaticCall => {
+				stack.perform_pop6_push0(|gas, to, in_offset, in_len, out_offset, out_len| {
+					let (trap, end) = Self::new_from_params(
+						scheme, memory, state, gas, to, None, in_offset, in_len, out_offset,
+						out_len,
+					)?;
+					memory.resize_end(end)?;
+					state.as_mut().retbuf = Vec::new();
+					Ok(((), trap))
+				})
+			}
+			// `CALL`, `CALLCODE` and `AUTHCALL` carry a value.
+			_ => stack.perform_pop7_push0(
 				|gas, to, value, in_offset, in_len, out_offset, out_len| {
-					Self::new_from_params(
+					let (trap, end) = Self::new_from_params(
 						scheme,
 						memory,
 						state,
@@ -239,20 +315,43 @@ impl CallTrapData {
 						in_len,
 						out_offset,
 						out_len,
-					)
+					)?;
+					memory.resize_end(end)?;
+					state.as_mut().retbuf = Vec::new();
+					Ok(((), trap))
 				},
 			),
-			CallScheme::DelegateCall | CallScheme::StaticCall => {
-				stack.perform_pop6_push0(|gas, to, in_offset, in_len, out_offset, out_len| {
-					Self::new_from_params(
-						scheme, memory, state, gas, to, None, in_offset, in_len, out_offset,
-						out_len,
-					)
-				})
-			}
 		}
 	}
 
+	/// Trap data of `scheme`, read from the stack and memory of the trapped
+	/// machine without consuming them.
+	pub fn peek_from<S: AsRef<RuntimeState>, M: MemoryStorage>(
+		scheme: CallScheme,
+		machine: &Machine<S, M>,
+	) -> Result<Self, ExitError> {
+		let stack = &machine.stack;
+		// `CALL`, `CALLCODE` and `AUTHCALL` carry a value.
+		let (value, rest) = match scheme {
+			CallScheme::DelegateCall | CallScheme::StaticCall => (None, 2),
+			_ => (Some(stack.peek(2)?), 3),
+		};
+
+		let (trap, _) = Self::new_from_params(
+			scheme,
+			&machine.memory,
+			&machine.state,
+			&stack.peek(0)?,
+			&stack.peek(1)?,
+			value.as_ref(),
+			&stack.peek(rest)?,
+			&stack.peek(rest + 1)?,
+			&stack.peek(rest + 2)?,
+			&stack.peek(rest + 3)?,
+		)?;
+		Ok(trap)
+	}
+
 	pub fn feedback<I: Interpreter>(
 		self,
 		reason: ExitResult,
@@ -309,6 +408,11 @@ impl CallTrapData {
 
 				Err(e.into())
 			}
+			Err(ExitError::InstructionLimit) => {
+				interpreter.machine_mut().stack.push(H256::default())?;
+
+				Err(ExitError::InstructionLimit)
+			}
 		};
 
 		match ret {
@@ -351,25 +455,24 @@ pub enum CreateScheme {
 
 impl CreateScheme {
 	pub fn address<H: RuntimeBackend>(&self, handler: &H) -> H160 {
+		self.address_with_nonce(handler.nonce(self.caller()))
+	}
+
+	/// Address of the created contract, given the nonce of the caller. The
+	/// nonce is only used by the legacy scheme.
+	#[must_use]
+	pub fn address_with_nonce(&self, nonce: U256) -> H160 {
 		match self {
 			Self::Create2 {
 				caller,
 				code_hash,
 				salt,
-			} => {
-				let mut hasher = Keccak256::new();
-				hasher.update([0xff]);
-				hasher.update(&caller[..]);
-				hasher.update(&salt[..]);
-				hasher.update(&code_hash[..]);
-				H256::from_slice(hasher.finalize().as_slice()).into()
-			}
+			} => keccak256_parts(&[&[0xff], &caller[..], &salt[..], &code_hash[..]]).into(),
 			Self::Legacy { caller } => {
-				let nonce = handler.nonce(*caller);
 				let mut stream = rlp::RlpStream::new_list(2);
 				stream.append(caller);
 				stream.append(&nonce);
-				H256::from_slice(Keccak256::digest(&stream.out()).as_slice()).into()
+				keccak256(&stream.out()).into()
 			}
 		}
 	}
@@ -387,92 +490,113 @@ impl CreateScheme {
 pub struct CreateTrapData {
 	pub scheme: CreateScheme,
 	pub value: U256,
-	pub code: Vec<u8>,
+	pub code: Shared<Vec<u8>>,
 }
 
 impl CreateTrapData {
-	pub fn new_create_from<S: AsRef<RuntimeState> + AsMut<RuntimeState>>(
-		machine: &mut Machine<S>,
+	/// Trap data of the given parameters, for `CREATE2` if `salt` is set.
+	fn new_from_params<S: AsRef<RuntimeState>, M: MemoryStorage>(
+		memory: &Memory<M>,
+		state: &S,
+		value: &H256,
+		code_offset: &H256,
+		code_len: &H256,
+		salt: Option<&H256>,
 	) -> Result<Self, ExitError> {
-		let stack = &mut machine.stack;
-		let memory = &mut machine.memory;
-		let state = &mut machine.state;
+		let value = h256_to_u256(*value);
+		let code_offset = h256_to_u256(*code_offset);
+		let code_len = h256_to_u256(*code_len);
 
-		stack.perform_pop3_push0(|value, code_offset, code_len| {
-			let value = h256_to_u256(*value);
-			let code_offset = h256_to_u256(*code_offset);
-			let code_len = h256_to_u256(*code_len);
-
-			let code_offset_len = if code_len == U256::zero() {
-				None
-			} else {
-				Some((u256_to_usize(code_offset)?, u256_to_usize(code_len)?))
-			};
+		let code_offset_len = if code_len == U256::zero() {
+			None
+		} else {
+			Some((u256_to_usize(code_offset)?, u256_to_usize(code_len)?))
+		};
 
-			let code = code_offset_len
+		let code = Shared::new(
+			code_offset_len
 				.map(|(code_offset, code_len)| memory.get(code_offset, code_len))
-				.unwrap_or(Vec::new());
-
-			let scheme = CreateScheme::Legacy {
-				caller: state.as_ref().context.address,
-			};
+				.unwrap_or(Vec::new()),
+		);
 
-			state.as_mut().retbuf = Vec::new();
+		let caller = state.as_ref().context.address;
+		let scheme = match salt {
+			Some(salt) => CreateScheme::Create2 {
+				caller,
+				salt: *salt,
+				code_hash: keccak256(&code[..]),
+			},
+			None => CreateScheme::Legacy { caller },
+		};
 
-			Ok((
-				(),
-				Self {
-					scheme,
-					value,
-					code,
-				},
-			))
+		Ok(Self {
+			scheme,
+			value,
+			code,
 		})
 	}
 
-	pub fn new_create2_from<S: AsRef<RuntimeState> + AsMut<RuntimeState>>(
-		machine: &mut Machine<S>,
+	pub fn new_create_from<S: AsRef<RuntimeState> + AsMut<RuntimeState>, M: MemoryStorage>(
+		machine: &mut Machine<S, M>,
 	) -> Result<Self, ExitError> {
 		let stack = &mut machine.stack;
 		let memory = &mut machine.memory;
 		let state = &mut machine.state;
 
-		stack.perform_pop4_push0(|value, code_offset, code_len, salt| {
-			let value = h256_to_u256(*value);
-			let code_offset = h256_to_u256(*code_offset);
-			let code_len = h256_to_u256(*code_len);
-
-			let code_offset_len = if code_len == U256::zero() {
-				None
-			} else {
-				Some((u256_to_usize(code_offset)?, u256_to_usize(code_len)?))
-			};
-
-			let code = code_offset_len
-				.map(|(code_offset, code_len)| memory.get(code_offset, code_len))
-				.unwrap_or(Vec::new());
-
-			let code_hash = H256::from_slice(Keccak256::digest(&code).as_slice());
+		stack.perform_pop3_push0(|value, code_offset, code_len| {
+			let trap = Self::new_from_params(memory, state, value, code_offset, code_len, None)?;
+			state.as_mut().retbuf = Vec::new();
+			Ok(((), trap))
+		})
+	}
 
-			let scheme = CreateScheme::Create2 {
-				caller: state.as_ref().context.address,
-				salt: *salt,
-				code_hash,
-			};
+	pub fn new_create2_from<S: AsRef<RuntimeState> + AsMut<RuntimeState>, M: MemoryStorage>(
+		machine: &mut Machine<S, M>,
+	) -> Result<Self, ExitError> {
+		let stack = &mut machine.stack;
+		let memory = &mut machine.memory;
+		let state = &mut machine.state;
 
+		stack.perform_pop4_push0(|value, code_offset, code_len, salt| {
+			let trap =
+				Self::new_from_params(memory, state, value, code_offset, code_len, Some(salt))?;
 			state.as_mut().retbuf = Vec::new();
-
-			Ok((
-				(),
-				Self {
-					scheme,
-					value,
-					code,
-				},
-			))
+			Ok(((), trap))
 		})
 	}
 
+	/// `CREATE` trap data, read from the stack and memory of the trapped
+	/// machine without consuming them.
+	pub fn peek_create_from<S: AsRef<RuntimeState>, M: MemoryStorage>(
+		machine: &Machine<S, M>,
+	) -> Result<Self, ExitError> {
+		let stack = &machine.stack;
+		Self::new_from_params(
+			&machine.memory,
+			&machine.state,
+			&stack.peek(0)?,
+			&stack.peek(1)?,
+			&stack.peek(2)?,
+			None,
+		)
+	}
+
+	/// `CREATE2` trap data, read from the stack and memory of the trapped
+	/// machine without consuming them.
+	pub fn peek_create2_from<S: AsRef<RuntimeState>, M: MemoryStorage>(
+		machine: &Machine<S, M>,
+	) -> Result<Self, ExitError> {
+		let stack = &machine.stack;
+		Self::new_from_params(
+			&machine.memory,
+			&machine.state,
+			&stack.peek(0)?,
+			&stack.peek(1)?,
+			&stack.peek(2)?,
+			Some(&stack.peek(3)?),
+		)
+	}
+
 	pub fn feedback<I: Interpreter>(
 		self,
 		reason: Result<H160, ExitError>,
@@ -499,6 +623,10 @@ impl CreateTrapData {
 				interpreter.machine_mut().stack.push(H256::default())?;
 				Err(e.into())
 			}
+			Err(ExitError::InstructionLimit) => {
+				interpreter.machine_mut().stack.push(H256::default())?;
+				Err(ExitError::InstructionLimit)
+			}
 		};
 
 		match ret {
@@ -1,12 +1,16 @@
+use alloc::{string::String, vec::Vec};
 use core::{
+	cell::Cell,
+	fmt::Write,
 	marker::PhantomData,
 	ops::{Deref, DerefMut},
 };
 
 use crate::{
-	error::{CallCreateTrap, ExitResult, TrapConstruct},
+	error::{CallCreateTrap, ExitError, ExitResult, TrapConstruct},
 	eval::*,
-	machine::Machine,
+	interpreter::Fused,
+	machine::{Machine, MemoryStorage, Pages},
 	opcode::Opcode,
 	runtime::{GasState, RuntimeBackend, RuntimeEnvironment, RuntimeState},
 };
@@ -15,47 +19,83 @@ pub trait EtableSet {
 	type State;
 	type Handle;
 	type Trap;
+	/// Storage of the memory of the machines, usually [Pages].
+	type Memory: MemoryStorage;
 
 	fn eval(
 		&self,
-		machine: &mut Machine<Self::State>,
+		machine: &mut Machine<Self::State, Self::Memory>,
 		handle: &mut Self::Handle,
 		opcode: Opcode,
 		position: usize,
 	) -> Control<Self::Trap>;
+
+	/// Evaluate a superinstruction at `position`, whose stack is
+	/// [Fused::fusable]. Returns `None` if the set does not support it, in
+	/// which case its opcodes are evaluated one by one.
+	///
+	/// Etables only support it if they are [Etable::is_standard], as a
+	/// superinstruction bypasses the entries of its opcodes.
+	fn eval_fused(
+		&self,
+		_machine: &mut Machine<Self::State, Self::Memory>,
+		_handle: &mut Self::Handle,
+		_fused: Fused,
+		_position: usize,
+	) -> Option<Control<Self::Trap>> {
+		None
+	}
 }
 
-impl<S, H, Tr, F> EtableSet for Etable<S, H, Tr, F>
+impl<S, H, Tr, M, F> EtableSet for Etable<S, H, Tr, M, F>
 where
-	F: Fn(&mut Machine<S>, &mut H, Opcode, usize) -> Control<Tr>,
+	M: MemoryStorage,
+	F: Fn(&mut Machine<S, M>, &mut H, Opcode, usize) -> Control<Tr>,
 {
 	type State = S;
 	type Handle = H;
 	type Trap = Tr;
+	type Memory = M;
 
 	fn eval(
 		&self,
-		machine: &mut Machine<S>,
+		machine: &mut Machine<S, M>,
 		handle: &mut H,
 		opcode: Opcode,
 		position: usize,
 	) -> Control<Tr> {
 		self[opcode.as_usize()](machine, handle, opcode, position)
 	}
+
+	fn eval_fused(
+		&self,
+		machine: &mut Machine<S, M>,
+		_handle: &mut H,
+		fused: Fused,
+		_position: usize,
+	) -> Option<Control<Tr>> {
+		if self.1 {
+			Some(fused.eval(machine))
+		} else {
+			None
+		}
+	}
 }
 
-impl<S, H, Tr, F1, F2> EtableSet for (Etable<S, H, Tr, F1>, Etable<S, H, Tr, F2>)
+impl<S, H, Tr, M, F1, F2> EtableSet for (Etable<S, H, Tr, M, F1>, Etable<S, H, Tr, M, F2>)
 where
-	F1: Fn(&mut Machine<S>, &mut H, Opcode, usize) -> Control<Tr>,
-	F2: Fn(&mut Machine<S>, &mut H, Opcode, usize) -> Control<Tr>,
+	M: MemoryStorage,
+	F1: Fn(&mut Machine<S, M>, &mut H, Opcode, usize) -> Control<Tr>,
+	F2: Fn(&mut Machine<S, M>, &mut H, Opcode, usize) -> Control<Tr>,
 {
 	type State = S;
 	type Handle = H;
 	type Trap = Tr;
+	type Memory = M;
 
 	fn eval(
 		&self,
-		machine: &mut Machine<S>,
+		machine: &mut Machine<S, M>,
 		handle: &mut H,
 		opcode: Opcode,
 		position: usize,
@@ -68,18 +108,161 @@ where
 
 		ret
 	}
+
+	fn eval_fused(
+		&self,
+		machine: &mut Machine<S, M>,
+		handle: &mut H,
+		fused: Fused,
+		position: usize,
+	) -> Option<Control<Tr>> {
+		// The entries of the first etable, usually the gasometer, are still
+		// evaluated for each opcode. Only the second one is bypassed.
+		if !self.1.is_standard() {
+			return None;
+		}
+
+		for (opcode, position) in fused.opcodes(position).into_iter().flatten() {
+			let ret = self.0[opcode.as_usize()](machine, handle, opcode, position);
+			if !matches!(ret, Control::Continue) {
+				return Some(ret);
+			}
+		}
+
+		Some(fused.eval(machine))
+	}
 }
 
-/// Evaluation function type.
-pub type Efn<S, H, Tr> = fn(&mut Machine<S>, &mut H, Opcode, usize) -> Control<Tr>;
+/// An [EtableSet] wrapper that counts executed opcodes, independent of gas.
+///
+/// If a limit is set, execution exits with [ExitError::InstructionLimit] once
+/// the limit is reached. All interpreters sharing the same wrapper share the
+/// count, so a wrapper created per transaction bounds the full call stack.
+pub struct InstructionCounter<ES> {
+	etable: ES,
+	limit: Option<u64>,
+	count: Cell<u64>,
+}
 
-/// The evaluation table for the EVM.
-pub struct Etable<S, H, Tr, F = Efn<S, H, Tr>>([F; 256], PhantomData<(S, H, Tr)>);
+impl<ES> InstructionCounter<ES> {
+	/// Wrap an etable, optionally limiting the number of executed opcodes.
+	pub const fn new(etable: ES, limit: Option<u64>) -> Self {
+		Self {
+			etable,
+			limit,
+			count: Cell::new(0),
+		}
+	}
+
+	/// Number of opcodes executed so far.
+	pub fn count(&self) -> u64 {
+		self.count.get()
+	}
+
+	/// Reset the count, usually before a new transaction.
+	pub fn reset(&self) {
+		self.count.set(0);
+	}
+
+	/// Get the inner etable.
+	pub fn inner(&self) -> &ES {
+		&self.etable
+	}
+}
+
+impl<ES: EtableSet> EtableSet for InstructionCounter<ES> {
+	type State = ES::State;
+	type Handle = ES::Handle;
+	type Trap = ES::Trap;
+	type Memory = ES::Memory;
+
+	fn eval(
+		&self,
+		machine: &mut Machine<Self::State, Self::Memory>,
+		handle: &mut Self::Handle,
+		opcode: Opcode,
+		position: usize,
+	) -> Control<Self::Trap> {
+		let count = self.count.get();
+		if self.limit.map_or(false, |limit| count >= limit) {
+			return Control::Exit(Err(ExitError::InstructionLimit));
+		}
+		self.count.set(count + 1);
+
+		self.etable.eval(machine, handle, opcode, position)
+	}
+}
+
+/// An [EtableSet] wrapper calling closures before and after each opcode
+/// with the machine, the opcode and its position, for instrumentation without
+/// building a custom etable.
+///
+/// Superinstructions are not evaluated through it, so the closures see every
+/// opcode.
+pub struct HookedEtable<ES, B, A> {
+	etable: ES,
+	before: B,
+	after: A,
+}
+
+impl<ES, B, A> HookedEtable<ES, B, A> {
+	/// Wrap an etable with closures called before and after each opcode.
+	pub const fn new(etable: ES, before: B, after: A) -> Self {
+		Self {
+			etable,
+			before,
+			after,
+		}
+	}
 
-unsafe impl<S, H, Tr, F: Send> Send for Etable<S, H, Tr, F> {}
-unsafe impl<S, H, Tr, F: Sync> Sync for Etable<S, H, Tr, F> {}
+	/// Get the inner etable.
+	pub fn inner(&self) -> &ES {
+		&self.etable
+	}
+}
 
-impl<S, H, Tr, F> Deref for Etable<S, H, Tr, F> {
+impl<ES, B, A> EtableSet for HookedEtable<ES, B, A>
+where
+	ES: EtableSet,
+	B: Fn(&Machine<ES::State, ES::Memory>, Opcode, usize),
+	A: Fn(&Machine<ES::State, ES::Memory>, Opcode, usize),
+{
+	type State = ES::State;
+	type Handle = ES::Handle;
+	type Trap = ES::Trap;
+	type Memory = ES::Memory;
+
+	fn eval(
+		&self,
+		machine: &mut Machine<Self::State, Self::Memory>,
+		handle: &mut Self::Handle,
+		opcode: Opcode,
+		position: usize,
+	) -> Control<Self::Trap> {
+		(self.before)(machine, opcode, position);
+		let ret = self.etable.eval(machine, handle, opcode, position);
+		(self.after)(machine, opcode, position);
+		ret
+	}
+}
+
+/// Evaluation function type.
+pub type Efn<S, H, Tr, M = Pages> = fn(&mut Machine<S, M>, &mut H, Opcode, usize) -> Control<Tr>;
+
+/// The evaluation table for the EVM.
+///
+/// The second field records whether the entries are the ones of
+/// [Etable::core] or [Etable::runtime], and were never modified since.
+pub struct Etable<S, H, Tr, M = Pages, F = Efn<S, H, Tr, M>>(
+	[F; 256],
+	bool,
+	PhantomData<(S, H, Tr, M)>,
+);
+
+unsafe impl<S, H, Tr, M, F: Send> Send for Etable<S, H, Tr, M, F> {}
+unsafe impl<S, H, Tr, M, F: Sync> Sync for Etable<S, H, Tr, M, F> {}
+
+impl<S, H, Tr, M, F> Deref for Etable<S, H, Tr, M, F> {
 	type Target = [F; 256];
 
 	fn deref(&self) -> &[F; 256] {
@@ -87,28 +270,36 @@ impl<S, H, Tr, F> Deref for Etable<S, H, Tr, F> {
 	}
 }
 
-impl<S, H, Tr, F> DerefMut for Etable<S, H, Tr, F> {
+impl<S, H, Tr, M, F> DerefMut for Etable<S, H, Tr, M, F> {
 	fn deref_mut(&mut self) -> &mut [F; 256] {
+		self.1 = false;
 		&mut self.0
 	}
 }
 
-impl<S, H, Tr, F> Etable<S, H, Tr, F>
+impl<S, H, Tr, M, F> Etable<S, H, Tr, M, F>
 where
-	F: Fn(&mut Machine<S>, &mut H, Opcode, usize) -> Control<Tr>,
+	F: Fn(&mut Machine<S, M>, &mut H, Opcode, usize) -> Control<Tr>,
 {
 	pub const fn single(f: F) -> Self
 	where
 		F: Copy,
 	{
-		Self([f; 256], PhantomData)
+		Self([f; 256], false, PhantomData)
+	}
+
+	/// Whether the entries are the standard ones of [Etable::core] or
+	/// [Etable::runtime]. Superinstructions are only evaluated with standard
+	/// etables (see [EtableSet::eval_fused]).
+	pub fn is_standard(&self) -> bool {
+		self.1
 	}
 
 	/// Wrap to create a new Etable.
-	pub fn wrap<FW, FR>(self, wrapper: FW) -> Etable<S, H, Tr, FR>
+	pub fn wrap<FW, FR>(self, wrapper: FW) -> Etable<S, H, Tr, M, FR>
 	where
 		FW: Fn(F, Opcode) -> FR,
-		FR: Fn(&mut Machine<S>, &mut H, Opcode, usize) -> Control<Tr>,
+		FR: Fn(&mut Machine<S, M>, &mut H, Opcode, usize) -> Control<Tr>,
 	{
 		let mut current_opcode = Opcode(0);
 		Etable(
@@ -119,20 +310,78 @@ where
 				}
 				fr
 			}),
+			false,
 			PhantomData,
 		)
 	}
 }
 
-impl<S, H, Tr> Etable<S, H, Tr> {
+impl<S, H, Tr, M: MemoryStorage> Etable<S, H, Tr, M> {
+	/// Opcodes with an entry other than [eval_unknown].
+	///
+	/// Entries are compared by function pointer. Identical functions may be
+	/// merged by the compiler, so this is meant for diagnostics only.
+	pub fn defined(&self) -> impl Iterator<Item = Opcode> + '_ {
+		let unknown = Self::none();
+		(0..=255u8)
+			.map(Opcode)
+			.filter(move |opcode| !unknown.same_entry(self, *opcode))
+	}
+
+	/// Opcodes whose entry differs from the one of `base`, such as custom
+	/// opcodes registered on top of [Etable::runtime].
+	///
+	/// Entries are compared by function pointer, as in [Etable::defined].
+	pub fn overridden<'a>(&'a self, base: &'a Self) -> impl Iterator<Item = Opcode> + 'a {
+		(0..=255u8)
+			.map(Opcode)
+			.filter(move |opcode| !base.same_entry(self, *opcode))
+	}
+
+	/// Human-readable table of named etables evaluated in order, such as
+	/// the gas and runtime etables of a chain. Each defined opcode is listed
+	/// with its mnemonic and the etables defining it.
+	///
+	/// Entries are compared by function pointer, as in [Etable::defined].
+	#[must_use]
+	pub fn describe(tables: &[(&str, &Self)]) -> String {
+		let unknown = Self::none();
+		let mut ret = String::new();
+
+		for opcode in (0..=255u8).map(Opcode) {
+			let names = tables
+				.iter()
+				.filter(|(_, table)| !unknown.same_entry(table, opcode))
+				.map(|(name, _)| *name)
+				.collect::<Vec<_>>();
+			if names.is_empty() {
+				continue;
+			}
+
+			let _ = writeln!(
+				ret,
+				"0x{:02x} {:<14} {}",
+				opcode.0,
+				opcode.name().unwrap_or("UNKNOWN"),
+				names.join(", ")
+			);
+		}
+
+		ret
+	}
+
+	fn same_entry(&self, other: &Self, opcode: Opcode) -> bool {
+		self[opcode.as_usize()] as usize == other[opcode.as_usize()] as usize
+	}
+
 	#[must_use]
 	pub const fn none() -> Self {
-		Self([eval_unknown as _; 256], PhantomData)
+		Self([eval_unknown as _; 256], false, PhantomData)
 	}
 
 	#[must_use]
 	pub const fn pass() -> Self {
-		Self([eval_pass as _; 256], PhantomData)
+		Self([eval_pass as _; 256], false, PhantomData)
 	}
 
 	/// Default core value for Etable.
@@ -168,6 +417,7 @@ impl<S, H, Tr> Etable<S, H, Tr> {
 		table[Opcode::SHL.as_usize()] = eval_shl as _;
 		table[Opcode::SHR.as_usize()] = eval_shr as _;
 		table[Opcode::SAR.as_usize()] = eval_sar as _;
+		table[Opcode::CLZ.as_usize()] = eval_clz as _;
 
 		table[Opcode::CALLDATALOAD.as_usize()] = eval_calldataload as _;
 		table[Opcode::CALLDATASIZE.as_usize()] = eval_calldatasize as _;
@@ -262,12 +512,15 @@ impl<S, H, Tr> Etable<S, H, Tr> {
 
 		table[Opcode::INVALID.as_usize()] = eval_invalid as _;
 
-		Self(table, PhantomData)
+		Self(table, true, PhantomData)
 	}
 }
 
-impl<S, H: RuntimeEnvironment + RuntimeBackend, Tr: TrapConstruct<CallCreateTrap>> Etable<S, H, Tr>
+impl<S, H, Tr, M> Etable<S, H, Tr, M>
 where
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr: TrapConstruct<CallCreateTrap>,
+	M: MemoryStorage,
 	S: AsRef<RuntimeState> + GasState,
 {
 	/// Runtime Etable.
@@ -299,6 +552,8 @@ where
 		table.0[Opcode::CHAINID.as_usize()] = eval_chainid as _;
 		table.0[Opcode::SELFBALANCE.as_usize()] = eval_selfbalance as _;
 		table.0[Opcode::BASEFEE.as_usize()] = eval_basefee as _;
+		table.0[Opcode::BLOBHASH.as_usize()] = eval_blobhash as _;
+		table.0[Opcode::BLOBBASEFEE.as_usize()] = eval_blobbasefee as _;
 
 		table.0[Opcode::SLOAD.as_usize()] = eval_sload as _;
 		table.0[Opcode::SSTORE.as_usize()] = eval_sstore as _;
@@ -329,6 +584,26 @@ where
 	}
 }
 
+#[cfg(feature = "eip3074")]
+impl<S, H, Tr, M> Etable<S, H, Tr, M>
+where
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr: TrapConstruct<CallCreateTrap>,
+	M: MemoryStorage,
+	S: AsRef<RuntimeState> + AsMut<RuntimeState> + GasState,
+{
+	/// Runtime Etable with `AUTH` and `AUTHCALL` (EIP-3074).
+	#[must_use]
+	pub const fn runtime_eip3074() -> Self {
+		let mut table = Self::runtime();
+
+		table.0[Opcode::AUTH.as_usize()] = eval_auth as _;
+		table.0[Opcode::AUTHCALL.as_usize()] = eval_authcall as _;
+
+		table
+	}
+}
+
 /// Control state.
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum Control<Trap> {
@@ -121,9 +121,24 @@ pub fn signextend(op1: U256, op2: U256) -> U256 {
 	}
 }
 
+/// Count the number of leading zero bits, as defined in EIP-7939. Returns 256 for zero.
+#[inline]
+pub fn clz(op1: U256) -> U256 {
+	U256::from(256 - op1.bits())
+}
+
 #[cfg(test)]
 mod tests {
-	use super::{signextend, U256};
+	use super::{clz, signextend, U256};
+
+	#[test]
+	fn test_clz() {
+		assert_eq!(clz(U256::zero()), U256::from(256));
+		assert_eq!(clz(U256::one()), U256::from(255));
+		assert_eq!(clz(U256::from(0xff)), U256::from(248));
+		assert_eq!(clz(U256::one() << 255), U256::zero());
+		assert_eq!(clz(U256::MAX), U256::zero());
+	}
 
 	/// Test to ensure new (optimized) `signextend` implementation is equivalent to the previous
 	/// implementation.
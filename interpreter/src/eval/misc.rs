@@ -5,12 +5,12 @@ use primitive_types::{H256, U256};
 use crate::{
 	error::{ExitError, ExitException, ExitFatal, ExitSucceed},
 	etable::Control,
-	machine::Machine,
+	machine::{Machine, MemoryStorage},
 	utils::u256_to_h256,
 };
 
 #[inline]
-pub fn codesize<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn codesize<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	let stack = &mut state.stack;
 	let code = &state.code;
 
@@ -24,7 +24,7 @@ pub fn codesize<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 }
 
 #[inline]
-pub fn codecopy<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn codecopy<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	pop_u256!(state, memory_offset, code_offset, len);
 
 	try_or_fail!(state.memory.resize_offset(memory_offset, len));
@@ -38,7 +38,7 @@ pub fn codecopy<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 }
 
 #[inline]
-pub fn calldataload<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn calldataload<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	pop_u256!(state, index);
 
 	let mut load = [0u8; 32];
@@ -59,14 +59,14 @@ pub fn calldataload<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 }
 
 #[inline]
-pub fn calldatasize<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn calldatasize<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	let len = U256::from(state.data.len());
 	push_u256!(state, len);
 	Control::Continue
 }
 
 #[inline]
-pub fn calldatacopy<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn calldatacopy<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	pop_u256!(state, memory_offset, data_offset, len);
 
 	try_or_fail!(state.memory.resize_offset(memory_offset, len));
@@ -84,13 +84,13 @@ pub fn calldatacopy<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 }
 
 #[inline]
-pub fn pop<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn pop<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	pop!(state, _val);
 	Control::Continue
 }
 
 #[inline]
-pub fn mload<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn mload<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	pop_u256!(state, index);
 	try_or_fail!(state.memory.resize_offset(index, U256::from(32)));
 	let index = as_usize_or_fail!(index);
@@ -101,7 +101,7 @@ pub fn mload<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 
 /// Support for EIP-5656: MCOPY instruction.
 #[inline]
-pub fn mcopy<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn mcopy<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	pop_u256!(state, dst, src, len);
 	try_or_fail!(state.memory.resize_offset(max(dst, src), len));
 
@@ -117,7 +117,7 @@ pub fn mcopy<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 }
 
 #[inline]
-pub fn mstore<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn mstore<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	pop_u256!(state, index);
 	pop!(state, value);
 	try_or_fail!(state.memory.resize_offset(index, U256::from(32)));
@@ -129,7 +129,7 @@ pub fn mstore<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 }
 
 #[inline]
-pub fn mstore8<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn mstore8<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	pop_u256!(state, index, value);
 	try_or_fail!(state.memory.resize_offset(index, U256::one()));
 	let index = as_usize_or_fail!(index);
@@ -141,7 +141,7 @@ pub fn mstore8<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 }
 
 #[inline]
-pub fn jump<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn jump<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	pop_u256!(state, dest);
 	let dest = as_usize_or_fail!(dest, ExitException::InvalidJump);
 
@@ -149,7 +149,7 @@ pub fn jump<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 }
 
 #[inline]
-pub fn jumpi<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn jumpi<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	pop_u256!(state, dest);
 	pop!(state, value);
 
@@ -162,19 +162,23 @@ pub fn jumpi<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
 }
 
 #[inline]
-pub fn pc<S, Tr>(state: &mut Machine<S>, position: usize) -> Control<Tr> {
+pub fn pc<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>, position: usize) -> Control<Tr> {
 	push_u256!(state, U256::from(position));
 	Control::Continue
 }
 
 #[inline]
-pub fn msize<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn msize<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	push_u256!(state, state.memory.effective_len());
 	Control::Continue
 }
 
 #[inline]
-pub fn push<S, Tr>(state: &mut Machine<S>, n: usize, position: usize) -> Control<Tr> {
+pub fn push<S, Tr, M: MemoryStorage>(
+	state: &mut Machine<S, M>,
+	n: usize,
+	position: usize,
+) -> Control<Tr> {
 	let end = min(position + 1 + n, state.code.len());
 	let slice = &state.code[(position + 1)..end];
 	let mut val = [0u8; 32];
@@ -186,7 +190,7 @@ pub fn push<S, Tr>(state: &mut Machine<S>, n: usize, position: usize) -> Control
 }
 
 #[inline]
-pub fn dup<S, Tr>(state: &mut Machine<S>, n: usize) -> Control<Tr> {
+pub fn dup<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>, n: usize) -> Control<Tr> {
 	let value = match state.stack.peek(n - 1) {
 		Ok(value) => value,
 		Err(e) => return Control::Exit(e.into()),
@@ -196,7 +200,7 @@ pub fn dup<S, Tr>(state: &mut Machine<S>, n: usize) -> Control<Tr> {
 }
 
 #[inline]
-pub fn swap<S, Tr>(state: &mut Machine<S>, n: usize) -> Control<Tr> {
+pub fn swap<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>, n: usize) -> Control<Tr> {
 	let val1 = match state.stack.peek(0) {
 		Ok(value) => value,
 		Err(e) => return Control::Exit(e.into()),
@@ -217,19 +221,17 @@ pub fn swap<S, Tr>(state: &mut Machine<S>, n: usize) -> Control<Tr> {
 }
 
 #[inline]
-pub fn ret<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn ret<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	pop_u256!(state, start, len);
 	try_or_fail!(state.memory.resize_offset(start, len));
-	state.memory.resize_to_range(start..(start + len));
-	state.memory.swap_and_clear(&mut state.retval);
+	state.retval = state.memory.take_range(start..(start + len));
 	Control::Exit(ExitSucceed::Returned.into())
 }
 
 #[inline]
-pub fn revert<S, Tr>(state: &mut Machine<S>) -> Control<Tr> {
+pub fn revert<S, Tr, M: MemoryStorage>(state: &mut Machine<S, M>) -> Control<Tr> {
 	pop_u256!(state, start, len);
 	try_or_fail!(state.memory.resize_offset(start, len));
-	state.memory.resize_to_range(start..(start + len));
-	state.memory.swap_and_clear(&mut state.retval);
+	state.retval = state.memory.take_range(start..(start + len));
 	Control::Exit(ExitError::Reverted.into())
 }
@@ -12,13 +12,13 @@ use primitive_types::{H256, U256};
 use crate::{
 	error::{CallCreateTrap, ExitException, ExitSucceed, TrapConstruct},
 	etable::Control,
-	machine::Machine,
+	machine::{Machine, MemoryStorage},
 	opcode::Opcode,
 	runtime::{GasState, RuntimeBackend, RuntimeEnvironment, RuntimeState},
 };
 
-pub fn eval_pass<S, H, Tr>(
-	_machine: &mut Machine<S>,
+pub fn eval_pass<S, H, Tr, M: MemoryStorage>(
+	_machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -26,8 +26,8 @@ pub fn eval_pass<S, H, Tr>(
 	Control::Continue
 }
 
-pub fn eval_stop<S, H, Tr>(
-	_machine: &mut Machine<S>,
+pub fn eval_stop<S, H, Tr, M: MemoryStorage>(
+	_machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -35,8 +35,8 @@ pub fn eval_stop<S, H, Tr>(
 	Control::Exit(ExitSucceed::Stopped.into())
 }
 
-pub fn eval_add<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_add<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -44,8 +44,8 @@ pub fn eval_add<S, H, Tr>(
 	op2_u256_tuple!(machine, overflowing_add)
 }
 
-pub fn eval_mul<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_mul<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -53,8 +53,8 @@ pub fn eval_mul<S, H, Tr>(
 	op2_u256_tuple!(machine, overflowing_mul)
 }
 
-pub fn eval_sub<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_sub<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -62,8 +62,8 @@ pub fn eval_sub<S, H, Tr>(
 	op2_u256_tuple!(machine, overflowing_sub)
 }
 
-pub fn eval_div<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_div<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -71,8 +71,8 @@ pub fn eval_div<S, H, Tr>(
 	op2_u256_fn!(machine, self::arithmetic::div)
 }
 
-pub fn eval_sdiv<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_sdiv<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -80,8 +80,8 @@ pub fn eval_sdiv<S, H, Tr>(
 	op2_u256_fn!(machine, self::arithmetic::sdiv)
 }
 
-pub fn eval_mod<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_mod<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -89,8 +89,8 @@ pub fn eval_mod<S, H, Tr>(
 	op2_u256_fn!(machine, self::arithmetic::rem)
 }
 
-pub fn eval_smod<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_smod<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -98,8 +98,8 @@ pub fn eval_smod<S, H, Tr>(
 	op2_u256_fn!(machine, self::arithmetic::srem)
 }
 
-pub fn eval_addmod<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_addmod<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -107,8 +107,8 @@ pub fn eval_addmod<S, H, Tr>(
 	op3_u256_fn!(machine, self::arithmetic::addmod)
 }
 
-pub fn eval_mulmod<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_mulmod<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -116,8 +116,8 @@ pub fn eval_mulmod<S, H, Tr>(
 	op3_u256_fn!(machine, self::arithmetic::mulmod)
 }
 
-pub fn eval_exp<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_exp<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -125,8 +125,8 @@ pub fn eval_exp<S, H, Tr>(
 	op2_u256_fn!(machine, self::arithmetic::exp)
 }
 
-pub fn eval_signextend<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_signextend<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -134,8 +134,8 @@ pub fn eval_signextend<S, H, Tr>(
 	op2_u256_fn!(machine, self::arithmetic::signextend)
 }
 
-pub fn eval_lt<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_lt<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -143,8 +143,8 @@ pub fn eval_lt<S, H, Tr>(
 	op2_u256_bool_ref!(machine, lt)
 }
 
-pub fn eval_gt<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_gt<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -152,8 +152,8 @@ pub fn eval_gt<S, H, Tr>(
 	op2_u256_bool_ref!(machine, gt)
 }
 
-pub fn eval_slt<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_slt<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -161,8 +161,8 @@ pub fn eval_slt<S, H, Tr>(
 	op2_u256_fn!(machine, self::bitwise::slt)
 }
 
-pub fn eval_sgt<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_sgt<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -170,8 +170,8 @@ pub fn eval_sgt<S, H, Tr>(
 	op2_u256_fn!(machine, self::bitwise::sgt)
 }
 
-pub fn eval_eq<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_eq<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -179,8 +179,8 @@ pub fn eval_eq<S, H, Tr>(
 	op2_u256_bool_ref!(machine, eq)
 }
 
-pub fn eval_iszero<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_iszero<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -188,8 +188,8 @@ pub fn eval_iszero<S, H, Tr>(
 	op1_u256_fn!(machine, self::bitwise::iszero)
 }
 
-pub fn eval_and<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_and<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -197,8 +197,8 @@ pub fn eval_and<S, H, Tr>(
 	op2_u256!(machine, bitand)
 }
 
-pub fn eval_or<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_or<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -206,8 +206,8 @@ pub fn eval_or<S, H, Tr>(
 	op2_u256!(machine, bitor)
 }
 
-pub fn eval_xor<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_xor<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -215,8 +215,8 @@ pub fn eval_xor<S, H, Tr>(
 	op2_u256!(machine, bitxor)
 }
 
-pub fn eval_not<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_not<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -224,8 +224,8 @@ pub fn eval_not<S, H, Tr>(
 	op1_u256_fn!(machine, self::bitwise::not)
 }
 
-pub fn eval_byte<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_byte<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -233,8 +233,8 @@ pub fn eval_byte<S, H, Tr>(
 	op2_u256_fn!(machine, self::bitwise::byte)
 }
 
-pub fn eval_shl<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_shl<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -242,8 +242,8 @@ pub fn eval_shl<S, H, Tr>(
 	op2_u256_fn!(machine, self::bitwise::shl)
 }
 
-pub fn eval_shr<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_shr<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -251,8 +251,8 @@ pub fn eval_shr<S, H, Tr>(
 	op2_u256_fn!(machine, self::bitwise::shr)
 }
 
-pub fn eval_sar<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_sar<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -260,8 +260,17 @@ pub fn eval_sar<S, H, Tr>(
 	op2_u256_fn!(machine, self::bitwise::sar)
 }
 
-pub fn eval_codesize<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_clz<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
+	_handle: &mut H,
+	_opcode: Opcode,
+	_position: usize,
+) -> Control<Tr> {
+	op1_u256_fn!(machine, self::arithmetic::clz)
+}
+
+pub fn eval_codesize<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -269,8 +278,8 @@ pub fn eval_codesize<S, H, Tr>(
 	self::misc::codesize(machine)
 }
 
-pub fn eval_codecopy<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_codecopy<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -278,8 +287,8 @@ pub fn eval_codecopy<S, H, Tr>(
 	self::misc::codecopy(machine)
 }
 
-pub fn eval_calldataload<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_calldataload<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -287,8 +296,8 @@ pub fn eval_calldataload<S, H, Tr>(
 	self::misc::calldataload(machine)
 }
 
-pub fn eval_calldatasize<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_calldatasize<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -296,8 +305,8 @@ pub fn eval_calldatasize<S, H, Tr>(
 	self::misc::calldatasize(machine)
 }
 
-pub fn eval_calldatacopy<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_calldatacopy<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -305,8 +314,8 @@ pub fn eval_calldatacopy<S, H, Tr>(
 	self::misc::calldatacopy(machine)
 }
 
-pub fn eval_pop<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_pop<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -314,8 +323,8 @@ pub fn eval_pop<S, H, Tr>(
 	self::misc::pop(machine)
 }
 
-pub fn eval_mload<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_mload<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -323,8 +332,8 @@ pub fn eval_mload<S, H, Tr>(
 	self::misc::mload(machine)
 }
 
-pub fn eval_mstore<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_mstore<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -332,8 +341,8 @@ pub fn eval_mstore<S, H, Tr>(
 	self::misc::mstore(machine)
 }
 
-pub fn eval_mstore8<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_mstore8<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -341,8 +350,8 @@ pub fn eval_mstore8<S, H, Tr>(
 	self::misc::mstore8(machine)
 }
 
-pub fn eval_jump<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_jump<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -350,8 +359,8 @@ pub fn eval_jump<S, H, Tr>(
 	self::misc::jump(machine)
 }
 
-pub fn eval_jumpi<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_jumpi<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -359,8 +368,8 @@ pub fn eval_jumpi<S, H, Tr>(
 	self::misc::jumpi(machine)
 }
 
-pub fn eval_pc<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_pc<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	position: usize,
@@ -368,8 +377,8 @@ pub fn eval_pc<S, H, Tr>(
 	self::misc::pc(machine, position)
 }
 
-pub fn eval_msize<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_msize<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -377,8 +386,8 @@ pub fn eval_msize<S, H, Tr>(
 	self::misc::msize(machine)
 }
 
-pub fn eval_jumpdest<S, H, Tr>(
-	_machine: &mut Machine<S>,
+pub fn eval_jumpdest<S, H, Tr, M: MemoryStorage>(
+	_machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -386,8 +395,8 @@ pub fn eval_jumpdest<S, H, Tr>(
 	Control::Continue
 }
 
-pub fn eval_mcopy<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_mcopy<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -398,8 +407,8 @@ pub fn eval_mcopy<S, H, Tr>(
 macro_rules! eval_push {
     ($($num:expr),*) => {
 		$(paste::paste! {
-			pub fn [<eval_push $num>]<S, H, Tr>(
-				machine: &mut Machine<S>,
+			pub fn [<eval_push $num>]<S, H, Tr, M: MemoryStorage>(
+				machine: &mut Machine<S, M>,
 				_handle: &mut H,
 				_opcode: Opcode,
 				position: usize,
@@ -418,8 +427,8 @@ eval_push! {
 macro_rules! eval_dup {
     ($($num:expr),*) => {
 		$(paste::paste! {
-			pub fn [<eval_dup $num>]<S, H, Tr>(
-				machine: &mut Machine<S>,
+			pub fn [<eval_dup $num>]<S, H, Tr, M: MemoryStorage>(
+				machine: &mut Machine<S, M>,
 				_handle: &mut H,
 				_opcode: Opcode,
 				_position: usize,
@@ -435,8 +444,8 @@ eval_dup! { 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16 }
 macro_rules! eval_swap {
     ($($num:expr),*) => {
 		$(paste::paste! {
-			pub fn [<eval_swap $num>]<S, H, Tr>(
-				machine: &mut Machine<S>,
+			pub fn [<eval_swap $num>]<S, H, Tr, M: MemoryStorage>(
+				machine: &mut Machine<S, M>,
 				_handle: &mut H,
 				_opcode: Opcode,
 				_position: usize,
@@ -449,8 +458,8 @@ macro_rules! eval_swap {
 
 eval_swap! { 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16 }
 
-pub fn eval_return<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_return<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -458,8 +467,8 @@ pub fn eval_return<S, H, Tr>(
 	self::misc::ret(machine)
 }
 
-pub fn eval_revert<S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_revert<S, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -467,8 +476,8 @@ pub fn eval_revert<S, H, Tr>(
 	self::misc::revert(machine)
 }
 
-pub fn eval_invalid<S, H, Tr>(
-	_machine: &mut Machine<S>,
+pub fn eval_invalid<S, H, Tr, M: MemoryStorage>(
+	_machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -476,8 +485,8 @@ pub fn eval_invalid<S, H, Tr>(
 	Control::Exit(ExitException::DesignatedInvalid.into())
 }
 
-pub fn eval_unknown<S, H, Tr>(
-	_machine: &mut Machine<S>,
+pub fn eval_unknown<S, H, Tr, M: MemoryStorage>(
+	_machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	opcode: Opcode,
 	_position: usize,
@@ -485,8 +494,13 @@ pub fn eval_unknown<S, H, Tr>(
 	Control::Exit(ExitException::InvalidOpcode(opcode).into())
 }
 
-pub fn eval_sha3<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_sha3<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -494,8 +508,13 @@ pub fn eval_sha3<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend,
 	self::system::sha3(machine)
 }
 
-pub fn eval_address<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_address<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -503,8 +522,13 @@ pub fn eval_address<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacke
 	self::system::address(machine)
 }
 
-pub fn eval_balance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_balance<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -512,8 +536,13 @@ pub fn eval_balance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacke
 	self::system::balance(machine, handle)
 }
 
-pub fn eval_selfbalance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_selfbalance<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -521,8 +550,13 @@ pub fn eval_selfbalance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeB
 	self::system::selfbalance(machine, handle)
 }
 
-pub fn eval_origin<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_origin<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -530,8 +564,13 @@ pub fn eval_origin<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	self::system::origin(machine, handle)
 }
 
-pub fn eval_caller<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_caller<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -539,8 +578,13 @@ pub fn eval_caller<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	self::system::caller(machine)
 }
 
-pub fn eval_callvalue<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_callvalue<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -548,8 +592,13 @@ pub fn eval_callvalue<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBac
 	self::system::callvalue(machine)
 }
 
-pub fn eval_gasprice<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_gasprice<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -557,8 +606,13 @@ pub fn eval_gasprice<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBack
 	self::system::gasprice(machine, handle)
 }
 
-pub fn eval_extcodesize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_extcodesize<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -566,8 +620,13 @@ pub fn eval_extcodesize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeB
 	self::system::extcodesize(machine, handle)
 }
 
-pub fn eval_extcodehash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_extcodehash<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -575,8 +634,13 @@ pub fn eval_extcodehash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeB
 	self::system::extcodehash(machine, handle)
 }
 
-pub fn eval_extcodecopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_extcodecopy<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -584,8 +648,13 @@ pub fn eval_extcodecopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeB
 	self::system::extcodecopy(machine, handle)
 }
 
-pub fn eval_returndatasize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_returndatasize<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -593,8 +662,13 @@ pub fn eval_returndatasize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + Runti
 	self::system::returndatasize(machine)
 }
 
-pub fn eval_returndatacopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_returndatacopy<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -602,8 +676,13 @@ pub fn eval_returndatacopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + Runti
 	self::system::returndatacopy(machine)
 }
 
-pub fn eval_blockhash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_blockhash<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -611,8 +690,13 @@ pub fn eval_blockhash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBac
 	self::system::blockhash(machine, handle)
 }
 
-pub fn eval_coinbase<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_coinbase<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -620,8 +704,13 @@ pub fn eval_coinbase<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBack
 	self::system::coinbase(machine, handle)
 }
 
-pub fn eval_timestamp<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_timestamp<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -629,8 +718,13 @@ pub fn eval_timestamp<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBac
 	self::system::timestamp(machine, handle)
 }
 
-pub fn eval_number<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_number<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -638,8 +732,13 @@ pub fn eval_number<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	self::system::number(machine, handle)
 }
 
-pub fn eval_difficulty<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_difficulty<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -647,8 +746,13 @@ pub fn eval_difficulty<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBa
 	self::system::prevrandao(machine, handle)
 }
 
-pub fn eval_gaslimit<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_gaslimit<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -656,8 +760,13 @@ pub fn eval_gaslimit<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBack
 	self::system::gaslimit(machine, handle)
 }
 
-pub fn eval_sload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_sload<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -665,8 +774,13 @@ pub fn eval_sload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend
 	self::system::sload(machine, handle)
 }
 
-pub fn eval_sstore<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_sstore<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -674,8 +788,8 @@ pub fn eval_sstore<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	self::system::sstore(machine, handle)
 }
 
-pub fn eval_gas<S: GasState, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_gas<S: GasState, H: RuntimeEnvironment + RuntimeBackend, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -683,8 +797,13 @@ pub fn eval_gas<S: GasState, H: RuntimeEnvironment + RuntimeBackend, Tr>(
 	self::system::gas(machine, handle)
 }
 
-pub fn eval_tload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_tload<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -692,8 +811,13 @@ pub fn eval_tload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend
 	self::system::tload(machine, handle)
 }
 
-pub fn eval_tstore<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_tstore<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -704,8 +828,8 @@ pub fn eval_tstore<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 macro_rules! eval_log {
     ($($num:expr),*) => {
 		$(paste::paste! {
-			pub fn [<eval_log $num>]<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-				machine: &mut Machine<S>,
+			pub fn [<eval_log $num>]<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr, M: MemoryStorage>(
+				machine: &mut Machine<S, M>,
 				handle: &mut H,
 				_opcode: Opcode,
 				_position: usize,
@@ -718,8 +842,13 @@ macro_rules! eval_log {
 
 eval_log! { 0, 1, 2, 3, 4 }
 
-pub fn eval_suicide<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_suicide<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -727,8 +856,13 @@ pub fn eval_suicide<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacke
 	self::system::suicide(machine, handle)
 }
 
-pub fn eval_chainid<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_chainid<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -736,8 +870,13 @@ pub fn eval_chainid<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacke
 	self::system::chainid(machine, handle)
 }
 
-pub fn eval_basefee<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval_basefee<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handle: &mut H,
 	_opcode: Opcode,
 	_position: usize,
@@ -745,8 +884,60 @@ pub fn eval_basefee<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacke
 	self::system::basefee(machine, handle)
 }
 
-pub fn eval_call_create_trap<S, H, Tr: TrapConstruct<CallCreateTrap>>(
-	_machine: &mut Machine<S>,
+pub fn eval_blobhash<S: AsRef<RuntimeState>, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
+	_handle: &mut H,
+	_opcode: Opcode,
+	_position: usize,
+) -> Control<Tr> {
+	self::system::blobhash(machine)
+}
+
+pub fn eval_blobbasefee<S: AsRef<RuntimeState>, H, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
+	_handle: &mut H,
+	_opcode: Opcode,
+	_position: usize,
+) -> Control<Tr> {
+	self::system::blobbasefee(machine)
+}
+
+#[cfg(feature = "eip3074")]
+pub fn eval_auth<
+	S: AsRef<RuntimeState> + AsMut<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
+	handle: &mut H,
+	_opcode: Opcode,
+	_position: usize,
+) -> Control<Tr> {
+	self::system::auth(machine, handle)
+}
+
+#[cfg(feature = "eip3074")]
+pub fn eval_authcall<
+	S: AsRef<RuntimeState>,
+	H,
+	Tr: TrapConstruct<CallCreateTrap>,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
+	_handle: &mut H,
+	_opcode: Opcode,
+	_position: usize,
+) -> Control<Tr> {
+	if machine.state.as_ref().authorized.is_none() {
+		return Control::Exit(Err(ExitException::Unauthorized.into()));
+	}
+
+	Control::Trap(Tr::construct(CallCreateTrap::AuthCall))
+}
+
+pub fn eval_call_create_trap<S, H, Tr: TrapConstruct<CallCreateTrap>, M: MemoryStorage>(
+	_machine: &mut Machine<S, M>,
 	_handle: &mut H,
 	opcode: Opcode,
 	_position: usize,
@@ -1,16 +1,18 @@
 use alloc::vec::Vec;
 
 use primitive_types::{H256, U256};
-use sha3::{Digest, Keccak256};
 
 use crate::{
 	error::{ExitException, ExitFatal, ExitSucceed},
 	etable::Control,
-	machine::Machine,
+	machine::{Machine, MemoryStorage},
 	runtime::{GasState, Log, RuntimeBackend, RuntimeEnvironment, RuntimeState, Transfer},
+	utils::keccak256,
 };
 
-pub fn sha3<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> Control<Tr> {
+pub fn sha3<S: AsRef<RuntimeState>, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
+) -> Control<Tr> {
 	pop_u256!(machine, from, len);
 
 	try_or_fail!(machine.memory.resize_offset(from, len));
@@ -23,14 +25,19 @@ pub fn sha3<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> Control<Tr>
 		machine.memory.get(from, len)
 	};
 
-	let ret = Keccak256::digest(data.as_slice());
-	push!(machine, H256::from_slice(ret.as_slice()));
+	let ret = keccak256(data.as_slice());
+	push!(machine, ret);
 
 	Control::Continue
 }
 
-pub fn chainid<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn chainid<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &H,
 ) -> Control<Tr> {
 	push_u256!(machine, handler.chain_id());
@@ -38,15 +45,22 @@ pub fn chainid<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, T
 	Control::Continue
 }
 
-pub fn address<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> Control<Tr> {
+pub fn address<S: AsRef<RuntimeState>, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
+) -> Control<Tr> {
 	let ret = H256::from(machine.state.as_ref().context.address);
 	push!(machine, ret);
 
 	Control::Continue
 }
 
-pub fn balance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn balance<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &mut H,
 ) -> Control<Tr> {
 	pop!(machine, address);
@@ -55,8 +69,13 @@ pub fn balance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, T
 	Control::Continue
 }
 
-pub fn selfbalance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn selfbalance<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &H,
 ) -> Control<Tr> {
 	push_u256!(
@@ -67,8 +86,13 @@ pub fn selfbalance<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	Control::Continue
 }
 
-pub fn origin<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn origin<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	_handler: &H,
 ) -> Control<Tr> {
 	let ret = H256::from(machine.state.as_ref().transaction_context.origin);
@@ -77,14 +101,18 @@ pub fn origin<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr
 	Control::Continue
 }
 
-pub fn caller<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> Control<Tr> {
+pub fn caller<S: AsRef<RuntimeState>, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
+) -> Control<Tr> {
 	let ret = H256::from(machine.state.as_ref().context.caller);
 	push!(machine, ret);
 
 	Control::Continue
 }
 
-pub fn callvalue<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> Control<Tr> {
+pub fn callvalue<S: AsRef<RuntimeState>, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
+) -> Control<Tr> {
 	let mut ret = H256::default();
 	machine
 		.state
@@ -97,8 +125,13 @@ pub fn callvalue<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> Contro
 	Control::Continue
 }
 
-pub fn gasprice<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn gasprice<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	_handler: &H,
 ) -> Control<Tr> {
 	let mut ret = H256::default();
@@ -113,8 +146,13 @@ pub fn gasprice<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend,
 	Control::Continue
 }
 
-pub fn basefee<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn basefee<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &H,
 ) -> Control<Tr> {
 	let mut ret = H256::default();
@@ -124,8 +162,43 @@ pub fn basefee<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, T
 	Control::Continue
 }
 
-pub fn extcodesize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn blobhash<S: AsRef<RuntimeState>, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
+) -> Control<Tr> {
+	pop_u256!(machine, index);
+	let blob_hashes = &machine.state.as_ref().transaction_context.blob_hashes;
+	let ret = if index < U256::from(blob_hashes.len()) {
+		blob_hashes[index.as_usize()]
+	} else {
+		H256::default()
+	};
+	push!(machine, ret);
+
+	Control::Continue
+}
+
+pub fn blobbasefee<S: AsRef<RuntimeState>, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
+) -> Control<Tr> {
+	let mut ret = H256::default();
+	machine
+		.state
+		.as_ref()
+		.transaction_context
+		.blob_base_fee
+		.to_big_endian(&mut ret[..]);
+	push!(machine, ret);
+
+	Control::Continue
+}
+
+pub fn extcodesize<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &mut H,
 ) -> Control<Tr> {
 	pop!(machine, address);
@@ -135,8 +208,13 @@ pub fn extcodesize<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	Control::Continue
 }
 
-pub fn extcodehash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn extcodehash<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &mut H,
 ) -> Control<Tr> {
 	pop!(machine, address);
@@ -146,8 +224,13 @@ pub fn extcodehash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	Control::Continue
 }
 
-pub fn extcodecopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn extcodecopy<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &mut H,
 ) -> Control<Tr> {
 	pop!(machine, address);
@@ -166,14 +249,18 @@ pub fn extcodecopy<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBacken
 	Control::Continue
 }
 
-pub fn returndatasize<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> Control<Tr> {
+pub fn returndatasize<S: AsRef<RuntimeState>, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
+) -> Control<Tr> {
 	let size = U256::from(machine.state.as_ref().retbuf.len());
 	push_u256!(machine, size);
 
 	Control::Continue
 }
 
-pub fn returndatacopy<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> Control<Tr> {
+pub fn returndatacopy<S: AsRef<RuntimeState>, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
+) -> Control<Tr> {
 	pop_u256!(machine, memory_offset, data_offset, len);
 
 	try_or_fail!(machine.memory.resize_offset(memory_offset, len));
@@ -194,8 +281,13 @@ pub fn returndatacopy<S: AsRef<RuntimeState>, Tr>(machine: &mut Machine<S>) -> C
 	}
 }
 
-pub fn blockhash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn blockhash<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &H,
 ) -> Control<Tr> {
 	pop_u256!(machine, number);
@@ -204,40 +296,65 @@ pub fn blockhash<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend,
 	Control::Continue
 }
 
-pub fn coinbase<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn coinbase<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &H,
 ) -> Control<Tr> {
 	push!(machine, handler.block_coinbase().into());
 	Control::Continue
 }
 
-pub fn timestamp<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn timestamp<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &H,
 ) -> Control<Tr> {
 	push_u256!(machine, handler.block_timestamp());
 	Control::Continue
 }
 
-pub fn number<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn number<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &H,
 ) -> Control<Tr> {
 	push_u256!(machine, handler.block_number());
 	Control::Continue
 }
 
-pub fn difficulty<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn difficulty<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &H,
 ) -> Control<Tr> {
 	push_u256!(machine, handler.block_difficulty());
 	Control::Continue
 }
 
-pub fn prevrandao<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn prevrandao<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &H,
 ) -> Control<Tr> {
 	if let Some(rand) = handler.block_randomness() {
@@ -248,16 +365,26 @@ pub fn prevrandao<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend
 	}
 }
 
-pub fn gaslimit<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn gaslimit<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &H,
 ) -> Control<Tr> {
 	push_u256!(machine, handler.block_gas_limit());
 	Control::Continue
 }
 
-pub fn sload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn sload<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &mut H,
 ) -> Control<Tr> {
 	pop!(machine, index);
@@ -267,8 +394,13 @@ pub fn sload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>
 	Control::Continue
 }
 
-pub fn sstore<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn sstore<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &mut H,
 ) -> Control<Tr> {
 	pop!(machine, index, value);
@@ -279,8 +411,8 @@ pub fn sstore<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr
 	}
 }
 
-pub fn gas<S: GasState, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn gas<S: GasState, H: RuntimeEnvironment + RuntimeBackend, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	_handler: &H,
 ) -> Control<Tr> {
 	push_u256!(machine, machine.state.gas());
@@ -288,8 +420,13 @@ pub fn gas<S: GasState, H: RuntimeEnvironment + RuntimeBackend, Tr>(
 	Control::Continue
 }
 
-pub fn tload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn tload<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &mut H,
 ) -> Control<Tr> {
 	pop!(machine, index);
@@ -299,8 +436,13 @@ pub fn tload<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>
 	Control::Continue
 }
 
-pub fn tstore<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn tstore<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &mut H,
 ) -> Control<Tr> {
 	pop!(machine, index, value);
@@ -310,8 +452,8 @@ pub fn tstore<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr
 	}
 }
 
-pub fn log<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn log<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	n: u8,
 	handler: &mut H,
 ) -> Control<Tr> {
@@ -347,11 +489,21 @@ pub fn log<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
 	}
 }
 
-pub fn suicide<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, Tr>(
-	machine: &mut Machine<S>,
+pub fn suicide<
+	S: AsRef<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
 	handler: &mut H,
 ) -> Control<Tr> {
 	let address = machine.state.as_ref().context.address;
+	let only_created = machine
+		.state
+		.as_ref()
+		.transaction_context
+		.selfdestruct_only_created;
 
 	match machine.stack.perform_pop1_push0(|target| {
 		let balance = handler.balance(address);
@@ -362,8 +514,12 @@ pub fn suicide<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, T
 			value: balance,
 		})?;
 
-		handler.mark_delete(address);
-		handler.reset_balance(address);
+		// With EIP-6780, an account created before the transaction is not
+		// deleted, and keeps any balance it sent to itself.
+		if !only_created || handler.created_in_transaction(address) {
+			handler.mark_delete(address);
+			handler.reset_balance(address);
+		}
 
 		Ok(((), ()))
 	}) {
@@ -371,3 +527,88 @@ pub fn suicide<S: AsRef<RuntimeState>, H: RuntimeEnvironment + RuntimeBackend, T
 		Err(e) => Control::Exit(Err(e)),
 	}
 }
+
+/// Magic byte prefixed to the `AUTH` message.
+#[cfg(feature = "eip3074")]
+const AUTH_MAGIC: u8 = 0x04;
+
+/// Length of the `AUTH` signature and commit in memory, `yParity || r || s || commit`.
+#[cfg(feature = "eip3074")]
+const AUTH_DATA_LEN: usize = 97;
+
+#[cfg(feature = "eip3074")]
+pub fn auth<
+	S: AsRef<RuntimeState> + AsMut<RuntimeState>,
+	H: RuntimeEnvironment + RuntimeBackend,
+	Tr,
+	M: MemoryStorage,
+>(
+	machine: &mut Machine<S, M>,
+	handler: &mut H,
+) -> Control<Tr> {
+	use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+	use primitive_types::H160;
+
+	pop!(machine, authority);
+	pop_u256!(machine, offset, len);
+
+	try_or_fail!(machine.memory.resize_offset(offset, len));
+	let data = if len == U256::zero() {
+		Vec::new()
+	} else {
+		let offset = as_usize_or_fail!(offset);
+		let len = as_usize_or_fail!(len);
+
+		machine
+			.memory
+			.get(offset, core::cmp::min(len, AUTH_DATA_LEN))
+	};
+	let mut input = [0u8; AUTH_DATA_LEN];
+	input[..data.len()].copy_from_slice(&data);
+
+	let authority = H160::from(authority);
+	let invoker = machine.state.as_ref().context.address;
+
+	let recover = || -> Option<H160> {
+		let recid = RecoveryId::from_byte(input[0]).filter(|_| input[0] <= 1)?;
+		let sig = Signature::from_slice(&input[1..65]).ok()?;
+		if sig.normalize_s().is_some() {
+			// Signatures with high `s` are malleable and rejected.
+			return None;
+		}
+
+		let mut message = [0u8; 129];
+		message[0] = AUTH_MAGIC;
+		handler.chain_id().to_big_endian(&mut message[1..33]);
+		handler.nonce(authority).to_big_endian(&mut message[33..65]);
+		message[77..97].copy_from_slice(&invoker[..]);
+		message[97..129].copy_from_slice(&input[65..97]);
+		let message = keccak256(&message[..]);
+
+		let pubkey = VerifyingKey::recover_from_prehash(&message[..], &sig, recid).ok()?;
+		let pubkey = pubkey.to_encoded_point(false);
+		let hash = keccak256(&pubkey.as_bytes()[1..]);
+		Some(H160::from_slice(&hash[12..]))
+	};
+
+	let authorized = match recover() {
+		Some(recovered)
+			if recovered == authority && handler.code_size(authority) == U256::zero() =>
+		{
+			Some(authority)
+		}
+		_ => None,
+	};
+	machine.state.as_mut().authorized = authorized;
+
+	push_u256!(
+		machine,
+		if authorized.is_some() {
+			U256::one()
+		} else {
+			U256::zero()
+		}
+	);
+
+	Control::Continue
+}
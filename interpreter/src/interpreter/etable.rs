@@ -1,31 +1,32 @@
-use alloc::vec::Vec;
+use alloc::{sync::Arc, vec::Vec};
 use core::ops::{Deref, DerefMut};
 
 use crate::{
 	error::{Capture, ExitError, ExitException, ExitFatal, ExitResult, ExitSucceed},
 	etable::{Control, EtableSet},
-	interpreter::{valids::Valids, Interpreter, RunInterpreter, StepInterpreter},
+	interpreter::{fusion::Fusions, valids::Valids, Interpreter, RunInterpreter, StepInterpreter},
 	machine::{Machine, Stack},
 	opcode::Opcode,
 };
 
 pub struct EtableInterpreter<'etable, ES: EtableSet> {
-	valids: Valids,
+	valids: Arc<Valids>,
+	fusions: Option<Arc<Fusions>>,
 	position: usize,
-	machine: Machine<ES::State>,
+	machine: Machine<ES::State, ES::Memory>,
 	etable: &'etable ES,
 }
 
 impl<'etable, ES: EtableSet> Deref for EtableInterpreter<'etable, ES> {
-	type Target = Machine<ES::State>;
+	type Target = Machine<ES::State, ES::Memory>;
 
-	fn deref(&self) -> &Machine<ES::State> {
+	fn deref(&self) -> &Machine<ES::State, ES::Memory> {
 		&self.machine
 	}
 }
 
 impl<'etable, ES: EtableSet> DerefMut for EtableInterpreter<'etable, ES> {
-	fn deref_mut(&mut self) -> &mut Machine<ES::State> {
+	fn deref_mut(&mut self) -> &mut Machine<ES::State, ES::Memory> {
 		&mut self.machine
 	}
 }
@@ -39,18 +40,51 @@ where
 		self.position
 	}
 
-	pub fn new(machine: Machine<ES::State>, etable: &'etable ES) -> Self {
-		let valids = Valids::new(&machine.code[..]);
+	pub fn new(machine: Machine<ES::State, ES::Memory>, etable: &'etable ES) -> Self {
+		let valids = Arc::new(Valids::new(&machine.code[..]));
 
+		Self::new_with_valids(machine, etable, valids)
+	}
+
+	/// Create a new interpreter with an already analyzed valid jump destination mapping. The
+	/// mapping must be the one of the machine's code.
+	pub fn new_with_valids(
+		machine: Machine<ES::State, ES::Memory>,
+		etable: &'etable ES,
+		valids: Arc<Valids>,
+	) -> Self {
 		Self {
 			machine,
 			valids,
+			fusions: None,
 			position: 0,
 			etable,
 		}
 	}
 
-	pub fn deconstruct(self) -> Machine<ES::State> {
+	/// Evaluate common sequences of opcodes as superinstructions (see
+	/// [crate::Fused]) when running the machine. Stepping still evaluates
+	/// opcodes one by one.
+	///
+	/// Superinstructions are evaluated with the core semantics of their
+	/// opcodes, so etables that are not [crate::etable::Etable::is_standard]
+	/// keep evaluating them one by one.
+	#[must_use]
+	pub fn with_fusion(self) -> Self {
+		let fusions = Arc::new(Fusions::new(&self.machine.code[..]));
+
+		self.with_fusions(fusions)
+	}
+
+	/// Evaluate superinstructions as in [Self::with_fusion], with already
+	/// found ones. They must be the ones of the machine's code.
+	#[must_use]
+	pub fn with_fusions(mut self, fusions: Arc<Fusions>) -> Self {
+		self.fusions = Some(fusions);
+		self
+	}
+
+	pub fn deconstruct(self) -> Machine<ES::State, ES::Memory> {
 		self.machine
 	}
 
@@ -85,19 +119,44 @@ where
 	pub fn peek_opcode(&self) -> Option<Opcode> {
 		self.code.get(self.position).map(|opcode| Opcode(*opcode))
 	}
+
+	/// Run the machine for at most `budget` opcodes. Returns `None` if the
+	/// budget ran out before the machine exited or trapped, in which case
+	/// it can be resumed by running it again.
+	///
+	/// Opcodes are evaluated one by one, so the number of opcodes run for a
+	/// budget is deterministic, with or without fusion.
+	pub fn run_budget(
+		&mut self,
+		handle: &mut ES::Handle,
+		budget: usize,
+	) -> Option<Capture<ExitResult, ES::Trap>> {
+		for _ in 0..budget {
+			if let Err(res) = self.step_inner(handle, false) {
+				return Some(res);
+			}
+		}
+
+		None
+	}
 }
 
 impl<'etable, ES: EtableSet> Interpreter for EtableInterpreter<'etable, ES> {
 	type State = ES::State;
+	type Memory = ES::Memory;
 
-	fn machine(&self) -> &Machine<ES::State> {
+	fn machine(&self) -> &Machine<ES::State, ES::Memory> {
 		&self.machine
 	}
 
-	fn machine_mut(&mut self) -> &mut Machine<ES::State> {
+	fn machine_mut(&mut self) -> &mut Machine<ES::State, ES::Memory> {
 		&mut self.machine
 	}
 
+	fn position(&self) -> usize {
+		self.position
+	}
+
 	fn deconstruct(self) -> (ES::State, Vec<u8>) {
 		(self.machine.state, self.machine.retval)
 	}
@@ -117,7 +176,7 @@ where
 {
 	fn run(&mut self, handle: &mut H) -> Capture<ExitResult, Tr> {
 		loop {
-			match self.step(handle) {
+			match self.step_inner(handle, true) {
 				Ok(()) => (),
 				Err(res) => return res,
 			}
@@ -131,6 +190,44 @@ where
 {
 	#[inline]
 	fn step(&mut self, handle: &mut H) -> Result<(), Capture<ExitResult, Tr>> {
+		self.step_inner(handle, false)
+	}
+}
+
+impl<'etable, ES: EtableSet> EtableInterpreter<'etable, ES> {
+	/// Evaluate the opcode at `position`, or the superinstruction starting
+	/// there if `fuse` is set.
+	#[inline]
+	fn eval(
+		&mut self,
+		handle: &mut ES::Handle,
+		opcode: Opcode,
+		position: usize,
+		fuse: bool,
+	) -> Control<ES::Trap> {
+		if fuse {
+			if let Some(fused) = self.fusions.as_ref().and_then(|f| f.get(position)) {
+				if fused.fusable(&self.machine.stack) {
+					if let Some(control) =
+						self.etable
+							.eval_fused(&mut self.machine, handle, fused, position)
+					{
+						return control;
+					}
+				}
+			}
+		}
+
+		self.etable
+			.eval(&mut self.machine, handle, opcode, position)
+	}
+
+	#[inline]
+	fn step_inner(
+		&mut self,
+		handle: &mut ES::Handle,
+		fuse: bool,
+	) -> Result<(), Capture<ExitResult, ES::Trap>> {
 		if self.is_empty() {
 			return Err(Capture::Exit(ExitSucceed::Stopped.into()));
 		}
@@ -141,9 +238,7 @@ where
 		}
 
 		let opcode = Opcode(self.code[position]);
-		let control = self
-			.etable
-			.eval(&mut self.machine, handle, opcode, self.position);
+		let control = self.eval(handle, opcode, position, fuse);
 
 		match control {
 			Control::Continue => {
@@ -0,0 +1,196 @@
+use alloc::vec::Vec;
+
+use primitive_types::{H256, U256};
+
+use crate::{
+	error::ExitException,
+	etable::Control,
+	machine::{Machine, MemoryStorage, Stack},
+	opcode::Opcode,
+};
+
+/// Superinstruction, replacing a common sequence of opcodes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Fused {
+	/// `PUSH1 dest JUMP`.
+	Push1Jump {
+		/// Jump destination.
+		dest: U256,
+	},
+	/// `PUSHn a PUSHm b ADD`.
+	PushPushAdd {
+		/// First push and its value.
+		a: (Opcode, U256),
+		/// Second push and its value.
+		b: (Opcode, U256),
+	},
+	/// `ISZERO PUSHn dest JUMPI`.
+	IsZeroPushJumpi {
+		/// Push of the jump destination.
+		push: Opcode,
+		/// Jump destination.
+		dest: U256,
+	},
+}
+
+impl Fused {
+	/// Opcodes of the sequence and their positions, given the position of
+	/// the first one.
+	#[must_use]
+	pub fn opcodes(&self, position: usize) -> [Option<(Opcode, usize)>; 3] {
+		match self {
+			Self::Push1Jump { .. } => [
+				Some((Opcode::PUSH1, position)),
+				Some((Opcode::JUMP, position + 2)),
+				None,
+			],
+			Self::PushPushAdd { a, b } => {
+				let b_position = position + push_len(a.0);
+				[
+					Some((a.0, position)),
+					Some((b.0, b_position)),
+					Some((Opcode::ADD, b_position + push_len(b.0))),
+				]
+			}
+			Self::IsZeroPushJumpi { push, .. } => [
+				Some((Opcode::ISZERO, position)),
+				Some((*push, position + 1)),
+				Some((Opcode::JUMPI, position + 1 + push_len(*push))),
+			],
+		}
+	}
+
+	/// Length of the sequence in code.
+	#[must_use]
+	pub fn code_len(&self) -> usize {
+		match self {
+			Self::Push1Jump { .. } => 3,
+			Self::PushPushAdd { a, b } => push_len(a.0) + push_len(b.0) + 1,
+			Self::IsZeroPushJumpi { push, .. } => push_len(*push) + 2,
+		}
+	}
+
+	/// Whether the sequence can be evaluated at once on the given stack,
+	/// which is the case if none of its opcodes would fail on it.
+	#[must_use]
+	pub fn fusable(&self, stack: &Stack) -> bool {
+		match self {
+			Self::Push1Jump { .. } => stack.len() < stack.limit(),
+			Self::PushPushAdd { .. } => stack.len() + 1 < stack.limit(),
+			Self::IsZeroPushJumpi { .. } => !stack.is_empty() && stack.len() < stack.limit(),
+		}
+	}
+
+	/// Evaluate the sequence with the core semantics of its opcodes, on a
+	/// stack that is [Self::fusable].
+	pub fn eval<S, Tr, M: MemoryStorage>(&self, machine: &mut Machine<S, M>) -> Control<Tr> {
+		match self {
+			Self::Push1Jump { dest } => jump(*dest),
+			Self::PushPushAdd { a, b } => {
+				let (value, _) = a.1.overflowing_add(b.1);
+				let mut ret = H256::default();
+				value.to_big_endian(&mut ret[..]);
+				match machine.stack.push(ret) {
+					Ok(()) => Control::ContinueN(self.code_len()),
+					Err(e) => Control::Exit(e.into()),
+				}
+			}
+			Self::IsZeroPushJumpi { dest, .. } => match machine.stack.pop() {
+				Ok(value) if value == H256::zero() => jump(*dest),
+				Ok(_) => Control::ContinueN(self.code_len()),
+				Err(e) => Control::Exit(e.into()),
+			},
+		}
+	}
+}
+
+fn jump<Tr>(dest: U256) -> Control<Tr> {
+	if dest > U256::from(usize::MAX) {
+		Control::Exit(ExitException::InvalidJump.into())
+	} else {
+		Control::Jump(dest.as_usize())
+	}
+}
+
+fn push_len(opcode: Opcode) -> usize {
+	opcode.is_push().map_or(1, |n| n as usize + 1)
+}
+
+/// Superinstructions of code, by position of their first opcode.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Fusions {
+	index: Vec<Option<u32>>,
+	fused: Vec<Fused>,
+}
+
+impl Fusions {
+	/// Find the fusable sequences of given code bytes.
+	#[must_use]
+	pub fn new(code: &[u8]) -> Self {
+		let mut fusions = Self {
+			index: Vec::with_capacity(code.len()),
+			fused: Vec::new(),
+		};
+		fusions.index.resize(code.len(), None);
+
+		let mut i = 0;
+		while i < code.len() {
+			let opcode = Opcode(code[i]);
+			if let Some(fused) = fuse(code, i) {
+				fusions.index[i] = Some(fusions.fused.len() as u32);
+				fusions.fused.push(fused);
+			}
+
+			i += push_len(opcode);
+		}
+
+		fusions
+	}
+
+	/// Superinstruction starting at the position, if any.
+	#[must_use]
+	pub fn get(&self, position: usize) -> Option<Fused> {
+		self.index
+			.get(position)
+			.copied()
+			.flatten()
+			.map(|index| self.fused[index as usize])
+	}
+}
+
+/// Push at `position` and its value, if its data is all in code.
+fn push_at(code: &[u8], position: usize) -> Option<(Opcode, U256)> {
+	let opcode = Opcode(*code.get(position)?);
+	let n = opcode.is_push()? as usize;
+	let data = code.get((position + 1)..(position + 1 + n))?;
+
+	Some((opcode, U256::from_big_endian(data)))
+}
+
+fn fuse(code: &[u8], position: usize) -> Option<Fused> {
+	let opcode_at = |position: usize| code.get(position).copied().map(Opcode);
+
+	match Opcode(code[position]) {
+		Opcode::PUSH1 if opcode_at(position + 2) == Some(Opcode::JUMP) => {
+			let (_, dest) = push_at(code, position)?;
+			Some(Fused::Push1Jump { dest })
+		}
+		Opcode::ISZERO => {
+			let (push, dest) = push_at(code, position + 1)?;
+			if opcode_at(position + 1 + push_len(push)) == Some(Opcode::JUMPI) {
+				Some(Fused::IsZeroPushJumpi { push, dest })
+			} else {
+				None
+			}
+		}
+		_ => {
+			let a = push_at(code, position)?;
+			let b = push_at(code, position + push_len(a.0))?;
+			if opcode_at(position + push_len(a.0) + push_len(b.0)) == Some(Opcode::ADD) {
+				Some(Fused::PushPushAdd { a, b })
+			} else {
+				None
+			}
+		}
+	}
+}
@@ -1,22 +1,54 @@
 mod etable;
+mod fusion;
 mod valids;
 
 use alloc::vec::Vec;
 
-pub use self::etable::EtableInterpreter;
+pub use self::{
+	etable::EtableInterpreter,
+	fusion::{Fused, Fusions},
+	valids::Valids,
+};
 use crate::{
 	error::{Capture, ExitResult},
-	machine::Machine,
+	machine::{Machine, Memory, MemoryStorage, Stack},
+	opcode::Opcode,
 };
 
 pub trait Interpreter {
 	type State;
+	/// Storage of the memory of the machine.
+	type Memory: MemoryStorage;
 
-	fn machine(&self) -> &Machine<Self::State>;
-	fn machine_mut(&mut self) -> &mut Machine<Self::State>;
+	fn machine(&self) -> &Machine<Self::State, Self::Memory>;
+	fn machine_mut(&mut self) -> &mut Machine<Self::State, Self::Memory>;
+	fn position(&self) -> usize;
 
 	fn deconstruct(self) -> (Self::State, Vec<u8>);
 	fn advance(&mut self);
+
+	/// Opcode at the current position, `None` at the end of the code.
+	fn opcode(&self) -> Option<Opcode> {
+		self.machine()
+			.code()
+			.get(self.position())
+			.map(|opcode| Opcode(*opcode))
+	}
+
+	/// Current stack.
+	fn stack(&self) -> &Stack {
+		&self.machine().stack
+	}
+
+	/// Current memory.
+	fn memory(&self) -> &Memory<Self::Memory> {
+		&self.machine().memory
+	}
+
+	/// Current extra state of the machine.
+	fn state(&self) -> &Self::State {
+		&self.machine().state
+	}
 }
 
 pub trait RunInterpreter<H, Tr>: Interpreter {
@@ -6,6 +6,9 @@
 
 extern crate alloc;
 
+pub mod asm;
+pub mod disasm;
+pub mod eof;
 pub mod error;
 pub mod etable;
 pub mod eval;
@@ -15,4 +18,17 @@ pub mod opcode;
 pub mod runtime;
 pub mod utils;
 
-pub use self::interpreter::{EtableInterpreter, Interpreter, RunInterpreter, StepInterpreter};
+pub use self::interpreter::{
+	EtableInterpreter, Fused, Fusions, Interpreter, RunInterpreter, StepInterpreter, Valids,
+};
+
+/// Pointer sharing code, call data and transaction context between machines.
+/// `Arc` with the `sync` feature, making machines and call stacks `Send`, and
+/// `Rc` otherwise.
+#[cfg(feature = "sync")]
+pub type Shared<T> = alloc::sync::Arc<T>;
+/// Pointer sharing code, call data and transaction context between machines.
+/// `Arc` with the `sync` feature, making machines and call stacks `Send`, and
+/// `Rc` otherwise.
+#[cfg(not(feature = "sync"))]
+pub type Shared<T> = alloc::rc::Rc<T>;
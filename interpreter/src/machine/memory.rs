@@ -1,7 +1,7 @@
-use alloc::{vec, vec::Vec};
+use alloc::{boxed::Box, vec, vec::Vec};
 use core::{
 	cmp::{max, min},
-	mem,
+	fmt::Debug,
 	ops::{BitAnd, Not, Range},
 };
 
@@ -9,23 +9,193 @@ use primitive_types::U256;
 
 use crate::error::{ExitException, ExitFatal};
 
-/// A sequencial memory. It uses Rust's `Vec` for internal
-/// representation.
+/// Size of a memory page, in bytes.
+const PAGE_SIZE: usize = 4096;
+
+/// Content of the pages never written to.
+static ZERO_PAGE: [u8; PAGE_SIZE] = [0; PAGE_SIZE];
+
+/// Byte storage backing a [Memory], such as a custom allocator, a guest
+/// memory region or an instrumented buffer.
+///
+/// The [Memory] keeps track of the length, limits and budget, and only reads
+/// and writes within its length. Bytes never written must read as zero.
+pub trait MemoryStorage: Debug {
+	/// Read `buf.len()` bytes at `offset` into `buf`.
+	fn read(&self, offset: usize, buf: &mut [u8]);
+
+	/// Write `value` at `offset`.
+	fn write(&mut self, offset: usize, value: &[u8]);
+
+	/// Zero `len` bytes at `offset`.
+	fn zero(&mut self, offset: usize, len: usize) {
+		const ZEROS: [u8; 256] = [0; 256];
+
+		let mut done = 0;
+		while done < len {
+			let n = min(ZEROS.len(), len - done);
+			self.write(offset + done, &ZEROS[..n]);
+			done += n;
+		}
+	}
+
+	/// Borrow the contiguous bytes at `offset`, at most `len` of them, and
+	/// at least one if `len` is not zero.
+	fn chunk(&self, offset: usize, len: usize) -> &[u8];
+
+	/// Discard all content, so that everything reads as zero.
+	fn clear(&mut self);
+}
+
+/// Default [MemoryStorage]. It is stored as pages of 4 KiB, which are only
+/// allocated when written to. Untouched pages read as zero, so expanding the
+/// memory does not zero-fill it.
+#[derive(Clone, Debug, Default)]
+pub struct Pages {
+	pages: Vec<Option<Box<[u8]>>>,
+}
+
+impl MemoryStorage for Pages {
+	fn read(&self, offset: usize, buf: &mut [u8]) {
+		let mut done = 0;
+		while done < buf.len() {
+			let position = offset + done;
+			let (page, start) = (position / PAGE_SIZE, position % PAGE_SIZE);
+			let n = min(PAGE_SIZE - start, buf.len() - done);
+
+			match self.pages.get(page) {
+				Some(Some(page)) => {
+					buf[done..(done + n)].copy_from_slice(&page[start..(start + n)])
+				}
+				_ => buf[done..(done + n)].fill(0),
+			}
+
+			done += n;
+		}
+	}
+
+	fn write(&mut self, offset: usize, value: &[u8]) {
+		let mut done = 0;
+		while done < value.len() {
+			let position = offset + done;
+			let (page, start) = (position / PAGE_SIZE, position % PAGE_SIZE);
+			let n = min(PAGE_SIZE - start, value.len() - done);
+
+			if self.pages.len() <= page {
+				#[cfg(feature = "pool")]
+				if self.pages.capacity() == 0 {
+					if let Some(pages) = super::pool::take_memory() {
+						self.pages = pages;
+					}
+				}
+				self.pages.resize(page + 1, None);
+			}
+			let page = self.pages[page].get_or_insert_with(new_page);
+			page[start..(start + n)].copy_from_slice(&value[done..(done + n)]);
+
+			done += n;
+		}
+	}
+
+	/// Pages not allocated are left as is.
+	fn zero(&mut self, offset: usize, len: usize) {
+		let mut done = 0;
+		while done < len {
+			let position = offset + done;
+			let (page, start) = (position / PAGE_SIZE, position % PAGE_SIZE);
+			let n = min(PAGE_SIZE - start, len - done);
+
+			if let Some(Some(page)) = self.pages.get_mut(page) {
+				page[start..(start + n)].fill(0);
+			}
+
+			done += n;
+		}
+	}
+
+	fn chunk(&self, offset: usize, len: usize) -> &[u8] {
+		let (page, start) = (offset / PAGE_SIZE, offset % PAGE_SIZE);
+		let n = min(PAGE_SIZE - start, len);
+
+		match self.pages.get(page) {
+			Some(Some(page)) => &page[start..(start + n)],
+			_ => &ZERO_PAGE[..n],
+		}
+	}
+
+	/// Drop all pages, returning them to the pool.
+	fn clear(&mut self) {
+		#[cfg(feature = "pool")]
+		for page in self.pages.drain(..).flatten() {
+			super::pool::put_page(page);
+		}
+
+		self.pages.clear();
+	}
+}
+
+#[cfg(feature = "pool")]
+impl Drop for Pages {
+	fn drop(&mut self) {
+		self.clear();
+		super::pool::put_memory(core::mem::take(&mut self.pages));
+	}
+}
+
+/// A sequencial memory, stored in a [MemoryStorage], by default [Pages].
 #[derive(Clone, Debug)]
-pub struct Memory {
-	data: Vec<u8>,
+pub struct Memory<M = Pages> {
+	storage: M,
+	len: usize,
 	effective_len: U256,
 	limit: usize,
+	budget: usize,
+	version: u64,
+}
+
+/// Iterator over the chunks of a [Memory], returned by [Memory::chunks].
+pub struct MemoryChunks<'a, M = Pages> {
+	memory: &'a Memory<M>,
+	offset: usize,
+}
+
+impl<'a, M: MemoryStorage> Iterator for MemoryChunks<'a, M> {
+	type Item = &'a [u8];
+
+	fn next(&mut self) -> Option<&'a [u8]> {
+		if self.offset >= self.memory.len {
+			return None;
+		}
+
+		let chunk = self
+			.memory
+			.storage
+			.chunk(self.offset, self.memory.len - self.offset);
+		self.offset += chunk.len();
+		Some(chunk)
+	}
 }
 
 impl Memory {
 	/// Create a new memory with the given limit.
 	#[must_use]
 	pub fn new(limit: usize) -> Self {
+		Self::with_storage(limit, Pages::default())
+	}
+}
+
+impl<M: MemoryStorage> Memory<M> {
+	/// Create a new memory with the given limit, stored in `storage`. The
+	/// storage must be empty.
+	#[must_use]
+	pub fn with_storage(limit: usize, storage: M) -> Self {
 		Self {
-			data: Vec::new(),
+			storage,
+			len: 0,
 			effective_len: U256::zero(),
 			limit,
+			budget: usize::MAX,
+			version: 0,
 		}
 	}
 
@@ -35,10 +205,27 @@ impl Memory {
 		self.limit
 	}
 
+	/// Set the memory limit.
+	pub fn set_limit(&mut self, limit: usize) {
+		self.limit = limit;
+	}
+
+	/// Memory budget, left of the transaction memory budget.
+	#[must_use]
+	pub const fn budget(&self) -> usize {
+		self.budget
+	}
+
+	/// Set the memory budget. Unlike the limit, exceeding it fails with
+	/// [ExitFatal::MemoryBudgetExceeded].
+	pub fn set_budget(&mut self, budget: usize) {
+		self.budget = budget;
+	}
+
 	/// Get the length of the current memory range.
 	#[must_use]
-	pub fn len(&self) -> usize {
-		self.data.len()
+	pub const fn len(&self) -> usize {
+		self.len
 	}
 
 	/// Get the effective length.
@@ -49,20 +236,30 @@ impl Memory {
 
 	/// Return true if current effective memory range is zero.
 	#[must_use]
-	pub fn is_empty(&self) -> bool {
+	pub const fn is_empty(&self) -> bool {
 		self.len() == 0
 	}
 
-	/// Return the full memory.
+	/// Borrow the full memory, as contiguous chunks in order, such as its
+	/// pages.
+	pub fn chunks(&self) -> MemoryChunks<'_, M> {
+		MemoryChunks {
+			memory: self,
+			offset: 0,
+		}
+	}
+
+	/// Copy the full memory.
 	#[must_use]
-	pub const fn data(&self) -> &Vec<u8> {
-		&self.data
+	pub fn to_vec(&self) -> Vec<u8> {
+		self.get(0, self.len)
 	}
 
-	pub(crate) fn swap_and_clear(&mut self, other: &mut Vec<u8>) {
-		mem::swap(&mut self.data, other);
-		self.data = Vec::new();
-		self.effective_len = U256::zero();
+	/// Number of writes to the memory so far, to tell whether it changed
+	/// without comparing its content.
+	#[must_use]
+	pub const fn version(&self) -> u64 {
+		self.version
 	}
 
 	/// Resize the memory, making it cover the memory region of `offset..(offset
@@ -90,7 +287,27 @@ impl Memory {
 
 	/// Resize to range. Used for return value.
 	pub fn resize_to_range(&mut self, return_range: Range<U256>) {
-		let ret = if return_range.start > U256::from(usize::MAX) {
+		let ret = self.get_range(return_range.clone());
+		self.version += 1;
+		self.storage.clear();
+		self.len = ret.len();
+		self.storage.write(0, &ret);
+		self.effective_len = return_range.end - return_range.start;
+	}
+
+	/// Take the memory region of `range` and clear the memory. Used for the
+	/// return value, which is copied out of memory only once.
+	pub fn take_range(&mut self, range: Range<U256>) -> Vec<u8> {
+		let ret = self.get_range(range);
+		self.version += 1;
+		self.storage.clear();
+		self.len = 0;
+		self.effective_len = U256::zero();
+		ret
+	}
+
+	fn get_range(&self, return_range: Range<U256>) -> Vec<u8> {
+		if return_range.start > U256::from(usize::MAX) {
 			vec![0; (return_range.end - return_range.start).as_usize()]
 		} else if return_range.end > U256::from(usize::MAX) {
 			let mut ret = self.get(
@@ -106,9 +323,7 @@ impl Memory {
 				return_range.start.as_usize(),
 				(return_range.end - return_range.start).as_usize(),
 			)
-		};
-		self.data = ret;
-		self.effective_len = return_range.end - return_range.start;
+		}
 	}
 
 	/// Get memory region at given offset.
@@ -121,19 +336,9 @@ impl Memory {
 	pub fn get(&self, offset: usize, size: usize) -> Vec<u8> {
 		let mut ret = vec![0; size];
 
-		#[allow(clippy::needless_range_loop)]
-		for index in 0..size {
-			let position = if let Some(position) = offset.checked_add(index) {
-				position
-			} else {
-				break;
-			};
-
-			if position >= self.data.len() {
-				break;
-			}
-
-			ret[index] = self.data[position];
+		let end = min(offset.saturating_add(size), self.len);
+		if offset < end {
+			self.storage.read(offset, &mut ret[..(end - offset)]);
 		}
 
 		ret
@@ -152,25 +357,23 @@ impl Memory {
 			return Ok(());
 		}
 
-		if offset
-			.checked_add(target_size)
-			.map(|pos| pos > self.limit)
-			.unwrap_or(true)
-		{
+		let end = offset.checked_add(target_size);
+		if end.map(|pos| pos > self.limit).unwrap_or(true) {
 			return Err(ExitFatal::NotSupported);
 		}
-
-		if self.data.len() < offset + target_size {
-			self.data.resize(offset + target_size, 0);
+		if end.map(|pos| pos > self.budget).unwrap_or(true) {
+			return Err(ExitFatal::MemoryBudgetExceeded);
 		}
 
+		self.len = max(self.len, offset + target_size);
+		self.version += 1;
+
 		if target_size > value.len() {
-			self.data[offset..((value.len()) + offset)].clone_from_slice(value);
-			for index in (value.len())..target_size {
-				self.data[offset + index] = 0;
-			}
+			self.storage.write(offset, value);
+			self.storage
+				.zero(offset + value.len(), target_size - value.len());
 		} else {
-			self.data[offset..(target_size + offset)].clone_from_slice(&value[..target_size]);
+			self.storage.write(offset, &value[..target_size]);
 		}
 
 		Ok(())
@@ -225,14 +428,23 @@ impl Memory {
 
 	/// Copies part of the memory inside another part of itself.
 	pub fn copy(&mut self, dst: usize, src: usize, len: usize) {
-		let resize_offset = max(dst, src);
-		if self.data.len() < resize_offset + len {
-			self.data.resize(resize_offset + len, 0);
-		}
-		self.data.copy_within(src..src + len, dst);
+		let value = self.get(src, len);
+		self.len = max(self.len, max(dst, src) + len);
+		self.version += 1;
+		self.storage.write(dst, &value);
 	}
 }
 
+/// A zeroed page, from the pool if possible.
+fn new_page() -> Box<[u8]> {
+	#[cfg(feature = "pool")]
+	if let Some(page) = super::pool::take_page() {
+		return page;
+	}
+
+	vec![0; PAGE_SIZE].into_boxed_slice()
+}
+
 /// Rounds up `x` to the closest multiple of 32. If `x % 32 == 0` then `x` is returned.
 #[inline]
 fn next_multiple_of_32(x: U256) -> Option<U256> {
@@ -286,14 +498,14 @@ mod tests {
 		// We insert the [1,2,3,4] array on index 3,
 		// that's why we have the zero padding at the beginning.
 		memory.set(3usize, &[1u8, 2u8, 3u8, 4u8], None).unwrap();
-		assert_eq!(memory.data(), &[0u8, 0u8, 0u8, 1u8, 2u8, 3u8, 4u8].to_vec());
+		assert_eq!(memory.to_vec(), [0u8, 0u8, 0u8, 1u8, 2u8, 3u8, 4u8]);
 
 		// Copy 1 byte into index 0.
 		// As the length is 1, we only copy the byte present on index 3.
 		memory.copy(0usize, 3usize, 1usize);
 
 		// Now the new memory data results in [1,0,0,1,2,3,4]
-		assert_eq!(memory.data(), &[1u8, 0u8, 0u8, 1u8, 2u8, 3u8, 4u8].to_vec());
+		assert_eq!(memory.to_vec(), [1u8, 0u8, 0u8, 1u8, 2u8, 3u8, 4u8]);
 	}
 
 	#[test]
@@ -306,7 +518,7 @@ mod tests {
 		// We insert the [1,2,3,4] array on index 3,
 		// that's why we have the zero padding at the beginning.
 		memory.set(3usize, &[1u8, 2u8, 3u8, 4u8], None).unwrap();
-		assert_eq!(memory.data(), &[0u8, 0u8, 0u8, 1u8, 2u8, 3u8, 4u8].to_vec());
+		assert_eq!(memory.to_vec(), [0u8, 0u8, 0u8, 1u8, 2u8, 3u8, 4u8]);
 
 		// Copy 2 bytes into index 3.
 		// As the length is 2, we copy the bytes present on indexes 6 and 7,
@@ -315,9 +527,103 @@ mod tests {
 
 		// Now the new memory data results in [0, 0, 0, 4, 0, 3, 4, 0].
 		// An extra element is added due to resizing.
+		assert_eq!(memory.to_vec(), [0u8, 0u8, 0u8, 4u8, 0u8, 3u8, 4u8, 0u8]);
+	}
+
+	#[test]
+	fn test_memory_pages() {
+		let mut memory = Memory::new(3 * super::PAGE_SIZE);
+
+		// Write across the boundary of the first two pages.
+		memory
+			.set(super::PAGE_SIZE - 2, &[1u8, 2u8, 3u8, 4u8], None)
+			.unwrap();
+		assert_eq!(memory.len(), super::PAGE_SIZE + 2);
+		assert_eq!(
+			memory.get(super::PAGE_SIZE - 3, 6),
+			[0u8, 1u8, 2u8, 3u8, 4u8, 0u8]
+		);
+
+		// Zero-padding an unallocated page reads as zero.
+		memory
+			.set(2 * super::PAGE_SIZE + 1, &[5u8], Some(3))
+			.unwrap();
+		assert_eq!(memory.len(), 2 * super::PAGE_SIZE + 4);
+		assert_eq!(
+			memory.get(2 * super::PAGE_SIZE, 5),
+			[0u8, 5u8, 0u8, 0u8, 0u8]
+		);
+
+		// Copy from one page into another.
+		memory.copy(1, super::PAGE_SIZE - 1, 3);
+		assert_eq!(memory.get(0, 5), [0u8, 2u8, 3u8, 4u8, 0u8]);
+
+		// Chunks borrow each page, up to the length.
+		let chunks = memory.chunks().collect::<alloc::vec::Vec<_>>();
 		assert_eq!(
-			memory.data(),
-			&[0u8, 0u8, 0u8, 4u8, 0u8, 3u8, 4u8, 0u8].to_vec()
+			chunks
+				.iter()
+				.map(|chunk| chunk.len())
+				.collect::<alloc::vec::Vec<_>>(),
+			[super::PAGE_SIZE, super::PAGE_SIZE, 4]
 		);
+		assert_eq!(chunks.concat(), memory.to_vec());
+	}
+
+	#[test]
+	fn test_memory_storage() {
+		use alloc::sync::Arc;
+		use core::sync::atomic::{AtomicUsize, Ordering};
+
+		use super::{MemoryStorage, Pages};
+
+		/// Pages counting the bytes written.
+		#[derive(Clone, Debug, Default)]
+		struct Counted(Pages, Arc<AtomicUsize>);
+
+		impl MemoryStorage for Counted {
+			fn read(&self, offset: usize, buf: &mut [u8]) {
+				self.0.read(offset, buf)
+			}
+
+			fn write(&mut self, offset: usize, value: &[u8]) {
+				self.1.fetch_add(value.len(), Ordering::Relaxed);
+				self.0.write(offset, value)
+			}
+
+			fn chunk(&self, offset: usize, len: usize) -> &[u8] {
+				self.0.chunk(offset, len)
+			}
+
+			fn clear(&mut self) {
+				self.0.clear()
+			}
+		}
+
+		let written = Arc::new(AtomicUsize::new(0));
+		let mut memory = Memory::with_storage(100, Counted(Pages::default(), written.clone()));
+		memory.set(3usize, &[1u8, 2u8], Some(4)).unwrap();
+		memory.copy(0usize, 3usize, 2usize);
+		assert_eq!(memory.to_vec(), [1u8, 2u8, 0u8, 1u8, 2u8, 0u8, 0u8]);
+		// Two bytes set, two zero-padded, and two copied.
+		assert_eq!(written.load(Ordering::Relaxed), 6);
+	}
+
+	#[cfg(feature = "pool")]
+	#[test]
+	fn test_memory_pool() {
+		use super::super::pool::{self, PoolLimits};
+
+		pool::using(PoolLimits::DEFAULT, || {
+			let mut memory = Memory::new(100usize);
+			memory.set(0usize, &[1u8, 2u8, 3u8], None).unwrap();
+			drop(memory);
+
+			// The page is reused, and zeroed.
+			let mut memory = Memory::new(100usize);
+			memory.set(4usize, &[4u8], None).unwrap();
+			assert_eq!(memory.to_vec(), [0u8, 0u8, 0u8, 0u8, 4u8]);
+		});
+		assert_eq!(pool::limits(), PoolLimits::NONE);
 	}
 }
@@ -1,22 +1,28 @@
 mod memory;
+#[cfg(feature = "pool")]
+pub mod pool;
 mod stack;
 
-use alloc::{rc::Rc, vec::Vec};
+use alloc::vec::Vec;
 
-pub use self::{memory::Memory, stack::Stack};
+pub use self::{
+	memory::{Memory, MemoryChunks, MemoryStorage, Pages},
+	stack::{Stack, STACK_CAPACITY},
+};
+use crate::Shared;
 
-/// Core execution layer for EVM.
-pub struct Machine<S> {
+/// Core execution layer for EVM, with its memory stored in `M`.
+pub struct Machine<S, M = Pages> {
 	/// Program data.
-	pub(crate) data: Rc<Vec<u8>>,
+	pub(crate) data: Shared<Vec<u8>>,
 	/// Program code.
-	pub(crate) code: Rc<Vec<u8>>,
+	pub(crate) code: Shared<Vec<u8>>,
 	/// Return value. Note the difference between `retbuf`.
 	/// A `retval` holds what's returned by the current machine, with `RETURN` or `REVERT` opcode.
 	/// A `retbuf` holds the buffer of returned value by sub-calls.
 	pub retval: Vec<u8>,
 	/// Memory.
-	pub memory: Memory,
+	pub memory: Memory<M>,
 	/// Stack.
 	pub stack: Stack,
 	/// Extra state,
@@ -26,8 +32,8 @@ pub struct Machine<S> {
 impl<S> Machine<S> {
 	/// Create a new machine with given code and data.
 	pub fn new(
-		code: Rc<Vec<u8>>,
-		data: Rc<Vec<u8>>,
+		code: Shared<Vec<u8>>,
+		data: Shared<Vec<u8>>,
 		stack_limit: usize,
 		memory_limit: usize,
 		state: S,
@@ -41,6 +47,27 @@ impl<S> Machine<S> {
 			state,
 		}
 	}
+}
+
+impl<S, M: MemoryStorage> Machine<S, M> {
+	/// Create a new machine with given code and data, and memory stored in
+	/// another [MemoryStorage] than [Pages].
+	pub fn with_memory(
+		code: Shared<Vec<u8>>,
+		data: Shared<Vec<u8>>,
+		stack_limit: usize,
+		memory: Memory<M>,
+		state: S,
+	) -> Self {
+		Self {
+			data,
+			code,
+			retval: Vec::new(),
+			memory,
+			stack: Stack::new(stack_limit),
+			state,
+		}
+	}
 
 	/// Machine code.
 	pub fn code(&self) -> &[u8] {
@@ -0,0 +1,157 @@
+//! Pool of machine storage, reused by the machines of the current thread
+//! instead of allocating it for every call frame.
+//!
+//! A machine allocates its stack slots, the page table of its memory and the
+//! memory pages it writes to. All three are returned to the pool when the
+//! machine is dropped, and taken from it by the next machine, so that once the
+//! pool is warm, entering a call frame does not allocate.
+//!
+//! Pooling is disabled by default. [using] enables it for the duration of a
+//! closure, usually a transaction, and [set_limits] enables it for the
+//! thread, reusing storage across transactions.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::cell::RefCell;
+
+use super::stack::Slots;
+
+/// Number of items of each kind the pool of a thread keeps.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PoolLimits {
+	/// Stacks, with their slots.
+	pub stacks: usize,
+	/// Page tables of memories.
+	pub memories: usize,
+	/// Memory pages, of 4 KiB each.
+	pub pages: usize,
+}
+
+impl PoolLimits {
+	/// No pooling.
+	pub const NONE: Self = Self {
+		stacks: 0,
+		memories: 0,
+		pages: 0,
+	};
+
+	/// Limits enough for the frames of most transactions, keeping at most
+	/// 1 MiB of memory pages.
+	pub const DEFAULT: Self = Self {
+		stacks: 64,
+		memories: 64,
+		pages: 256,
+	};
+}
+
+impl Default for PoolLimits {
+	fn default() -> Self {
+		Self::DEFAULT
+	}
+}
+
+struct Pool {
+	limits: PoolLimits,
+	stacks: Vec<Slots>,
+	memories: Vec<Vec<Option<Box<[u8]>>>>,
+	pages: Vec<Box<[u8]>>,
+}
+
+std::thread_local! {
+	static POOL: RefCell<Pool> = RefCell::new(Pool {
+		limits: PoolLimits::NONE,
+		stacks: Vec::new(),
+		memories: Vec::new(),
+		pages: Vec::new(),
+	});
+}
+
+/// Set how many items of each kind the pool of the current thread keeps.
+/// [PoolLimits::NONE] disables pooling. Pooled items beyond the limits are
+/// freed.
+pub fn set_limits(limits: PoolLimits) {
+	with(|pool| {
+		pool.limits = limits;
+		pool.stacks.truncate(limits.stacks);
+		pool.memories.truncate(limits.memories);
+		pool.pages.truncate(limits.pages);
+	});
+}
+
+/// Current limits of the pool of the current thread.
+#[must_use]
+pub fn limits() -> PoolLimits {
+	with(|pool| pool.limits).unwrap_or(PoolLimits::NONE)
+}
+
+/// Run `f` with the pool of the current thread keeping up to `limits` items.
+/// The previous limits are restored afterwards.
+pub fn using<R, F: FnOnce() -> R>(limits: PoolLimits, f: F) -> R {
+	struct Restore(PoolLimits);
+
+	impl Drop for Restore {
+		fn drop(&mut self) {
+			set_limits(self.0);
+		}
+	}
+
+	let _restore = Restore(self::limits());
+	set_limits(limits);
+	f()
+}
+
+fn with<R, F: FnOnce(&mut Pool) -> R>(f: F) -> Option<R> {
+	POOL.try_with(|pool| pool.try_borrow_mut().ok().map(|mut pool| f(&mut pool)))
+		.ok()
+		.flatten()
+}
+
+/// Take pooled slots for a stack. They keep the values of their last stack,
+/// which are overwritten before being read.
+pub(crate) fn take_stack() -> Option<Slots> {
+	with(|pool| pool.stacks.pop()).flatten()
+}
+
+/// Return the slots of a dropped stack to the pool.
+pub(crate) fn put_stack(stack: Slots) {
+	with(|pool| {
+		if pool.stacks.len() < pool.limits.stacks {
+			pool.stacks.push(stack);
+		}
+	});
+}
+
+/// Take a pooled page table of a memory, empty.
+pub(crate) fn take_memory() -> Option<Vec<Option<Box<[u8]>>>> {
+	with(|pool| pool.memories.pop()).flatten()
+}
+
+/// Return the page table of a dropped memory to the pool. Its pages must
+/// already have been returned.
+pub(crate) fn put_memory(mut memory: Vec<Option<Box<[u8]>>>) {
+	if memory.capacity() == 0 {
+		return;
+	}
+	memory.clear();
+
+	with(|pool| {
+		if pool.memories.len() < pool.limits.memories {
+			pool.memories.push(memory);
+		}
+	});
+}
+
+/// Take a pooled memory page, zeroed.
+pub(crate) fn take_page() -> Option<Box<[u8]>> {
+	let mut page = with(|pool| pool.pages.pop()).flatten()?;
+	page.fill(0);
+	Some(page)
+}
+
+/// Return a page of a dropped or cleared memory to the pool.
+pub(crate) fn put_page(page: Box<[u8]>) {
+	with(|pool| {
+		if pool.pages.len() < pool.limits.pages {
+			pool.pages.push(page);
+		}
+	});
+}
@@ -1,13 +1,23 @@
-use alloc::vec::Vec;
+use alloc::boxed::Box;
+use core::fmt;
 
 use primitive_types::H256;
 
 use crate::error::{ExitError, ExitException};
 
-/// EVM stack.
-#[derive(Clone, Debug)]
+/// Number of slots of a stack, the stack limit of all hard forks. Limits above
+/// it are capped to it.
+pub const STACK_CAPACITY: usize = 1024;
+
+/// Slots of a stack.
+pub(crate) type Slots = Box<[H256; STACK_CAPACITY]>;
+
+/// EVM stack. Its slots are allocated once, on the first push, and never
+/// grow: the values are the first `len` slots.
+#[derive(Clone)]
 pub struct Stack {
-	data: Vec<H256>,
+	slots: Option<Slots>,
+	len: usize,
 	limit: usize,
 }
 
@@ -48,13 +58,46 @@ macro_rules! impl_perform_popn_pushn {
 	(INTERNAL_TYPE_H256, $e:expr) => { H256 };
 }
 
+/// Slots of a stack, from the pool if possible.
+fn new_slots() -> Slots {
+	#[cfg(feature = "pool")]
+	if let Some(slots) = super::pool::take_stack() {
+		return slots;
+	}
+
+	Box::new([H256::zero(); STACK_CAPACITY])
+}
+
+#[cfg(feature = "pool")]
+impl Drop for Stack {
+	fn drop(&mut self) {
+		if let Some(slots) = self.slots.take() {
+			super::pool::put_stack(slots);
+		}
+	}
+}
+
+impl fmt::Debug for Stack {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Stack")
+			.field("data", &self.data())
+			.field("limit", &self.limit)
+			.finish()
+	}
+}
+
 impl Stack {
-	/// Create a new stack with given limit.
+	/// Create a new stack with given limit, capped to [STACK_CAPACITY].
 	#[must_use]
 	pub const fn new(limit: usize) -> Self {
 		Self {
-			data: Vec::new(),
-			limit,
+			slots: None,
+			len: 0,
+			limit: if limit < STACK_CAPACITY {
+				limit
+			} else {
+				STACK_CAPACITY
+			},
 		}
 	}
 
@@ -68,34 +111,41 @@ impl Stack {
 	/// Stack length.
 	#[inline]
 	#[must_use]
-	pub fn len(&self) -> usize {
-		self.data.len()
+	pub const fn len(&self) -> usize {
+		self.len
 	}
 
 	/// Whether the stack is empty.
 	#[inline]
 	#[must_use]
-	pub fn is_empty(&self) -> bool {
-		self.data.is_empty()
+	pub const fn is_empty(&self) -> bool {
+		self.len == 0
 	}
 
-	/// Stack data.
+	/// Stack data, from bottom to top.
 	#[inline]
 	#[must_use]
-	pub const fn data(&self) -> &Vec<H256> {
-		&self.data
+	pub fn data(&self) -> &[H256] {
+		match &self.slots {
+			Some(slots) => &slots[..self.len],
+			None => &[],
+		}
 	}
 
 	/// Clear the stack.
 	pub fn clear(&mut self) {
-		self.data.clear();
+		self.len = 0;
 	}
 
 	/// Pop a value from the stack.
 	/// If the stack is already empty, returns the `StackUnderflow` error.
 	#[inline]
 	pub fn pop(&mut self) -> Result<H256, ExitException> {
-		self.data.pop().ok_or(ExitException::StackUnderflow)
+		if self.len == 0 {
+			return Err(ExitException::StackUnderflow);
+		}
+		self.len -= 1;
+		Ok(self.data_slots()[self.len])
 	}
 
 	/// Push a new value into the stack.
@@ -103,39 +153,48 @@ impl Stack {
 	/// leaves the stack unchanged.
 	#[inline]
 	pub fn push(&mut self, value: H256) -> Result<(), ExitException> {
-		if self.data.len() + 1 > self.limit {
+		if self.len + 1 > self.limit {
 			return Err(ExitException::StackOverflow);
 		}
-		self.data.push(value);
+		self.push_unchecked(value);
 		Ok(())
 	}
 
+	/// Slots of a stack with at least one value.
+	#[inline]
+	fn data_slots(&self) -> &Slots {
+		self.slots.as_ref().expect("stack with values has slots")
+	}
+
+	#[inline]
+	fn push_unchecked(&mut self, value: H256) {
+		let slots = self.slots.get_or_insert_with(new_slots);
+		slots[self.len] = value;
+		self.len += 1;
+	}
+
 	/// Check whether it's possible to pop and push enough items in the stack.
 	pub fn check_pop_push(&self, pop: usize, push: usize) -> Result<(), ExitException> {
-		if self.data.len() < pop {
+		if self.len < pop {
 			return Err(ExitException::StackUnderflow);
 		}
-		if self.data.len() - pop + push + 1 > self.limit {
+		if self.len - pop + push + 1 > self.limit {
 			return Err(ExitException::StackOverflow);
 		}
 		Ok(())
 	}
 
 	fn unchecked_peek(&self, no_from_top: usize) -> &H256 {
-		&self.data[self.data.len() - no_from_top - 1]
+		&self.data_slots()[self.len - no_from_top - 1]
 	}
 
 	fn unchecked_pop_push1(&mut self, pop: usize, p1: H256) {
-		for _ in 0..pop {
-			self.data.pop();
-		}
-		self.data.push(p1);
+		self.len -= pop;
+		self.push_unchecked(p1);
 	}
 
 	fn unchecked_pop_push0(&mut self, pop: usize, _p1: ()) {
-		for _ in 0..pop {
-			self.data.pop();
-		}
+		self.len -= pop;
 	}
 
 	/// Peek a value at given index for the stack, where the top of
@@ -143,8 +202,8 @@ impl Stack {
 	/// `StackError::Underflow` is returned.
 	#[inline]
 	pub fn peek(&self, no_from_top: usize) -> Result<H256, ExitException> {
-		if self.data.len() > no_from_top {
-			Ok(self.data[self.data.len() - no_from_top - 1])
+		if self.len > no_from_top {
+			Ok(self.data_slots()[self.len - no_from_top - 1])
 		} else {
 			Err(ExitException::StackUnderflow)
 		}
@@ -155,9 +214,9 @@ impl Stack {
 	/// `StackError::Underflow` is returned.
 	#[inline]
 	pub fn set(&mut self, no_from_top: usize, val: H256) -> Result<(), ExitException> {
-		if self.data.len() > no_from_top {
-			let len = self.data.len();
-			self.data[len - no_from_top - 1] = val;
+		if self.len > no_from_top {
+			let index = self.len - no_from_top - 1;
+			self.slots.as_mut().expect("stack with values has slots")[index] = val;
 			Ok(())
 		} else {
 			Err(ExitException::StackUnderflow)
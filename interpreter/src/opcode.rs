@@ -1,5 +1,9 @@
+use core::fmt;
+
+use crate::disasm::eof_name;
+
 /// Opcode enum. One-to-one corresponding to an `u8` value.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(
 	feature = "scale",
 	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
@@ -63,6 +67,8 @@ impl Opcode {
 	pub const SHR: Opcode = Opcode(0x1c);
 	/// `SAR`
 	pub const SAR: Opcode = Opcode(0x1d);
+	/// `CLZ`
+	pub const CLZ: Opcode = Opcode(0x1e);
 
 	/// `CALLDATALOAD`
 	pub const CALLDATALOAD: Opcode = Opcode(0x35);
@@ -182,6 +188,17 @@ impl Opcode {
 	pub const INVALID: Opcode = Opcode(0xfe);
 }
 
+impl fmt::Display for Opcode {
+	/// Standard mnemonic of the opcode, including those only defined in EOF
+	/// code, or its value in hex if it is not defined.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.name().or_else(|| eof_name(*self)) {
+			Some(name) => write!(f, "{}", name),
+			None => write!(f, "UNKNOWN(0x{:02x})", self.0),
+		}
+	}
+}
+
 // External opcodes
 impl Opcode {
 	/// `SHA3`
@@ -229,6 +246,10 @@ impl Opcode {
 	pub const SELFBALANCE: Opcode = Opcode(0x47);
 	/// `BASEFEE`
 	pub const BASEFEE: Opcode = Opcode(0x48);
+	/// `BLOBHASH`
+	pub const BLOBHASH: Opcode = Opcode(0x49);
+	/// `BLOBBASEFEE`
+	pub const BLOBBASEFEE: Opcode = Opcode(0x4a);
 
 	/// `SLOAD`
 	pub const SLOAD: Opcode = Opcode(0x54);
@@ -261,6 +282,10 @@ impl Opcode {
 	pub const DELEGATECALL: Opcode = Opcode(0xf4);
 	/// `CREATE2`
 	pub const CREATE2: Opcode = Opcode(0xf5);
+	/// `AUTH`
+	pub const AUTH: Opcode = Opcode(0xf6);
+	/// `AUTHCALL`
+	pub const AUTHCALL: Opcode = Opcode(0xf7);
 
 	/// `STATICCALL`
 	pub const STATICCALL: Opcode = Opcode(0xfa);
@@ -292,4 +317,199 @@ impl Opcode {
 	pub const fn as_usize(&self) -> usize {
 		self.0 as usize
 	}
+
+	/// Number of stack items the opcode pops and pushes. `None` if the
+	/// opcode is not defined.
+	#[must_use]
+	pub const fn stack_io(&self) -> Option<(usize, usize)> {
+		Some(match self.0 {
+			0x00 | 0x5b | 0xfe => (0, 0),
+			0x01..=0x07 | 0x0a | 0x0b | 0x10..=0x14 | 0x16..=0x18 | 0x1a..=0x1d | 0x20 => (2, 1),
+			0x08 | 0x09 => (3, 1),
+			0x15 | 0x19 | 0x1e => (1, 1),
+			0x30 | 0x32..=0x34 | 0x36 | 0x38 | 0x3a | 0x3d => (0, 1),
+			0x31 | 0x35 | 0x3b | 0x3f | 0x40 | 0x49 | 0x51 | 0x54 | 0x5c => (1, 1),
+			0x37 | 0x39 | 0x3e | 0x5e => (3, 0),
+			0x3c => (4, 0),
+			0x41..=0x48 | 0x4a | 0x58..=0x5a => (0, 1),
+			0x50 | 0x56 | 0xff => (1, 0),
+			0x52 | 0x53 | 0x55 | 0x57 | 0x5d | 0xf3 | 0xfd => (2, 0),
+			0x5f..=0x7f => (0, 1),
+			0x80..=0x8f => {
+				let n = (self.0 - 0x80) as usize + 1;
+				(n, n + 1)
+			}
+			0x90..=0x9f => {
+				let n = (self.0 - 0x90) as usize + 2;
+				(n, n)
+			}
+			0xa0..=0xa4 => ((self.0 - 0xa0) as usize + 2, 0),
+			0xf0 | 0xf6 => (3, 1),
+			0xf1 | 0xf2 | 0xf7 => (7, 1),
+			0xf4 | 0xfa => (6, 1),
+			0xf5 => (4, 1),
+			_ => return None,
+		})
+	}
+
+	/// Mnemonic of the opcode, as printed by geth. `None` if the opcode is
+	/// not defined.
+	#[must_use]
+	pub const fn name(&self) -> Option<&'static str> {
+		Some(match self.0 {
+			0x00 => "STOP",
+			0x01 => "ADD",
+			0x02 => "MUL",
+			0x03 => "SUB",
+			0x04 => "DIV",
+			0x05 => "SDIV",
+			0x06 => "MOD",
+			0x07 => "SMOD",
+			0x08 => "ADDMOD",
+			0x09 => "MULMOD",
+			0x0a => "EXP",
+			0x0b => "SIGNEXTEND",
+			0x10 => "LT",
+			0x11 => "GT",
+			0x12 => "SLT",
+			0x13 => "SGT",
+			0x14 => "EQ",
+			0x15 => "ISZERO",
+			0x16 => "AND",
+			0x17 => "OR",
+			0x18 => "XOR",
+			0x19 => "NOT",
+			0x1a => "BYTE",
+			0x1b => "SHL",
+			0x1c => "SHR",
+			0x1d => "SAR",
+			0x1e => "CLZ",
+			0x20 => "KECCAK256",
+			0x30 => "ADDRESS",
+			0x31 => "BALANCE",
+			0x32 => "ORIGIN",
+			0x33 => "CALLER",
+			0x34 => "CALLVALUE",
+			0x35 => "CALLDATALOAD",
+			0x36 => "CALLDATASIZE",
+			0x37 => "CALLDATACOPY",
+			0x38 => "CODESIZE",
+			0x39 => "CODECOPY",
+			0x3a => "GASPRICE",
+			0x3b => "EXTCODESIZE",
+			0x3c => "EXTCODECOPY",
+			0x3d => "RETURNDATASIZE",
+			0x3e => "RETURNDATACOPY",
+			0x3f => "EXTCODEHASH",
+			0x40 => "BLOCKHASH",
+			0x41 => "COINBASE",
+			0x42 => "TIMESTAMP",
+			0x43 => "NUMBER",
+			0x44 => "DIFFICULTY",
+			0x45 => "GASLIMIT",
+			0x46 => "CHAINID",
+			0x47 => "SELFBALANCE",
+			0x48 => "BASEFEE",
+			0x49 => "BLOBHASH",
+			0x4a => "BLOBBASEFEE",
+			0x50 => "POP",
+			0x51 => "MLOAD",
+			0x52 => "MSTORE",
+			0x53 => "MSTORE8",
+			0x54 => "SLOAD",
+			0x55 => "SSTORE",
+			0x56 => "JUMP",
+			0x57 => "JUMPI",
+			0x58 => "PC",
+			0x59 => "MSIZE",
+			0x5a => "GAS",
+			0x5b => "JUMPDEST",
+			0x5c => "TLOAD",
+			0x5d => "TSTORE",
+			0x5e => "MCOPY",
+			0x5f => "PUSH0",
+			0x60 => "PUSH1",
+			0x61 => "PUSH2",
+			0x62 => "PUSH3",
+			0x63 => "PUSH4",
+			0x64 => "PUSH5",
+			0x65 => "PUSH6",
+			0x66 => "PUSH7",
+			0x67 => "PUSH8",
+			0x68 => "PUSH9",
+			0x69 => "PUSH10",
+			0x6a => "PUSH11",
+			0x6b => "PUSH12",
+			0x6c => "PUSH13",
+			0x6d => "PUSH14",
+			0x6e => "PUSH15",
+			0x6f => "PUSH16",
+			0x70 => "PUSH17",
+			0x71 => "PUSH18",
+			0x72 => "PUSH19",
+			0x73 => "PUSH20",
+			0x74 => "PUSH21",
+			0x75 => "PUSH22",
+			0x76 => "PUSH23",
+			0x77 => "PUSH24",
+			0x78 => "PUSH25",
+			0x79 => "PUSH26",
+			0x7a => "PUSH27",
+			0x7b => "PUSH28",
+			0x7c => "PUSH29",
+			0x7d => "PUSH30",
+			0x7e => "PUSH31",
+			0x7f => "PUSH32",
+			0x80 => "DUP1",
+			0x81 => "DUP2",
+			0x82 => "DUP3",
+			0x83 => "DUP4",
+			0x84 => "DUP5",
+			0x85 => "DUP6",
+			0x86 => "DUP7",
+			0x87 => "DUP8",
+			0x88 => "DUP9",
+			0x89 => "DUP10",
+			0x8a => "DUP11",
+			0x8b => "DUP12",
+			0x8c => "DUP13",
+			0x8d => "DUP14",
+			0x8e => "DUP15",
+			0x8f => "DUP16",
+			0x90 => "SWAP1",
+			0x91 => "SWAP2",
+			0x92 => "SWAP3",
+			0x93 => "SWAP4",
+			0x94 => "SWAP5",
+			0x95 => "SWAP6",
+			0x96 => "SWAP7",
+			0x97 => "SWAP8",
+			0x98 => "SWAP9",
+			0x99 => "SWAP10",
+			0x9a => "SWAP11",
+			0x9b => "SWAP12",
+			0x9c => "SWAP13",
+			0x9d => "SWAP14",
+			0x9e => "SWAP15",
+			0x9f => "SWAP16",
+			0xa0 => "LOG0",
+			0xa1 => "LOG1",
+			0xa2 => "LOG2",
+			0xa3 => "LOG3",
+			0xa4 => "LOG4",
+			0xf0 => "CREATE",
+			0xf1 => "CALL",
+			0xf2 => "CALLCODE",
+			0xf3 => "RETURN",
+			0xf4 => "DELEGATECALL",
+			0xf5 => "CREATE2",
+			0xf6 => "AUTH",
+			0xf7 => "AUTHCALL",
+			0xfa => "STATICCALL",
+			0xfd => "REVERT",
+			0xfe => "INVALID",
+			0xff => "SELFDESTRUCT",
+			_ => return None,
+		})
+	}
 }
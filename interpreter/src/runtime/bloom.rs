@@ -0,0 +1,196 @@
+//! The 2048-bit logs bloom filter of receipts and block headers.
+
+use core::fmt;
+
+use crate::{runtime::Log, utils::keccak256};
+
+/// Size of a bloom in bytes.
+pub const BLOOM_SIZE: usize = 256;
+
+/// Logs bloom. Each log sets three bits for its address and for each of its
+/// topics.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+pub struct Bloom(pub [u8; BLOOM_SIZE]);
+
+impl Default for Bloom {
+	fn default() -> Self {
+		Self([0; BLOOM_SIZE])
+	}
+}
+
+impl Bloom {
+	/// Bloom of the given logs.
+	pub fn from_logs<'a>(logs: impl IntoIterator<Item = &'a Log>) -> Self {
+		let mut bloom = Self::default();
+		for log in logs {
+			bloom.accrue_log(log);
+		}
+		bloom
+	}
+
+	fn bits(input: &[u8]) -> [(usize, u8); 3] {
+		let hash = keccak256(input);
+		let mut bits = [(0, 0); 3];
+		for (i, bit) in bits.iter_mut().enumerate() {
+			let index = (usize::from(hash[2 * i]) << 8 | usize::from(hash[2 * i + 1])) & 2047;
+			*bit = (BLOOM_SIZE - 1 - index / 8, 1 << (index % 8));
+		}
+		bits
+	}
+
+	/// Set the bits of an input, an address or a topic.
+	pub fn accrue(&mut self, input: &[u8]) {
+		for (byte, mask) in Self::bits(input) {
+			self.0[byte] |= mask;
+		}
+	}
+
+	/// Set the bits of a log.
+	pub fn accrue_log(&mut self, log: &Log) {
+		self.accrue(&log.address[..]);
+		for topic in &log.topics {
+			self.accrue(&topic[..]);
+		}
+	}
+
+	/// Set the bits of another bloom, as when combining the blooms of
+	/// receipts into the bloom of a block.
+	pub fn accrue_bloom(&mut self, other: &Bloom) {
+		for (byte, other) in self.0.iter_mut().zip(other.0.iter()) {
+			*byte |= other;
+		}
+	}
+
+	/// Whether the bits of an input are set. False positives are possible.
+	pub fn contains_input(&self, input: &[u8]) -> bool {
+		Self::bits(input)
+			.into_iter()
+			.all(|(byte, mask)| self.0[byte] & mask == mask)
+	}
+
+	/// Whether no bit is set.
+	pub fn is_empty(&self) -> bool {
+		self.0.iter().all(|byte| *byte == 0)
+	}
+}
+
+impl From<[u8; BLOOM_SIZE]> for Bloom {
+	fn from(bytes: [u8; BLOOM_SIZE]) -> Self {
+		Self(bytes)
+	}
+}
+
+impl AsRef<[u8]> for Bloom {
+	fn as_ref(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl fmt::LowerHex for Bloom {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if f.alternate() {
+			write!(f, "0x")?;
+		}
+		for byte in &self.0 {
+			write!(f, "{:02x}", byte)?;
+		}
+		Ok(())
+	}
+}
+
+/// Serialized as a `0x`-prefixed hex string, as in JSON-RPC receipts.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Bloom {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(&format_args!("{:#x}", self))
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Bloom {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct Visitor;
+
+		impl<'de> serde::de::Visitor<'de> for Visitor {
+			type Value = Bloom;
+
+			fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				write!(f, "a hex string of {} bytes", BLOOM_SIZE)
+			}
+
+			fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Bloom, E> {
+				let hex = value.strip_prefix("0x").unwrap_or(value);
+				if hex.len() != 2 * BLOOM_SIZE {
+					return Err(E::invalid_length(hex.len() / 2, &self));
+				}
+
+				let mut bloom = Bloom::default();
+				for (byte, digits) in bloom.0.iter_mut().zip(hex.as_bytes().chunks(2)) {
+					*byte = core::str::from_utf8(digits)
+						.ok()
+						.and_then(|digits| u8::from_str_radix(digits, 16).ok())
+						.ok_or_else(|| {
+							E::invalid_value(serde::de::Unexpected::Str(value), &self)
+						})?;
+				}
+				Ok(bloom)
+			}
+		}
+
+		deserializer.deserialize_str(Visitor)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+
+	use primitive_types::{H160, H256};
+
+	use super::*;
+
+	#[test]
+	fn accrue_log_sets_address_and_topics() {
+		let log = Log {
+			address: H160::repeat_byte(0x11),
+			topics: vec![H256::repeat_byte(0x22)],
+			data: vec![0x33],
+		};
+		let bloom = Bloom::from_logs([&log]);
+
+		assert!(bloom.contains_input(&log.address[..]));
+		assert!(bloom.contains_input(&log.topics[0][..]));
+		assert!(!bloom.contains_input(&[0x33]));
+		let bits: u32 = bloom.0.iter().map(|byte| byte.count_ones()).sum();
+		assert!(bits > 0 && bits <= 6);
+	}
+
+	#[test]
+	fn accrue_bloom_combines() {
+		let mut a = Bloom::default();
+		a.accrue(b"a");
+		let mut b = Bloom::default();
+		b.accrue(b"b");
+		assert!(Bloom::default().is_empty());
+
+		a.accrue_bloom(&b);
+		assert!(a.contains_input(b"a"));
+		assert!(a.contains_input(b"b"));
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn serde_round_trip() {
+		let mut bloom = Bloom::default();
+		bloom.accrue(b"a");
+
+		let json = serde_json::to_string(&bloom).unwrap();
+		assert_eq!(json, alloc::format!("\"{:#x}\"", bloom));
+		assert_eq!(serde_json::from_str::<Bloom>(&json).unwrap(), bloom);
+		assert!(serde_json::from_str::<Bloom>("\"0x00\"").is_err());
+	}
+}
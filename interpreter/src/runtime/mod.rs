@@ -1,9 +1,11 @@
-use alloc::{rc::Rc, vec::Vec};
+pub mod bloom;
+
+use alloc::vec::Vec;
 
 use primitive_types::{H160, H256, U256};
-use sha3::{Digest, Keccak256};
 
-use crate::error::ExitError;
+pub use self::bloom::Bloom;
+use crate::{error::ExitError, utils::keccak256, Shared};
 
 /// Gas state.
 pub trait GasState {
@@ -16,9 +18,15 @@ pub struct RuntimeState {
 	/// Runtime context.
 	pub context: Context,
 	/// Transaction context.
-	pub transaction_context: Rc<TransactionContext>,
+	pub transaction_context: Shared<TransactionContext>,
 	/// Return data buffer.
 	pub retbuf: Vec<u8>,
+	/// Resource limits of the frame, over the ones of the config.
+	pub limits: FrameLimits,
+	/// Address authorized by `AUTH`, used as the caller of `AUTHCALL`. See
+	/// [EIP-3074](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-3074.md).
+	#[cfg(feature = "eip3074")]
+	pub authorized: Option<H160>,
 }
 
 impl AsRef<Self> for RuntimeState {
@@ -56,6 +64,102 @@ pub struct TransactionContext {
 	pub gas_price: U256,
 	/// Origin.
 	pub origin: H160,
+	/// Versioned hashes of the blobs of the transaction (see EIP-4844).
+	pub blob_hashes: Vec<H256>,
+	/// Blob base fee of the block (see EIP-4844).
+	pub blob_base_fee: U256,
+	/// Whether the transaction is a protocol system call.
+	pub is_system: bool,
+	/// Whether `SELFDESTRUCT` only deletes accounts created in the
+	/// transaction (see EIP-6780).
+	pub selfdestruct_only_created: bool,
+}
+
+/// Resource limits of a frame, carried in its [RuntimeState]. Unset limits
+/// are the ones of the config. This lets a host grant different budgets to
+/// different frames, for example tighter limits to simulated untrusted code
+/// than to system calls.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FrameLimits {
+	/// Stack limit of the machine of the frame.
+	pub stack_limit: Option<usize>,
+	/// Memory limit of the machine of the frame.
+	pub memory_limit: Option<usize>,
+	/// Maximum size of the data returned to the frame by a sub-call. A
+	/// sub-call returning more fails.
+	pub retbuf_limit: Option<usize>,
+}
+
+impl FrameLimits {
+	/// No limits over the ones of the config.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			stack_limit: None,
+			memory_limit: None,
+			retbuf_limit: None,
+		}
+	}
+}
+
+// Limits are encoded as `u64`, since `usize` differs between the host and the
+// runtime of a Substrate chain.
+#[cfg(feature = "scale")]
+impl scale_codec::Encode for FrameLimits {
+	fn encode_to<T: scale_codec::Output + ?Sized>(&self, dest: &mut T) {
+		let limit = |limit: Option<usize>| limit.map(|limit| limit as u64);
+		limit(self.stack_limit).encode_to(dest);
+		limit(self.memory_limit).encode_to(dest);
+		limit(self.retbuf_limit).encode_to(dest);
+	}
+}
+
+#[cfg(feature = "scale")]
+impl scale_codec::Decode for FrameLimits {
+	fn decode<I: scale_codec::Input>(input: &mut I) -> Result<Self, scale_codec::Error> {
+		let mut limit = || -> Result<Option<usize>, scale_codec::Error> {
+			Option::<u64>::decode(input)?
+				.map(|limit| {
+					usize::try_from(limit).map_err(|_| "frame limit does not fit in usize".into())
+				})
+				.transpose()
+		};
+		Ok(Self {
+			stack_limit: limit()?,
+			memory_limit: limit()?,
+			retbuf_limit: limit()?,
+		})
+	}
+}
+
+#[cfg(feature = "scale")]
+impl scale_info::TypeInfo for FrameLimits {
+	type Identity = Self;
+
+	fn type_info() -> scale_info::Type {
+		use scale_info::{build::Fields, Path, Type};
+
+		Type::builder()
+			.path(Path::new("FrameLimits", module_path!()))
+			.composite(
+				Fields::named()
+					.field(|f| {
+						f.ty::<Option<u64>>()
+							.name("stack_limit")
+							.type_name("Option<usize>")
+					})
+					.field(|f| {
+						f.ty::<Option<u64>>()
+							.name("memory_limit")
+							.type_name("Option<usize>")
+					})
+					.field(|f| {
+						f.ty::<Option<u64>>()
+							.name("retbuf_limit")
+							.type_name("Option<usize>")
+					}),
+			)
+	}
 }
 
 /// Transfer from source to target, with given value.
@@ -70,7 +174,12 @@ pub struct Transfer {
 }
 
 /// Log
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Log {
 	pub address: H160,
 	pub topics: Vec<H256>,
@@ -84,7 +193,7 @@ pub enum SetCodeOrigin {
 	Subcall(H160),
 }
 
-#[auto_impl::auto_impl(&, Box)]
+#[auto_impl::auto_impl(&, &mut, Box)]
 pub trait RuntimeEnvironment {
 	/// Get environmental block hash.
 	fn block_hash(&self, number: U256) -> H256;
@@ -102,11 +211,13 @@ pub trait RuntimeEnvironment {
 	fn block_gas_limit(&self) -> U256;
 	/// Environmental block base fee.
 	fn block_base_fee_per_gas(&self) -> U256;
+	/// Environmental excess blob gas (see EIP-4844).
+	fn block_excess_blob_gas(&self) -> U256;
 	/// Get environmental chain ID.
 	fn chain_id(&self) -> U256;
 }
 
-#[auto_impl::auto_impl(&, Box)]
+#[auto_impl::auto_impl(&, &mut, Box)]
 pub trait RuntimeBaseBackend {
 	/// Get balance of address.
 	fn balance(&self, address: H160) -> U256;
@@ -116,7 +227,7 @@ pub trait RuntimeBaseBackend {
 	}
 	/// Get code hash of address.
 	fn code_hash(&self, address: H160) -> H256 {
-		H256::from_slice(&Keccak256::digest(&self.code(address)[..]))
+		keccak256(&self.code(address)[..])
 	}
 	/// Get code of address.
 	fn code(&self, address: H160) -> Vec<u8>;
@@ -139,6 +250,8 @@ pub trait RuntimeBackend: RuntimeBaseBackend {
 	fn original_storage(&self, address: H160, index: H256) -> H256;
 	/// Check whether an address has already been deleted.
 	fn deleted(&self, address: H160) -> bool;
+	/// Check whether an address has been created in the current transaction.
+	fn created_in_transaction(&self, address: H160) -> bool;
 	/// Checks if the address or (address, index) pair has been previously accessed.
 	fn is_cold(&self, address: H160, index: Option<H256>) -> bool;
 	fn is_hot(&self, address: H160, index: Option<H256>) -> bool {
@@ -158,6 +271,8 @@ pub trait RuntimeBackend: RuntimeBaseBackend {
 	) -> Result<(), ExitError>;
 	/// Create a log owned by address with given topics and data.
 	fn log(&mut self, log: Log) -> Result<(), ExitError>;
+	/// Mark an address as created in the current transaction.
+	fn mark_create(&mut self, address: H160);
 	/// Mark an address to be deleted.
 	fn mark_delete(&mut self, address: H160);
 	/// Fully delete storages of an account.
@@ -182,3 +297,22 @@ pub trait RuntimeBackend: RuntimeBaseBackend {
 	/// Increase the nonce value.
 	fn inc_nonce(&mut self, address: H160) -> Result<(), ExitError>;
 }
+
+#[cfg(all(test, feature = "scale"))]
+mod tests {
+	use scale_codec::{Decode, Encode};
+
+	use super::*;
+
+	#[test]
+	fn frame_limits_scale_round_trip() {
+		let limits = FrameLimits {
+			stack_limit: Some(1024),
+			memory_limit: None,
+			retbuf_limit: Some(0),
+		};
+		let encoded = limits.encode();
+		assert_eq!(encoded, (Some(1024u64), None::<u64>, Some(0u64)).encode());
+		assert_eq!(FrameLimits::decode(&mut &encoded[..]).unwrap(), limits);
+	}
+}
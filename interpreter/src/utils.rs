@@ -9,6 +9,45 @@ use primitive_types::{H256, U256};
 
 use crate::error::{ExitError, ExitFatal};
 
+/// Keccak-256 hash of `data`.
+#[must_use]
+pub fn keccak256(data: &[u8]) -> H256 {
+	keccak256_parts(&[data])
+}
+
+/// Keccak-256 hash of the concatenation of `parts`.
+///
+/// All hashing of the EVM, such as `SHA3`, code hashes and create addresses,
+/// goes through it. It is computed by `tiny-keccak` with the `tiny-keccak`
+/// feature, and by `sha3` otherwise, which uses assembly on supported
+/// platforms with the `keccak-asm` feature.
+#[must_use]
+pub fn keccak256_parts(parts: &[&[u8]]) -> H256 {
+	#[cfg(feature = "tiny-keccak")]
+	{
+		use tiny_keccak::{Hasher, Keccak};
+
+		let mut hasher = Keccak::v256();
+		for part in parts {
+			hasher.update(part);
+		}
+		let mut ret = H256::default();
+		hasher.finalize(&mut ret[..]);
+		ret
+	}
+
+	#[cfg(not(feature = "tiny-keccak"))]
+	{
+		use sha3::{Digest, Keccak256};
+
+		let mut hasher = Keccak256::new();
+		for part in parts {
+			hasher.update(part);
+		}
+		H256::from_slice(hasher.finalize().as_slice())
+	}
+}
+
 /// Convert [U256] into [H256].
 #[must_use]
 pub fn u256_to_h256(v: U256) -> H256 {
@@ -195,4 +234,18 @@ mod tests {
 		assert_eq!(one_hundred / minus_one, neg_one_hundred);
 		assert_eq!(one_hundred / two, fifty);
 	}
+
+	#[test]
+	fn keccak() {
+		let empty = H256::from_slice(
+			&hex::decode("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+				.unwrap(),
+		);
+		assert_eq!(keccak256(&[]), empty);
+		assert_eq!(keccak256_parts(&[]), empty);
+		assert_eq!(
+			keccak256_parts(&[b"hello ", b"world"]),
+			keccak256(b"hello world")
+		);
+	}
 }
@@ -1,10 +1,8 @@
-use std::rc::Rc;
-
 use evm_interpreter::{
 	error::{Capture, ExitSucceed},
 	etable::Etable,
 	machine::Machine,
-	EtableInterpreter, RunInterpreter,
+	EtableInterpreter, RunInterpreter, Shared,
 };
 
 static ETABLE: Etable<(), (), ()> = Etable::core();
@@ -16,13 +14,24 @@ macro_rules! ret_test {
 			let code = hex::decode($code).unwrap();
 			let data = hex::decode($data).unwrap();
 
-			let machine = Machine::new(Rc::new(code), Rc::new(data), 1024, 10000, ());
-			let mut vm = EtableInterpreter::new(machine, &ETABLE);
-			assert_eq!(
-				vm.run(&mut ()),
-				Capture::Exit(Ok(ExitSucceed::Returned.into()))
-			);
-			assert_eq!(vm.retval, hex::decode($ret).unwrap());
+			for fusion in [false, true] {
+				let machine = Machine::new(
+					Shared::new(code.clone()),
+					Shared::new(data.clone()),
+					1024,
+					10000,
+					(),
+				);
+				let mut vm = EtableInterpreter::new(machine, &ETABLE);
+				if fusion {
+					vm = vm.with_fusion();
+				}
+				assert_eq!(
+					vm.run(&mut ()),
+					Capture::Exit(Ok(ExitSucceed::Returned.into()))
+				);
+				assert_eq!(vm.retval, hex::decode($ret).unwrap());
+			}
 		}
 	};
 }
@@ -54,3 +63,22 @@ ret_test!(
 	"61047ff40000000000000000000000000000000000000000000000000000000000000010",
 	"00000000000000000000000000000000000000000000000000000000000003db"
 );
+
+#[test]
+fn memory_stress() {
+	// MSTORE8 1 at 0x0fffff, then RETURN 0x100000 bytes from 0.
+	let code = hex::decode("6001620fffff53621000006000f3").unwrap();
+
+	let machine = Machine::new(
+		Shared::new(code),
+		Shared::new(Vec::new()),
+		1024,
+		0x100000,
+		(),
+	);
+	let mut vm = EtableInterpreter::new(machine, &ETABLE);
+	assert_eq!(vm.run(&mut ()), Capture::Exit(Ok(ExitSucceed::Returned)));
+	assert_eq!(vm.retval.len(), 0x100000);
+	assert!(vm.retval[..0x0fffff].iter().all(|b| *b == 0));
+	assert_eq!(vm.retval[0x0fffff], 1);
+}
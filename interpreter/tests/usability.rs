@@ -1,15 +1,18 @@
-use std::rc::Rc;
-
 use evm_interpreter::{
-	error::{CallCreateTrap, Capture, ExitError, ExitSucceed},
-	etable::{Control, Etable},
-	machine::Machine,
+	asm::{AsmError, Assembler},
+	disasm::disassemble,
+	eof::{validate, ContainerKind, EofError, EofErrorKind},
+	error::{
+		CallCreateTrap, CallCreateTrapData, CallScheme, Capture, ExitError, ExitFatal, ExitSucceed,
+	},
+	etable::{Control, Etable, HookedEtable, InstructionCounter},
+	machine::{Machine, Memory, MemoryStorage},
 	opcode::Opcode,
 	runtime::{
-		Context, Log, RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment, RuntimeState,
-		SetCodeOrigin, TransactionContext,
+		Context, FrameLimits, Log, RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment,
+		RuntimeState, SetCodeOrigin, TransactionContext,
 	},
-	EtableInterpreter, RunInterpreter,
+	EtableInterpreter, Fused, Fusions, Interpreter, RunInterpreter, Shared, StepInterpreter,
 };
 use primitive_types::{H160, H256, U256};
 
@@ -30,7 +33,7 @@ fn etable_wrap() {
 		}
 	});
 
-	let machine = Machine::new(Rc::new(code), Rc::new(data), 1024, 10000, ());
+	let machine = Machine::new(Shared::new(code), Shared::new(data), 1024, 10000, ());
 	let mut vm = EtableInterpreter::new(machine, &wrapped_etable);
 	let result = vm.run(&mut ());
 	assert_eq!(result, Capture::Exit(Ok(ExitSucceed::Returned)));
@@ -60,12 +63,60 @@ fn etable_wrap2() {
 		},
 	);
 
-	let machine = Machine::new(Rc::new(code), Rc::new(data), 1024, 10000, ());
+	let machine = Machine::new(Shared::new(code), Shared::new(data), 1024, 10000, ());
 	let mut vm = EtableInterpreter::new(machine, &wrapped_etable);
 	let result = vm.run(&mut ());
 	assert_eq!(result, Capture::Trap(Opcode(0x50)));
 }
 
+/// Memory stored in a single buffer.
+#[derive(Clone, Debug, Default)]
+struct FlatMemory(Vec<u8>);
+
+impl MemoryStorage for FlatMemory {
+	fn read(&self, offset: usize, buf: &mut [u8]) {
+		for (index, byte) in buf.iter_mut().enumerate() {
+			*byte = self.0.get(offset + index).copied().unwrap_or(0);
+		}
+	}
+
+	fn write(&mut self, offset: usize, value: &[u8]) {
+		let end = offset + value.len();
+		if self.0.len() < end {
+			self.0.resize(end, 0);
+		}
+		self.0[offset..end].copy_from_slice(value);
+	}
+
+	fn chunk(&self, offset: usize, len: usize) -> &[u8] {
+		static ZEROS: [u8; 32] = [0; 32];
+
+		if offset < self.0.len() {
+			&self.0[offset..self.0.len().min(offset + len)]
+		} else {
+			&ZEROS[..len.min(ZEROS.len())]
+		}
+	}
+
+	fn clear(&mut self) {
+		self.0.clear();
+	}
+}
+
+#[test]
+fn etable_memory_storage() {
+	let code = hex::decode(CODE1).unwrap();
+	let data = hex::decode(DATA1).unwrap();
+
+	let etable = Etable::<_, _, Opcode, FlatMemory>::core();
+	let memory = Memory::with_storage(10000, FlatMemory::default());
+	let machine = Machine::with_memory(Shared::new(code), Shared::new(data), 1024, memory, ());
+	let mut vm = EtableInterpreter::new(machine, &etable);
+	let result = vm.run(&mut ());
+	assert_eq!(result, Capture::Exit(Ok(ExitSucceed::Returned)));
+	assert_eq!(vm.retval, hex::decode(RET1).unwrap());
+}
+
 pub struct UnimplementedHandler;
 
 impl RuntimeEnvironment for UnimplementedHandler {
@@ -93,6 +144,9 @@ impl RuntimeEnvironment for UnimplementedHandler {
 	fn block_base_fee_per_gas(&self) -> U256 {
 		unimplemented!()
 	}
+	fn block_excess_blob_gas(&self) -> U256 {
+		unimplemented!()
+	}
 	fn chain_id(&self) -> U256 {
 		unimplemented!()
 	}
@@ -135,6 +189,9 @@ impl RuntimeBackend for UnimplementedHandler {
 	fn deleted(&self, _address: H160) -> bool {
 		unimplemented!()
 	}
+	fn created_in_transaction(&self, _address: H160) -> bool {
+		unimplemented!()
+	}
 	fn is_cold(&self, _address: H160, _index: Option<H256>) -> bool {
 		unimplemented!()
 	}
@@ -157,6 +214,9 @@ impl RuntimeBackend for UnimplementedHandler {
 	fn log(&mut self, _log: Log) -> Result<(), ExitError> {
 		unimplemented!()
 	}
+	fn mark_create(&mut self, _address: H160) {
+		unimplemented!()
+	}
 	fn mark_delete(&mut self, _address: H160) {
 		unimplemented!()
 	}
@@ -200,8 +260,8 @@ fn etable_runtime() {
 	let mut handler = UnimplementedHandler;
 
 	let machine = Machine::new(
-		Rc::new(code),
-		Rc::new(data),
+		Shared::new(code),
+		Shared::new(data),
 		1024,
 		10000,
 		RuntimeState {
@@ -213,9 +273,16 @@ fn etable_runtime() {
 			transaction_context: TransactionContext {
 				gas_price: U256::default(),
 				origin: H160::default(),
+				blob_hashes: Vec::new(),
+				blob_base_fee: U256::default(),
+				is_system: false,
+				selfdestruct_only_created: false,
 			}
 			.into(),
 			retbuf: Vec::new(),
+			limits: FrameLimits::default(),
+			#[cfg(feature = "eip3074")]
+			authorized: None,
 		},
 	);
 	let mut vm = EtableInterpreter::new(machine, &RUNTIME_ETABLE);
@@ -224,3 +291,504 @@ fn etable_runtime() {
 	assert_eq!(res, Ok(ExitSucceed::Returned));
 	assert_eq!(vm.retval, hex::decode(RET1).unwrap());
 }
+
+#[test]
+fn trap_inspection() {
+	let code = Assembler::new()
+		.push(0xaabbu64)
+		.push(0u64)
+		.op(Opcode::MSTORE)
+		.push(0u64)
+		.push(0u64)
+		.push(2u64)
+		.push(30u64)
+		.push(5u64)
+		.push(0x1234u64)
+		.push(1000u64)
+		.op(Opcode::CALL)
+		.assemble()
+		.unwrap();
+	let mut handler = UnimplementedHandler;
+
+	let machine = Machine::new(
+		Shared::new(code),
+		Shared::new(Vec::new()),
+		1024,
+		10000,
+		RuntimeState {
+			context: Context {
+				address: H160::from_low_u64_be(1),
+				caller: H160::default(),
+				apparent_value: U256::default(),
+			},
+			transaction_context: TransactionContext {
+				gas_price: U256::default(),
+				origin: H160::default(),
+				blob_hashes: Vec::new(),
+				blob_base_fee: U256::default(),
+				is_system: false,
+				selfdestruct_only_created: false,
+			}
+			.into(),
+			retbuf: Vec::new(),
+			limits: FrameLimits::default(),
+			#[cfg(feature = "eip3074")]
+			authorized: None,
+		},
+	);
+	let mut vm = EtableInterpreter::new(machine, &RUNTIME_ETABLE);
+
+	let trap = match vm.run(&mut handler) {
+		Capture::Trap(trap) => trap,
+		Capture::Exit(result) => panic!("unexpected exit {:?}", result),
+	};
+	assert_eq!(trap, CallCreateTrap::Call);
+
+	let data = match CallCreateTrapData::peek_from(trap, vm.machine()).unwrap() {
+		CallCreateTrapData::Call(data) => data,
+		CallCreateTrapData::Create(_) => panic!("unexpected create"),
+	};
+	assert_eq!(data.scheme, CallScheme::Call);
+	assert_eq!(data.target, H160::from_low_u64_be(0x1234));
+	assert_eq!(data.gas, U256::from(1000));
+	assert_eq!(&data.input[..], &[0xaa, 0xbb]);
+	let transfer = data.transfer.unwrap();
+	assert_eq!(transfer.source, H160::from_low_u64_be(1));
+	assert_eq!(transfer.value, U256::from(5));
+
+	// Peeking consumes nothing.
+	assert_eq!(vm.machine().stack.len(), 7);
+	let consumed = match CallCreateTrapData::new_from(trap, vm.machine_mut()).unwrap() {
+		CallCreateTrapData::Call(data) => data,
+		CallCreateTrapData::Create(_) => panic!("unexpected create"),
+	};
+	assert_eq!(consumed.target, data.target);
+	assert_eq!(consumed.input, data.input);
+	assert_eq!(vm.machine().stack.len(), 0);
+}
+
+#[test]
+fn etable_instruction_limit() {
+	// JUMPDEST PUSH1 0x00 JUMP
+	let code = hex::decode("5b600056").unwrap();
+
+	let etable = InstructionCounter::new(Etable::<(), (), Opcode>::core(), Some(100));
+	let machine = Machine::new(Shared::new(code), Shared::new(Vec::new()), 1024, 10000, ());
+	let mut vm = EtableInterpreter::new(machine, &etable);
+	let result = vm.run(&mut ());
+	assert_eq!(result, Capture::Exit(Err(ExitError::InstructionLimit)));
+	assert_eq!(etable.count(), 100);
+}
+
+#[test]
+fn fusions() {
+	// PUSH1 10, JUMPDEST, DUP1, ISZERO, PUSH1 18, JUMPI, PUSH1 1, PUSH1 0, ADD,
+	// SWAP1, SUB, PUSH1 2, JUMP, JUMPDEST, STOP.
+	let code = hex::decode("600a5b8015601257600160000190036002565b00").unwrap();
+	let fusions = Fusions::new(&code);
+
+	assert_eq!(
+		fusions.get(4),
+		Some(Fused::IsZeroPushJumpi {
+			push: Opcode::PUSH1,
+			dest: U256::from(18),
+		})
+	);
+	assert_eq!(
+		fusions.get(8),
+		Some(Fused::PushPushAdd {
+			a: (Opcode::PUSH1, U256::one()),
+			b: (Opcode::PUSH1, U256::zero()),
+		})
+	);
+	assert_eq!(
+		fusions.get(15),
+		Some(Fused::Push1Jump {
+			dest: U256::from(2)
+		})
+	);
+	assert_eq!(fusions.get(0), None);
+	assert_eq!(fusions.get(10), None);
+}
+
+#[test]
+fn interpreter_inspection() {
+	fn inspect<I: Interpreter<State = ()>>(vm: &I) -> (Option<Opcode>, usize, usize, usize) {
+		(
+			vm.opcode(),
+			vm.position(),
+			vm.stack().len(),
+			vm.memory().len(),
+		)
+	}
+
+	// PUSH1 1, PUSH1 0, MSTORE, STOP.
+	let code = hex::decode("600160005200").unwrap();
+	let etable = Etable::<(), (), Opcode>::core();
+	let machine = Machine::new(Shared::new(code), Shared::new(Vec::new()), 1024, 10000, ());
+	let mut vm = EtableInterpreter::new(machine, &etable);
+
+	assert_eq!(inspect(&vm), (Some(Opcode::PUSH1), 0, 0, 0));
+	vm.step(&mut ()).unwrap();
+	vm.step(&mut ()).unwrap();
+	assert_eq!(inspect(&vm), (Some(Opcode::MSTORE), 4, 2, 0));
+	assert_eq!(vm.stack().peek(1).unwrap(), H256::from_low_u64_be(1));
+	vm.step(&mut ()).unwrap();
+	assert_eq!(inspect(&vm), (Some(Opcode::STOP), 5, 0, 32));
+	assert_eq!(
+		vm.step(&mut ()),
+		Err(Capture::Exit(Ok(ExitSucceed::Stopped)))
+	);
+	assert_eq!(inspect(&vm).0, None);
+}
+
+#[test]
+fn etable_hooked() {
+	use std::cell::RefCell;
+
+	let code = Assembler::new()
+		.push(1)
+		.push(2)
+		.op(Opcode::ADD)
+		.op(Opcode::STOP)
+		.assemble()
+		.unwrap();
+	let events = RefCell::new(Vec::new());
+
+	let etable = HookedEtable::new(
+		Etable::<(), (), Opcode>::core(),
+		|machine: &Machine<()>, opcode, position| {
+			events
+				.borrow_mut()
+				.push(("before", opcode, position, machine.stack.len()))
+		},
+		|machine: &Machine<()>, opcode, position| {
+			events
+				.borrow_mut()
+				.push(("after", opcode, position, machine.stack.len()))
+		},
+	);
+	let machine = Machine::new(Shared::new(code), Shared::new(Vec::new()), 1024, 10000, ());
+	let mut vm = EtableInterpreter::new(machine, &etable).with_fusion();
+	let result = vm.run(&mut ());
+	assert_eq!(result, Capture::Exit(Ok(ExitSucceed::Stopped)));
+
+	assert_eq!(
+		events.into_inner(),
+		vec![
+			("before", Opcode::PUSH1, 0, 0),
+			("after", Opcode::PUSH1, 0, 1),
+			("before", Opcode::PUSH1, 2, 1),
+			("after", Opcode::PUSH1, 2, 2),
+			("before", Opcode::ADD, 4, 2),
+			("after", Opcode::ADD, 4, 1),
+			("before", Opcode::STOP, 5, 1),
+			("after", Opcode::STOP, 5, 1),
+		]
+	);
+}
+
+#[test]
+fn etable_fusion_only_standard() {
+	fn eval_mul_instead(
+		machine: &mut Machine<()>,
+		_handle: &mut (),
+		_opcode: Opcode,
+		_position: usize,
+	) -> Control<Opcode> {
+		let a = machine.stack.pop().unwrap();
+		let b = machine.stack.pop().unwrap();
+		let product = U256::from_big_endian(&a[..]) * U256::from_big_endian(&b[..]);
+		let mut word = H256::default();
+		product.to_big_endian(&mut word[..]);
+		machine.stack.push(word).unwrap();
+		Control::Continue
+	}
+
+	// `PUSH1 2 PUSH1 3 ADD` is fused into a single superinstruction.
+	let code = hex::decode("6002600301").unwrap();
+	let run = |etable: &Etable<(), (), Opcode>| {
+		let machine = Machine::new(
+			Shared::new(code.clone()),
+			Shared::new(Vec::new()),
+			1024,
+			10000,
+			(),
+		);
+		let mut vm = EtableInterpreter::new(machine, etable).with_fusion();
+		assert_eq!(vm.run(&mut ()), Capture::Exit(Ok(ExitSucceed::Stopped)));
+		vm.stack().peek(0).unwrap()
+	};
+
+	let standard = Etable::core();
+	assert!(standard.is_standard());
+	assert_eq!(run(&standard), H256::from_low_u64_be(5));
+
+	let mut custom = Etable::core();
+	custom[Opcode::ADD.as_usize()] = eval_mul_instead;
+	assert!(!custom.is_standard());
+	assert_eq!(run(&custom), H256::from_low_u64_be(6));
+}
+
+#[test]
+fn etable_run_budget() {
+	let code = Assembler::new()
+		.label("loop")
+		.jump("loop")
+		.assemble()
+		.unwrap();
+
+	let etable = InstructionCounter::new(Etable::<(), (), Opcode>::core(), Some(100));
+	let machine = Machine::new(Shared::new(code), Shared::new(Vec::new()), 1024, 10000, ());
+	let mut vm = EtableInterpreter::new(machine, &etable);
+
+	for slice in 1..=10 {
+		assert_eq!(vm.run_budget(&mut (), 10), None);
+		assert_eq!(etable.count(), slice * 10);
+	}
+	assert_eq!(
+		vm.run_budget(&mut (), 10),
+		Some(Capture::Exit(Err(ExitError::InstructionLimit)))
+	);
+	assert_eq!(
+		vm.run_budget(&mut (), 10),
+		Some(Capture::Exit(ExitFatal::AlreadyExited.into()))
+	);
+}
+
+#[test]
+fn etable_introspection() {
+	fn eval_custom(
+		_machine: &mut Machine<()>,
+		_handle: &mut (),
+		_opcode: Opcode,
+		_position: usize,
+	) -> Control<Opcode> {
+		Control::Continue
+	}
+
+	let core = Etable::<(), (), Opcode>::core();
+	let mut custom = Etable::<(), (), Opcode>::core();
+	custom[0xc0] = eval_custom;
+	custom[Opcode::ADD.as_usize()] = eval_custom;
+
+	assert!(core.defined().any(|opcode| opcode == Opcode::MSTORE));
+	assert!(core.defined().all(|opcode| opcode != Opcode(0xc0)));
+	assert_eq!(custom.defined().count(), core.defined().count() + 1);
+	assert_eq!(
+		custom.overridden(&core).collect::<Vec<_>>(),
+		vec![Opcode::ADD, Opcode(0xc0)]
+	);
+
+	let description = Etable::describe(&[("core", &core), ("custom", &custom)]);
+	assert!(description.starts_with("0x00 STOP           core, custom\n"));
+	assert!(description.contains("\n0xc0 UNKNOWN        custom\n"));
+}
+
+#[test]
+fn disassembler() {
+	// PUSH1 0x80, PUSH1 0x40, MSTORE, 0xc0, PUSH2 0x01 (truncated).
+	let code = hex::decode("6080604052c06101").unwrap();
+	let instructions = disassemble(&code);
+	assert!(!instructions.is_eof());
+	assert_eq!(
+		instructions
+			.map(|instruction| (instruction.offset, instruction.to_string()))
+			.collect::<Vec<_>>(),
+		vec![
+			(0, "PUSH1 0x80".to_string()),
+			(2, "PUSH1 0x40".to_string()),
+			(4, "MSTORE".to_string()),
+			(5, "UNKNOWN(0xc0)".to_string()),
+			(6, "PUSH2 0x01".to_string()),
+		]
+	);
+
+	// EOF container with one code section: PUSH1 0x01, RJUMPV [0, 1], STOP,
+	// STOP.
+	let code = hex::decode("ef0001010004020001000aff000000008000016001e201000000010000").unwrap();
+	let instructions = disassemble(&code);
+	assert!(instructions.is_eof());
+	assert_eq!(instructions.sections().len(), 1);
+	assert_eq!(instructions.sections()[0], 19..29);
+	assert_eq!(
+		instructions
+			.map(|instruction| (instruction.offset, instruction.to_string()))
+			.collect::<Vec<_>>(),
+		vec![
+			(19, "PUSH1 0x01".to_string()),
+			(21, "RJUMPV 0x0100000001".to_string()),
+			(27, "STOP".to_string()),
+			(28, "STOP".to_string()),
+		]
+	);
+}
+
+#[test]
+fn assembler() {
+	let code = Assembler::new()
+		.push(0)
+		.push(0x1234)
+		.push(U256::MAX)
+		.label("loop")
+		.op(Opcode::DUP1)
+		.jumpi("loop")
+		.jump("end")
+		.label("end")
+		.assemble()
+		.unwrap();
+
+	let mut expected = hex::decode("5f6112347f").unwrap();
+	expected.extend_from_slice(&[0xff; 32]);
+	expected.extend_from_slice(&hex::decode("5b806100255761002f565b").unwrap());
+	assert_eq!(code, expected);
+
+	assert_eq!(
+		Assembler::new().jump("missing").assemble(),
+		Err(AsmError::UndefinedLabel("missing".into()))
+	);
+	assert_eq!(
+		Assembler::new().label("a").label("a").assemble(),
+		Err(AsmError::DuplicateLabel("a".into()))
+	);
+}
+
+/// EOF container with a single code section.
+fn eof_container(code: &[u8], max_stack_increase: u16, data_size: u16, data: &[u8]) -> Vec<u8> {
+	let mut container = vec![0xef, 0x00, 0x01, 0x01, 0x00, 0x04, 0x02, 0x00, 0x01];
+	container.extend_from_slice(&(code.len() as u16).to_be_bytes());
+	container.push(0xff);
+	container.extend_from_slice(&data_size.to_be_bytes());
+	container.extend_from_slice(&[0x00, 0x00, 0x80]);
+	container.extend_from_slice(&max_stack_increase.to_be_bytes());
+	container.extend_from_slice(code);
+	container.extend_from_slice(data);
+	container
+}
+
+#[test]
+fn eof_validation() {
+	// PUSH1 0x01, RJUMPV [0, 1], STOP, STOP.
+	let code = hex::decode("6001e201000000010000").unwrap();
+	let header = validate(&eof_container(&code, 1, 0, &[]), ContainerKind::Runtime).unwrap();
+	assert_eq!(header.code_sections, vec![19..29]);
+	assert_eq!(
+		validate(&eof_container(&code, 1, 0, &[]), ContainerKind::Initcode),
+		Err(EofError {
+			kind: EofErrorKind::IncompatibleContainerKind,
+			offset: 27,
+		})
+	);
+	assert_eq!(
+		validate(&eof_container(&code, 2, 0, &[]), ContainerKind::Runtime).map_err(|err| err.kind),
+		Err(EofErrorKind::InvalidMaxStackIncrease)
+	);
+
+	let invalid = [
+		// ADD, STOP.
+		("0100", EofErrorKind::StackUnderflow),
+		// PUSH0, JUMP.
+		("5f56", EofErrorKind::UndefinedInstruction),
+		// PUSH0, POP.
+		("5f50", EofErrorKind::MissingStopOpcode),
+		// RJUMP 1, STOP, STOP.
+		("e000010000", EofErrorKind::UnreachableInstructions),
+		// PUSH0, RJUMP -4.
+		("5fe0fffc", EofErrorKind::StackHeightMismatch),
+		// RJUMP -1, into its own immediate.
+		("e0ffff", EofErrorKind::InvalidRjumpDestination),
+	];
+	for (code, kind) in invalid {
+		let code = hex::decode(code).unwrap();
+		assert_eq!(
+			validate(&eof_container(&code, 1, 0, &[]), ContainerKind::Runtime)
+				.map_err(|err| err.kind),
+			Err(kind)
+		);
+	}
+
+	assert_eq!(
+		validate(&eof_container(&[0x00], 0, 1, &[]), ContainerKind::Runtime),
+		Err(EofError {
+			kind: EofErrorKind::TopLevelContainerTruncated,
+			offset: 20,
+		})
+	);
+	assert_eq!(
+		validate(
+			&eof_container(&[0x00], 0, 0, &[0x01]),
+			ContainerKind::Runtime
+		)
+		.map_err(|err| err.kind),
+		Err(EofErrorKind::InvalidSectionBodiesSize)
+	);
+	assert_eq!(
+		EofErrorKind::from_name("STACK_UNDERFLOW"),
+		Some(EofErrorKind::StackUnderflow)
+	);
+}
+
+#[test]
+fn stack_has_fixed_capacity() {
+	use evm_interpreter::machine::{Stack, STACK_CAPACITY};
+
+	// Slots are only allocated on the first push.
+	const EMPTY: Stack = Stack::new(1024);
+	assert!(EMPTY.data().is_empty());
+
+	// Limits above the capacity are capped to it.
+	let mut stack = Stack::new(usize::MAX);
+	assert_eq!(stack.limit(), STACK_CAPACITY);
+	for value in 0..STACK_CAPACITY as u64 {
+		stack.push(H256::from_low_u64_be(value)).unwrap();
+	}
+	assert_eq!(stack.len(), STACK_CAPACITY);
+	assert_eq!(
+		stack.peek(0).unwrap(),
+		H256::from_low_u64_be(STACK_CAPACITY as u64 - 1)
+	);
+	assert_eq!(stack.data()[0], H256::zero());
+	assert_eq!(
+		stack.push(H256::zero()),
+		Err(evm_interpreter::error::ExitException::StackOverflow)
+	);
+
+	let mut stack = Stack::new(2);
+	stack.push(H256::zero()).unwrap();
+	stack.push(H256::zero()).unwrap();
+	assert_eq!(
+		stack.push(H256::zero()),
+		Err(evm_interpreter::error::ExitException::StackOverflow)
+	);
+	assert_eq!(stack.len(), 2);
+	assert_eq!(stack.pop(), Ok(H256::zero()));
+	assert_eq!(stack.data(), [H256::zero()]);
+}
+
+#[cfg(feature = "pool")]
+#[test]
+fn stack_reuses_pooled_slots() {
+	use evm_interpreter::machine::{
+		pool::{self, PoolLimits},
+		Stack,
+	};
+
+	pool::using(PoolLimits::DEFAULT, || {
+		let mut stack = Stack::new(1024);
+		stack.push(H256::repeat_byte(1)).unwrap();
+		let slots = stack.data().as_ptr();
+		drop(stack);
+
+		// The slots are reused, and empty.
+		let mut stack = Stack::new(1024);
+		stack.push(H256::repeat_byte(2)).unwrap();
+		assert_eq!(stack.data().as_ptr(), slots);
+		assert_eq!(stack.data(), [H256::repeat_byte(2)]);
+	});
+
+	// Without pooling, dropped slots are freed.
+	let mut stack = Stack::new(1024);
+	stack.push(H256::zero()).unwrap();
+	drop(stack);
+	assert_eq!(pool::limits(), PoolLimits::NONE);
+}
@@ -0,0 +1,637 @@
+//! Runner of `BlockchainTests`: blocks are decoded from RLP and imported one
+//! by one on top of their parent, which may not be the head of the chain.
+//! Invalid blocks are rejected without changing the chain, and the chain
+//! with the highest total difficulty is the canonical one.
+//!
+//! Berlin and London, and transitions between them, are supported.
+
+use std::{
+	collections::BTreeMap,
+	fs::{self, File},
+	io::BufReader,
+};
+
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment, OverlayedBackend},
+	interpreter::{runtime::Bloom, utils::keccak256},
+	standard::{
+		transact_with_gas, BlockError, Config, Etable, EtableResolver, ForkSchedule, Invoker,
+		Receipt, Transaction, TransactionError,
+	},
+};
+use evm_precompile::StandardPrecompileSet;
+use primitive_types::{H160, H256, U256};
+use rlp::{DecoderError, Rlp, RlpStream};
+
+use crate::{
+	error::{Error, TestError},
+	filter::TestFilter,
+	run::{fork_config, get_short_file_name, in_memory_state, initial_accessed, TestLine},
+	types::{BlockchainTest, Fork, TestCompletionStatus, TestNetwork},
+};
+
+/// Chain ID of the blockchain tests.
+const CHAIN_ID: u64 = 1;
+
+/// Maximum number of ommers of a block.
+const MAX_OMMERS: usize = 2;
+
+/// Maximum distance between a block and its ommers.
+const MAX_OMMER_DEPTH: u64 = 6;
+
+/// Base fee of the first London block (see EIP-1559).
+const INITIAL_BASE_FEE: u64 = 1_000_000_000;
+
+/// Reason a block is rejected.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum BlockImportError {
+	#[error("invalid rlp: {0}")]
+	Rlp(#[from] DecoderError),
+	#[error("unknown parent block")]
+	UnknownParent,
+	#[error("invalid header: {0}")]
+	InvalidHeader(&'static str),
+	#[error("invalid ommer: {0}")]
+	InvalidOmmer(&'static str),
+	#[error("transaction {0}: {1}")]
+	Transaction(usize, TransactionError),
+	#[error("{0}")]
+	Execution(BlockError),
+	#[error("{0} is different from the header")]
+	Mismatch(&'static str),
+}
+
+/// Block header. Fields added after the base fee by later forks are not
+/// decoded.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlockHeader {
+	pub hash: H256,
+	pub parent_hash: H256,
+	pub ommers_hash: H256,
+	pub beneficiary: H160,
+	pub state_root: H256,
+	pub transactions_root: H256,
+	pub receipts_root: H256,
+	pub logs_bloom: Bloom,
+	pub difficulty: U256,
+	pub number: U256,
+	pub gas_limit: U256,
+	pub gas_used: U256,
+	pub timestamp: U256,
+	pub extra_data: Vec<u8>,
+	pub base_fee_per_gas: Option<U256>,
+}
+
+impl BlockHeader {
+	/// Decode a header from its RLP list.
+	pub fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		let item_count = rlp.item_count()?;
+		if item_count < 15 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+
+		let logs_bloom: Vec<u8> = rlp.val_at(6)?;
+		let logs_bloom = Bloom(
+			logs_bloom
+				.try_into()
+				.map_err(|_| DecoderError::RlpInvalidLength)?,
+		);
+
+		Ok(Self {
+			hash: keccak256(rlp.as_raw()),
+			parent_hash: rlp.val_at(0)?,
+			ommers_hash: rlp.val_at(1)?,
+			beneficiary: rlp.val_at(2)?,
+			state_root: rlp.val_at(3)?,
+			transactions_root: rlp.val_at(4)?,
+			receipts_root: rlp.val_at(5)?,
+			logs_bloom,
+			difficulty: rlp.val_at(7)?,
+			number: rlp.val_at(8)?,
+			gas_limit: rlp.val_at(9)?,
+			gas_used: rlp.val_at(10)?,
+			timestamp: rlp.val_at(11)?,
+			extra_data: rlp.val_at(12)?,
+			base_fee_per_gas: if item_count > 15 {
+				Some(rlp.val_at(15)?)
+			} else {
+				None
+			},
+		})
+	}
+}
+
+/// Block, with a body matching its header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Block {
+	pub header: BlockHeader,
+	pub transactions: Vec<Transaction>,
+	pub ommers: Vec<BlockHeader>,
+}
+
+impl Block {
+	/// Decode a block, checking its transactions and ommers against the
+	/// roots of its header.
+	pub fn decode(bytes: &[u8]) -> Result<Self, BlockImportError> {
+		let rlp = Rlp::new(bytes);
+		if rlp.item_count()? != 3 {
+			return Err(DecoderError::RlpIncorrectListLen.into());
+		}
+		let header = BlockHeader::decode(&rlp.at(0)?)?;
+
+		let mut transactions = Vec::new();
+		let mut encoded_transactions = Vec::new();
+		for (index, item) in rlp.at(1)?.iter().enumerate() {
+			// Legacy transactions are RLP lists, typed ones are byte strings.
+			let encoded = if item.is_list() {
+				item.as_raw()
+			} else {
+				item.data()?
+			};
+			transactions.push(
+				Transaction::decode(encoded)
+					.map_err(|err| BlockImportError::Transaction(index, err))?,
+			);
+			encoded_transactions.push(encoded);
+		}
+		if ethereum::util::ordered_trie_root(encoded_transactions) != header.transactions_root {
+			return Err(BlockImportError::Mismatch("transactions root"));
+		}
+
+		let ommers = rlp.at(2)?;
+		if keccak256(ommers.as_raw()) != header.ommers_hash {
+			return Err(BlockImportError::Mismatch("ommers hash"));
+		}
+		let ommers = ommers
+			.iter()
+			.map(|ommer| BlockHeader::decode(&ommer))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		Ok(Self {
+			header,
+			transactions,
+			ommers,
+		})
+	}
+}
+
+/// Validation of a block header against its parent, under the config of the
+/// block, before the block is executed. Usable to add the checks of a seal
+/// engine.
+pub trait HeaderValidator {
+	fn validate(
+		&self,
+		config: &Config,
+		header: &BlockHeader,
+		parent: &BlockHeader,
+	) -> Result<(), BlockImportError>;
+}
+
+/// Checks of the number, timestamp, gas limit, extra data and base fee of a
+/// header. The proof of work is not checked, as in tests with the `NoProof`
+/// seal engine.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StandardHeaderValidator;
+
+impl HeaderValidator for StandardHeaderValidator {
+	fn validate(
+		&self,
+		config: &Config,
+		header: &BlockHeader,
+		parent: &BlockHeader,
+	) -> Result<(), BlockImportError> {
+		if header.number != parent.number + 1 {
+			return Err(BlockImportError::InvalidHeader("number"));
+		}
+		if header.timestamp <= parent.timestamp {
+			return Err(BlockImportError::InvalidHeader("timestamp"));
+		}
+		if header.gas_used > header.gas_limit {
+			return Err(BlockImportError::InvalidHeader("gas used"));
+		}
+
+		// The gas limit of the first London block is bounded around the
+		// doubled limit of its parent, the elasticity of EIP-1559.
+		let parent_gas_limit = if config.eip_1559_enabled && parent.base_fee_per_gas.is_none() {
+			parent.gas_limit * 2
+		} else {
+			parent.gas_limit
+		};
+		let gas_limit_bound = parent_gas_limit / 1024;
+		if header.gas_limit >= parent_gas_limit.saturating_add(gas_limit_bound)
+			|| header.gas_limit <= parent_gas_limit.saturating_sub(gas_limit_bound)
+			|| header.gas_limit < U256::from(5000)
+		{
+			return Err(BlockImportError::InvalidHeader("gas limit"));
+		}
+
+		if header.extra_data.len() > 32 {
+			return Err(BlockImportError::InvalidHeader("extra data"));
+		}
+
+		let base_fee_per_gas = if config.eip_1559_enabled {
+			Some(expected_base_fee(parent))
+		} else {
+			None
+		};
+		if header.base_fee_per_gas != base_fee_per_gas {
+			return Err(BlockImportError::InvalidHeader("base fee"));
+		}
+
+		Ok(())
+	}
+}
+
+/// Base fee of a child of `parent` (see EIP-1559).
+fn expected_base_fee(parent: &BlockHeader) -> U256 {
+	let parent_base_fee = match parent.base_fee_per_gas {
+		Some(base_fee) => base_fee,
+		None => return U256::from(INITIAL_BASE_FEE),
+	};
+	let gas_target = parent.gas_limit / 2;
+	if gas_target.is_zero() {
+		return parent_base_fee;
+	}
+
+	if parent.gas_used > gas_target {
+		let delta = parent_base_fee * (parent.gas_used - gas_target) / gas_target / 8;
+		parent_base_fee + delta.max(U256::one())
+	} else {
+		let delta = parent_base_fee * (gas_target - parent.gas_used) / gas_target / 8;
+		parent_base_fee.saturating_sub(delta)
+	}
+}
+
+/// Imported block, with the state after it.
+struct ChainBlock {
+	header: BlockHeader,
+	state: BTreeMap<H160, InMemoryAccount>,
+	total_difficulty: U256,
+}
+
+/// Chain of imported blocks, including forks.
+struct Chain<'config> {
+	schedule: &'config ForkSchedule,
+	blocks: BTreeMap<H256, ChainBlock>,
+	head: H256,
+}
+
+impl<'config> Chain<'config> {
+	fn new(
+		schedule: &'config ForkSchedule,
+		genesis: BlockHeader,
+		state: BTreeMap<H160, InMemoryAccount>,
+	) -> Self {
+		let head = genesis.hash;
+		let mut blocks = BTreeMap::new();
+		blocks.insert(
+			head,
+			ChainBlock {
+				total_difficulty: genesis.difficulty,
+				header: genesis,
+				state,
+			},
+		);
+
+		Self {
+			schedule,
+			blocks,
+			head,
+		}
+	}
+
+	/// Import a block on top of its parent. The block becomes the head if
+	/// its chain has the highest total difficulty.
+	fn import<V: HeaderValidator>(
+		&mut self,
+		block: Block,
+		validator: &V,
+	) -> Result<(), BlockImportError> {
+		let parent = self
+			.blocks
+			.get(&block.header.parent_hash)
+			.ok_or(BlockImportError::UnknownParent)?;
+		let config = self.config(&block.header);
+		validator.validate(config, &block.header, &parent.header)?;
+		self.validate_ommers(&block)?;
+
+		let state = self.execute(config, &block, parent)?;
+
+		let total_difficulty = parent.total_difficulty + block.header.difficulty;
+		let hash = block.header.hash;
+		if total_difficulty > self.blocks[&self.head].total_difficulty {
+			self.head = hash;
+		}
+		self.blocks.insert(
+			hash,
+			ChainBlock {
+				header: block.header,
+				state,
+				total_difficulty,
+			},
+		);
+
+		Ok(())
+	}
+
+	/// Config of the fork of a block.
+	fn config(&self, header: &BlockHeader) -> &'config Config {
+		self.schedule
+			.config_at(header.number.low_u64(), header.timestamp.low_u64())
+	}
+
+	fn validate_ommers(&self, block: &Block) -> Result<(), BlockImportError> {
+		if block.ommers.len() > MAX_OMMERS {
+			return Err(BlockImportError::InvalidOmmer("too many ommers"));
+		}
+
+		for (index, ommer) in block.ommers.iter().enumerate() {
+			if block.ommers[..index]
+				.iter()
+				.any(|other| other.hash == ommer.hash)
+			{
+				return Err(BlockImportError::InvalidOmmer("duplicate ommer"));
+			}
+			if ommer.number >= block.header.number
+				|| block.header.number - ommer.number > U256::from(MAX_OMMER_DEPTH)
+			{
+				return Err(BlockImportError::InvalidOmmer("depth"));
+			}
+			if self.blocks.contains_key(&ommer.hash) {
+				return Err(BlockImportError::InvalidOmmer("ommer is an ancestor"));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Hashes of the 256 ancestors of a block, by number.
+	fn block_hashes(&self, parent_hash: H256) -> BTreeMap<U256, H256> {
+		let mut block_hashes = BTreeMap::new();
+		let mut hash = parent_hash;
+		while let Some(block) = self.blocks.get(&hash) {
+			block_hashes.insert(block.header.number, hash);
+			if block_hashes.len() == 256 {
+				break;
+			}
+			hash = block.header.parent_hash;
+		}
+		block_hashes
+	}
+
+	/// Execute a block on the state of its parent, and check the result
+	/// against its header.
+	fn execute(
+		&self,
+		config: &Config,
+		block: &Block,
+		parent: &ChainBlock,
+	) -> Result<BTreeMap<H160, InMemoryAccount>, BlockImportError> {
+		let header = &block.header;
+		let mut backend = InMemoryBackend {
+			environment: InMemoryEnvironment {
+				block_hashes: self.block_hashes(header.parent_hash),
+				block_number: header.number,
+				block_coinbase: header.beneficiary,
+				block_timestamp: header.timestamp,
+				block_difficulty: header.difficulty,
+				block_randomness: None,
+				block_gas_limit: header.gas_limit,
+				block_base_fee_per_gas: header.base_fee_per_gas.unwrap_or_default(),
+				block_excess_blob_gas: U256::zero(),
+				chain_id: U256::from(CHAIN_ID),
+			},
+			state: parent.state.clone(),
+		};
+
+		let etable = (
+			Etable::single(evm::standard::eval_gasometer),
+			Etable::runtime(),
+		);
+		let precompiles = StandardPrecompileSet::new(config);
+		let resolver = EtableResolver::new(config, &precompiles, &etable);
+		let invoker = Invoker::new(config, &resolver);
+
+		let mut cumulative_gas_used = U256::zero();
+		let mut receipts = Vec::new();
+		for (index, transaction) in block.transactions.iter().enumerate() {
+			// The supported forks are all after Homestead.
+			let caller = transaction
+				.recover_caller(CHAIN_ID, true)
+				.map_err(|err| BlockImportError::Transaction(index, err))?;
+			let args = transaction.transact_args(caller);
+			if args.gas_limit() > header.gas_limit.saturating_sub(cumulative_gas_used) {
+				return Err(BlockImportError::Execution(BlockError::GasLimitReached(
+					index,
+				)));
+			}
+
+			let mut overlay = OverlayedBackend::new(backend, initial_accessed());
+			let result = transact_with_gas(args, None, &mut overlay, &invoker);
+			let (inner, changeset) = overlay.deconstruct();
+			backend = inner;
+			let (result, gas) = result.map_err(|err| {
+				BlockImportError::Execution(BlockError::InvalidTransaction(index, err))
+			})?;
+			backend.apply_overlayed(&changeset);
+
+			cumulative_gas_used = cumulative_gas_used.saturating_add(gas.effective);
+			receipts.push(Receipt {
+				status: result.is_ok(),
+				gas_used: gas.effective,
+				cumulative_gas_used,
+				logs_bloom: Bloom::from_logs(&changeset.logs),
+				logs: changeset.logs,
+			});
+		}
+
+		apply_rewards(&mut backend.state, block);
+
+		if cumulative_gas_used != header.gas_used {
+			return Err(BlockImportError::Mismatch("gas used"));
+		}
+		let mut logs_bloom = Bloom::default();
+		for receipt in &receipts {
+			logs_bloom.accrue_bloom(&receipt.logs_bloom);
+		}
+		if logs_bloom != header.logs_bloom {
+			return Err(BlockImportError::Mismatch("logs bloom"));
+		}
+		if receipts_root(&block.transactions, &receipts) != header.receipts_root {
+			return Err(BlockImportError::Mismatch("receipts root"));
+		}
+		if crate::hash::state_root(&backend) != header.state_root {
+			return Err(BlockImportError::Mismatch("state root"));
+		}
+
+		Ok(backend.state)
+	}
+}
+
+/// Credit the block reward to the beneficiary of the block and of its ommers.
+fn apply_rewards(state: &mut BTreeMap<H160, InMemoryAccount>, block: &Block) {
+	let reward = U256::from(2) * U256::exp10(18);
+
+	let mut beneficiary_reward = reward;
+	for ommer in &block.ommers {
+		beneficiary_reward += reward / 32;
+
+		let ommer_reward = (U256::from(8) + ommer.number - block.header.number) * reward / 8;
+		state.entry(ommer.beneficiary).or_default().balance += ommer_reward;
+	}
+	state.entry(block.header.beneficiary).or_default().balance += beneficiary_reward;
+}
+
+/// Root of the receipts of a block, typed as their transactions (see
+/// EIP-2718).
+fn receipts_root(transactions: &[Transaction], receipts: &[Receipt]) -> H256 {
+	ethereum::util::ordered_trie_root(transactions.iter().zip(receipts).map(
+		|(transaction, receipt)| {
+			let mut stream = RlpStream::new_list(4);
+			stream.append(&u8::from(receipt.status));
+			stream.append(&receipt.cumulative_gas_used);
+			stream.append(&receipt.logs_bloom.0.to_vec());
+			stream.begin_list(receipt.logs.len());
+			for log in &receipt.logs {
+				stream.begin_list(3);
+				stream.append(&log.address);
+				stream.append_list(&log.topics);
+				stream.append(&log.data);
+			}
+
+			let encoded = stream.out().to_vec();
+			match transaction {
+				Transaction::Legacy(_) => encoded,
+				Transaction::Eip2930(_) => [&[1][..], &encoded].concat(),
+				Transaction::Eip1559(_) => [&[2][..], &encoded].concat(),
+			}
+		},
+	))
+}
+
+/// Schedule of the forks of a test network, if they are all supported.
+fn fork_schedule(network: &str) -> Option<ForkSchedule> {
+	let config = |fork| match fork {
+		Fork::Berlin | Fork::London => fork_config(fork),
+		_ => None,
+	};
+
+	match TestNetwork::from_name(network)? {
+		TestNetwork::Fork(fork) => Some(ForkSchedule::new(config(fork)?)),
+		TestNetwork::Transition { from, to, at } => {
+			Some(ForkSchedule::new(config(from)?).with_fork(at, config(to)?))
+		}
+	}
+}
+
+/// Run tests for specific json file with debug flag
+pub fn run_file(
+	filename: &str,
+	debug: bool,
+	filter: &TestFilter,
+) -> Result<TestCompletionStatus, Error> {
+	let tests: BTreeMap<String, BlockchainTest> =
+		serde_json::from_reader(BufReader::new(File::open(filename)?))?;
+	let mut tests_status = TestCompletionStatus::default();
+	let short_file_name = get_short_file_name(filename);
+
+	for (test_name, test) in tests {
+		if !filter.matches(&test_name, &test.network, None) {
+			continue;
+		}
+		let line = TestLine::new(&short_file_name, test_name, test.network.clone(), debug);
+		let result = run_test(test, &StandardHeaderValidator, debug);
+		if let Err(err) = line.finish(&mut tests_status, result) {
+			return Err(err.with_status(tests_status));
+		}
+	}
+
+	tests_status.print_completion();
+	Ok(tests_status)
+}
+
+/// Run the tests selected by `filter` for single json file or directory
+pub fn run_single(
+	filename: &str,
+	debug: bool,
+	filter: &TestFilter,
+) -> Result<TestCompletionStatus, Error> {
+	if fs::metadata(filename)?.is_dir() {
+		let mut tests_status = TestCompletionStatus::default();
+
+		for filename in fs::read_dir(filename)? {
+			let filepath = filename?.path();
+			let filename = filepath.to_str().ok_or(Error::NonUtf8Filename)?;
+			println!("RUN for: {filename}");
+			match run_file(filename, debug, filter) {
+				Ok(file_status) => tests_status += file_status,
+				Err(err) => return Err(err.with_status(tests_status)),
+			}
+		}
+		tests_status.print_total_for_dir(filename);
+		Ok(tests_status)
+	} else {
+		run_file(filename, debug, filter)
+	}
+}
+
+/// Run single blockchain test, validating headers with `validator`
+pub fn run_test<V: HeaderValidator>(
+	test: BlockchainTest,
+	validator: &V,
+	debug: bool,
+) -> Result<(), Error> {
+	let schedule = fork_schedule(&test.network).ok_or(Error::UnsupportedFork)?;
+
+	let genesis =
+		BlockHeader::decode(&Rlp::new(&test.genesis_rlp.0)).map_err(BlockImportError::from)?;
+	let mut chain = Chain::new(&schedule, genesis, in_memory_state(test.pre));
+
+	for (index, test_block) in test.blocks.into_iter().enumerate() {
+		let result = Block::decode(&test_block.rlp.0).and_then(|block| {
+			let hash = block.header.hash;
+			chain.import(block, validator).map(|()| hash)
+		});
+		if debug {
+			println!();
+			println!("block {}: {:?}", index, result);
+		}
+
+		match (result, test_block.expect_exception) {
+			(Ok(_), None) | (Err(_), Some(_)) => (),
+			(Ok(_), Some(_)) => return Err(TestError::BlockImported.into()),
+			(Err(err), None) => return Err(err.into()),
+		}
+	}
+
+	if chain.head != test.lastblockhash {
+		return Err(TestError::LastBlockHashMismatch.into());
+	}
+
+	let head = InMemoryBackend {
+		environment: Default::default(),
+		state: chain
+			.blocks
+			.remove(&chain.head)
+			.expect("head is imported")
+			.state,
+	};
+	let expected_state_root = match (test.post_state_hash, test.post_state) {
+		(Some(state_root), _) => state_root,
+		(None, Some(post_state)) => crate::hash::state_root(&InMemoryBackend {
+			environment: Default::default(),
+			state: in_memory_state(post_state),
+		}),
+		(None, None) => return Ok(()),
+	};
+	let state_root = crate::hash::state_root(&head);
+	if state_root != expected_state_root {
+		return Err(TestError::StateMismatch {
+			expected: expected_state_root,
+			actual: state_root,
+		}
+		.into());
+	}
+
+	Ok(())
+}
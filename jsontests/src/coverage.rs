@@ -0,0 +1,119 @@
+//! Coverage of the interpreter by the tests run: the opcodes executed, the
+//! gas cost rules applied and the results call frames exit with.
+
+use std::{cell::RefCell, collections::BTreeMap, ops::AddAssign};
+
+use evm::{
+	interpreter::{
+		error::{ExitError, ExitException, ExitResult},
+		opcode::Opcode,
+	},
+	standard::{FrameHook, FrameInfo, GasProfile},
+};
+use primitive_types::U256;
+
+/// Parts of the interpreter exercised by the tests.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Coverage {
+	/// Number of executions of each opcode.
+	pub opcodes: BTreeMap<Opcode, u64>,
+	/// Gas charged by each cost rule, named as in [GasProfile::costs].
+	pub costs: BTreeMap<&'static str, u64>,
+	/// Number of call frames exited with each result, such as `Returned` or
+	/// `OutOfGas`.
+	pub exits: BTreeMap<String, u64>,
+}
+
+impl AddAssign for Coverage {
+	fn add_assign(&mut self, rhs: Self) {
+		for (opcode, count) in rhs.opcodes {
+			*self.opcodes.entry(opcode).or_default() += count;
+		}
+		for (cost, gas) in rhs.costs {
+			*self.costs.entry(cost).or_default() += gas;
+		}
+		for (exit, count) in rhs.exits {
+			*self.exits.entry(exit).or_default() += count;
+		}
+	}
+}
+
+impl Coverage {
+	/// Record the opcodes and cost rules of the gas profile of a
+	/// transaction.
+	pub fn record_profile(&mut self, profile: &GasProfile) {
+		for (opcode, gas) in &profile.opcodes {
+			*self.opcodes.entry(*opcode).or_default() += gas.count;
+		}
+		for (cost, gas) in &profile.costs {
+			*self.costs.entry(*cost).or_default() += *gas;
+		}
+	}
+
+	/// Print the parts exercised, and the defined opcodes that are not.
+	pub fn print_summary(&self) {
+		let defined = (0..=u8::MAX)
+			.map(Opcode)
+			.filter(|opcode| opcode.name().is_some())
+			.collect::<Vec<_>>();
+		let missed = defined
+			.iter()
+			.filter(|opcode| !self.opcodes.contains_key(opcode))
+			.map(|opcode| opcode.to_string())
+			.collect::<Vec<_>>();
+
+		println!(
+			"\nCOVERAGE:\n\tOPCODES: {} of {} defined",
+			defined.len() - missed.len(),
+			defined.len()
+		);
+		if !missed.is_empty() {
+			println!("\tNOT EXECUTED: {}", missed.join(" "));
+		}
+		println!("\tCOSTS:");
+		for (cost, gas) in &self.costs {
+			println!("\t\t{}: {} gas", cost, gas);
+		}
+		println!("\tEXITS:");
+		for (exit, count) in &self.exits {
+			println!("\t\t{}: {}", exit, count);
+		}
+	}
+}
+
+/// [FrameHook] counting the results call frames exit with.
+#[derive(Default)]
+pub struct ExitCounter {
+	exits: RefCell<BTreeMap<String, u64>>,
+}
+
+impl ExitCounter {
+	/// Take the counts recorded so far.
+	pub fn take(&self) -> BTreeMap<String, u64> {
+		self.exits.take()
+	}
+}
+
+impl FrameHook for ExitCounter {
+	fn on_frame_enter(&self, _frame: &FrameInfo) {}
+
+	fn on_frame_exit(&self, result: &ExitResult, _gas_left: U256) {
+		*self
+			.exits
+			.borrow_mut()
+			.entry(exit_name(result))
+			.or_default() += 1;
+	}
+}
+
+/// Name of an exit result, without the details of the variant.
+fn exit_name(result: &ExitResult) -> String {
+	match result {
+		Ok(succeed) => format!("{:?}", succeed),
+		Err(ExitError::Exception(ExitException::InvalidOpcode(_))) => "InvalidOpcode".to_string(),
+		Err(ExitError::Exception(ExitException::Other(_))) => "OtherException".to_string(),
+		Err(ExitError::Exception(exception)) => format!("{:?}", exception),
+		Err(ExitError::Fatal(_)) => "Fatal".to_string(),
+		Err(err) => format!("{:?}", err),
+	}
+}
@@ -0,0 +1,558 @@
+//! Differential execution against a reference implementation of the EVM.
+//!
+//! A transaction is executed on a state by this crate and by the reference,
+//! and the gas used, the logs and the post states are compared, to catch
+//! consensus bugs that the fixtures do not cover. The reference is geth's
+//! `evm t8n` tool, run as a subprocess. It also runs the system calls of the
+//! block, which this crate does not, so states with system contracts can
+//! differ in their storage.
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fs::{self, File},
+	io::{BufReader, Write},
+	process::{Command, Stdio},
+};
+
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment, OverlayedBackend},
+	interpreter::runtime::Log,
+	standard::{transact_with_gas, Etable, EtableResolver, Invoker, TransactArgs},
+};
+use evm_precompile::StandardPrecompileSet;
+use primitive_types::{H160, H256, U256};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{
+	error::{Error, TestError},
+	filter::TestFilter,
+	run::{
+		fork_config, get_short_file_name, in_memory_state, initial_accessed, test_environment,
+		test_transact_args, TestLine,
+	},
+	types::{Fork, HexBytes, TestCompletionStatus, TestData, TestExpectException, TestMulti},
+};
+
+/// A transaction to execute on a state.
+#[derive(Clone, Debug)]
+pub struct DiffInput {
+	pub fork: Fork,
+	pub environment: InMemoryEnvironment,
+	pub state: BTreeMap<H160, InMemoryAccount>,
+	pub args: TransactArgs,
+	/// Secret key of the caller, for references that execute signed
+	/// transactions.
+	pub secret_key: Option<H256>,
+}
+
+/// Outcome of the execution of a transaction.
+#[derive(Clone, Debug)]
+pub struct DiffOutcome {
+	/// Gas used of the receipt, `None` if the transaction is invalid.
+	pub gas_used: Option<U256>,
+	pub logs: Vec<Log>,
+	pub state: BTreeMap<H160, InMemoryAccount>,
+}
+
+/// Difference between the outcome of this crate and the one of the
+/// reference.
+#[derive(Clone, Debug)]
+pub enum Difference {
+	GasUsed {
+		local: Option<U256>,
+		reference: Option<U256>,
+	},
+	Logs {
+		local: Vec<Log>,
+		reference: Vec<Log>,
+	},
+	/// Account of the post state, `None` if it is empty or does not exist.
+	Account {
+		address: H160,
+		local: Option<InMemoryAccount>,
+		reference: Option<InMemoryAccount>,
+	},
+}
+
+/// Implementation of the EVM to compare this crate with.
+pub trait ReferenceEvm {
+	/// Execute the transaction of `input`.
+	fn execute(&self, input: &DiffInput) -> Result<DiffOutcome, Error>;
+}
+
+/// Execute the transaction of `input` with this crate.
+pub fn execute(input: &DiffInput) -> Result<DiffOutcome, Error> {
+	let config = fork_config(input.fork).ok_or(Error::UnsupportedFork)?;
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let precompiles = StandardPrecompileSet::new(&config);
+	let resolver = EtableResolver::new(&config, &precompiles, &etable);
+	let invoker = Invoker::new(&config, &resolver);
+
+	let mut backend = InMemoryBackend {
+		environment: input.environment.clone(),
+		state: input.state.clone(),
+	};
+	let mut overlay = OverlayedBackend::new(&backend, initial_accessed());
+	let result = transact_with_gas(input.args.clone(), None, &mut overlay, &invoker);
+	let changeset = overlay.deconstruct().1;
+
+	match result {
+		Ok((_, gas)) => {
+			backend.apply_overlayed(&changeset);
+			Ok(DiffOutcome {
+				gas_used: Some(gas.effective),
+				logs: changeset.logs,
+				state: backend.state,
+			})
+		}
+		Err(_) => Ok(DiffOutcome {
+			gas_used: None,
+			logs: Vec::new(),
+			state: backend.state,
+		}),
+	}
+}
+
+/// Differences between the outcome of this crate and the one of the
+/// reference. Empty accounts are treated as missing ones.
+pub fn diff(local: &DiffOutcome, reference: &DiffOutcome) -> Vec<Difference> {
+	let mut differences = Vec::new();
+
+	if local.gas_used != reference.gas_used {
+		differences.push(Difference::GasUsed {
+			local: local.gas_used,
+			reference: reference.gas_used,
+		});
+	}
+
+	let same_logs = local.logs.len() == reference.logs.len()
+		&& local
+			.logs
+			.iter()
+			.zip(&reference.logs)
+			.all(|(local, reference)| {
+				local.address == reference.address
+					&& local.topics == reference.topics
+					&& local.data == reference.data
+			});
+	if !same_logs {
+		differences.push(Difference::Logs {
+			local: local.logs.clone(),
+			reference: reference.logs.clone(),
+		});
+	}
+
+	let addresses = local
+		.state
+		.keys()
+		.chain(reference.state.keys())
+		.collect::<BTreeSet<_>>();
+	for address in addresses {
+		let local = non_empty(&local.state, address);
+		let reference = non_empty(&reference.state, address);
+		if local != reference {
+			differences.push(Difference::Account {
+				address: *address,
+				local,
+				reference,
+			});
+		}
+	}
+
+	differences
+}
+
+fn non_empty(state: &BTreeMap<H160, InMemoryAccount>, address: &H160) -> Option<InMemoryAccount> {
+	state
+		.get(address)
+		.filter(|account| **account != InMemoryAccount::default())
+		.cloned()
+}
+
+/// Execute the transaction of `input` with this crate and with `reference`,
+/// and return their differences.
+pub fn compare(input: &DiffInput, reference: &dyn ReferenceEvm) -> Result<Vec<Difference>, Error> {
+	let local = execute(input)?;
+	let reference = reference.execute(input)?;
+	Ok(diff(&local, &reference))
+}
+
+/// Geth's `evm t8n` tool, executing the transaction in a block of its own,
+/// without block reward.
+#[derive(Clone, Debug)]
+pub struct GethT8n {
+	/// Path of the `evm` binary.
+	pub binary: String,
+}
+
+impl GethT8n {
+	pub fn new(binary: impl Into<String>) -> Self {
+		Self {
+			binary: binary.into(),
+		}
+	}
+}
+
+#[derive(Deserialize)]
+struct T8nOutput {
+	alloc: BTreeMap<H160, T8nAccount>,
+	result: T8nResult,
+}
+
+#[derive(Deserialize)]
+struct T8nAccount {
+	#[serde(default)]
+	balance: U256,
+	#[serde(default)]
+	code: Option<HexBytes>,
+	#[serde(default)]
+	nonce: U256,
+	#[serde(default)]
+	storage: BTreeMap<H256, H256>,
+}
+
+#[derive(Deserialize)]
+struct T8nResult {
+	receipts: Vec<T8nReceipt>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct T8nReceipt {
+	gas_used: U256,
+	logs: Option<Vec<T8nLog>>,
+}
+
+#[derive(Deserialize)]
+struct T8nLog {
+	address: H160,
+	topics: Vec<H256>,
+	data: HexBytes,
+}
+
+impl ReferenceEvm for GethT8n {
+	fn execute(&self, input: &DiffInput) -> Result<DiffOutcome, Error> {
+		let config = fork_config(input.fork).ok_or(Error::UnsupportedFork)?;
+		let post_merge = matches!(
+			input.fork,
+			Fork::Merge | Fork::Shanghai | Fork::Cancun | Fork::Prague | Fork::Osaka
+		);
+		let environment = &input.environment;
+
+		let alloc = input
+			.state
+			.iter()
+			.map(|(address, account)| {
+				let account = json!({
+					"balance": account.balance,
+					"nonce": account.nonce,
+					"code": format!("0x{}", hex::encode(&account.code)),
+					"storage": account.storage,
+				});
+				(format!("{:?}", address), account)
+			})
+			.collect::<serde_json::Map<_, _>>();
+
+		let mut env = json!({
+			"currentCoinbase": environment.block_coinbase,
+			"currentGasLimit": environment.block_gas_limit,
+			"currentNumber": environment.block_number,
+			"currentTimestamp": environment.block_timestamp,
+			"blockHashes": environment
+				.block_hashes
+				.iter()
+				.map(|(number, hash)| (number.to_string(), json!(hash)))
+				.collect::<serde_json::Map<_, _>>(),
+		});
+		if post_merge {
+			env["currentRandom"] = json!(environment.block_randomness.unwrap_or_default());
+		} else {
+			env["currentDifficulty"] = json!(environment.block_difficulty);
+		}
+		if config.eip_1559_enabled {
+			env["currentBaseFee"] = json!(environment.block_base_fee_per_gas);
+		}
+		if matches!(
+			input.fork,
+			Fork::Shanghai | Fork::Cancun | Fork::Prague | Fork::Osaka
+		) {
+			env["withdrawals"] = json!([]);
+		}
+		if matches!(input.fork, Fork::Cancun | Fork::Prague | Fork::Osaka) {
+			env["currentExcessBlobGas"] = json!(environment.block_excess_blob_gas);
+			env["parentBeaconBlockRoot"] = json!(H256::zero());
+		}
+
+		let request = json!({
+			"alloc": alloc,
+			"env": env,
+			"txs": [t8n_transaction(input)?],
+		});
+
+		let mut child = Command::new(&self.binary)
+			.args([
+				"t8n",
+				"--input.alloc=stdin",
+				"--input.env=stdin",
+				"--input.txs=stdin",
+				"--output.alloc=stdout",
+				"--output.result=stdout",
+				"--state.reward=-1",
+			])
+			.arg(format!("--state.fork={:?}", input.fork))
+			.arg(format!("--state.chainid={}", environment.chain_id))
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.spawn()?;
+		if let Some(mut stdin) = child.stdin.take() {
+			stdin.write_all(serde_json::to_string(&request)?.as_bytes())?;
+		}
+		let output = child.wait_with_output()?;
+		if !output.status.success() {
+			return Err(Error::Reference(
+				String::from_utf8_lossy(&output.stderr).into_owned(),
+			));
+		}
+
+		let output: T8nOutput = serde_json::from_slice(&output.stdout)?;
+		let receipt = output.result.receipts.into_iter().next();
+		Ok(DiffOutcome {
+			gas_used: receipt.as_ref().map(|receipt| receipt.gas_used),
+			logs: receipt
+				.and_then(|receipt| receipt.logs)
+				.unwrap_or_default()
+				.into_iter()
+				.map(|log| Log {
+					address: log.address,
+					topics: log.topics,
+					data: log.data.0,
+				})
+				.collect(),
+			state: output
+				.alloc
+				.into_iter()
+				.map(|(address, account)| {
+					let account = InMemoryAccount {
+						balance: account.balance,
+						code: account.code.map(|code| code.0).unwrap_or_default(),
+						nonce: account.nonce,
+						storage: account
+							.storage
+							.into_iter()
+							.filter(|(_, value)| *value != H256::zero())
+							.collect(),
+					};
+					(address, account)
+				})
+				.collect(),
+		})
+	}
+}
+
+/// Transaction of `input` in the format of `evm t8n`, signed by the tool
+/// with the secret key of the caller.
+fn t8n_transaction(input: &DiffInput) -> Result<Value, Error> {
+	let secret_key = input.secret_key.ok_or(Error::UnsupportedTransaction)?;
+	let (caller, to, value, data, nonce, fee_payer, blob_hashes, max_fee_per_blob_gas, overrides) =
+		match &input.args {
+			TransactArgs::Call {
+				caller,
+				address,
+				value,
+				data,
+				nonce,
+				fee_payer,
+				blob_hashes,
+				max_fee_per_blob_gas,
+				overrides,
+				..
+			} => (
+				caller,
+				Some(*address),
+				value,
+				data,
+				nonce,
+				fee_payer,
+				blob_hashes.clone(),
+				*max_fee_per_blob_gas,
+				overrides,
+			),
+			TransactArgs::Create {
+				caller,
+				value,
+				init_code,
+				salt: None,
+				nonce,
+				fee_payer,
+				overrides,
+				..
+			} => (
+				caller,
+				None,
+				value,
+				init_code,
+				nonce,
+				fee_payer,
+				Vec::new(),
+				None,
+				overrides,
+			),
+			_ => return Err(Error::UnsupportedTransaction),
+		};
+	if fee_payer.is_some() || *overrides != Default::default() {
+		return Err(Error::UnsupportedTransaction);
+	}
+
+	let nonce = nonce.unwrap_or_else(|| {
+		input
+			.state
+			.get(caller)
+			.map(|account| account.nonce)
+			.unwrap_or_default()
+	});
+	let access_list = input
+		.args
+		.access_list()
+		.iter()
+		.map(|(address, storage_keys)| {
+			json!({
+				"address": address,
+				"storageKeys": storage_keys,
+			})
+		})
+		.collect::<Vec<_>>();
+
+	let mut transaction = json!({
+		"chainId": input.environment.chain_id,
+		"nonce": nonce,
+		"gas": input.args.gas_limit(),
+		"to": to,
+		"value": value,
+		"input": format!("0x{}", hex::encode(data)),
+		"accessList": access_list,
+		"v": U256::zero(),
+		"r": U256::zero(),
+		"s": U256::zero(),
+		"secretKey": secret_key,
+	});
+	if !blob_hashes.is_empty() || max_fee_per_blob_gas.is_some() {
+		transaction["type"] = json!("0x3");
+		transaction["blobVersionedHashes"] = json!(blob_hashes);
+		transaction["maxFeePerBlobGas"] = json!(max_fee_per_blob_gas.unwrap_or_default());
+	} else if input.args.max_priority_fee_per_gas().is_some() {
+		transaction["type"] = json!("0x2");
+	} else if !access_list.is_empty() {
+		transaction["type"] = json!("0x1");
+	} else {
+		transaction["type"] = json!("0x0");
+	}
+	match input.args.max_priority_fee_per_gas() {
+		Some(max_priority_fee_per_gas) => {
+			transaction["maxFeePerGas"] = json!(input.args.gas_price());
+			transaction["maxPriorityFeePerGas"] = json!(max_priority_fee_per_gas);
+		}
+		None => transaction["gasPrice"] = json!(input.args.gas_price()),
+	}
+
+	Ok(transaction)
+}
+
+/// Run tests for specific json file against `reference`, with debug flag
+pub fn run_file(
+	filename: &str,
+	debug: bool,
+	filter: &TestFilter,
+	reference: &dyn ReferenceEvm,
+) -> Result<TestCompletionStatus, Error> {
+	let test_multi: BTreeMap<String, TestMulti> =
+		serde_json::from_reader(BufReader::new(File::open(filename)?))?;
+	let mut tests_status = TestCompletionStatus::default();
+
+	for (test_name, test_multi) in test_multi {
+		let tests = test_multi.tests();
+		let short_file_name = get_short_file_name(filename);
+		for test in &tests {
+			if !filter.matches(&test_name, &format!("{:?}", test.fork), Some(test.index)) {
+				continue;
+			}
+			let line = TestLine::new(
+				&short_file_name,
+				format!("{}/{}", test_name, test.index),
+				format!("{:?}", test.fork),
+				debug,
+			);
+			let result = run_test(test, reference, debug);
+			if let Err(err) = line.finish(&mut tests_status, result) {
+				return Err(err.with_status(tests_status));
+			}
+		}
+
+		tests_status.print_completion();
+	}
+
+	Ok(tests_status)
+}
+
+/// Run the tests selected by `filter` for single json file or directory
+/// against `reference`
+pub fn run_single(
+	filename: &str,
+	debug: bool,
+	filter: &TestFilter,
+	reference: &dyn ReferenceEvm,
+) -> Result<TestCompletionStatus, Error> {
+	if fs::metadata(filename)?.is_dir() {
+		let mut tests_status = TestCompletionStatus::default();
+
+		for filename in fs::read_dir(filename)? {
+			let filepath = filename?.path();
+			let filename = filepath.to_str().ok_or(Error::NonUtf8Filename)?;
+			println!("RUN for: {filename}");
+			match run_file(filename, debug, filter, reference) {
+				Ok(file_status) => tests_status += file_status,
+				Err(err) => return Err(err.with_status(tests_status)),
+			}
+		}
+		tests_status.print_total_for_dir(filename);
+		Ok(tests_status)
+	} else {
+		run_file(filename, debug, filter, reference)
+	}
+}
+
+/// Execute the transaction of a test with this crate and with `reference`,
+/// failing if they differ
+pub fn run_test(test: &TestData, reference: &dyn ReferenceEvm, debug: bool) -> Result<(), Error> {
+	let config = fork_config(test.fork).ok_or(Error::UnsupportedFork)?;
+	if test.transaction.has_authorization_list
+		|| test.post.expect_exception == Some(TestExpectException::TR_TypeNotSupported)
+	{
+		return Err(Error::UnsupportedTransaction);
+	}
+
+	let input = DiffInput {
+		fork: test.fork,
+		environment: test_environment(test, &config),
+		state: in_memory_state(test.pre.clone()),
+		args: test_transact_args(test),
+		secret_key: Some(test.transaction.secret_key),
+	};
+	let differences = compare(&input, reference)?;
+	if debug {
+		println!();
+		for difference in &differences {
+			println!("{:?}", difference);
+		}
+	}
+
+	if differences.is_empty() {
+		Ok(())
+	} else {
+		Err(TestError::Divergence(differences).into())
+	}
+}
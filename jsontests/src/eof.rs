@@ -0,0 +1,128 @@
+//! Runner of `EOFTests`: containers are validated, and the result compared
+//! with the expected one for each fork that supports EOF. The exception of
+//! an invalid container is compared when it is one that the validator
+//! reports.
+
+use std::{
+	collections::BTreeMap,
+	fs::{self, File},
+	io::BufReader,
+};
+
+use evm::interpreter::eof::{validate, ContainerKind, EofErrorKind};
+
+use crate::{
+	error::{Error, TestError},
+	filter::TestFilter,
+	run::{get_short_file_name, TestLine},
+	types::{
+		EofTest, EofTestContainerKind, EofTestResult, EofTestVector, Fork, TestCompletionStatus,
+	},
+};
+
+/// Run tests for specific json file with debug flag
+pub fn run_file(
+	filename: &str,
+	debug: bool,
+	filter: &TestFilter,
+) -> Result<TestCompletionStatus, Error> {
+	let tests: BTreeMap<String, EofTest> =
+		serde_json::from_reader(BufReader::new(File::open(filename)?))?;
+	let mut tests_status = TestCompletionStatus::default();
+	let short_file_name = get_short_file_name(filename);
+
+	for (test_name, test) in tests {
+		for (vector_name, vector) in &test.vectors {
+			for (fork, result) in &vector.results {
+				if !filter.matches(&test_name, fork, vector_name.parse().ok()) {
+					continue;
+				}
+				let line = TestLine::new(
+					&short_file_name,
+					format!("{}/{}", test_name, vector_name),
+					fork.clone(),
+					debug,
+				);
+				let result = run_test(fork, vector, result, debug);
+				if let Err(err) = line.finish(&mut tests_status, result) {
+					return Err(err.with_status(tests_status));
+				}
+			}
+		}
+	}
+
+	tests_status.print_completion();
+	Ok(tests_status)
+}
+
+/// Run the tests selected by `filter` for single json file or directory
+pub fn run_single(
+	filename: &str,
+	debug: bool,
+	filter: &TestFilter,
+) -> Result<TestCompletionStatus, Error> {
+	if fs::metadata(filename)?.is_dir() {
+		let mut tests_status = TestCompletionStatus::default();
+
+		for filename in fs::read_dir(filename)? {
+			let filepath = filename?.path();
+			let filename = filepath.to_str().ok_or(Error::NonUtf8Filename)?;
+			println!("RUN for: {filename}");
+			match run_file(filename, debug, filter) {
+				Ok(file_status) => tests_status += file_status,
+				Err(err) => return Err(err.with_status(tests_status)),
+			}
+		}
+		tests_status.print_total_for_dir(filename);
+		Ok(tests_status)
+	} else {
+		run_file(filename, debug, filter)
+	}
+}
+
+/// Validate the container of a test vector, expecting `expected` under
+/// `fork`
+pub fn run_test(
+	fork: &str,
+	vector: &EofTestVector,
+	expected: &EofTestResult,
+	debug: bool,
+) -> Result<(), Error> {
+	match Fork::from_name(fork) {
+		Some(Fork::Prague | Fork::Osaka) => (),
+		_ => return Err(Error::UnsupportedFork),
+	}
+
+	let kind = match vector.container_kind {
+		EofTestContainerKind::Runtime => ContainerKind::Runtime,
+		EofTestContainerKind::Initcode => ContainerKind::Initcode,
+	};
+	let result = validate(&vector.code.0, kind);
+	if debug {
+		println!();
+		println!("result: {:?}, expected: {:?}", result, expected);
+	}
+
+	match (result, expected.result) {
+		(Ok(_), true) => Ok(()),
+		(Ok(_), false) => Err(TestError::EofAccepted.into()),
+		(Err(err), true) => Err(TestError::EofRejected(err).into()),
+		(Err(err), false) => {
+			let exceptions = expected
+				.exception
+				.as_deref()
+				.unwrap_or_default()
+				.split('|')
+				.filter_map(|exception| {
+					EofErrorKind::from_name(exception.trim_start_matches("EOFException."))
+				})
+				.collect::<Vec<_>>();
+
+			if exceptions.is_empty() || exceptions.contains(&err.kind) {
+				Ok(())
+			} else {
+				Err(TestError::EofExceptionMismatch(err).into())
+			}
+		}
+	}
+}
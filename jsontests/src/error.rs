@@ -1,10 +1,27 @@
+use primitive_types::H256;
+
+use crate::types::TestCompletionStatus;
+
 #[derive(Debug, thiserror::Error)]
 #[allow(dead_code)]
 pub enum TestError {
 	#[error("state root is different")]
-	StateMismatch,
+	StateMismatch { expected: H256, actual: H256 },
 	#[error("expect error, but got okay")]
 	ExpectException,
+	#[error("expect block to be rejected, but it is imported")]
+	BlockImported,
+	#[error("last block hash is different")]
+	LastBlockHashMismatch,
+	#[error("expect container to be invalid, but it is valid")]
+	EofAccepted,
+	#[error("expect container to be valid, but it is invalid")]
+	EofRejected(evm::interpreter::eof::EofError),
+	#[error("container is invalid, but with a different exception")]
+	EofExceptionMismatch(evm::interpreter::eof::EofError),
+	#[cfg(feature = "differential")]
+	#[error("execution differs from the reference")]
+	Divergence(Vec<crate::differential::Difference>),
 }
 
 #[allow(clippy::upper_case_acronyms)]
@@ -18,8 +35,54 @@ pub enum Error {
 	EVM(#[from] evm::interpreter::error::ExitError),
 	#[error("unsupported fork")]
 	UnsupportedFork,
+	#[error("unsupported transaction")]
+	UnsupportedTransaction,
 	#[error("non-utf8 filename")]
 	NonUtf8Filename,
 	#[error("test error")]
 	Test(#[from] TestError),
+	#[error("block rejected")]
+	Block(#[from] crate::blockchain::BlockImportError),
+	#[cfg(feature = "differential")]
+	#[error("reference implementation failed")]
+	Reference(String),
+	#[error("test panicked")]
+	Panicked(String),
+	/// Error of a test, with the status of the tests run up to it.
+	#[error("{1}")]
+	Failed(Box<TestCompletionStatus>, Box<Error>),
+}
+
+impl Error {
+	/// Attach the status of the tests run before the error. A status
+	/// already attached is appended to it.
+	#[must_use]
+	pub fn with_status(self, mut status: TestCompletionStatus) -> Self {
+		match self {
+			Self::Failed(failed, err) => {
+				status += *failed;
+				Self::Failed(Box::new(status), err)
+			}
+			err => Self::Failed(Box::new(status), Box::new(err)),
+		}
+	}
+
+	/// Split the error into the status attached to it, empty if there is
+	/// none, and the error itself.
+	#[must_use]
+	pub fn split(self) -> (TestCompletionStatus, Self) {
+		match self {
+			Self::Failed(status, err) => (*status, *err),
+			err => (TestCompletionStatus::default(), err),
+		}
+	}
+
+	/// Status attached to the error.
+	#[must_use]
+	pub fn status(&self) -> Option<&TestCompletionStatus> {
+		match self {
+			Self::Failed(status, _) => Some(status),
+			_ => None,
+		}
+	}
 }
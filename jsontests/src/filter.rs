@@ -0,0 +1,101 @@
+//! Selection of the tests to run in a file, by name, fork and case index.
+
+use crate::types::Fork;
+
+/// Filter of the tests to run. Tests are run if they match all the given
+/// criteria.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TestFilter {
+	/// Pattern searched in the test name. It is a subset of regular
+	/// expressions: `.` matches any character, `*` repeats the previous
+	/// one, `^` and `$` anchor the pattern and `\` escapes a character.
+	pub name: Option<String>,
+	/// Fork or network, compared case-insensitively. Fork aliases, such as
+	/// `Paris` for `Merge`, are accepted.
+	pub fork: Option<String>,
+	/// Index of the case in the test, such as the post state of a state
+	/// test. Tests without cases are not filtered by it.
+	pub case_index: Option<usize>,
+}
+
+impl TestFilter {
+	/// Whether the case `index` of the test `name` for `fork` is selected.
+	#[must_use]
+	pub fn matches(&self, name: &str, fork: &str, index: Option<usize>) -> bool {
+		if let Some(pattern) = &self.name {
+			let pattern = pattern.chars().collect::<Vec<_>>();
+			let name = name.chars().collect::<Vec<_>>();
+			if !search(&pattern, &name) {
+				return false;
+			}
+		}
+
+		if let Some(filter_fork) = &self.fork {
+			let same_fork = match (Fork::from_name(filter_fork), Fork::from_name(fork)) {
+				(Some(filter_fork), Some(fork)) => filter_fork == fork,
+				_ => filter_fork.eq_ignore_ascii_case(fork),
+			};
+			if !same_fork {
+				return false;
+			}
+		}
+
+		match (self.case_index, index) {
+			(Some(case_index), Some(index)) => case_index == index,
+			_ => true,
+		}
+	}
+}
+
+/// Whether `pattern` matches somewhere in `text`.
+fn search(pattern: &[char], text: &[char]) -> bool {
+	if let Some(('^', pattern)) = pattern.split_first() {
+		return match_here(pattern, text);
+	}
+	(0..=text.len()).any(|start| match_here(pattern, &text[start..]))
+}
+
+/// Whether `pattern` matches at the start of `text`.
+fn match_here(pattern: &[char], text: &[char]) -> bool {
+	let (matches, len) = match pattern {
+		[] => return true,
+		['$'] => return text.is_empty(),
+		['\\', c, ..] => (Matcher::Char(*c), 2),
+		['.', ..] => (Matcher::Any, 1),
+		[c, ..] => (Matcher::Char(*c), 1),
+	};
+
+	let rest = &pattern[len..];
+	if let Some(('*', rest)) = rest.split_first() {
+		// The shortest repetition that lets the rest match.
+		let mut text = text;
+		loop {
+			if match_here(rest, text) {
+				return true;
+			}
+			match text.split_first() {
+				Some((c, tail)) if matches.matches(*c) => text = tail,
+				_ => return false,
+			}
+		}
+	}
+
+	match text.split_first() {
+		Some((c, tail)) => matches.matches(*c) && match_here(rest, tail),
+		None => false,
+	}
+}
+
+enum Matcher {
+	Any,
+	Char(char),
+}
+
+impl Matcher {
+	fn matches(&self, c: char) -> bool {
+		match self {
+			Self::Any => true,
+			Self::Char(expected) => *expected == c,
+		}
+	}
+}
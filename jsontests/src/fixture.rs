@@ -0,0 +1,230 @@
+//! Generation of `GeneralStateTests` fixtures, to turn the reproduction of a
+//! bug into a regression test.
+//!
+//! The post state of the fixture is the one computed by this crate, so the
+//! fixture records the current behavior: it is only as correct as the
+//! execution it is generated with.
+
+use std::fs;
+
+use evm::{
+	backend::{InMemoryBackend, OverlayedBackend},
+	interpreter::{runtime::Log, utils::h256_to_u256},
+	standard::{transact_with_gas, Config, Etable, EtableResolver, Invoker, TransactArgs},
+};
+use evm_precompile::StandardPrecompileSet;
+use primitive_types::{H256, U256};
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+
+use crate::{error::Error, hash::state_root, run::initial_accessed, types::Fork};
+
+/// Generate a state test fixture named `name`, executing `args` on the
+/// state and the block of `backend` under `config`, the config of `fork`.
+/// The transaction is signed with `secret_key` by the clients that need it,
+/// which is expected to be the key of its caller.
+///
+/// An invalid transaction gives a fixture expecting an exception, with the
+/// pre state as post state.
+pub fn state_test(
+	name: &str,
+	fork: Fork,
+	config: &Config,
+	backend: &InMemoryBackend,
+	args: &TransactArgs,
+	secret_key: H256,
+) -> Result<Value, Error> {
+	let (caller, to, value, data, nonce, fee_payer, blob_hashes, max_fee_per_blob_gas, overrides) =
+		match args {
+			TransactArgs::Call {
+				caller,
+				address,
+				value,
+				data,
+				nonce,
+				fee_payer,
+				blob_hashes,
+				max_fee_per_blob_gas,
+				overrides,
+				..
+			} => (
+				caller,
+				Some(*address),
+				value,
+				data,
+				nonce,
+				fee_payer,
+				blob_hashes.clone(),
+				*max_fee_per_blob_gas,
+				overrides,
+			),
+			TransactArgs::Create {
+				caller,
+				value,
+				init_code,
+				salt: None,
+				nonce,
+				fee_payer,
+				overrides,
+				..
+			} => (
+				caller,
+				None,
+				value,
+				init_code,
+				nonce,
+				fee_payer,
+				Vec::new(),
+				None,
+				overrides,
+			),
+			_ => return Err(Error::UnsupportedTransaction),
+		};
+	if fee_payer.is_some() || *overrides != Default::default() {
+		// Not expressible as a signed transaction.
+		return Err(Error::UnsupportedTransaction);
+	}
+
+	let environment = &backend.environment;
+	let mut env = json!({
+		"currentCoinbase": environment.block_coinbase,
+		"currentDifficulty": environment.block_difficulty,
+		"currentGasLimit": environment.block_gas_limit,
+		"currentNumber": environment.block_number,
+		"currentTimestamp": environment.block_timestamp,
+		"currentExcessBlobGas": environment.block_excess_blob_gas,
+	});
+	if config.eip_1559_enabled {
+		env["currentBaseFee"] = json!(environment.block_base_fee_per_gas);
+	}
+	if let Some(randomness) = environment.block_randomness {
+		env["currentRandom"] = json!(randomness);
+	}
+
+	let pre = backend
+		.state
+		.iter()
+		.map(|(address, account)| {
+			let storage = account
+				.storage
+				.iter()
+				.map(|(key, value)| {
+					(
+						format!("0x{:x}", h256_to_u256(*key)),
+						json!(h256_to_u256(*value)),
+					)
+				})
+				.collect::<serde_json::Map<_, _>>();
+			let account = json!({
+				"balance": account.balance,
+				"code": format!("0x{}", hex::encode(&account.code)),
+				"nonce": account.nonce,
+				"storage": storage,
+			});
+			(format!("{:?}", address), account)
+		})
+		.collect::<serde_json::Map<_, _>>();
+
+	let nonce = nonce.unwrap_or_else(|| {
+		backend
+			.state
+			.get(caller)
+			.map(|account| account.nonce)
+			.unwrap_or_default()
+	});
+	let mut transaction = json!({
+		"data": [format!("0x{}", hex::encode(data))],
+		"gasLimit": [args.gas_limit()],
+		"nonce": nonce,
+		"secretKey": secret_key,
+		"sender": caller,
+		"to": to.map(|to| format!("{:?}", to)).unwrap_or_default(),
+		"value": [value],
+	});
+	match args.max_priority_fee_per_gas() {
+		Some(max_priority_fee_per_gas) => {
+			transaction["maxFeePerGas"] = json!(args.gas_price());
+			transaction["maxPriorityFeePerGas"] = json!(max_priority_fee_per_gas);
+		}
+		None => transaction["gasPrice"] = json!(args.gas_price()),
+	}
+	if !args.access_list().is_empty() {
+		let access_list = args
+			.access_list()
+			.iter()
+			.map(|(address, storage_keys)| {
+				json!({
+					"address": address,
+					"storageKeys": storage_keys,
+				})
+			})
+			.collect::<Vec<_>>();
+		transaction["accessLists"] = json!([access_list]);
+	}
+	if let Some(max_fee_per_blob_gas) = max_fee_per_blob_gas {
+		transaction["maxFeePerBlobGas"] = json!(max_fee_per_blob_gas);
+		transaction["blobVersionedHashes"] = json!(blob_hashes);
+	}
+
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let precompiles = StandardPrecompileSet::new(config);
+	let resolver = EtableResolver::new(config, &precompiles, &etable);
+	let invoker = Invoker::new(config, &resolver);
+
+	let mut overlay = OverlayedBackend::new(backend, initial_accessed());
+	let result = transact_with_gas(args.clone(), None, &mut overlay, &invoker);
+	let changeset = overlay.deconstruct().1;
+
+	let mut post_backend = backend.clone();
+	let mut post = json!({
+		"indexes": { "data": 0, "gas": 0, "value": 0 },
+		"txbytes": "0x",
+	});
+	match result {
+		Ok(_) => {
+			post_backend.apply_overlayed(&changeset);
+			post["logs"] = json!(logs_hash(&changeset.logs));
+		}
+		Err(err) => {
+			post["expectException"] = json!(format!("{:?}", err));
+			post["logs"] = json!(logs_hash(&[]));
+		}
+	}
+	post["hash"] = json!(state_root(&post_backend));
+
+	let mut test = json!({
+		"_info": {
+			"comment": "Generated by the jsontests fixture generator of the evm crate",
+		},
+		"env": env,
+		"pre": pre,
+		"transaction": transaction,
+		"post": { format!("{:?}", fork): [post] },
+	});
+	if environment.chain_id != U256::one() {
+		test["config"] = json!({ "chainid": environment.chain_id });
+	}
+
+	Ok(json!({ name: test }))
+}
+
+/// Write `fixture` to `path`.
+pub fn write(fixture: &Value, path: &str) -> Result<(), Error> {
+	fs::write(path, serde_json::to_string_pretty(fixture)?)?;
+	Ok(())
+}
+
+/// Hash of the RLP encoding of `logs`, as the `logs` of the post states.
+fn logs_hash(logs: &[Log]) -> H256 {
+	let mut stream = rlp::RlpStream::new_list(logs.len());
+	for log in logs {
+		stream.begin_list(3);
+		stream.append(&log.address);
+		stream.append_list(&log.topics);
+		stream.append(&log.data);
+	}
+	H256::from_slice(&Keccak256::digest(stream.out()))
+}
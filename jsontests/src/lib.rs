@@ -1,76 +1,87 @@
+pub mod blockchain;
+pub mod coverage;
+#[cfg(feature = "differential")]
+pub mod differential;
+pub mod eof;
 pub mod error;
+pub mod filter;
+pub mod fixture;
 pub mod hash;
-pub mod in_memory;
+pub mod parallel;
+pub mod report;
 pub mod run;
 pub mod types;
 
+#[cfg(test)]
+use crate::filter::TestFilter;
+
 #[test]
 fn st_args_zero_one_balance() {
 	const JSON_FILENAME: &str = "res/ethtests/GeneralStateTests/stArgsZeroOneBalance/";
-	let tests_status = run::run_single(JSON_FILENAME, false).unwrap();
+	let tests_status = run::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
 	tests_status.print_total();
 }
 
 #[test]
 fn st_code_copy_test() {
 	const JSON_FILENAME: &str = "res/ethtests/GeneralStateTests/stCodeCopyTest/";
-	let tests_status = run::run_single(JSON_FILENAME, false).unwrap();
+	let tests_status = run::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
 	tests_status.print_total();
 }
 
 #[test]
 fn st_example() {
 	const JSON_FILENAME: &str = "res/ethtests/GeneralStateTests/stExample/";
-	let tests_status = run::run_single(JSON_FILENAME, false).unwrap();
+	let tests_status = run::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
 	tests_status.print_total();
 }
 
 #[test]
 fn st_self_balance() {
 	const JSON_FILENAME: &str = "res/ethtests/GeneralStateTests/stSelfBalance/";
-	let tests_status = run::run_single(JSON_FILENAME, false).unwrap();
+	let tests_status = run::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
 	tests_status.print_total();
 }
 
 #[test]
 fn st_s_load_test() {
 	const JSON_FILENAME: &str = "res/ethtests/GeneralStateTests/stSLoadTest/";
-	let tests_status = run::run_single(JSON_FILENAME, false).unwrap();
+	let tests_status = run::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
 	tests_status.print_total();
 }
 
 #[test]
 fn vm_arithmetic_test() {
 	const JSON_FILENAME: &str = "res/ethtests/GeneralStateTests/VMTests/vmArithmeticTest/";
-	let tests_status = run::run_single(JSON_FILENAME, false).unwrap();
+	let tests_status = run::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
 	tests_status.print_total();
 }
 
 #[test]
 fn vm_bitwise_logic_operation() {
 	const JSON_FILENAME: &str = "res/ethtests/GeneralStateTests/VMTests/vmBitwiseLogicOperation/";
-	let tests_status = run::run_single(JSON_FILENAME, false).unwrap();
+	let tests_status = run::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
 	tests_status.print_total();
 }
 
 #[test]
 fn vm_io_and_flow_operations() {
 	const JSON_FILENAME: &str = "res/ethtests/GeneralStateTests/VMTests/vmIOandFlowOperations/";
-	let tests_status = run::run_single(JSON_FILENAME, false).unwrap();
+	let tests_status = run::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
 	tests_status.print_total();
 }
 
 #[test]
 fn vm_log_test() {
 	const JSON_FILENAME: &str = "res/ethtests/GeneralStateTests/VMTests/vmLogTest/";
-	let tests_status = run::run_single(JSON_FILENAME, false).unwrap();
+	let tests_status = run::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
 	tests_status.print_total();
 }
 
 #[test]
 fn vm_tests() {
 	const JSON_FILENAME: &str = "res/ethtests/GeneralStateTests/VMTests/vmTests/";
-	let tests_status = run::run_single(JSON_FILENAME, false).unwrap();
+	let tests_status = run::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
 	tests_status.print_total();
 }
 
@@ -78,6 +89,94 @@ fn vm_tests() {
 fn sqt_eip_2930() {
 	const JSON_FILENAME: &str =
 		"res/ethtests/GeneralStateTests/stEIP150singleCodeGasPrices/eip2929.json";
-	let tests_status = run::run_single(JSON_FILENAME, false).unwrap();
+	let tests_status = run::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
+	tests_status.print_total();
+}
+
+#[test]
+fn bc_state_tests() {
+	const JSON_FILENAME: &str = "res/ethtests/BlockchainTests/ValidBlocks/bcStateTests/";
+	let tests_status =
+		blockchain::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
 	tests_status.print_total();
 }
+
+#[test]
+fn eof_validation() {
+	const JSON_FILENAME: &str = "res/ethtests/EOFTests/efValidation/";
+	let tests_status = eof::run_single(JSON_FILENAME, false, &TestFilter::default()).unwrap();
+	tests_status.print_total();
+}
+
+#[test]
+fn generated_state_test() {
+	use evm::{
+		backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+		standard::{Config, TransactArgs},
+	};
+	use primitive_types::{H160, H256, U256};
+
+	let caller =
+		H160::from_slice(&hex::decode("a94f5374fce5edbc8e2a8697c15331677e6ebf0b").unwrap());
+	let contract = H160::repeat_byte(0x10);
+	let mut backend = InMemoryBackend {
+		environment: InMemoryEnvironment {
+			block_gas_limit: U256::from(10_000_000),
+			block_base_fee_per_gas: U256::from(7),
+			block_randomness: Some(H256::zero()),
+			chain_id: U256::one(),
+			..Default::default()
+		},
+		state: Default::default(),
+	};
+	backend.state.insert(
+		caller,
+		InMemoryAccount {
+			balance: U256::from(1_000_000_000_000u64),
+			..Default::default()
+		},
+	);
+	// SSTORE 1 at 0, then LOG0 of a word of memory.
+	backend.state.insert(
+		contract,
+		InMemoryAccount {
+			code: hex::decode("6001600055600160005260206000a0").unwrap(),
+			nonce: U256::one(),
+			..Default::default()
+		},
+	);
+	let args = TransactArgs::Call {
+		caller,
+		address: contract,
+		value: U256::one(),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::from(10),
+		max_priority_fee_per_gas: Some(U256::from(2)),
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	};
+	let secret_key = H256::from_slice(
+		&hex::decode("45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8").unwrap(),
+	);
+
+	let fixture = fixture::state_test(
+		"generated",
+		types::Fork::Shanghai,
+		&Config::shanghai(),
+		&backend,
+		&args,
+		secret_key,
+	)
+	.unwrap();
+	let path = std::env::temp_dir().join("jsontests_generated_state_test.json");
+	let path = path.to_str().unwrap();
+	fixture::write(&fixture, path).unwrap();
+
+	let tests_status = run::run_single(path, false, &TestFilter::default()).unwrap();
+	assert_eq!(tests_status.completed, 1);
+}
@@ -1,12 +1,19 @@
+mod blockchain;
+mod coverage;
+#[cfg(feature = "differential")]
+mod differential;
+mod eof;
 mod error;
+mod filter;
 mod hash;
-mod in_memory;
+mod parallel;
+mod report;
 mod run;
 mod types;
 
 use clap::Parser;
 
-use crate::{error::Error, types::*};
+use crate::{error::Error, filter::TestFilter, types::*};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -15,15 +22,139 @@ struct Cli {
 
 	#[arg(short, long, default_value_t = false)]
 	debug: bool,
+
+	/// Run the files as `BlockchainTests` instead of `GeneralStateTests`.
+	#[arg(short, long, default_value_t = false)]
+	blockchain: bool,
+
+	/// Run the files as `EOFTests` instead of `GeneralStateTests`.
+	#[arg(short, long, default_value_t = false)]
+	eof: bool,
+
+	/// Run the files on this number of threads, or on as many threads as
+	/// the available parallelism if 0. Debug output is disabled with more
+	/// than one thread.
+	#[arg(short, long, default_value_t = 1)]
+	jobs: usize,
+
+	/// Only run the tests whose name matches this pattern: `.` matches any
+	/// character, `*` repeats the previous one, `^` and `$` anchor the
+	/// pattern and `\\` escapes a character.
+	#[arg(long)]
+	filter: Option<String>,
+
+	/// Only run the tests of this fork or network.
+	#[arg(long)]
+	fork: Option<String>,
+
+	/// Only run this case of each test, such as a post state of a state
+	/// test.
+	#[arg(long)]
+	case_index: Option<usize>,
+
+	/// Print the opcodes, gas cost rules and frame exits exercised by the
+	/// state tests run.
+	#[arg(long, default_value_t = false)]
+	coverage: bool,
+
+	/// Write a JSON report of the tests to this file.
+	#[arg(long)]
+	report_json: Option<String>,
+
+	/// Write a JUnit XML report of the tests to this file.
+	#[arg(long)]
+	report_junit: Option<String>,
+
+	/// Execute the transactions of the `GeneralStateTests` with this `evm`
+	/// binary of geth too, and compare the outcomes instead of the state
+	/// roots. The files are run serially.
+	#[cfg(feature = "differential")]
+	#[arg(long)]
+	differential: Option<String>,
+}
+
+impl Cli {
+	#[cfg(feature = "differential")]
+	fn differential(&self) -> bool {
+		self.differential.is_some()
+	}
+
+	#[cfg(not(feature = "differential"))]
+	fn differential(&self) -> bool {
+		false
+	}
+}
+
+/// Run the `GeneralStateTests` of a file or directory.
+fn run_state(
+	cli: &Cli,
+	filename: &str,
+	filter: &TestFilter,
+) -> Result<TestCompletionStatus, Error> {
+	#[cfg(feature = "differential")]
+	if let Some(binary) = &cli.differential {
+		let reference = differential::GethT8n::new(binary.clone());
+		return differential::run_single(filename, cli.debug, filter, &reference);
+	}
+	run::run_single(filename, cli.debug, filter)
+}
+
+/// Run the files one after the other, stopping at the first error.
+fn run_serial(cli: &Cli, filter: &TestFilter) -> Result<TestCompletionStatus, Error> {
+	let mut tests_status = TestCompletionStatus::default();
+	for filename in &cli.filenames {
+		let result = if cli.blockchain {
+			blockchain::run_single(filename, cli.debug, filter)
+		} else if cli.eof {
+			eof::run_single(filename, cli.debug, filter)
+		} else {
+			run_state(cli, filename, filter)
+		};
+		match result {
+			Ok(file_status) => tests_status += file_status,
+			Err(err) => return Err(err.with_status(tests_status)),
+		}
+	}
+	Ok(tests_status)
 }
 
 fn main() -> Result<(), Error> {
 	let cli = Cli::parse();
 
-	let mut tests_status = TestCompletionStatus::default();
-	for filename in cli.filenames {
-		tests_status += run::run_single(&filename, cli.debug)?;
+	let filter = TestFilter {
+		name: cli.filter.clone(),
+		fork: cli.fork.clone(),
+		case_index: cli.case_index,
+	};
+	let result = if cli.jobs == 1 || cli.differential() {
+		run_serial(&cli, &filter)
+	} else {
+		let runner: parallel::FileRunner = if cli.blockchain {
+			blockchain::run_file
+		} else if cli.eof {
+			eof::run_file
+		} else {
+			run::run_file
+		};
+		parallel::run_parallel(&cli.filenames, runner, &filter, cli.jobs)
+	};
+
+	let default_status = TestCompletionStatus::default();
+	let reported_status = match &result {
+		Ok(tests_status) => tests_status,
+		Err(err) => err.status().unwrap_or(&default_status),
+	};
+	if let Some(path) = &cli.report_json {
+		report::write_json(reported_status, path)?;
 	}
+	if let Some(path) = &cli.report_junit {
+		report::write_junit(reported_status, path)?;
+	}
+	if cli.coverage {
+		reported_status.coverage.print_summary();
+	}
+
+	let tests_status = result.map_err(|err| err.split().1)?;
 	tests_status.print_total();
 
 	Ok(())
@@ -0,0 +1,129 @@
+//! Parallel execution of test files.
+//!
+//! Files are run on a pool of threads, each file on its own: an error or a
+//! panic in a file does not stop the others. Results are aggregated in the
+//! order of the files, whatever the order in which they finish.
+
+use std::{
+	fs,
+	panic::{self, AssertUnwindSafe},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		mpsc, Arc,
+	},
+	thread,
+};
+
+use crate::{error::Error, filter::TestFilter, types::TestCompletionStatus};
+
+/// Runner of the tests of a single file, such as [crate::run::run_file].
+pub type FileRunner = fn(&str, bool, &TestFilter) -> Result<TestCompletionStatus, Error>;
+
+/// Files to run for a file or a directory, as in `run_single`.
+fn files(filename: &str) -> Result<Vec<String>, Error> {
+	if !fs::metadata(filename)?.is_dir() {
+		return Ok(vec![filename.to_string()]);
+	}
+
+	let mut files = Vec::new();
+	for filename in fs::read_dir(filename)? {
+		let filepath = filename?.path();
+		let filename = filepath.to_str().ok_or(Error::NonUtf8Filename)?;
+		files.push(filename.to_string());
+	}
+	files.sort();
+	Ok(files)
+}
+
+/// Run the tests selected by `filter` in the files of `filenames`, or of
+/// the directories among them, with `runner` on `jobs` threads, or on as many threads as the available
+/// parallelism if `jobs` is 0.
+///
+/// The status of each file is printed once all of them are run. The error
+/// of the first failing file is returned, with the status of all files.
+pub fn run_parallel(
+	filenames: &[String],
+	runner: FileRunner,
+	filter: &TestFilter,
+	jobs: usize,
+) -> Result<TestCompletionStatus, Error> {
+	let mut all_files = Vec::new();
+	for filename in filenames {
+		all_files.extend(files(filename)?);
+	}
+	let all_files = Arc::new(all_files);
+	let filter = Arc::new(filter.clone());
+
+	let jobs = if jobs == 0 {
+		thread::available_parallelism().map_or(1, |jobs| jobs.get())
+	} else {
+		jobs
+	};
+
+	let next = Arc::new(AtomicUsize::new(0));
+	let (sender, receiver) = mpsc::channel();
+	let workers = (0..jobs.min(all_files.len()))
+		.map(|_| {
+			let all_files = all_files.clone();
+			let next = next.clone();
+			let filter = filter.clone();
+			let sender = sender.clone();
+			thread::spawn(move || loop {
+				let index = next.fetch_add(1, Ordering::SeqCst);
+				let filename = match all_files.get(index) {
+					Some(filename) => filename,
+					None => break,
+				};
+
+				let result =
+					panic::catch_unwind(AssertUnwindSafe(|| runner(filename, false, &filter)))
+						.unwrap_or_else(|payload| {
+							let message = payload
+								.downcast_ref::<&str>()
+								.map(|message| message.to_string())
+								.or_else(|| payload.downcast_ref::<String>().cloned())
+								.unwrap_or_default();
+							Err(Error::Panicked(message))
+						});
+				if sender.send((index, result)).is_err() {
+					break;
+				}
+			})
+		})
+		.collect::<Vec<_>>();
+	drop(sender);
+
+	let mut results = (0..all_files.len()).map(|_| None).collect::<Vec<_>>();
+	for (index, result) in receiver {
+		results[index] = Some(result);
+	}
+	for worker in workers {
+		let _ = worker.join();
+	}
+
+	let mut tests_status = TestCompletionStatus::default();
+	let mut first_error = None;
+	println!();
+	for (filename, result) in all_files.iter().zip(results) {
+		match result.unwrap_or(Err(Error::Panicked(String::new()))) {
+			Ok(file_status) => {
+				println!(
+					"{}: COMPLETED {}, SKIPPED {}",
+					filename, file_status.completed, file_status.skipped
+				);
+				tests_status += file_status;
+			}
+			Err(err) => {
+				let (file_status, err) = err.split();
+				tests_status += file_status;
+				println!("{}: ERROR: {:?}", filename, err);
+				first_error.get_or_insert(err);
+			}
+		}
+	}
+
+	match first_error {
+		Some(err) => Err(err.with_status(tests_status)),
+		None => Ok(tests_status),
+	}
+}
@@ -0,0 +1,139 @@
+//! Machine-readable reports of the tests run, for CI systems and
+//! dashboards: JSON, or JUnit XML with a test suite per file.
+
+use std::{fs, time::Duration};
+
+use serde_json::json;
+
+use crate::{
+	error::Error,
+	types::{TestCompletionStatus, TestOutcome, TestReport},
+};
+
+/// Write a JSON report of `status` to `path`.
+pub fn write_json(status: &TestCompletionStatus, path: &str) -> Result<(), Error> {
+	let tests = status
+		.reports
+		.iter()
+		.map(|report| {
+			let mut test = json!({
+				"file": report.file,
+				"name": report.name,
+				"fork": report.fork,
+				"duration": report.duration.as_secs_f64(),
+			});
+			match &report.outcome {
+				TestOutcome::Passed => test["outcome"] = json!("passed"),
+				TestOutcome::Skipped => test["outcome"] = json!("skipped"),
+				TestOutcome::Failed {
+					reason,
+					state_root_mismatch,
+				} => {
+					test["outcome"] = json!("failed");
+					test["reason"] = json!(reason);
+					if let Some((expected, actual)) = state_root_mismatch {
+						test["expectedStateRoot"] = json!(expected);
+						test["actualStateRoot"] = json!(actual);
+					}
+				}
+			}
+			test
+		})
+		.collect::<Vec<_>>();
+
+	let report = json!({
+		"completed": status.completed,
+		"skipped": status.skipped,
+		"failed": failures(&status.reports),
+		"tests": tests,
+	});
+	fs::write(path, serde_json::to_string_pretty(&report)?)?;
+	Ok(())
+}
+
+/// Write a JUnit XML report of `status` to `path`.
+pub fn write_junit(status: &TestCompletionStatus, path: &str) -> Result<(), Error> {
+	// Group the reports by file, in the order files are first run.
+	let mut suites: Vec<(&str, Vec<&TestReport>)> = Vec::new();
+	for report in &status.reports {
+		match suites.iter_mut().find(|(file, _)| *file == report.file) {
+			Some((_, reports)) => reports.push(report),
+			None => suites.push((&report.file, vec![report])),
+		}
+	}
+
+	let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+	xml += &format!(
+		"<testsuites tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{}\">\n",
+		status.reports.len(),
+		failures(&status.reports),
+		status.skipped,
+		seconds(status.reports.iter().map(|report| report.duration).sum()),
+	);
+	for (file, reports) in suites {
+		xml += &format!(
+			"  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{}\">\n",
+			escape(file),
+			reports.len(),
+			failures(reports.iter().copied()),
+			reports
+				.iter()
+				.filter(|report| report.outcome == TestOutcome::Skipped)
+				.count(),
+			seconds(reports.iter().map(|report| report.duration).sum()),
+		);
+		for report in reports {
+			xml += &format!(
+				"    <testcase name=\"{}\" classname=\"{}\" time=\"{}\"",
+				escape(&report.name),
+				escape(&report.fork),
+				seconds(report.duration),
+			);
+			match &report.outcome {
+				TestOutcome::Passed => xml += "/>\n",
+				TestOutcome::Skipped => xml += ">\n      <skipped/>\n    </testcase>\n",
+				TestOutcome::Failed {
+					reason,
+					state_root_mismatch,
+				} => {
+					let mut details = reason.clone();
+					if let Some((expected, actual)) = state_root_mismatch {
+						details += &format!(
+							"\nexpected state root: {:?}\nactual state root: {:?}",
+							expected, actual
+						);
+					}
+					xml += &format!(
+						">\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+						escape(reason),
+						escape(&details),
+					);
+				}
+			}
+		}
+		xml += "  </testsuite>\n";
+	}
+	xml += "</testsuites>\n";
+
+	fs::write(path, xml)?;
+	Ok(())
+}
+
+fn failures<'a>(reports: impl IntoIterator<Item = &'a TestReport>) -> usize {
+	reports
+		.into_iter()
+		.filter(|report| matches!(report.outcome, TestOutcome::Failed { .. }))
+		.count()
+}
+
+fn seconds(duration: Duration) -> String {
+	format!("{:.6}", duration.as_secs_f64())
+}
+
+fn escape(text: &str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+		.replace('\'', "&apos;")
+}
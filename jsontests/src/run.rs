@@ -1,29 +1,39 @@
 use std::{
 	collections::{BTreeMap, BTreeSet},
+	fmt,
 	fs::{self, File},
 	io::BufReader,
+	time::Instant,
 };
 
 use evm::{
-	backend::OverlayedBackend,
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment, OverlayedBackend},
 	interpreter::{error::Capture, runtime::GasState, utils::u256_to_h256, Interpreter},
 	standard::{Config, Etable, EtableResolver, Invoker, TransactArgs},
 };
 use evm_precompile::StandardPrecompileSet;
-use primitive_types::U256;
+use primitive_types::{H160, H256, U256};
 
 use crate::{
+	coverage::{Coverage, ExitCounter},
 	error::{Error, TestError},
-	in_memory::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
-	types::{Fork, TestCompletionStatus, TestData, TestExpectException, TestMulti},
+	filter::TestFilter,
+	types::{
+		Fork, TestCompletionStatus, TestData, TestExpectException, TestMulti, TestOutcome,
+		TestPreState, TestReport,
+	},
 };
 
-const BASIC_FILE_PATH_TO_TRIM: [&str; 2] = [
+const BASIC_FILE_PATH_TO_TRIM: [&str; 6] = [
 	"jsontests/res/ethtests/GeneralStateTests/",
 	"res/ethtests/GeneralStateTests/",
+	"jsontests/res/ethtests/BlockchainTests/",
+	"res/ethtests/BlockchainTests/",
+	"jsontests/res/ethtests/EOFTests/",
+	"res/ethtests/EOFTests/",
 ];
 
-fn get_short_file_name(filename: &str) -> String {
+pub(crate) fn get_short_file_name(filename: &str) -> String {
 	let mut short_file_name = String::from(filename);
 	for pattern in BASIC_FILE_PATH_TO_TRIM {
 		short_file_name = short_file_name.replace(pattern, "");
@@ -31,8 +41,98 @@ fn get_short_file_name(filename: &str) -> String {
 	short_file_name.clone().to_string()
 }
 
+/// Line of a test in the output. Without `debug`, the title of the test is
+/// printed with its outcome on a single line, so that the lines of files run
+/// in parallel do not mix. With `debug`, it is printed before the debug
+/// output of the test.
+pub(crate) struct TestLine {
+	file: String,
+	name: String,
+	fork: String,
+	debug: bool,
+	start: Instant,
+}
+
+impl TestLine {
+	pub(crate) fn new(file: &str, name: String, fork: String, debug: bool) -> Self {
+		let line = Self {
+			file: file.to_string(),
+			name,
+			fork,
+			debug,
+			start: Instant::now(),
+		};
+		if debug {
+			print!("{} DEBUG: ", line.title());
+		}
+		line
+	}
+
+	fn title(&self) -> String {
+		format!("[{}] {} | {}", self.fork, self.file, self.name)
+	}
+
+	fn outcome(&self, outcome: impl fmt::Display) {
+		if self.debug {
+			println!("{}", outcome);
+		} else {
+			println!("{}: {}", self.title(), outcome);
+		}
+	}
+
+	/// Print the outcome of the test and record it in `status`. Tests of
+	/// unsupported forks or transactions are skipped, other errors are
+	/// returned.
+	pub(crate) fn finish(
+		self,
+		status: &mut TestCompletionStatus,
+		result: Result<(), Error>,
+	) -> Result<(), Error> {
+		let duration = self.start.elapsed();
+		let (outcome, result) = match result {
+			Ok(()) => {
+				status.inc_completed();
+				self.outcome("ok");
+				(TestOutcome::Passed, Ok(()))
+			}
+			Err(Error::UnsupportedFork | Error::UnsupportedTransaction) => {
+				status.inc_skipped();
+				self.outcome("skipped");
+				(TestOutcome::Skipped, Ok(()))
+			}
+			Err(err) => {
+				self.outcome(format_args!("ERROR: {:?}", err));
+				let state_root_mismatch = match &err {
+					Error::Test(TestError::StateMismatch { expected, actual }) => {
+						Some((*expected, *actual))
+					}
+					_ => None,
+				};
+				let outcome = TestOutcome::Failed {
+					reason: format!("{:?}", err),
+					state_root_mismatch,
+				};
+				(outcome, Err(err))
+			}
+		};
+
+		status.reports.push(TestReport {
+			file: self.file,
+			name: self.name,
+			fork: self.fork,
+			duration,
+			outcome,
+		});
+		result
+	}
+}
+
 /// Run tests for specific json file with debug flag
-fn run_file(filename: &str, debug: bool) -> Result<TestCompletionStatus, Error> {
+pub fn run_file(
+	filename: &str,
+	debug: bool,
+	filter: &TestFilter,
+) -> Result<TestCompletionStatus, Error> {
 	let test_multi: BTreeMap<String, TestMulti> =
 		serde_json::from_reader(BufReader::new(File::open(filename)?))?;
 	let mut tests_status = TestCompletionStatus::default();
@@ -41,30 +141,24 @@ fn run_file(filename: &str, debug: bool) -> Result<TestCompletionStatus, Error>
 		let tests = test_multi.tests();
 		let short_file_name = get_short_file_name(filename);
 		for test in &tests {
-			if debug {
-				print!(
-					"[{:?}] {} | {}/{} DEBUG: ",
-					test.fork, short_file_name, test_name, test.index
-				);
-			} else {
-				print!(
-					"[{:?}] {} | {}/{}: ",
-					test.fork, short_file_name, test_name, test.index
-				);
+			if !filter.matches(&test_name, &format!("{:?}", test.fork), Some(test.index)) {
+				continue;
 			}
-			match run_test(filename, &test_name, test.clone(), debug) {
-				Ok(()) => {
-					tests_status.inc_completed();
-					println!("ok")
-				}
-				Err(Error::UnsupportedFork) => {
-					tests_status.inc_skipped();
-					println!("skipped")
-				}
-				Err(err) => {
-					println!("ERROR: {:?}", err);
-					return Err(err);
-				}
+			let line = TestLine::new(
+				&short_file_name,
+				format!("{}/{}", test_name, test.index),
+				format!("{:?}", test.fork),
+				debug,
+			);
+			let result = run_test(
+				filename,
+				&test_name,
+				test.clone(),
+				debug,
+				&mut tests_status.coverage,
+			);
+			if let Err(err) = line.finish(&mut tests_status, result) {
+				return Err(err.with_status(tests_status));
 			}
 			if debug {
 				println!();
@@ -77,8 +171,12 @@ fn run_file(filename: &str, debug: bool) -> Result<TestCompletionStatus, Error>
 	Ok(tests_status)
 }
 
-/// Run test for single json file or directory
-pub fn run_single(filename: &str, debug: bool) -> Result<TestCompletionStatus, Error> {
+/// Run the tests selected by `filter` for single json file or directory
+pub fn run_single(
+	filename: &str,
+	debug: bool,
+	filter: &TestFilter,
+) -> Result<TestCompletionStatus, Error> {
 	if fs::metadata(filename)?.is_dir() {
 		let mut tests_status = TestCompletionStatus::default();
 
@@ -86,48 +184,31 @@ pub fn run_single(filename: &str, debug: bool) -> Result<TestCompletionStatus, E
 			let filepath = filename?.path();
 			let filename = filepath.to_str().ok_or(Error::NonUtf8Filename)?;
 			println!("RUM for: {filename}");
-			tests_status += run_file(filename, debug)?;
+			match run_file(filename, debug, filter) {
+				Ok(file_status) => tests_status += file_status,
+				Err(err) => return Err(err.with_status(tests_status)),
+			}
 		}
 		tests_status.print_total_for_dir(filename);
 		Ok(tests_status)
 	} else {
-		run_file(filename, debug)
+		run_file(filename, debug, filter)
 	}
 }
 
-/// Run single test
-pub fn run_test(
-	_filename: &str,
-	_test_name: &str,
-	test: TestData,
-	debug: bool,
-) -> Result<(), Error> {
-	let config = match test.fork {
-		Fork::Berlin => Config::berlin(),
-		_ => return Err(Error::UnsupportedFork),
-	};
-
-	if test.post.expect_exception == Some(TestExpectException::TR_TypeNotSupported) {
-		// The `evm` crate does not understand transaction format, only the `ethereum` crate. So
-		// there's nothing for us to test here for `TR_TypeNotSupported`.
-		return Ok(());
+/// Config of a fork, `None` if the fork is not supported.
+pub fn fork_config(fork: Fork) -> Option<Config> {
+	match fork {
+		Fork::Berlin | Fork::London | Fork::Merge | Fork::Shanghai | Fork::Cancun => {
+			Config::from_fork_name(&format!("{:?}", fork))
+		}
+		_ => None,
 	}
+}
 
-	let env = InMemoryEnvironment {
-		block_hashes: BTreeMap::new(), // TODO: fill in this field.
-		block_number: test.env.current_number,
-		block_coinbase: test.env.current_coinbase,
-		block_timestamp: test.env.current_timestamp,
-		block_difficulty: test.env.current_difficulty,
-		block_randomness: Some(test.env.current_random),
-		block_gas_limit: test.env.current_gas_limit,
-		block_base_fee_per_gas: test.transaction.gas_price,
-		chain_id: U256::zero(), // TODO: fill in this field.
-	};
-
-	let state = test
-		.pre
-		.clone()
+/// Convert accounts of a test to the state of an [InMemoryBackend].
+pub fn in_memory_state(accounts: BTreeMap<H160, TestPreState>) -> BTreeMap<H160, InMemoryAccount> {
+	accounts
 		.into_iter()
 		.map(|(address, account)| {
 			let storage = account
@@ -144,40 +225,119 @@ pub fn run_test(
 					code: account.code.0,
 					nonce: account.nonce,
 					storage,
-					transient_storage: Default::default(),
 				},
 			)
 		})
-		.collect::<BTreeMap<_, _>>();
+		.collect()
+}
+
+/// Addresses accessed before a transaction starts: the precompiles.
+pub fn initial_accessed() -> BTreeSet<(H160, Option<H256>)> {
+	let mut hots = BTreeSet::new();
+	for i in 1..10 {
+		hots.insert((u256_to_h256(U256::from(i)).into(), None));
+	}
+	hots
+}
+
+/// Environment of the block of a test.
+pub fn test_environment(test: &TestData, config: &Config) -> InMemoryEnvironment {
+	InMemoryEnvironment {
+		block_hashes: BTreeMap::new(), // TODO: fill in this field.
+		block_number: test.env.current_number,
+		block_coinbase: test.env.current_coinbase,
+		block_timestamp: test.env.current_timestamp,
+		block_difficulty: test.env.current_difficulty,
+		block_randomness: Some(test.env.current_random),
+		block_gas_limit: test.env.current_gas_limit,
+		block_base_fee_per_gas: if config.eip_1559_enabled {
+			test.env.current_base_fee
+		} else {
+			test.transaction.gas_price
+		},
+		block_excess_blob_gas: test.env.current_excess_blob_gas,
+		chain_id: test.chain_id,
+	}
+}
+
+/// Arguments of the transaction of a test.
+pub fn test_transact_args(test: &TestData) -> TransactArgs {
+	let transaction = test.transaction.clone();
+	let access_list = transaction
+		.access_list
+		.into_iter()
+		.map(|access| (access.address, access.storage_keys))
+		.collect();
+	match transaction.to {
+		Some(address) => TransactArgs::Call {
+			caller: transaction.sender,
+			address,
+			value: transaction.value,
+			data: transaction.data,
+			nonce: None,
+			fee_payer: None,
+			gas_limit: transaction.gas_limit,
+			gas_price: transaction.gas_price,
+			max_priority_fee_per_gas: transaction.gas_priority_fee,
+			access_list,
+			blob_hashes: transaction.blob_versioned_hashes,
+			max_fee_per_blob_gas: transaction.max_fee_per_blob_gas,
+			overrides: Default::default(),
+		},
+		None => TransactArgs::Create {
+			caller: transaction.sender,
+			value: transaction.value,
+			init_code: transaction.data,
+			salt: None,
+			nonce: None,
+			fee_payer: None,
+			gas_limit: transaction.gas_limit,
+			gas_price: transaction.gas_price,
+			max_priority_fee_per_gas: transaction.gas_priority_fee,
+			access_list,
+			overrides: Default::default(),
+		},
+	}
+}
+
+/// Run single test, recording the parts of the interpreter it exercises in
+/// `coverage`
+pub fn run_test(
+	_filename: &str,
+	_test_name: &str,
+	test: TestData,
+	debug: bool,
+	coverage: &mut Coverage,
+) -> Result<(), Error> {
+	let config = fork_config(test.fork).ok_or(Error::UnsupportedFork)?;
+	if test.transaction.has_authorization_list {
+		// Transactions with EIP-7702 authorizations cannot be expressed as
+		// `TransactArgs`.
+		return Err(Error::UnsupportedTransaction);
+	}
+
+	if test.post.expect_exception == Some(TestExpectException::TR_TypeNotSupported) {
+		// The `evm` crate does not understand transaction format, only the `ethereum` crate. So
+		// there's nothing for us to test here for `TR_TypeNotSupported`.
+		return Ok(());
+	}
+
+	let env = test_environment(&test, &config);
+
+	let state = in_memory_state(test.pre.clone());
 
 	let gas_etable = Etable::single(evm::standard::eval_gasometer);
 	let exec_etable = Etable::runtime();
 	let etable = (gas_etable, exec_etable);
 	let precompiles = StandardPrecompileSet::new(&config);
 	let resolver = EtableResolver::new(&config, &precompiles, &etable);
-	let invoker = Invoker::new(&config, &resolver);
-	let args = TransactArgs::Call {
-		caller: test.transaction.sender,
-		address: test.transaction.to,
-		value: test.transaction.value,
-		data: test.transaction.data,
-		gas_limit: test.transaction.gas_limit,
-		gas_price: test.transaction.gas_price,
-		access_list: test
-			.transaction
-			.access_list
-			.into_iter()
-			.map(|access| (access.address, access.storage_keys))
-			.collect(),
-	};
+	let exits = ExitCounter::default();
+	let invoker = Invoker::new(&config, &resolver)
+		.with_gas_profile(true)
+		.with_frame_hook(&exits);
+	let args = test_transact_args(&test);
 
-	let initial_accessed = {
-		let mut hots = BTreeSet::new();
-		for i in 1..10 {
-			hots.insert((u256_to_h256(U256::from(i)).into(), None));
-		}
-		hots
-	};
+	let initial_accessed = initial_accessed();
 
 	let base_backend = InMemoryBackend {
 		environment: env,
@@ -188,7 +348,17 @@ pub fn run_test(
 	let mut step_backend = OverlayedBackend::new(&base_backend, initial_accessed.clone());
 
 	// Run
-	let run_result = evm::transact(args.clone(), Some(4), &mut run_backend, &invoker);
+	let run_result =
+		evm::standard::transact_with_gas(args.clone(), Some(4), &mut run_backend, &invoker)
+			.and_then(|(result, gas)| {
+				if let Some(profile) = &gas.profile {
+					coverage.record_profile(profile);
+				}
+				result
+			});
+	for (exit, count) in exits.take() {
+		*coverage.exits.entry(exit).or_default() += count;
+	}
 	let run_changeset = run_backend.deconstruct().1;
 	let mut run_backend = base_backend.clone();
 	run_backend.apply_overlayed(&run_changeset);
@@ -241,7 +411,11 @@ pub fn run_test(
 			}
 		}
 
-		return Err(TestError::StateMismatch.into());
+		return Err(TestError::StateMismatch {
+			expected: test.post.hash,
+			actual: state_root,
+		}
+		.into());
 	}
 
 	Ok(())
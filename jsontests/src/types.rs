@@ -1,5 +1,6 @@
-use std::{collections::BTreeMap, fmt};
+use std::{collections::BTreeMap, fmt, time::Duration};
 
+use evm::standard::ForkActivation;
 use hex::FromHex;
 use primitive_types::{H160, H256, U256};
 use serde::{
@@ -7,20 +8,52 @@ use serde::{
 	Deserialize, Deserializer,
 };
 
+use crate::coverage::Coverage;
+
 /// Statistic type to gather tests pass completion status
 #[derive(Default, Clone, Debug, Eq, PartialEq)]
 pub struct TestCompletionStatus {
 	pub completed: usize,
 	pub skipped: usize,
+	/// Reports of the tests run, in order, including a failed test.
+	pub reports: Vec<TestReport>,
+	/// Coverage of the interpreter by the state tests run.
+	pub coverage: Coverage,
 }
 
 impl std::ops::AddAssign for TestCompletionStatus {
 	fn add_assign(&mut self, rhs: Self) {
 		self.completed += rhs.completed;
 		self.skipped += rhs.skipped;
+		self.reports.extend(rhs.reports);
+		self.coverage += rhs.coverage;
 	}
 }
 
+/// Report of a single test.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TestReport {
+	/// File of the test, without the common prefix of the test suites.
+	pub file: String,
+	pub name: String,
+	/// Fork or network the test is run for.
+	pub fork: String,
+	pub duration: Duration,
+	pub outcome: TestOutcome,
+}
+
+/// Outcome of a test in a [TestReport].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TestOutcome {
+	Passed,
+	Skipped,
+	Failed {
+		reason: String,
+		/// Expected and actual state roots, if they are different.
+		state_root_mismatch: Option<(H256, H256)>,
+	},
+}
+
 impl TestCompletionStatus {
 	/// Increment `completed` statistic field
 	pub fn inc_completed(&mut self) {
@@ -40,8 +73,10 @@ impl TestCompletionStatus {
 	/// Print completion status.
 	/// Most useful for single file completion statistic info
 	pub fn print_completion(&self) {
-		println!("COMPLETED: {} tests", self.completed);
-		println!("SKIPPED: {} tests\n", self.skipped);
+		println!(
+			"COMPLETED: {} tests\nSKIPPED: {} tests\n",
+			self.completed, self.skipped
+		);
 	}
 
 	/// Print tests pass total statistic info for directory
@@ -65,6 +100,10 @@ impl TestCompletionStatus {
 
 /// `TestMulti` represents raw data from `jsontest` data file.
 /// It contains multiple test data for passing tests.
+///
+/// Both the `ethereum/tests` layout and the one of
+/// [execution-spec-tests](https://github.com/ethereum/execution-spec-tests)
+/// fixtures are accepted.
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
 pub struct TestMulti {
 	#[serde(rename = "_info")]
@@ -73,6 +112,8 @@ pub struct TestMulti {
 	pub post: BTreeMap<Fork, Vec<TestPostState>>,
 	pub pre: BTreeMap<H160, TestPreState>,
 	pub transaction: TestMultiTransaction,
+	/// Chain config of execution-spec-tests fixtures.
+	pub config: Option<TestConfig>,
 }
 
 impl TestMulti {
@@ -91,12 +132,18 @@ impl TestMulti {
 					index,
 					post: post_state.clone(),
 					pre: self.pre.clone(),
+					chain_id: self
+						.config
+						.as_ref()
+						.and_then(|config| config.chainid)
+						.unwrap_or(U256::one()),
 					transaction: TestTransaction {
 						data: self.transaction.data[post_state.indexes.data].0.clone(),
 						gas_limit: self.transaction.gas_limit[post_state.indexes.gas],
 						gas_price: self
 							.transaction
 							.gas_price
+							.or(self.transaction.max_fee_per_gas)
 							.unwrap_or(self.env.current_base_fee),
 						gas_priority_fee: self.transaction.max_priority_fee_per_gas,
 						nonce: self.transaction.nonce,
@@ -108,6 +155,9 @@ impl TestMulti {
 							Some(access_lists) => access_lists[post_state.indexes.data].clone(),
 							None => Vec::new(),
 						},
+						max_fee_per_blob_gas: self.transaction.max_fee_per_blob_gas,
+						blob_versioned_hashes: self.transaction.blob_versioned_hashes.clone(),
+						has_authorization_list: self.transaction.authorization_list.is_some(),
 					},
 				});
 			}
@@ -126,18 +176,24 @@ pub struct TestData {
 	pub index: usize,
 	pub post: TestPostState,
 	pub pre: BTreeMap<H160, TestPreState>,
+	pub chain_id: U256,
 	pub transaction: TestTransaction,
 }
 
-/// `TestInfo` contains information data about test from json file
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
-#[serde(rename_all = "camelCase")]
+/// `TestInfo` contains information data about test from json file. Fields
+/// missing in the layout of the test are empty.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct TestInfo {
 	pub comment: String,
 	#[serde(rename = "filling-rpc-server")]
 	pub filling_rpc_server: String,
 	#[serde(rename = "filling-tool-version")]
 	pub filling_tool_version: String,
+	#[serde(rename = "filling-transition-tool")]
+	pub filling_transition_tool: String,
+	#[serde(rename = "fixture-format")]
+	pub fixture_format: String,
 	pub generated_test_hash: String,
 	pub lllcversion: String,
 	pub solidity: String,
@@ -145,19 +201,33 @@ pub struct TestInfo {
 	pub source_hash: String,
 }
 
-/// `TestEnv` represents Ethereum environment data
+/// `TestConfig` is the chain config of execution-spec-tests fixtures.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct TestConfig {
+	pub chainid: Option<U256>,
+}
+
+/// `TestEnv` represents Ethereum environment data. Fields not given by a
+/// fork, or by execution-spec-tests fixtures, are zero.
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TestEnv {
+	#[serde(default)]
 	pub current_base_fee: U256,
+	#[serde(default)]
 	pub current_beacon_root: H256,
 	pub current_coinbase: H160,
 	pub current_difficulty: U256,
+	#[serde(default)]
+	pub current_excess_blob_gas: U256,
 	pub current_gas_limit: U256,
 	pub current_number: U256,
+	#[serde(default)]
 	pub current_random: H256,
 	pub current_timestamp: U256,
+	#[serde(default)]
 	pub current_withdrawals_root: H256,
+	#[serde(default)]
 	pub previous_hash: H256,
 }
 
@@ -167,7 +237,10 @@ pub enum Fork {
 	Berlin,
 	Cancun,
 	London,
+	#[serde(alias = "Paris")]
 	Merge,
+	Osaka,
+	Prague,
 	Shanghai,
 	Byzantium,
 	Constantinople,
@@ -179,6 +252,55 @@ pub enum Fork {
 	Istanbul,
 }
 
+impl Fork {
+	/// Fork of the given name, as in the post states of state tests.
+	pub fn from_name(name: &str) -> Option<Self> {
+		Self::deserialize(serde::de::value::StrDeserializer::<serde::de::value::Error>::new(name))
+			.ok()
+	}
+}
+
+/// `TestNetwork` is the network of a blockchain test: a single fork, or a
+/// transition between two forks, such as `BerlinToLondonAt5` or
+/// `ShanghaiToCancunAtTime15k`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TestNetwork {
+	Fork(Fork),
+	Transition {
+		from: Fork,
+		to: Fork,
+		at: ForkActivation,
+	},
+}
+
+impl TestNetwork {
+	/// Parse a network name. `None` if a fork is unknown, or if the
+	/// transition is not at a block number or timestamp.
+	pub fn from_name(name: &str) -> Option<Self> {
+		let (from, rest) = match name.split_once("To") {
+			Some(transition) => transition,
+			None => return Fork::from_name(name).map(Self::Fork),
+		};
+		let (to, at) = rest.split_once("At")?;
+
+		// Activations are numbers, with a `k` suffix for thousands.
+		let number = |value: &str| match value.strip_suffix('k') {
+			Some(thousands) => thousands.parse::<u64>().ok()?.checked_mul(1000),
+			None => value.parse::<u64>().ok(),
+		};
+		let at = match at.strip_prefix("Time") {
+			Some(timestamp) => ForkActivation::Timestamp(number(timestamp)?),
+			None => ForkActivation::Block(number(at)?),
+		};
+
+		Some(Self::Transition {
+			from: Fork::from_name(from)?,
+			to: Fork::from_name(to)?,
+			at,
+		})
+	}
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TestPostState {
@@ -187,14 +309,21 @@ pub struct TestPostState {
 	pub logs: H256,
 	pub txbytes: HexBytes,
 	pub expect_exception: Option<TestExpectException>,
+	/// Full post state, given by execution-spec-tests fixtures.
+	pub state: Option<BTreeMap<H160, TestPreState>>,
 }
 
 /// `TestExpectException` expected Ethereum exception
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
 #[allow(non_camel_case_types)]
 pub enum TestExpectException {
+	#[serde(alias = "TransactionException.TYPE_NOT_SUPPORTED")]
 	TR_TypeNotSupported,
+	#[serde(alias = "TransactionException.INTRINSIC_GAS_TOO_LOW")]
 	TR_IntrinsicGas,
+	/// Any other exception, or several of them.
+	#[serde(other)]
+	Other,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
@@ -223,9 +352,24 @@ pub struct TestMultiTransaction {
 	pub nonce: U256,
 	pub secret_key: H256,
 	pub sender: H160,
-	pub to: H160,
+	/// Empty for create transactions.
+	#[serde(deserialize_with = "deserialize_to")]
+	pub to: Option<H160>,
 	pub value: Vec<U256>,
 	pub access_lists: Option<Vec<Vec<TestAccessListItem>>>,
+	pub max_fee_per_blob_gas: Option<U256>,
+	#[serde(default)]
+	pub blob_versioned_hashes: Vec<H256>,
+	pub authorization_list: Option<Vec<TestAuthorization>>,
+}
+
+/// Authorization of an EIP-7702 transaction.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestAuthorization {
+	pub chain_id: U256,
+	pub address: H160,
+	pub nonce: U256,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
@@ -244,14 +388,98 @@ pub struct TestTransaction {
 	pub nonce: U256,
 	pub secret_key: H256,
 	pub sender: H160,
-	pub to: H160,
+	pub to: Option<H160>,
 	pub value: U256,
 	pub access_list: Vec<TestAccessListItem>,
+	pub max_fee_per_blob_gas: Option<U256>,
+	pub blob_versioned_hashes: Vec<H256>,
+	pub has_authorization_list: bool,
+}
+
+/// `BlockchainTest` represents a single test of a `BlockchainTests` data
+/// file: blocks imported on top of a genesis block, and the expected head and
+/// state of the chain.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockchainTest {
+	pub blocks: Vec<TestBlock>,
+	#[serde(rename = "genesisRLP")]
+	pub genesis_rlp: HexBytes,
+	pub lastblockhash: H256,
+	pub network: String,
+	pub post_state: Option<BTreeMap<H160, TestPreState>>,
+	pub post_state_hash: Option<H256>,
+	pub pre: BTreeMap<H160, TestPreState>,
+}
+
+/// Block of a `BlockchainTest`, rejected if it expects an exception.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestBlock {
+	pub rlp: HexBytes,
+	pub expect_exception: Option<String>,
+}
+
+/// `EofTest` represents a single test of an `EOFTests` data file: containers
+/// to validate, with the expected result for each fork.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct EofTest {
+	#[serde(rename = "_info", default)]
+	pub info: TestInfo,
+	pub vectors: BTreeMap<String, EofTestVector>,
+}
+
+/// Container of an `EofTest`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EofTestVector {
+	pub code: HexBytes,
+	#[serde(default)]
+	pub container_kind: EofTestContainerKind,
+	pub results: BTreeMap<String, EofTestResult>,
+}
+
+/// How the container of an `EofTestVector` is used.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum EofTestContainerKind {
+	Runtime,
+	Initcode,
+}
+
+impl Default for EofTestContainerKind {
+	fn default() -> Self {
+		Self::Runtime
+	}
+}
+
+/// Expected validation result of an `EofTestVector` for a fork. The
+/// exception is given for invalid containers, as `EOFException.<NAME>`,
+/// possibly with alternatives separated by `|`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct EofTestResult {
+	pub result: bool,
+	pub exception: Option<String>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
 pub struct HexBytes(#[serde(deserialize_with = "deserialize_hex_bytes")] pub Vec<u8>);
 
+fn deserialize_to<'de, D>(deserializer: D) -> Result<Option<H160>, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	let to = String::deserialize(deserializer)?;
+	if to.is_empty() {
+		return Ok(None);
+	}
+
+	let to = to.strip_prefix("0x").unwrap_or(&to);
+	<[u8; 20]>::from_hex(to)
+		.map(|to| Some(H160(to)))
+		.map_err(Error::custom)
+}
+
 fn deserialize_hex_bytes<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
 where
 	D: Deserializer<'de>,
@@ -0,0 +1,219 @@
+use alloc::{
+	collections::{BTreeMap, BTreeSet},
+	vec::Vec,
+};
+use core::{cell::RefCell, future::Future};
+
+use primitive_types::{H160, H256, U256};
+
+use crate::backend::{RuntimeBaseBackend, RuntimeEnvironment};
+
+/// Basic information of an account, as fetched by an [AsyncRuntimeBackend].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AsyncAccount {
+	/// Account balance.
+	pub balance: U256,
+	/// Account nonce.
+	pub nonce: U256,
+}
+
+/// A backend whose state lives behind asynchronous calls, like a remote
+/// key-value store. The block environment is expected to be known upfront.
+pub trait AsyncRuntimeBackend: RuntimeEnvironment {
+	/// Error of a lookup.
+	type Error;
+
+	/// Get an account, or `None` if it does not exist.
+	fn account(
+		&self,
+		address: H160,
+	) -> impl Future<Output = Result<Option<AsyncAccount>, Self::Error>>;
+	/// Get code of an account.
+	fn code(&self, address: H160) -> impl Future<Output = Result<Vec<u8>, Self::Error>>;
+	/// Get storage value of an account at index.
+	fn storage(
+		&self,
+		address: H160,
+		index: H256,
+	) -> impl Future<Output = Result<H256, Self::Error>>;
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+enum Lookup {
+	Account(H160),
+	Code(H160),
+	Storage(H160, H256),
+}
+
+/// Synchronous view of the state already fetched from an
+/// [AsyncRuntimeBackend]. Lookups of state not yet fetched return the default
+/// value and are recorded, so that they can be fetched before executing again.
+pub struct AsyncSnapshot<'backend, A> {
+	backend: &'backend A,
+	accounts: RefCell<BTreeMap<H160, Option<AsyncAccount>>>,
+	codes: RefCell<BTreeMap<H160, Vec<u8>>>,
+	storages: RefCell<BTreeMap<(H160, H256), H256>>,
+	missing: RefCell<BTreeSet<Lookup>>,
+}
+
+impl<'backend, A: AsyncRuntimeBackend> AsyncSnapshot<'backend, A> {
+	fn new(backend: &'backend A) -> Self {
+		Self {
+			backend,
+			accounts: RefCell::new(BTreeMap::new()),
+			codes: RefCell::new(BTreeMap::new()),
+			storages: RefCell::new(BTreeMap::new()),
+			missing: RefCell::new(BTreeSet::new()),
+		}
+	}
+
+	/// Whether all lookups so far were served from fetched state.
+	pub fn is_complete(&self) -> bool {
+		self.missing.borrow().is_empty()
+	}
+
+	fn account(&self, address: H160) -> Option<AsyncAccount> {
+		match self.accounts.borrow().get(&address) {
+			Some(account) => account.clone(),
+			None => {
+				self.missing.borrow_mut().insert(Lookup::Account(address));
+				None
+			}
+		}
+	}
+
+	async fn fetch_missing(&self) -> Result<(), A::Error> {
+		let missing = core::mem::take(&mut *self.missing.borrow_mut());
+
+		for lookup in missing {
+			match lookup {
+				Lookup::Account(address) => {
+					let account = self.backend.account(address).await?;
+					self.accounts.borrow_mut().insert(address, account);
+				}
+				Lookup::Code(address) => {
+					let code = self.backend.code(address).await?;
+					self.codes.borrow_mut().insert(address, code);
+				}
+				Lookup::Storage(address, index) => {
+					let value = self.backend.storage(address, index).await?;
+					self.storages.borrow_mut().insert((address, index), value);
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl<'backend, A: AsyncRuntimeBackend> RuntimeEnvironment for AsyncSnapshot<'backend, A> {
+	fn block_hash(&self, number: U256) -> H256 {
+		self.backend.block_hash(number)
+	}
+
+	fn block_number(&self) -> U256 {
+		self.backend.block_number()
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		self.backend.block_coinbase()
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		self.backend.block_timestamp()
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		self.backend.block_difficulty()
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		self.backend.block_randomness()
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		self.backend.block_gas_limit()
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		self.backend.block_base_fee_per_gas()
+	}
+
+	fn block_excess_blob_gas(&self) -> U256 {
+		self.backend.block_excess_blob_gas()
+	}
+
+	fn chain_id(&self) -> U256 {
+		self.backend.chain_id()
+	}
+}
+
+impl<'backend, A: AsyncRuntimeBackend> RuntimeBaseBackend for AsyncSnapshot<'backend, A> {
+	fn balance(&self, address: H160) -> U256 {
+		self.account(address)
+			.map(|account| account.balance)
+			.unwrap_or_default()
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		match self.codes.borrow().get(&address) {
+			Some(code) => code.clone(),
+			None => {
+				self.missing.borrow_mut().insert(Lookup::Code(address));
+				Vec::new()
+			}
+		}
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		match self.storages.borrow().get(&(address, index)) {
+			Some(value) => *value,
+			None => {
+				self.missing
+					.borrow_mut()
+					.insert(Lookup::Storage(address, index));
+				H256::default()
+			}
+		}
+	}
+
+	fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+		H256::default()
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		self.account(address).is_some()
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		self.account(address)
+			.map(|account| account.nonce)
+			.unwrap_or_default()
+	}
+}
+
+/// Execute against an [AsyncRuntimeBackend] without blocking the thread on
+/// lookups.
+///
+/// `execute` runs the execution synchronously on an [AsyncSnapshot], usually by
+/// wrapping it in an [crate::backend::OverlayedBackend] and calling
+/// [crate::transact]. Whenever the execution touches state that is not
+/// fetched yet, the missing state is awaited and `execute` runs again from
+/// scratch, until it completes with all the state it needs. `execute` must
+/// therefore not have side effects other than on its snapshot.
+pub async fn transact_async<A, F, R>(backend: &A, mut execute: F) -> Result<R, A::Error>
+where
+	A: AsyncRuntimeBackend,
+	F: FnMut(&AsyncSnapshot<'_, A>) -> R,
+{
+	let snapshot = AsyncSnapshot::new(backend);
+
+	loop {
+		let ret = execute(&snapshot);
+		if snapshot.is_complete() {
+			return Ok(ret);
+		}
+
+		snapshot.fetch_missing().await?;
+	}
+}
@@ -0,0 +1,298 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::cell::{Cell, RefCell};
+
+use primitive_types::{H160, H256, U256};
+
+use crate::backend::{RuntimeBaseBackend, RuntimeEnvironment};
+
+/// Hit and miss statistics of a [CachedBackend].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+	/// Lookups served from the cache.
+	pub hits: u64,
+	/// Lookups forwarded to the inner backend.
+	pub misses: u64,
+}
+
+struct Lru<K, V> {
+	entries: BTreeMap<K, (V, u64)>,
+	recency: BTreeMap<u64, K>,
+	tick: u64,
+	capacity: usize,
+}
+
+impl<K: Ord + Clone, V> Lru<K, V> {
+	fn new(capacity: usize) -> Self {
+		Self {
+			entries: BTreeMap::new(),
+			recency: BTreeMap::new(),
+			tick: 0,
+			capacity,
+		}
+	}
+
+	fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+		self.tick += 1;
+		let (value, last_used) = self.entries.get_mut(key)?;
+		self.recency.remove(last_used);
+		*last_used = self.tick;
+		self.recency.insert(self.tick, key.clone());
+		Some(value)
+	}
+
+	fn insert(&mut self, key: K, value: V) {
+		if self.capacity == 0 {
+			return;
+		}
+
+		self.remove(&key);
+		while self.entries.len() >= self.capacity {
+			let oldest = self.recency.keys().next().copied();
+			match oldest.and_then(|tick| self.recency.remove(&tick)) {
+				Some(evicted) => {
+					self.entries.remove(&evicted);
+				}
+				None => break,
+			}
+		}
+
+		self.tick += 1;
+		self.recency.insert(self.tick, key.clone());
+		self.entries.insert(key, (value, self.tick));
+	}
+
+	fn remove(&mut self, key: &K) {
+		if let Some((_, last_used)) = self.entries.remove(key) {
+			self.recency.remove(&last_used);
+		}
+	}
+
+	fn retain(&mut self, mut f: impl FnMut(&K) -> bool) {
+		let recency = &mut self.recency;
+		self.entries.retain(|key, (_, last_used)| {
+			let keep = f(key);
+			if !keep {
+				recency.remove(last_used);
+			}
+			keep
+		});
+	}
+
+	fn clear(&mut self) {
+		self.entries.clear();
+		self.recency.clear();
+	}
+}
+
+#[derive(Clone, Default)]
+struct CachedAccount {
+	balance: Option<U256>,
+	nonce: Option<U256>,
+	code_hash: Option<H256>,
+	exists: Option<bool>,
+}
+
+/// A read-through cache over a slow base backend, such as an RPC or on-disk
+/// trie backend.
+///
+/// Account fields, storage and code (by code hash) are memoized across
+/// transactions, each bounded to `capacity` entries with least recently used
+/// eviction. Like any base backend, it is meant to be wrapped in an
+/// [crate::backend::OverlayedBackend] for execution. When the state of the
+/// inner backend changes, for example after applying a block, invalidate the
+/// affected accounts.
+pub struct CachedBackend<B> {
+	backend: B,
+	accounts: RefCell<Lru<H160, CachedAccount>>,
+	storages: RefCell<Lru<(H160, H256), H256>>,
+	codes: RefCell<Lru<H256, Vec<u8>>>,
+	hits: Cell<u64>,
+	misses: Cell<u64>,
+}
+
+impl<B> CachedBackend<B> {
+	/// Wrap a backend, caching up to `capacity` entries of each kind.
+	pub fn new(backend: B, capacity: usize) -> Self {
+		Self {
+			backend,
+			accounts: RefCell::new(Lru::new(capacity)),
+			storages: RefCell::new(Lru::new(capacity)),
+			codes: RefCell::new(Lru::new(capacity)),
+			hits: Cell::new(0),
+			misses: Cell::new(0),
+		}
+	}
+
+	/// The inner backend.
+	pub fn inner(&self) -> &B {
+		&self.backend
+	}
+
+	/// Mutable inner backend. Invalidate the cache for anything changed
+	/// through it.
+	pub fn inner_mut(&mut self) -> &mut B {
+		&mut self.backend
+	}
+
+	/// Unwrap the inner backend.
+	pub fn into_inner(self) -> B {
+		self.backend
+	}
+
+	/// Hit and miss statistics.
+	pub fn stats(&self) -> CacheStats {
+		CacheStats {
+			hits: self.hits.get(),
+			misses: self.misses.get(),
+		}
+	}
+
+	/// Reset the statistics.
+	pub fn reset_stats(&self) {
+		self.hits.set(0);
+		self.misses.set(0);
+	}
+
+	/// Drop the cached fields and storage of an account.
+	pub fn invalidate(&self, address: H160) {
+		self.accounts.borrow_mut().remove(&address);
+		self.storages
+			.borrow_mut()
+			.retain(|(storage_address, _)| *storage_address != address);
+	}
+
+	/// Drop all cached entries.
+	pub fn clear(&self) {
+		self.accounts.borrow_mut().clear();
+		self.storages.borrow_mut().clear();
+		self.codes.borrow_mut().clear();
+	}
+
+	fn account_field<T: Copy>(
+		&self,
+		address: H160,
+		field: impl Fn(&mut CachedAccount) -> &mut Option<T>,
+		fetch: impl FnOnce() -> T,
+	) -> T {
+		let mut accounts = self.accounts.borrow_mut();
+		if let Some(value) = accounts.get_mut(&address).and_then(|a| *field(a)) {
+			self.hits.set(self.hits.get() + 1);
+			return value;
+		}
+
+		self.misses.set(self.misses.get() + 1);
+		let value = fetch();
+		match accounts.get_mut(&address) {
+			Some(account) => *field(account) = Some(value),
+			None => {
+				let mut account = CachedAccount::default();
+				*field(&mut account) = Some(value);
+				accounts.insert(address, account);
+			}
+		}
+		value
+	}
+}
+
+impl<B: RuntimeEnvironment> RuntimeEnvironment for CachedBackend<B> {
+	fn block_hash(&self, number: U256) -> H256 {
+		self.backend.block_hash(number)
+	}
+
+	fn block_number(&self) -> U256 {
+		self.backend.block_number()
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		self.backend.block_coinbase()
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		self.backend.block_timestamp()
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		self.backend.block_difficulty()
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		self.backend.block_randomness()
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		self.backend.block_gas_limit()
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		self.backend.block_base_fee_per_gas()
+	}
+
+	fn block_excess_blob_gas(&self) -> U256 {
+		self.backend.block_excess_blob_gas()
+	}
+
+	fn chain_id(&self) -> U256 {
+		self.backend.chain_id()
+	}
+}
+
+impl<B: RuntimeBaseBackend> RuntimeBaseBackend for CachedBackend<B> {
+	fn balance(&self, address: H160) -> U256 {
+		self.account_field(
+			address,
+			|a| &mut a.balance,
+			|| self.backend.balance(address),
+		)
+	}
+
+	fn code_size(&self, address: H160) -> U256 {
+		U256::from(self.code(address).len())
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		self.account_field(
+			address,
+			|a| &mut a.code_hash,
+			|| self.backend.code_hash(address),
+		)
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		let code_hash = self.code_hash(address);
+
+		if let Some(code) = self.codes.borrow_mut().get_mut(&code_hash) {
+			self.hits.set(self.hits.get() + 1);
+			return code.clone();
+		}
+
+		self.misses.set(self.misses.get() + 1);
+		let code = self.backend.code(address);
+		self.codes.borrow_mut().insert(code_hash, code.clone());
+		code
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		let mut storages = self.storages.borrow_mut();
+		if let Some(value) = storages.get_mut(&(address, index)) {
+			self.hits.set(self.hits.get() + 1);
+			return *value;
+		}
+
+		self.misses.set(self.misses.get() + 1);
+		let value = self.backend.storage(address, index);
+		storages.insert((address, index), value);
+		value
+	}
+
+	fn transient_storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.transient_storage(address, index)
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		self.account_field(address, |a| &mut a.exists, || self.backend.exists(address))
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		self.account_field(address, |a| &mut a.nonce, || self.backend.nonce(address))
+	}
+}
@@ -0,0 +1,334 @@
+//! Backend forking a live chain over JSON-RPC.
+
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::{cell::RefCell, fmt};
+
+use primitive_types::{H160, H256, U256};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::backend::{RuntimeBaseBackend, RuntimeEnvironment};
+
+/// Error of the forking backend.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ForkError {
+	/// The transport failed to deliver the request.
+	Transport(String),
+	/// The endpoint returned a JSON-RPC error.
+	Rpc(String),
+	/// The endpoint returned a response that could not be decoded.
+	Decode(String),
+}
+
+impl std::error::Error for ForkError {}
+
+impl fmt::Display for ForkError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Transport(err) => write!(f, "transport error: {}", err),
+			Self::Rpc(err) => write!(f, "rpc error: {}", err),
+			Self::Decode(err) => write!(f, "invalid rpc response: {}", err),
+		}
+	}
+}
+
+/// A JSON-RPC transport, usually an HTTP client.
+pub trait RpcTransport {
+	/// Send a request and return the `result` field of the response. A
+	/// response with an `error` field should be returned as [ForkError::Rpc].
+	fn request(&self, method: &str, params: Value) -> Result<Value, ForkError>;
+}
+
+/// Block environment of the forking backend. It is fetched from the pinned
+/// block, and can be modified to simulate a different block.
+#[derive(Clone, Debug)]
+pub struct ForkEnvironment {
+	/// Block number.
+	pub block_number: U256,
+	/// Block coinbase.
+	pub block_coinbase: H160,
+	/// Block timestamp.
+	pub block_timestamp: U256,
+	/// Block difficulty.
+	pub block_difficulty: U256,
+	/// Block randomness (`prevRandao`), after the Merge.
+	pub block_randomness: Option<H256>,
+	/// Block gas limit.
+	pub block_gas_limit: U256,
+	/// Block base fee.
+	pub block_base_fee_per_gas: U256,
+	/// Block excess blob gas, after Cancun.
+	pub block_excess_blob_gas: U256,
+	/// Chain ID.
+	pub chain_id: U256,
+}
+
+#[derive(Clone, Debug)]
+struct ForkAccount {
+	balance: U256,
+	nonce: U256,
+	code: Vec<u8>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcBlock {
+	hash: H256,
+	number: U256,
+	miner: H160,
+	timestamp: U256,
+	difficulty: U256,
+	mix_hash: Option<H256>,
+	gas_limit: U256,
+	base_fee_per_gas: Option<U256>,
+	excess_blob_gas: Option<U256>,
+}
+
+/// A read-only backend that lazily fetches accounts and storage from a
+/// JSON-RPC endpoint at a pinned block, and caches them locally.
+///
+/// Wrap it in an [crate::backend::OverlayedBackend] to execute transactions
+/// on top of the forked state.
+///
+/// The backend traits are infallible, so a failed request makes the lookup
+/// return the default value and records the error. Check [ForkBackend::take_error]
+/// after each execution, and discard its result if there was an error.
+pub struct ForkBackend<T> {
+	transport: T,
+	block: U256,
+	environment: ForkEnvironment,
+	accounts: RefCell<BTreeMap<H160, ForkAccount>>,
+	storages: RefCell<BTreeMap<(H160, H256), H256>>,
+	block_hashes: RefCell<BTreeMap<U256, H256>>,
+	error: RefCell<Option<ForkError>>,
+}
+
+fn quantity(value: U256) -> String {
+	format!("{:#x}", value)
+}
+
+fn decode<R: DeserializeOwned>(value: Value) -> Result<R, ForkError> {
+	serde_json::from_value(value).map_err(|e| ForkError::Decode(e.to_string()))
+}
+
+impl<T: RpcTransport> ForkBackend<T> {
+	/// Fork the chain at the given block number.
+	pub fn new(transport: T, block: U256) -> Result<Self, ForkError> {
+		let header: RpcBlock =
+			decode(transport.request("eth_getBlockByNumber", json!([quantity(block), false]))?)?;
+		let chain_id: U256 = decode(transport.request("eth_chainId", json!([]))?)?;
+
+		let environment = ForkEnvironment {
+			block_number: header.number,
+			block_coinbase: header.miner,
+			block_timestamp: header.timestamp,
+			block_difficulty: header.difficulty,
+			block_randomness: if header.difficulty.is_zero() {
+				header.mix_hash
+			} else {
+				None
+			},
+			block_gas_limit: header.gas_limit,
+			block_base_fee_per_gas: header.base_fee_per_gas.unwrap_or_default(),
+			block_excess_blob_gas: header.excess_blob_gas.unwrap_or_default(),
+			chain_id,
+		};
+
+		let mut block_hashes = BTreeMap::new();
+		block_hashes.insert(header.number, header.hash);
+
+		Ok(Self {
+			transport,
+			block,
+			environment,
+			accounts: RefCell::new(BTreeMap::new()),
+			storages: RefCell::new(BTreeMap::new()),
+			block_hashes: RefCell::new(block_hashes),
+			error: RefCell::new(None),
+		})
+	}
+
+	/// The pinned block number.
+	pub fn block(&self) -> U256 {
+		self.block
+	}
+
+	/// Block environment.
+	pub fn environment(&self) -> &ForkEnvironment {
+		&self.environment
+	}
+
+	/// Mutable block environment.
+	pub fn environment_mut(&mut self) -> &mut ForkEnvironment {
+		&mut self.environment
+	}
+
+	/// Take the first error that happened since the last call.
+	pub fn take_error(&self) -> Option<ForkError> {
+		self.error.borrow_mut().take()
+	}
+
+	/// Drop all cached state.
+	pub fn clear_cache(&self) {
+		self.accounts.borrow_mut().clear();
+		self.storages.borrow_mut().clear();
+	}
+
+	fn record(&self, err: ForkError) {
+		let mut error = self.error.borrow_mut();
+		if error.is_none() {
+			*error = Some(err);
+		}
+	}
+
+	fn fetch<R: DeserializeOwned>(&self, method: &str, params: Value) -> Result<R, ForkError> {
+		decode(self.transport.request(method, params)?)
+	}
+
+	fn account(&self, address: H160) -> Option<ForkAccount> {
+		if let Some(account) = self.accounts.borrow().get(&address) {
+			return Some(account.clone());
+		}
+
+		let block = quantity(self.block);
+		let fetched = (|| -> Result<ForkAccount, ForkError> {
+			let balance = self.fetch("eth_getBalance", json!([address, block]))?;
+			let nonce = self.fetch("eth_getTransactionCount", json!([address, block]))?;
+			let code: String = self.fetch("eth_getCode", json!([address, block]))?;
+			let code = hex::decode(code.trim_start_matches("0x"))
+				.map_err(|e| ForkError::Decode(e.to_string()))?;
+
+			Ok(ForkAccount {
+				balance,
+				nonce,
+				code,
+			})
+		})();
+
+		match fetched {
+			Ok(account) => {
+				self.accounts.borrow_mut().insert(address, account.clone());
+				Some(account)
+			}
+			Err(err) => {
+				self.record(err);
+				None
+			}
+		}
+	}
+}
+
+impl<T: RpcTransport> RuntimeEnvironment for ForkBackend<T> {
+	fn block_hash(&self, number: U256) -> H256 {
+		let current = self.environment.block_number;
+		if number >= current || current - number > U256::from(256) {
+			return H256::default();
+		}
+
+		if let Some(hash) = self.block_hashes.borrow().get(&number) {
+			return *hash;
+		}
+
+		let result =
+			self.fetch::<RpcBlock>("eth_getBlockByNumber", json!([quantity(number), false]));
+		match result {
+			Ok(block) => {
+				self.block_hashes.borrow_mut().insert(number, block.hash);
+				block.hash
+			}
+			Err(err) => {
+				self.record(err);
+				H256::default()
+			}
+		}
+	}
+
+	fn block_number(&self) -> U256 {
+		self.environment.block_number
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		self.environment.block_coinbase
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		self.environment.block_timestamp
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		self.environment.block_difficulty
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		self.environment.block_randomness
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		self.environment.block_gas_limit
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		self.environment.block_base_fee_per_gas
+	}
+
+	fn block_excess_blob_gas(&self) -> U256 {
+		self.environment.block_excess_blob_gas
+	}
+
+	fn chain_id(&self) -> U256 {
+		self.environment.chain_id
+	}
+}
+
+impl<T: RpcTransport> RuntimeBaseBackend for ForkBackend<T> {
+	fn balance(&self, address: H160) -> U256 {
+		self.account(address)
+			.map(|account| account.balance)
+			.unwrap_or_default()
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		self.account(address)
+			.map(|account| account.code)
+			.unwrap_or_default()
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		if let Some(value) = self.storages.borrow().get(&(address, index)) {
+			return *value;
+		}
+
+		let result = self.fetch(
+			"eth_getStorageAt",
+			json!([address, index, quantity(self.block)]),
+		);
+		match result {
+			Ok(value) => {
+				self.storages.borrow_mut().insert((address, index), value);
+				value
+			}
+			Err(err) => {
+				self.record(err);
+				H256::default()
+			}
+		}
+	}
+
+	fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+		H256::default()
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		self.account(address)
+			.map(|account| {
+				!(account.balance.is_zero() && account.nonce.is_zero() && account.code.is_empty())
+			})
+			.unwrap_or(false)
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		self.account(address)
+			.map(|account| account.nonce)
+			.unwrap_or_default()
+	}
+}
@@ -0,0 +1,288 @@
+use alloc::vec::Vec;
+
+use evm_interpreter::{
+	error::ExitError,
+	runtime::{
+		Log, RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment, SetCodeOrigin, Transfer,
+	},
+};
+use primitive_types::{H160, H256, U256};
+
+use crate::{backend::TransactionalBackend, MergeStrategy};
+
+/// Operation on native balances delegated to a [HostBalance].
+#[derive(Clone, Debug)]
+pub enum BalanceOp {
+	/// Value moved between accounts, by calls, creates and selfdestruct
+	/// sweeps.
+	Transfer(Transfer),
+	/// Value credited, by fee refunds, coinbase rewards and deposits.
+	Deposit { target: H160, value: U256 },
+	/// Value debited, by fee payments and the balance reset of deleted
+	/// accounts.
+	Withdrawal { source: H160, value: U256 },
+}
+
+/// Native balances managed by the host, for example by a Substrate balances
+/// pallet, instead of by the backend.
+pub trait HostBalance {
+	/// Balance of an account.
+	fn balance(&self, address: H160) -> U256;
+
+	/// Apply an operation.
+	///
+	/// On error, balances must be left unchanged. The error fails the call or
+	/// create making the transfer, and makes the transaction invalid if it
+	/// comes from a fee payment. A lack of funds should be reported as
+	/// [crate::interpreter::error::ExitException::OutOfFund]. Deposits must
+	/// not fail, as there is no way to report it.
+	fn apply(&mut self, op: &BalanceOp) -> Result<(), ExitError>;
+
+	/// Undo an applied operation, whose substate is reverted or discarded.
+	/// Operations are undone in the reverse order they were applied, so this
+	/// must not fail.
+	fn undo(&mut self, op: &BalanceOp);
+}
+
+/// A wrapper over a backend, usually an [crate::backend::OverlayedBackend],
+/// delegating native balances and all value transfers to a [HostBalance].
+///
+/// Operations are recorded per substate, and undone when the substate is
+/// reverted or discarded. Operations outside of any substate, such as fee
+/// payments, are never undone.
+pub struct HostBalanceBackend<B, T> {
+	backend: B,
+	host: T,
+	journal: Vec<Vec<BalanceOp>>,
+}
+
+impl<B, T> HostBalanceBackend<B, T> {
+	/// Wrap a backend with host balances.
+	pub fn new(backend: B, host: T) -> Self {
+		Self {
+			backend,
+			host,
+			journal: Vec::new(),
+		}
+	}
+
+	/// The inner backend.
+	pub fn inner(&self) -> &B {
+		&self.backend
+	}
+
+	/// The host balances.
+	pub fn host(&self) -> &T {
+		&self.host
+	}
+
+	/// Unwrap the inner backend and the host balances.
+	pub fn into_parts(self) -> (B, T) {
+		(self.backend, self.host)
+	}
+}
+
+impl<B, T: HostBalance> HostBalanceBackend<B, T> {
+	fn apply(&mut self, op: BalanceOp) -> Result<(), ExitError> {
+		self.host.apply(&op)?;
+		if let Some(layer) = self.journal.last_mut() {
+			layer.push(op);
+		}
+		Ok(())
+	}
+}
+
+impl<B: RuntimeEnvironment, T> RuntimeEnvironment for HostBalanceBackend<B, T> {
+	fn block_hash(&self, number: U256) -> H256 {
+		self.backend.block_hash(number)
+	}
+
+	fn block_number(&self) -> U256 {
+		self.backend.block_number()
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		self.backend.block_coinbase()
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		self.backend.block_timestamp()
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		self.backend.block_difficulty()
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		self.backend.block_randomness()
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		self.backend.block_gas_limit()
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		self.backend.block_base_fee_per_gas()
+	}
+
+	fn block_excess_blob_gas(&self) -> U256 {
+		self.backend.block_excess_blob_gas()
+	}
+
+	fn chain_id(&self) -> U256 {
+		self.backend.chain_id()
+	}
+}
+
+impl<B: RuntimeBaseBackend, T: HostBalance> RuntimeBaseBackend for HostBalanceBackend<B, T> {
+	fn balance(&self, address: H160) -> U256 {
+		self.host.balance(address)
+	}
+
+	fn code_size(&self, address: H160) -> U256 {
+		self.backend.code_size(address)
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		self.backend.code(address)
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.storage(address, index)
+	}
+
+	fn transient_storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.transient_storage(address, index)
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		self.backend.exists(address) || !self.host.balance(address).is_zero()
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		self.backend.nonce(address)
+	}
+}
+
+impl<B: RuntimeBackend, T: HostBalance> RuntimeBackend for HostBalanceBackend<B, T> {
+	fn original_storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.original_storage(address, index)
+	}
+
+	fn deleted(&self, address: H160) -> bool {
+		self.backend.deleted(address)
+	}
+
+	fn created_in_transaction(&self, address: H160) -> bool {
+		self.backend.created_in_transaction(address)
+	}
+
+	fn is_cold(&self, address: H160, index: Option<H256>) -> bool {
+		self.backend.is_cold(address, index)
+	}
+
+	fn mark_hot(&mut self, address: H160, index: Option<H256>) {
+		self.backend.mark_hot(address, index)
+	}
+
+	fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError> {
+		self.backend.set_storage(address, index, value)
+	}
+
+	fn set_transient_storage(
+		&mut self,
+		address: H160,
+		index: H256,
+		value: H256,
+	) -> Result<(), ExitError> {
+		self.backend.set_transient_storage(address, index, value)
+	}
+
+	fn log(&mut self, log: Log) -> Result<(), ExitError> {
+		self.backend.log(log)
+	}
+
+	fn mark_create(&mut self, address: H160) {
+		self.backend.mark_create(address)
+	}
+
+	fn mark_delete(&mut self, address: H160) {
+		self.backend.mark_delete(address)
+	}
+
+	fn reset_storage(&mut self, address: H160) {
+		self.backend.reset_storage(address)
+	}
+
+	fn set_code(
+		&mut self,
+		address: H160,
+		code: Vec<u8>,
+		origin: SetCodeOrigin,
+	) -> Result<(), ExitError> {
+		self.backend.set_code(address, code, origin)
+	}
+
+	fn reset_balance(&mut self, address: H160) {
+		let value = self.host.balance(address);
+		if !value.is_zero() {
+			// Withdrawing the whole balance cannot lack funds.
+			let _ = self.apply(BalanceOp::Withdrawal {
+				source: address,
+				value,
+			});
+		}
+	}
+
+	fn deposit(&mut self, target: H160, value: U256) {
+		if !value.is_zero() {
+			let _ = self.apply(BalanceOp::Deposit { target, value });
+		}
+	}
+
+	fn withdrawal(&mut self, source: H160, value: U256) -> Result<(), ExitError> {
+		if value.is_zero() {
+			return Ok(());
+		}
+
+		self.apply(BalanceOp::Withdrawal { source, value })
+	}
+
+	fn transfer(&mut self, transfer: Transfer) -> Result<(), ExitError> {
+		if transfer.value.is_zero() {
+			return Ok(());
+		}
+
+		self.apply(BalanceOp::Transfer(transfer))
+	}
+
+	fn inc_nonce(&mut self, address: H160) -> Result<(), ExitError> {
+		self.backend.inc_nonce(address)
+	}
+}
+
+impl<B: TransactionalBackend, T: HostBalance> TransactionalBackend for HostBalanceBackend<B, T> {
+	fn push_substate(&mut self) {
+		self.journal.push(Vec::new());
+		self.backend.push_substate();
+	}
+
+	fn pop_substate(&mut self, strategy: MergeStrategy) {
+		let layer = self.journal.pop().expect("uneven substate pop");
+
+		match strategy {
+			MergeStrategy::Commit => {
+				if let Some(parent) = self.journal.last_mut() {
+					parent.extend(layer);
+				}
+			}
+			MergeStrategy::Revert | MergeStrategy::Discard => {
+				for op in layer.iter().rev() {
+					self.host.undo(op);
+				}
+			}
+		}
+
+		self.backend.pop_substate(strategy);
+	}
+}
@@ -0,0 +1,255 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use primitive_types::{H160, H256, U256};
+
+use crate::backend::{
+	ApplyBackend, OverlayedBackend, OverlayedChangeSet, RuntimeBaseBackend, RuntimeEnvironment,
+};
+
+/// Block environment of an [InMemoryBackend].
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryEnvironment {
+	/// Hashes of previous blocks, by number.
+	pub block_hashes: BTreeMap<U256, H256>,
+	/// Block number.
+	pub block_number: U256,
+	/// Block coinbase.
+	pub block_coinbase: H160,
+	/// Block timestamp.
+	pub block_timestamp: U256,
+	/// Block difficulty.
+	pub block_difficulty: U256,
+	/// Block randomness (`prevRandao`), after the Merge.
+	pub block_randomness: Option<H256>,
+	/// Block gas limit.
+	pub block_gas_limit: U256,
+	/// Block base fee.
+	pub block_base_fee_per_gas: U256,
+	/// Block excess blob gas, after Cancun.
+	pub block_excess_blob_gas: U256,
+	/// Chain ID.
+	pub chain_id: U256,
+}
+
+/// An account of an [InMemoryBackend].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InMemoryAccount {
+	/// Account balance.
+	pub balance: U256,
+	/// Account code.
+	pub code: Vec<u8>,
+	/// Account nonce.
+	pub nonce: U256,
+	/// Account storage. Zero values are not stored.
+	pub storage: BTreeMap<H256, H256>,
+}
+
+/// A backend keeping the whole state in memory.
+///
+/// This is a base backend. To execute transactions, create an overlay with
+/// [InMemoryBackend::overlay], which handles substates, and apply its
+/// changes back with [InMemoryBackend::apply_overlayed] once done.
+///
+/// ```
+/// use evm::{
+///     backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+///     standard::{Config, Etable, EtableResolver, Invoker, TransactArgs},
+/// };
+/// use primitive_types::{H160, U256};
+///
+/// let config = Config::shanghai();
+/// let caller = H160::repeat_byte(0x11);
+/// let mut backend = InMemoryBackend::from_genesis(
+///     InMemoryEnvironment::default(),
+///     [(caller, InMemoryAccount { balance: U256::from(1_000_000), ..Default::default() })].into(),
+/// );
+///
+/// let etable = (Etable::single(evm::standard::eval_gasometer), Etable::runtime());
+/// let resolver = EtableResolver::new(&config, &(), &etable);
+/// let invoker = Invoker::new(&config, &resolver);
+/// let args = TransactArgs::Call {
+///     caller,
+///     address: H160::repeat_byte(0x22),
+///     value: U256::from(1000),
+///     data: Vec::new(),
+///     nonce: None,
+///     fee_payer: None,
+///     gas_limit: U256::from(21000),
+///     gas_price: U256::zero(),
+///     max_priority_fee_per_gas: None,
+///     access_list: Vec::new(),
+///     blob_hashes: Vec::new(),
+///     max_fee_per_blob_gas: None,
+///     overrides: Default::default(),
+/// };
+///
+/// let mut overlay = backend.overlay();
+/// evm::transact(args, None, &mut overlay, &invoker).unwrap();
+/// let changeset = overlay.deconstruct().1;
+/// backend.apply_overlayed(&changeset);
+/// assert_eq!(backend.state[&H160::repeat_byte(0x22)].balance, U256::from(1000));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryBackend {
+	/// Block environment.
+	pub environment: InMemoryEnvironment,
+	/// Accounts, by address.
+	pub state: BTreeMap<H160, InMemoryAccount>,
+}
+
+impl InMemoryBackend {
+	/// Create an empty backend.
+	pub fn new(environment: InMemoryEnvironment) -> Self {
+		Self {
+			environment,
+			state: BTreeMap::new(),
+		}
+	}
+
+	/// Create a backend with the given genesis accounts.
+	pub fn from_genesis(
+		environment: InMemoryEnvironment,
+		alloc: BTreeMap<H160, InMemoryAccount>,
+	) -> Self {
+		let state = alloc
+			.into_iter()
+			.map(|(address, mut account)| {
+				account.storage.retain(|_, value| *value != H256::default());
+				(address, account)
+			})
+			.collect();
+
+		Self { environment, state }
+	}
+
+	/// Create an overlay to execute transactions on, with no address marked
+	/// as accessed.
+	pub fn overlay(&self) -> OverlayedBackend<&Self> {
+		OverlayedBackend::new(self, Default::default())
+	}
+
+	/// Apply the changes of an overlay.
+	pub fn apply_overlayed(&mut self, changeset: &OverlayedChangeSet) {
+		for (address, balance) in changeset.balances.clone() {
+			self.state.entry(address).or_default().balance = balance;
+		}
+
+		for (address, code) in changeset.codes.clone() {
+			self.state.entry(address).or_default().code = code;
+		}
+
+		for (address, nonce) in changeset.nonces.clone() {
+			self.state.entry(address).or_default().nonce = nonce;
+		}
+
+		for address in changeset.storage_resets.clone() {
+			self.state.entry(address).or_default().storage = BTreeMap::new();
+		}
+
+		for ((address, key), value) in changeset.storages.clone() {
+			let account = self.state.entry(address).or_default();
+
+			if value == H256::default() {
+				account.storage.remove(&key);
+			} else {
+				account.storage.insert(key, value);
+			}
+		}
+
+		for address in changeset.deletes.clone() {
+			self.state.remove(&address);
+		}
+	}
+}
+
+impl ApplyBackend for InMemoryBackend {
+	fn apply_overlayed(&mut self, changeset: &OverlayedChangeSet) {
+		InMemoryBackend::apply_overlayed(self, changeset)
+	}
+}
+
+impl RuntimeEnvironment for InMemoryBackend {
+	fn block_hash(&self, number: U256) -> H256 {
+		self.environment
+			.block_hashes
+			.get(&number)
+			.cloned()
+			.unwrap_or_default()
+	}
+
+	fn block_number(&self) -> U256 {
+		self.environment.block_number
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		self.environment.block_coinbase
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		self.environment.block_timestamp
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		self.environment.block_difficulty
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		self.environment.block_randomness
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		self.environment.block_gas_limit
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		self.environment.block_base_fee_per_gas
+	}
+
+	fn block_excess_blob_gas(&self) -> U256 {
+		self.environment.block_excess_blob_gas
+	}
+
+	fn chain_id(&self) -> U256 {
+		self.environment.chain_id
+	}
+}
+
+impl RuntimeBaseBackend for InMemoryBackend {
+	fn balance(&self, address: H160) -> U256 {
+		self.state
+			.get(&address)
+			.map(|account| account.balance)
+			.unwrap_or_default()
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		self.state
+			.get(&address)
+			.map(|account| account.code.clone())
+			.unwrap_or_default()
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		self.state.contains_key(&address)
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		self.state
+			.get(&address)
+			.and_then(|account| account.storage.get(&index).cloned())
+			.unwrap_or_default()
+	}
+
+	fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+		// Transient storage is discarded after every transaction, so it never
+		// reaches the base backend.
+		H256::default()
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		self.state
+			.get(&address)
+			.map(|account| account.nonce)
+			.unwrap_or_default()
+	}
+}
@@ -11,11 +11,35 @@
 //! pushing/poping layers are dealt by extern functions), layers are handled
 //! internally inside a backend.
 
+#[cfg(feature = "async")]
+mod asynchronous;
+mod cached;
+#[cfg(feature = "fork")]
+pub mod fork;
+mod host_balance;
+mod in_memory;
 mod overlayed;
+mod state_diff;
+mod state_override;
+#[cfg(feature = "trie")]
+mod trie;
+mod witness;
 
 pub use evm_interpreter::runtime::{RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment};
 
-pub use self::overlayed::{OverlayedBackend, OverlayedChangeSet};
+#[cfg(feature = "async")]
+pub use self::asynchronous::{transact_async, AsyncAccount, AsyncRuntimeBackend, AsyncSnapshot};
+#[cfg(feature = "trie")]
+pub use self::trie::{MerkleTrie, TrieAccount, TrieBackend, EMPTY_CODE_HASH, EMPTY_TRIE_ROOT};
+pub use self::{
+	cached::{CacheStats, CachedBackend},
+	host_balance::{BalanceOp, HostBalance, HostBalanceBackend},
+	in_memory::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+	overlayed::{OverlayedBackend, OverlayedChangeSet, SnapshotId},
+	state_diff::{AccountDiff, Delta, StateDiff},
+	state_override::{AccountOverride, OverrideBackend, StateOverride, StateOverrideError},
+	witness::{ProofBackend, RecordingBackend, Witness, WitnessAccount},
+};
 
 /// Backend with layers that can transactionally be committed or discarded.
 pub trait TransactionalBackend {
@@ -28,3 +52,15 @@ pub trait TransactionalBackend {
 	/// are free to panic if it does not.
 	fn pop_substate(&mut self, strategy: crate::MergeStrategy);
 }
+
+/// Base backend the changes of an [OverlayedBackend] can be applied to.
+pub trait ApplyBackend {
+	/// Apply the changes of an overlay.
+	fn apply_overlayed(&mut self, changeset: &OverlayedChangeSet);
+}
+
+impl<T: ApplyBackend + ?Sized> ApplyBackend for &mut T {
+	fn apply_overlayed(&mut self, changeset: &OverlayedChangeSet) {
+		(**self).apply_overlayed(changeset)
+	}
+}
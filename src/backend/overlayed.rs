@@ -8,12 +8,16 @@ use core::mem;
 use evm_interpreter::{
 	error::{ExitError, ExitException},
 	runtime::{Log, RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment, SetCodeOrigin},
+	utils::keccak256,
 };
 use primitive_types::{H160, H256, U256};
 
-use crate::{backend::TransactionalBackend, MergeStrategy};
+use crate::{
+	backend::{AccountDiff, ApplyBackend, Delta, StateDiff, TransactionalBackend},
+	MergeStrategy,
+};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct OverlayedChangeSet {
 	pub logs: Vec<Log>,
 	pub balances: BTreeMap<H160, U256>,
@@ -25,10 +29,20 @@ pub struct OverlayedChangeSet {
 	pub deletes: BTreeSet<H160>,
 }
 
+/// Identifier of a snapshot taken with [OverlayedBackend::snapshot].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct SnapshotId(usize);
+
+struct Snapshot {
+	depth: usize,
+	accessed: BTreeSet<(H160, Option<H256>)>,
+}
+
 pub struct OverlayedBackend<B> {
 	backend: B,
 	substate: Box<Substate>,
 	accessed: BTreeSet<(H160, Option<H256>)>,
+	snapshots: Vec<Snapshot>,
 }
 
 impl<B> OverlayedBackend<B> {
@@ -37,10 +51,65 @@ impl<B> OverlayedBackend<B> {
 			backend,
 			substate: Box::new(Substate::new()),
 			accessed,
+			snapshots: Vec::new(),
 		}
 	}
 
-	pub fn deconstruct(self) -> (B, OverlayedChangeSet) {
+	/// The base backend.
+	pub fn inner(&self) -> &B {
+		&self.backend
+	}
+
+	/// Addresses and storage slots accessed so far, including the initially
+	/// accessed ones. Accesses in reverted substates are kept.
+	pub fn accessed(&self) -> &BTreeSet<(H160, Option<H256>)> {
+		&self.accessed
+	}
+
+	/// Take a snapshot of the changes so far. Snapshots are independent of
+	/// the substates of the call stack, and can span several transactions.
+	/// Snapshots taken later are nested in this one.
+	pub fn snapshot(&mut self) -> SnapshotId {
+		let id = SnapshotId(self.snapshots.len());
+		self.snapshots.push(Snapshot {
+			depth: self.substate.depth(),
+			accessed: self.accessed.clone(),
+		});
+		self.push_substate();
+		id
+	}
+
+	/// Discard the changes made since the snapshot, including those of the
+	/// snapshots nested in it, and release it.
+	pub fn revert_to(&mut self, id: SnapshotId) {
+		let snapshot = self.release(id);
+		while self.substate.depth() > snapshot.depth {
+			self.pop_substate(MergeStrategy::Revert);
+		}
+		self.accessed = snapshot.accessed;
+	}
+
+	/// Keep the changes made since the snapshot, including those of the
+	/// snapshots nested in it, and release it.
+	pub fn commit(&mut self, id: SnapshotId) {
+		let snapshot = self.release(id);
+		while self.substate.depth() > snapshot.depth {
+			self.pop_substate(MergeStrategy::Commit);
+		}
+	}
+
+	fn release(&mut self, id: SnapshotId) -> Snapshot {
+		assert!(id.0 < self.snapshots.len(), "snapshot already released");
+		self.snapshots.truncate(id.0 + 1);
+		self.snapshots.pop().expect("checked above")
+	}
+
+	/// Deconstruct the overlay. Snapshots not yet released are committed.
+	pub fn deconstruct(mut self) -> (B, OverlayedChangeSet) {
+		while self.substate.parent.is_some() {
+			self.pop_substate(MergeStrategy::Commit);
+		}
+
 		(
 			self.backend,
 			OverlayedChangeSet {
@@ -57,6 +126,97 @@ impl<B> OverlayedBackend<B> {
 	}
 }
 
+/// Apply the changes of an overlay stacked on this one, so that the
+/// transactions of a block can be executed one after another over a base
+/// backend that is only read. Accounts deleted by the changes are left
+/// empty, as there is no way to remove them from the base backend.
+impl<B> ApplyBackend for OverlayedBackend<B> {
+	fn apply_overlayed(&mut self, changeset: &OverlayedChangeSet) {
+		let substate = &mut self.substate;
+		for address in changeset.storage_resets.iter().chain(&changeset.deletes) {
+			substate.storages.retain(|(key, _), _| key != address);
+			substate.storage_resets.insert(*address);
+		}
+		substate.balances.extend(&changeset.balances);
+		substate.codes.extend(changeset.codes.clone());
+		substate.nonces.extend(&changeset.nonces);
+		substate.storages.extend(
+			changeset
+				.storages
+				.iter()
+				.filter(|((address, _), _)| !changeset.deletes.contains(address)),
+		);
+		for address in &changeset.deletes {
+			substate.balances.insert(*address, U256::zero());
+			substate.codes.insert(*address, Vec::new());
+			substate.nonces.insert(*address, U256::zero());
+		}
+	}
+}
+
+impl<B: RuntimeBaseBackend> OverlayedBackend<B> {
+	/// Compute the changes made so far compared to the base backend.
+	pub fn state_diff(&self) -> StateDiff {
+		let mut addresses = BTreeSet::new();
+		let mut slots = BTreeSet::new();
+		self.substate.touched(&mut addresses, &mut slots);
+
+		let mut accounts = BTreeMap::new();
+		for address in addresses {
+			let existed = self.backend.exists(address);
+			let exists = !self.deleted(address) && self.exists(address);
+			let slots = slots
+				.range((address, H256::zero())..=(address, H256::repeat_byte(0xff)))
+				.map(|(_, index)| *index);
+
+			let diff = match (existed, exists) {
+				(true, true) => AccountDiff {
+					balance: Delta::new(self.backend.balance(address), self.balance(address)),
+					nonce: Delta::new(self.backend.nonce(address), self.nonce(address)),
+					code: Delta::new(self.backend.code(address), self.code(address)),
+					storage: slots
+						.map(|index| {
+							let delta = Delta::new(
+								self.backend.storage(address, index),
+								self.storage(address, index),
+							);
+							(index, delta)
+						})
+						.filter(|(_, delta)| !delta.is_unchanged())
+						.collect(),
+				},
+				(false, true) => AccountDiff {
+					balance: Delta::Added(self.balance(address)),
+					nonce: Delta::Added(self.nonce(address)),
+					code: Delta::Added(self.code(address)),
+					storage: slots
+						.map(|index| (index, self.storage(address, index)))
+						.filter(|(_, value)| *value != H256::default())
+						.map(|(index, value)| (index, Delta::Added(value)))
+						.collect(),
+				},
+				(true, false) => AccountDiff {
+					balance: Delta::Removed(self.backend.balance(address)),
+					nonce: Delta::Removed(self.backend.nonce(address)),
+					code: Delta::Removed(self.backend.code(address)),
+					storage: slots
+						.map(|index| (index, self.backend.storage(address, index)))
+						.filter(|(_, value)| *value != H256::default())
+						.map(|(index, value)| (index, Delta::Removed(value)))
+						.collect(),
+				},
+				(false, false) => continue,
+			};
+
+			if !diff.is_unchanged() {
+				accounts.insert(address, diff);
+			}
+		}
+
+		StateDiff { accounts }
+	}
+}
+
 impl<B: RuntimeEnvironment> RuntimeEnvironment for OverlayedBackend<B> {
 	fn block_hash(&self, number: U256) -> H256 {
 		self.backend.block_hash(number)
@@ -90,6 +250,10 @@ impl<B: RuntimeEnvironment> RuntimeEnvironment for OverlayedBackend<B> {
 		self.backend.block_base_fee_per_gas()
 	}
 
+	fn block_excess_blob_gas(&self) -> U256 {
+		self.backend.block_excess_blob_gas()
+	}
+
 	fn chain_id(&self) -> U256 {
 		self.backend.chain_id()
 	}
@@ -112,6 +276,14 @@ impl<B: RuntimeBaseBackend> RuntimeBaseBackend for OverlayedBackend<B> {
 		}
 	}
 
+	fn code_hash(&self, address: H160) -> H256 {
+		if let Some(code) = self.substate.known_code(address) {
+			keccak256(&code)
+		} else {
+			self.backend.code_hash(address)
+		}
+	}
+
 	fn storage(&self, address: H160, index: H256) -> H256 {
 		if let Some(value) = self.substate.known_storage(address, index) {
 			value
@@ -154,6 +326,10 @@ impl<B: RuntimeBaseBackend> RuntimeBackend for OverlayedBackend<B> {
 		self.substate.deleted(address)
 	}
 
+	fn created_in_transaction(&self, address: H160) -> bool {
+		self.substate.created(address)
+	}
+
 	fn is_cold(&self, address: H160, index: Option<H256>) -> bool {
 		!self.accessed.contains(&(address, index))
 	}
@@ -184,6 +360,10 @@ impl<B: RuntimeBaseBackend> RuntimeBackend for OverlayedBackend<B> {
 		Ok(())
 	}
 
+	fn mark_create(&mut self, address: H160) {
+		self.substate.creates.insert(address);
+	}
+
 	fn mark_delete(&mut self, address: H160) {
 		self.substate.deletes.insert(address);
 	}
@@ -238,7 +418,7 @@ impl<B: RuntimeBaseBackend> RuntimeBackend for OverlayedBackend<B> {
 	}
 }
 
-impl<B: RuntimeBaseBackend> TransactionalBackend for OverlayedBackend<B> {
+impl<B> TransactionalBackend for OverlayedBackend<B> {
 	fn push_substate(&mut self) {
 		let mut parent = Box::new(Substate::new());
 		mem::swap(&mut parent, &mut self.substate);
@@ -275,6 +455,9 @@ impl<B: RuntimeBaseBackend> TransactionalBackend for OverlayedBackend<B> {
 						.transient_storage
 						.insert((address, key), value);
 				}
+				for address in child.creates {
+					self.substate.creates.insert(address);
+				}
 				for address in child.deletes {
 					self.substate.deletes.insert(address);
 				}
@@ -293,6 +476,7 @@ struct Substate {
 	storage_resets: BTreeSet<H160>,
 	storages: BTreeMap<(H160, H256), H256>,
 	transient_storage: BTreeMap<(H160, H256), H256>,
+	creates: BTreeSet<H160>,
 	deletes: BTreeSet<H160>,
 }
 
@@ -307,6 +491,7 @@ impl Substate {
 			storage_resets: Default::default(),
 			storages: Default::default(),
 			transient_storage: Default::default(),
+			creates: Default::default(),
 			deletes: Default::default(),
 		}
 	}
@@ -376,6 +561,37 @@ impl Substate {
 		}
 	}
 
+	pub fn touched(&self, addresses: &mut BTreeSet<H160>, slots: &mut BTreeSet<(H160, H256)>) {
+		addresses.extend(self.balances.keys());
+		addresses.extend(self.codes.keys());
+		addresses.extend(self.nonces.keys());
+		addresses.extend(&self.storage_resets);
+		addresses.extend(self.storages.keys().map(|(address, _)| *address));
+		addresses.extend(&self.deletes);
+		slots.extend(self.storages.keys());
+
+		if let Some(parent) = self.parent.as_ref() {
+			parent.touched(addresses, slots);
+		}
+	}
+
+	pub fn depth(&self) -> usize {
+		match self.parent.as_ref() {
+			Some(parent) => parent.depth() + 1,
+			None => 0,
+		}
+	}
+
+	pub fn created(&self, address: H160) -> bool {
+		if self.creates.contains(&address) {
+			true
+		} else if let Some(parent) = self.parent.as_ref() {
+			parent.created(address)
+		} else {
+			false
+		}
+	}
+
 	pub fn deleted(&self, address: H160) -> bool {
 		if self.deletes.contains(&address) {
 			true
@@ -0,0 +1,80 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use primitive_types::{H160, H256, U256};
+
+/// Change of a value. Serialized as in the `stateDiff` output of
+/// `trace_replayTransaction`: `"="`, `{"+": to}`, `{"-": from}` or
+/// `{"*": {"from": from, "to": to}}`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Delta<T> {
+	/// The value did not change.
+	#[cfg_attr(feature = "serde", serde(rename = "="))]
+	Unchanged,
+	/// The account was created with this value.
+	#[cfg_attr(feature = "serde", serde(rename = "+"))]
+	Added(T),
+	/// The account was removed, and had this value.
+	#[cfg_attr(feature = "serde", serde(rename = "-"))]
+	Removed(T),
+	/// The value changed.
+	#[cfg_attr(feature = "serde", serde(rename = "*"))]
+	Changed {
+		/// Value before.
+		from: T,
+		/// Value after.
+		to: T,
+	},
+}
+
+impl<T: PartialEq> Delta<T> {
+	pub(crate) fn new(from: T, to: T) -> Self {
+		if from == to {
+			Self::Unchanged
+		} else {
+			Self::Changed { from, to }
+		}
+	}
+
+	/// Whether the value did not change.
+	pub fn is_unchanged(&self) -> bool {
+		matches!(self, Self::Unchanged)
+	}
+}
+
+/// Changes of an account.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountDiff {
+	/// Balance change.
+	pub balance: Delta<U256>,
+	/// Nonce change.
+	pub nonce: Delta<U256>,
+	/// Code change.
+	pub code: Delta<Vec<u8>>,
+	/// Changed storage slots. Unchanged slots are omitted.
+	pub storage: BTreeMap<H256, Delta<H256>>,
+}
+
+impl AccountDiff {
+	/// Whether nothing in the account changed.
+	pub fn is_unchanged(&self) -> bool {
+		self.balance.is_unchanged()
+			&& self.nonce.is_unchanged()
+			&& self.code.is_unchanged()
+			&& self.storage.is_empty()
+	}
+}
+
+/// Changes of the state made in an [crate::backend::OverlayedBackend],
+/// compared to its base backend. Computed by
+/// [crate::backend::OverlayedBackend::state_diff].
+///
+/// Base backends cannot enumerate storage, so when the storage of an account
+/// is reset, only the slots written afterwards are reported.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateDiff {
+	/// Changed accounts. Unchanged accounts are omitted.
+	pub accounts: BTreeMap<H160, AccountDiff>,
+}
@@ -0,0 +1,217 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::backend::{RuntimeBaseBackend, RuntimeEnvironment};
+
+/// Overrides of an account, as in the `stateOverride` parameter of geth's
+/// `eth_call`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(rename_all = "camelCase", default)
+)]
+pub struct AccountOverride {
+	/// Balance to set.
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+	pub balance: Option<U256>,
+	/// Nonce to set.
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+	pub nonce: Option<U256>,
+	/// Code to set.
+	#[cfg_attr(
+		feature = "serde",
+		serde(with = "hex_bytes", skip_serializing_if = "Option::is_none")
+	)]
+	pub code: Option<Vec<u8>>,
+	/// Storage replacing the whole storage of the account. Slots not listed
+	/// read as zero.
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+	pub state: Option<BTreeMap<H256, H256>>,
+	/// Storage slots to set, keeping the rest of the storage.
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+	pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+/// Overrides of accounts, by address.
+pub type StateOverride = BTreeMap<H160, AccountOverride>;
+
+/// Error of an invalid [StateOverride].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StateOverrideError {
+	/// Both `state` and `stateDiff` are set for the account.
+	StateAndStateDiff(H160),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StateOverrideError {}
+
+impl fmt::Display for StateOverrideError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::StateAndStateDiff(address) => write!(
+				f,
+				"account {:?} has both state and stateDiff overrides",
+				address
+			),
+		}
+	}
+}
+
+/// A wrapper over a base backend applying a [StateOverride] on top of it,
+/// for simulations such as `eth_call`.
+///
+/// Like any base backend, it is meant to be wrapped in an
+/// [crate::backend::OverlayedBackend] for execution.
+pub struct OverrideBackend<B> {
+	backend: B,
+	overrides: StateOverride,
+}
+
+impl<B> OverrideBackend<B> {
+	/// Wrap a backend with overrides.
+	pub fn new(backend: B, overrides: StateOverride) -> Result<Self, StateOverrideError> {
+		for (address, account) in &overrides {
+			if account.state.is_some() && account.state_diff.is_some() {
+				return Err(StateOverrideError::StateAndStateDiff(*address));
+			}
+		}
+
+		Ok(Self { backend, overrides })
+	}
+
+	/// The inner backend.
+	pub fn inner(&self) -> &B {
+		&self.backend
+	}
+
+	/// The overrides.
+	pub fn overrides(&self) -> &StateOverride {
+		&self.overrides
+	}
+
+	/// Unwrap the inner backend.
+	pub fn into_inner(self) -> B {
+		self.backend
+	}
+}
+
+impl<B: RuntimeEnvironment> RuntimeEnvironment for OverrideBackend<B> {
+	fn block_hash(&self, number: U256) -> H256 {
+		self.backend.block_hash(number)
+	}
+
+	fn block_number(&self) -> U256 {
+		self.backend.block_number()
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		self.backend.block_coinbase()
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		self.backend.block_timestamp()
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		self.backend.block_difficulty()
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		self.backend.block_randomness()
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		self.backend.block_gas_limit()
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		self.backend.block_base_fee_per_gas()
+	}
+
+	fn block_excess_blob_gas(&self) -> U256 {
+		self.backend.block_excess_blob_gas()
+	}
+
+	fn chain_id(&self) -> U256 {
+		self.backend.chain_id()
+	}
+}
+
+impl<B: RuntimeBaseBackend> RuntimeBaseBackend for OverrideBackend<B> {
+	fn balance(&self, address: H160) -> U256 {
+		match self.overrides.get(&address).and_then(|o| o.balance) {
+			Some(balance) => balance,
+			None => self.backend.balance(address),
+		}
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		match self.overrides.get(&address).and_then(|o| o.code.as_ref()) {
+			Some(code) => code.clone(),
+			None => self.backend.code(address),
+		}
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		if let Some(account) = self.overrides.get(&address) {
+			if let Some(state) = &account.state {
+				return state.get(&index).cloned().unwrap_or_default();
+			}
+
+			if let Some(value) = account
+				.state_diff
+				.as_ref()
+				.and_then(|state_diff| state_diff.get(&index))
+			{
+				return *value;
+			}
+		}
+
+		self.backend.storage(address, index)
+	}
+
+	fn transient_storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.transient_storage(address, index)
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		self.overrides.contains_key(&address) || self.backend.exists(address)
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		match self.overrides.get(&address).and_then(|o| o.nonce) {
+			Some(nonce) => nonce,
+			None => self.backend.nonce(address),
+		}
+	}
+}
+
+#[cfg(feature = "serde")]
+mod hex_bytes {
+	use alloc::{format, string::String, vec::Vec};
+
+	use serde::{Deserialize, Deserializer, Serializer};
+
+	pub fn serialize<S: Serializer>(
+		value: &Option<Vec<u8>>,
+		serializer: S,
+	) -> Result<S::Ok, S::Error> {
+		match value {
+			Some(value) => serializer.serialize_str(&format!("0x{}", hex::encode(value))),
+			None => serializer.serialize_none(),
+		}
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(
+		deserializer: D,
+	) -> Result<Option<Vec<u8>>, D::Error> {
+		Option::<String>::deserialize(deserializer)?
+			.map(|value| {
+				hex::decode(value.trim_start_matches("0x")).map_err(serde::de::Error::custom)
+			})
+			.transpose()
+	}
+}
@@ -0,0 +1,343 @@
+mod mpt;
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use evm_interpreter::utils::keccak256;
+use primitive_types::{H160, H256, U256};
+use rlp::{Rlp, RlpStream};
+
+pub use self::mpt::MerkleTrie;
+use crate::backend::{
+	ApplyBackend, InMemoryAccount, InMemoryEnvironment, OverlayedBackend, OverlayedChangeSet,
+	ProofBackend, RuntimeBaseBackend, RuntimeEnvironment,
+};
+
+/// Hash of empty code.
+pub const EMPTY_CODE_HASH: H256 = H256([
+	0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+	0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+]);
+
+/// Root of an empty trie.
+pub const EMPTY_TRIE_ROOT: H256 = H256([
+	0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+	0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// An account as stored in the state trie.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TrieAccount {
+	/// Account nonce.
+	pub nonce: U256,
+	/// Account balance.
+	pub balance: U256,
+	/// Root of the storage trie.
+	pub storage_root: H256,
+	/// Hash of the account code.
+	pub code_hash: H256,
+}
+
+impl Default for TrieAccount {
+	fn default() -> Self {
+		Self {
+			nonce: U256::zero(),
+			balance: U256::zero(),
+			storage_root: EMPTY_TRIE_ROOT,
+			code_hash: EMPTY_CODE_HASH,
+		}
+	}
+}
+
+impl rlp::Encodable for TrieAccount {
+	fn rlp_append(&self, stream: &mut RlpStream) {
+		stream.begin_list(4);
+		stream.append(&self.nonce);
+		stream.append(&self.balance);
+		stream.append(&self.storage_root);
+		stream.append(&self.code_hash);
+	}
+}
+
+impl rlp::Decodable for TrieAccount {
+	fn decode(rlp: &Rlp) -> Result<Self, rlp::DecoderError> {
+		Ok(Self {
+			nonce: rlp.val_at(0)?,
+			balance: rlp.val_at(1)?,
+			storage_root: rlp.val_at(2)?,
+			code_hash: rlp.val_at(3)?,
+		})
+	}
+}
+
+/// A backend keeping the state in Merkle Patricia tries, so that the state
+/// root can be computed after applying changes.
+///
+/// Accounts are keyed by the hash of their address, and storage slots by the
+/// hash of their index, as in Ethereum's secure tries. Like
+/// [crate::backend::InMemoryBackend], this is a base backend: execute on an
+/// overlay created with [TrieBackend::overlay], and apply its changes with
+/// [TrieBackend::apply_overlayed], which returns the post-state root.
+pub struct TrieBackend {
+	/// Block environment.
+	pub environment: InMemoryEnvironment,
+	accounts: MerkleTrie,
+	storages: BTreeMap<H160, MerkleTrie>,
+	codes: BTreeMap<H256, Vec<u8>>,
+}
+
+impl TrieBackend {
+	/// Create an empty backend.
+	pub fn new(environment: InMemoryEnvironment) -> Self {
+		Self {
+			environment,
+			accounts: MerkleTrie::new(),
+			storages: BTreeMap::new(),
+			codes: BTreeMap::new(),
+		}
+	}
+
+	/// Create a backend with the given genesis accounts.
+	pub fn from_genesis(
+		environment: InMemoryEnvironment,
+		alloc: BTreeMap<H160, InMemoryAccount>,
+	) -> Self {
+		let mut backend = Self::new(environment);
+
+		for (address, account) in alloc {
+			let mut storage = MerkleTrie::new();
+			for (index, value) in account.storage {
+				storage.insert(keccak256(&index[..]).as_bytes(), encode_storage(value));
+			}
+
+			let code_hash = backend.insert_code(account.code);
+			let trie_account = TrieAccount {
+				nonce: account.nonce,
+				balance: account.balance,
+				storage_root: storage.root(),
+				code_hash,
+			};
+
+			backend.storages.insert(address, storage);
+			backend.set_account(address, &trie_account);
+		}
+
+		backend
+	}
+
+	/// Create an overlay to execute transactions on, with no address marked
+	/// as accessed.
+	pub fn overlay(&self) -> OverlayedBackend<&Self> {
+		OverlayedBackend::new(self, Default::default())
+	}
+
+	/// Apply the changes of an overlay, and return the post-state root.
+	pub fn apply_overlayed(&mut self, changeset: &OverlayedChangeSet) -> H256 {
+		let mut touched = BTreeMap::<H160, TrieAccount>::new();
+
+		for (address, balance) in &changeset.balances {
+			self.touch(&mut touched, *address).balance = *balance;
+		}
+
+		for (address, code) in &changeset.codes {
+			let code_hash = self.insert_code(code.clone());
+			self.touch(&mut touched, *address).code_hash = code_hash;
+		}
+
+		for (address, nonce) in &changeset.nonces {
+			self.touch(&mut touched, *address).nonce = *nonce;
+		}
+
+		for address in &changeset.storage_resets {
+			self.touch(&mut touched, *address);
+			self.storages.remove(address);
+		}
+
+		for ((address, index), value) in &changeset.storages {
+			self.touch(&mut touched, *address);
+			self.storages
+				.entry(*address)
+				.or_default()
+				.insert(keccak256(&index[..]).as_bytes(), encode_storage(*value));
+		}
+
+		for address in &changeset.deletes {
+			touched.remove(address);
+			self.storages.remove(address);
+			self.accounts.remove(keccak256(&address[..]).as_bytes());
+		}
+
+		for (address, mut account) in touched {
+			account.storage_root = self
+				.storages
+				.get_mut(&address)
+				.map(|storage| storage.root())
+				.unwrap_or(EMPTY_TRIE_ROOT);
+			self.set_account(address, &account);
+		}
+
+		self.state_root()
+	}
+
+	/// Root of the state trie.
+	pub fn state_root(&mut self) -> H256 {
+		self.accounts.root()
+	}
+
+	/// Get an account, or `None` if it does not exist.
+	pub fn account(&self, address: H160) -> Option<TrieAccount> {
+		self.accounts
+			.get(keccak256(&address[..]).as_bytes())
+			.map(|value| rlp::decode(value).expect("account rlp is encoded by the backend"))
+	}
+
+	fn touch<'a>(
+		&self,
+		touched: &'a mut BTreeMap<H160, TrieAccount>,
+		address: H160,
+	) -> &'a mut TrieAccount {
+		touched
+			.entry(address)
+			.or_insert_with(|| self.account(address).unwrap_or_default())
+	}
+
+	fn set_account(&mut self, address: H160, account: &TrieAccount) {
+		self.accounts.insert(
+			keccak256(&address[..]).as_bytes(),
+			rlp::encode(account).to_vec(),
+		);
+	}
+
+	fn insert_code(&mut self, code: Vec<u8>) -> H256 {
+		let code_hash = keccak256(&code);
+		self.codes.insert(code_hash, code);
+		code_hash
+	}
+}
+
+/// Storage values are RLP encoded as integers. Zero encodes to an empty value,
+/// which removes the slot.
+fn encode_storage(value: H256) -> Vec<u8> {
+	if value == H256::default() {
+		Vec::new()
+	} else {
+		rlp::encode(&U256::from_big_endian(&value[..])).to_vec()
+	}
+}
+
+impl ApplyBackend for TrieBackend {
+	fn apply_overlayed(&mut self, changeset: &OverlayedChangeSet) {
+		TrieBackend::apply_overlayed(self, changeset);
+	}
+}
+
+impl ProofBackend for TrieBackend {
+	fn state_root(&mut self) -> H256 {
+		self.accounts.root()
+	}
+
+	fn prove_account(&mut self, address: H160) -> Vec<Vec<u8>> {
+		self.accounts.prove(keccak256(&address[..]).as_bytes())
+	}
+
+	fn prove_storage(&mut self, address: H160, index: H256) -> Vec<Vec<u8>> {
+		let key = keccak256(&index[..]);
+		match self.storages.get_mut(&address) {
+			Some(storage) => storage.prove(key.as_bytes()),
+			None => MerkleTrie::new().prove(key.as_bytes()),
+		}
+	}
+}
+
+impl RuntimeEnvironment for TrieBackend {
+	fn block_hash(&self, number: U256) -> H256 {
+		self.environment
+			.block_hashes
+			.get(&number)
+			.cloned()
+			.unwrap_or_default()
+	}
+
+	fn block_number(&self) -> U256 {
+		self.environment.block_number
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		self.environment.block_coinbase
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		self.environment.block_timestamp
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		self.environment.block_difficulty
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		self.environment.block_randomness
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		self.environment.block_gas_limit
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		self.environment.block_base_fee_per_gas
+	}
+
+	fn block_excess_blob_gas(&self) -> U256 {
+		self.environment.block_excess_blob_gas
+	}
+
+	fn chain_id(&self) -> U256 {
+		self.environment.chain_id
+	}
+}
+
+impl RuntimeBaseBackend for TrieBackend {
+	fn balance(&self, address: H160) -> U256 {
+		self.account(address)
+			.map(|account| account.balance)
+			.unwrap_or_default()
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		self.account(address)
+			.map(|account| account.code_hash)
+			.unwrap_or(EMPTY_CODE_HASH)
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		self.account(address)
+			.and_then(|account| self.codes.get(&account.code_hash).cloned())
+			.unwrap_or_default()
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		self.account(address).is_some()
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		self.storages
+			.get(&address)
+			.and_then(|storage| storage.get(keccak256(&index[..]).as_bytes()))
+			.map(|value| {
+				let value: U256 =
+					rlp::decode(value).expect("storage rlp is encoded by the backend");
+				let mut ret = H256::default();
+				value.to_big_endian(&mut ret[..]);
+				ret
+			})
+			.unwrap_or_default()
+	}
+
+	fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+		H256::default()
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		self.account(address)
+			.map(|account| account.nonce)
+			.unwrap_or_default()
+	}
+}
@@ -0,0 +1,334 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::mem;
+
+use evm_interpreter::utils::keccak256;
+use primitive_types::H256;
+use rlp::RlpStream;
+
+fn nibbles(key: &[u8]) -> Vec<u8> {
+	key.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Hex-prefix encoding of a nibble path.
+fn hex_prefix(path: &[u8], is_leaf: bool) -> Vec<u8> {
+	let flag = if is_leaf { 2 } else { 0 };
+	let mut ret = Vec::with_capacity(path.len() / 2 + 1);
+	let rest = if path.len() % 2 == 1 {
+		ret.push(((flag + 1) << 4) | path[0]);
+		&path[1..]
+	} else {
+		ret.push(flag << 4);
+		path
+	};
+	for pair in rest.chunks(2) {
+		ret.push((pair[0] << 4) | pair[1]);
+	}
+	ret
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+	a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+fn concat(a: &[u8], b: &[u8]) -> Vec<u8> {
+	let mut ret = Vec::with_capacity(a.len() + b.len());
+	ret.extend_from_slice(a);
+	ret.extend_from_slice(b);
+	ret
+}
+
+enum Kind {
+	Empty,
+	Leaf(Vec<u8>, Vec<u8>),
+	Extension(Vec<u8>, Box<Node>),
+	Branch(Box<[Node; 16]>, Option<Vec<u8>>),
+}
+
+struct Node {
+	kind: Kind,
+	/// Cached RLP encoding, cleared whenever the node changes.
+	encoded: Option<Vec<u8>>,
+}
+
+impl Node {
+	fn new(kind: Kind) -> Self {
+		Self {
+			kind,
+			encoded: None,
+		}
+	}
+
+	fn empty() -> Self {
+		Self::new(Kind::Empty)
+	}
+
+	fn is_empty(&self) -> bool {
+		matches!(self.kind, Kind::Empty)
+	}
+
+	fn branch() -> (Box<[Node; 16]>, Option<Vec<u8>>) {
+		(Box::new([(); 16].map(|_| Node::empty())), None)
+	}
+
+	fn encode(&mut self) -> &[u8] {
+		if self.encoded.is_none() {
+			let mut stream = RlpStream::new();
+			match &mut self.kind {
+				Kind::Empty => {
+					stream.append_empty_data();
+				}
+				Kind::Leaf(path, value) => {
+					stream.begin_list(2);
+					stream.append(&hex_prefix(path, true));
+					stream.append(value);
+				}
+				Kind::Extension(path, child) => {
+					stream.begin_list(2);
+					stream.append(&hex_prefix(path, false));
+					child.append_reference(&mut stream);
+				}
+				Kind::Branch(children, value) => {
+					stream.begin_list(17);
+					for child in children.iter_mut() {
+						child.append_reference(&mut stream);
+					}
+					match value {
+						Some(value) => stream.append(value),
+						None => stream.append_empty_data(),
+					};
+				}
+			}
+			self.encoded = Some(stream.out().to_vec());
+		}
+
+		self.encoded.as_deref().unwrap_or_default()
+	}
+
+	/// Append the node as a child of another one, inlined if its encoding is
+	/// shorter than a hash.
+	fn append_reference(&mut self, stream: &mut RlpStream) {
+		if self.is_empty() {
+			stream.append_empty_data();
+			return;
+		}
+
+		let encoded = self.encode();
+		if encoded.len() < 32 {
+			stream.append_raw(encoded, 1);
+		} else {
+			stream.append(&keccak256(encoded));
+		}
+	}
+
+	fn get(&self, path: &[u8]) -> Option<&[u8]> {
+		match &self.kind {
+			Kind::Empty => None,
+			Kind::Leaf(leaf_path, value) => (leaf_path[..] == path[..]).then(|| &value[..]),
+			Kind::Extension(ext_path, child) => path
+				.strip_prefix(&ext_path[..])
+				.and_then(|rest| child.get(rest)),
+			Kind::Branch(children, value) => match path.split_first() {
+				None => value.as_deref(),
+				Some((index, rest)) => children[*index as usize].get(rest),
+			},
+		}
+	}
+
+	fn insert(self, path: &[u8], value: Vec<u8>) -> Node {
+		match self.kind {
+			Kind::Empty => Node::new(Kind::Leaf(path.to_vec(), value)),
+			Kind::Leaf(leaf_path, leaf_value) => {
+				if leaf_path == path {
+					return Node::new(Kind::Leaf(leaf_path, value));
+				}
+
+				let common = common_prefix(&leaf_path, path);
+				let (mut children, mut branch_value) = Self::branch();
+				for (p, v) in [(&leaf_path[..], leaf_value), (path, value)] {
+					if p.len() == common {
+						branch_value = Some(v);
+					} else {
+						children[p[common] as usize] =
+							Node::new(Kind::Leaf(p[common + 1..].to_vec(), v));
+					}
+				}
+
+				Self::wrap_extension(&path[..common], Kind::Branch(children, branch_value))
+			}
+			Kind::Extension(ext_path, child) => {
+				let common = common_prefix(&ext_path, path);
+				if common == ext_path.len() {
+					let child = child.insert(&path[common..], value);
+					return Node::new(Kind::Extension(ext_path, Box::new(child)));
+				}
+
+				let (mut children, mut branch_value) = Self::branch();
+				children[ext_path[common] as usize] = if ext_path.len() == common + 1 {
+					*child
+				} else {
+					Node::new(Kind::Extension(ext_path[common + 1..].to_vec(), child))
+				};
+				if path.len() == common {
+					branch_value = Some(value);
+				} else {
+					children[path[common] as usize] =
+						Node::new(Kind::Leaf(path[common + 1..].to_vec(), value));
+				}
+
+				Self::wrap_extension(&path[..common], Kind::Branch(children, branch_value))
+			}
+			Kind::Branch(mut children, mut branch_value) => {
+				match path.split_first() {
+					None => branch_value = Some(value),
+					Some((index, rest)) => {
+						let child = mem::replace(&mut children[*index as usize], Node::empty());
+						children[*index as usize] = child.insert(rest, value);
+					}
+				}
+				Node::new(Kind::Branch(children, branch_value))
+			}
+		}
+	}
+
+	fn wrap_extension(path: &[u8], kind: Kind) -> Node {
+		if path.is_empty() {
+			Node::new(kind)
+		} else {
+			Node::new(Kind::Extension(path.to_vec(), Box::new(Node::new(kind))))
+		}
+	}
+
+	/// Remove the value at `path`. The node is returned untouched, with its
+	/// cached encoding, if there is no such value.
+	fn remove(self, path: &[u8]) -> Node {
+		if self.get(path).is_none() {
+			return self;
+		}
+
+		match self.kind {
+			Kind::Empty | Kind::Leaf(..) => Node::empty(),
+			Kind::Extension(ext_path, child) => {
+				let child = child.remove(&path[ext_path.len()..]);
+				Self::prepend(&ext_path, child)
+			}
+			Kind::Branch(mut children, mut branch_value) => {
+				match path.split_first() {
+					None => branch_value = None,
+					Some((index, rest)) => {
+						let child = mem::replace(&mut children[*index as usize], Node::empty());
+						children[*index as usize] = child.remove(rest);
+					}
+				}
+
+				let mut remaining = children
+					.iter()
+					.enumerate()
+					.filter(|(_, child)| !child.is_empty())
+					.map(|(index, _)| index);
+				match (remaining.next(), remaining.next(), branch_value) {
+					(None, _, None) => Node::empty(),
+					(None, _, Some(value)) => Node::new(Kind::Leaf(Vec::new(), value)),
+					(Some(index), None, None) => {
+						let child = mem::replace(&mut children[index], Node::empty());
+						Self::prepend(&[index as u8], child)
+					}
+					(_, _, branch_value) => Node::new(Kind::Branch(children, branch_value)),
+				}
+			}
+		}
+	}
+
+	/// Put `path` in front of a node, merging it into the node if possible.
+	fn prepend(path: &[u8], node: Node) -> Node {
+		match node.kind {
+			Kind::Empty => Node::empty(),
+			Kind::Leaf(rest, value) => Node::new(Kind::Leaf(concat(path, &rest), value)),
+			Kind::Extension(rest, child) => Node::new(Kind::Extension(concat(path, &rest), child)),
+			kind @ Kind::Branch(..) => Self::wrap_extension(path, kind),
+		}
+	}
+
+	fn prove(&mut self, path: &[u8], is_root: bool, proof: &mut Vec<Vec<u8>>) {
+		let encoded = self.encode();
+		if is_root || encoded.len() >= 32 {
+			proof.push(encoded.to_vec());
+		}
+
+		match &mut self.kind {
+			Kind::Empty | Kind::Leaf(..) => (),
+			Kind::Extension(ext_path, child) => {
+				if let Some(rest) = path.strip_prefix(&ext_path[..]) {
+					child.prove(rest, false, proof);
+				}
+			}
+			Kind::Branch(children, _) => {
+				if let Some((index, rest)) = path.split_first() {
+					children[*index as usize].prove(rest, false, proof);
+				}
+			}
+		}
+	}
+}
+
+/// An in-memory Merkle Patricia trie, as used by Ethereum for the state and
+/// storage tries.
+///
+/// Encodings of unchanged subtrees are cached, so that computing the root
+/// after a few changes only rehashes the changed paths.
+pub struct MerkleTrie {
+	root: Node,
+}
+
+impl Default for MerkleTrie {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl MerkleTrie {
+	/// Create an empty trie.
+	pub fn new() -> Self {
+		Self {
+			root: Node::empty(),
+		}
+	}
+
+	/// Whether the trie is empty.
+	pub fn is_empty(&self) -> bool {
+		self.root.is_empty()
+	}
+
+	/// Get the value at `key`.
+	pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+		self.root.get(&nibbles(key))
+	}
+
+	/// Set the value at `key`. An empty value removes the key.
+	pub fn insert(&mut self, key: &[u8], value: Vec<u8>) {
+		if value.is_empty() {
+			return self.remove(key);
+		}
+
+		let root = mem::replace(&mut self.root, Node::empty());
+		self.root = root.insert(&nibbles(key), value);
+	}
+
+	/// Remove the value at `key`.
+	pub fn remove(&mut self, key: &[u8]) {
+		let root = mem::replace(&mut self.root, Node::empty());
+		self.root = root.remove(&nibbles(key));
+	}
+
+	/// Root hash of the trie.
+	pub fn root(&mut self) -> H256 {
+		keccak256(self.root.encode())
+	}
+
+	/// Merkle proof of `key`: the RLP encoded nodes on its path, starting
+	/// from the root, in the format of `eth_getProof`.
+	pub fn prove(&mut self, key: &[u8]) -> Vec<Vec<u8>> {
+		let mut proof = Vec::new();
+		self.root.prove(&nibbles(key), true, &mut proof);
+		proof
+	}
+}
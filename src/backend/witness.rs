@@ -0,0 +1,218 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::cell::RefCell;
+
+use primitive_types::{H160, H256, U256};
+
+use crate::backend::{RuntimeBaseBackend, RuntimeEnvironment};
+
+/// A base backend that can prove its state against a state root.
+pub trait ProofBackend {
+	/// Root of the state.
+	fn state_root(&mut self) -> H256;
+	/// Merkle proof of an account, as RLP encoded trie nodes from the root.
+	fn prove_account(&mut self, address: H160) -> Vec<Vec<u8>>;
+	/// Merkle proof of a storage slot, as RLP encoded trie nodes from the
+	/// storage root of the account.
+	fn prove_storage(&mut self, address: H160, index: H256) -> Vec<Vec<u8>>;
+}
+
+/// An account read during execution, with the storage slots read from it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WitnessAccount {
+	/// Whether the account exists.
+	pub exists: bool,
+	/// Account balance.
+	pub balance: U256,
+	/// Account nonce.
+	pub nonce: U256,
+	/// Hash of the account code.
+	pub code_hash: H256,
+	/// Storage slots read.
+	pub storage: BTreeMap<H256, H256>,
+	/// Proof of the account, if exported with proofs.
+	pub proof: Vec<Vec<u8>>,
+	/// Proofs of the storage slots, if exported with proofs.
+	pub storage_proofs: BTreeMap<H256, Vec<Vec<u8>>>,
+}
+
+/// Everything an execution read from the state, enough to re-execute it
+/// without access to the full state.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Witness {
+	/// State root the proofs are against, if exported with proofs.
+	pub state_root: Option<H256>,
+	/// Accounts read, by address.
+	pub accounts: BTreeMap<H160, WitnessAccount>,
+	/// Code read, by code hash.
+	pub codes: BTreeMap<H256, Vec<u8>>,
+	/// Block hashes read, by number.
+	pub block_hashes: BTreeMap<U256, H256>,
+}
+
+/// A wrapper over a base backend recording every account, code and storage
+/// slot read through it, for example during [crate::transact].
+///
+/// Like any base backend, it is meant to be wrapped in an
+/// [crate::backend::OverlayedBackend] for execution. The reads of several
+/// executions accumulate until [RecordingBackend::clear] is called. Export
+/// them with [RecordingBackend::witness], or with
+/// [RecordingBackend::witness_with_proofs] if the inner backend can prove its
+/// state.
+pub struct RecordingBackend<B> {
+	backend: B,
+	witness: RefCell<Witness>,
+}
+
+impl<B> RecordingBackend<B> {
+	/// Wrap a backend.
+	pub fn new(backend: B) -> Self {
+		Self {
+			backend,
+			witness: RefCell::new(Witness::default()),
+		}
+	}
+
+	/// The inner backend.
+	pub fn inner(&self) -> &B {
+		&self.backend
+	}
+
+	/// Unwrap the inner backend.
+	pub fn into_inner(self) -> B {
+		self.backend
+	}
+
+	/// Forget all recorded reads.
+	pub fn clear(&self) {
+		*self.witness.borrow_mut() = Witness::default();
+	}
+
+	/// Export the recorded reads.
+	pub fn witness(&self) -> Witness {
+		self.witness.borrow().clone()
+	}
+}
+
+impl<B: ProofBackend> RecordingBackend<B> {
+	/// Export the recorded reads, with proofs of every account and storage
+	/// slot against the state root of the inner backend.
+	pub fn witness_with_proofs(&mut self) -> Witness {
+		let mut witness = self.witness();
+		witness.state_root = Some(self.backend.state_root());
+
+		for (address, account) in &mut witness.accounts {
+			account.proof = self.backend.prove_account(*address);
+			account.storage_proofs = account
+				.storage
+				.keys()
+				.map(|index| (*index, self.backend.prove_storage(*address, *index)))
+				.collect();
+		}
+
+		witness
+	}
+}
+
+impl<B: RuntimeBaseBackend> RecordingBackend<B> {
+	fn with_account<R>(&self, address: H160, f: impl FnOnce(&mut WitnessAccount) -> R) -> R {
+		let mut witness = self.witness.borrow_mut();
+		let account = witness.accounts.entry(address).or_insert_with(|| {
+			let exists = self.backend.exists(address);
+			WitnessAccount {
+				exists,
+				balance: self.backend.balance(address),
+				nonce: self.backend.nonce(address),
+				code_hash: self.backend.code_hash(address),
+				..Default::default()
+			}
+		});
+		f(account)
+	}
+}
+
+impl<B: RuntimeEnvironment> RuntimeEnvironment for RecordingBackend<B> {
+	fn block_hash(&self, number: U256) -> H256 {
+		let hash = self.backend.block_hash(number);
+		self.witness.borrow_mut().block_hashes.insert(number, hash);
+		hash
+	}
+
+	fn block_number(&self) -> U256 {
+		self.backend.block_number()
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		self.backend.block_coinbase()
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		self.backend.block_timestamp()
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		self.backend.block_difficulty()
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		self.backend.block_randomness()
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		self.backend.block_gas_limit()
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		self.backend.block_base_fee_per_gas()
+	}
+
+	fn block_excess_blob_gas(&self) -> U256 {
+		self.backend.block_excess_blob_gas()
+	}
+
+	fn chain_id(&self) -> U256 {
+		self.backend.chain_id()
+	}
+}
+
+impl<B: RuntimeBaseBackend> RuntimeBaseBackend for RecordingBackend<B> {
+	fn balance(&self, address: H160) -> U256 {
+		self.with_account(address, |account| account.balance)
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		self.with_account(address, |account| account.code_hash)
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		let code_hash = self.code_hash(address);
+		let code = self.backend.code(address);
+		self.witness
+			.borrow_mut()
+			.codes
+			.insert(code_hash, code.clone());
+		code
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		self.with_account(address, |account| {
+			*account
+				.storage
+				.entry(index)
+				.or_insert_with(|| self.backend.storage(address, index))
+		})
+	}
+
+	fn transient_storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.transient_storage(address, index)
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		self.with_account(address, |account| account.exists)
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		self.with_account(address, |account| account.nonce)
+	}
+}
@@ -1,12 +1,78 @@
 use alloc::vec::Vec;
-use core::convert::Infallible;
+use core::{cmp::min, convert::Infallible};
 
 use evm_interpreter::{
-	error::{Capture, ExitError, ExitFatal, ExitResult},
+	error::{Capture, ExitError, ExitException, ExitFatal, ExitResult},
 	Interpreter, RunInterpreter, StepInterpreter,
 };
 
-use crate::invoker::{Invoker, InvokerControl};
+use crate::{
+	invoker::{Invoker, InvokerControl},
+	standard::GasometerState,
+};
+
+/// Policy of the call stack running a transaction, given by
+/// [Invoker::call_stack_policy].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CallStackPolicy {
+	/// Depth from which [transact] keeps frames on the heap instead of the
+	/// native stack. `None` to always use the native stack.
+	pub heap_depth: Option<usize>,
+	/// Maximum number of frames kept on the heap. Entering a frame beyond it
+	/// fails with [ExitException::CallTooDeep].
+	pub max_heap_frames: Option<usize>,
+	/// Memory limit of each frame, lowering the limit set by the resolver.
+	pub frame_memory_limit: Option<usize>,
+	/// Remaining native stack, in bytes, under which [transact] keeps frames
+	/// on the heap, whatever the depth.
+	#[cfg(feature = "stacker")]
+	pub min_native_stack: Option<usize>,
+	/// Limits of the pool of machine storage of the thread while [transact]
+	/// runs, reusing the stacks and memories of exited frames.
+	#[cfg(feature = "pool")]
+	pub pool: evm_interpreter::machine::pool::PoolLimits,
+}
+
+impl CallStackPolicy {
+	/// Whether a frame at `depth` should be kept on the heap.
+	fn use_heap(&self, depth: usize) -> bool {
+		if self
+			.heap_depth
+			.map_or(false, |heap_depth| depth >= heap_depth)
+		{
+			return true;
+		}
+
+		#[cfg(feature = "stacker")]
+		if let Some(min_native_stack) = self.min_native_stack {
+			if stacker::remaining_stack().map_or(false, |remaining| remaining < min_native_stack) {
+				return true;
+			}
+		}
+
+		false
+	}
+
+	/// Run `f` with the pool limits of the policy.
+	fn pooled<R, F: FnOnce() -> R>(&self, f: F) -> R {
+		#[cfg(feature = "pool")]
+		{
+			evm_interpreter::machine::pool::using(self.pool, f)
+		}
+		#[cfg(not(feature = "pool"))]
+		{
+			f()
+		}
+	}
+
+	/// Apply the frame memory limit to a newly entered machine.
+	fn limit_memory<M: Interpreter>(&self, machine: &mut M) {
+		if let Some(limit) = self.frame_memory_limit {
+			let memory = &mut machine.machine_mut().memory;
+			memory.set_limit(min(memory.limit(), limit));
+		}
+	}
+}
 
 struct Substack<M, TrD> {
 	invoke: TrD,
@@ -30,6 +96,7 @@ struct CallStack<'backend, 'invoker, H, Tr, I: Invoker<H, Tr>> {
 	stack: Vec<Substack<I::Interpreter, I::SubstackInvoke>>,
 	last: Option<LastSubstack<I::Interpreter, Tr>>,
 	initial_depth: usize,
+	policy: CallStackPolicy,
 	backend: &'backend mut H,
 	invoker: &'invoker I,
 }
@@ -51,6 +118,7 @@ where
 				status: LastSubstackStatus::Running,
 			}),
 			initial_depth,
+			policy: invoker.call_stack_policy(),
 			backend,
 			invoker,
 		}
@@ -129,7 +197,33 @@ where
 					self.backend,
 					self.initial_depth + self.stack.len() + 1,
 				) {
-					Capture::Exit(Ok((trap_data, InvokerControl::Enter(sub_machine)))) => {
+					Capture::Exit(Ok((trap_data, InvokerControl::Enter(sub_machine))))
+						if self
+							.policy
+							.max_heap_frames
+							.map_or(false, |max| self.stack.len() >= max) =>
+					{
+						let feedback_result = self.invoker.exit_substack(
+							ExitException::CallTooDeep.into(),
+							sub_machine.deconstruct(),
+							trap_data,
+							&mut machine,
+							self.backend,
+						);
+
+						match feedback_result {
+							Ok(()) => Some(LastSubstack {
+								status: LastSubstackStatus::Running,
+								machine,
+							}),
+							Err(err) => Some(LastSubstack {
+								machine,
+								status: LastSubstackStatus::Exited(Capture::Exit(Err(err))),
+							}),
+						}
+					}
+					Capture::Exit(Ok((trap_data, InvokerControl::Enter(mut sub_machine)))) => {
+						self.policy.limit_memory(&mut sub_machine);
 						self.stack.push(Substack {
 							invoke: trap_data,
 							machine,
@@ -237,7 +331,7 @@ where
 fn execute<H, Tr, I>(
 	mut machine: I::Interpreter,
 	initial_depth: usize,
-	heap_depth: Option<usize>,
+	policy: &CallStackPolicy,
 	backend: &mut H,
 	invoker: &I,
 ) -> Result<(ExitResult, I::Interpreter), ExitFatal>
@@ -252,10 +346,10 @@ where
 			Capture::Exit(exit) => return Ok((exit, machine)),
 			Capture::Trap(trap) => {
 				match invoker.enter_substack(trap, &mut machine, backend, initial_depth + 1) {
-					Capture::Exit(Ok((trap_data, InvokerControl::Enter(sub_machine)))) => {
-						let (sub_result, sub_machine) = if heap_depth
-							.map_or(false, |hd| initial_depth + 1 >= hd)
-						{
+					Capture::Exit(Ok((trap_data, InvokerControl::Enter(mut sub_machine)))) => {
+						policy.limit_memory(&mut sub_machine);
+
+						let (sub_result, sub_machine) = if policy.use_heap(initial_depth + 1) {
 							match CallStack::new(sub_machine, initial_depth + 1, backend, invoker)
 								.run()
 							{
@@ -263,7 +357,7 @@ where
 								Capture::Trap(infallible) => match infallible {},
 							}
 						} else {
-							execute(sub_machine, initial_depth + 1, heap_depth, backend, invoker)?
+							execute(sub_machine, initial_depth + 1, policy, backend, invoker)?
 						};
 
 						match invoker.exit_substack(
@@ -390,7 +484,8 @@ where
 					.map_err(|err| Capture::Exit(Err(err)))?;
 
 				match control {
-					InvokerControl::Enter(machine) => {
+					InvokerControl::Enter(mut machine) => {
+						invoker.call_stack_policy().limit_memory(&mut machine);
 						let call_stack = CallStack::new(machine, 0, backend, invoker);
 
 						ret = Ok(());
@@ -426,6 +521,53 @@ where
 			_ => None,
 		}
 	}
+
+	/// Gasometer of the last item on the call stack, to read its gas left,
+	/// used and refunded while stepping. This will be `None` if the heap
+	/// stack is just created or has exited.
+	pub fn last_gasometer<'config, G>(&self) -> Option<&GasometerState<'config, G>>
+	where
+		I::State: AsRef<GasometerState<'config, G>>,
+	{
+		self.last_interpreter()
+			.map(|interpreter| interpreter.machine().state.as_ref())
+	}
+
+	/// Depth of the last item on the call stack, zero for the transaction
+	/// itself. This will be `None` if the heap stack is just created or has
+	/// exited.
+	pub fn depth(&self) -> Option<usize> {
+		match &self.0 {
+			Some(HeapTransactState::Running { call_stack, .. }) if call_stack.last.is_some() => {
+				Some(call_stack.initial_depth + call_stack.stack.len())
+			}
+			_ => None,
+		}
+	}
+
+	/// The backend of the transaction.
+	pub fn backend(&self) -> Option<&H> {
+		match &self.0 {
+			Some(HeapTransactState::Created { backend, .. }) => Some(backend),
+			Some(HeapTransactState::Running { call_stack, .. }) => Some(call_stack.backend),
+			None => None,
+		}
+	}
+
+	/// Whether the machine of the last item on the call stack is about to
+	/// execute its next opcode, as opposed to entering or exiting a substack.
+	pub(crate) fn is_running(&self) -> bool {
+		match &self.0 {
+			Some(HeapTransactState::Running { call_stack, .. }) => matches!(
+				call_stack.last,
+				Some(LastSubstack {
+					status: LastSubstackStatus::Running,
+					..
+				})
+			),
+			_ => false,
+		}
+	}
 }
 
 impl<'backend, 'invoker, H, Tr, I> HeapTransact<'backend, 'invoker, H, Tr, I>
@@ -516,7 +658,8 @@ where
 ///
 /// After `heap_depth`, a heap-based call stack is then used.
 ///
-/// If `heap_depth` is `None`, then always use a stack-based call stack.
+/// If `heap_depth` is `None`, then the heap depth of the [CallStackPolicy] of
+/// the invoker is used, which by default always uses a stack-based call stack.
 ///
 /// Because a stack-based call stack cannot handle interrupts, the [Invoker]
 /// type must have its `Interrupt` type set to [Infallible].
@@ -530,16 +673,47 @@ where
 	I: Invoker<H, Tr, Interrupt = Infallible>,
 	I::Interpreter: RunInterpreter<H, Tr>,
 {
-	let (transact_invoke, control) = invoker.new_transact(args, backend)?;
+	transact_with(
+		args,
+		heap_depth,
+		backend,
+		invoker,
+		|invoke, exit, machine, backend| invoker.finalize_transact(invoke, exit, machine, backend),
+	)
+}
 
-	match control {
-		InvokerControl::Enter(machine) => {
-			let (ret, machine) = execute(machine, 0, heap_depth, backend, invoker)?;
-			let machine = machine.deconstruct();
-			invoker.finalize_transact(&transact_invoke, ret, machine, backend)
-		}
-		InvokerControl::DirectExit((exit, machine)) => {
-			invoker.finalize_transact(&transact_invoke, exit, machine, backend)
-		}
+/// Like [transact], but finalize the transaction with `finalize` instead of
+/// [Invoker::finalize_transact].
+pub(crate) fn transact_with<H, Tr, I, F, T>(
+	args: I::TransactArgs,
+	heap_depth: Option<usize>,
+	backend: &mut H,
+	invoker: &I,
+	finalize: F,
+) -> Result<T, ExitError>
+where
+	I: Invoker<H, Tr, Interrupt = Infallible>,
+	I::Interpreter: RunInterpreter<H, Tr>,
+	F: FnOnce(&I::TransactInvoke, ExitResult, (I::State, Vec<u8>), &mut H) -> Result<T, ExitError>,
+{
+	let mut policy = invoker.call_stack_policy();
+	if heap_depth.is_some() {
+		policy.heap_depth = heap_depth;
 	}
+
+	policy.pooled(|| {
+		let (transact_invoke, control) = invoker.new_transact(args, backend)?;
+
+		match control {
+			InvokerControl::Enter(mut machine) => {
+				policy.limit_memory(&mut machine);
+				let (ret, machine) = execute(machine, 0, &policy, backend, invoker)?;
+				let machine = machine.deconstruct();
+				finalize(&transact_invoke, ret, machine, backend)
+			}
+			InvokerControl::DirectExit((exit, machine)) => {
+				finalize(&transact_invoke, exit, machine, backend)
+			}
+		}
+	})
 }
@@ -0,0 +1,226 @@
+use alloc::vec::Vec;
+
+use evm_interpreter::{
+	error::{Capture, ExitError},
+	machine::Machine,
+	opcode::Opcode,
+	runtime::{RuntimeBaseBackend, RuntimeState},
+	Interpreter, StepInterpreter,
+};
+use primitive_types::{H160, H256};
+
+use crate::{call_stack::HeapTransact, invoker::Invoker, standard::GasometerState};
+
+/// Condition pausing a [Debugger].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Breakpoint {
+	/// Before executing the opcode at the position, in any call frame.
+	Pc(usize),
+	/// Before executing the opcode.
+	Opcode(Opcode),
+	/// When entering a call frame whose context address is the address.
+	Address(H160),
+}
+
+/// Interactive debugger on top of a [HeapTransact].
+///
+/// The debugger pauses before each opcode. [Debugger::step] executes a
+/// single opcode, stepping into calls, [Debugger::step_over] runs any call
+/// made by the opcode to completion, and [Debugger::resume] runs until a
+/// [Breakpoint] is hit. Whenever paused, the machine, the backend and the
+/// storage of the current call frame can be inspected.
+///
+/// All methods return `Err` when the transaction exits or is interrupted,
+/// like [HeapTransact::step].
+pub struct Debugger<'backend, 'invoker, H, Tr, I: Invoker<H, Tr>> {
+	transact: HeapTransact<'backend, 'invoker, H, Tr, I>,
+	breakpoints: Vec<Breakpoint>,
+	entered: bool,
+}
+
+impl<'backend, 'invoker, H, Tr, I> Debugger<'backend, 'invoker, H, Tr, I>
+where
+	I: Invoker<H, Tr>,
+	I::Interpreter: StepInterpreter<H, Tr>,
+	I::State: AsRef<RuntimeState>,
+{
+	/// Create a new debugger for a transaction. It is not paused until it is
+	/// first stepped or resumed.
+	pub fn new(
+		args: I::TransactArgs,
+		invoker: &'invoker I,
+		backend: &'backend mut H,
+	) -> Result<Self, ExitError> {
+		Ok(Self {
+			transact: HeapTransact::new(args, invoker, backend)?,
+			breakpoints: Vec::new(),
+			entered: false,
+		})
+	}
+
+	/// Add a breakpoint.
+	pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+		if !self.breakpoints.contains(&breakpoint) {
+			self.breakpoints.push(breakpoint);
+		}
+	}
+
+	/// Remove a breakpoint, returning whether it was set.
+	pub fn remove_breakpoint(&mut self, breakpoint: &Breakpoint) -> bool {
+		let len = self.breakpoints.len();
+		self.breakpoints.retain(|b| b != breakpoint);
+		self.breakpoints.len() != len
+	}
+
+	/// Remove all breakpoints.
+	pub fn clear_breakpoints(&mut self) {
+		self.breakpoints.clear();
+	}
+
+	/// Breakpoints set.
+	pub fn breakpoints(&self) -> &[Breakpoint] {
+		&self.breakpoints
+	}
+
+	/// Execute the next opcode, and pause before the one following it. If
+	/// the opcode is a call or create, this pauses at the start of the new
+	/// call frame. Initially, this pauses before the first opcode of the
+	/// transaction.
+	#[allow(clippy::type_complexity)]
+	pub fn step(
+		&mut self,
+	) -> Result<(), Capture<Result<I::TransactValue, ExitError>, I::Interrupt>> {
+		let depth = self.transact.depth();
+
+		loop {
+			self.transact.step()?;
+
+			if self.transact.is_running() {
+				self.entered = match (depth, self.transact.depth()) {
+					(Some(before), Some(after)) => after > before,
+					_ => true,
+				};
+				return Ok(());
+			}
+		}
+	}
+
+	/// Like [Debugger::step], but if the opcode is a call or create, run it
+	/// to completion and pause after it in the current call frame. Returns
+	/// the breakpoint hit inside the call, if any, pausing there.
+	#[allow(clippy::type_complexity)]
+	pub fn step_over(
+		&mut self,
+	) -> Result<Option<Breakpoint>, Capture<Result<I::TransactValue, ExitError>, I::Interrupt>> {
+		let depth = self.transact.depth();
+		self.step()?;
+
+		while self.transact.depth() > depth {
+			self.step()?;
+
+			if let Some(breakpoint) = self.hit() {
+				return Ok(Some(breakpoint));
+			}
+		}
+
+		Ok(None)
+	}
+
+	/// Run until a breakpoint is hit, and pause there. Returns the
+	/// breakpoint hit.
+	#[allow(clippy::type_complexity)]
+	pub fn resume(
+		&mut self,
+	) -> Result<Breakpoint, Capture<Result<I::TransactValue, ExitError>, I::Interrupt>> {
+		loop {
+			self.step()?;
+
+			if let Some(breakpoint) = self.hit() {
+				return Ok(breakpoint);
+			}
+		}
+	}
+
+	/// The first breakpoint matching the current pause.
+	pub fn hit(&self) -> Option<Breakpoint> {
+		let (position, opcode, address) = (self.position()?, self.opcode(), self.address()?);
+
+		self.breakpoints
+			.iter()
+			.find(|breakpoint| match breakpoint {
+				Breakpoint::Pc(pc) => *pc == position,
+				Breakpoint::Opcode(op) => Some(*op) == opcode,
+				Breakpoint::Address(a) => self.entered && *a == address,
+			})
+			.cloned()
+	}
+
+	/// The interpreter of the current call frame, if paused.
+	pub fn interpreter(&self) -> Option<&I::Interpreter> {
+		if self.transact.is_running() {
+			self.transact.last_interpreter()
+		} else {
+			None
+		}
+	}
+
+	/// The machine of the current call frame, including its stack and
+	/// memory, if paused.
+	pub fn machine(&self) -> Option<&Machine<I::State, <I::Interpreter as Interpreter>::Memory>> {
+		self.interpreter().map(|interpreter| interpreter.machine())
+	}
+
+	/// Gasometer of the current call frame, with its gas left, used and
+	/// refunded, if paused.
+	pub fn gasometer<'config, G>(&self) -> Option<&GasometerState<'config, G>>
+	where
+		I::State: AsRef<GasometerState<'config, G>>,
+	{
+		self.machine().map(|machine| machine.state.as_ref())
+	}
+
+	/// Position of the next opcode in the code, if paused.
+	pub fn position(&self) -> Option<usize> {
+		self.interpreter().map(|interpreter| interpreter.position())
+	}
+
+	/// The next opcode. This is `None` if not paused, or at the end of the
+	/// code.
+	pub fn opcode(&self) -> Option<Opcode> {
+		self.interpreter()?.opcode()
+	}
+
+	/// Address of the current call frame, if paused.
+	pub fn address(&self) -> Option<H160> {
+		self.machine()
+			.map(|machine| machine.state.as_ref().context.address)
+	}
+
+	/// Depth of the current call frame, zero for the transaction itself, if
+	/// paused.
+	pub fn depth(&self) -> Option<usize> {
+		if self.transact.is_running() {
+			self.transact.depth()
+		} else {
+			None
+		}
+	}
+
+	/// The backend of the transaction.
+	pub fn backend(&self) -> Option<&H> {
+		self.transact.backend()
+	}
+
+	/// Storage value of the current call frame, if paused.
+	pub fn storage(&self, index: H256) -> Option<H256>
+	where
+		H: RuntimeBaseBackend,
+	{
+		Some(self.backend()?.storage(self.address()?, index))
+	}
+
+	/// Unwrap the underlying [HeapTransact].
+	pub fn into_inner(self) -> HeapTransact<'backend, 'invoker, H, Tr, I> {
+		self.transact
+	}
+}
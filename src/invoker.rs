@@ -5,6 +5,8 @@ use evm_interpreter::{
 	Interpreter,
 };
 
+use crate::CallStackPolicy;
+
 /// Control for an invoker.
 pub enum InvokerControl<VE, VD> {
 	/// Pushing the call stack.
@@ -73,6 +75,11 @@ pub trait Invoker<H, Tr> {
 		Self::Interrupt,
 	>;
 
+	/// Policy of the call stack running transactions with this invoker.
+	fn call_stack_policy(&self) -> CallStackPolicy {
+		CallStackPolicy::default()
+	}
+
 	/// Exit a sub-layer call stack.
 	fn exit_substack(
 		&self,
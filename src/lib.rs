@@ -38,6 +38,10 @@
 //! heap-only call stack [HeapTransact]. Parameters passed to [HeapTransact] are
 //! the same as [transact].
 //!
+//! For a debugger frontend, [Debugger] builds on [HeapTransact] with
+//! breakpoints on positions, opcodes and addresses, stepping over calls, and
+//! inspection of the stack, memory and storage at each pause.
+//!
 //! ### Tracing
 //!
 //! The interpreter machine uses information from an [Etable] to decide how each
@@ -46,6 +50,9 @@
 //!
 //! If you also want to trace inside gasometers, simply create a wrapper struct
 //! of the gasometer you use, and pass that into the invoker.
+//! With the `tracing` feature, the standard gasometer also emits an event for
+//! every gas charge, refund, memory gas update and submeter merge, attributed
+//! to its opcode or transaction cost. See `standard::tracing`.
 //!
 //! ## Customization
 //!
@@ -66,17 +73,23 @@
 extern crate alloc;
 
 pub mod backend;
+#[cfg(feature = "parallel")]
+pub mod parallel;
 pub mod standard;
 
 mod call_stack;
+mod debugger;
 mod gasometer;
 mod invoker;
 
 pub use evm_interpreter as interpreter;
 
+#[cfg(feature = "async")]
+pub use crate::backend::transact_async;
 pub use crate::{
 	backend::TransactionalBackend,
-	call_stack::{transact, HeapTransact},
+	call_stack::{transact, CallStackPolicy, HeapTransact},
+	debugger::{Breakpoint, Debugger},
 	gasometer::GasMutState,
 	invoker::{Invoker, InvokerControl},
 };
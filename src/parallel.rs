@@ -0,0 +1,412 @@
+//! Optimistic parallel execution of an ordered list of transactions.
+//!
+//! Transactions are executed concurrently on worker threads, each against the
+//! state committed by the transactions before it at the time. The reads of
+//! every execution are recorded, and its result is only committed if none of
+//! the transactions committed in the meantime wrote to what it read.
+//! Otherwise it is executed again on the updated state. The outcome is the
+//! same as executing the transactions one after another.
+//!
+//! Conflicts are tracked per account field and storage slot. Transactions
+//! paying fees to the same coinbase all write its balance, so to benefit
+//! from parallelism, fees should be handled outside of the executed
+//! transactions, for example with a zero gas price.
+//!
+//! Workers are scoped threads, so this module requires Rust 1.63, above the
+//! minimum supported version of the rest of the crate.
+
+use alloc::{
+	collections::{BTreeMap, BTreeSet},
+	vec::Vec,
+};
+use core::{
+	cell::RefCell,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+use std::{
+	sync::{Arc, Mutex},
+	thread,
+};
+
+use primitive_types::{H160, H256, U256};
+
+use crate::backend::{
+	OverlayedBackend, OverlayedChangeSet, RuntimeBaseBackend, RuntimeEnvironment,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+enum Key {
+	Balance(H160),
+	Nonce(H160),
+	Code(H160),
+	Exists(H160),
+	Storage(H160, H256),
+}
+
+impl Key {
+	fn address(&self) -> H160 {
+		match self {
+			Self::Balance(address)
+			| Self::Nonce(address)
+			| Self::Code(address)
+			| Self::Exists(address)
+			| Self::Storage(address, _) => *address,
+		}
+	}
+}
+
+#[derive(Default)]
+struct WriteSet {
+	keys: BTreeSet<Key>,
+	storage_resets: BTreeSet<H160>,
+	deletes: BTreeSet<H160>,
+}
+
+impl WriteSet {
+	fn record(&mut self, changeset: &OverlayedChangeSet) {
+		for address in changeset.balances.keys() {
+			self.keys.insert(Key::Balance(*address));
+			self.keys.insert(Key::Exists(*address));
+		}
+		for address in changeset.codes.keys() {
+			self.keys.insert(Key::Code(*address));
+			self.keys.insert(Key::Exists(*address));
+		}
+		for address in changeset.nonces.keys() {
+			self.keys.insert(Key::Nonce(*address));
+			self.keys.insert(Key::Exists(*address));
+		}
+		for (address, index) in changeset.storages.keys() {
+			self.keys.insert(Key::Storage(*address, *index));
+			self.keys.insert(Key::Exists(*address));
+		}
+		self.storage_resets
+			.extend(changeset.storage_resets.iter().cloned());
+		self.deletes.extend(changeset.deletes.iter().cloned());
+	}
+
+	fn merge(&mut self, other: &WriteSet) {
+		self.keys.extend(other.keys.iter().cloned());
+		self.storage_resets
+			.extend(other.storage_resets.iter().cloned());
+		self.deletes.extend(other.deletes.iter().cloned());
+	}
+
+	fn conflicts(&self, read: &Key) -> bool {
+		self.keys.contains(read)
+			|| self.deletes.contains(&read.address())
+			|| (matches!(read, Key::Storage(..)) && self.storage_resets.contains(&read.address()))
+	}
+}
+
+#[derive(Default)]
+struct CommittedAccount {
+	balance: Option<U256>,
+	nonce: Option<U256>,
+	code: Option<Vec<u8>>,
+	storage: BTreeMap<H256, H256>,
+	storage_reset: bool,
+	deleted: bool,
+}
+
+/// Changes of the committed transactions, on top of the base backend.
+#[derive(Default)]
+struct Committed {
+	accounts: BTreeMap<H160, CommittedAccount>,
+}
+
+impl Committed {
+	fn apply(&mut self, changeset: &OverlayedChangeSet) {
+		for (address, balance) in &changeset.balances {
+			let account = self.accounts.entry(*address).or_default();
+			account.balance = Some(*balance);
+			account.deleted = false;
+		}
+
+		for (address, code) in &changeset.codes {
+			let account = self.accounts.entry(*address).or_default();
+			account.code = Some(code.clone());
+			account.deleted = false;
+		}
+
+		for (address, nonce) in &changeset.nonces {
+			let account = self.accounts.entry(*address).or_default();
+			account.nonce = Some(*nonce);
+			account.deleted = false;
+		}
+
+		for address in &changeset.storage_resets {
+			let account = self.accounts.entry(*address).or_default();
+			account.storage = BTreeMap::new();
+			account.storage_reset = true;
+			account.deleted = false;
+		}
+
+		for ((address, index), value) in &changeset.storages {
+			let account = self.accounts.entry(*address).or_default();
+			account.storage.insert(*index, *value);
+			account.deleted = false;
+		}
+
+		for address in &changeset.deletes {
+			self.accounts.insert(
+				*address,
+				CommittedAccount {
+					balance: Some(U256::zero()),
+					nonce: Some(U256::zero()),
+					code: Some(Vec::new()),
+					storage: BTreeMap::new(),
+					storage_reset: true,
+					deleted: true,
+				},
+			);
+		}
+	}
+}
+
+/// View of the state for an execution: the base backend with the changes of
+/// the committed transactions. Reads are recorded for conflict detection.
+pub struct ParallelView<'backend, B> {
+	backend: &'backend B,
+	committed: Arc<Committed>,
+	reads: RefCell<BTreeSet<Key>>,
+}
+
+impl<'backend, B> ParallelView<'backend, B> {
+	fn read(&self, key: Key) -> Option<&CommittedAccount> {
+		self.reads.borrow_mut().insert(key);
+		self.committed.accounts.get(&key.address())
+	}
+}
+
+impl<'backend, B: RuntimeEnvironment> RuntimeEnvironment for ParallelView<'backend, B> {
+	fn block_hash(&self, number: U256) -> H256 {
+		self.backend.block_hash(number)
+	}
+
+	fn block_number(&self) -> U256 {
+		self.backend.block_number()
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		self.backend.block_coinbase()
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		self.backend.block_timestamp()
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		self.backend.block_difficulty()
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		self.backend.block_randomness()
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		self.backend.block_gas_limit()
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		self.backend.block_base_fee_per_gas()
+	}
+
+	fn block_excess_blob_gas(&self) -> U256 {
+		self.backend.block_excess_blob_gas()
+	}
+
+	fn chain_id(&self) -> U256 {
+		self.backend.chain_id()
+	}
+}
+
+impl<'backend, B: RuntimeBaseBackend> RuntimeBaseBackend for ParallelView<'backend, B> {
+	fn balance(&self, address: H160) -> U256 {
+		match self.read(Key::Balance(address)).and_then(|a| a.balance) {
+			Some(balance) => balance,
+			None => self.backend.balance(address),
+		}
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		match self.read(Key::Code(address)).and_then(|a| a.code.as_ref()) {
+			Some(code) => code.clone(),
+			None => self.backend.code(address),
+		}
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		match self.read(Key::Storage(address, index)) {
+			Some(account) => match account.storage.get(&index) {
+				Some(value) => *value,
+				None if account.storage_reset => H256::default(),
+				None => self.backend.storage(address, index),
+			},
+			None => self.backend.storage(address, index),
+		}
+	}
+
+	fn transient_storage(&self, _address: H160, _index: H256) -> H256 {
+		H256::default()
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		match self.read(Key::Exists(address)) {
+			Some(account) => !account.deleted,
+			None => self.backend.exists(address),
+		}
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		match self.read(Key::Nonce(address)).and_then(|a| a.nonce) {
+			Some(nonce) => nonce,
+			None => self.backend.nonce(address),
+		}
+	}
+}
+
+/// Outcome of [execute_parallel].
+pub struct ParallelOutput<R> {
+	/// Result and changes of every transaction, in order. Apply the changes
+	/// to the base backend in this order.
+	pub results: Vec<(R, OverlayedChangeSet)>,
+	/// Number of executions, including re-executions after conflicts.
+	pub executions: usize,
+}
+
+struct Execution<R> {
+	result: R,
+	changeset: OverlayedChangeSet,
+	reads: BTreeSet<Key>,
+	writes: WriteSet,
+	/// Number of transactions committed when it was executed.
+	base: usize,
+	/// Whether it conflicted and must run again. Stale executions are kept
+	/// as estimates of what the transaction reads and writes.
+	stale: bool,
+}
+
+/// Execute `count` transactions on `threads` worker threads, as if executed
+/// in order on `backend`.
+///
+/// `execute` is called with the index of a transaction and an overlay to
+/// execute it on, usually with [crate::transact]. It may be called several
+/// times for the same transaction, and must not have side effects other than
+/// on the overlay.
+pub fn execute_parallel<'backend, B, F, R>(
+	backend: &'backend B,
+	count: usize,
+	threads: usize,
+	execute: F,
+) -> ParallelOutput<R>
+where
+	B: RuntimeEnvironment + RuntimeBaseBackend + Sync,
+	F: Fn(usize, &mut OverlayedBackend<ParallelView<'backend, B>>) -> R + Sync,
+	R: Send,
+{
+	let mut committed = Arc::new(Committed::default());
+	let mut writes = Vec::<WriteSet>::with_capacity(count);
+	let mut results = Vec::with_capacity(count);
+	let mut executions = 0;
+	let mut pending: BTreeMap<usize, Option<Execution<R>>> =
+		(0..count).map(|i| (i, None)).collect();
+
+	while !pending.is_empty() {
+		let base = results.len();
+
+		// Defer transactions estimated to read what an earlier pending one
+		// writes, as they would conflict anyway. The first pending
+		// transaction always runs.
+		let mut estimated = WriteSet::default();
+		let mut to_execute = Vec::new();
+		for (index, execution) in &pending {
+			match execution {
+				Some(execution) => {
+					if execution.stale
+						&& !execution.reads.iter().any(|read| estimated.conflicts(read))
+					{
+						to_execute.push(*index);
+					}
+					estimated.merge(&execution.writes);
+				}
+				None => to_execute.push(*index),
+			}
+		}
+		executions += to_execute.len();
+
+		let next = AtomicUsize::new(0);
+		let done = Mutex::new(Vec::with_capacity(to_execute.len()));
+		thread::scope(|scope| {
+			for _ in 0..threads.max(1).min(to_execute.len()) {
+				scope.spawn(|| {
+					while let Some(index) = to_execute.get(next.fetch_add(1, Ordering::Relaxed)) {
+						let view = ParallelView {
+							backend,
+							committed: committed.clone(),
+							reads: RefCell::new(BTreeSet::new()),
+						};
+						let mut overlay = OverlayedBackend::new(view, Default::default());
+						let result = execute(*index, &mut overlay);
+						let (view, changeset) = overlay.deconstruct();
+						let mut writes = WriteSet::default();
+						writes.record(&changeset);
+						let execution = Execution {
+							result,
+							changeset,
+							reads: view.reads.into_inner(),
+							writes,
+							base,
+							stale: false,
+						};
+
+						done.lock()
+							.expect("worker panicked")
+							.push((*index, execution));
+					}
+				});
+			}
+		});
+		for (index, execution) in done.into_inner().expect("worker panicked") {
+			pending.insert(index, Some(execution));
+		}
+
+		// Commit valid executions in order, until the first conflict. Later
+		// executions already conflicting are marked stale to run again.
+		let mut blocked = false;
+		for slot in pending.values_mut() {
+			let execution = match slot {
+				Some(execution) => execution,
+				None => {
+					blocked = true;
+					continue;
+				}
+			};
+
+			let valid = !execution.stale
+				&& !execution.reads.iter().any(|read| {
+					writes[execution.base..]
+						.iter()
+						.any(|write| write.conflicts(read))
+				});
+
+			if !valid {
+				execution.stale = true;
+				blocked = true;
+			} else if !blocked {
+				let execution = slot.take().expect("checked above");
+				Arc::get_mut(&mut committed)
+					.expect("views are dropped after execution")
+					.apply(&execution.changeset);
+				writes.push(execution.writes);
+				results.push((execution.result, execution.changeset));
+			}
+		}
+		pending.retain(|index, _| *index >= results.len());
+	}
+
+	ParallelOutput {
+		results,
+		executions,
+	}
+}
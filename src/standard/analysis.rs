@@ -0,0 +1,221 @@
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::ops::Range;
+
+use evm_interpreter::opcode::Opcode;
+
+use crate::standard::{gasometer::opcode_enabled, Config};
+
+/// Static analysis of code, see [analyze_code].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CodeAnalysis {
+	/// Highest stack height reached following static jumps, that is jumps
+	/// whose destination is pushed right before them. Above the stack limit
+	/// if the stack can overflow, such as in a loop growing it.
+	pub max_stack_height: usize,
+	/// Whether the code has jumps whose destination is not pushed right
+	/// before them. Their destinations are not followed for the stack
+	/// height.
+	pub dynamic_jumps: bool,
+	/// Code that cannot be executed: the code following an opcode that halts
+	/// or jumps, up to the next `JUMPDEST`.
+	pub unreachable: Vec<Range<usize>>,
+	/// Offset of a push whose data is cut by the end of the code.
+	pub truncated_push: Option<usize>,
+	/// Reachable opcodes that are not defined under the config, with their
+	/// offsets. The designated `INVALID` opcode is not included.
+	pub invalid_opcodes: Vec<(usize, Opcode)>,
+}
+
+struct Instruction {
+	offset: usize,
+	opcode: Opcode,
+	enabled: bool,
+	value: Option<usize>,
+}
+
+impl Instruction {
+	fn halts(&self) -> bool {
+		!self.enabled
+			|| matches!(
+				self.opcode,
+				Opcode::STOP | Opcode::RETURN | Opcode::REVERT | Opcode::INVALID | Opcode::SUICIDE
+			)
+	}
+
+	fn ends_block(&self) -> bool {
+		self.halts() || matches!(self.opcode, Opcode::JUMP | Opcode::JUMPI)
+	}
+}
+
+/// Analyze legacy code under `config`, without executing it. Usable to
+/// reject pathological code, for example in a
+/// [crate::standard::CreateFilter] or a deployment policy.
+#[must_use]
+pub fn analyze_code(code: &[u8], config: &Config) -> CodeAnalysis {
+	let mut analysis = CodeAnalysis::default();
+
+	let mut instructions = Vec::new();
+	let mut position = 0;
+	while position < code.len() {
+		let opcode = Opcode(code[position]);
+		let mut value = None;
+		let mut next = position + 1;
+
+		if let Some(n) = opcode.is_push() {
+			let n = n as usize;
+			let data = &code[next..code.len().min(next + n)];
+			if data.len() < n {
+				analysis.truncated_push = Some(position);
+			}
+			value = push_value(data, n);
+			next += n;
+		}
+
+		instructions.push(Instruction {
+			offset: position,
+			opcode,
+			enabled: opcode == Opcode::INVALID || opcode_enabled(opcode, config),
+			value,
+		});
+		position = next;
+	}
+
+	let mut reachable = true;
+	for (i, instruction) in instructions.iter().enumerate() {
+		if instruction.opcode == Opcode::JUMPDEST {
+			reachable = true;
+		}
+		if !reachable {
+			match analysis.unreachable.last_mut() {
+				Some(range) if range.end == instruction.offset => {
+					range.end = next_offset(&instructions, i, code.len())
+				}
+				_ => analysis
+					.unreachable
+					.push(instruction.offset..next_offset(&instructions, i, code.len())),
+			}
+			continue;
+		}
+
+		if !instruction.enabled {
+			analysis
+				.invalid_opcodes
+				.push((instruction.offset, instruction.opcode));
+		}
+		if instruction.halts() || instruction.opcode == Opcode::JUMP {
+			reachable = false;
+		}
+	}
+
+	stack_heights(&instructions, config, &mut analysis);
+	analysis
+}
+
+fn next_offset(instructions: &[Instruction], index: usize, len: usize) -> usize {
+	instructions.get(index + 1).map_or(len, |next| next.offset)
+}
+
+/// Value of a push of `n` bytes, if it fits in `usize`. Push data cut by the
+/// end of the code is padded with zeros.
+fn push_value(data: &[u8], n: usize) -> Option<usize> {
+	let mut value = 0usize;
+	for i in 0..n {
+		let byte = data.get(i).copied().unwrap_or(0);
+		value = value.checked_mul(256)?.checked_add(byte as usize)?;
+	}
+	Some(value)
+}
+
+/// Propagate stack heights through the blocks of the code, following
+/// static jumps.
+fn stack_heights(instructions: &[Instruction], config: &Config, analysis: &mut CodeAnalysis) {
+	let mut starts = vec![0];
+	for (i, instruction) in instructions.iter().enumerate() {
+		if instruction.opcode == Opcode::JUMPDEST && i != 0 {
+			starts.push(i);
+		}
+		if instruction.ends_block() && i + 1 < instructions.len() {
+			starts.push(i + 1);
+		}
+	}
+	starts.sort_unstable();
+	starts.dedup();
+
+	let jumpdests = starts
+		.iter()
+		.enumerate()
+		.filter(|(_, start)| {
+			instructions
+				.get(**start)
+				.map_or(false, |instruction| instruction.opcode == Opcode::JUMPDEST)
+		})
+		.map(|(block, start)| (instructions[*start].offset, block))
+		.collect::<BTreeMap<_, _>>();
+
+	let mut entries = vec![None; starts.len()];
+	let mut pending = Vec::new();
+	if !instructions.is_empty() {
+		entries[0] = Some(0);
+		pending.push(0);
+	}
+
+	while let Some(block) = pending.pop() {
+		let mut height = match entries[block] {
+			Some(height) => height,
+			None => continue,
+		};
+		let end = starts.get(block + 1).copied().unwrap_or(instructions.len());
+		let mut successors = [None, None];
+		let mut halted = false;
+
+		for i in starts[block]..end {
+			let instruction = &instructions[i];
+			if instruction.halts() {
+				halted = true;
+				break;
+			}
+
+			let (pops, pushes) = match instruction.opcode.stack_io() {
+				Some(io) => io,
+				None => {
+					halted = true;
+					break;
+				}
+			};
+			if height < pops {
+				halted = true;
+				break;
+			}
+			height = height - pops + pushes;
+			analysis.max_stack_height = analysis.max_stack_height.max(height);
+			if height > config.stack_limit {
+				halted = true;
+				break;
+			}
+
+			if matches!(instruction.opcode, Opcode::JUMP | Opcode::JUMPI) {
+				let target = i
+					.checked_sub(1)
+					.and_then(|previous| instructions[previous].value);
+				match target {
+					Some(target) => successors[0] = jumpdests.get(&target).copied(),
+					None => analysis.dynamic_jumps = true,
+				}
+				if instruction.opcode == Opcode::JUMP {
+					halted = true;
+				}
+			}
+		}
+
+		if !halted && block + 1 < starts.len() {
+			successors[1] = Some(block + 1);
+		}
+
+		for successor in successors.into_iter().flatten() {
+			if entries[successor].map_or(true, |entry| entry < height) {
+				entries[successor] = Some(height);
+				pending.push(successor);
+			}
+		}
+	}
+}
@@ -0,0 +1,164 @@
+use alloc::vec::Vec;
+use core::{convert::Infallible, fmt};
+
+use evm_interpreter::{
+	error::ExitError,
+	runtime::{Bloom, Log, RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment, RuntimeState},
+	RunInterpreter,
+};
+use primitive_types::{H160, U256};
+
+use crate::{
+	backend::{ApplyBackend, OverlayedBackend},
+	call_stack::transact_with,
+	standard::{Invoker, InvokerState, Resolver, TransactArgs, TransactInvoke},
+	Invoker as InvokerT,
+};
+
+/// Receipt of a transaction executed in a block.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Receipt {
+	/// Whether the execution succeeded.
+	pub status: bool,
+	/// Gas used by the transaction.
+	pub gas_used: U256,
+	/// Gas used in the block up to and including the transaction.
+	pub cumulative_gas_used: U256,
+	/// Logs emitted, empty if the execution failed.
+	pub logs: Vec<Log>,
+	/// Bloom filter of the logs.
+	pub logs_bloom: Bloom,
+}
+
+/// Withdrawal from the beacon chain (see EIP-4895).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Withdrawal {
+	/// Index of the withdrawal.
+	pub index: u64,
+	/// Index of the validator.
+	pub validator_index: u64,
+	/// Recipient of the withdrawal.
+	pub address: H160,
+	/// Amount in gwei.
+	pub amount: u64,
+}
+
+/// Credit the withdrawals of a block to their recipients, after its
+/// transactions. This does not execute any code and costs no gas.
+pub fn apply_withdrawals<'a, H: RuntimeBackend>(
+	withdrawals: impl IntoIterator<Item = &'a Withdrawal>,
+	backend: &mut H,
+) {
+	for withdrawal in withdrawals {
+		// A zero amount has no effect, and must not create the account.
+		if withdrawal.amount != 0 {
+			let amount = U256::from(withdrawal.amount) * U256::exp10(9);
+			backend.deposit(withdrawal.address, amount);
+		}
+	}
+}
+
+/// Error making a block invalid.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlockError {
+	/// The transaction at the index does not fit in the remaining block gas.
+	GasLimitReached(usize),
+	/// The transaction at the index is invalid.
+	InvalidTransaction(usize, ExitError),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BlockError {}
+
+impl fmt::Display for BlockError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::GasLimitReached(index) => {
+				write!(f, "transaction {} exceeds the block gas limit", index)
+			}
+			Self::InvalidTransaction(index, err) => {
+				write!(f, "transaction {} is invalid: {:?}", index, err)
+			}
+		}
+	}
+}
+
+/// Execute the transactions of a block in order, applying the changes of
+/// each to `backend`, which is returned afterwards.
+///
+/// The block header, including its gas limit, is the environment of
+/// `backend`. Failed executions still produce a receipt, but an invalid
+/// transaction, or one exceeding the remaining block gas, makes the block
+/// invalid. Changes of the transactions before it are then already applied.
+pub fn execute_block<'config, 'resolver, B, R, Tr>(
+	transactions: impl IntoIterator<Item = TransactArgs>,
+	heap_depth: Option<usize>,
+	mut backend: B,
+	invoker: &Invoker<'config, 'resolver, R>,
+) -> (B, Result<Vec<Receipt>, BlockError>)
+where
+	B: ApplyBackend + RuntimeEnvironment + RuntimeBaseBackend,
+	R: Resolver<OverlayedBackend<B>>,
+	R::State: InvokerState<'config> + AsRef<RuntimeState> + AsMut<RuntimeState>,
+	R::Interpreter: RunInterpreter<OverlayedBackend<B>, Tr>,
+	Invoker<'config, 'resolver, R>: InvokerT<
+		OverlayedBackend<B>,
+		Tr,
+		Interrupt = Infallible,
+		State = R::State,
+		Interpreter = R::Interpreter,
+		TransactArgs = TransactArgs,
+		TransactInvoke = TransactInvoke,
+	>,
+{
+	let block_gas_limit = backend.block_gas_limit();
+	let mut cumulative_gas_used = U256::zero();
+	let mut receipts = Vec::new();
+
+	for (index, args) in transactions.into_iter().enumerate() {
+		if args.gas_limit() > block_gas_limit.saturating_sub(cumulative_gas_used) {
+			return (backend, Err(BlockError::GasLimitReached(index)));
+		}
+
+		let mut overlay = OverlayedBackend::new(backend, Default::default());
+		let result = transact_with(
+			args,
+			heap_depth,
+			&mut overlay,
+			invoker,
+			|invoke, exit, machine, overlay| {
+				Ok(invoker.finalize_transact_with_gas(invoke, exit, machine, overlay))
+			},
+		);
+		let (inner, changeset) = overlay.deconstruct();
+		backend = inner;
+		let (result, gas) = match result {
+			Ok(result) => result,
+			Err(err) => return (backend, Err(BlockError::InvalidTransaction(index, err))),
+		};
+		backend.apply_overlayed(&changeset);
+		let gas_used = gas.effective;
+
+		cumulative_gas_used = cumulative_gas_used.saturating_add(gas_used);
+		receipts.push(Receipt {
+			status: result.is_ok(),
+			gas_used,
+			cumulative_gas_used,
+			logs_bloom: Bloom::from_logs(&changeset.logs),
+			logs: changeset.logs,
+		});
+	}
+
+	(backend, Ok(receipts))
+}
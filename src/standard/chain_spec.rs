@@ -0,0 +1,146 @@
+use core::fmt;
+
+use crate::standard::{Config, ForkActivation, ForkSchedule};
+
+/// A geth `genesis.json`, only the fields needed to build the configs.
+///
+/// Deserialize it with any serde format, for example `serde_json`, and pass it
+/// to [from_chain_spec].
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct ChainSpec {
+	/// Chain configuration.
+	pub config: ChainConfig,
+}
+
+/// The `config` object of a geth `genesis.json`.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainConfig {
+	/// Homestead switch block.
+	pub homestead_block: Option<u64>,
+	/// EIP-150 switch block.
+	pub eip150_block: Option<u64>,
+	/// EIP-155 switch block.
+	pub eip155_block: Option<u64>,
+	/// EIP-158 switch block.
+	pub eip158_block: Option<u64>,
+	/// Byzantium switch block.
+	pub byzantium_block: Option<u64>,
+	/// Constantinople switch block.
+	pub constantinople_block: Option<u64>,
+	/// Petersburg switch block.
+	pub petersburg_block: Option<u64>,
+	/// Istanbul switch block.
+	pub istanbul_block: Option<u64>,
+	/// Berlin switch block.
+	pub berlin_block: Option<u64>,
+	/// London switch block.
+	pub london_block: Option<u64>,
+	/// The Merge (Paris) switch block.
+	pub merge_netsplit_block: Option<u64>,
+	/// Shanghai switch time.
+	pub shanghai_time: Option<u64>,
+	/// Cancun switch time.
+	pub cancun_time: Option<u64>,
+	/// Prague switch time.
+	pub prague_time: Option<u64>,
+	/// Osaka switch time.
+	pub osaka_time: Option<u64>,
+}
+
+/// Error building configs from a chain spec.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChainSpecError {
+	/// Hard forks are not activated in order.
+	UnorderedFork(&'static str),
+	/// A hard fork without a preset, before Istanbul, is activated before the
+	/// first fork with a preset, or a hard fork with an incomplete preset,
+	/// from Prague, is activated.
+	UnsupportedFork(&'static str),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChainSpecError {}
+
+impl fmt::Display for ChainSpecError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::UnorderedFork(name) => write!(f, "hard fork {} activated out of order", name),
+			Self::UnsupportedFork(name) => {
+				write!(f, "hard fork {} activated before a supported fork", name)
+			}
+		}
+	}
+}
+
+/// Build the fork schedule of a chain from its geth chain spec.
+///
+/// Forks between Frontier and Istanbul have no preset, so they must not be
+/// activated before the first fork with a preset. A chain activating them
+/// after genesis, such as mainnet, is rejected with
+/// [ChainSpecError::UnsupportedFork]. So is a chain activating Prague or
+/// Osaka, whose presets are incomplete.
+pub fn from_chain_spec(spec: &ChainSpec) -> Result<ForkSchedule, ChainSpecError> {
+	let chain = &spec.config;
+
+	let by_block: [(&'static str, Option<u64>, Option<Config>); 11] = [
+		("homestead", chain.homestead_block, None),
+		("eip150", chain.eip150_block, None),
+		("eip155", chain.eip155_block, None),
+		("eip158", chain.eip158_block, None),
+		("byzantium", chain.byzantium_block, None),
+		("constantinople", chain.constantinople_block, None),
+		("petersburg", chain.petersburg_block, None),
+		("istanbul", chain.istanbul_block, Some(Config::istanbul())),
+		("berlin", chain.berlin_block, Some(Config::berlin())),
+		("london", chain.london_block, Some(Config::london())),
+		("merge", chain.merge_netsplit_block, Some(Config::merge())),
+	];
+	let by_timestamp: [(&'static str, Option<u64>, Option<Config>); 4] = [
+		("shanghai", chain.shanghai_time, Some(Config::shanghai())),
+		("cancun", chain.cancun_time, Some(Config::cancun())),
+		("prague", chain.prague_time, None),
+		("osaka", chain.osaka_time, None),
+	];
+
+	let mut schedule = ForkSchedule::new(Config::frontier());
+
+	let first_supported = by_block
+		.iter()
+		.filter(|(_, _, config)| config.is_some())
+		.find_map(|(_, block, _)| *block);
+
+	let mut last = 0;
+	for (name, block, config) in by_block {
+		if let Some(block) = block {
+			if block < last {
+				return Err(ChainSpecError::UnorderedFork(name));
+			}
+			last = block;
+			match config {
+				Some(config) => schedule.push(ForkActivation::Block(block), config),
+				None => {
+					if first_supported.map_or(true, |first| block < first) {
+						return Err(ChainSpecError::UnsupportedFork(name));
+					}
+				}
+			}
+		}
+	}
+
+	let mut last = 0;
+	for (name, timestamp, config) in by_timestamp {
+		if let Some(timestamp) = timestamp {
+			if timestamp < last {
+				return Err(ChainSpecError::UnorderedFork(name));
+			}
+			last = timestamp;
+			match config {
+				Some(config) => schedule.push(ForkActivation::Timestamp(timestamp), config),
+				None => return Err(ChainSpecError::UnsupportedFork(name)),
+			}
+		}
+	}
+
+	Ok(schedule)
+}
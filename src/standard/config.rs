@@ -1,5 +1,11 @@
+use alloc::{string::String, sync::Arc};
+use core::{fmt, ops::Deref};
+
+use primitive_types::{U256, U512};
+
 /// Runtime configuration.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Config {
 	/// Gas paid for extcode.
 	pub gas_ext_code: u64,
@@ -61,6 +67,14 @@ pub struct Config {
 	pub err_on_call_with_more_gas: bool,
 	/// Take l64 for callcreate after gas.
 	pub call_l64_after_gas: bool,
+	/// Divisor of the gas retained by the caller of a call or create when
+	/// `call_l64_after_gas` is set: all but `1/divisor` of the available gas
+	/// can be given (see EIP-150).
+	#[cfg_attr(
+		feature = "serde",
+		serde(default = "default_call_gas_retention_divisor")
+	)]
+	pub call_gas_retention_divisor: u64,
 	/// Whether empty account is considered exists.
 	pub empty_considered_exists: bool,
 	/// Whether create transactions and create opcode increases nonce by one.
@@ -77,6 +91,13 @@ pub struct Config {
 	pub max_initcode_size: Option<usize>,
 	/// Call stipend.
 	pub call_stipend: u64,
+	/// `BLOB_BASE_FEE_UPDATE_FRACTION`, deriving the blob base fee from the
+	/// excess blob gas (see EIP-4844 and EIP-7691).
+	pub blob_base_fee_update_fraction: u64,
+	/// `MAX_BLOBS_PER_TX`, the maximum number of blobs of a transaction (see
+	/// EIP-4844, EIP-7691 and EIP-7594).
+	#[cfg_attr(feature = "serde", serde(default = "default_max_blobs_per_tx"))]
+	pub max_blobs_per_tx: usize,
 	/// Has delegate call.
 	pub has_delegate_call: bool,
 	/// Has create2.
@@ -103,6 +124,176 @@ pub struct Config {
 	pub eip_5656_enabled: bool,
 	/// Uses EIP-1559 (Base fee is burned when this flag is enabled) [EIP-1559](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-1559.md)
 	pub eip_1559_enabled: bool,
+	/// Only allows SELFDESTRUCT to delete accounts created in the same transaction. See [EIP-6780](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-6780.md)
+	pub eip_6780_enabled: bool,
+	/// Enables CLZ instruction. See [EIP-7939](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-7939.md)
+	pub eip_7939_enabled: bool,
+	/// Enables AUTH and AUTHCALL instructions, requires the `eip3074` feature. See [EIP-3074](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-3074.md)
+	pub eip_3074_enabled: bool,
+	/// Enables blob transactions, BLOBHASH and BLOBBASEFEE. See [EIP-4844](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-4844.md)
+	pub eip_4844_enabled: bool,
+	/// Accepts senders whose code is a delegation, and charges authorizations
+	/// in [crate::standard::intrinsic_gas]. Authorization lists are not
+	/// applied, so set code transactions are not supported, and no preset
+	/// enables it. See [EIP-7702](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-7702.md)
+	pub eip_7702_enabled: bool,
+	/// Enforces a floor on the gas used by calldata heavy transactions. See [EIP-7623](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-7623.md)
+	pub eip_7623_enabled: bool,
+	/// Rejects transactions whose sender has code, other than a delegation of EIP-7702. See [EIP-3607](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-3607.md)
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub eip_3607_enabled: bool,
+	/// Gas schedule of opcodes with static costs, and of the other constants
+	/// that no hard fork changed.
+	#[cfg_attr(feature = "serde", serde(default = "GasSchedule::ethereum"))]
+	pub gas_schedule: GasSchedule,
+}
+
+#[cfg(feature = "serde")]
+const fn default_call_gas_retention_divisor() -> u64 {
+	64
+}
+
+#[cfg(feature = "serde")]
+const fn default_max_blobs_per_tx() -> usize {
+	6
+}
+
+/// Gas schedule of the standard gasometer, for chains with modified opcode
+/// pricing.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasSchedule {
+	/// Gas of the zero tier, like `STOP` and `RETURN`.
+	pub gas_zero: u64,
+	/// Gas of the base tier, like `ADDRESS` and `POP`.
+	pub gas_base: u64,
+	/// Gas of the very low tier, like `ADD` and `PUSH1`. Also the base of the
+	/// copy opcodes.
+	pub gas_verylow: u64,
+	/// Gas of the low tier, like `MUL`.
+	pub gas_low: u64,
+	/// Gas of the mid tier, like `ADDMOD` and `JUMP`.
+	pub gas_mid: u64,
+	/// Gas of the high tier, like `JUMPI`.
+	pub gas_high: u64,
+	/// Gas of `JUMPDEST`.
+	pub gas_jumpdest: u64,
+	/// Gas paid for CREATE opcode.
+	pub gas_create: u64,
+	/// Gas paid for a value transfer of CALL opcode.
+	pub gas_call_value: u64,
+	/// Gas paid for CALL opcode when it hits a new account.
+	pub gas_new_account: u64,
+	/// Gas paid per byte of deployed contract code.
+	pub gas_code_deposit: u64,
+	/// Gas paid for EXP opcode.
+	pub gas_exp: u64,
+	/// Gas paid per word of memory expansion.
+	pub gas_memory: u64,
+	/// Gas paid for LOG opcode.
+	pub gas_log: u64,
+	/// Gas paid for LOG opcode for every byte of data.
+	pub gas_log_data: u64,
+	/// Gas paid for LOG opcode for every topic.
+	pub gas_log_topic: u64,
+	/// Gas paid for SHA3 opcode.
+	pub gas_sha3: u64,
+	/// Gas paid for SHA3 opcode for every word of input.
+	pub gas_sha3_word: u64,
+	/// Gas paid for copy opcodes for every word copied.
+	pub gas_copy: u64,
+	/// Gas paid for BLOCKHASH opcode.
+	pub gas_blockhash: u64,
+	/// Gas refunded for SUICIDE opcode, before EIP-3529.
+	pub refund_suicide: i64,
+	/// `TOTAL_COST_FLOOR_PER_TOKEN` (see EIP-7623).
+	pub gas_total_cost_floor_per_token: u64,
+	/// `STANDARD_TOKEN_COST` multiplier of non-zero calldata bytes (see
+	/// EIP-7623).
+	pub standard_tokens_per_non_zero_byte: u64,
+	/// `PER_EMPTY_ACCOUNT_COST` of each authorization (see EIP-7702).
+	pub gas_per_empty_account: u64,
+	/// Gas of `AUTH`, on top of the address access cost (see EIP-3074).
+	pub gas_auth: u64,
+	/// Static gas of individual opcodes, overriding their tier. Only opcodes
+	/// with a static cost can be overridden, and `None` entries keep the
+	/// tier cost. Shared between clones of the schedule, and serialized as a
+	/// list of 256 entries.
+	#[cfg_attr(feature = "serde", serde(default, with = "opcode_costs_serde"))]
+	pub opcode_costs: Option<Arc<[Option<u64>; 256]>>,
+}
+
+impl GasSchedule {
+	/// Gas schedule of Ethereum, which all hard forks share.
+	pub const fn ethereum() -> Self {
+		Self {
+			gas_zero: 0,
+			gas_base: 2,
+			gas_verylow: 3,
+			gas_low: 5,
+			gas_mid: 8,
+			gas_high: 10,
+			gas_jumpdest: 1,
+			gas_create: 32000,
+			gas_call_value: 9000,
+			gas_new_account: 25000,
+			gas_code_deposit: 200,
+			gas_exp: 10,
+			gas_memory: 3,
+			gas_log: 375,
+			gas_log_data: 8,
+			gas_log_topic: 375,
+			gas_sha3: 30,
+			gas_sha3_word: 6,
+			gas_copy: 3,
+			gas_blockhash: 20,
+			refund_suicide: 24000,
+			gas_total_cost_floor_per_token: 10,
+			standard_tokens_per_non_zero_byte: 4,
+			gas_per_empty_account: 25000,
+			gas_auth: 3100,
+			opcode_costs: None,
+		}
+	}
+}
+
+impl Default for GasSchedule {
+	fn default() -> Self {
+		Self::ethereum()
+	}
+}
+
+/// Serde of [GasSchedule::opcode_costs], which does not derive it for arrays
+/// of more than 32 entries.
+#[cfg(feature = "serde")]
+mod opcode_costs_serde {
+	use alloc::{sync::Arc, vec::Vec};
+
+	use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+	type OpcodeCosts = Option<Arc<[Option<u64>; 256]>>;
+
+	pub fn serialize<S: Serializer>(costs: &OpcodeCosts, serializer: S) -> Result<S::Ok, S::Error> {
+		costs.as_ref().map(|costs| &costs[..]).serialize(serializer)
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(
+		deserializer: D,
+	) -> Result<OpcodeCosts, D::Error> {
+		match Option::<Vec<Option<u64>>>::deserialize(deserializer)? {
+			Some(costs) => {
+				let len = costs.len();
+				let costs = <[Option<u64>; 256]>::try_from(costs)
+					.map_err(|_| D::Error::invalid_length(len, &"256 opcode costs"))?;
+				Ok(Some(Arc::new(costs)))
+			}
+			None => Ok(None),
+		}
+	}
 }
 
 impl Config {
@@ -140,12 +331,15 @@ impl Config {
 			empty_considered_exists: true,
 			create_increase_nonce: false,
 			call_l64_after_gas: false,
+			call_gas_retention_divisor: 64,
 			stack_limit: 1024,
 			memory_limit: usize::MAX,
 			call_stack_limit: 1024,
 			create_contract_limit: None,
 			max_initcode_size: None,
 			call_stipend: 2300,
+			blob_base_fee_update_fraction: 0,
+			max_blobs_per_tx: 0,
 			has_delegate_call: false,
 			has_create2: false,
 			has_revert: false,
@@ -159,6 +353,14 @@ impl Config {
 			eip_1153_enabled: false,
 			eip_5656_enabled: false,
 			eip_1559_enabled: false,
+			eip_6780_enabled: false,
+			eip_7939_enabled: false,
+			eip_3074_enabled: false,
+			eip_4844_enabled: false,
+			eip_7702_enabled: false,
+			eip_7623_enabled: false,
+			eip_3607_enabled: false,
+			gas_schedule: GasSchedule::ethereum(),
 		}
 	}
 
@@ -196,12 +398,15 @@ impl Config {
 			empty_considered_exists: false,
 			create_increase_nonce: true,
 			call_l64_after_gas: true,
+			call_gas_retention_divisor: 64,
 			stack_limit: 1024,
 			memory_limit: usize::MAX,
 			call_stack_limit: 1024,
 			create_contract_limit: Some(0x6000),
 			max_initcode_size: None,
 			call_stipend: 2300,
+			blob_base_fee_update_fraction: 0,
+			max_blobs_per_tx: 0,
 			has_delegate_call: true,
 			has_create2: true,
 			has_revert: true,
@@ -215,6 +420,14 @@ impl Config {
 			eip_1153_enabled: false,
 			eip_5656_enabled: false,
 			eip_1559_enabled: false,
+			eip_6780_enabled: false,
+			eip_7939_enabled: false,
+			eip_3074_enabled: false,
+			eip_4844_enabled: false,
+			eip_7702_enabled: false,
+			eip_7623_enabled: false,
+			eip_3607_enabled: false,
+			gas_schedule: GasSchedule::ethereum(),
 		}
 	}
 
@@ -243,6 +456,174 @@ impl Config {
 		Self::config_with_derived_values(DerivedConfigInputs::cancun())
 	}
 
+	/// Prague hard fork configuration, limited to the calldata floor of
+	/// EIP-7623. Set code transactions (EIP-7702), the BLS12-381 precompiles
+	/// (EIP-2537) and the historical block hashes contract (EIP-2935) are not
+	/// implemented.
+	pub const fn prague() -> Config {
+		Self::config_with_derived_values(DerivedConfigInputs::prague())
+	}
+
+	/// Osaka hard fork configuration, limited to the rules of
+	/// [Config::prague], the CLZ instruction of EIP-7939 and the blob limit
+	/// per transaction of EIP-7594.
+	pub const fn osaka() -> Config {
+		Self::config_with_derived_values(DerivedConfigInputs::osaka())
+	}
+
+	/// BNB Smart Chain configuration, as of the Haber hard fork. BSC keeps
+	/// the Ethereum gas schedule, and Haber adopts the Cancun opcodes and blob
+	/// transactions with the blob parameters of Ethereum
+	/// ([BEP-336](https://github.com/bnb-chain/BEPs/blob/master/BEPs/BEP-336.md)).
+	pub const fn bsc() -> Config {
+		Self::cancun()
+	}
+
+	/// Polygon PoS configuration, as of the Ahmedabad hard fork. Napoli adopts
+	/// the Cancun opcodes, without blob transactions
+	/// ([PIP-33](https://github.com/maticnetwork/Polygon-Improvement-Proposals/blob/main/PIPs/PIP-33.md)),
+	/// and Ahmedabad raises the code size limit to 32 KiB and the init code
+	/// size limit to 64 KiB
+	/// ([PIP-30](https://github.com/maticnetwork/Polygon-Improvement-Proposals/blob/main/PIPs/PIP-30.md)).
+	pub const fn polygon() -> Config {
+		let mut config = Self::cancun();
+		config.eip_4844_enabled = false;
+		config.create_contract_limit = Some(0x8000);
+		config.max_initcode_size = Some(0x10000);
+		config
+	}
+
+	/// Names of the hard forks accepted by [Config::from_fork_name].
+	pub const FORK_NAMES: [&'static str; 7] = [
+		"frontier", "istanbul", "berlin", "london", "merge", "shanghai", "cancun",
+	];
+
+	/// Hard fork configuration, by case-insensitive name. `paris` is accepted
+	/// for the Merge. Prague and Osaka are not, as their presets are
+	/// incomplete: use [Config::prague] and [Config::osaka] explicitly.
+	pub fn from_fork_name(name: &str) -> Option<Config> {
+		match name.to_ascii_lowercase().as_str() {
+			"frontier" => Some(Self::frontier()),
+			"istanbul" => Some(Self::istanbul()),
+			"berlin" => Some(Self::berlin()),
+			"london" => Some(Self::london()),
+			"merge" | "paris" => Some(Self::merge()),
+			"shanghai" => Some(Self::shanghai()),
+			"cancun" => Some(Self::cancun()),
+			_ => None,
+		}
+	}
+
+	/// Check that the config does not combine flags and costs in a way that
+	/// no hard fork does, and that would make execution misbehave.
+	pub fn validate(&self) -> Result<(), ConfigError> {
+		let requires = |enabled: bool, flag, required: bool, requires| {
+			if enabled && !required {
+				Err(ConfigError::MissingDependency { flag, requires })
+			} else {
+				Ok(())
+			}
+		};
+
+		requires(
+			self.has_base_fee,
+			"has_base_fee",
+			self.eip_1559_enabled,
+			"eip_1559_enabled",
+		)?;
+		requires(
+			self.eip_4844_enabled,
+			"eip_4844_enabled",
+			self.has_base_fee,
+			"has_base_fee",
+		)?;
+		requires(
+			self.warm_coinbase_address,
+			"warm_coinbase_address",
+			self.increase_state_access_gas,
+			"increase_state_access_gas",
+		)?;
+		requires(
+			self.decrease_clears_refund,
+			"decrease_clears_refund",
+			self.increase_state_access_gas,
+			"increase_state_access_gas",
+		)?;
+		requires(
+			self.eip_7702_enabled,
+			"eip_7702_enabled",
+			self.increase_state_access_gas,
+			"increase_state_access_gas",
+		)?;
+
+		for (cost, value) in [
+			("gas_account_access_cold", self.gas_account_access_cold),
+			("gas_storage_read_warm", self.gas_storage_read_warm),
+			("gas_sload_cold", self.gas_sload_cold),
+			("gas_access_list_address", self.gas_access_list_address),
+			(
+				"gas_access_list_storage_key",
+				self.gas_access_list_storage_key,
+			),
+		] {
+			if value != 0 && !self.increase_state_access_gas {
+				return Err(ConfigError::UnusedCost {
+					cost,
+					requires: "increase_state_access_gas",
+				});
+			}
+		}
+
+		if self.max_refund_quotient == 0 {
+			return Err(ConfigError::InvalidValue("max_refund_quotient"));
+		}
+		if self.call_gas_retention_divisor == 0 {
+			return Err(ConfigError::InvalidValue("call_gas_retention_divisor"));
+		}
+		if self.eip_4844_enabled && self.max_blobs_per_tx == 0 {
+			return Err(ConfigError::InvalidValue("max_blobs_per_tx"));
+		}
+
+		if self.eip_3074_enabled && !cfg!(feature = "eip3074") {
+			return Err(ConfigError::FeatureDisabled {
+				flag: "eip_3074_enabled",
+				feature: "eip3074",
+			});
+		}
+
+		Ok(())
+	}
+
+	/// Blob base fee of a block with the given excess blob gas, as
+	/// `fake_exponential(MIN_BASE_FEE_PER_BLOB_GAS, excess_blob_gas,
+	/// BLOB_BASE_FEE_UPDATE_FRACTION)` of EIP-4844. Saturates at
+	/// `U256::MAX`.
+	pub fn blob_base_fee(&self, excess_blob_gas: U256) -> U256 {
+		if self.blob_base_fee_update_fraction == 0 {
+			return U256::one();
+		}
+
+		let denominator = U512::from(self.blob_base_fee_update_fraction);
+		let numerator = U512::from(excess_blob_gas);
+		let max_output = U512::from(U256::MAX) * denominator;
+		let mut output = U512::zero();
+		let mut accum = denominator;
+		let mut i = U512::one();
+		while !accum.is_zero() {
+			output += accum;
+			if output > max_output {
+				return U256::MAX;
+			}
+			accum = match accum.checked_mul(numerator) {
+				Some(product) => product / (denominator * i),
+				None => return U256::MAX,
+			};
+			i += U512::one();
+		}
+
+		U256::try_from(output / denominator).unwrap_or(U256::MAX)
+	}
+
 	const fn config_with_derived_values(inputs: DerivedConfigInputs) -> Config {
 		let DerivedConfigInputs {
 			gas_storage_read_warm,
@@ -254,9 +635,18 @@ impl Config {
 			disallow_executable_format,
 			warm_coinbase_address,
 			max_initcode_size,
+			blob_base_fee_update_fraction,
+			max_blobs_per_tx,
 			eip_1153_enabled,
 			eip_5656_enabled,
 			eip_1559_enabled,
+			eip_6780_enabled,
+			eip_7939_enabled,
+			eip_3074_enabled,
+			eip_4844_enabled,
+			eip_7702_enabled,
+			eip_7623_enabled,
+			eip_3607_enabled,
 		} = inputs;
 
 		// See https://eips.ethereum.org/EIPS/eip-2929
@@ -303,12 +693,15 @@ impl Config {
 			empty_considered_exists: false,
 			create_increase_nonce: true,
 			call_l64_after_gas: true,
+			call_gas_retention_divisor: 64,
 			stack_limit: 1024,
 			memory_limit: usize::MAX,
 			call_stack_limit: 1024,
 			create_contract_limit: Some(0x6000),
 			max_initcode_size,
 			call_stipend: 2300,
+			blob_base_fee_update_fraction,
+			max_blobs_per_tx,
 			has_delegate_call: true,
 			has_create2: true,
 			has_revert: true,
@@ -322,6 +715,65 @@ impl Config {
 			eip_1153_enabled,
 			eip_5656_enabled,
 			eip_1559_enabled,
+			eip_6780_enabled,
+			eip_7939_enabled,
+			eip_3074_enabled,
+			eip_4844_enabled,
+			eip_7702_enabled,
+			eip_7623_enabled,
+			eip_3607_enabled,
+			gas_schedule: GasSchedule::ethereum(),
+		}
+	}
+}
+
+/// Error of [Config::validate].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+	/// A flag is enabled without another one it builds on.
+	MissingDependency {
+		/// The enabled flag.
+		flag: &'static str,
+		/// The flag it requires.
+		requires: &'static str,
+	},
+	/// A cost is set, but the flag that charges it is disabled.
+	UnusedCost {
+		/// The cost.
+		cost: &'static str,
+		/// The flag that charges it.
+		requires: &'static str,
+	},
+	/// A value that is never valid.
+	InvalidValue(&'static str),
+	/// No field with this name, returned by [DynConfig].
+	UnknownField(String),
+	/// A flag is enabled, but the crate feature implementing it is not.
+	FeatureDisabled {
+		/// The enabled flag.
+		flag: &'static str,
+		/// The crate feature.
+		feature: &'static str,
+	},
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConfigError {}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::MissingDependency { flag, requires } => {
+				write!(f, "{} is enabled but {} is not", flag, requires)
+			}
+			Self::UnusedCost { cost, requires } => {
+				write!(f, "{} is set but {} is not enabled", cost, requires)
+			}
+			Self::InvalidValue(name) => write!(f, "invalid value of {}", name),
+			Self::UnknownField(name) => write!(f, "unknown config field {}", name),
+			Self::FeatureDisabled { flag, feature } => {
+				write!(f, "{} requires the {} feature", flag, feature)
+			}
 		}
 	}
 }
@@ -341,9 +793,18 @@ struct DerivedConfigInputs {
 	disallow_executable_format: bool,
 	warm_coinbase_address: bool,
 	max_initcode_size: Option<usize>,
+	blob_base_fee_update_fraction: u64,
+	max_blobs_per_tx: usize,
 	eip_1153_enabled: bool,
 	eip_5656_enabled: bool,
 	eip_1559_enabled: bool,
+	eip_6780_enabled: bool,
+	eip_7939_enabled: bool,
+	eip_3074_enabled: bool,
+	eip_4844_enabled: bool,
+	eip_7702_enabled: bool,
+	eip_7623_enabled: bool,
+	eip_3607_enabled: bool,
 }
 
 impl DerivedConfigInputs {
@@ -358,9 +819,18 @@ impl DerivedConfigInputs {
 			disallow_executable_format: false,
 			warm_coinbase_address: false,
 			max_initcode_size: None,
+			blob_base_fee_update_fraction: 0,
+			max_blobs_per_tx: 0,
 			eip_1153_enabled: false,
 			eip_5656_enabled: false,
 			eip_1559_enabled: false,
+			eip_6780_enabled: false,
+			eip_7939_enabled: false,
+			eip_3074_enabled: false,
+			eip_4844_enabled: false,
+			eip_7702_enabled: false,
+			eip_7623_enabled: false,
+			eip_3607_enabled: false,
 		}
 	}
 
@@ -375,9 +845,18 @@ impl DerivedConfigInputs {
 			disallow_executable_format: true,
 			warm_coinbase_address: false,
 			max_initcode_size: None,
+			blob_base_fee_update_fraction: 0,
+			max_blobs_per_tx: 0,
 			eip_1153_enabled: false,
 			eip_5656_enabled: false,
 			eip_1559_enabled: true,
+			eip_6780_enabled: false,
+			eip_7939_enabled: false,
+			eip_3074_enabled: false,
+			eip_4844_enabled: false,
+			eip_7702_enabled: false,
+			eip_7623_enabled: false,
+			eip_3607_enabled: true,
 		}
 	}
 
@@ -392,9 +871,18 @@ impl DerivedConfigInputs {
 			disallow_executable_format: true,
 			warm_coinbase_address: false,
 			max_initcode_size: None,
+			blob_base_fee_update_fraction: 0,
+			max_blobs_per_tx: 0,
 			eip_1153_enabled: false,
 			eip_5656_enabled: false,
 			eip_1559_enabled: true,
+			eip_6780_enabled: false,
+			eip_7939_enabled: false,
+			eip_3074_enabled: false,
+			eip_4844_enabled: false,
+			eip_7702_enabled: false,
+			eip_7623_enabled: false,
+			eip_3607_enabled: true,
 		}
 	}
 
@@ -410,9 +898,18 @@ impl DerivedConfigInputs {
 			warm_coinbase_address: true,
 			// 2 * 24576 as per EIP-3860
 			max_initcode_size: Some(0xC000),
+			blob_base_fee_update_fraction: 0,
+			max_blobs_per_tx: 0,
 			eip_1153_enabled: false,
 			eip_5656_enabled: false,
 			eip_1559_enabled: true,
+			eip_6780_enabled: false,
+			eip_7939_enabled: false,
+			eip_3074_enabled: false,
+			eip_4844_enabled: false,
+			eip_7702_enabled: false,
+			eip_7623_enabled: false,
+			eip_3607_enabled: true,
 		}
 	}
 
@@ -428,9 +925,394 @@ impl DerivedConfigInputs {
 			warm_coinbase_address: true,
 			// 2 * (MAX_CODE_SIZE = `24576`) = (0xC000 = 49152) as per EIP-3860
 			max_initcode_size: Some(0xC000),
+			blob_base_fee_update_fraction: 3338477,
+			max_blobs_per_tx: 6,
+			eip_1153_enabled: true,
+			eip_5656_enabled: true,
+			eip_1559_enabled: true,
+			eip_6780_enabled: true,
+			eip_7939_enabled: false,
+			eip_3074_enabled: false,
+			eip_4844_enabled: true,
+			eip_7702_enabled: false,
+			eip_7623_enabled: false,
+			eip_3607_enabled: true,
+		}
+	}
+
+	const fn prague() -> Self {
+		Self {
+			gas_storage_read_warm: 100,
+			gas_sload_cold: 2100,
+			gas_access_list_storage_key: 1900,
+			decrease_clears_refund: true,
+			has_base_fee: true,
+			has_push0: true,
+			disallow_executable_format: true,
+			warm_coinbase_address: true,
+			// 2 * (MAX_CODE_SIZE = `24576`) = (0xC000 = 49152) as per EIP-3860
+			max_initcode_size: Some(0xC000),
+			blob_base_fee_update_fraction: 5007716,
+			// Raised with the blob target and maximum per block by EIP-7691.
+			max_blobs_per_tx: 9,
 			eip_1153_enabled: true,
 			eip_5656_enabled: true,
 			eip_1559_enabled: true,
+			eip_6780_enabled: true,
+			eip_7939_enabled: false,
+			eip_3074_enabled: false,
+			eip_4844_enabled: true,
+			eip_7702_enabled: false,
+			eip_7623_enabled: true,
+			eip_3607_enabled: true,
 		}
 	}
+
+	const fn osaka() -> Self {
+		Self {
+			gas_storage_read_warm: 100,
+			gas_sload_cold: 2100,
+			gas_access_list_storage_key: 1900,
+			decrease_clears_refund: true,
+			has_base_fee: true,
+			has_push0: true,
+			disallow_executable_format: true,
+			warm_coinbase_address: true,
+			// 2 * (MAX_CODE_SIZE = `24576`) = (0xC000 = 49152) as per EIP-3860
+			max_initcode_size: Some(0xC000),
+			blob_base_fee_update_fraction: 5007716,
+			// Capped per transaction by EIP-7594.
+			max_blobs_per_tx: 6,
+			eip_1153_enabled: true,
+			eip_5656_enabled: true,
+			eip_1559_enabled: true,
+			eip_6780_enabled: true,
+			eip_7939_enabled: true,
+			eip_3074_enabled: false,
+			eip_4844_enabled: true,
+			eip_7702_enabled: false,
+			eip_7623_enabled: true,
+			eip_3607_enabled: true,
+		}
+	}
+}
+
+macro_rules! builder_setters {
+	( $( $name:ident: $ty:ty, )* ) => {
+		$(
+			#[doc = concat!("Set `", stringify!($name), "`.")]
+			#[must_use]
+			pub const fn $name(mut self, value: $ty) -> Self {
+				self.config.$name = value;
+				self
+			}
+		)*
+	};
+}
+
+/// Builder of [Config], starting from a hard fork preset and overriding
+/// individual values.
+///
+/// ```
+/// use evm::standard::{Config, ConfigBuilder};
+///
+/// let config = ConfigBuilder::new(Config::cancun())
+///     .eip_7939_enabled(true)
+///     .call_stack_limit(512)
+///     .build();
+/// assert!(config.eip_7939_enabled);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConfigBuilder {
+	config: Config,
+}
+
+impl ConfigBuilder {
+	/// Start building from the given base config.
+	pub const fn new(base: Config) -> Self {
+		Self { config: base }
+	}
+
+	/// Finish building.
+	pub fn build(self) -> Config {
+		self.config
+	}
+
+	/// Set `gas_schedule`.
+	#[must_use]
+	pub fn gas_schedule(mut self, value: GasSchedule) -> Self {
+		self.config.gas_schedule = value;
+		self
+	}
+
+	builder_setters! {
+		gas_ext_code: u64,
+		gas_ext_code_hash: u64,
+		gas_sstore_set: u64,
+		gas_sstore_reset: u64,
+		refund_sstore_clears: i64,
+		max_refund_quotient: u64,
+		gas_balance: u64,
+		gas_sload: u64,
+		gas_sload_cold: u64,
+		gas_suicide: u64,
+		gas_suicide_new_account: u64,
+		gas_call: u64,
+		gas_expbyte: u64,
+		gas_transaction_create: u64,
+		gas_transaction_call: u64,
+		gas_transaction_zero_data: u64,
+		gas_transaction_non_zero_data: u64,
+		gas_access_list_address: u64,
+		gas_access_list_storage_key: u64,
+		gas_account_access_cold: u64,
+		gas_storage_read_warm: u64,
+		sstore_gas_metering: bool,
+		sstore_revert_under_stipend: bool,
+		increase_state_access_gas: bool,
+		decrease_clears_refund: bool,
+		disallow_executable_format: bool,
+		warm_coinbase_address: bool,
+		err_on_call_with_more_gas: bool,
+		call_l64_after_gas: bool,
+		call_gas_retention_divisor: u64,
+		empty_considered_exists: bool,
+		create_increase_nonce: bool,
+		stack_limit: usize,
+		memory_limit: usize,
+		call_stack_limit: usize,
+		create_contract_limit: Option<usize>,
+		max_initcode_size: Option<usize>,
+		call_stipend: u64,
+		blob_base_fee_update_fraction: u64,
+		max_blobs_per_tx: usize,
+		has_delegate_call: bool,
+		has_create2: bool,
+		has_revert: bool,
+		has_return_data: bool,
+		has_bitwise_shifting: bool,
+		has_chain_id: bool,
+		has_self_balance: bool,
+		has_ext_code_hash: bool,
+		has_base_fee: bool,
+		has_push0: bool,
+		eip_1153_enabled: bool,
+		eip_5656_enabled: bool,
+		eip_1559_enabled: bool,
+		eip_6780_enabled: bool,
+		eip_7939_enabled: bool,
+		eip_3074_enabled: bool,
+		eip_4844_enabled: bool,
+		eip_7702_enabled: bool,
+		eip_7623_enabled: bool,
+		eip_3607_enabled: bool,
+	}
+}
+
+impl From<Config> for ConfigBuilder {
+	fn from(config: Config) -> Self {
+		Self::new(config)
+	}
+}
+
+macro_rules! dyn_fields {
+	(
+		$get:ident, $set:ident, $ty:ty, $what:literal,
+		[ $( $name:ident, )* ]
+		$( , gas_schedule [ $( $schedule_name:ident, )* ] )?
+	) => {
+		#[doc = concat!("Current value of the ", $what, " `name`, or `None` if there is no such ", $what, ".")]
+		pub fn $get(&self, name: &str) -> Option<$ty> {
+			match name {
+				$( stringify!($name) => Some(self.config.$name), )*
+				$( $( stringify!($schedule_name) => Some(self.config.gas_schedule.$schedule_name), )* )?
+				_ => None,
+			}
+		}
+
+		#[doc = concat!("Override the ", $what, " `name`.")]
+		pub fn $set(&mut self, name: &str, value: $ty) -> Result<(), ConfigError> {
+			match name {
+				$( stringify!($name) => self.config.$name = value, )*
+				$( $( stringify!($schedule_name) => self.config.gas_schedule.$schedule_name = value, )* )?
+				_ => return Err(ConfigError::UnknownField(name.into())),
+			}
+			Ok(())
+		}
+	};
+}
+
+/// A [Config] that can be adjusted at runtime by field name, for serving many
+/// chains from a single binary.
+///
+/// It starts from a preset, and individual EIP flags and gas constants can be
+/// overridden and reset back to the preset.
+///
+/// ```
+/// use evm::standard::{Config, DynConfig};
+///
+/// let mut config = DynConfig::new(Config::cancun());
+/// config.set_flag("eip_7939_enabled", true).unwrap();
+/// config.set_gas("gas_sstore_set", 10000).unwrap();
+/// assert!(config.eip_7939_enabled);
+/// assert!(config.set_flag("no_such_flag", true).is_err());
+///
+/// config.reset();
+/// assert_eq!(config.gas("gas_sstore_set"), Some(20000));
+/// ```
+#[derive(Clone, Debug)]
+pub struct DynConfig {
+	preset: Config,
+	config: Config,
+}
+
+impl DynConfig {
+	/// Start from the given preset.
+	pub fn new(preset: Config) -> Self {
+		Self {
+			config: preset.clone(),
+			preset,
+		}
+	}
+
+	/// The preset this config started from.
+	pub fn preset(&self) -> &Config {
+		&self.preset
+	}
+
+	/// The config with all overrides applied.
+	pub fn config(&self) -> &Config {
+		&self.config
+	}
+
+	/// Drop all overrides.
+	pub fn reset(&mut self) {
+		self.config = self.preset.clone();
+	}
+
+	dyn_fields!(
+		flag,
+		set_flag,
+		bool,
+		"flag",
+		[
+			sstore_gas_metering,
+			sstore_revert_under_stipend,
+			increase_state_access_gas,
+			decrease_clears_refund,
+			disallow_executable_format,
+			warm_coinbase_address,
+			err_on_call_with_more_gas,
+			call_l64_after_gas,
+			empty_considered_exists,
+			create_increase_nonce,
+			has_delegate_call,
+			has_create2,
+			has_revert,
+			has_return_data,
+			has_bitwise_shifting,
+			has_chain_id,
+			has_self_balance,
+			has_ext_code_hash,
+			has_base_fee,
+			has_push0,
+			eip_1153_enabled,
+			eip_5656_enabled,
+			eip_1559_enabled,
+			eip_6780_enabled,
+			eip_7939_enabled,
+			eip_3074_enabled,
+			eip_4844_enabled,
+			eip_7702_enabled,
+			eip_7623_enabled,
+			eip_3607_enabled,
+		]
+	);
+
+	dyn_fields!(
+		gas,
+		set_gas,
+		u64,
+		"gas constant",
+		[
+			gas_ext_code,
+			gas_ext_code_hash,
+			gas_sstore_set,
+			gas_sstore_reset,
+			max_refund_quotient,
+			gas_balance,
+			gas_sload,
+			gas_sload_cold,
+			gas_suicide,
+			gas_suicide_new_account,
+			gas_call,
+			gas_expbyte,
+			gas_transaction_create,
+			gas_transaction_call,
+			gas_transaction_zero_data,
+			gas_transaction_non_zero_data,
+			gas_access_list_address,
+			gas_access_list_storage_key,
+			gas_account_access_cold,
+			gas_storage_read_warm,
+			call_stipend,
+			call_gas_retention_divisor,
+			blob_base_fee_update_fraction,
+		],
+		gas_schedule [
+			gas_zero,
+			gas_base,
+			gas_verylow,
+			gas_low,
+			gas_mid,
+			gas_high,
+			gas_jumpdest,
+			gas_create,
+			gas_call_value,
+			gas_new_account,
+			gas_code_deposit,
+			gas_exp,
+			gas_memory,
+			gas_log,
+			gas_log_data,
+			gas_log_topic,
+			gas_sha3,
+			gas_sha3_word,
+			gas_copy,
+			gas_blockhash,
+			gas_total_cost_floor_per_token,
+			standard_tokens_per_non_zero_byte,
+			gas_per_empty_account,
+			gas_auth,
+		]
+	);
+
+	dyn_fields!(
+		refund,
+		set_refund,
+		i64,
+		"refund constant",
+		[refund_sstore_clears,],
+		gas_schedule [refund_suicide,]
+	);
+}
+
+impl AsRef<Config> for DynConfig {
+	fn as_ref(&self) -> &Config {
+		&self.config
+	}
+}
+
+impl Deref for DynConfig {
+	type Target = Config;
+
+	fn deref(&self) -> &Config {
+		&self.config
+	}
+}
+
+impl From<Config> for DynConfig {
+	fn from(preset: Config) -> Self {
+		Self::new(preset)
+	}
 }
@@ -0,0 +1,80 @@
+use alloc::{vec, vec::Vec};
+
+use crate::standard::Config;
+
+/// When a hard fork activates.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ForkActivation {
+	/// Activates at the given block number.
+	Block(u64),
+	/// Activates at the given block timestamp.
+	Timestamp(u64),
+}
+
+impl ForkActivation {
+	/// Whether the fork is active for a block with the given number and
+	/// timestamp.
+	pub fn is_active(&self, block_number: u64, timestamp: u64) -> bool {
+		match *self {
+			Self::Block(block) => block_number >= block,
+			Self::Timestamp(time) => timestamp >= time,
+		}
+	}
+}
+
+/// Ordered list of hard forks of a chain.
+///
+/// Block activated forks can be mixed with timestamp activated ones (all forks
+/// since Shanghai), the config of a block is the one of the last fork active
+/// for it.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForkSchedule {
+	forks: Vec<(ForkActivation, Config)>,
+}
+
+impl ForkSchedule {
+	/// Create a new schedule with the config active from genesis.
+	pub fn new(genesis: Config) -> Self {
+		Self {
+			forks: vec![(ForkActivation::Block(0), genesis)],
+		}
+	}
+
+	/// Add a fork after all existing ones. A fork with the same activation as
+	/// the last one replaces it.
+	pub fn push(&mut self, activation: ForkActivation, config: Config) {
+		if self.forks.last().map(|(last, _)| *last) == Some(activation) {
+			self.forks.pop();
+		}
+
+		self.forks.push((activation, config));
+	}
+
+	/// Add a fork after all existing ones, see [ForkSchedule::push].
+	#[must_use]
+	pub fn with_fork(mut self, activation: ForkActivation, config: Config) -> Self {
+		self.push(activation, config);
+		self
+	}
+
+	/// All forks in the schedule, in order.
+	pub fn forks(&self) -> &[(ForkActivation, Config)] {
+		&self.forks
+	}
+
+	/// Config of a block with the given number and timestamp.
+	pub fn config_at(&self, block_number: u64, timestamp: u64) -> &Config {
+		self.forks
+			.iter()
+			.rev()
+			.find(|(activation, _)| activation.is_active(block_number, timestamp))
+			.map(|(_, config)| config)
+			.unwrap_or(&self.forks[0].1)
+	}
+}
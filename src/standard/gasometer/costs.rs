@@ -1,22 +1,22 @@
 use evm_interpreter::error::ExitException;
 use primitive_types::{H256, U256};
 
-use super::{consts::*, utils::log2floor};
+use super::utils::log2floor;
 use crate::standard::Config;
 
-pub fn call_extra_check(gas: U256, after_gas: u64, config: &Config) -> Result<(), ExitException> {
-	if config.err_on_call_with_more_gas && U256::from(after_gas) < gas {
+pub fn call_extra_check(gas: U256, after_gas: U256, config: &Config) -> Result<(), ExitException> {
+	if config.err_on_call_with_more_gas && after_gas < gas {
 		Err(ExitException::OutOfGas)
 	} else {
 		Ok(())
 	}
 }
 
-pub fn suicide_refund(already_removed: bool) -> i64 {
+pub fn suicide_refund(already_removed: bool, config: &Config) -> i64 {
 	if already_removed {
 		0
 	} else {
-		R_SUICIDE
+		config.gas_schedule.refund_suicide
 	}
 }
 
@@ -59,8 +59,8 @@ pub fn sstore_refund(original: H256, current: H256, new: H256, config: &Config)
 	}
 }
 
-pub fn create2_cost(len: U256) -> Result<u64, ExitException> {
-	let base = U256::from(G_CREATE);
+pub fn create2_cost(len: U256, config: &Config) -> Result<u64, ExitException> {
+	let base = U256::from(config.gas_schedule.gas_create);
 	// ceil(len / 32.0)
 	let sha_addup_base = len / U256::from(32)
 		+ if len % U256::from(32) == U256::zero() {
@@ -68,7 +68,7 @@ pub fn create2_cost(len: U256) -> Result<u64, ExitException> {
 		} else {
 			U256::one()
 		};
-	let sha_addup = U256::from(G_SHA3WORD)
+	let sha_addup = U256::from(config.gas_schedule.gas_sha3_word)
 		.checked_mul(sha_addup_base)
 		.ok_or(ExitException::OutOfGas)?;
 	let gas = base.checked_add(sha_addup).ok_or(ExitException::OutOfGas)?;
@@ -82,9 +82,9 @@ pub fn create2_cost(len: U256) -> Result<u64, ExitException> {
 
 pub fn exp_cost(power: U256, config: &Config) -> Result<u64, ExitException> {
 	if power == U256::zero() {
-		Ok(G_EXP)
+		Ok(config.gas_schedule.gas_exp)
 	} else {
-		let gas = U256::from(G_EXP)
+		let gas = U256::from(config.gas_schedule.gas_exp)
 			.checked_add(
 				U256::from(config.gas_expbyte)
 					.checked_mul(U256::from(log2floor(power) / 8 + 1))
@@ -100,13 +100,13 @@ pub fn exp_cost(power: U256, config: &Config) -> Result<u64, ExitException> {
 	}
 }
 
-pub fn verylowcopy_cost(len: U256) -> Result<u64, ExitException> {
+pub fn verylowcopy_cost(len: U256, config: &Config) -> Result<u64, ExitException> {
 	let wordd = len / U256::from(32);
 	let wordr = len % U256::from(32);
 
-	let gas = U256::from(G_VERYLOW)
+	let gas = U256::from(config.gas_schedule.gas_verylow)
 		.checked_add(
-			U256::from(G_COPY)
+			U256::from(config.gas_schedule.gas_copy)
 				.checked_mul(if wordr == U256::zero() {
 					wordd
 				} else {
@@ -128,7 +128,7 @@ pub fn extcodecopy_cost(len: U256, is_cold: bool, config: &Config) -> Result<u64
 	let wordr = len % U256::from(32);
 	let gas = U256::from(address_access_cost(is_cold, config.gas_ext_code, config))
 		.checked_add(
-			U256::from(G_COPY)
+			U256::from(config.gas_schedule.gas_copy)
 				.checked_mul(if wordr == U256::zero() {
 					wordd
 				} else {
@@ -145,15 +145,15 @@ pub fn extcodecopy_cost(len: U256, is_cold: bool, config: &Config) -> Result<u64
 	Ok(gas.as_u64())
 }
 
-pub fn log_cost(n: u8, len: U256) -> Result<u64, ExitException> {
-	let gas = U256::from(G_LOG)
+pub fn log_cost(n: u8, len: U256, config: &Config) -> Result<u64, ExitException> {
+	let gas = U256::from(config.gas_schedule.gas_log)
 		.checked_add(
-			U256::from(G_LOGDATA)
+			U256::from(config.gas_schedule.gas_log_data)
 				.checked_mul(len)
 				.ok_or(ExitException::OutOfGas)?,
 		)
 		.ok_or(ExitException::OutOfGas)?
-		.checked_add(U256::from(G_LOGTOPIC * n as u64))
+		.checked_add(U256::from(config.gas_schedule.gas_log_topic * n as u64))
 		.ok_or(ExitException::OutOfGas)?;
 
 	if gas > U256::from(u64::MAX) {
@@ -163,13 +163,13 @@ pub fn log_cost(n: u8, len: U256) -> Result<u64, ExitException> {
 	Ok(gas.as_u64())
 }
 
-pub fn sha3_cost(len: U256) -> Result<u64, ExitException> {
+pub fn sha3_cost(len: U256, config: &Config) -> Result<u64, ExitException> {
 	let wordd = len / U256::from(32);
 	let wordr = len % U256::from(32);
 
-	let gas = U256::from(G_SHA3)
+	let gas = U256::from(config.gas_schedule.gas_sha3)
 		.checked_add(
-			U256::from(G_SHA3WORD)
+			U256::from(config.gas_schedule.gas_sha3_word)
 				.checked_mul(if wordr == U256::zero() {
 					wordd
 				} else {
@@ -280,7 +280,7 @@ pub fn call_cost(
 ) -> u64 {
 	let transfers_value = value != U256::default();
 	address_access_cost(is_cold, config.gas_call, config)
-		+ xfer_cost(is_call_or_callcode, transfers_value)
+		+ xfer_cost(is_call_or_callcode, transfers_value, config)
 		+ new_cost(is_call_or_staticcall, new_account, transfers_value, config)
 }
 
@@ -296,9 +296,9 @@ pub fn address_access_cost(is_cold: bool, regular_value: u64, config: &Config) -
 	}
 }
 
-fn xfer_cost(is_call_or_callcode: bool, transfers_value: bool) -> u64 {
+fn xfer_cost(is_call_or_callcode: bool, transfers_value: bool, config: &Config) -> u64 {
 	if is_call_or_callcode && transfers_value {
-		G_CALLVALUE
+		config.gas_schedule.gas_call_value
 	} else {
 		0
 	}
@@ -314,12 +314,12 @@ fn new_cost(
 	if is_call_or_staticcall {
 		if eip161 {
 			if transfers_value && new_account {
-				G_NEWACCOUNT
+				config.gas_schedule.gas_new_account
 			} else {
 				0
 			}
 		} else if new_account {
-			G_NEWACCOUNT
+			config.gas_schedule.gas_new_account
 		} else {
 			0
 		}
@@ -328,9 +328,11 @@ fn new_cost(
 	}
 }
 
-pub fn memory_gas(a: usize) -> Result<u64, ExitException> {
+pub fn memory_gas(a: usize, config: &Config) -> Result<u64, ExitException> {
 	let a = a as u64;
-	G_MEMORY
+	config
+		.gas_schedule
+		.gas_memory
 		.checked_mul(a)
 		.ok_or(ExitException::OutOfGas)?
 		.checked_add(a.checked_mul(a).ok_or(ExitException::OutOfGas)? / 512)
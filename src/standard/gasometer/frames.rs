@@ -0,0 +1,53 @@
+use alloc::vec::Vec;
+
+use primitive_types::{H160, U256};
+
+/// Gas of a call frame, as a node of the tree of frames of a transaction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameGas {
+	/// Index of the parent frame, `None` for the transaction frame.
+	pub parent: Option<usize>,
+	/// Depth of the frame, zero for the transaction frame.
+	pub depth: usize,
+	/// Address the frame executes in.
+	pub address: H160,
+	/// Gas given to the frame, including the call stipend.
+	pub gas_limit: U256,
+	/// Gas used by the frame, including its subframes. All of the gas limit
+	/// if the frame failed with an exception.
+	pub gas_used: U256,
+	/// Gas refunds accrued by the frame and its subframes, zero if the frame
+	/// was reverted or failed.
+	pub gas_refunded: U256,
+}
+
+/// Frames of a transaction, in the order they were entered. The transaction
+/// frame is the first one.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasFrames(pub Vec<FrameGas>);
+
+impl GasFrames {
+	/// Indices of the direct subframes of frame `index`.
+	pub fn children(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+		self.0
+			.iter()
+			.enumerate()
+			.filter(move |(_, frame)| frame.parent == Some(index))
+			.map(|(i, _)| i)
+	}
+
+	pub(crate) fn enter(&mut self, parent: Option<usize>, address: H160, gas_limit: U256) -> usize {
+		let depth = parent.map_or(0, |parent| self.0[parent].depth + 1);
+		self.0.push(FrameGas {
+			parent,
+			depth,
+			address,
+			gas_limit,
+			gas_used: U256::zero(),
+			gas_refunded: U256::zero(),
+		});
+		self.0.len() - 1
+	}
+}
@@ -0,0 +1,77 @@
+use core::{
+	fmt::Debug,
+	ops::{Add, Div, Sub},
+};
+
+use primitive_types::U256;
+
+/// Integer type of the gas limit and used gas of a
+/// [crate::standard::GasometerState].
+///
+/// `u64` is enough for all public chains. `U256` allows gas limits beyond
+/// `u64::MAX`, for private chains or zkVM contexts with giant gas budgets.
+/// Individual costs, memory gas and refunds are `u64` in both cases.
+pub trait GasInt:
+	Copy
+	+ Ord
+	+ Default
+	+ Debug
+	+ From<u64>
+	+ Into<U256>
+	+ Add<Output = Self>
+	+ Sub<Output = Self>
+	+ Div<Output = Self>
+{
+	/// Convert from `U256`, if it fits.
+	fn from_u256(value: U256) -> Option<Self>;
+	/// Convert to `u64`, saturating at `u64::MAX`.
+	fn saturating_u64(self) -> u64;
+	/// Checked addition.
+	fn checked_add(self, other: Self) -> Option<Self>;
+	/// Saturating addition.
+	fn saturating_add(self, other: Self) -> Self;
+}
+
+impl GasInt for u64 {
+	fn from_u256(value: U256) -> Option<Self> {
+		if value > U256::from(u64::MAX) {
+			None
+		} else {
+			Some(value.as_u64())
+		}
+	}
+
+	fn saturating_u64(self) -> u64 {
+		self
+	}
+
+	fn checked_add(self, other: Self) -> Option<Self> {
+		u64::checked_add(self, other)
+	}
+
+	fn saturating_add(self, other: Self) -> Self {
+		u64::saturating_add(self, other)
+	}
+}
+
+impl GasInt for U256 {
+	fn from_u256(value: U256) -> Option<Self> {
+		Some(value)
+	}
+
+	fn saturating_u64(self) -> u64 {
+		if self > U256::from(u64::MAX) {
+			u64::MAX
+		} else {
+			self.as_u64()
+		}
+	}
+
+	fn checked_add(self, other: Self) -> Option<Self> {
+		U256::checked_add(self, other)
+	}
+
+	fn saturating_add(self, other: Self) -> Self {
+		U256::saturating_add(self, other)
+	}
+}
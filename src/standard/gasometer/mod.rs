@@ -1,31 +1,86 @@
 mod consts;
 mod costs;
+mod frames;
+mod int;
+mod profile;
+mod refunds;
+pub mod tracing;
 mod utils;
 
+#[cfg(not(feature = "sync"))]
+use alloc::rc::Rc;
 use alloc::vec::Vec;
-use core::cmp::{max, min};
+#[cfg(not(feature = "sync"))]
+use core::cell::RefCell;
+use core::{
+	cmp::{max, min},
+	ops::DerefMut,
+};
+#[cfg(feature = "sync")]
+use std::sync::{Arc, Mutex};
 
 use evm_interpreter::{
 	error::{ExitError, ExitException},
 	etable::Control,
-	machine::{Machine, Stack},
+	machine::{Machine, MemoryStorage, Stack},
 	opcode::Opcode,
 	runtime::{RuntimeBackend, RuntimeState},
 };
 use primitive_types::{H160, H256, U256};
 
+use self::tracing::{Event, GasReason, Snapshot};
+pub use self::{
+	frames::{FrameGas, GasFrames},
+	int::GasInt,
+	profile::{GasProfile, OpcodeGas},
+	refunds::{RefundAudit, RefundEvent},
+};
 use crate::{standard::Config, MergeStrategy};
 
-pub struct GasometerState<'config> {
-	gas_limit: u64,
+/// Gasometer of the standard machine, with gas limit and used gas of type
+/// `G` (see [GasInt]).
+pub struct GasometerState<'config, G = u64> {
+	gas_limit: G,
 	memory_gas: u64,
-	used_gas: u64,
+	used_gas: G,
 	refunded_gas: u64,
+	floor_gas: u64,
+	profile: Option<Recorder<GasProfile>>,
+	frames: Option<Recorder<GasFrames>>,
+	frame: Option<usize>,
+	refund_audit: Option<Recorder<Vec<RefundEvent>>>,
 	pub is_static: bool,
 	pub config: &'config Config,
 }
 
-impl<'config> GasometerState<'config> {
+/// Instrumentation shared by a gasometer and its submeters.
+#[cfg(not(feature = "sync"))]
+type Recorder<T> = Rc<RefCell<T>>;
+/// Instrumentation shared by a gasometer and its submeters.
+#[cfg(feature = "sync")]
+type Recorder<T> = Arc<Mutex<T>>;
+
+#[cfg(not(feature = "sync"))]
+fn recorder<T>(value: T) -> Recorder<T> {
+	Rc::new(RefCell::new(value))
+}
+
+#[cfg(feature = "sync")]
+fn recorder<T>(value: T) -> Recorder<T> {
+	Arc::new(Mutex::new(value))
+}
+
+#[cfg(not(feature = "sync"))]
+fn access<T>(recorder: &Recorder<T>) -> impl DerefMut<Target = T> + '_ {
+	recorder.borrow_mut()
+}
+
+#[cfg(feature = "sync")]
+fn access<T>(recorder: &Recorder<T>) -> impl DerefMut<Target = T> + '_ {
+	recorder.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl<'config, G: GasInt> GasometerState<'config, G> {
 	/// Perform any operation on the gasometer. Set the gasometer to `OutOfGas`
 	/// if the operation fails.
 	#[inline]
@@ -49,55 +104,112 @@ impl<'config> GasometerState<'config> {
 		self.used_gas = self.gas_limit;
 	}
 
+	/// Gas limit.
+	pub fn gas_limit(&self) -> G {
+		self.gas_limit
+	}
+
+	/// Gas used by memory expansion.
+	pub fn memory_gas(&self) -> u64 {
+		self.memory_gas
+	}
+
+	/// Refunds accrued, before the refund quotient is applied.
+	pub fn refunded_gas(&self) -> u64 {
+		self.refunded_gas
+	}
+
 	/// Total used gas. Simply used gas plus memory cost.
-	pub fn total_used_gas(&self) -> u64 {
-		self.used_gas + self.memory_gas
+	pub fn total_used_gas(&self) -> G {
+		self.used_gas + G::from(self.memory_gas)
 	}
 
-	/// Left gas that is supposed to be available to the current interpreter.
+	/// Left gas that is supposed to be available to the current interpreter,
+	/// saturating at `u64::MAX`.
 	pub fn gas64(&self) -> u64 {
-		self.gas_limit - self.memory_gas - self.used_gas
+		self.left_gas().saturating_u64()
 	}
 
 	pub fn gas(&self) -> U256 {
-		self.gas64().into()
+		self.left_gas().into()
+	}
+
+	fn left_gas(&self) -> G {
+		self.gas_limit - G::from(self.memory_gas) - self.used_gas
+	}
+
+	fn snapshot(&self) -> Snapshot {
+		Snapshot {
+			gas_limit: self.gas_limit.saturating_u64(),
+			memory_gas: self.memory_gas,
+			used_gas: self.used_gas.saturating_u64(),
+			refunded_gas: self.refunded_gas,
+		}
 	}
 
 	/// Record an explicit cost.
 	pub fn record_gas64(&mut self, cost: u64) -> Result<(), ExitError> {
+		self.record_gas_for(G::from(cost), GasReason::Other)
+	}
+
+	fn record_gas_for(&mut self, cost: G, reason: GasReason) -> Result<(), ExitError> {
 		let all_gas_cost = self.total_used_gas().checked_add(cost);
-		if let Some(all_gas_cost) = all_gas_cost {
+		let ret = if let Some(all_gas_cost) = all_gas_cost {
 			if self.gas_limit < all_gas_cost {
 				Err(ExitException::OutOfGas.into())
 			} else {
-				self.used_gas += cost;
+				self.used_gas = self.used_gas + cost;
 				Ok(())
 			}
 		} else {
 			Err(ExitException::OutOfGas.into())
+		};
+
+		tracing::emit(|| Event::RecordGas {
+			cost: cost.saturating_u64(),
+			reason,
+			out_of_gas: ret.is_err(),
+			snapshot: self.snapshot(),
+		});
+		ret
+	}
+
+	fn record_refund(&mut self, refund: i64, reason: GasReason) {
+		if refund >= 0 {
+			self.refunded_gas += refund as u64;
+		} else {
+			self.refunded_gas = self.refunded_gas.saturating_sub(-refund as u64);
 		}
+
+		tracing::emit(|| Event::RecordRefund {
+			refund,
+			reason,
+			snapshot: self.snapshot(),
+		});
 	}
 
 	pub fn record_gas(&mut self, cost: U256) -> Result<(), ExitError> {
-		if cost > U256::from(u64::MAX) {
-			return Err(ExitException::OutOfGas.into());
-		}
+		let cost = G::from_u256(cost).ok_or(ExitException::OutOfGas)?;
 
-		self.record_gas64(cost.as_u64())
+		self.record_gas_for(cost, GasReason::Other)
 	}
 
 	pub fn record_codedeposit(&mut self, len: usize) -> Result<(), ExitError> {
 		self.perform(|gasometer| {
-			let cost = len as u64 * consts::G_CODEDEPOSIT;
-			gasometer.record_gas64(cost)?;
+			let cost = len as u64 * gasometer.config.gas_schedule.gas_code_deposit;
+			gasometer.record_gas_for(G::from(cost), GasReason::CodeDeposit)?;
 			Ok(())
 		})
 	}
 
 	/// Set memory gas usage.
 	pub fn set_memory_gas(&mut self, memory_cost: u64) -> Result<(), ExitError> {
-		let all_gas_cost = self.used_gas.checked_add(memory_cost);
-		if let Some(all_gas_cost) = all_gas_cost {
+		self.set_memory_gas_for(memory_cost, GasReason::Other)
+	}
+
+	fn set_memory_gas_for(&mut self, memory_cost: u64, reason: GasReason) -> Result<(), ExitError> {
+		let all_gas_cost = self.used_gas.checked_add(G::from(memory_cost));
+		let ret = if let Some(all_gas_cost) = all_gas_cost {
 			if self.gas_limit < all_gas_cost {
 				Err(ExitException::OutOfGas.into())
 			} else {
@@ -106,37 +218,129 @@ impl<'config> GasometerState<'config> {
 			}
 		} else {
 			Err(ExitException::OutOfGas.into())
-		}
+		};
+
+		tracing::emit(|| Event::RecordMemoryGas {
+			memory_gas: memory_cost,
+			reason,
+			out_of_gas: ret.is_err(),
+			snapshot: self.snapshot(),
+		});
+		ret
 	}
 
 	/// Create a new gasometer with the given gas limit and chain config.
-	pub fn new(gas_limit: u64, is_static: bool, config: &'config Config) -> Self {
+	pub fn new(gas_limit: G, is_static: bool, config: &'config Config) -> Self {
 		Self {
 			gas_limit,
 			memory_gas: 0,
-			used_gas: 0,
+			used_gas: G::default(),
 			refunded_gas: 0,
+			floor_gas: 0,
+			profile: None,
+			frames: None,
+			frame: None,
+			refund_audit: None,
 			is_static,
 			config,
 		}
 	}
 
+	/// Accumulate the gas charged by opcodes into a [GasProfile], shared with
+	/// all submeters created afterwards.
+	pub fn enable_profile(&mut self) {
+		if self.profile.is_none() {
+			self.profile = Some(recorder(GasProfile::default()));
+		}
+	}
+
+	/// Gas profile accumulated so far, if enabled.
+	pub fn profile(&self) -> Option<GasProfile> {
+		self.profile.as_ref().map(|profile| access(profile).clone())
+	}
+
+	/// Record the gas of every frame into [GasFrames], starting with this
+	/// gasometer as the frame executing in `address`. Submeters created
+	/// afterwards are recorded as its subframes.
+	pub fn enable_frames(&mut self, address: H160) {
+		if self.frames.is_none() {
+			let mut frames = GasFrames::default();
+			self.frame = Some(frames.enter(None, address, self.gas_limit.into()));
+			self.frames = Some(recorder(frames));
+		}
+	}
+
+	/// Set the address of the frame of this gasometer, if frames are enabled.
+	pub fn set_frame_address(&mut self, address: H160) {
+		if let (Some(frames), Some(frame)) = (&self.frames, self.frame) {
+			access(frames).0[frame].address = address;
+		}
+	}
+
+	/// Frames recorded so far, if enabled. The frame of this gasometer is
+	/// reported with its current usage.
+	pub fn frames(&self) -> Option<GasFrames> {
+		let mut frames = access(self.frames.as_ref()?).clone();
+		if let Some(frame) = self.frame {
+			frames.0[frame].gas_used = self.total_used_gas().into();
+			frames.0[frame].gas_refunded = self.refunded_gas.into();
+		}
+		Some(frames)
+	}
+
+	/// Record every refund into a [RefundAudit], shared with all submeters
+	/// created afterwards.
+	pub fn enable_refund_audit(&mut self) {
+		if self.refund_audit.is_none() {
+			self.refund_audit = Some(recorder(Vec::new()));
+		}
+	}
+
+	/// Refund audit so far, if enabled, clamped as if this gasometer was
+	/// the transaction one.
+	pub fn refund_audit(&self) -> Option<RefundAudit> {
+		let events = access(self.refund_audit.as_ref()?).clone();
+		let cap =
+			(self.total_used_gas() / G::from(self.config.max_refund_quotient)).saturating_u64();
+		Some(RefundAudit {
+			events,
+			accrued: self.refunded_gas,
+			cap,
+			applied: self.applied_refund(),
+		})
+	}
+
+	fn audit_refund(&self, event: RefundEvent) {
+		if let Some(refund_audit) = &self.refund_audit {
+			access(refund_audit).push(event);
+		}
+	}
+
+	fn record_profile(&self, opcode: Opcode, kind: &'static str, cost: u64, memory: u64) {
+		if let Some(profile) = &self.profile {
+			access(profile).record(opcode, kind, cost, memory);
+		}
+	}
+
+	/// Create the gasometer of a transaction, without charging any intrinsic
+	/// gas.
+	pub fn new_transact(gas_limit: U256, config: &'config Config) -> Result<Self, ExitError> {
+		let gas_limit = G::from_u256(gas_limit).ok_or(ExitException::OutOfGas)?;
+
+		Ok(Self::new(gas_limit, false, config))
+	}
+
 	pub fn new_transact_call(
 		gas_limit: U256,
 		data: &[u8],
 		access_list: &[(H160, Vec<H256>)],
 		config: &'config Config,
 	) -> Result<Self, ExitError> {
-		let gas_limit = if gas_limit > U256::from(u64::MAX) {
-			return Err(ExitException::OutOfGas.into());
-		} else {
-			gas_limit.as_u64()
-		};
-
-		let mut s = Self::new(gas_limit, false, config);
-		let transaction_cost = TransactionCost::call(data, access_list).cost(config);
+		let mut s = Self::new_transact(gas_limit, config)?;
+		let intrinsic = intrinsic_gas(TransactionKind::Call, data, access_list, 0, config);
 
-		s.record_gas64(transaction_cost)?;
+		s.record_gas_for(G::from(intrinsic.used), GasReason::Transaction)?;
+		s.record_floor_gas(intrinsic.floor)?;
 		Ok(s)
 	}
 
@@ -146,28 +350,43 @@ impl<'config> GasometerState<'config> {
 		access_list: &[(H160, Vec<H256>)],
 		config: &'config Config,
 	) -> Result<Self, ExitError> {
-		let gas_limit = if gas_limit > U256::from(u64::MAX) {
+		let mut s = Self::new_transact(gas_limit, config)?;
+		let intrinsic = intrinsic_gas(TransactionKind::Create, code, access_list, 0, config);
+
+		s.record_gas_for(G::from(intrinsic.used), GasReason::Transaction)?;
+		s.record_floor_gas(intrinsic.floor)?;
+		Ok(s)
+	}
+
+	/// Record the minimum gas the transaction is charged (see EIP-7623). The
+	/// gas limit must cover it.
+	fn record_floor_gas(&mut self, floor_gas: u64) -> Result<(), ExitError> {
+		if G::from(floor_gas) > self.gas_limit {
 			return Err(ExitException::OutOfGas.into());
-		} else {
-			gas_limit.as_u64()
-		};
+		}
 
-		let mut s = Self::new(gas_limit, false, config);
-		let transaction_cost = TransactionCost::create(code, access_list).cost(config);
+		self.floor_gas = floor_gas;
+		Ok(())
+	}
 
-		s.record_gas64(transaction_cost)?;
-		Ok(s)
+	/// Refunded gas, capped by the refund quotient of the used gas.
+	pub fn applied_refund(&self) -> u64 {
+		min(
+			self.total_used_gas() / G::from(self.config.max_refund_quotient),
+			G::from(self.refunded_gas),
+		)
+		.saturating_u64()
+	}
+
+	/// Minimum gas charged for the transaction (see EIP-7623).
+	pub fn floor_gas(&self) -> u64 {
+		self.floor_gas
 	}
 
 	pub fn effective_gas(&self) -> U256 {
-		U256::from(
-			self.gas_limit
-				- (self.total_used_gas()
-					- min(
-						self.total_used_gas() / self.config.max_refund_quotient,
-						self.refunded_gas,
-					)),
-		)
+		let used_gas = self.total_used_gas() - G::from(self.applied_refund());
+
+		(self.gas_limit - max(used_gas, G::from(self.floor_gas))).into()
 	}
 
 	pub fn submeter(
@@ -176,44 +395,73 @@ impl<'config> GasometerState<'config> {
 		is_static: bool,
 		call_has_value: bool,
 	) -> Result<Self, ExitError> {
-		let mut gas_limit = if gas_limit > U256::from(u64::MAX) {
-			return Err(ExitException::OutOfGas.into());
-		} else {
-			gas_limit.as_u64()
-		};
+		let mut gas_limit = G::from_u256(gas_limit).ok_or(ExitException::OutOfGas)?;
 
-		self.record_gas64(gas_limit)?;
+		self.record_gas_for(gas_limit, GasReason::Submeter)?;
 
 		if call_has_value {
-			gas_limit = gas_limit.saturating_add(self.config.call_stipend);
+			gas_limit = gas_limit.saturating_add(G::from(self.config.call_stipend));
 		}
 
-		Ok(Self::new(gas_limit, is_static, self.config))
+		let mut submeter = Self::new(gas_limit, is_static, self.config);
+		submeter.profile = self.profile.clone();
+		submeter.refund_audit = self.refund_audit.clone();
+		if let Some(frames) = &self.frames {
+			submeter.frame = Some(access(frames).enter(self.frame, H160::zero(), gas_limit.into()));
+			submeter.frames = Some(frames.clone());
+		}
+		Ok(submeter)
 	}
 
 	pub fn merge(&mut self, other: Self, strategy: MergeStrategy) {
 		match strategy {
 			MergeStrategy::Commit => {
-				self.used_gas -= other.gas64();
+				self.used_gas = self.used_gas - other.left_gas();
 				self.refunded_gas += other.refunded_gas;
 			}
 			MergeStrategy::Revert => {
-				self.used_gas -= other.gas64();
+				self.used_gas = self.used_gas - other.left_gas();
 			}
 			MergeStrategy::Discard => {}
 		}
+
+		if !matches!(strategy, MergeStrategy::Commit) && other.refunded_gas != 0 {
+			self.audit_refund(RefundEvent::Dropped {
+				refund: other.refunded_gas,
+			});
+		}
+
+		if let (Some(frames), Some(frame)) = (&self.frames, other.frame) {
+			let frame = &mut access(frames).0[frame];
+			frame.gas_used = match strategy {
+				MergeStrategy::Discard => other.gas_limit.into(),
+				_ => (other.gas_limit - other.left_gas()).into(),
+			};
+			frame.gas_refunded = match strategy {
+				MergeStrategy::Commit => other.refunded_gas.into(),
+				_ => U256::zero(),
+			};
+		}
+
+		tracing::emit(|| Event::Merge {
+			strategy,
+			gas_left: other.gas64(),
+			refunded_gas: other.refunded_gas,
+			snapshot: self.snapshot(),
+		});
 	}
 }
 
-pub fn eval<'config, S, H, Tr>(
-	machine: &mut Machine<S>,
+pub fn eval<'config, S, H, Tr, G, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	handler: &mut H,
 	opcode: Opcode,
 	position: usize,
 ) -> Control<Tr>
 where
-	S: AsRef<GasometerState<'config>> + AsMut<GasometerState<'config>> + AsRef<RuntimeState>,
+	S: AsRef<GasometerState<'config, G>> + AsMut<GasometerState<'config, G>> + AsRef<RuntimeState>,
 	H: RuntimeBackend,
+	G: GasInt,
 {
 	match eval_to_result(machine, handler, opcode, position) {
 		Ok(()) => Control::Continue,
@@ -221,15 +469,16 @@ where
 	}
 }
 
-fn eval_to_result<'config, S, H>(
-	machine: &mut Machine<S>,
+fn eval_to_result<'config, S, H, G, M: MemoryStorage>(
+	machine: &mut Machine<S, M>,
 	handler: &mut H,
 	opcode: Opcode,
-	_position: usize,
+	position: usize,
 ) -> Result<(), ExitError>
 where
-	S: AsRef<GasometerState<'config>> + AsMut<GasometerState<'config>> + AsRef<RuntimeState>,
+	S: AsRef<GasometerState<'config, G>> + AsMut<GasometerState<'config, G>> + AsRef<RuntimeState>,
 	H: RuntimeBackend,
+	G: GasInt,
 {
 	if machine.code().is_empty() {
 		return Ok(());
@@ -240,8 +489,11 @@ where
 		.address;
 
 	machine.state.as_mut().perform(|gasometer| {
-		if let Some(cost) = consts::STATIC_COST_TABLE[opcode.as_usize()] {
-			gasometer.record_gas64(cost)?;
+		let reason = GasReason::Opcode(opcode);
+
+		if let Some(cost) = consts::static_cost(opcode, &gasometer.config.gas_schedule) {
+			gasometer.record_gas_for(G::from(cost), reason)?;
+			gasometer.record_profile(opcode, "Static", cost, 0);
 		} else {
 			let (gas, memory_gas) = dynamic_opcode_cost(
 				address,
@@ -254,20 +506,30 @@ where
 			let cost = gas.cost(gasometer.gas64(), gasometer.config)?;
 			let refund = gas.refund(gasometer.config);
 
-			gasometer.record_gas64(cost)?;
-			if refund >= 0 {
-				gasometer.refunded_gas += refund as u64;
-			} else {
-				gasometer.refunded_gas = gasometer.refunded_gas.saturating_sub(-refund as u64);
+			gasometer.record_gas_for(G::from(cost), reason)?;
+			if refund != 0 {
+				gasometer.record_refund(refund, reason);
+				gasometer.audit_refund(RefundEvent::Opcode {
+					opcode,
+					position,
+					refund,
+				});
 			}
+			let prev_memory_gas = gasometer.memory_gas;
 			if let Some(memory_gas) = memory_gas {
-				let memory_cost = memory_gas.cost()?;
+				let memory_cost = memory_gas.cost(gasometer.config)?;
 				if let Some(memory_cost) = memory_cost {
-					gasometer.set_memory_gas(max(gasometer.memory_gas, memory_cost))?;
+					gasometer.set_memory_gas_for(max(gasometer.memory_gas, memory_cost), reason)?;
 				}
 			}
+			gasometer.record_profile(
+				opcode,
+				gas.name(),
+				cost,
+				gasometer.memory_gas - prev_memory_gas,
+			);
 
-			let after_gas = gasometer.gas64();
+			let after_gas = gasometer.gas();
 			gas.extra_check(after_gas, gasometer.config)?;
 		}
 
@@ -275,6 +537,58 @@ where
 	})
 }
 
+/// Whether `opcode` is defined under `config`, independent of the static
+/// context. Mirrors the opcodes charged by the gasometer.
+pub(crate) fn opcode_enabled(opcode: Opcode, config: &Config) -> bool {
+	if consts::static_cost(opcode, &config.gas_schedule).is_some() {
+		return true;
+	}
+
+	match opcode {
+		Opcode::RETURN
+		| Opcode::MLOAD
+		| Opcode::MSTORE
+		| Opcode::MSTORE8
+		| Opcode::EXTCODESIZE
+		| Opcode::BALANCE
+		| Opcode::BLOCKHASH
+		| Opcode::CALLCODE
+		| Opcode::STATICCALL
+		| Opcode::SHA3
+		| Opcode::EXTCODECOPY
+		| Opcode::CALLDATACOPY
+		| Opcode::CODECOPY
+		| Opcode::EXP
+		| Opcode::SLOAD
+		| Opcode::SSTORE
+		| Opcode::LOG0
+		| Opcode::LOG1
+		| Opcode::LOG2
+		| Opcode::LOG3
+		| Opcode::LOG4
+		| Opcode::CREATE
+		| Opcode::SUICIDE
+		| Opcode::CALL => true,
+		Opcode::REVERT => config.has_revert,
+		Opcode::CHAINID => config.has_chain_id,
+		Opcode::SHL | Opcode::SHR | Opcode::SAR => config.has_bitwise_shifting,
+		Opcode::CLZ => config.eip_7939_enabled,
+		Opcode::SELFBALANCE => config.has_self_balance,
+		Opcode::BASEFEE => config.has_base_fee,
+		Opcode::BLOBHASH | Opcode::BLOBBASEFEE => config.eip_4844_enabled,
+		Opcode::EXTCODEHASH => config.has_ext_code_hash,
+		Opcode::MCOPY => config.eip_5656_enabled,
+		Opcode::TLOAD | Opcode::TSTORE => config.eip_1153_enabled,
+		Opcode::DELEGATECALL => config.has_delegate_call,
+		Opcode::RETURNDATASIZE | Opcode::RETURNDATACOPY => config.has_return_data,
+		Opcode::CREATE2 => config.has_create2,
+		Opcode::PUSH0 => config.has_push0,
+		#[cfg(feature = "eip3074")]
+		Opcode::AUTH | Opcode::AUTHCALL => config.eip_3074_enabled,
+		_ => false,
+	}
+}
+
 /// Calculate the opcode cost.
 #[allow(clippy::nonminimal_bool)]
 fn dynamic_opcode_cost<H: RuntimeBackend>(
@@ -299,12 +613,19 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 		Opcode::SHL | Opcode::SHR | Opcode::SAR if config.has_bitwise_shifting => GasCost::VeryLow,
 		Opcode::SHL | Opcode::SHR | Opcode::SAR => GasCost::Invalid(opcode),
 
+		Opcode::CLZ if config.eip_7939_enabled => GasCost::Low,
+		Opcode::CLZ => GasCost::Invalid(opcode),
+
 		Opcode::SELFBALANCE if config.has_self_balance => GasCost::Low,
 		Opcode::SELFBALANCE => GasCost::Invalid(opcode),
 
 		Opcode::BASEFEE if config.has_base_fee => GasCost::Base,
 		Opcode::BASEFEE => GasCost::Invalid(opcode),
 
+		Opcode::BLOBHASH if config.eip_4844_enabled => GasCost::VeryLow,
+		Opcode::BLOBBASEFEE if config.eip_4844_enabled => GasCost::Base,
+		Opcode::BLOBHASH | Opcode::BLOBBASEFEE => GasCost::Invalid(opcode),
+
 		Opcode::EXTCODESIZE => {
 			let target = stack.peek(0)?.into();
 
@@ -493,6 +814,35 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 
 		Opcode::PUSH0 if config.has_push0 => GasCost::Base,
 
+		#[cfg(feature = "eip3074")]
+		Opcode::AUTH if config.eip_3074_enabled => {
+			let target = stack.peek(0)?.into();
+
+			// https://eips.ethereum.org/EIPS/eip-2929
+			let target_is_cold = handler.is_cold(target, None);
+			handler.mark_hot(target, None);
+
+			GasCost::Auth { target_is_cold }
+		}
+		#[cfg(feature = "eip3074")]
+		Opcode::AUTHCALL
+			if config.eip_3074_enabled
+				&& (!is_static || U256::from_big_endian(&stack.peek(2)?[..]) == U256::zero()) =>
+		{
+			let target = stack.peek(1)?.into();
+
+			// https://eips.ethereum.org/EIPS/eip-2929
+			let target_is_cold = handler.is_cold(target, None);
+			handler.mark_hot(target, None);
+
+			GasCost::AuthCall {
+				value: U256::from_big_endian(&stack.peek(2)?[..]),
+				gas: U256::from_big_endian(&stack.peek(0)?[..]),
+				target_is_cold,
+				target_exists: { handler.exists(target) },
+			}
+		}
+
 		_ => GasCost::Invalid(opcode),
 	};
 
@@ -544,6 +894,24 @@ fn dynamic_opcode_cost<H: RuntimeBackend>(
 			len: U256::from_big_endian(&stack.peek(2)?[..]),
 		}),
 
+		#[cfg(feature = "eip3074")]
+		Opcode::AUTH => Some(MemoryCost {
+			offset: U256::from_big_endian(&stack.peek(1)?[..]),
+			len: U256::from_big_endian(&stack.peek(2)?[..]),
+		}),
+
+		#[cfg(feature = "eip3074")]
+		Opcode::AUTHCALL => Some(
+			MemoryCost {
+				offset: U256::from_big_endian(&stack.peek(3)?[..]),
+				len: U256::from_big_endian(&stack.peek(4)?[..]),
+			}
+			.join(MemoryCost {
+				offset: U256::from_big_endian(&stack.peek(5)?[..]),
+				len: U256::from_big_endian(&stack.peek(6)?[..]),
+			}),
+		),
+
 		Opcode::CALL | Opcode::CALLCODE => Some(
 			MemoryCost {
 				offset: U256::from_big_endian(&stack.peek(3)?[..]),
@@ -644,6 +1012,24 @@ enum GasCost {
 		/// Whether the target exists.
 		target_exists: bool,
 	},
+	/// Gas cost for `AUTH`.
+	#[cfg(feature = "eip3074")]
+	Auth {
+		/// True if authority has not been previously accessed in this transaction
+		target_is_cold: bool,
+	},
+	/// Gas cost for `AUTHCALL`.
+	#[cfg(feature = "eip3074")]
+	AuthCall {
+		/// Call value.
+		value: U256,
+		/// Call gas.
+		gas: U256,
+		/// True if target has not been previously accessed in this transaction
+		target_is_cold: bool,
+		/// Whether the target exists.
+		target_exists: bool,
+	},
 	/// Gas cost for `SUICIDE`.
 	Suicide {
 		/// Value.
@@ -753,6 +1139,17 @@ impl GasCost {
 				!target_exists,
 				config,
 			),
+			#[cfg(feature = "eip3074")]
+			GasCost::Auth { target_is_cold } => {
+				config.gas_schedule.gas_auth + costs::address_access_cost(target_is_cold, 0, config)
+			}
+			#[cfg(feature = "eip3074")]
+			GasCost::AuthCall {
+				value,
+				target_is_cold,
+				target_exists,
+				..
+			} => costs::call_cost(value, target_is_cold, true, true, !target_exists, config),
 
 			GasCost::Suicide {
 				value,
@@ -768,18 +1165,18 @@ impl GasCost {
 			} => costs::sstore_cost(original, current, new, gas, target_is_cold, config)?,
 			GasCost::TLoad => costs::tload_cost(config)?,
 			GasCost::TStore => costs::tstore_cost(config)?,
-			GasCost::Sha3 { len } => costs::sha3_cost(len)?,
-			GasCost::Log { n, len } => costs::log_cost(n, len)?,
-			GasCost::VeryLowCopy { len } => costs::verylowcopy_cost(len)?,
+			GasCost::Sha3 { len } => costs::sha3_cost(len, config)?,
+			GasCost::Log { n, len } => costs::log_cost(n, len, config)?,
+			GasCost::VeryLowCopy { len } => costs::verylowcopy_cost(len, config)?,
 			GasCost::Exp { power } => costs::exp_cost(power, config)?,
-			GasCost::Create => consts::G_CREATE,
-			GasCost::Create2 { len } => costs::create2_cost(len)?,
+			GasCost::Create => config.gas_schedule.gas_create,
+			GasCost::Create2 { len } => costs::create2_cost(len, config)?,
 			GasCost::SLoad { target_is_cold } => costs::sload_cost(target_is_cold, config),
 
-			GasCost::Zero => consts::G_ZERO,
-			GasCost::Base => consts::G_BASE,
-			GasCost::VeryLow => consts::G_VERYLOW,
-			GasCost::Low => consts::G_LOW,
+			GasCost::Zero => config.gas_schedule.gas_zero,
+			GasCost::Base => config.gas_schedule.gas_base,
+			GasCost::VeryLow => config.gas_schedule.gas_verylow,
+			GasCost::Low => config.gas_schedule.gas_low,
 			GasCost::Invalid(opcode) => return Err(ExitException::InvalidOpcode(opcode).into()),
 
 			GasCost::ExtCodeSize { target_is_cold } => {
@@ -792,13 +1189,48 @@ impl GasCost {
 			GasCost::Balance { target_is_cold } => {
 				costs::address_access_cost(target_is_cold, config.gas_balance, config)
 			}
-			GasCost::BlockHash => consts::G_BLOCKHASH,
+			GasCost::BlockHash => config.gas_schedule.gas_blockhash,
 			GasCost::ExtCodeHash { target_is_cold } => {
 				costs::address_access_cost(target_is_cold, config.gas_ext_code_hash, config)
 			}
 		})
 	}
 
+	/// Name of the cost rule, as counted in [GasProfile::costs].
+	pub fn name(&self) -> &'static str {
+		match *self {
+			GasCost::Zero => "Zero",
+			GasCost::Base => "Base",
+			GasCost::VeryLow => "VeryLow",
+			GasCost::Low => "Low",
+			GasCost::Invalid(_) => "Invalid",
+			GasCost::ExtCodeSize { .. } => "ExtCodeSize",
+			GasCost::Balance { .. } => "Balance",
+			GasCost::BlockHash => "BlockHash",
+			GasCost::ExtCodeHash { .. } => "ExtCodeHash",
+			GasCost::Call { .. } => "Call",
+			GasCost::CallCode { .. } => "CallCode",
+			GasCost::DelegateCall { .. } => "DelegateCall",
+			GasCost::StaticCall { .. } => "StaticCall",
+			#[cfg(feature = "eip3074")]
+			GasCost::Auth { .. } => "Auth",
+			#[cfg(feature = "eip3074")]
+			GasCost::AuthCall { .. } => "AuthCall",
+			GasCost::Suicide { .. } => "Suicide",
+			GasCost::SStore { .. } => "SStore",
+			GasCost::TLoad => "TLoad",
+			GasCost::TStore => "TStore",
+			GasCost::Sha3 { .. } => "Sha3",
+			GasCost::Log { .. } => "Log",
+			GasCost::ExtCodeCopy { .. } => "ExtCodeCopy",
+			GasCost::VeryLowCopy { .. } => "VeryLowCopy",
+			GasCost::Exp { .. } => "Exp",
+			GasCost::Create => "Create",
+			GasCost::Create2 { .. } => "Create2",
+			GasCost::SLoad { .. } => "SLoad",
+		}
+	}
+
 	/// Numeric value for the refund.
 	pub fn refund(&self, config: &Config) -> i64 {
 		match *self {
@@ -810,18 +1242,20 @@ impl GasCost {
 			} => costs::sstore_refund(original, current, new, config),
 			GasCost::Suicide {
 				already_removed, ..
-			} if !config.decrease_clears_refund => costs::suicide_refund(already_removed),
+			} if !config.decrease_clears_refund => costs::suicide_refund(already_removed, config),
 			_ => 0,
 		}
 	}
 
 	/// Extra check of the cost.
-	pub fn extra_check(&self, after_gas: u64, config: &Config) -> Result<(), ExitException> {
+	pub fn extra_check(&self, after_gas: U256, config: &Config) -> Result<(), ExitException> {
 		match *self {
 			GasCost::Call { gas, .. } => costs::call_extra_check(gas, after_gas, config),
 			GasCost::CallCode { gas, .. } => costs::call_extra_check(gas, after_gas, config),
 			GasCost::DelegateCall { gas, .. } => costs::call_extra_check(gas, after_gas, config),
 			GasCost::StaticCall { gas, .. } => costs::call_extra_check(gas, after_gas, config),
+			#[cfg(feature = "eip3074")]
+			GasCost::AuthCall { gas, .. } => costs::call_extra_check(gas, after_gas, config),
 			_ => Ok(()),
 		}
 	}
@@ -858,7 +1292,7 @@ impl MemoryCost {
 	}
 
 	/// Numeric value of the cost.
-	pub fn cost(&self) -> Result<Option<u64>, ExitError> {
+	pub fn cost(&self, config: &Config) -> Result<Option<u64>, ExitError> {
 		let from = self.offset;
 		let len = self.len;
 
@@ -876,7 +1310,63 @@ impl MemoryCost {
 		let rem = end % 32;
 		let new = if rem == 0 { end / 32 } else { end / 32 + 1 };
 
-		Ok(Some(costs::memory_gas(new)?))
+		Ok(Some(costs::memory_gas(new, config)?))
+	}
+}
+
+/// Kind of a transaction, for its intrinsic gas.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransactionKind {
+	/// A call transaction.
+	Call,
+	/// A create transaction.
+	Create,
+}
+
+/// Intrinsic gas of a transaction, charged before any execution.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct IntrinsicGas {
+	/// Gas used by the transaction data, access list, authorizations and, for
+	/// create transactions, init code.
+	pub used: u64,
+	/// Minimum gas charged for the transaction, zero if EIP-7623 is not
+	/// enabled.
+	pub floor: u64,
+}
+
+impl IntrinsicGas {
+	/// Minimum gas limit of a valid transaction.
+	pub fn min_gas_limit(&self) -> u64 {
+		max(self.used, self.floor)
+	}
+}
+
+/// Intrinsic gas of a transaction with `data` (the init code of a create
+/// transaction), `access_list` and `authorization_count` EIP-7702
+/// authorizations, without executing it.
+///
+/// A transaction whose gas limit is below [IntrinsicGas::min_gas_limit] is
+/// invalid, which allows mempools and RPC servers to reject it early.
+pub fn intrinsic_gas(
+	kind: TransactionKind,
+	data: &[u8],
+	access_list: &[(H160, Vec<H256>)],
+	authorization_count: usize,
+	config: &Config,
+) -> IntrinsicGas {
+	let transaction_cost = match kind {
+		TransactionKind::Call => TransactionCost::call(data, access_list),
+		TransactionKind::Create => TransactionCost::create(data, access_list),
+	};
+
+	let mut used = transaction_cost.cost(config);
+	if config.eip_7702_enabled {
+		used += authorization_count as u64 * config.gas_schedule.gas_per_empty_account;
+	}
+
+	IntrinsicGas {
+		used,
+		floor: transaction_cost.floor_cost(config),
 	}
 }
 
@@ -975,6 +1465,30 @@ impl TransactionCost {
 			}
 		}
 	}
+
+	/// Minimum gas charged for the transaction, zero if EIP-7623 is not enabled.
+	pub fn floor_cost(&self, config: &Config) -> u64 {
+		if !config.eip_7623_enabled {
+			return 0;
+		}
+
+		let (zero_data_len, non_zero_data_len) = match self {
+			TransactionCost::Call {
+				zero_data_len,
+				non_zero_data_len,
+				..
+			}
+			| TransactionCost::Create {
+				zero_data_len,
+				non_zero_data_len,
+				..
+			} => (*zero_data_len as u64, *non_zero_data_len as u64),
+		};
+		let schedule = &config.gas_schedule;
+		let tokens = zero_data_len + non_zero_data_len * schedule.standard_tokens_per_non_zero_byte;
+
+		config.gas_transaction_call + tokens * schedule.gas_total_cost_floor_per_token
+	}
 }
 
 /// Counts the number of addresses and storage keys in the access list
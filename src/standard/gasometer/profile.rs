@@ -0,0 +1,47 @@
+use alloc::collections::BTreeMap;
+
+use evm_interpreter::opcode::Opcode;
+
+/// Gas used by one opcode over a transaction.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpcodeGas {
+	/// Number of executions that were charged.
+	pub count: u64,
+	/// Gas charged, including memory expansion. Gas given to a call or
+	/// create is not included.
+	pub gas: u64,
+}
+
+/// Gas charged by the opcodes of a transaction, across all its call frames,
+/// whether or not they were reverted.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasProfile {
+	/// Gas per opcode.
+	pub opcodes: BTreeMap<Opcode, OpcodeGas>,
+	/// Gas per kind of cost, excluding memory expansion. Kinds are named
+	/// after the cost rule applied, such as `"SStore"` or `"Call"`, and
+	/// opcodes with a fixed cost are all counted as `"Static"`. The names
+	/// are static, so they are serialized but not deserialized.
+	#[cfg_attr(feature = "serde", serde(skip_deserializing))]
+	pub costs: BTreeMap<&'static str, u64>,
+	/// Gas charged for memory expansion.
+	pub memory: u64,
+}
+
+impl GasProfile {
+	/// Total gas charged by opcodes.
+	pub fn total(&self) -> u64 {
+		self.opcodes.values().map(|o| o.gas).sum()
+	}
+
+	pub(crate) fn record(&mut self, opcode: Opcode, kind: &'static str, cost: u64, memory: u64) {
+		let entry = self.opcodes.entry(opcode).or_default();
+		entry.count += 1;
+		entry.gas += cost + memory;
+
+		*self.costs.entry(kind).or_default() += cost;
+		self.memory += memory;
+	}
+}
@@ -0,0 +1,38 @@
+use alloc::vec::Vec;
+
+use evm_interpreter::opcode::Opcode;
+
+/// Refund event of a [RefundAudit].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RefundEvent {
+	/// Refund accrued by an opcode, negative if removed.
+	Opcode {
+		/// Opcode.
+		opcode: Opcode,
+		/// Position of the opcode in its code.
+		position: usize,
+		/// Refund.
+		refund: i64,
+	},
+	/// Refunds of a reverted or failed frame, dropped.
+	Dropped {
+		/// Refunds dropped.
+		refund: u64,
+	},
+}
+
+/// Every refund of a transaction and its final clamping by
+/// `max_refund_quotient`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RefundAudit {
+	/// Refund events, in execution order.
+	pub events: Vec<RefundEvent>,
+	/// Refunds accrued by the transaction.
+	pub accrued: u64,
+	/// Maximum refund: the used gas divided by `max_refund_quotient`.
+	pub cap: u64,
+	/// Refund applied: the accrued refunds, capped.
+	pub applied: u64,
+}
@@ -0,0 +1,122 @@
+//! # Gasometer tracing
+//!
+//! Events emitted by [crate::standard::GasometerState] for every gas charge,
+//! refund, memory gas update and submeter merge. Register an
+//! [EventListener] for the current thread with [using].
+
+#[cfg(feature = "tracing")]
+use alloc::rc::Rc;
+#[cfg(feature = "tracing")]
+use core::cell::RefCell;
+
+use evm_interpreter::opcode::Opcode;
+
+use crate::MergeStrategy;
+
+/// Gas accounting of a gasometer, after the event.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Snapshot {
+	/// Gas limit.
+	pub gas_limit: u64,
+	/// Gas used by memory expansion.
+	pub memory_gas: u64,
+	/// Gas used, excluding memory gas.
+	pub used_gas: u64,
+	/// Gas refunded.
+	pub refunded_gas: u64,
+}
+
+/// What a charge is attributed to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GasReason {
+	/// Cost of an opcode.
+	Opcode(Opcode),
+	/// Intrinsic cost of the transaction.
+	Transaction,
+	/// Cost of depositing the code of a created contract.
+	CodeDeposit,
+	/// Gas given to a call or create.
+	Submeter,
+	/// Any other explicit charge, such as precompile costs.
+	Other,
+}
+
+/// Gasometer event.
+#[derive(Clone, Copy, Debug)]
+pub enum Event {
+	/// Gas charged. If `out_of_gas` is set, the charge failed and the
+	/// snapshot is before it.
+	RecordGas {
+		cost: u64,
+		reason: GasReason,
+		out_of_gas: bool,
+		snapshot: Snapshot,
+	},
+	/// Refund accrued, negative if removed.
+	RecordRefund {
+		refund: i64,
+		reason: GasReason,
+		snapshot: Snapshot,
+	},
+	/// Memory gas updated to the cost of the whole memory. If `out_of_gas`
+	/// is set, the update failed and the snapshot is before it.
+	RecordMemoryGas {
+		memory_gas: u64,
+		reason: GasReason,
+		out_of_gas: bool,
+		snapshot: Snapshot,
+	},
+	/// Submeter with `gas_left` and `refunded_gas` merged into the parent.
+	Merge {
+		strategy: MergeStrategy,
+		gas_left: u64,
+		refunded_gas: u64,
+		snapshot: Snapshot,
+	},
+}
+
+/// Listener of gasometer events.
+#[cfg(feature = "tracing")]
+pub trait EventListener {
+	/// Handle an event.
+	fn event(&mut self, event: Event);
+}
+
+#[cfg(feature = "tracing")]
+std::thread_local! {
+	static LISTENER: RefCell<Option<Rc<RefCell<dyn EventListener>>>> = RefCell::new(None);
+}
+
+/// Run `f` with `listener` receiving the gasometer events of the current
+/// thread. The previous listener, if any, is restored afterwards.
+#[cfg(feature = "tracing")]
+pub fn using<R, F: FnOnce() -> R>(listener: Rc<RefCell<dyn EventListener>>, f: F) -> R {
+	struct Restore(Option<Rc<RefCell<dyn EventListener>>>);
+
+	impl Drop for Restore {
+		fn drop(&mut self) {
+			let previous = self.0.take();
+			LISTENER.with(|listener| *listener.borrow_mut() = previous);
+		}
+	}
+
+	let _restore = Restore(LISTENER.with(|current| current.borrow_mut().replace(listener)));
+	f()
+}
+
+/// Emit an event to the listener. The event is only constructed if the
+/// `tracing` feature is enabled and a listener is registered.
+#[inline]
+pub(crate) fn emit<F: FnOnce() -> Event>(f: F) {
+	#[cfg(feature = "tracing")]
+	LISTENER.with(|listener| {
+		if let Some(listener) = &*listener.borrow() {
+			if let Ok(mut listener) = listener.try_borrow_mut() {
+				listener.event(f());
+			}
+		}
+	});
+
+	#[cfg(not(feature = "tracing"))]
+	let _ = f;
+}
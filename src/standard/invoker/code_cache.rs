@@ -0,0 +1,204 @@
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use alloc::{boxed::Box, collections::BTreeMap};
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard};
+
+use evm_interpreter::{
+	eof::{self, ContainerKind, EofHeader},
+	Fusions, Valids,
+};
+use primitive_types::H256;
+
+/// Default size bound of a code cache, in bytes of analyzed code.
+#[cfg(feature = "std")]
+pub const DEFAULT_CODE_CACHE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Analysis artifacts of a piece of code.
+#[derive(Clone, Debug)]
+pub struct CodeArtifacts {
+	/// Valid jump destinations of the code.
+	pub valids: Arc<Valids>,
+	/// Superinstructions of the code, used if fusion is enabled.
+	pub fusions: Arc<Fusions>,
+	/// Header of the code, if it is a valid EOF runtime container.
+	pub eof: Option<Arc<EofHeader>>,
+}
+
+impl CodeArtifacts {
+	/// Analyze the given code.
+	pub fn analyze(code: &[u8]) -> Self {
+		let eof = if code.starts_with(&eof::MAGIC) {
+			eof::validate(code, ContainerKind::Runtime)
+				.ok()
+				.map(Arc::new)
+		} else {
+			None
+		};
+
+		Self {
+			valids: Arc::new(Valids::new(code)),
+			fusions: Arc::new(Fusions::new(code)),
+			eof,
+		}
+	}
+}
+
+/// Store of code analysis artifacts, keyed by code hash, consulted by
+/// [crate::standard::EtableResolver] for every called contract.
+///
+/// The hash is the one of [crate::backend::RuntimeBaseBackend::code_hash].
+/// Use the cache with backends storing it, such as
+/// [crate::backend::TrieBackend] or [crate::backend::CachedBackend], as
+/// hashing the code on every call costs more than analyzing it again.
+///
+/// [CodeCache] is the in-memory implementation. Backends can implement it to
+/// keep the artifacts next to the code, or in `no_std` environments.
+pub trait AnalysisCache: Sync {
+	/// Get the artifacts of `code`, analyzing it if they are not stored.
+	/// `code_hash` is the Keccak hash of `code`.
+	fn get_or_analyze(&self, code_hash: H256, code: &[u8]) -> CodeArtifacts;
+}
+
+#[cfg(feature = "std")]
+struct Entry {
+	artifacts: CodeArtifacts,
+	size: usize,
+	last_used: u64,
+}
+
+#[cfg(feature = "std")]
+struct Inner {
+	entries: BTreeMap<H256, Entry>,
+	recency: BTreeMap<u64, H256>,
+	tick: u64,
+	size: usize,
+}
+
+/// A thread-safe cache of code analysis artifacts, keyed by code hash.
+///
+/// The cache is bounded by the total length of the analyzed code. When the
+/// bound is exceeded, the least recently used entries are evicted. A single
+/// cache can be shared by any number of resolvers, across threads, so that hot
+/// contracts only get analyzed once.
+#[cfg(feature = "std")]
+pub struct CodeCache {
+	inner: Mutex<Inner>,
+	max_size: usize,
+}
+
+#[cfg(feature = "std")]
+impl CodeCache {
+	/// Create a new cache, bounded by `max_size` bytes of analyzed code.
+	pub fn new(max_size: usize) -> Self {
+		Self {
+			inner: Mutex::new(Inner {
+				entries: BTreeMap::new(),
+				recency: BTreeMap::new(),
+				tick: 0,
+				size: 0,
+			}),
+			max_size,
+		}
+	}
+
+	/// Create a new cache that lives for the rest of the process, to be
+	/// shared by resolvers with
+	/// [crate::standard::EtableResolver::with_code_cache].
+	pub fn leak(max_size: usize) -> &'static CodeCache {
+		Box::leak(Box::new(Self::new(max_size)))
+	}
+
+	fn lock(&self) -> MutexGuard<'_, Inner> {
+		// The cache never leaves its invariants broken across a panic, so a
+		// poisoned lock is still safe to use.
+		self.inner.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	/// Get the artifacts of `code`, analyzing and inserting it if it is not
+	/// yet in the cache. `code_hash` must be the Keccak hash of `code`.
+	pub fn get_or_analyze(&self, code_hash: H256, code: &[u8]) -> CodeArtifacts {
+		{
+			let mut inner = self.lock();
+			let inner = &mut *inner;
+			inner.tick += 1;
+
+			if let Some(entry) = inner.entries.get_mut(&code_hash) {
+				inner.recency.remove(&entry.last_used);
+				entry.last_used = inner.tick;
+				inner.recency.insert(entry.last_used, code_hash);
+				return entry.artifacts.clone();
+			}
+		}
+
+		// Analyze outside of the lock so that other threads are not blocked.
+		let artifacts = CodeArtifacts::analyze(code);
+		self.insert(code_hash, code.len(), artifacts.clone());
+		artifacts
+	}
+
+	fn insert(&self, code_hash: H256, size: usize, artifacts: CodeArtifacts) {
+		if size > self.max_size {
+			return;
+		}
+
+		let mut inner = self.lock();
+		let inner = &mut *inner;
+		if inner.entries.contains_key(&code_hash) {
+			return;
+		}
+
+		while inner.size + size > self.max_size {
+			let (tick, evicted) = match inner.recency.iter().next() {
+				Some((tick, evicted)) => (*tick, *evicted),
+				None => break,
+			};
+			inner.recency.remove(&tick);
+			if let Some(entry) = inner.entries.remove(&evicted) {
+				inner.size -= entry.size;
+			}
+		}
+
+		inner.tick += 1;
+		inner.recency.insert(inner.tick, code_hash);
+		inner.entries.insert(
+			code_hash,
+			Entry {
+				artifacts,
+				size,
+				last_used: inner.tick,
+			},
+		);
+		inner.size += size;
+	}
+
+	/// Number of cached entries.
+	pub fn len(&self) -> usize {
+		self.lock().entries.len()
+	}
+
+	/// Whether the cache is empty.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Total length of the cached code, in bytes.
+	pub fn size(&self) -> usize {
+		self.lock().size
+	}
+
+	/// Remove all entries.
+	pub fn clear(&self) {
+		let mut inner = self.lock();
+		inner.entries.clear();
+		inner.recency.clear();
+		inner.size = 0;
+	}
+}
+
+#[cfg(feature = "std")]
+impl AnalysisCache for CodeCache {
+	fn get_or_analyze(&self, code_hash: H256, code: &[u8]) -> CodeArtifacts {
+		CodeCache::get_or_analyze(self, code_hash, code)
+	}
+}
@@ -0,0 +1,150 @@
+use alloc::vec::Vec;
+
+use evm_interpreter::{
+	error::{CallScheme, CreateScheme, ExitError, ExitResult},
+	runtime::{Context, RuntimeBackend, RuntimeState},
+	Shared,
+};
+use primitive_types::{H160, U256};
+
+use crate::{gasometer::GasMutState, invoker::InvokerControl, standard::Resolver};
+
+/// Filter of contract creations, for example for permissioned deployment.
+///
+/// It is consulted before every creation, both of create transactions and of
+/// `CREATE`/`CREATE2` at any depth.
+pub trait CreateFilter {
+	/// Check the creation by `caller` with `scheme` and `init_code`.
+	///
+	/// Returning an error rejects it. A rejected create transaction is
+	/// invalid, before any fee is charged. A rejected `CREATE` or `CREATE2`
+	/// fails with the error as its exit, so the gas given to it is only
+	/// returned if the error is [ExitError::Reverted].
+	fn filter(
+		&self,
+		caller: H160,
+		scheme: &CreateScheme,
+		init_code: &[u8],
+	) -> Result<(), ExitError>;
+}
+
+/// Derivation of the addresses of created contracts, for example for chains
+/// with namespaced or prefixed contract addresses.
+///
+/// It is used for create transactions and for `CREATE`/`CREATE2` at any
+/// depth. Without one, the invoker derives addresses as on Ethereum.
+pub trait AddressScheme {
+	/// Address of the contract created with `scheme`, which holds the caller,
+	/// and for `CREATE2`, the salt and the init code hash. `nonce` is the
+	/// current nonce of the caller.
+	fn create_address(&self, scheme: &CreateScheme, nonce: U256) -> H160;
+}
+
+/// Scheme of a call frame.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FrameScheme {
+	/// A message call. A call transaction is a [CallScheme::Call].
+	Call(CallScheme),
+	/// A contract creation, by a create transaction or `CREATE`/`CREATE2`.
+	Create(CreateScheme),
+}
+
+/// A call frame entered by the invoker.
+#[derive(Clone, Debug)]
+pub struct FrameInfo {
+	/// Depth of the frame, zero for the transaction.
+	pub depth: usize,
+	/// Caller of the frame.
+	pub caller: H160,
+	/// Address the frame executes in, or the created address.
+	pub address: H160,
+	/// Address of the code executed, which differs from `address` for
+	/// `DELEGATECALL` and `CALLCODE`.
+	pub code_address: H160,
+	/// Scheme of the frame.
+	pub scheme: FrameScheme,
+	/// Gas given to the frame, not counting the call stipend.
+	pub gas: U256,
+}
+
+/// Observer of the call frames entered and exited by the invoker, for example
+/// for metrics, tracing or policy layers.
+///
+/// Unlike an [crate::standard::Etable] wrapper, it is only notified at frame
+/// boundaries, including of frames exiting directly, such as precompiles.
+pub trait FrameHook {
+	/// A frame is entered.
+	fn on_frame_enter(&self, frame: &FrameInfo);
+
+	/// The last entered frame is exited with `result`, returning `gas_left`
+	/// to its caller. For the transaction, `gas_left` is the gas refunded to
+	/// the sender.
+	fn on_frame_exit(&self, result: &ExitResult, gas_left: U256);
+}
+
+/// Interceptor servicing calls to host-defined addresses, for example
+/// precompiles whose addresses are stored in the state.
+///
+/// It is consulted for every call before code resolution, including before
+/// the precompile set of the resolver.
+pub trait CallInterceptor {
+	/// Service a call to `code_address`, returning its exit and output, or
+	/// `None` to fall through to code resolution.
+	///
+	/// `context` holds the caller, the address and the apparent value of the
+	/// call, whose value is already transferred. Gas of the call is charged
+	/// through `gas`.
+	fn intercept(
+		&self,
+		code_address: H160,
+		input: &[u8],
+		context: &Context,
+		gas: &mut dyn GasMutState,
+		backend: &mut dyn RuntimeBackend,
+	) -> Option<(ExitResult, Vec<u8>)>;
+}
+
+/// Resolver consulting a [CallInterceptor] before the inner resolver.
+pub(crate) struct InterceptingResolver<'a, R> {
+	pub resolver: &'a R,
+	pub interceptor: Option<&'a dyn CallInterceptor>,
+}
+
+impl<'a, H, R> Resolver<H> for InterceptingResolver<'a, R>
+where
+	R::State: GasMutState + AsRef<RuntimeState>,
+	H: RuntimeBackend,
+	R: Resolver<H>,
+{
+	type State = R::State;
+	type Interpreter = R::Interpreter;
+
+	fn resolve_call(
+		&self,
+		code_address: H160,
+		input: Shared<Vec<u8>>,
+		mut state: R::State,
+		handler: &mut H,
+	) -> Result<InvokerControl<R::Interpreter, (ExitResult, (R::State, Vec<u8>))>, ExitError> {
+		if let Some(interceptor) = self.interceptor {
+			let context = state.as_ref().context.clone();
+			if let Some((result, retval)) =
+				interceptor.intercept(code_address, &input, &context, &mut state, handler)
+			{
+				return Ok(InvokerControl::DirectExit((result, (state, retval))));
+			}
+		}
+
+		self.resolver
+			.resolve_call(code_address, input, state, handler)
+	}
+
+	fn resolve_create(
+		&self,
+		init_code: Shared<Vec<u8>>,
+		state: R::State,
+		handler: &mut H,
+	) -> Result<InvokerControl<R::Interpreter, (ExitResult, (R::State, Vec<u8>))>, ExitError> {
+		self.resolver.resolve_create(init_code, state, handler)
+	}
+}
@@ -1,36 +1,59 @@
+mod code_cache;
+mod hooks;
+#[cfg(feature = "op")]
+mod op;
+mod reentrancy;
 mod resolver;
 pub mod routines;
 mod state;
 
-use alloc::{rc::Rc, vec::Vec};
+use alloc::vec::Vec;
 use core::{cmp::min, convert::Infallible};
 
 use evm_interpreter::{
 	error::{
-		CallCreateTrap, CallCreateTrapData, CallTrapData, Capture, CreateScheme, CreateTrapData,
-		ExitError, ExitException, ExitResult, ExitSucceed, TrapConsume,
+		CallCreateTrap, CallCreateTrapData, CallScheme, CallTrapData, Capture, CreateScheme,
+		CreateTrapData, ExitError, ExitException, ExitFatal, ExitResult, ExitSucceed, TrapConsume,
 	},
+	machine::{Machine, MemoryStorage},
 	opcode::Opcode,
 	runtime::{
-		Context, GasState, RuntimeBackend, RuntimeEnvironment, RuntimeState, SetCodeOrigin,
-		TransactionContext, Transfer,
+		Context, FrameLimits, GasState, RuntimeBackend, RuntimeEnvironment, RuntimeState,
+		SetCodeOrigin, TransactionContext, Transfer,
 	},
-	Interpreter,
+	utils::keccak256,
+	Interpreter, RunInterpreter, Shared,
 };
 use primitive_types::{H160, H256, U256};
-use sha3::{Digest, Keccak256};
 
+#[cfg(feature = "std")]
+pub use self::code_cache::{CodeCache, DEFAULT_CODE_CACHE_SIZE};
+use self::hooks::InterceptingResolver;
+#[cfg(feature = "op")]
+use self::op::DEPOSIT_OVERRIDES;
+#[cfg(feature = "op")]
+pub use self::op::{op_config, DepositInfo, DepositTransaction, L1CostHook, L1_FEE_VAULT};
 pub use self::{
+	code_cache::{AnalysisCache, CodeArtifacts},
+	hooks::{AddressScheme, CallInterceptor, CreateFilter, FrameHook, FrameInfo, FrameScheme},
+	reentrancy::{Reentrancy, ReentrancyGuard, ReentrancyMode},
 	resolver::{EtableResolver, PrecompileSet, Resolver},
 	state::InvokerState,
 };
 use crate::{
 	backend::TransactionalBackend,
+	call_stack::transact_with,
+	gasometer::GasMutState,
 	invoker::{Invoker as InvokerT, InvokerControl},
-	standard::Config,
-	MergeStrategy,
+	standard::{Config, GasFrames, GasProfile, RefundAudit},
+	CallStackPolicy, MergeStrategy,
 };
 
+/// Blob gas of each blob (see EIP-4844).
+const GAS_PER_BLOB: u64 = 131072;
+/// Version byte of KZG commitment hashes (see EIP-4844).
+const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
 /// A trap that can be turned into either a call/create trap (where we push new
 /// call stack), or an interrupt (an external signal).
 pub trait IntoCallCreateTrap {
@@ -56,6 +79,11 @@ pub enum SubstackInvoke {
 }
 
 /// Return value of a transaction.
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransactValue {
 	Call {
 		/// The exit result. If we return a value, then it will be an
@@ -73,16 +101,117 @@ pub enum TransactValue {
 	},
 }
 
+/// Gas accounting of a transaction, whether or not its execution succeeded.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransactGas {
+	/// Gas limit of the transaction.
+	pub limit: U256,
+	/// Gas used, including the intrinsic gas, before refunds.
+	pub used: U256,
+	/// Gas refunded, capped by the refund quotient.
+	pub refunded: U256,
+	/// Minimum gas charged (see EIP-7623), zero if not enabled.
+	pub floor: U256,
+	/// Gas charged: the used gas less the refunds, at least the floor. This
+	/// is the gas used of the receipt.
+	pub effective: U256,
+	/// Gas charged per opcode, if enabled with [Invoker::with_gas_profile].
+	pub profile: Option<GasProfile>,
+	/// Gas per call frame, if enabled with [Invoker::with_gas_frames].
+	pub frames: Option<GasFrames>,
+	/// Every refund and their clamping, if enabled with
+	/// [Invoker::with_refund_audit].
+	pub refund_audit: Option<RefundAudit>,
+}
+
 /// The invoke used in a top-layer transaction stack.
 pub struct TransactInvoke {
 	pub create_address: Option<H160>,
 	pub gas_limit: U256,
 	pub gas_price: U256,
 	pub caller: H160,
+	/// Account paying the fees and receiving the refunds.
+	pub fee_payer: H160,
+	/// Whether this is a system call, paying no fee.
+	pub system: bool,
+	#[cfg(feature = "op")]
+	pub deposit: Option<DepositInfo>,
+}
+
+/// Per-transaction relaxations of the consensus rules, for example for
+/// `eth_call` and `eth_estimateGas`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+pub struct TransactOverrides {
+	/// Allow senders with code, if [Config::eip_3607_enabled].
+	pub skip_sender_code_check: bool,
+	/// Allow a gas price below the block base fee (see EIP-1559).
+	pub skip_base_fee_check: bool,
+	/// Do not charge the intrinsic gas of the transaction.
+	pub zero_intrinsic_gas: bool,
+	/// Execute as a protocol system call, as for EIP-4788 and EIP-2935: the
+	/// nonce is not incremented, the balance is not checked nor transferred,
+	/// and no fee is paid. See [TransactArgs::system_call].
+	pub system: bool,
+	/// Resource limits of the frames of the transaction, inherited by each
+	/// sub-call and sub-create.
+	pub limits: FrameLimits,
+}
+
+/// Handling of transaction nonces.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+pub enum NoncePolicy {
+	/// The nonce must be given and equal the nonce of the sender, as in
+	/// consensus.
+	Strict,
+	/// The nonce is not checked, for example for `eth_call`.
+	IgnoreMismatch,
+	/// A missing nonce is filled from the nonce of the sender, and a given
+	/// one is checked.
+	AutoFill,
+}
+
+impl Default for NoncePolicy {
+	fn default() -> Self {
+		Self::AutoFill
+	}
+}
+
+impl NoncePolicy {
+	/// Check the nonce of a transaction against the nonce of its sender.
+	pub fn check(&self, nonce: Option<U256>, sender_nonce: U256) -> Result<(), ExitError> {
+		match (self, nonce) {
+			(Self::IgnoreMismatch, _) | (Self::AutoFill, None) => Ok(()),
+			(Self::Strict, None) => Err(ExitException::InvalidNonce.into()),
+			(_, Some(nonce)) if nonce == sender_nonce => Ok(()),
+			(_, Some(_)) => Err(ExitException::InvalidNonce.into()),
+		}
+	}
 }
 
+/// Caller of system calls (see EIP-4788).
+pub const SYSTEM_ADDRESS: H160 = H160([
+	0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+	0xff, 0xff, 0xff, 0xfe,
+]);
+
+/// Gas limit of system calls (see EIP-4788).
+pub const SYSTEM_CALL_GAS_LIMIT: u64 = 30_000_000;
+
 /// Transaction arguments.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
 pub enum TransactArgs {
 	/// A call transaction.
 	Call {
@@ -94,12 +223,26 @@ pub enum TransactArgs {
 		value: U256,
 		/// Transaction call data.
 		data: Vec<u8>,
+		/// Transaction nonce. `None` to leave it to the [NoncePolicy].
+		nonce: Option<U256>,
+		/// Account paying the fees and receiving the refunds, if not the
+		/// caller. The nonce and `msg.sender` remain the caller's.
+		fee_payer: Option<H160>,
 		/// Transaction gas limit.
 		gas_limit: U256,
-		/// Transaction gas price.
+		/// Transaction gas price, or the max fee per gas if
+		/// `max_priority_fee_per_gas` is set.
 		gas_price: U256,
+		/// Max priority fee per gas of an EIP-1559 transaction.
+		max_priority_fee_per_gas: Option<U256>,
 		/// Access list information, in the format of (address, storage keys).
 		access_list: Vec<(H160, Vec<H256>)>,
+		/// Versioned hashes of the blobs of an EIP-4844 transaction.
+		blob_hashes: Vec<H256>,
+		/// Max fee per blob gas of an EIP-4844 transaction.
+		max_fee_per_blob_gas: Option<U256>,
+		/// Relaxed consensus rules for this transaction.
+		overrides: TransactOverrides,
 	},
 	/// A create transaction.
 	Create {
@@ -111,29 +254,102 @@ pub enum TransactArgs {
 		init_code: Vec<u8>,
 		/// Salt of `CREATE2`. `None` for a normal create transaction.
 		salt: Option<H256>,
+		/// Transaction nonce. `None` to leave it to the [NoncePolicy].
+		nonce: Option<U256>,
+		/// Account paying the fees and receiving the refunds, if not the
+		/// caller. The nonce and `msg.sender` remain the caller's.
+		fee_payer: Option<H160>,
 		/// Transaction gas limit.
 		gas_limit: U256,
-		/// Transaction gas price.
+		/// Transaction gas price, or the max fee per gas if
+		/// `max_priority_fee_per_gas` is set.
 		gas_price: U256,
+		/// Max priority fee per gas of an EIP-1559 transaction.
+		max_priority_fee_per_gas: Option<U256>,
 		/// Access list information, in the format of (address, storage keys).
 		access_list: Vec<(H160, Vec<H256>)>,
+		/// Relaxed consensus rules for this transaction.
+		overrides: TransactOverrides,
 	},
+	/// An OP-stack deposit transaction.
+	#[cfg(feature = "op")]
+	Deposit(DepositTransaction),
 }
 
 impl TransactArgs {
+	/// A protocol system call of `address` from [SYSTEM_ADDRESS], charging
+	/// no intrinsic gas and paying no fee.
+	pub fn system_call(address: H160, data: Vec<u8>) -> Self {
+		Self::Call {
+			caller: SYSTEM_ADDRESS,
+			address,
+			value: U256::zero(),
+			data,
+			nonce: None,
+			fee_payer: None,
+			gas_limit: U256::from(SYSTEM_CALL_GAS_LIMIT),
+			gas_price: U256::zero(),
+			max_priority_fee_per_gas: None,
+			access_list: Vec::new(),
+			blob_hashes: Vec::new(),
+			max_fee_per_blob_gas: None,
+			overrides: TransactOverrides {
+				skip_sender_code_check: true,
+				skip_base_fee_check: true,
+				zero_intrinsic_gas: true,
+				system: true,
+				limits: FrameLimits::new(),
+			},
+		}
+	}
+
 	/// Transaction gas limit.
 	pub fn gas_limit(&self) -> U256 {
 		match self {
 			Self::Call { gas_limit, .. } => *gas_limit,
 			Self::Create { gas_limit, .. } => *gas_limit,
+			#[cfg(feature = "op")]
+			Self::Deposit(deposit) => deposit.gas_limit,
 		}
 	}
 
-	/// Transaction gas price.
+	/// Transaction gas price, or the max fee per gas of an EIP-1559
+	/// transaction.
 	pub fn gas_price(&self) -> U256 {
 		match self {
 			Self::Call { gas_price, .. } => *gas_price,
 			Self::Create { gas_price, .. } => *gas_price,
+			#[cfg(feature = "op")]
+			Self::Deposit(_) => U256::zero(),
+		}
+	}
+
+	/// Max priority fee per gas of an EIP-1559 transaction.
+	pub fn max_priority_fee_per_gas(&self) -> Option<U256> {
+		match self {
+			Self::Call {
+				max_priority_fee_per_gas,
+				..
+			} => *max_priority_fee_per_gas,
+			Self::Create {
+				max_priority_fee_per_gas,
+				..
+			} => *max_priority_fee_per_gas,
+			#[cfg(feature = "op")]
+			Self::Deposit(_) => None,
+		}
+	}
+
+	/// Gas price paid given the block base fee: the base fee plus the
+	/// priority fee, capped at the max fee, for EIP-1559 transactions, and
+	/// the gas price otherwise.
+	pub fn effective_gas_price(&self, base_fee_per_gas: U256) -> U256 {
+		match self.max_priority_fee_per_gas() {
+			Some(max_priority_fee_per_gas) => min(
+				self.gas_price(),
+				base_fee_per_gas.saturating_add(max_priority_fee_per_gas),
+			),
+			None => self.gas_price(),
 		}
 	}
 
@@ -142,6 +358,30 @@ impl TransactArgs {
 		match self {
 			Self::Call { access_list, .. } => access_list,
 			Self::Create { access_list, .. } => access_list,
+			#[cfg(feature = "op")]
+			Self::Deposit(_) => {
+				const EMPTY: &Vec<(H160, Vec<H256>)> = &Vec::new();
+				EMPTY
+			}
+		}
+	}
+
+	/// Versioned hashes of the blobs of an EIP-4844 transaction.
+	pub fn blob_hashes(&self) -> &[H256] {
+		match self {
+			Self::Call { blob_hashes, .. } => blob_hashes,
+			_ => &[],
+		}
+	}
+
+	/// Max fee per blob gas of an EIP-4844 transaction.
+	pub fn max_fee_per_blob_gas(&self) -> Option<U256> {
+		match self {
+			Self::Call {
+				max_fee_per_blob_gas,
+				..
+			} => *max_fee_per_blob_gas,
+			_ => None,
 		}
 	}
 
@@ -150,6 +390,43 @@ impl TransactArgs {
 		match self {
 			Self::Call { caller, .. } => *caller,
 			Self::Create { caller, .. } => *caller,
+			#[cfg(feature = "op")]
+			Self::Deposit(deposit) => deposit.caller,
+		}
+	}
+
+	/// Account paying the fees of the transaction: the fee payer if given,
+	/// and the caller otherwise.
+	pub fn fee_payer(&self) -> H160 {
+		match self {
+			Self::Call {
+				caller, fee_payer, ..
+			} => fee_payer.unwrap_or(*caller),
+			Self::Create {
+				caller, fee_payer, ..
+			} => fee_payer.unwrap_or(*caller),
+			#[cfg(feature = "op")]
+			Self::Deposit(deposit) => deposit.caller,
+		}
+	}
+
+	/// Relaxed consensus rules for this transaction.
+	pub fn overrides(&self) -> TransactOverrides {
+		match self {
+			Self::Call { overrides, .. } => *overrides,
+			Self::Create { overrides, .. } => *overrides,
+			#[cfg(feature = "op")]
+			Self::Deposit(_) => DEPOSIT_OVERRIDES,
+		}
+	}
+
+	/// Transaction nonce, if given.
+	pub fn nonce(&self) -> Option<U256> {
+		match self {
+			Self::Call { nonce, .. } => *nonce,
+			Self::Create { nonce, .. } => *nonce,
+			#[cfg(feature = "op")]
+			Self::Deposit(_) => None,
 		}
 	}
 
@@ -158,6 +435,8 @@ impl TransactArgs {
 		match self {
 			Self::Call { value, .. } => *value,
 			Self::Create { value, .. } => *value,
+			#[cfg(feature = "op")]
+			Self::Deposit(deposit) => deposit.value,
 		}
 	}
 }
@@ -173,18 +452,449 @@ impl TransactArgs {
 pub struct Invoker<'config, 'resolver, R> {
 	config: &'config Config,
 	resolver: &'resolver R,
+	create_filter: Option<&'config dyn CreateFilter>,
+	call_interceptor: Option<&'config dyn CallInterceptor>,
+	address_scheme: Option<&'config dyn AddressScheme>,
+	nonce_policy: NoncePolicy,
+	call_stack_policy: CallStackPolicy,
+	frame_hook: Option<&'config dyn FrameHook>,
+	reentrancy_guard: Option<&'config ReentrancyGuard>,
+	gas_profile: bool,
+	gas_frames: bool,
+	refund_audit: bool,
+	memory_budget: Option<usize>,
+	#[cfg(feature = "op")]
+	l1_cost: Option<&'config dyn L1CostHook>,
 }
 
 impl<'config, 'resolver, R> Invoker<'config, 'resolver, R> {
 	/// Create a new standard invoker with the given config and resolver.
 	pub fn new(config: &'config Config, resolver: &'resolver R) -> Self {
-		Self { config, resolver }
+		Self {
+			config,
+			resolver,
+			create_filter: None,
+			call_interceptor: None,
+			address_scheme: None,
+			nonce_policy: NoncePolicy::AutoFill,
+			call_stack_policy: CallStackPolicy::default(),
+			frame_hook: None,
+			reentrancy_guard: None,
+			gas_profile: false,
+			gas_frames: false,
+			refund_audit: false,
+			memory_budget: None,
+			#[cfg(feature = "op")]
+			l1_cost: None,
+		}
+	}
+
+	/// Config of the invoker.
+	pub fn config(&self) -> &'config Config {
+		self.config
+	}
+
+	/// Consult the filter before every contract creation.
+	#[must_use]
+	pub fn with_create_filter(mut self, filter: &'config dyn CreateFilter) -> Self {
+		self.create_filter = Some(filter);
+		self
+	}
+
+	/// Consult the interceptor before resolving the code of every call.
+	#[must_use]
+	pub fn with_call_interceptor(mut self, interceptor: &'config dyn CallInterceptor) -> Self {
+		self.call_interceptor = Some(interceptor);
+		self
+	}
+
+	/// Derive the addresses of created contracts with the scheme.
+	#[must_use]
+	pub fn with_address_scheme(mut self, scheme: &'config dyn AddressScheme) -> Self {
+		self.address_scheme = Some(scheme);
+		self
+	}
+
+	/// Handle transaction nonces with the policy, instead of
+	/// [NoncePolicy::AutoFill].
+	#[must_use]
+	pub fn with_nonce_policy(mut self, policy: NoncePolicy) -> Self {
+		self.nonce_policy = policy;
+		self
+	}
+
+	/// Run transactions with the call stack policy.
+	#[must_use]
+	pub fn with_call_stack_policy(mut self, policy: CallStackPolicy) -> Self {
+		self.call_stack_policy = policy;
+		self
+	}
+
+	/// Notify the hook of every call frame entered and exited.
+	#[must_use]
+	pub fn with_frame_hook(mut self, hook: &'config dyn FrameHook) -> Self {
+		self.frame_hook = Some(hook);
+		self
+	}
+
+	/// Flag or reject re-entrant calls with the guard.
+	#[must_use]
+	pub fn with_reentrancy_guard(mut self, guard: &'config ReentrancyGuard) -> Self {
+		self.reentrancy_guard = Some(guard);
+		self
+	}
+
+	/// Accumulate the gas charged per opcode over each transaction, returned
+	/// in [TransactGas::profile].
+	#[must_use]
+	pub fn with_gas_profile(mut self, enabled: bool) -> Self {
+		self.gas_profile = enabled;
+		self
+	}
+
+	/// Record the gas of each call frame of each transaction, returned in
+	/// [TransactGas::frames].
+	#[must_use]
+	pub fn with_gas_frames(mut self, enabled: bool) -> Self {
+		self.gas_frames = enabled;
+		self
+	}
+
+	/// Record every refund of each transaction, returned in
+	/// [TransactGas::refund_audit].
+	#[must_use]
+	pub fn with_refund_audit(mut self, enabled: bool) -> Self {
+		self.refund_audit = enabled;
+		self
+	}
+
+	/// Limit the memory of all live frames of each transaction, including
+	/// their return buffers, to `budget` bytes. Exceeding it aborts the
+	/// transaction with [ExitFatal::MemoryBudgetExceeded].
+	#[must_use]
+	pub fn with_memory_budget(mut self, budget: usize) -> Self {
+		self.memory_budget = Some(budget);
+		self
+	}
+
+	/// Charge the L1 data fee computed by the hook to all non-deposit
+	/// transactions.
+	#[cfg(feature = "op")]
+	#[must_use]
+	pub fn with_l1_cost(mut self, hook: &'config dyn L1CostHook) -> Self {
+		self.l1_cost = Some(hook);
+		self
+	}
+}
+
+impl<'config, 'resolver, R> Invoker<'config, 'resolver, R> {
+	/// The resolver of calls, consulting the call interceptor first.
+	fn call_resolver(&self) -> InterceptingResolver<'_, R> {
+		InterceptingResolver {
+			resolver: self.resolver,
+			interceptor: self.call_interceptor,
+		}
+	}
+
+	/// Address of the contract created with `scheme`, derived by the
+	/// address scheme if any.
+	fn create_address<H: RuntimeBackend>(&self, scheme: &CreateScheme, handler: &H) -> H160 {
+		match self.address_scheme {
+			Some(address_scheme) => {
+				address_scheme.create_address(scheme, handler.nonce(scheme.caller()))
+			}
+			None => scheme.address(handler),
+		}
+	}
+
+	/// Notify the frame hook and the reentrancy guard of an entered frame.
+	fn frame_entered(&self, frame: &FrameInfo) {
+		if let Some(guard) = self.reentrancy_guard {
+			guard.enter(frame);
+		}
+		if let Some(hook) = self.frame_hook {
+			hook.on_frame_enter(frame);
+		}
+	}
+
+	/// Notify the frame hook and the reentrancy guard of an entered substack.
+	fn substack_entered(&self, depth: usize, invoke: &SubstackInvoke, gas: U256) {
+		if self.frame_hook.is_none() && self.reentrancy_guard.is_none() {
+			return;
+		}
+
+		let frame = match invoke {
+			SubstackInvoke::Call { trap } => FrameInfo {
+				depth,
+				caller: trap.context.caller,
+				address: trap.context.address,
+				code_address: trap.target,
+				scheme: FrameScheme::Call(trap.scheme),
+				gas,
+			},
+			SubstackInvoke::Create { trap, address } => FrameInfo {
+				depth,
+				caller: trap.scheme.caller(),
+				address: *address,
+				code_address: *address,
+				scheme: FrameScheme::Create(trap.scheme),
+				gas,
+			},
+		};
+		self.frame_entered(&frame);
+	}
+
+	/// Notify the frame hook and the reentrancy guard of an exited frame.
+	fn frame_exited<S: GasState>(&self, result: &ExitResult, state: &S) {
+		if let Some(guard) = self.reentrancy_guard {
+			guard.exit();
+		}
+		if let Some(hook) = self.frame_hook {
+			let gas_left = match result {
+				Ok(_) | Err(ExitError::Reverted) => state.gas(),
+				Err(_) => U256::zero(),
+			};
+			hook.on_frame_exit(result, gas_left);
+		}
+	}
+
+	/// Memory budget of a substack: what the parent leaves of its own.
+	fn substack_memory_budget<S, M: MemoryStorage>(&self, parent: &Machine<S, M>) -> Option<usize> {
+		self.memory_budget
+			.map(|_| parent.memory.budget().saturating_sub(parent.memory.len()))
+	}
+
+	/// Charge a new return buffer of the parent to its memory budget.
+	fn charge_retbuf<S: AsRef<RuntimeState>, M: MemoryStorage>(
+		&self,
+		parent: &mut Machine<S, M>,
+		old_retbuf_len: usize,
+	) -> Result<(), ExitError> {
+		if self.memory_budget.is_none() {
+			return Ok(());
+		}
+
+		let retbuf_len = parent.state.as_ref().retbuf.len();
+		let budget = parent
+			.memory
+			.budget()
+			.saturating_add(old_retbuf_len)
+			.checked_sub(retbuf_len)
+			.filter(|budget| *budget >= parent.memory.len())
+			.ok_or(ExitFatal::MemoryBudgetExceeded)?;
+		parent.memory.set_budget(budget);
+		Ok(())
+	}
+
+	/// Check the fees of a transaction against the block, and withdraw them
+	/// from the sender.
+	fn charge_fees<H: RuntimeEnvironment + RuntimeBackend>(
+		&self,
+		args: &TransactArgs,
+		gas_price: U256,
+		blob_base_fee: U256,
+		handler: &mut H,
+	) -> Result<(), ExitError> {
+		let fee_payer = args.fee_payer();
+		let base_fee_per_gas = handler.block_base_fee_per_gas();
+		let overrides = args.overrides();
+		let blob_gas =
+			U256::from(args.blob_hashes().len()).saturating_mul(U256::from(GAS_PER_BLOB));
+
+		match args.max_fee_per_blob_gas() {
+			Some(max_fee_per_blob_gas) => {
+				if !self.config.eip_4844_enabled
+					|| args.blob_hashes().is_empty()
+					|| args
+						.blob_hashes()
+						.iter()
+						.any(|hash| hash[0] != VERSIONED_HASH_VERSION_KZG)
+				{
+					return Err(ExitException::InvalidBlobHashes.into());
+				}
+
+				if args.blob_hashes().len() > self.config.max_blobs_per_tx {
+					return Err(ExitException::TooManyBlobs.into());
+				}
+
+				if !overrides.skip_base_fee_check && max_fee_per_blob_gas < blob_base_fee {
+					return Err(ExitException::BlobGasPriceBelowBaseFee.into());
+				}
+			}
+			None if !args.blob_hashes().is_empty() => {
+				return Err(ExitException::InvalidBlobHashes.into());
+			}
+			None => (),
+		}
+
+		if self.config.eip_1559_enabled {
+			if args
+				.max_priority_fee_per_gas()
+				.map_or(false, |priority_fee| priority_fee > args.gas_price())
+			{
+				return Err(ExitException::PriorityFeeAboveMaxFee.into());
+			}
+
+			if !overrides.skip_base_fee_check && args.gas_price() < base_fee_per_gas {
+				return Err(ExitException::GasPriceBelowBaseFee.into());
+			}
+
+			// The fee payer must afford the max fees, even if it pays less.
+			let max_gas_fee = args
+				.gas_limit()
+				.saturating_mul(args.gas_price())
+				.saturating_add(
+					blob_gas.saturating_mul(args.max_fee_per_blob_gas().unwrap_or_default()),
+				);
+			if handler.balance(fee_payer) < max_gas_fee {
+				return Err(ExitException::OutOfFund.into());
+			}
+		}
+
+		let gas_fee = args.gas_limit().saturating_mul(gas_price);
+		handler.withdrawal(fee_payer, gas_fee)?;
+
+		// The blob fee is burned, and not refunded.
+		if !blob_gas.is_zero() {
+			handler.withdrawal(fee_payer, blob_gas.saturating_mul(blob_base_fee))?;
+		}
+
+		Ok(())
+	}
+
+	/// Finalize a transaction, also returning its gas accounting, as gas is
+	/// charged whether or not the execution succeeded.
+	pub(crate) fn finalize_transact_with_gas<H>(
+		&self,
+		invoke: &TransactInvoke,
+		result: ExitResult,
+		(mut substate, retval): (R::State, Vec<u8>),
+		handler: &mut H,
+	) -> (Result<TransactValue, ExitError>, TransactGas)
+	where
+		R::State: InvokerState<'config> + AsRef<RuntimeState> + AsMut<RuntimeState>,
+		H: RuntimeEnvironment + RuntimeBackend + TransactionalBackend,
+		R: Resolver<H>,
+	{
+		let left_gas = substate.effective_gas();
+		let gas = TransactGas {
+			limit: invoke.gas_limit,
+			used: substate.used_gas(),
+			refunded: substate.refunded_gas(),
+			floor: substate.floor_gas(),
+			effective: invoke.gas_limit.saturating_sub(left_gas),
+			profile: substate.gas_profile(),
+			frames: substate.gas_frames(),
+			refund_audit: substate.refund_audit(),
+		};
+
+		let work = || -> Result<TransactValue, ExitError> {
+			match result {
+				Ok(result) => {
+					if let Some(address) = invoke.create_address {
+						let retbuf = retval;
+
+						routines::deploy_create_code(
+							self.config,
+							address,
+							retbuf,
+							&mut substate,
+							handler,
+							SetCodeOrigin::Transaction,
+						)?;
+
+						Ok(TransactValue::Create {
+							succeed: result,
+							address,
+						})
+					} else {
+						Ok(TransactValue::Call {
+							succeed: result,
+							retval,
+						})
+					}
+				}
+				Err(result) => Err(result),
+			}
+		};
+
+		let result = work();
+
+		let refunded_gas = match result {
+			Ok(_) | Err(ExitError::Reverted) => left_gas,
+			Err(_) => U256::zero(),
+		};
+		let gas = match result {
+			Ok(_) | Err(ExitError::Reverted) => gas,
+			Err(_) => TransactGas {
+				limit: invoke.gas_limit,
+				used: invoke.gas_limit,
+				refunded: U256::zero(),
+				floor: gas.floor,
+				effective: invoke.gas_limit,
+				profile: gas.profile,
+				frames: gas.frames,
+				refund_audit: gas.refund_audit,
+			},
+		};
+
+		if let Some(guard) = self.reentrancy_guard {
+			guard.exit();
+		}
+		if let Some(hook) = self.frame_hook {
+			let exit = match &result {
+				Ok(TransactValue::Call { succeed, .. })
+				| Ok(TransactValue::Create { succeed, .. }) => Ok(*succeed),
+				Err(err) => Err(err.clone()),
+			};
+			hook.on_frame_exit(&exit, refunded_gas);
+		}
+
+		match &result {
+			Ok(_) => {
+				handler.pop_substate(MergeStrategy::Commit);
+			}
+			Err(_) => {
+				handler.pop_substate(MergeStrategy::Discard);
+			}
+		}
+
+		// System calls and deposits buy no gas, so nothing is refunded to the
+		// fee payer and the coinbase gets no reward.
+		if invoke.system {
+			return (result, gas);
+		}
+		#[cfg(feature = "op")]
+		if invoke.deposit.is_some() {
+			return (result, gas);
+		}
+
+		let refunded_fee = refunded_gas.saturating_mul(invoke.gas_price);
+		handler.deposit(invoke.fee_payer, refunded_fee);
+		// Reward coinbase address
+		// EIP-1559 updated the fee system so that miners only get to keep the priority fee.
+		// The base fee is always burned. The gas price of the invoke is already the
+		// effective one, `min(max_fee, base_fee + max_priority_fee)`.
+		let coinbase_gas_price = if substate.config().eip_1559_enabled {
+			invoke
+				.gas_price
+				.saturating_sub(handler.block_base_fee_per_gas())
+		} else {
+			invoke.gas_price
+		};
+		let coinbase_reward = invoke
+			.gas_limit
+			.saturating_sub(refunded_gas)
+			.saturating_mul(coinbase_gas_price);
+		handler.deposit(handler.block_coinbase(), coinbase_reward);
+
+		(result, gas)
 	}
 }
 
 impl<'config, 'resolver, H, R, Tr> InvokerT<H, Tr> for Invoker<'config, 'resolver, R>
 where
-	R::State: InvokerState<'config> + AsRef<RuntimeState> + AsMut<RuntimeState>,
+	R::State: InvokerState<'config> + GasMutState + AsRef<RuntimeState> + AsMut<RuntimeState>,
 	H: RuntimeEnvironment + RuntimeBackend + TransactionalBackend,
 	R: Resolver<H>,
 	Tr: TrapConsume<CallCreateTrap>,
@@ -208,46 +918,116 @@ where
 		),
 		ExitError,
 	> {
+		#[cfg(feature = "op")]
+		let (args, deposit) = match args {
+			TransactArgs::Deposit(deposit) => {
+				let (args, info) = deposit.into_parts();
+				// Minted before anything else, so that it persists even if
+				// the transaction fails.
+				handler.deposit(args.caller(), info.mint);
+				(args, Some(info))
+			}
+			args => (args, None),
+		};
+
 		let caller = args.caller();
-		let gas_price = args.gas_price();
+		let base_fee_per_gas = handler.block_base_fee_per_gas();
+		let gas_price = if self.config.eip_1559_enabled {
+			args.effective_gas_price(base_fee_per_gas)
+		} else {
+			args.gas_price()
+		};
+		let overrides = args.overrides();
+
+		if self.config.eip_3607_enabled && !overrides.skip_sender_code_check {
+			let code = handler.code(caller);
+			let is_delegation = self.config.eip_7702_enabled
+				&& code.len() == 23
+				&& code.starts_with(&[0xef, 0x01, 0x00]);
+			if !code.is_empty() && !is_delegation {
+				return Err(ExitException::SenderHasCode.into());
+			}
+		}
 
-		let gas_fee = args.gas_limit().saturating_mul(gas_price);
-		handler.withdrawal(caller, gas_fee)?;
+		if let (
+			Some(filter),
+			TransactArgs::Create {
+				caller,
+				salt,
+				init_code,
+				..
+			},
+		) = (self.create_filter, &args)
+		{
+			filter.filter(
+				*caller,
+				&create_scheme(*caller, *salt, init_code),
+				init_code,
+			)?;
+		}
 
-		handler.inc_nonce(caller)?;
+		let blob_base_fee = if self.config.eip_4844_enabled {
+			self.config.blob_base_fee(handler.block_excess_blob_gas())
+		} else {
+			U256::zero()
+		};
+		// System calls pay no fees, and do not touch the nonce of the caller.
+		if !overrides.system {
+			// Deposits carry no nonce.
+			#[cfg(feature = "op")]
+			let check_nonce = deposit.is_none();
+			#[cfg(not(feature = "op"))]
+			let check_nonce = true;
+			if check_nonce {
+				self.nonce_policy
+					.check(args.nonce(), handler.nonce(caller))?;
+			}
+
+			self.charge_fees(&args, gas_price, blob_base_fee, handler)?;
+
+			#[cfg(feature = "op")]
+			if let (None, Some(hook)) = (deposit, self.l1_cost) {
+				let l1_fee = hook.l1_cost(&args);
+				handler.withdrawal(args.fee_payer(), l1_fee)?;
+				handler.deposit(L1_FEE_VAULT, l1_fee);
+			}
 
-		let address = match &args {
-			TransactArgs::Call { address, .. } => *address,
+			handler.inc_nonce(caller)?;
+		}
+
+		let (address, frame_scheme) = match &args {
+			TransactArgs::Call { address, .. } => (*address, FrameScheme::Call(CallScheme::Call)),
+			#[cfg(feature = "op")]
+			TransactArgs::Deposit(_) => unreachable!("deposit converted above"),
 			TransactArgs::Create {
 				caller,
 				salt,
 				init_code,
 				..
-			} => match salt {
-				Some(salt) => {
-					let scheme = CreateScheme::Create2 {
-						caller: *caller,
-						code_hash: H256::from_slice(Keccak256::digest(init_code).as_slice()),
-						salt: *salt,
-					};
-					scheme.address(handler)
-				}
-				None => {
-					let scheme = CreateScheme::Legacy { caller: *caller };
-					scheme.address(handler)
-				}
-			},
+			} => {
+				let scheme = create_scheme(*caller, *salt, init_code);
+				(
+					self.create_address(&scheme, handler),
+					FrameScheme::Create(scheme),
+				)
+			}
 		};
 		let value = args.value();
 
 		let invoke = TransactInvoke {
 			gas_limit: args.gas_limit(),
-			gas_price: args.gas_price(),
+			gas_price,
 			caller: args.caller(),
+			fee_payer: args.fee_payer(),
+			system: overrides.system,
 			create_address: match &args {
 				TransactArgs::Call { .. } => None,
 				TransactArgs::Create { .. } => Some(address),
+				#[cfg(feature = "op")]
+				TransactArgs::Deposit(_) => unreachable!("deposit converted above"),
 			},
+			#[cfg(feature = "op")]
+			deposit,
 		};
 
 		handler.push_substate();
@@ -260,6 +1040,10 @@ where
 		let transaction_context = TransactionContext {
 			origin: caller,
 			gas_price,
+			blob_hashes: args.blob_hashes().to_vec(),
+			blob_base_fee,
+			is_system: overrides.system,
+			selfdestruct_only_created: self.config.eip_6780_enabled,
 		};
 		let transfer = Transfer {
 			source: caller,
@@ -268,8 +1052,11 @@ where
 		};
 		let runtime_state = RuntimeState {
 			context,
-			transaction_context: Rc::new(transaction_context),
+			transaction_context: Shared::new(transaction_context),
 			retbuf: Vec::new(),
+			limits: overrides.limits,
+			#[cfg(feature = "eip3074")]
+			authorized: None,
 		};
 
 		let work = || -> Result<(TransactInvoke, _), ExitError> {
@@ -289,20 +1076,34 @@ where
 						}
 					}
 
-					let state = <R::State>::new_transact_call(
+					let mut state = <R::State>::new_transact_call(
 						runtime_state,
 						gas_limit,
 						&data,
 						&access_list,
+						&overrides,
 						self.config,
 					)?;
+					if self.gas_profile {
+						state.enable_gas_profile();
+					}
+					if self.gas_frames {
+						state.enable_gas_frames();
+					}
+					if self.refund_audit {
+						state.enable_refund_audit();
+					}
 
 					let machine = routines::make_enter_call_machine(
 						self.config,
-						self.resolver,
+						&self.call_resolver(),
 						address,
-						data,
-						Some(transfer),
+						Shared::new(data),
+						if overrides.system {
+							None
+						} else {
+							Some(transfer)
+						},
 						state,
 						handler,
 					)?;
@@ -325,19 +1126,29 @@ where
 					access_list,
 					..
 				} => {
-					let state = <R::State>::new_transact_create(
+					let mut state = <R::State>::new_transact_create(
 						runtime_state,
 						gas_limit,
 						&init_code,
 						&access_list,
+						&overrides,
 						self.config,
 					)?;
+					if self.gas_profile {
+						state.enable_gas_profile();
+					}
+					if self.gas_frames {
+						state.enable_gas_frames();
+					}
+					if self.refund_audit {
+						state.enable_refund_audit();
+					}
 
 					let machine = routines::make_enter_create_machine(
 						self.config,
 						self.resolver,
 						caller,
-						init_code,
+						Shared::new(init_code),
 						transfer,
 						state,
 						handler,
@@ -345,89 +1156,44 @@ where
 
 					Ok((invoke, machine))
 				}
+				#[cfg(feature = "op")]
+				TransactArgs::Deposit(_) => unreachable!("deposit converted above"),
 			}
 		};
 
-		work().map_err(|err| {
+		let (invoke, mut machine) = work().map_err(|err| {
 			handler.pop_substate(MergeStrategy::Discard);
 			err
-		})
+		})?;
+		if let (Some(budget), InvokerControl::Enter(machine)) = (self.memory_budget, &mut machine) {
+			machine.machine_mut().memory.set_budget(budget);
+		}
+
+		self.frame_entered(&FrameInfo {
+			depth: 0,
+			caller,
+			address,
+			code_address: address,
+			scheme: frame_scheme,
+			gas: invoke.gas_limit,
+		});
+
+		Ok((invoke, machine))
 	}
 
 	fn finalize_transact(
 		&self,
 		invoke: &Self::TransactInvoke,
 		result: ExitResult,
-		(mut substate, retval): (R::State, Vec<u8>),
+		machine: (R::State, Vec<u8>),
 		handler: &mut H,
 	) -> Result<Self::TransactValue, ExitError> {
-		let left_gas = substate.effective_gas();
-
-		let work = || -> Result<Self::TransactValue, ExitError> {
-			match result {
-				Ok(result) => {
-					if let Some(address) = invoke.create_address {
-						let retbuf = retval;
-
-						routines::deploy_create_code(
-							self.config,
-							address,
-							retbuf,
-							&mut substate,
-							handler,
-							SetCodeOrigin::Transaction,
-						)?;
-
-						Ok(TransactValue::Create {
-							succeed: result,
-							address,
-						})
-					} else {
-						Ok(TransactValue::Call {
-							succeed: result,
-							retval,
-						})
-					}
-				}
-				Err(result) => Err(result),
-			}
-		};
-
-		let result = work();
-
-		let refunded_gas = match result {
-			Ok(_) | Err(ExitError::Reverted) => left_gas,
-			Err(_) => U256::zero(),
-		};
-
-		match &result {
-			Ok(_) => {
-				handler.pop_substate(MergeStrategy::Commit);
-			}
-			Err(_) => {
-				handler.pop_substate(MergeStrategy::Discard);
-			}
-		}
-
-		let refunded_fee = refunded_gas.saturating_mul(invoke.gas_price);
-		handler.deposit(invoke.caller, refunded_fee);
-		// Reward coinbase address
-		// EIP-1559 updated the fee system so that miners only get to keep the priority fee.
-		// The base fee is always burned.
-		let coinbase_gas_price = if substate.config().eip_1559_enabled {
-			invoke
-				.gas_price
-				.saturating_sub(handler.block_base_fee_per_gas())
-		} else {
-			invoke.gas_price
-		};
-		let coinbase_reward = invoke
-			.gas_limit
-			.saturating_mul(coinbase_gas_price)
-			.saturating_sub(refunded_fee);
-		handler.deposit(handler.block_coinbase(), coinbase_reward);
+		self.finalize_transact_with_gas(invoke, result, machine, handler)
+			.0
+	}
 
-		result
+	fn call_stack_policy(&self) -> CallStackPolicy {
+		self.call_stack_policy
 	}
 
 	fn enter_substack(
@@ -446,10 +1212,6 @@ where
 		>,
 		Self::Interrupt,
 	> {
-		fn l64(gas: U256) -> U256 {
-			gas - gas / U256::from(64)
-		}
-
 		let opcode = match trap.consume() {
 			Ok(opcode) => opcode,
 			Err(interrupt) => return Capture::Trap(interrupt),
@@ -465,7 +1227,8 @@ where
 		};
 
 		let after_gas = if self.config.call_l64_after_gas {
-			l64(machine.machine().state.gas())
+			let gas = machine.machine().state.gas();
+			gas - gas / U256::from(self.config.call_gas_retention_divisor)
 		} else {
 			machine.machine().state.gas()
 		};
@@ -485,6 +1248,8 @@ where
 		};
 
 		let transaction_context = machine.machine().state.as_ref().transaction_context.clone();
+		let limits = machine.machine().state.as_ref().limits;
+		let memory_budget = self.substack_memory_budget(machine.machine());
 
 		match trap_data {
 			CallCreateTrapData::Call(call_trap_data) => {
@@ -493,6 +1258,9 @@ where
 						context: call_trap_data.context.clone(),
 						transaction_context,
 						retbuf: Vec::new(),
+						limits,
+						#[cfg(feature = "eip3074")]
+						authorized: None,
 					},
 					gas_limit,
 					is_static,
@@ -504,18 +1272,49 @@ where
 
 				let target = call_trap_data.target;
 
-				Capture::Exit(routines::enter_call_substack(
+				if let Some(guard) = self.reentrancy_guard {
+					let frame = FrameInfo {
+						depth,
+						caller: call_trap_data.context.caller,
+						address: call_trap_data.context.address,
+						code_address: target,
+						scheme: FrameScheme::Call(call_trap_data.scheme),
+						gas: gas_limit,
+					};
+					if let Err(err) = guard.check(&frame) {
+						handler.push_substate();
+						self.frame_entered(&frame);
+						return Capture::Exit(Ok((
+							SubstackInvoke::Call {
+								trap: call_trap_data,
+							},
+							InvokerControl::DirectExit((Err(err), (substate, Vec::new()))),
+						)));
+					}
+				}
+
+				let mut entered = routines::enter_call_substack(
 					self.config,
-					self.resolver,
+					&self.call_resolver(),
 					call_trap_data,
 					target,
 					substate,
 					handler,
-				))
+				);
+				if let Ok((invoke, _)) = &entered {
+					self.substack_entered(depth, invoke, gas_limit);
+				}
+				if let (Some(budget), Ok((_, InvokerControl::Enter(machine)))) =
+					(memory_budget, &mut entered)
+				{
+					machine.machine_mut().memory.set_budget(budget);
+				}
+
+				Capture::Exit(entered)
 			}
 			CallCreateTrapData::Create(create_trap_data) => {
 				let caller = create_trap_data.scheme.caller();
-				let address = create_trap_data.scheme.address(handler);
+				let address = self.create_address(&create_trap_data.scheme, handler);
 				let code = create_trap_data.code.clone();
 
 				let substate = match machine.machine_mut().state.substate(
@@ -527,6 +1326,9 @@ where
 						},
 						transaction_context,
 						retbuf: Vec::new(),
+						limits,
+						#[cfg(feature = "eip3074")]
+						authorized: None,
 					},
 					gas_limit,
 					is_static,
@@ -536,14 +1338,40 @@ where
 					Err(err) => return Capture::Exit(Err(err)),
 				};
 
-				Capture::Exit(routines::enter_create_substack(
+				if let Some(filter) = self.create_filter {
+					if let Err(err) = filter.filter(caller, &create_trap_data.scheme, &code) {
+						handler.push_substate();
+						let invoke = SubstackInvoke::Create {
+							address,
+							trap: create_trap_data,
+						};
+						self.substack_entered(depth, &invoke, gas_limit);
+						return Capture::Exit(Ok((
+							invoke,
+							InvokerControl::DirectExit((Err(err), (substate, Vec::new()))),
+						)));
+					}
+				}
+
+				let mut entered = routines::enter_create_substack(
 					self.config,
 					self.resolver,
 					code,
 					create_trap_data,
+					address,
 					substate,
 					handler,
-				))
+				);
+				if let Ok((invoke, _)) = &entered {
+					self.substack_entered(depth, invoke, gas_limit);
+				}
+				if let (Some(budget), Ok((_, InvokerControl::Enter(machine)))) =
+					(memory_budget, &mut entered)
+				{
+					machine.machine_mut().memory.set_budget(budget);
+				}
+
+				Capture::Exit(entered)
 			}
 		}
 	}
@@ -556,6 +1384,15 @@ where
 		parent: &mut Self::Interpreter,
 		handler: &mut H,
 	) -> Result<(), ExitError> {
+		let (result, retval) = match parent.machine().state.as_ref().limits.retbuf_limit {
+			Some(limit)
+				if retval.len() > limit && matches!(result, Ok(_) | Err(ExitError::Reverted)) =>
+			{
+				(Err(ExitException::ReturnDataLimit.into()), Vec::new())
+			}
+			_ => (result, retval),
+		};
+
 		let strategy = match &result {
 			Ok(_) => MergeStrategy::Commit,
 			Err(ExitError::Reverted) => MergeStrategy::Revert,
@@ -567,7 +1404,7 @@ where
 				let retbuf = retval;
 				let caller = trap.scheme.caller();
 
-				let result = result.and_then(|_| {
+				let result = result.and_then(|succeed| {
 					routines::deploy_create_code(
 						self.config,
 						address,
@@ -577,26 +1414,81 @@ where
 						SetCodeOrigin::Subcall(caller),
 					)?;
 
-					Ok(address)
+					Ok(succeed)
 				});
+				self.frame_exited(&result, &substate);
+				let result = result.map(|_| address);
 
 				parent.machine_mut().state.merge(substate, strategy);
 				handler.pop_substate(strategy);
 
+				let retbuf_len = parent.machine().state.as_ref().retbuf.len();
 				trap.feedback(result, retbuf, parent)?;
+				self.charge_retbuf(parent.machine_mut(), retbuf_len)?;
 
 				Ok(())
 			}
 			SubstackInvoke::Call { trap } => {
 				let retbuf = retval;
 
+				self.frame_exited(&result, &substate);
 				parent.machine_mut().state.merge(substate, strategy);
 				handler.pop_substate(strategy);
 
+				let retbuf_len = parent.machine().state.as_ref().retbuf.len();
 				trap.feedback(result, retbuf, parent)?;
+				self.charge_retbuf(parent.machine_mut(), retbuf_len)?;
 
 				Ok(())
 			}
 		}
 	}
 }
+
+/// Creation scheme of a create transaction.
+fn create_scheme(caller: H160, salt: Option<H256>, init_code: &[u8]) -> CreateScheme {
+	match salt {
+		Some(salt) => CreateScheme::Create2 {
+			caller,
+			code_hash: keccak256(init_code),
+			salt,
+		},
+		None => CreateScheme::Legacy { caller },
+	}
+}
+
+/// Like [crate::transact], but also returning the gas accounting of the
+/// transaction, whether or not its execution succeeded. The outer error is an
+/// invalid transaction, which charges no gas.
+#[allow(clippy::type_complexity)]
+pub fn transact_with_gas<'config, 'resolver, H, R, Tr>(
+	args: TransactArgs,
+	heap_depth: Option<usize>,
+	backend: &mut H,
+	invoker: &Invoker<'config, 'resolver, R>,
+) -> Result<(Result<TransactValue, ExitError>, TransactGas), ExitError>
+where
+	H: RuntimeEnvironment + RuntimeBackend + TransactionalBackend,
+	R: Resolver<H>,
+	R::State: InvokerState<'config> + AsRef<RuntimeState> + AsMut<RuntimeState>,
+	R::Interpreter: RunInterpreter<H, Tr>,
+	Invoker<'config, 'resolver, R>: InvokerT<
+		H,
+		Tr,
+		Interrupt = Infallible,
+		State = R::State,
+		Interpreter = R::Interpreter,
+		TransactArgs = TransactArgs,
+		TransactInvoke = TransactInvoke,
+	>,
+{
+	transact_with(
+		args,
+		heap_depth,
+		backend,
+		invoker,
+		|invoke, exit, machine, backend| {
+			Ok(invoker.finalize_transact_with_gas(invoke, exit, machine, backend))
+		},
+	)
+}
@@ -0,0 +1,115 @@
+use alloc::vec::Vec;
+
+use evm_interpreter::runtime::FrameLimits;
+use primitive_types::{H160, U256};
+
+use super::{TransactArgs, TransactOverrides};
+use crate::standard::Config;
+
+/// The `L1FeeVault` predeploy, receiving the L1 data fee of all non-deposit
+/// transactions.
+pub const L1_FEE_VAULT: H160 = H160([
+	0x42, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x1a,
+]);
+
+/// Deposits are not checked against the sender code and the base fee.
+pub(crate) const DEPOSIT_OVERRIDES: TransactOverrides = TransactOverrides {
+	skip_sender_code_check: true,
+	skip_base_fee_check: true,
+	zero_intrinsic_gas: false,
+	system: false,
+	limits: FrameLimits::new(),
+};
+
+/// Config of the latest supported OP-stack hard fork (Isthmus).
+pub const fn op_config() -> Config {
+	Config::prague()
+}
+
+/// An OP-stack deposit transaction, derived from L1.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+	feature = "scale",
+	derive(scale_codec::Encode, scale_codec::Decode, scale_info::TypeInfo)
+)]
+pub struct DepositTransaction {
+	/// Transaction sender.
+	pub caller: H160,
+	/// Transaction target. `None` for a create transaction.
+	pub address: Option<H160>,
+	/// Amount minted to the sender on L2, before the transaction executes.
+	/// The mint persists even if the transaction fails.
+	pub mint: U256,
+	/// Transaction value.
+	pub value: U256,
+	/// Transaction call data, or init code for a create transaction.
+	pub data: Vec<u8>,
+	/// Transaction gas limit.
+	pub gas_limit: U256,
+	/// Whether this is a system deposit transaction.
+	pub is_system_transaction: bool,
+}
+
+/// Deposit related information of a transaction.
+#[derive(Clone, Copy, Debug)]
+pub struct DepositInfo {
+	/// Amount minted to the sender.
+	pub mint: U256,
+	/// Whether this is a system deposit transaction. System deposits are not
+	/// counted in the block gas used.
+	pub is_system_transaction: bool,
+}
+
+/// Hook to compute the L1 data fee of a transaction.
+///
+/// The fee is withdrawn from the sender together with the L2 gas fee, and
+/// credited to [L1_FEE_VAULT]. It is never charged to deposit transactions.
+pub trait L1CostHook {
+	/// L1 data fee of the transaction.
+	fn l1_cost(&self, args: &TransactArgs) -> U256;
+}
+
+impl DepositTransaction {
+	/// Split into regular transaction arguments that execute the deposit, and
+	/// the deposit information.
+	pub(crate) fn into_parts(self) -> (TransactArgs, DepositInfo) {
+		let info = DepositInfo {
+			mint: self.mint,
+			is_system_transaction: self.is_system_transaction,
+		};
+
+		let args = match self.address {
+			Some(address) => TransactArgs::Call {
+				caller: self.caller,
+				address,
+				value: self.value,
+				data: self.data,
+				nonce: None,
+				fee_payer: None,
+				gas_limit: self.gas_limit,
+				gas_price: U256::zero(),
+				max_priority_fee_per_gas: None,
+				access_list: Vec::new(),
+				blob_hashes: Vec::new(),
+				max_fee_per_blob_gas: None,
+				overrides: DEPOSIT_OVERRIDES,
+			},
+			None => TransactArgs::Create {
+				caller: self.caller,
+				value: self.value,
+				init_code: self.data,
+				salt: None,
+				nonce: None,
+				fee_payer: None,
+				gas_limit: self.gas_limit,
+				gas_price: U256::zero(),
+				max_priority_fee_per_gas: None,
+				access_list: Vec::new(),
+				overrides: DEPOSIT_OVERRIDES,
+			},
+		};
+
+		(args, info)
+	}
+}
@@ -0,0 +1,113 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+use core::cell::RefCell;
+
+use evm_interpreter::error::{CallScheme, ExitError, ExitException};
+use primitive_types::H160;
+
+use super::{FrameInfo, FrameScheme};
+
+/// Action of a [ReentrancyGuard] on re-entrant calls.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReentrancyMode {
+	/// Record re-entrant calls, and let them execute.
+	Flag,
+	/// Fail re-entrant calls with [ExitException::Reentrancy].
+	Reject,
+}
+
+/// A call re-entering a contract already on the call path.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reentrancy {
+	/// Depth of the re-entrant call.
+	pub depth: usize,
+	/// Caller of the re-entrant call.
+	pub caller: H160,
+	/// Contract re-entered.
+	pub address: H160,
+}
+
+/// Guard tracking the contracts on the call path of a transaction, and
+/// flagging or rejecting calls re-entering one of them.
+///
+/// Only `CALL` and `STATICCALL` can re-enter a contract, as `DELEGATECALL`
+/// and `CALLCODE` execute in the context of their caller. Calls to contracts
+/// in the allowlist are never re-entrant.
+pub struct ReentrancyGuard {
+	mode: ReentrancyMode,
+	allowlist: BTreeSet<H160>,
+	path: RefCell<Vec<H160>>,
+	flagged: RefCell<Vec<Reentrancy>>,
+}
+
+impl ReentrancyGuard {
+	/// Create a new guard with an empty allowlist.
+	pub fn new(mode: ReentrancyMode) -> Self {
+		Self {
+			mode,
+			allowlist: BTreeSet::new(),
+			path: RefCell::new(Vec::new()),
+			flagged: RefCell::new(Vec::new()),
+		}
+	}
+
+	/// Allow re-entering `address`.
+	#[must_use]
+	pub fn with_allowed(mut self, address: H160) -> Self {
+		self.allowlist.insert(address);
+		self
+	}
+
+	/// Action on re-entrant calls.
+	pub fn mode(&self) -> ReentrancyMode {
+		self.mode
+	}
+
+	/// Re-entrant calls recorded since the last [ReentrancyGuard::take_flagged],
+	/// across transactions.
+	pub fn flagged(&self) -> Vec<Reentrancy> {
+		self.flagged.borrow().clone()
+	}
+
+	/// Take the re-entrant calls recorded.
+	pub fn take_flagged(&self) -> Vec<Reentrancy> {
+		core::mem::take(&mut *self.flagged.borrow_mut())
+	}
+
+	/// Check a frame about to be entered against the call path.
+	pub(crate) fn check(&self, frame: &FrameInfo) -> Result<(), ExitError> {
+		match frame.scheme {
+			FrameScheme::Call(CallScheme::Call) | FrameScheme::Call(CallScheme::StaticCall) => (),
+			_ => return Ok(()),
+		}
+
+		if self.allowlist.contains(&frame.address) || !self.path.borrow().contains(&frame.address) {
+			return Ok(());
+		}
+
+		match self.mode {
+			ReentrancyMode::Flag => {
+				self.flagged.borrow_mut().push(Reentrancy {
+					depth: frame.depth,
+					caller: frame.caller,
+					address: frame.address,
+				});
+				Ok(())
+			}
+			ReentrancyMode::Reject => Err(ExitException::Reentrancy.into()),
+		}
+	}
+
+	/// A frame is entered. The call path is reset by the transaction frame.
+	pub(crate) fn enter(&self, frame: &FrameInfo) {
+		let mut path = self.path.borrow_mut();
+		if frame.depth == 0 {
+			path.clear();
+		}
+		path.push(frame.address);
+	}
+
+	/// The last entered frame is exited.
+	pub(crate) fn exit(&self) {
+		self.path.borrow_mut().pop();
+	}
+}
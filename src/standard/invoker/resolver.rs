@@ -1,15 +1,20 @@
-use alloc::{rc::Rc, vec::Vec};
+use alloc::vec::Vec;
 
 use evm_interpreter::{
 	error::{ExitError, ExitResult},
 	etable::EtableSet,
-	machine::Machine,
+	machine::{Machine, Memory, MemoryStorage, Pages},
 	runtime::{RuntimeBackend, RuntimeState},
-	EtableInterpreter, Interpreter,
+	EtableInterpreter, Interpreter, Shared,
 };
 use primitive_types::H160;
 
-use crate::{invoker::InvokerControl, standard::Config};
+#[cfg(feature = "std")]
+use crate::standard::CodeCache;
+use crate::{
+	invoker::InvokerControl,
+	standard::{AnalysisCache, Config},
+};
 
 /// A code resolver.
 ///
@@ -26,7 +31,7 @@ pub trait Resolver<H> {
 	fn resolve_call(
 		&self,
 		code_address: H160,
-		input: Vec<u8>,
+		input: Shared<Vec<u8>>,
 		state: Self::State,
 		handler: &mut H,
 	) -> Result<InvokerControl<Self::Interpreter, (ExitResult, (Self::State, Vec<u8>))>, ExitError>;
@@ -35,7 +40,7 @@ pub trait Resolver<H> {
 	#[allow(clippy::type_complexity)]
 	fn resolve_create(
 		&self,
-		init_code: Vec<u8>,
+		init_code: Shared<Vec<u8>>,
 		state: Self::State,
 		handler: &mut H,
 	) -> Result<InvokerControl<Self::Interpreter, (ExitResult, (Self::State, Vec<u8>))>, ExitError>;
@@ -68,10 +73,16 @@ impl<S, H> PrecompileSet<S, H> for () {
 
 /// The standard code resolver where the color is an [Etable]. This is usually
 /// what you need.
-pub struct EtableResolver<'config, 'precompile, 'etable, Pre, ES> {
+///
+/// The memory of its machines is stored in `M`, which must be the memory
+/// storage of the etable.
+pub struct EtableResolver<'config, 'precompile, 'etable, Pre, ES, M = Pages> {
 	config: &'config Config,
 	etable: &'etable ES,
 	precompiles: &'precompile Pre,
+	code_cache: Option<&'static dyn AnalysisCache>,
+	fusion: bool,
+	memory_storage: fn() -> M,
 }
 
 impl<'config, 'precompile, 'etable, Pre, ES>
@@ -86,17 +97,97 @@ impl<'config, 'precompile, 'etable, Pre, ES>
 			config,
 			precompiles,
 			etable,
+			code_cache: None,
+			fusion: false,
+			memory_storage: Pages::default,
+		}
+	}
+}
+
+impl<'config, 'precompile, 'etable, Pre, ES, M>
+	EtableResolver<'config, 'precompile, 'etable, Pre, ES, M>
+{
+	/// Share code analysis artifacts of called contracts through the given
+	/// cache, usually created once with [CodeCache::leak].
+	#[cfg(feature = "std")]
+	pub fn with_code_cache(self, code_cache: &'static CodeCache) -> Self {
+		self.with_analysis_cache(code_cache)
+	}
+
+	/// Get code analysis artifacts of called contracts from the given store,
+	/// instead of analyzing their code for every call.
+	pub fn with_analysis_cache(mut self, cache: &'static dyn AnalysisCache) -> Self {
+		self.code_cache = Some(cache);
+		self
+	}
+
+	/// Run called and created code with common sequences of opcodes fused
+	/// into superinstructions (see [EtableInterpreter::with_fusion]). It has
+	/// no effect with etables that are not
+	/// [evm_interpreter::etable::Etable::is_standard].
+	pub fn with_fusion(mut self, fusion: bool) -> Self {
+		self.fusion = fusion;
+		self
+	}
+
+	/// Back the memory of called and created code with storage created by
+	/// `storage`, instead of the default [Pages]. The etable must evaluate
+	/// machines with this storage.
+	pub fn with_memory_storage<M2: MemoryStorage>(
+		self,
+		storage: fn() -> M2,
+	) -> EtableResolver<'config, 'precompile, 'etable, Pre, ES, M2> {
+		EtableResolver {
+			config: self.config,
+			etable: self.etable,
+			precompiles: self.precompiles,
+			code_cache: self.code_cache,
+			fusion: self.fusion,
+			memory_storage: storage,
 		}
 	}
 }
 
-impl<'config, 'precompile, 'etable, H, Pre, ES> Resolver<H>
-	for EtableResolver<'config, 'precompile, 'etable, Pre, ES>
+impl<'config, 'precompile, 'etable, Pre, ES, M>
+	EtableResolver<'config, 'precompile, 'etable, Pre, ES, M>
+where
+	ES: EtableSet<Memory = M>,
+	ES::State: AsRef<RuntimeState>,
+	M: MemoryStorage,
+{
+	/// Machine of a frame, with the limits of its state over the ones of the
+	/// config.
+	fn machine(
+		&self,
+		code: Shared<Vec<u8>>,
+		data: Shared<Vec<u8>>,
+		state: ES::State,
+	) -> Machine<ES::State, M> {
+		let limits = state.as_ref().limits;
+		let stack_limit = limits.stack_limit.unwrap_or(self.config.stack_limit);
+		let memory_limit = limits.memory_limit.unwrap_or(self.config.memory_limit);
+		let memory = Memory::with_storage(memory_limit, (self.memory_storage)());
+
+		Machine::with_memory(code, data, stack_limit, memory, state)
+	}
+
+	fn fuse(&self, interpreter: EtableInterpreter<'etable, ES>) -> EtableInterpreter<'etable, ES> {
+		if self.fusion {
+			interpreter.with_fusion()
+		} else {
+			interpreter
+		}
+	}
+}
+
+impl<'config, 'precompile, 'etable, H, Pre, ES, M> Resolver<H>
+	for EtableResolver<'config, 'precompile, 'etable, Pre, ES, M>
 where
 	ES::State: AsRef<RuntimeState> + AsMut<RuntimeState>,
 	H: RuntimeBackend,
 	Pre: PrecompileSet<ES::State, H>,
-	ES: EtableSet<Handle = H>,
+	ES: EtableSet<Handle = H, Memory = M>,
+	M: MemoryStorage,
 {
 	type State = ES::State;
 	type Interpreter = EtableInterpreter<'etable, ES>;
@@ -106,7 +197,7 @@ where
 	fn resolve_call(
 		&self,
 		code_address: H160,
-		input: Vec<u8>,
+		input: Shared<Vec<u8>>,
 		mut state: ES::State,
 		handler: &mut H,
 	) -> Result<InvokerControl<Self::Interpreter, (ExitResult, (ES::State, Vec<u8>))>, ExitError> {
@@ -119,15 +210,23 @@ where
 
 		let code = handler.code(code_address);
 
-		let machine = Machine::<ES::State>::new(
-			Rc::new(code),
-			Rc::new(input),
-			self.config.stack_limit,
-			self.config.memory_limit,
-			state,
-		);
+		let machine = self.machine(Shared::new(code), input, state);
+
+		let interpreter = if let Some(code_cache) = self.code_cache {
+			let artifacts =
+				code_cache.get_or_analyze(handler.code_hash(code_address), machine.code());
+			let interpreter =
+				EtableInterpreter::new_with_valids(machine, self.etable, artifacts.valids);
+			if self.fusion {
+				interpreter.with_fusions(artifacts.fusions)
+			} else {
+				interpreter
+			}
+		} else {
+			self.fuse(EtableInterpreter::new(machine, self.etable))
+		};
 
-		let ret = InvokerControl::Enter(EtableInterpreter::new(machine, self.etable));
+		let ret = InvokerControl::Enter(interpreter);
 
 		Ok(ret)
 	}
@@ -136,19 +235,13 @@ where
 	#[allow(clippy::type_complexity)]
 	fn resolve_create(
 		&self,
-		init_code: Vec<u8>,
+		init_code: Shared<Vec<u8>>,
 		state: ES::State,
 		_handler: &mut H,
 	) -> Result<InvokerControl<Self::Interpreter, (ExitResult, (ES::State, Vec<u8>))>, ExitError> {
-		let machine = Machine::new(
-			Rc::new(init_code),
-			Rc::new(Vec::new()),
-			self.config.stack_limit,
-			self.config.memory_limit,
-			state,
-		);
-
-		let ret = InvokerControl::Enter(EtableInterpreter::new(machine, self.etable));
+		let machine = self.machine(init_code, Shared::new(Vec::new()), state);
+
+		let ret = InvokerControl::Enter(self.fuse(EtableInterpreter::new(machine, self.etable)));
 
 		Ok(ret)
 	}
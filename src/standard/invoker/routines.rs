@@ -4,6 +4,7 @@ use evm_interpreter::{
 	error::{CallTrapData, CreateTrapData, ExitError, ExitException, ExitResult},
 	opcode::Opcode,
 	runtime::{RuntimeBackend, RuntimeEnvironment, RuntimeState, SetCodeOrigin, Transfer},
+	Shared,
 };
 use primitive_types::{H160, U256};
 
@@ -19,7 +20,7 @@ pub fn make_enter_call_machine<H, R>(
 	_config: &Config,
 	resolver: &R,
 	code_address: H160,
-	input: Vec<u8>,
+	input: Shared<Vec<u8>>,
 	transfer: Option<Transfer>,
 	state: R::State,
 	handler: &mut H,
@@ -43,7 +44,7 @@ pub fn make_enter_create_machine<H, R>(
 	config: &Config,
 	resolver: &R,
 	caller: H160,
-	init_code: Vec<u8>,
+	init_code: Shared<Vec<u8>>,
 	transfer: Transfer,
 	state: R::State,
 	handler: &mut H,
@@ -75,6 +76,7 @@ where
 	}
 
 	handler.reset_storage(state.as_ref().context.address);
+	handler.mark_create(state.as_ref().context.address);
 
 	resolver.resolve_create(init_code, state, handler)
 }
@@ -128,8 +130,9 @@ where
 pub fn enter_create_substack<H, R>(
 	config: &Config,
 	resolver: &R,
-	code: Vec<u8>,
+	code: Shared<Vec<u8>>,
 	trap_data: CreateTrapData,
+	address: H160,
 	state: R::State,
 	handler: &mut H,
 ) -> Result<
@@ -154,7 +157,6 @@ where
 		} = trap_data.clone();
 
 		let caller = scheme.caller();
-		let address = scheme.address(handler);
 
 		let transfer = Transfer {
 			source: caller,
@@ -6,7 +6,11 @@ use evm_interpreter::{
 };
 use primitive_types::{H160, H256, U256};
 
-use crate::{standard::Config, MergeStrategy};
+use super::TransactOverrides;
+use crate::{
+	standard::{Config, GasFrames, GasProfile, RefundAudit},
+	MergeStrategy,
+};
 
 pub trait InvokerState<'config>: GasState + Sized {
 	fn new_transact_call(
@@ -14,6 +18,7 @@ pub trait InvokerState<'config>: GasState + Sized {
 		gas_limit: U256,
 		data: &[u8],
 		access_list: &[(H160, Vec<H256>)],
+		overrides: &TransactOverrides,
 		config: &'config Config,
 	) -> Result<Self, ExitError>;
 	fn new_transact_create(
@@ -21,6 +26,7 @@ pub trait InvokerState<'config>: GasState + Sized {
 		gas_limit: U256,
 		code: &[u8],
 		access_list: &[(H160, Vec<H256>)],
+		overrides: &TransactOverrides,
 		config: &'config Config,
 	) -> Result<Self, ExitError>;
 
@@ -37,5 +43,31 @@ pub trait InvokerState<'config>: GasState + Sized {
 
 	fn is_static(&self) -> bool;
 	fn effective_gas(&self) -> U256;
+	/// Gas used, before refunds.
+	fn used_gas(&self) -> U256;
+	/// Gas refunded, capped by the refund quotient.
+	fn refunded_gas(&self) -> U256;
+	/// Minimum gas charged for the transaction (see EIP-7623).
+	fn floor_gas(&self) -> U256;
+	/// Start accumulating a gas profile of the opcodes executed, including in
+	/// substates.
+	fn enable_gas_profile(&mut self) {}
+	/// Gas profile accumulated, if enabled and supported.
+	fn gas_profile(&self) -> Option<GasProfile> {
+		None
+	}
+	/// Start recording the gas of this frame and of all substates as a tree
+	/// of frames.
+	fn enable_gas_frames(&mut self) {}
+	/// Gas of the frames recorded, if enabled and supported.
+	fn gas_frames(&self) -> Option<GasFrames> {
+		None
+	}
+	/// Start recording every refund, including in substates.
+	fn enable_refund_audit(&mut self) {}
+	/// Refunds recorded, if enabled and supported.
+	fn refund_audit(&self) -> Option<RefundAudit> {
+		None
+	}
 	fn config(&self) -> &Config;
 }
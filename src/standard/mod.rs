@@ -4,104 +4,147 @@
 //! Ethereum mainnet. Most of them can still be customized to add additional functionality, by
 //! wrapping them or replacing the generic parameters.
 
+mod analysis;
+mod block;
+#[cfg(feature = "serde")]
+mod chain_spec;
 mod config;
+mod fork_schedule;
 mod gasometer;
 mod invoker;
+#[cfg(feature = "transaction")]
+mod transaction;
 
 use alloc::vec::Vec;
 
 use evm_interpreter::{
 	error::{CallCreateTrap, ExitError},
-	etable, machine,
+	etable,
+	machine::{self, Pages},
 	runtime::{GasState, RuntimeState},
 };
 use primitive_types::{H160, H256, U256};
 
+#[cfg(feature = "serde")]
+pub use self::chain_spec::{from_chain_spec, ChainConfig, ChainSpec, ChainSpecError};
+#[cfg(feature = "tracing")]
+pub use self::gasometer::tracing;
+#[cfg(feature = "op")]
+pub use self::invoker::{op_config, DepositInfo, DepositTransaction, L1CostHook, L1_FEE_VAULT};
+#[cfg(feature = "std")]
+pub use self::invoker::{CodeCache, DEFAULT_CODE_CACHE_SIZE};
+#[cfg(feature = "transaction")]
+pub use self::transaction::{
+	AccessList, Eip1559Transaction, Eip2930Transaction, LegacyTransaction, Transaction,
+	TransactionAction, TransactionError,
+};
 pub use self::{
-	config::Config,
-	gasometer::{eval as eval_gasometer, GasometerState},
+	analysis::{analyze_code, CodeAnalysis},
+	block::{apply_withdrawals, execute_block, BlockError, Receipt, Withdrawal},
+	config::{Config, ConfigBuilder, ConfigError, DynConfig, GasSchedule},
+	fork_schedule::{ForkActivation, ForkSchedule},
+	gasometer::{
+		eval as eval_gasometer, intrinsic_gas, FrameGas, GasFrames, GasInt, GasProfile,
+		GasometerState, IntrinsicGas, OpcodeGas, RefundAudit, RefundEvent, TransactionKind,
+	},
 	invoker::{
-		routines, EtableResolver, Invoker, InvokerState, PrecompileSet, Resolver, SubstackInvoke,
-		TransactArgs, TransactInvoke, TransactValue,
+		routines, transact_with_gas, AddressScheme, AnalysisCache, CallInterceptor, CodeArtifacts,
+		CreateFilter, EtableResolver, FrameHook, FrameInfo, FrameScheme, Invoker, InvokerState,
+		NoncePolicy, PrecompileSet, Reentrancy, ReentrancyGuard, ReentrancyMode, Resolver,
+		SubstackInvoke, TransactArgs, TransactGas, TransactInvoke, TransactOverrides,
+		TransactValue, SYSTEM_ADDRESS, SYSTEM_CALL_GAS_LIMIT,
 	},
 };
 use crate::{gasometer::GasMutState, MergeStrategy};
 
 /// Standard machine.
-pub type Machine<'config> = machine::Machine<State<'config>>;
+pub type Machine<'config, M = Pages> = machine::Machine<State<'config>, M>;
 
 /// Standard Etable opcode handle function.
-pub type Efn<'config, H> = etable::Efn<State<'config>, H, CallCreateTrap>;
+pub type Efn<'config, H, M = Pages> = etable::Efn<State<'config>, H, CallCreateTrap, M>;
 
 /// Standard Etable.
-pub type Etable<'config, H, F = Efn<'config, H>> =
-	etable::Etable<State<'config>, H, CallCreateTrap, F>;
+pub type Etable<'config, H, M = Pages, F = Efn<'config, H, M>> =
+	etable::Etable<State<'config>, H, CallCreateTrap, M, F>;
 
-pub struct State<'config> {
+/// Standard state, with the gas integer type `G` (see [GasInt]).
+pub struct State<'config, G = u64> {
 	pub runtime: RuntimeState,
-	pub gasometer: GasometerState<'config>,
+	pub gasometer: GasometerState<'config, G>,
 }
 
-impl<'config> AsRef<RuntimeState> for State<'config> {
+/// Standard state supporting gas limits beyond `u64::MAX`. Use it as the
+/// state of the resolver to select it for the invoker.
+pub type WideState<'config> = State<'config, U256>;
+
+impl<'config, G> AsRef<RuntimeState> for State<'config, G> {
 	fn as_ref(&self) -> &RuntimeState {
 		&self.runtime
 	}
 }
 
-impl<'config> AsMut<RuntimeState> for State<'config> {
+impl<'config, G> AsMut<RuntimeState> for State<'config, G> {
 	fn as_mut(&mut self) -> &mut RuntimeState {
 		&mut self.runtime
 	}
 }
 
-impl<'config> AsRef<GasometerState<'config>> for State<'config> {
-	fn as_ref(&self) -> &GasometerState<'config> {
+impl<'config, G> AsRef<GasometerState<'config, G>> for State<'config, G> {
+	fn as_ref(&self) -> &GasometerState<'config, G> {
 		&self.gasometer
 	}
 }
 
-impl<'config> AsMut<GasometerState<'config>> for State<'config> {
-	fn as_mut(&mut self) -> &mut GasometerState<'config> {
+impl<'config, G> AsMut<GasometerState<'config, G>> for State<'config, G> {
+	fn as_mut(&mut self) -> &mut GasometerState<'config, G> {
 		&mut self.gasometer
 	}
 }
 
-impl<'config> GasState for State<'config> {
+impl<'config, G: GasInt> GasState for State<'config, G> {
 	fn gas(&self) -> U256 {
 		self.gasometer.gas()
 	}
 }
 
-impl<'config> GasMutState for State<'config> {
+impl<'config, G: GasInt> GasMutState for State<'config, G> {
 	fn record_gas(&mut self, gas: U256) -> Result<(), ExitError> {
 		self.gasometer.record_gas(gas)
 	}
 }
 
-impl<'config> InvokerState<'config> for State<'config> {
+impl<'config, G: GasInt> InvokerState<'config> for State<'config, G> {
 	fn new_transact_call(
 		runtime: RuntimeState,
 		gas_limit: U256,
 		data: &[u8],
 		access_list: &[(H160, Vec<H256>)],
+		overrides: &TransactOverrides,
 		config: &'config Config,
 	) -> Result<Self, ExitError> {
-		Ok(Self {
-			runtime,
-			gasometer: GasometerState::new_transact_call(gas_limit, data, access_list, config)?,
-		})
+		let gasometer = if overrides.zero_intrinsic_gas {
+			GasometerState::new_transact(gas_limit, config)?
+		} else {
+			GasometerState::new_transact_call(gas_limit, data, access_list, config)?
+		};
+
+		Ok(Self { runtime, gasometer })
 	}
 	fn new_transact_create(
 		runtime: RuntimeState,
 		gas_limit: U256,
 		code: &[u8],
 		access_list: &[(H160, Vec<H256>)],
+		overrides: &TransactOverrides,
 		config: &'config Config,
 	) -> Result<Self, ExitError> {
-		Ok(Self {
-			runtime,
-			gasometer: GasometerState::new_transact_create(gas_limit, code, access_list, config)?,
-		})
+		let gasometer = if overrides.zero_intrinsic_gas {
+			GasometerState::new_transact(gas_limit, config)?
+		} else {
+			GasometerState::new_transact_create(gas_limit, code, access_list, config)?
+		};
+
+		Ok(Self { runtime, gasometer })
 	}
 
 	fn substate(
@@ -111,12 +154,12 @@ impl<'config> InvokerState<'config> for State<'config> {
 		is_static: bool,
 		call_has_value: bool,
 	) -> Result<Self, ExitError> {
-		Ok(Self {
-			runtime,
-			gasometer: self
-				.gasometer
-				.submeter(gas_limit, is_static, call_has_value)?,
-		})
+		let mut gasometer = self
+			.gasometer
+			.submeter(gas_limit, is_static, call_has_value)?;
+		gasometer.set_frame_address(runtime.context.address);
+
+		Ok(Self { runtime, gasometer })
 	}
 	fn merge(&mut self, substate: Self, strategy: MergeStrategy) {
 		self.gasometer.merge(substate.gasometer, strategy)
@@ -132,6 +175,33 @@ impl<'config> InvokerState<'config> for State<'config> {
 	fn effective_gas(&self) -> U256 {
 		self.gasometer.effective_gas()
 	}
+	fn used_gas(&self) -> U256 {
+		self.gasometer.total_used_gas().into()
+	}
+	fn refunded_gas(&self) -> U256 {
+		self.gasometer.applied_refund().into()
+	}
+	fn floor_gas(&self) -> U256 {
+		self.gasometer.floor_gas().into()
+	}
+	fn enable_gas_profile(&mut self) {
+		self.gasometer.enable_profile()
+	}
+	fn gas_profile(&self) -> Option<GasProfile> {
+		self.gasometer.profile()
+	}
+	fn enable_gas_frames(&mut self) {
+		self.gasometer.enable_frames(self.runtime.context.address)
+	}
+	fn gas_frames(&self) -> Option<GasFrames> {
+		self.gasometer.frames()
+	}
+	fn enable_refund_audit(&mut self) {
+		self.gasometer.enable_refund_audit()
+	}
+	fn refund_audit(&self) -> Option<RefundAudit> {
+		self.gasometer.refund_audit()
+	}
 	fn config(&self) -> &Config {
 		self.gasometer.config
 	}
@@ -0,0 +1,435 @@
+//! Signed transactions, as they are included in blocks.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use evm_interpreter::utils::{keccak256, keccak256_parts};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use primitive_types::{H160, H256, U256};
+use rlp::{DecoderError, Rlp, RlpStream};
+
+use crate::standard::TransactArgs;
+
+/// Target of a transaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransactionAction {
+	/// Call an address.
+	Call(H160),
+	/// Create a contract.
+	Create,
+}
+
+/// Access list, in the format of (address, storage keys).
+pub type AccessList = Vec<(H160, Vec<H256>)>;
+
+/// Legacy transaction, optionally replay-protected by
+/// [EIP-155](https://eips.ethereum.org/EIPS/eip-155).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegacyTransaction {
+	pub nonce: U256,
+	pub gas_price: U256,
+	pub gas_limit: U256,
+	pub action: TransactionAction,
+	pub value: U256,
+	pub input: Vec<u8>,
+	/// `27` or `28`, or `chain_id * 2 + 35` or `chain_id * 2 + 36` with
+	/// EIP-155.
+	pub v: u64,
+	pub r: H256,
+	pub s: H256,
+}
+
+impl LegacyTransaction {
+	/// Chain ID the transaction is signed for, `None` if it is not
+	/// replay-protected.
+	pub fn chain_id(&self) -> Option<u64> {
+		if self.v >= 35 {
+			Some((self.v - 35) / 2)
+		} else {
+			None
+		}
+	}
+}
+
+/// Transaction with an access list, of
+/// [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Eip2930Transaction {
+	pub chain_id: u64,
+	pub nonce: U256,
+	pub gas_price: U256,
+	pub gas_limit: U256,
+	pub action: TransactionAction,
+	pub value: U256,
+	pub input: Vec<u8>,
+	pub access_list: AccessList,
+	pub odd_y_parity: bool,
+	pub r: H256,
+	pub s: H256,
+}
+
+/// Transaction with a priority fee, of
+/// [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Eip1559Transaction {
+	pub chain_id: u64,
+	pub nonce: U256,
+	pub max_priority_fee_per_gas: U256,
+	pub max_fee_per_gas: U256,
+	pub gas_limit: U256,
+	pub action: TransactionAction,
+	pub value: U256,
+	pub input: Vec<u8>,
+	pub access_list: AccessList,
+	pub odd_y_parity: bool,
+	pub r: H256,
+	pub s: H256,
+}
+
+/// Signed transaction of any supported type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Transaction {
+	Legacy(LegacyTransaction),
+	Eip2930(Eip2930Transaction),
+	Eip1559(Eip1559Transaction),
+}
+
+/// Error decoding a transaction or recovering its sender.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TransactionError {
+	/// Invalid RLP encoding.
+	Rlp(DecoderError),
+	/// Unknown transaction type byte.
+	UnsupportedType(u8),
+	/// The transaction is signed for another chain.
+	InvalidChainId,
+	/// The signature is malformed or does not recover a key.
+	InvalidSignature,
+	/// The `s` value of the signature is in the upper half of the curve
+	/// order, forbidden by [EIP-2](https://eips.ethereum.org/EIPS/eip-2).
+	HighS,
+}
+
+impl From<DecoderError> for TransactionError {
+	fn from(err: DecoderError) -> Self {
+		Self::Rlp(err)
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TransactionError {}
+
+impl fmt::Display for TransactionError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Rlp(err) => write!(f, "invalid transaction encoding: {}", err),
+			Self::UnsupportedType(ty) => write!(f, "unsupported transaction type {}", ty),
+			Self::InvalidChainId => write!(f, "transaction signed for another chain"),
+			Self::InvalidSignature => write!(f, "invalid transaction signature"),
+			Self::HighS => write!(f, "transaction signature s value too high"),
+		}
+	}
+}
+
+fn decode_action(rlp: &Rlp<'_>) -> Result<TransactionAction, DecoderError> {
+	if rlp.is_empty() {
+		if rlp.is_data() {
+			Ok(TransactionAction::Create)
+		} else {
+			Err(DecoderError::RlpExpectedToBeData)
+		}
+	} else {
+		Ok(TransactionAction::Call(rlp.as_val()?))
+	}
+}
+
+fn append_action(stream: &mut RlpStream, action: &TransactionAction) {
+	match action {
+		TransactionAction::Call(address) => stream.append(address),
+		TransactionAction::Create => stream.append_empty_data(),
+	};
+}
+
+fn decode_access_list(rlp: &Rlp<'_>) -> Result<AccessList, DecoderError> {
+	rlp.iter()
+		.map(|item| {
+			if item.item_count()? != 2 {
+				return Err(DecoderError::RlpIncorrectListLen);
+			}
+			Ok((item.val_at(0)?, item.list_at(1)?))
+		})
+		.collect()
+}
+
+fn append_access_list(stream: &mut RlpStream, access_list: &AccessList) {
+	stream.begin_list(access_list.len());
+	for (address, keys) in access_list {
+		stream.begin_list(2);
+		stream.append(address);
+		stream.append_list(keys);
+	}
+}
+
+/// Decode a list of `count` items spanning the whole input.
+fn decode_list(bytes: &[u8], count: usize) -> Result<Rlp<'_>, DecoderError> {
+	let rlp = Rlp::new(bytes);
+	if rlp.payload_info()?.total() != bytes.len() {
+		return Err(DecoderError::RlpInconsistentLengthAndData);
+	}
+	if rlp.item_count()? != count {
+		return Err(DecoderError::RlpIncorrectListLen);
+	}
+	Ok(rlp)
+}
+
+fn decode_parity(rlp: &Rlp<'_>, index: usize) -> Result<bool, DecoderError> {
+	match rlp.val_at::<u8>(index)? {
+		0 => Ok(false),
+		1 => Ok(true),
+		_ => Err(DecoderError::Custom("invalid y parity")),
+	}
+}
+
+fn decode_scalar(rlp: &Rlp<'_>, index: usize) -> Result<H256, DecoderError> {
+	let value: U256 = rlp.val_at(index)?;
+	let mut scalar = H256::zero();
+	value.to_big_endian(&mut scalar.0);
+	Ok(scalar)
+}
+
+/// Recover the address signing `hash`, rejecting high `s` values from
+/// Homestead on.
+fn recover(
+	hash: H256,
+	mut odd_y_parity: bool,
+	r: H256,
+	s: H256,
+	homestead: bool,
+) -> Result<H160, TransactionError> {
+	let mut signature =
+		Signature::from_scalars(r.0, s.0).map_err(|_| TransactionError::InvalidSignature)?;
+	if let Some(normalized) = signature.normalize_s() {
+		if homestead {
+			return Err(TransactionError::HighS);
+		}
+		// Negating `s` negates the recovered point, flipping its parity.
+		signature = normalized;
+		odd_y_parity = !odd_y_parity;
+	}
+
+	let key = VerifyingKey::recover_from_prehash(
+		&hash[..],
+		&signature,
+		RecoveryId::new(odd_y_parity, false),
+	)
+	.map_err(|_| TransactionError::InvalidSignature)?;
+	let hash = keccak256(&key.to_encoded_point(false).as_bytes()[1..]);
+	Ok(H160::from_slice(&hash[12..]))
+}
+
+impl Transaction {
+	/// Decode a transaction in its network encoding: an RLP list for legacy
+	/// transactions, and the type byte followed by the RLP payload for
+	/// [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718) typed ones.
+	pub fn decode(bytes: &[u8]) -> Result<Self, TransactionError> {
+		match bytes.first() {
+			None => Err(DecoderError::RlpIsTooShort.into()),
+			Some(first) if *first >= 0xc0 => {
+				let rlp = decode_list(bytes, 9)?;
+				Ok(Self::Legacy(LegacyTransaction {
+					nonce: rlp.val_at(0)?,
+					gas_price: rlp.val_at(1)?,
+					gas_limit: rlp.val_at(2)?,
+					action: decode_action(&rlp.at(3)?)?,
+					value: rlp.val_at(4)?,
+					input: rlp.val_at(5)?,
+					v: rlp.val_at(6)?,
+					r: decode_scalar(&rlp, 7)?,
+					s: decode_scalar(&rlp, 8)?,
+				}))
+			}
+			Some(1) => {
+				let rlp = decode_list(&bytes[1..], 11)?;
+				Ok(Self::Eip2930(Eip2930Transaction {
+					chain_id: rlp.val_at(0)?,
+					nonce: rlp.val_at(1)?,
+					gas_price: rlp.val_at(2)?,
+					gas_limit: rlp.val_at(3)?,
+					action: decode_action(&rlp.at(4)?)?,
+					value: rlp.val_at(5)?,
+					input: rlp.val_at(6)?,
+					access_list: decode_access_list(&rlp.at(7)?)?,
+					odd_y_parity: decode_parity(&rlp, 8)?,
+					r: decode_scalar(&rlp, 9)?,
+					s: decode_scalar(&rlp, 10)?,
+				}))
+			}
+			Some(2) => {
+				let rlp = decode_list(&bytes[1..], 12)?;
+				Ok(Self::Eip1559(Eip1559Transaction {
+					chain_id: rlp.val_at(0)?,
+					nonce: rlp.val_at(1)?,
+					max_priority_fee_per_gas: rlp.val_at(2)?,
+					max_fee_per_gas: rlp.val_at(3)?,
+					gas_limit: rlp.val_at(4)?,
+					action: decode_action(&rlp.at(5)?)?,
+					value: rlp.val_at(6)?,
+					input: rlp.val_at(7)?,
+					access_list: decode_access_list(&rlp.at(8)?)?,
+					odd_y_parity: decode_parity(&rlp, 9)?,
+					r: decode_scalar(&rlp, 10)?,
+					s: decode_scalar(&rlp, 11)?,
+				}))
+			}
+			Some(ty) => Err(TransactionError::UnsupportedType(*ty)),
+		}
+	}
+
+	/// Hash signed by the sender.
+	pub fn signing_hash(&self) -> H256 {
+		let mut stream = RlpStream::new();
+		let mut prefix = None;
+		match self {
+			Self::Legacy(tx) => {
+				stream.begin_list(if tx.chain_id().is_some() { 9 } else { 6 });
+				stream.append(&tx.nonce);
+				stream.append(&tx.gas_price);
+				stream.append(&tx.gas_limit);
+				append_action(&mut stream, &tx.action);
+				stream.append(&tx.value);
+				stream.append(&tx.input);
+				if let Some(chain_id) = tx.chain_id() {
+					stream.append(&chain_id);
+					stream.append(&0u8);
+					stream.append(&0u8);
+				}
+			}
+			Self::Eip2930(tx) => {
+				prefix = Some(1);
+				stream.begin_list(8);
+				stream.append(&tx.chain_id);
+				stream.append(&tx.nonce);
+				stream.append(&tx.gas_price);
+				stream.append(&tx.gas_limit);
+				append_action(&mut stream, &tx.action);
+				stream.append(&tx.value);
+				stream.append(&tx.input);
+				append_access_list(&mut stream, &tx.access_list);
+			}
+			Self::Eip1559(tx) => {
+				prefix = Some(2);
+				stream.begin_list(9);
+				stream.append(&tx.chain_id);
+				stream.append(&tx.nonce);
+				stream.append(&tx.max_priority_fee_per_gas);
+				stream.append(&tx.max_fee_per_gas);
+				stream.append(&tx.gas_limit);
+				append_action(&mut stream, &tx.action);
+				stream.append(&tx.value);
+				stream.append(&tx.input);
+				append_access_list(&mut stream, &tx.access_list);
+			}
+		}
+
+		match prefix {
+			Some(prefix) => keccak256_parts(&[&[prefix], &stream.out()]),
+			None => keccak256(&stream.out()),
+		}
+	}
+
+	/// Recover the sender of the transaction, checking that it is signed
+	/// for `chain_id`. Legacy transactions without replay protection are
+	/// accepted on any chain. High `s` values are only rejected if
+	/// `homestead` is set, for blocks from the Homestead fork on.
+	pub fn recover_caller(&self, chain_id: u64, homestead: bool) -> Result<H160, TransactionError> {
+		let (signed_chain_id, odd_y_parity, r, s) = match self {
+			Self::Legacy(tx) => {
+				let odd_y_parity = match (tx.chain_id(), tx.v) {
+					(Some(_), v) => (v - 35) % 2 == 1,
+					(None, 27) => false,
+					(None, 28) => true,
+					(None, _) => return Err(TransactionError::InvalidSignature),
+				};
+				(tx.chain_id(), odd_y_parity, tx.r, tx.s)
+			}
+			Self::Eip2930(tx) => (Some(tx.chain_id), tx.odd_y_parity, tx.r, tx.s),
+			Self::Eip1559(tx) => (Some(tx.chain_id), tx.odd_y_parity, tx.r, tx.s),
+		};
+		if signed_chain_id.map_or(false, |signed| signed != chain_id) {
+			return Err(TransactionError::InvalidChainId);
+		}
+
+		recover(self.signing_hash(), odd_y_parity, r, s, homestead)
+	}
+
+	/// Arguments to execute the transaction from `caller`, usually
+	/// [Transaction::recover_caller].
+	pub fn transact_args(&self, caller: H160) -> TransactArgs {
+		let nonce = match self {
+			Self::Legacy(tx) => tx.nonce,
+			Self::Eip2930(tx) => tx.nonce,
+			Self::Eip1559(tx) => tx.nonce,
+		};
+		let (gas_price, max_priority_fee_per_gas, gas_limit, action, value, input, access_list) =
+			match self {
+				Self::Legacy(tx) => (
+					tx.gas_price,
+					None,
+					tx.gas_limit,
+					tx.action,
+					tx.value,
+					&tx.input,
+					Vec::new(),
+				),
+				Self::Eip2930(tx) => (
+					tx.gas_price,
+					None,
+					tx.gas_limit,
+					tx.action,
+					tx.value,
+					&tx.input,
+					tx.access_list.clone(),
+				),
+				Self::Eip1559(tx) => (
+					tx.max_fee_per_gas,
+					Some(tx.max_priority_fee_per_gas),
+					tx.gas_limit,
+					tx.action,
+					tx.value,
+					&tx.input,
+					tx.access_list.clone(),
+				),
+			};
+
+		match action {
+			TransactionAction::Call(address) => TransactArgs::Call {
+				caller,
+				address,
+				value,
+				data: input.clone(),
+				nonce: Some(nonce),
+				fee_payer: None,
+				gas_limit,
+				gas_price,
+				max_priority_fee_per_gas,
+				access_list,
+				blob_hashes: Vec::new(),
+				max_fee_per_blob_gas: None,
+				overrides: Default::default(),
+			},
+			TransactionAction::Create => TransactArgs::Create {
+				caller,
+				value,
+				init_code: input.clone(),
+				salt: None,
+				nonce: Some(nonce),
+				fee_payer: None,
+				gas_limit,
+				gas_price,
+				max_priority_fee_per_gas,
+				access_list,
+				overrides: Default::default(),
+			},
+		}
+	}
+}
@@ -0,0 +1,72 @@
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+	interpreter::error::{ExitError, ExitException},
+	standard::{transact_with_gas, Config, Etable, EtableResolver, Invoker, TransactArgs},
+};
+use primitive_types::{H160, H256, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const RECIPIENT: H160 = H160::repeat_byte(0x22);
+
+/// Transaction with `blobs` blobs, at the minimum blob base fee.
+fn transact_blobs(config: &Config, blobs: usize) -> Result<(), ExitError> {
+	let backend = InMemoryBackend::from_genesis(
+		InMemoryEnvironment {
+			block_gas_limit: U256::from(30_000_000),
+			..Default::default()
+		},
+		[(
+			CALLER,
+			InMemoryAccount {
+				balance: U256::from(10_000_000),
+				..Default::default()
+			},
+		)]
+		.into(),
+	);
+
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let resolver = EtableResolver::new(config, &(), &etable);
+	let invoker = Invoker::new(config, &resolver);
+	let mut blob_hash = H256::zero();
+	blob_hash.0[0] = 0x01;
+	let args = TransactArgs::Call {
+		caller: CALLER,
+		address: RECIPIENT,
+		value: U256::zero(),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: Some(U256::zero()),
+		access_list: Vec::new(),
+		blob_hashes: vec![blob_hash; blobs],
+		max_fee_per_blob_gas: Some(U256::one()),
+		overrides: Default::default(),
+	};
+
+	let mut overlay = backend.overlay();
+	let (result, _) = transact_with_gas(args, None, &mut overlay, &invoker)?;
+	result.map(|_| ())
+}
+
+#[test]
+fn blobs_per_transaction_are_limited() {
+	assert_eq!(Config::cancun().max_blobs_per_tx, 6);
+	assert_eq!(transact_blobs(&Config::cancun(), 6), Ok(()));
+	assert_eq!(
+		transact_blobs(&Config::cancun(), 7),
+		Err(ExitException::TooManyBlobs.into())
+	);
+
+	assert_eq!(Config::prague().max_blobs_per_tx, 9);
+	assert_eq!(transact_blobs(&Config::prague(), 9), Ok(()));
+	assert_eq!(
+		transact_blobs(&Config::prague(), 10),
+		Err(ExitException::TooManyBlobs.into())
+	);
+}
@@ -0,0 +1,174 @@
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+	interpreter::{runtime::Bloom, utils::keccak256},
+	standard::{
+		execute_block, BlockError, Config, Etable, EtableResolver, Invoker, Receipt, TransactArgs,
+	},
+};
+use primitive_types::{H160, H256, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const LOGGER: H160 = H160::repeat_byte(0x22);
+const REVERTER: H160 = H160::repeat_byte(0x33);
+const RECIPIENT: H160 = H160::repeat_byte(0x44);
+
+/// Store 42, and log it with topic 1.
+const LOG: &str = "602a600052600160206000a1";
+/// Revert, without data.
+const REVERT: &str = "60006000fd";
+
+fn backend(block_gas_limit: u64) -> InMemoryBackend {
+	InMemoryBackend::from_genesis(
+		InMemoryEnvironment {
+			block_gas_limit: U256::from(block_gas_limit),
+			..Default::default()
+		},
+		[
+			(
+				CALLER,
+				InMemoryAccount {
+					balance: U256::from(1_000_000),
+					..Default::default()
+				},
+			),
+			(
+				LOGGER,
+				InMemoryAccount {
+					code: hex::decode(LOG).unwrap(),
+					..Default::default()
+				},
+			),
+			(
+				REVERTER,
+				InMemoryAccount {
+					code: hex::decode(format!("{}{}", LOG, REVERT)).unwrap(),
+					..Default::default()
+				},
+			),
+		]
+		.into(),
+	)
+}
+
+fn call(address: H160, value: u64, gas_limit: u64) -> TransactArgs {
+	TransactArgs::Call {
+		caller: CALLER,
+		address,
+		value: U256::from(value),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(gas_limit),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	}
+}
+
+fn execute(
+	backend: &mut InMemoryBackend,
+	transactions: Vec<TransactArgs>,
+) -> Result<Vec<Receipt>, BlockError> {
+	let config = Config::shanghai();
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let resolver = EtableResolver::new(&config, &(), &etable);
+	let invoker = Invoker::new(&config, &resolver);
+
+	execute_block(transactions, None, backend, &invoker).1
+}
+
+/// Bloom with the bits of `inputs` set, as specified by the yellow paper.
+fn bloom(inputs: &[&[u8]]) -> Bloom {
+	let mut bloom = Bloom([0; 256]);
+	for input in inputs {
+		let hash = keccak256(input);
+		for i in 0..3 {
+			let bit = (usize::from(hash[2 * i]) << 8 | usize::from(hash[2 * i + 1])) & 2047;
+			bloom.0[255 - bit / 8] |= 1 << (bit % 8);
+		}
+	}
+	bloom
+}
+
+#[test]
+fn receipts_accumulate_gas() {
+	let mut backend = self::backend(30_000_000);
+	let receipts = execute(
+		&mut backend,
+		vec![
+			call(LOGGER, 0, 100_000),
+			call(REVERTER, 0, 100_000),
+			call(RECIPIENT, 1000, 100_000),
+		],
+	)
+	.unwrap();
+
+	assert_eq!(receipts.len(), 3);
+	assert!(receipts[0].status);
+	assert!(!receipts[1].status);
+	assert!(receipts[2].status);
+	assert_eq!(receipts[2].gas_used, U256::from(21_000));
+
+	let mut cumulative_gas_used = U256::zero();
+	for receipt in &receipts {
+		assert!(receipt.gas_used > U256::zero());
+		cumulative_gas_used += receipt.gas_used;
+		assert_eq!(receipt.cumulative_gas_used, cumulative_gas_used);
+	}
+
+	assert_eq!(backend.state[&RECIPIENT].balance, U256::from(1000));
+	assert_eq!(backend.state[&CALLER].nonce, U256::from(3));
+}
+
+#[test]
+fn failed_transaction_has_receipt_without_logs() {
+	let mut backend = self::backend(30_000_000);
+	let receipts = execute(&mut backend, vec![call(REVERTER, 0, 100_000)]).unwrap();
+
+	assert_eq!(receipts.len(), 1);
+	assert!(!receipts[0].status);
+	assert!(receipts[0].logs.is_empty());
+	assert!(receipts[0].logs_bloom.is_empty());
+	// The nonce is still increased.
+	assert_eq!(backend.state[&CALLER].nonce, U256::from(1));
+}
+
+#[test]
+fn bloom_has_address_and_topics() {
+	let mut backend = self::backend(30_000_000);
+	let receipts = execute(&mut backend, vec![call(LOGGER, 0, 100_000)]).unwrap();
+
+	let topic = H256::from_low_u64_be(1);
+	assert_eq!(receipts[0].logs.len(), 1);
+	assert_eq!(receipts[0].logs[0].address, LOGGER);
+	assert_eq!(receipts[0].logs[0].topics, [topic]);
+	assert_eq!(
+		receipts[0].logs_bloom,
+		bloom(&[LOGGER.as_bytes(), topic.as_bytes()])
+	);
+	assert!(!receipts[0].logs_bloom.contains_input(RECIPIENT.as_bytes()));
+}
+
+#[test]
+fn gas_limit_reached() {
+	let mut backend = self::backend(50_000);
+	let result = execute(
+		&mut backend,
+		vec![
+			call(RECIPIENT, 1000, 30_000),
+			// Only 29,000 gas is left in the block.
+			call(RECIPIENT, 1000, 30_000),
+		],
+	);
+
+	assert_eq!(result.unwrap_err(), BlockError::GasLimitReached(1));
+	// The changes of the first transaction are applied.
+	assert_eq!(backend.state[&RECIPIENT].balance, U256::from(1000));
+	assert_eq!(backend.state[&CALLER].nonce, U256::from(1));
+}
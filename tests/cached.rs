@@ -0,0 +1,104 @@
+use evm::backend::{
+	CacheStats, CachedBackend, InMemoryAccount, InMemoryBackend, InMemoryEnvironment,
+	RuntimeBaseBackend,
+};
+use primitive_types::{H160, H256, U256};
+
+const A: H160 = H160::repeat_byte(0xaa);
+const B: H160 = H160::repeat_byte(0xbb);
+
+fn backend() -> InMemoryBackend {
+	let account = InMemoryAccount {
+		balance: U256::from(1000),
+		code: vec![0x60, 0x00],
+		storage: [(H256::from_low_u64_be(1), H256::from_low_u64_be(2))].into(),
+		..Default::default()
+	};
+	InMemoryBackend::from_genesis(
+		InMemoryEnvironment::default(),
+		[(A, account.clone()), (B, account)].into(),
+	)
+}
+
+fn stats(hits: u64, misses: u64) -> CacheStats {
+	CacheStats { hits, misses }
+}
+
+#[test]
+fn repeated_reads_hit() {
+	let inner = backend();
+	let cached = CachedBackend::new(&inner, 16);
+
+	assert_eq!(cached.balance(A), U256::from(1000));
+	assert_eq!(cached.balance(A), U256::from(1000));
+	assert_eq!(cached.stats(), stats(1, 1));
+
+	// Other fields of the account are fetched separately.
+	assert_eq!(cached.nonce(A), U256::zero());
+	assert!(cached.exists(A));
+	assert_eq!(cached.stats(), stats(1, 3));
+
+	let index = H256::from_low_u64_be(1);
+	assert_eq!(cached.storage(A, index), H256::from_low_u64_be(2));
+	assert_eq!(cached.storage(A, index), H256::from_low_u64_be(2));
+	assert_eq!(cached.stats(), stats(2, 4));
+
+	cached.reset_stats();
+	assert_eq!(cached.stats(), stats(0, 0));
+}
+
+#[test]
+fn code_is_shared_by_hash() {
+	let inner = backend();
+	let cached = CachedBackend::new(&inner, 16);
+
+	assert_eq!(cached.code(A), [0x60, 0x00]);
+	// Code hash and code of `A` miss.
+	assert_eq!(cached.stats(), stats(0, 2));
+
+	// `B` has the same code: only its code hash misses.
+	assert_eq!(cached.code(B), [0x60, 0x00]);
+	assert_eq!(cached.code_size(B), U256::from(2));
+	assert_eq!(cached.stats(), stats(3, 3));
+}
+
+#[test]
+fn least_recently_used_is_evicted() {
+	let inner = backend();
+	let cached = CachedBackend::new(&inner, 1);
+
+	cached.balance(A);
+	cached.balance(B);
+	cached.balance(A);
+	assert_eq!(cached.stats(), stats(0, 3));
+
+	let cached = CachedBackend::new(&inner, 0);
+	cached.balance(A);
+	cached.balance(A);
+	assert_eq!(cached.stats(), stats(0, 2));
+}
+
+#[test]
+fn invalidate_refetches_changed_account() {
+	let mut cached = CachedBackend::new(backend(), 16);
+	let index = H256::from_low_u64_be(1);
+	cached.balance(A);
+	cached.storage(A, index);
+	cached.balance(B);
+
+	let account = cached.inner_mut().state.get_mut(&A).unwrap();
+	account.balance = U256::from(5);
+	account.storage.insert(index, H256::from_low_u64_be(3));
+	cached.inner_mut().state.get_mut(&B).unwrap().balance = U256::from(5);
+
+	// Stale until invalidated.
+	assert_eq!(cached.balance(A), U256::from(1000));
+
+	cached.invalidate(A);
+	assert_eq!(cached.balance(A), U256::from(5));
+	assert_eq!(cached.storage(A, index), H256::from_low_u64_be(3));
+	assert_eq!(cached.balance(B), U256::from(1000));
+
+	cached.clear();
+	assert_eq!(cached.balance(B), U256::from(5));
+}
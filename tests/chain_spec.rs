@@ -0,0 +1,112 @@
+#![cfg(feature = "serde")]
+
+use evm::standard::{from_chain_spec, ChainConfig, ChainSpec, ChainSpecError, ForkActivation};
+
+/// A chain with every fork up to Istanbul at genesis, and the later forks of
+/// mainnet.
+fn chain() -> ChainConfig {
+	ChainConfig {
+		homestead_block: Some(0),
+		eip150_block: Some(0),
+		eip155_block: Some(0),
+		eip158_block: Some(0),
+		byzantium_block: Some(0),
+		constantinople_block: Some(0),
+		petersburg_block: Some(0),
+		istanbul_block: Some(0),
+		berlin_block: Some(12_244_000),
+		london_block: Some(12_965_000),
+		merge_netsplit_block: None,
+		shanghai_time: Some(1_681_338_455),
+		cancun_time: Some(1_710_338_135),
+		prague_time: None,
+		osaka_time: None,
+	}
+}
+
+#[test]
+fn forks_are_scheduled() {
+	let schedule = from_chain_spec(&ChainSpec { config: chain() }).unwrap();
+
+	let activations = schedule
+		.forks()
+		.iter()
+		.map(|(activation, _)| *activation)
+		.collect::<Vec<_>>();
+	assert_eq!(
+		activations,
+		[
+			ForkActivation::Block(0),
+			ForkActivation::Block(12_244_000),
+			ForkActivation::Block(12_965_000),
+			ForkActivation::Timestamp(1_681_338_455),
+			ForkActivation::Timestamp(1_710_338_135),
+		]
+	);
+
+	assert!(schedule.config_at(0, 0).has_chain_id);
+	assert!(schedule.config_at(12_965_000, 0).eip_1559_enabled);
+	assert!(!schedule.config_at(12_965_000, 0).has_push0);
+	assert!(schedule.config_at(17_034_870, 1_681_338_455).has_push0);
+}
+
+#[test]
+fn forks_before_istanbul_without_preset_are_rejected() {
+	// Mainnet activates Homestead to Petersburg before Istanbul.
+	let mut chain = self::chain();
+	chain.homestead_block = Some(1_150_000);
+	chain.eip150_block = Some(2_463_000);
+	chain.eip155_block = Some(2_675_000);
+	chain.eip158_block = Some(2_675_000);
+	chain.byzantium_block = Some(4_370_000);
+	chain.constantinople_block = Some(7_280_000);
+	chain.petersburg_block = Some(7_280_000);
+	chain.istanbul_block = Some(9_069_000);
+	assert_eq!(
+		from_chain_spec(&ChainSpec { config: chain }).unwrap_err(),
+		ChainSpecError::UnsupportedFork("homestead")
+	);
+
+	let mut chain = ChainConfig {
+		byzantium_block: Some(0),
+		..Default::default()
+	};
+	assert_eq!(
+		from_chain_spec(&ChainSpec {
+			config: chain.clone()
+		})
+		.unwrap_err(),
+		ChainSpecError::UnsupportedFork("byzantium")
+	);
+
+	// Activated with the first fork with a preset.
+	chain.berlin_block = Some(0);
+	assert!(from_chain_spec(&ChainSpec { config: chain }).is_ok());
+}
+
+#[test]
+fn forks_with_incomplete_presets_are_rejected() {
+	let mut chain = self::chain();
+	chain.prague_time = Some(1_746_612_311);
+	assert_eq!(
+		from_chain_spec(&ChainSpec { config: chain }).unwrap_err(),
+		ChainSpecError::UnsupportedFork("prague")
+	);
+}
+
+#[test]
+fn unordered_forks_are_rejected() {
+	let mut chain = self::chain();
+	chain.byzantium_block = Some(1_000_000);
+	assert_eq!(
+		from_chain_spec(&ChainSpec { config: chain }).unwrap_err(),
+		ChainSpecError::UnorderedFork("constantinople")
+	);
+
+	let mut chain = self::chain();
+	chain.london_block = Some(10_000_000);
+	assert_eq!(
+		from_chain_spec(&ChainSpec { config: chain }).unwrap_err(),
+		ChainSpecError::UnorderedFork("london")
+	);
+}
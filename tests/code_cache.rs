@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use evm::{interpreter::utils::keccak256, standard::CodeCache};
+
+fn get(cache: &CodeCache, code: &[u8]) -> evm::standard::CodeArtifacts {
+	cache.get_or_analyze(keccak256(code), code)
+}
+
+#[test]
+fn hit_returns_cached_artifacts() {
+	let cache = CodeCache::new(1024);
+	let code = hex::decode("6001600201").unwrap();
+
+	let first = get(&cache, &code);
+	let second = get(&cache, &code);
+	assert!(Arc::ptr_eq(&first.valids, &second.valids));
+	assert!(Arc::ptr_eq(&first.fusions, &second.fusions));
+	assert!(first.eof.is_none());
+	assert_eq!(cache.len(), 1);
+	assert_eq!(cache.size(), code.len());
+}
+
+#[test]
+fn miss_analyzes_new_code() {
+	let cache = CodeCache::new(1024);
+	let a = hex::decode("6001600201").unwrap();
+	let b = hex::decode("5b600056").unwrap();
+
+	let first = get(&cache, &a);
+	let second = get(&cache, &b);
+	assert!(!Arc::ptr_eq(&first.valids, &second.valids));
+	assert!(second.valids.is_valid(0));
+	assert_eq!(cache.len(), 2);
+	assert_eq!(cache.size(), a.len() + b.len());
+}
+
+#[test]
+fn evicts_least_recently_used() {
+	let cache = CodeCache::new(8);
+	let a = [0x60, 0x01, 0x00, 0x00];
+	let b = [0x60, 0x02, 0x00, 0x00];
+	let c = [0x60, 0x03, 0x00, 0x00];
+
+	let a_first = get(&cache, &a);
+	let b_first = get(&cache, &b);
+	// Use `a` again, so that `b` is the least recently used.
+	get(&cache, &a);
+	get(&cache, &c);
+	assert_eq!(cache.len(), 2);
+	assert_eq!(cache.size(), 8);
+
+	assert!(Arc::ptr_eq(&a_first.valids, &get(&cache, &a).valids));
+	assert!(!Arc::ptr_eq(&b_first.valids, &get(&cache, &b).valids));
+}
+
+#[test]
+fn code_larger_than_bound_is_not_cached() {
+	let cache = CodeCache::new(2);
+	get(&cache, &[0x60, 0x01, 0x00]);
+	assert!(cache.is_empty());
+	assert_eq!(cache.size(), 0);
+}
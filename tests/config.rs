@@ -0,0 +1,61 @@
+use evm::standard::Config;
+
+#[test]
+fn fork_names_are_case_insensitive() {
+	for name in Config::FORK_NAMES {
+		assert!(Config::from_fork_name(name).is_some(), "{}", name);
+		assert!(
+			Config::from_fork_name(&name.to_uppercase()).is_some(),
+			"{}",
+			name
+		);
+	}
+
+	assert!(Config::from_fork_name("Paris").unwrap().has_base_fee);
+	assert!(!Config::from_fork_name("London").unwrap().has_push0);
+	assert!(Config::from_fork_name("Shanghai").unwrap().has_push0);
+	assert!(Config::from_fork_name("homestead").is_none());
+	assert!(Config::from_fork_name("prague").is_none());
+	assert!(Config::from_fork_name("Osaka").is_none());
+	assert!(Config::from_fork_name("").is_none());
+}
+
+#[test]
+fn alt_l1_presets_are_valid() {
+	assert_eq!(Config::bsc().validate(), Ok(()));
+	assert_eq!(
+		Config::bsc().gas_sstore_set,
+		Config::cancun().gas_sstore_set
+	);
+	assert!(Config::bsc().eip_4844_enabled);
+
+	let polygon = Config::polygon();
+	assert_eq!(polygon.validate(), Ok(()));
+	assert!(polygon.eip_1153_enabled);
+	assert!(!polygon.eip_4844_enabled);
+	assert_eq!(polygon.create_contract_limit, Some(32 * 1024));
+	assert_eq!(polygon.max_initcode_size, Some(64 * 1024));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn opcode_costs_survive_serialization() {
+	use std::sync::Arc;
+
+	use evm::{interpreter::opcode::Opcode, standard::ConfigBuilder};
+
+	let mut costs = [None; 256];
+	costs[Opcode::ADD.as_usize()] = Some(1);
+	let mut schedule = Config::cancun().gas_schedule;
+	schedule.opcode_costs = Some(Arc::new(costs));
+	let config = ConfigBuilder::new(Config::cancun())
+		.gas_schedule(schedule)
+		.build();
+
+	let json = serde_json::to_string(&config).unwrap();
+	let decoded: Config = serde_json::from_str(&json).unwrap();
+	assert_eq!(decoded.gas_schedule, config.gas_schedule);
+
+	let json = json.replacen("[null,", "[", 1);
+	assert!(serde_json::from_str::<Config>(&json).is_err());
+}
@@ -0,0 +1,84 @@
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+	standard::{transact_with_gas, Config, Etable, EtableResolver, Invoker, TransactArgs},
+};
+use primitive_types::{H160, H256, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const CONTRACT: H160 = H160::repeat_byte(0x22);
+const COINBASE: H160 = H160::repeat_byte(0x33);
+
+#[test]
+fn coinbase_gets_priority_fee_of_gas_used_after_refund() {
+	let config = Config::cancun();
+	let base_fee = U256::from(10);
+	let initial = U256::from(10_000_000_000u64);
+
+	// Clear slot 0, refunding the gas of the reset.
+	let code = hex::decode("600060005500").unwrap();
+	let mut backend = InMemoryBackend::from_genesis(
+		InMemoryEnvironment {
+			block_coinbase: COINBASE,
+			block_gas_limit: U256::from(30_000_000),
+			block_base_fee_per_gas: base_fee,
+			..Default::default()
+		},
+		[
+			(
+				CALLER,
+				InMemoryAccount {
+					balance: initial,
+					..Default::default()
+				},
+			),
+			(
+				CONTRACT,
+				InMemoryAccount {
+					code,
+					storage: [(H256::zero(), H256::from_low_u64_be(1))].into(),
+					..Default::default()
+				},
+			),
+		]
+		.into(),
+	);
+
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let resolver = EtableResolver::new(&config, &(), &etable);
+	let invoker = Invoker::new(&config, &resolver);
+	let args = TransactArgs::Call {
+		caller: CALLER,
+		address: CONTRACT,
+		value: U256::zero(),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::from(100),
+		max_priority_fee_per_gas: Some(U256::from(3)),
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	};
+
+	let mut overlay = backend.overlay();
+	let (result, gas) = transact_with_gas(args, None, &mut overlay, &invoker).unwrap();
+	result.unwrap();
+	let changeset = overlay.deconstruct().1;
+	backend.apply_overlayed(&changeset);
+
+	assert_eq!(gas.refunded, U256::from(4800));
+	assert_eq!(gas.effective, gas.used - gas.refunded);
+
+	// The effective gas price is min(100, 10 + 3) = 13, of which 3 go to the
+	// coinbase and 10 are burned.
+	let paid = initial - backend.state[&CALLER].balance;
+	let rewarded = backend.state[&COINBASE].balance;
+	assert_eq!(paid, gas.effective * 13);
+	assert_eq!(rewarded, gas.effective * 3);
+	assert_eq!(paid - rewarded, gas.effective * base_fee);
+}
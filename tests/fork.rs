@@ -0,0 +1,169 @@
+#![cfg(feature = "fork")]
+
+use std::cell::{Cell, RefCell};
+
+use evm::backend::{
+	fork::{ForkBackend, ForkError, RpcTransport},
+	RuntimeBaseBackend, RuntimeEnvironment,
+};
+use primitive_types::{H160, H256, U256};
+use serde_json::{json, Value};
+
+const ACCOUNT: H160 = H160::repeat_byte(0x11);
+
+/// Transport answering from canned responses, and recording the requests.
+#[derive(Default)]
+struct MockTransport {
+	requests: RefCell<Vec<(String, Value)>>,
+	fail: Cell<bool>,
+}
+
+impl MockTransport {
+	fn methods(&self) -> Vec<String> {
+		self.requests
+			.borrow()
+			.iter()
+			.map(|(method, _)| method.clone())
+			.collect()
+	}
+}
+
+fn block(number: u64, hash: u8, difficulty: u64) -> Value {
+	json!({
+		"hash": H256::repeat_byte(hash),
+		"number": format!("{:#x}", number),
+		"miner": H160::repeat_byte(0xcb),
+		"timestamp": "0x64",
+		"difficulty": format!("{:#x}", difficulty),
+		"mixHash": H256::repeat_byte(0x77),
+		"gasLimit": "0x1c9c380",
+		"baseFeePerGas": "0x7",
+	})
+}
+
+impl RpcTransport for &MockTransport {
+	fn request(&self, method: &str, params: Value) -> Result<Value, ForkError> {
+		self.requests
+			.borrow_mut()
+			.push((method.to_string(), params.clone()));
+		if self.fail.get() {
+			return Err(ForkError::Transport("unreachable".into()));
+		}
+
+		Ok(match method {
+			"eth_getBlockByNumber" => {
+				let number = params[0].as_str().unwrap().trim_start_matches("0x");
+				let number = u64::from_str_radix(number, 16).unwrap();
+				block(number, number as u8, 0)
+			}
+			"eth_chainId" => json!("0x1"),
+			"eth_getBalance" => json!("0x3e8"),
+			"eth_getTransactionCount" => json!("0x2"),
+			"eth_getCode" => json!("0x6000"),
+			"eth_getStorageAt" => json!(H256::from_low_u64_be(42)),
+			_ => return Err(ForkError::Rpc(format!("unknown method {}", method))),
+		})
+	}
+}
+
+#[test]
+fn environment_is_fetched_from_pinned_block() {
+	let transport = MockTransport::default();
+	let backend = ForkBackend::new(&transport, U256::from(100)).unwrap();
+
+	assert_eq!(backend.block(), U256::from(100));
+	assert_eq!(backend.block_number(), U256::from(100));
+	assert_eq!(backend.block_coinbase(), H160::repeat_byte(0xcb));
+	assert_eq!(backend.block_timestamp(), U256::from(100));
+	assert_eq!(backend.block_gas_limit(), U256::from(30_000_000));
+	assert_eq!(backend.block_base_fee_per_gas(), U256::from(7));
+	assert_eq!(backend.chain_id(), U256::one());
+	// After the Merge, the mix hash is the randomness.
+	assert_eq!(backend.block_randomness(), Some(H256::repeat_byte(0x77)));
+	assert_eq!(
+		transport.requests.borrow()[0],
+		("eth_getBlockByNumber".to_string(), json!(["0x64", false]))
+	);
+}
+
+#[test]
+fn accounts_and_storage_are_cached() {
+	let transport = MockTransport::default();
+	let backend = ForkBackend::new(&transport, U256::from(100)).unwrap();
+	transport.requests.borrow_mut().clear();
+
+	assert_eq!(backend.balance(ACCOUNT), U256::from(1000));
+	assert_eq!(backend.nonce(ACCOUNT), U256::from(2));
+	assert_eq!(backend.code(ACCOUNT), [0x60, 0x00]);
+	assert!(backend.exists(ACCOUNT));
+	assert_eq!(
+		transport.methods(),
+		["eth_getBalance", "eth_getTransactionCount", "eth_getCode"]
+	);
+	assert_eq!(transport.requests.borrow()[0].1, json!([ACCOUNT, "0x64"]));
+
+	let index = H256::from_low_u64_be(1);
+	assert_eq!(backend.storage(ACCOUNT, index), H256::from_low_u64_be(42));
+	assert_eq!(backend.storage(ACCOUNT, index), H256::from_low_u64_be(42));
+	assert_eq!(transport.requests.borrow().len(), 4);
+
+	backend.clear_cache();
+	backend.balance(ACCOUNT);
+	assert_eq!(transport.requests.borrow().len(), 7);
+	assert_eq!(backend.take_error(), None);
+}
+
+#[test]
+fn block_hashes_of_the_last_256_blocks() {
+	let transport = MockTransport::default();
+	let mut backend = ForkBackend::new(&transport, U256::from(1000)).unwrap();
+	transport.requests.borrow_mut().clear();
+
+	assert_eq!(backend.block_hash(U256::from(1000)), H256::zero());
+	assert_eq!(backend.block_hash(U256::from(743)), H256::zero());
+	assert!(transport.requests.borrow().is_empty());
+
+	// 999 is 0x3e7, so its mock hash is made of 0xe7 bytes.
+	assert_eq!(backend.block_hash(U256::from(999)), H256::repeat_byte(0xe7));
+	assert_eq!(backend.block_hash(U256::from(999)), H256::repeat_byte(0xe7));
+	assert_eq!(transport.requests.borrow().len(), 1);
+
+	// The pinned block is already known, when simulating a later block.
+	backend.environment_mut().block_number = U256::from(1001);
+	assert_eq!(
+		backend.block_hash(U256::from(1000)),
+		H256::repeat_byte(0xe8)
+	);
+	assert_eq!(transport.requests.borrow().len(), 1);
+}
+
+#[test]
+fn failed_requests_are_recorded() {
+	let transport = MockTransport::default();
+	let backend = ForkBackend::new(&transport, U256::from(100)).unwrap();
+
+	transport.fail.set(true);
+	assert_eq!(backend.balance(ACCOUNT), U256::zero());
+	assert!(!backend.exists(ACCOUNT));
+	assert_eq!(backend.storage(ACCOUNT, H256::zero()), H256::zero());
+	assert_eq!(
+		backend.take_error(),
+		Some(ForkError::Transport("unreachable".into()))
+	);
+	assert_eq!(backend.take_error(), None);
+
+	// Failed lookups are not cached.
+	transport.fail.set(false);
+	assert_eq!(backend.balance(ACCOUNT), U256::from(1000));
+	assert_eq!(backend.take_error(), None);
+}
+
+#[test]
+fn fork_fails_if_block_cannot_be_fetched() {
+	let transport = MockTransport::default();
+	transport.fail.set(true);
+	assert!(matches!(
+		ForkBackend::new(&transport, U256::from(100)),
+		Err(ForkError::Transport(_))
+	));
+}
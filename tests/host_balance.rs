@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+
+use evm::{
+	backend::{
+		BalanceOp, HostBalance, HostBalanceBackend, InMemoryBackend, OverlayedBackend,
+		RuntimeBackend, RuntimeBaseBackend, TransactionalBackend,
+	},
+	interpreter::{
+		error::{ExitError, ExitException},
+		runtime::Transfer,
+	},
+	MergeStrategy,
+};
+use primitive_types::{H160, U256};
+
+const A: H160 = H160::repeat_byte(0xaa);
+const B: H160 = H160::repeat_byte(0xbb);
+
+/// Balances kept by the host.
+#[derive(Default)]
+struct Balances(BTreeMap<H160, U256>);
+
+impl Balances {
+	fn credit(&mut self, address: H160, value: U256) {
+		*self.0.entry(address).or_default() += value;
+	}
+
+	fn debit(&mut self, address: H160, value: U256) -> Result<(), ExitError> {
+		let balance = self.0.entry(address).or_default();
+		*balance = balance.checked_sub(value).ok_or(ExitException::OutOfFund)?;
+		Ok(())
+	}
+}
+
+impl HostBalance for Balances {
+	fn balance(&self, address: H160) -> U256 {
+		self.0.get(&address).cloned().unwrap_or_default()
+	}
+
+	fn apply(&mut self, op: &BalanceOp) -> Result<(), ExitError> {
+		match op {
+			BalanceOp::Transfer(transfer) => {
+				self.debit(transfer.source, transfer.value)?;
+				self.credit(transfer.target, transfer.value);
+			}
+			BalanceOp::Deposit { target, value } => self.credit(*target, *value),
+			BalanceOp::Withdrawal { source, value } => self.debit(*source, *value)?,
+		}
+		Ok(())
+	}
+
+	fn undo(&mut self, op: &BalanceOp) {
+		match op {
+			BalanceOp::Transfer(transfer) => {
+				self.debit(transfer.target, transfer.value)
+					.expect("undone transfer was applied");
+				self.credit(transfer.source, transfer.value);
+			}
+			BalanceOp::Deposit { target, value } => self
+				.debit(*target, *value)
+				.expect("undone deposit was applied"),
+			BalanceOp::Withdrawal { source, value } => self.credit(*source, *value),
+		}
+	}
+}
+
+fn transfer(value: u64) -> Transfer {
+	Transfer {
+		source: A,
+		target: B,
+		value: U256::from(value),
+	}
+}
+
+fn backend(
+	inner: &InMemoryBackend,
+) -> HostBalanceBackend<OverlayedBackend<&InMemoryBackend>, Balances> {
+	let mut balances = Balances::default();
+	balances.credit(A, U256::from(100));
+	HostBalanceBackend::new(inner.overlay(), balances)
+}
+
+#[test]
+fn balances_are_delegated_to_host() {
+	let inner = InMemoryBackend::default();
+	let mut backend = backend(&inner);
+
+	assert_eq!(backend.balance(A), U256::from(100));
+	assert!(backend.exists(A));
+	assert!(!backend.exists(B));
+
+	backend.transfer(transfer(30)).unwrap();
+	backend.withdrawal(A, U256::from(10)).unwrap();
+	backend.deposit(B, U256::from(5));
+	assert_eq!(backend.balance(A), U256::from(60));
+	assert_eq!(backend.balance(B), U256::from(35));
+
+	// A lack of funds changes nothing.
+	assert!(backend.transfer(transfer(61)).is_err());
+	assert_eq!(backend.balance(A), U256::from(60));
+
+	// The inner backend has no balance changes.
+	let (overlay, _) = backend.into_parts();
+	assert!(overlay.deconstruct().1.balances.is_empty());
+}
+
+#[test]
+fn reverted_substates_are_undone() {
+	let inner = InMemoryBackend::default();
+	let mut backend = backend(&inner);
+
+	backend.push_substate();
+	backend.transfer(transfer(10)).unwrap();
+	backend.push_substate();
+	backend.transfer(transfer(20)).unwrap();
+	backend.reset_balance(B);
+	backend.pop_substate(MergeStrategy::Revert);
+	assert_eq!(backend.balance(A), U256::from(90));
+	assert_eq!(backend.balance(B), U256::from(10));
+
+	// Committed operations are undone with their parent.
+	backend.push_substate();
+	backend.transfer(transfer(30)).unwrap();
+	backend.pop_substate(MergeStrategy::Commit);
+	backend.pop_substate(MergeStrategy::Discard);
+	assert_eq!(backend.balance(A), U256::from(100));
+	assert_eq!(backend.balance(B), U256::zero());
+}
+
+#[test]
+fn operations_outside_substates_are_kept() {
+	let inner = InMemoryBackend::default();
+	let mut backend = backend(&inner);
+
+	// Fee payment, before the call substate.
+	backend.withdrawal(A, U256::from(10)).unwrap();
+	backend.push_substate();
+	backend.transfer(transfer(20)).unwrap();
+	backend.pop_substate(MergeStrategy::Revert);
+	// Fee refund, after it.
+	backend.deposit(A, U256::from(3));
+
+	assert_eq!(backend.balance(A), U256::from(93));
+	assert_eq!(backend.host().balance(B), U256::zero());
+}
@@ -0,0 +1,238 @@
+#![cfg(feature = "parallel")]
+
+use evm::{
+	backend::{
+		InMemoryAccount, InMemoryBackend, InMemoryEnvironment, OverlayedBackend,
+		RuntimeBaseBackend, RuntimeEnvironment,
+	},
+	interpreter::error::ExitError,
+	parallel::{execute_parallel, ParallelOutput},
+	standard::{
+		transact_with_gas, Config, Etable, EtableResolver, Invoker, TransactArgs, TransactValue,
+	},
+};
+use primitive_types::{H160, U256};
+
+const THREADS: usize = 4;
+const BENEFICIARY: H160 = H160::repeat_byte(0xbe);
+
+/// Increment slot 0 and return its new value.
+const COUNTER: &str = "60005460010160005560005460005260206000f3";
+
+type Outcome = Result<Result<Vec<u8>, ExitError>, ExitError>;
+
+fn account(index: u8) -> H160 {
+	H160::repeat_byte(index)
+}
+
+fn backend(accounts: Vec<(H160, InMemoryAccount)>) -> InMemoryBackend {
+	InMemoryBackend::from_genesis(
+		InMemoryEnvironment {
+			block_gas_limit: U256::from(30_000_000),
+			..Default::default()
+		},
+		accounts.into_iter().collect(),
+	)
+}
+
+fn call(caller: H160, address: H160, value: U256) -> TransactArgs {
+	TransactArgs::Call {
+		caller,
+		address,
+		value,
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	}
+}
+
+fn transact<B>(config: &Config, args: TransactArgs, overlay: &mut OverlayedBackend<B>) -> Outcome
+where
+	B: RuntimeEnvironment + RuntimeBaseBackend,
+{
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let resolver = EtableResolver::new(config, &(), &etable);
+	let invoker = Invoker::new(config, &resolver);
+
+	let (result, _) = transact_with_gas(args, None, overlay, &invoker)?;
+	Ok(result.map(|value| match value {
+		TransactValue::Call { retval, .. } => retval,
+		TransactValue::Create { address, .. } => address.as_bytes().to_vec(),
+	}))
+}
+
+/// Execute `transactions` in parallel and one after another, and check that
+/// both have the same results and changes.
+fn check_against_sequential(
+	backend: &InMemoryBackend,
+	transactions: &[TransactArgs],
+) -> ParallelOutput<Outcome> {
+	let config = Config::shanghai();
+
+	let parallel = execute_parallel(backend, transactions.len(), THREADS, |index, overlay| {
+		transact(&config, transactions[index].clone(), overlay)
+	});
+
+	let mut sequential_backend = backend.clone();
+	let mut sequential = Vec::new();
+	for args in transactions {
+		let mut overlay = sequential_backend.overlay();
+		let result = transact(&config, args.clone(), &mut overlay);
+		let changeset = overlay.deconstruct().1;
+		sequential_backend.apply_overlayed(&changeset);
+		sequential.push((result, changeset));
+	}
+
+	let mut parallel_backend = backend.clone();
+	for (index, ((result, changeset), (expected_result, expected_changeset))) in
+		parallel.results.iter().zip(&sequential).enumerate()
+	{
+		assert_eq!(result, expected_result, "result of transaction {}", index);
+		assert_eq!(
+			changeset, expected_changeset,
+			"changes of transaction {}",
+			index
+		);
+		parallel_backend.apply_overlayed(changeset);
+	}
+	assert_eq!(parallel.results.len(), sequential.len());
+	assert_eq!(parallel_backend.state, sequential_backend.state);
+
+	parallel
+}
+
+#[test]
+fn conflicting_storage() {
+	let counter = account(0xcc);
+	let mut accounts = vec![(
+		counter,
+		InMemoryAccount {
+			code: hex::decode(COUNTER).unwrap(),
+			..Default::default()
+		},
+	)];
+	let transactions = (1..=8)
+		.map(|index| {
+			accounts.push((account(index), Default::default()));
+			call(account(index), counter, U256::zero())
+		})
+		.collect::<Vec<_>>();
+	let backend = backend(accounts);
+
+	let parallel = check_against_sequential(&backend, &transactions);
+	// All transactions first run on the genesis state, and all but the first
+	// conflict.
+	assert!(parallel.executions > transactions.len());
+	for (index, (result, _)) in parallel.results.into_iter().enumerate() {
+		let retval = result.unwrap().unwrap();
+		assert_eq!(U256::from_big_endian(&retval), U256::from(index + 1));
+	}
+}
+
+#[test]
+fn conflicting_balances() {
+	// Each account forwards the value it received from the previous one, so
+	// every transfer depends on the one before.
+	let mut accounts = vec![(
+		account(1),
+		InMemoryAccount {
+			balance: U256::from(100),
+			..Default::default()
+		},
+	)];
+	let transactions = (1..=8)
+		.map(|index| {
+			accounts.push((account(index + 1), Default::default()));
+			call(account(index), account(index + 1), U256::from(100))
+		})
+		.collect::<Vec<_>>();
+	let backend = backend(accounts);
+
+	let parallel = check_against_sequential(&backend, &transactions);
+	assert!(parallel
+		.results
+		.iter()
+		.all(|(result, _)| matches!(result, Ok(Ok(_)))));
+}
+
+#[test]
+fn conflicting_selfdestruct() {
+	let destructed = account(0xdd);
+	let mut code = vec![0x73];
+	code.extend_from_slice(BENEFICIARY.as_bytes());
+	code.push(0xff);
+
+	// Return the balance of the beneficiary.
+	let balance_of = account(0xbb);
+	let mut balance_code = vec![0x73];
+	balance_code.extend_from_slice(BENEFICIARY.as_bytes());
+	balance_code.extend_from_slice(&hex::decode("3160005260206000f3").unwrap());
+
+	let backend = backend(vec![
+		(
+			destructed,
+			InMemoryAccount {
+				balance: U256::from(1000),
+				code,
+				..Default::default()
+			},
+		),
+		(
+			balance_of,
+			InMemoryAccount {
+				code: balance_code,
+				..Default::default()
+			},
+		),
+		(
+			account(2),
+			InMemoryAccount {
+				balance: U256::from(10),
+				..Default::default()
+			},
+		),
+	]);
+
+	let transactions = [
+		call(account(1), destructed, U256::zero()),
+		// Recreate the account, without code.
+		call(account(2), destructed, U256::from(10)),
+		call(account(3), destructed, U256::zero()),
+		call(account(4), balance_of, U256::zero()),
+	];
+
+	let parallel = check_against_sequential(&backend, &transactions);
+	let retval = parallel.results[3].0.clone().unwrap().unwrap();
+	assert_eq!(U256::from_big_endian(&retval), U256::from(1000));
+}
+
+#[test]
+fn independent_transactions_execute_once() {
+	let mut accounts = Vec::new();
+	let transactions = (1..=8)
+		.map(|index| {
+			accounts.push((
+				account(index),
+				InMemoryAccount {
+					balance: U256::from(100),
+					..Default::default()
+				},
+			));
+			call(account(index), account(index + 0x80), U256::from(100))
+		})
+		.collect::<Vec<_>>();
+	let backend = backend(accounts);
+
+	let parallel = check_against_sequential(&backend, &transactions);
+	assert_eq!(parallel.executions, transactions.len());
+}
@@ -0,0 +1,138 @@
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment, OverlayedChangeSet},
+	standard::{
+		transact_with_gas, Config, Etable, EtableResolver, Invoker, TransactArgs, TransactValue,
+	},
+};
+use primitive_types::{H160, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const CONTRACT: H160 = H160::repeat_byte(0x22);
+const BENEFICIARY: H160 = H160::repeat_byte(0x33);
+
+/// Code self-destructing to `beneficiary`.
+fn selfdestruct_code(beneficiary: H160) -> Vec<u8> {
+	let mut code = vec![0x73];
+	code.extend_from_slice(beneficiary.as_bytes());
+	code.push(0xff);
+	code
+}
+
+fn backend(contract_code: Vec<u8>) -> InMemoryBackend {
+	InMemoryBackend::from_genesis(
+		InMemoryEnvironment {
+			block_gas_limit: U256::from(30_000_000),
+			..Default::default()
+		},
+		[
+			(
+				CALLER,
+				InMemoryAccount {
+					balance: U256::from(1_000_000),
+					..Default::default()
+				},
+			),
+			(
+				CONTRACT,
+				InMemoryAccount {
+					balance: U256::from(1000),
+					code: contract_code,
+					..Default::default()
+				},
+			),
+		]
+		.into(),
+	)
+}
+
+fn transact(
+	config: &Config,
+	backend: &mut InMemoryBackend,
+	args: TransactArgs,
+) -> (TransactValue, OverlayedChangeSet) {
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let resolver = EtableResolver::new(config, &(), &etable);
+	let invoker = Invoker::new(config, &resolver);
+
+	let mut overlay = backend.overlay();
+	let (result, _) = transact_with_gas(args, None, &mut overlay, &invoker).unwrap();
+	let changeset = overlay.deconstruct().1;
+	backend.apply_overlayed(&changeset);
+	(result.unwrap(), changeset)
+}
+
+fn call(config: &Config, backend: &mut InMemoryBackend) -> OverlayedChangeSet {
+	let args = TransactArgs::Call {
+		caller: CALLER,
+		address: CONTRACT,
+		value: U256::zero(),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	};
+	transact(config, backend, args).1
+}
+
+#[test]
+fn existing_contract_is_only_deleted_before_cancun() {
+	let mut backend = self::backend(selfdestruct_code(BENEFICIARY));
+	let changeset = call(&Config::shanghai(), &mut backend);
+	assert!(changeset.deletes.contains(&CONTRACT));
+	assert!(!backend.state.contains_key(&CONTRACT));
+	assert_eq!(backend.state[&BENEFICIARY].balance, U256::from(1000));
+
+	let mut backend = self::backend(selfdestruct_code(BENEFICIARY));
+	let changeset = call(&Config::cancun(), &mut backend);
+	assert!(changeset.deletes.is_empty());
+	assert_eq!(
+		backend.state[&CONTRACT].code,
+		selfdestruct_code(BENEFICIARY)
+	);
+	assert_eq!(backend.state[&CONTRACT].balance, U256::zero());
+	assert_eq!(backend.state[&BENEFICIARY].balance, U256::from(1000));
+}
+
+#[test]
+fn existing_contract_keeps_balance_sent_to_itself() {
+	let mut backend = self::backend(selfdestruct_code(CONTRACT));
+	let changeset = call(&Config::cancun(), &mut backend);
+	assert!(changeset.deletes.is_empty());
+	assert_eq!(backend.state[&CONTRACT].balance, U256::from(1000));
+}
+
+#[test]
+fn contract_created_in_transaction_is_deleted() {
+	let mut backend = self::backend(Vec::new());
+	let args = TransactArgs::Create {
+		caller: CALLER,
+		value: U256::from(500),
+		init_code: selfdestruct_code(BENEFICIARY),
+		salt: None,
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		overrides: Default::default(),
+	};
+	let (result, changeset) = transact(&Config::cancun(), &mut backend, args);
+
+	let address = match result {
+		TransactValue::Create { address, .. } => address,
+		TransactValue::Call { .. } => panic!("expected a create"),
+	};
+	assert!(changeset.deletes.contains(&address));
+	assert!(!backend.state.contains_key(&address));
+	assert_eq!(backend.state[&BENEFICIARY].balance, U256::from(500));
+}
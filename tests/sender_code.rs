@@ -0,0 +1,81 @@
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+	interpreter::error::{ExitError, ExitException},
+	standard::{
+		transact_with_gas, Config, Etable, EtableResolver, Invoker, TransactArgs, TransactOverrides,
+	},
+};
+use primitive_types::{H160, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const RECIPIENT: H160 = H160::repeat_byte(0x22);
+
+fn transfer(config: &Config, overrides: TransactOverrides) -> Result<(), ExitError> {
+	let backend = InMemoryBackend::from_genesis(
+		InMemoryEnvironment {
+			block_gas_limit: U256::from(30_000_000),
+			..Default::default()
+		},
+		[(
+			CALLER,
+			InMemoryAccount {
+				balance: U256::from(1_000_000),
+				// STOP.
+				code: vec![0x00],
+				..Default::default()
+			},
+		)]
+		.into(),
+	);
+
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let resolver = EtableResolver::new(config, &(), &etable);
+	let invoker = Invoker::new(config, &resolver);
+	let args = TransactArgs::Call {
+		caller: CALLER,
+		address: RECIPIENT,
+		value: U256::from(1000),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides,
+	};
+
+	let mut overlay = backend.overlay();
+	let (result, _) = transact_with_gas(args, None, &mut overlay, &invoker)?;
+	result.map(|_| ())
+}
+
+#[test]
+fn eip_3607_rejects_senders_with_code_from_london() {
+	assert!(!Config::berlin().eip_3607_enabled);
+	assert_eq!(transfer(&Config::berlin(), Default::default()), Ok(()));
+
+	assert!(Config::london().eip_3607_enabled);
+	assert_eq!(
+		transfer(&Config::london(), Default::default()),
+		Err(ExitException::SenderHasCode.into())
+	);
+	assert_eq!(
+		transfer(&Config::cancun(), Default::default()),
+		Err(ExitException::SenderHasCode.into())
+	);
+}
+
+#[test]
+fn eip_3607_check_can_be_skipped() {
+	let overrides = TransactOverrides {
+		skip_sender_code_check: true,
+		..Default::default()
+	};
+	assert_eq!(transfer(&Config::cancun(), overrides), Ok(()));
+}
@@ -0,0 +1,151 @@
+use evm::backend::{
+	AccountOverride, InMemoryAccount, InMemoryBackend, InMemoryEnvironment, OverrideBackend,
+	RuntimeBaseBackend, StateOverride, StateOverrideError,
+};
+use primitive_types::{H160, H256, U256};
+
+const ACCOUNT: H160 = H160::repeat_byte(0x11);
+const NEW: H160 = H160::repeat_byte(0x22);
+
+fn slot(value: u64) -> H256 {
+	H256::from_low_u64_be(value)
+}
+
+fn backend() -> InMemoryBackend {
+	InMemoryBackend::from_genesis(
+		InMemoryEnvironment::default(),
+		[(
+			ACCOUNT,
+			InMemoryAccount {
+				balance: U256::from(1000),
+				nonce: U256::from(1),
+				code: vec![0x00],
+				storage: [(slot(1), slot(10)), (slot(2), slot(20))].into(),
+			},
+		)]
+		.into(),
+	)
+}
+
+#[test]
+fn fields_are_overridden() {
+	let inner = backend();
+	let overrides: StateOverride = [(
+		ACCOUNT,
+		AccountOverride {
+			balance: Some(U256::from(5)),
+			code: Some(vec![0x60, 0x00]),
+			..Default::default()
+		},
+	)]
+	.into();
+	let backend = OverrideBackend::new(&inner, overrides).unwrap();
+
+	assert_eq!(backend.balance(ACCOUNT), U256::from(5));
+	assert_eq!(backend.code(ACCOUNT), [0x60, 0x00]);
+	// Not overridden.
+	assert_eq!(backend.nonce(ACCOUNT), U256::from(1));
+	assert_eq!(backend.storage(ACCOUNT, slot(1)), slot(10));
+}
+
+#[test]
+fn state_replaces_storage() {
+	let inner = backend();
+	let overrides: StateOverride = [(
+		ACCOUNT,
+		AccountOverride {
+			state: Some([(slot(1), slot(11))].into()),
+			..Default::default()
+		},
+	)]
+	.into();
+	let backend = OverrideBackend::new(&inner, overrides).unwrap();
+
+	assert_eq!(backend.storage(ACCOUNT, slot(1)), slot(11));
+	assert_eq!(backend.storage(ACCOUNT, slot(2)), H256::zero());
+}
+
+#[test]
+fn state_diff_keeps_storage() {
+	let inner = backend();
+	let overrides: StateOverride = [(
+		ACCOUNT,
+		AccountOverride {
+			state_diff: Some([(slot(1), slot(11))].into()),
+			..Default::default()
+		},
+	)]
+	.into();
+	let backend = OverrideBackend::new(&inner, overrides).unwrap();
+
+	assert_eq!(backend.storage(ACCOUNT, slot(1)), slot(11));
+	assert_eq!(backend.storage(ACCOUNT, slot(2)), slot(20));
+}
+
+#[test]
+fn state_and_state_diff_are_exclusive() {
+	let overrides: StateOverride = [(
+		ACCOUNT,
+		AccountOverride {
+			state: Some(Default::default()),
+			state_diff: Some(Default::default()),
+			..Default::default()
+		},
+	)]
+	.into();
+	assert_eq!(
+		OverrideBackend::new(backend(), overrides).err(),
+		Some(StateOverrideError::StateAndStateDiff(ACCOUNT))
+	);
+}
+
+#[test]
+fn overridden_account_exists() {
+	let inner = backend();
+	let overrides: StateOverride = [(
+		NEW,
+		AccountOverride {
+			nonce: Some(U256::from(7)),
+			..Default::default()
+		},
+	)]
+	.into();
+	let backend = OverrideBackend::new(&inner, overrides).unwrap();
+
+	assert!(!inner.exists(NEW));
+	assert!(backend.exists(NEW));
+	assert_eq!(backend.nonce(NEW), U256::from(7));
+	assert_eq!(backend.balance(NEW), U256::zero());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn deserialize_geth_format() {
+	let overrides: StateOverride = serde_json::from_str(
+		r#"{
+			"0x1111111111111111111111111111111111111111": {
+				"balance": "0x5",
+				"code": "0x6000",
+				"stateDiff": {
+					"0x0000000000000000000000000000000000000000000000000000000000000001":
+						"0x000000000000000000000000000000000000000000000000000000000000000b"
+				}
+			}
+		}"#,
+	)
+	.unwrap();
+
+	assert_eq!(
+		overrides[&ACCOUNT],
+		AccountOverride {
+			balance: Some(U256::from(5)),
+			code: Some(vec![0x60, 0x00]),
+			state_diff: Some([(slot(1), slot(11))].into()),
+			..Default::default()
+		}
+	);
+	assert_eq!(
+		serde_json::from_str::<StateOverride>(&serde_json::to_string(&overrides).unwrap()).unwrap(),
+		overrides
+	);
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "transaction")]
+
+use evm::standard::{Transaction, TransactionAction, TransactionError};
+use primitive_types::{H160, H256, U256};
+
+/// Sender of all the vectors, with the private key `0x4646..46` of the
+/// EIP-155 example.
+fn sender() -> H160 {
+	H160::from_slice(&hex::decode("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f").unwrap())
+}
+
+fn decode(raw: &str) -> Transaction {
+	Transaction::decode(&hex::decode(raw).unwrap()).unwrap()
+}
+
+fn hash(hash: &str) -> H256 {
+	H256::from_slice(&hex::decode(hash).unwrap())
+}
+
+#[test]
+fn legacy() {
+	let tx = decode("f86c808504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000801ca0ae4ec29e5426516043e3bbfd7950486f0301c1b640f1173c05928ac063e1bcdba03006963cacd3ec33bfc3c37a8f56187a33a333b01a7e11e405424aaedcdbc3f4");
+	let legacy = match &tx {
+		Transaction::Legacy(legacy) => legacy,
+		_ => panic!("expected a legacy transaction"),
+	};
+	assert_eq!(legacy.chain_id(), None);
+	assert_eq!(legacy.v, 28);
+	assert_eq!(
+		tx.signing_hash(),
+		hash("597779acf7a80f7bd5089cbfe09ee7bb0749dc593e38b85d17c5f4ab81c34600")
+	);
+	// Not replay-protected, so accepted on any chain.
+	assert_eq!(tx.recover_caller(1, true), Ok(sender()));
+	assert_eq!(tx.recover_caller(5, true), Ok(sender()));
+}
+
+#[test]
+fn eip155() {
+	// Example of the EIP.
+	let tx = decode("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83");
+	let legacy = match &tx {
+		Transaction::Legacy(legacy) => legacy,
+		_ => panic!("expected a legacy transaction"),
+	};
+	assert_eq!(legacy.chain_id(), Some(1));
+	assert_eq!(legacy.nonce, U256::from(9));
+	assert_eq!(legacy.value, U256::from(1_000_000_000_000_000_000u64));
+	assert_eq!(
+		tx.signing_hash(),
+		hash("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53")
+	);
+	assert_eq!(tx.recover_caller(1, true), Ok(sender()));
+	assert_eq!(
+		tx.recover_caller(5, true),
+		Err(TransactionError::InvalidChainId)
+	);
+}
+
+#[test]
+fn eip2930() {
+	let tx = decode("01f8a101018504a817c80082753094353535353535353535353535353535353535353580821234f838f7943535353535353535353535353535353535353535e1a0000000000000000000000000000000000000000000000000000000000000000001a00b950ea4ec84d2429d275f47c7e9179230c3b5bcf408ca10e28ead3ed0e639a5a0322b51229080633342d1b8bb71d091d07f65952148f3bf554f4a820d2f052f26");
+	let eip2930 = match &tx {
+		Transaction::Eip2930(eip2930) => eip2930,
+		_ => panic!("expected an EIP-2930 transaction"),
+	};
+	assert_eq!(eip2930.input, [0x12, 0x34]);
+	assert_eq!(
+		eip2930.access_list,
+		[(H160::repeat_byte(0x35), vec![H256::zero()])]
+	);
+	assert_eq!(
+		tx.signing_hash(),
+		hash("d2c380bda622e233f83d0dc996bdda8a3ed065181b0bd923a7a3ec4da74f071a")
+	);
+	assert_eq!(tx.recover_caller(1, true), Ok(sender()));
+}
+
+#[test]
+fn eip1559() {
+	let tx = decode("02f8590102843b9aca008506fc23ac0082ea608080826000c080a0091021ab4b5f943ff184c4b563deb17eba60d780caa56be945cff70324c406d4a038cbb7e11ae55f73be15dc20c7aa0d4f66b858dcfbd315801d09666a9671b02a");
+	let eip1559 = match &tx {
+		Transaction::Eip1559(eip1559) => eip1559,
+		_ => panic!("expected an EIP-1559 transaction"),
+	};
+	assert_eq!(eip1559.action, TransactionAction::Create);
+	assert_eq!(eip1559.max_priority_fee_per_gas, U256::from(1_000_000_000));
+	assert_eq!(eip1559.max_fee_per_gas, U256::from(30_000_000_000u64));
+	assert_eq!(
+		tx.signing_hash(),
+		hash("5549a6987d02659bba43020d853b6c788127d4df9256595248ff238f06c57542")
+	);
+	assert_eq!(tx.recover_caller(1, true), Ok(sender()));
+}
+
+#[test]
+fn high_s_is_rejected_from_homestead() {
+	let mut tx = decode("f86c808504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000801ca0ae4ec29e5426516043e3bbfd7950486f0301c1b640f1173c05928ac063e1bcdba03006963cacd3ec33bfc3c37a8f56187a33a333b01a7e11e405424aaedcdbc3f4");
+	let legacy = match &mut tx {
+		Transaction::Legacy(legacy) => legacy,
+		_ => panic!("expected a legacy transaction"),
+	};
+
+	// Replace `s` with `n - s`, and flip the parity: the signature is still
+	// valid, for the same sender.
+	let order = U256::from_big_endian(
+		&hex::decode("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141").unwrap(),
+	);
+	let high_s = order - U256::from_big_endian(&legacy.s.0);
+	high_s.to_big_endian(&mut legacy.s.0);
+	legacy.v = 27;
+
+	assert_eq!(tx.recover_caller(1, true), Err(TransactionError::HighS));
+	assert_eq!(tx.recover_caller(1, false), Ok(sender()));
+}
@@ -0,0 +1,197 @@
+#![cfg(feature = "trie")]
+
+use evm::{
+	backend::{
+		InMemoryAccount, InMemoryEnvironment, MerkleTrie, ProofBackend, RuntimeBackend,
+		RuntimeBaseBackend, TrieBackend, EMPTY_CODE_HASH, EMPTY_TRIE_ROOT,
+	},
+	interpreter::utils::keccak256,
+};
+use primitive_types::{H160, H256, U256};
+
+/// Items inserted in order, and the expected root.
+type Vector<'a> = (&'a [(&'a [u8], &'a [u8])], &'a str);
+
+fn root(hash: &str) -> H256 {
+	H256::from_slice(&hex::decode(hash).unwrap())
+}
+
+fn trie(items: &[(&[u8], &[u8])]) -> MerkleTrie {
+	let mut trie = MerkleTrie::new();
+	for (key, value) in items {
+		trie.insert(key, value.to_vec());
+	}
+	trie
+}
+
+#[test]
+fn empty_root() {
+	let mut trie = MerkleTrie::new();
+	assert!(trie.is_empty());
+	assert_eq!(trie.root(), EMPTY_TRIE_ROOT);
+	// Hash of the RLP encoding of an empty string.
+	assert_eq!(EMPTY_TRIE_ROOT, keccak256(&[0x80]));
+	assert_eq!(EMPTY_CODE_HASH, keccak256(&[]));
+}
+
+/// Vectors of `trietest.json` and `trieanyorder.json` of ethereum/tests.
+#[test]
+fn ethereum_tests_vectors() {
+	let vectors: [Vector; 6] = [
+		(
+			&[
+				(b"doe", b"reindeer"),
+				(b"dog", b"puppy"),
+				(b"dogglesworth", b"cat"),
+			],
+			"8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3",
+		),
+		(
+			&[(b"foo", b"bar"), (b"food", b"bass")],
+			"17beaa1648bafa633cda809c90c04af50fc8aed3cb40d16efbddee6fdf63c4c3",
+		),
+		(
+			&[(b"be", b"e"), (b"dog", b"puppy"), (b"bed", b"d")],
+			"3f67c7a47520f79faa29255d2d3c084a7a6df0453116ed7232ff10277a8be68b",
+		),
+		(
+			&[(b"test", b"test"), (b"te", b"testy")],
+			"8452568af70d8d140f58d941338542f645fcca50094b20f3c3d8c3df49337928",
+		),
+		(
+			&[
+				(&[0x00, 0x45], &[0x01, 0x23, 0x45, 0x67, 0x89]),
+				(&[0x45, 0x00], &[0x98, 0x76, 0x54, 0x32, 0x10]),
+			],
+			"285505fcabe84badc8aa310e2aae17eddc7d120aabec8a476902c8184b3a3503",
+		),
+		(
+			&[
+				(b"do", b"verb"),
+				(b"horse", b"stallion"),
+				(b"doge", b"coin"),
+				(b"dog", b"puppy"),
+			],
+			"5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84",
+		),
+	];
+
+	for (items, expected) in vectors {
+		assert_eq!(trie(items).root(), root(expected));
+
+		// The root does not depend on the order of insertion.
+		let reversed = items.iter().rev().cloned().collect::<Vec<_>>();
+		assert_eq!(trie(&reversed).root(), root(expected));
+	}
+}
+
+/// `emptyValues` of `trietest.json`: empty values remove keys.
+#[test]
+fn empty_values_remove_keys() {
+	let mut trie = trie(&[
+		(b"do", b"verb"),
+		(b"ether", b"wookiedoo"),
+		(b"horse", b"stallion"),
+		(b"shaman", b"horse"),
+		(b"doge", b"coin"),
+		(b"ether", b""),
+		(b"dog", b"puppy"),
+		(b"shaman", b""),
+	]);
+	assert_eq!(trie.get(b"ether"), None);
+	assert_eq!(trie.get(b"dog"), Some(&b"puppy"[..]));
+	assert_eq!(
+		trie.root(),
+		root("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+	);
+}
+
+#[test]
+fn insert_and_remove_back_to_same_root() {
+	let items: [(&[u8], &[u8]); 3] = [
+		(b"doe", b"reindeer"),
+		(b"dog", b"puppy"),
+		(b"dogglesworth", b"cat"),
+	];
+	let mut trie = trie(&items);
+	let expected = trie.root();
+
+	// Keys splitting a leaf, an extension and a branch, and a prefix of
+	// existing keys.
+	let extra: [&[u8]; 5] = [b"dogglesworth2", b"doge", b"d", b"cat", b"do"];
+	for key in extra {
+		trie.insert(key, b"value".to_vec());
+		assert_ne!(trie.root(), expected);
+	}
+	for key in extra.iter().rev() {
+		trie.remove(key);
+	}
+	assert_eq!(trie.root(), expected);
+
+	// Removing a missing key changes nothing.
+	trie.remove(b"horse");
+	assert_eq!(trie.root(), expected);
+
+	for (key, _) in items {
+		trie.remove(key);
+	}
+	assert!(trie.is_empty());
+	assert_eq!(trie.root(), EMPTY_TRIE_ROOT);
+}
+
+#[test]
+fn proof_starts_from_root() {
+	let mut trie = trie(&[
+		(b"doe", b"reindeer"),
+		(b"dog", b"puppy"),
+		(b"dogglesworth", b"cat"),
+	]);
+	let proof = trie.prove(b"dog");
+	assert!(!proof.is_empty());
+	assert_eq!(keccak256(&proof[0]), trie.root());
+}
+
+#[test]
+fn backend_state_root() {
+	let address = H160::repeat_byte(0x11);
+	let mut backend = TrieBackend::from_genesis(
+		InMemoryEnvironment::default(),
+		[(
+			address,
+			InMemoryAccount {
+				balance: U256::from(1000),
+				storage: [(H256::from_low_u64_be(1), H256::from_low_u64_be(2))].into(),
+				..Default::default()
+			},
+		)]
+		.into(),
+	);
+	let state_root = backend.state_root();
+	assert_ne!(state_root, EMPTY_TRIE_ROOT);
+	assert_eq!(backend.balance(address), U256::from(1000));
+	assert_eq!(
+		backend.storage(address, H256::from_low_u64_be(1)),
+		H256::from_low_u64_be(2)
+	);
+
+	let account = backend.account(address).unwrap();
+	assert_ne!(account.storage_root, EMPTY_TRIE_ROOT);
+	assert_eq!(account.code_hash, EMPTY_CODE_HASH);
+
+	// Changing and restoring a slot gives back the same root.
+	let mut overlay = backend.overlay();
+	overlay
+		.set_storage(address, H256::from_low_u64_be(1), H256::zero())
+		.unwrap();
+	let (_, changeset) = overlay.deconstruct();
+	assert_ne!(backend.apply_overlayed(&changeset), state_root);
+	let mut overlay = backend.overlay();
+	overlay
+		.set_storage(address, H256::from_low_u64_be(1), H256::from_low_u64_be(2))
+		.unwrap();
+	let (_, changeset) = overlay.deconstruct();
+	assert_eq!(backend.apply_overlayed(&changeset), state_root);
+
+	let proof = backend.prove_account(address);
+	assert_eq!(keccak256(&proof[0]), state_root);
+}
@@ -0,0 +1,194 @@
+use evm::{
+	backend::{
+		InMemoryAccount, InMemoryBackend, InMemoryEnvironment, OverlayedBackend, RecordingBackend,
+		RuntimeBaseBackend, RuntimeEnvironment, Witness,
+	},
+	standard::{
+		transact_with_gas, Config, Etable, EtableResolver, Invoker, TransactArgs, TransactValue,
+	},
+};
+use primitive_types::{H160, H256, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const CONTRACT: H160 = H160::repeat_byte(0x22);
+const OTHER: H160 = H160::repeat_byte(0x33);
+const UNTOUCHED: H160 = H160::repeat_byte(0x44);
+
+/// Return the sum of slot 1, the balance of `OTHER` and the hash of block 9.
+fn code() -> Vec<u8> {
+	let mut code = hex::decode("60015473").unwrap();
+	code.extend_from_slice(OTHER.as_bytes());
+	code.extend_from_slice(&hex::decode("31016009400160005260206000f3").unwrap());
+	code
+}
+
+fn environment() -> InMemoryEnvironment {
+	InMemoryEnvironment {
+		block_hashes: [(U256::from(9), H256::from_low_u64_be(100))].into(),
+		block_number: U256::from(10),
+		block_gas_limit: U256::from(30_000_000),
+		..Default::default()
+	}
+}
+
+fn backend() -> InMemoryBackend {
+	InMemoryBackend::from_genesis(
+		environment(),
+		[
+			(
+				CALLER,
+				InMemoryAccount {
+					balance: U256::from(1_000_000),
+					..Default::default()
+				},
+			),
+			(
+				CONTRACT,
+				InMemoryAccount {
+					code: code(),
+					storage: [
+						(H256::from_low_u64_be(1), H256::from_low_u64_be(10)),
+						(H256::from_low_u64_be(2), H256::from_low_u64_be(20)),
+					]
+					.into(),
+					..Default::default()
+				},
+			),
+			(
+				OTHER,
+				InMemoryAccount {
+					balance: U256::from(1000),
+					..Default::default()
+				},
+			),
+			(
+				UNTOUCHED,
+				InMemoryAccount {
+					balance: U256::from(1),
+					..Default::default()
+				},
+			),
+		]
+		.into(),
+	)
+}
+
+fn call<B: RuntimeEnvironment + RuntimeBaseBackend>(backend: B) -> U256 {
+	let config = Config::shanghai();
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let resolver = EtableResolver::new(&config, &(), &etable);
+	let invoker = Invoker::new(&config, &resolver);
+
+	let args = TransactArgs::Call {
+		caller: CALLER,
+		address: CONTRACT,
+		value: U256::zero(),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	};
+	let mut overlay = OverlayedBackend::new(backend, Default::default());
+	let (result, _) = transact_with_gas(args, None, &mut overlay, &invoker).unwrap();
+	match result.unwrap() {
+		TransactValue::Call { retval, .. } => U256::from_big_endian(&retval),
+		TransactValue::Create { .. } => panic!("expected a call"),
+	}
+}
+
+/// State with only what the witness contains.
+fn from_witness(witness: &Witness) -> InMemoryBackend {
+	let mut environment = environment();
+	environment.block_hashes = witness.block_hashes.clone();
+	let accounts = witness
+		.accounts
+		.iter()
+		.filter(|(_, account)| account.exists)
+		.map(|(address, account)| {
+			(
+				*address,
+				InMemoryAccount {
+					balance: account.balance,
+					nonce: account.nonce,
+					code: witness
+						.codes
+						.get(&account.code_hash)
+						.cloned()
+						.unwrap_or_default(),
+					storage: account.storage.clone(),
+				},
+			)
+		})
+		.collect();
+	InMemoryBackend::from_genesis(environment, accounts)
+}
+
+#[test]
+fn witness_records_reads() {
+	let backend = backend();
+	let recording = RecordingBackend::new(&backend);
+	assert_eq!(call(&recording), U256::from(1110));
+
+	let witness = recording.witness();
+	assert!(witness.state_root.is_none());
+	assert!(witness.accounts.contains_key(&CALLER));
+	assert!(!witness.accounts.contains_key(&UNTOUCHED));
+	assert_eq!(witness.accounts[&OTHER].balance, U256::from(1000));
+	assert_eq!(
+		witness.accounts[&CONTRACT].storage,
+		[(H256::from_low_u64_be(1), H256::from_low_u64_be(10))].into()
+	);
+	assert_eq!(
+		witness.codes.get(&witness.accounts[&CONTRACT].code_hash),
+		Some(&code())
+	);
+	assert_eq!(
+		witness.block_hashes,
+		[(U256::from(9), H256::from_low_u64_be(100))].into()
+	);
+
+	recording.clear();
+	assert_eq!(recording.witness(), Witness::default());
+}
+
+#[test]
+fn witness_is_enough_to_execute_again() {
+	let backend = backend();
+	let recording = RecordingBackend::new(&backend);
+	let expected = call(&recording);
+
+	let stateless = from_witness(&recording.witness());
+	assert!(!stateless.state.contains_key(&UNTOUCHED));
+	assert_eq!(call(&stateless), expected);
+}
+
+#[cfg(feature = "trie")]
+#[test]
+fn witness_with_proofs() {
+	use evm::{backend::TrieBackend, interpreter::utils::keccak256};
+
+	let backend = backend();
+	let trie = TrieBackend::from_genesis(environment(), backend.state.clone());
+	let mut recording = RecordingBackend::new(trie);
+	call(&recording);
+
+	let witness = recording.witness_with_proofs();
+	let state_root = witness.state_root.unwrap();
+	for account in witness.accounts.values() {
+		assert_eq!(keccak256(&account.proof[0]), state_root);
+	}
+	let contract = &witness.accounts[&CONTRACT];
+	assert_eq!(
+		contract.storage_proofs.keys().cloned().collect::<Vec<_>>(),
+		[H256::from_low_u64_be(1)]
+	);
+}
@@ -0,0 +1,238 @@
+use evm::{
+	interpreter::{
+		error::ExitError,
+		runtime::{
+			Log, RuntimeBackend, RuntimeBaseBackend, RuntimeEnvironment, SetCodeOrigin, Transfer,
+		},
+	},
+	MergeStrategy, TransactionalBackend,
+};
+use primitive_types::{H160, H256, U256};
+
+use crate::FrameTracer;
+
+/// A wrapper over a backend notifying a tracer of the logs emitted and the
+/// self-destructs.
+///
+/// Use it as the backend of a [crate::TracingInvoker], which notifies the
+/// tracer of the call frames.
+pub struct TracedBackend<H, T> {
+	backend: H,
+	tracer: T,
+	/// The last transfer, which sends the balance of a self-destructing
+	/// contract right before it is marked as deleted.
+	last_transfer: Option<Transfer>,
+}
+
+impl<H, T> TracedBackend<H, T> {
+	/// Wrap a backend with a tracer.
+	pub fn new(backend: H, tracer: T) -> Self {
+		Self {
+			backend,
+			tracer,
+			last_transfer: None,
+		}
+	}
+
+	/// The inner backend.
+	pub fn inner(&self) -> &H {
+		&self.backend
+	}
+
+	/// The tracer.
+	pub fn tracer(&self) -> &T {
+		&self.tracer
+	}
+
+	/// The tracer, mutably.
+	pub fn tracer_mut(&mut self) -> &mut T {
+		&mut self.tracer
+	}
+
+	pub(crate) fn parts_mut(&mut self) -> (&mut H, &mut T) {
+		(&mut self.backend, &mut self.tracer)
+	}
+
+	/// Unwrap the inner backend and the tracer.
+	pub fn into_parts(self) -> (H, T) {
+		(self.backend, self.tracer)
+	}
+}
+
+impl<H: RuntimeEnvironment, T> RuntimeEnvironment for TracedBackend<H, T> {
+	fn block_hash(&self, number: U256) -> H256 {
+		self.backend.block_hash(number)
+	}
+
+	fn block_number(&self) -> U256 {
+		self.backend.block_number()
+	}
+
+	fn block_coinbase(&self) -> H160 {
+		self.backend.block_coinbase()
+	}
+
+	fn block_timestamp(&self) -> U256 {
+		self.backend.block_timestamp()
+	}
+
+	fn block_difficulty(&self) -> U256 {
+		self.backend.block_difficulty()
+	}
+
+	fn block_randomness(&self) -> Option<H256> {
+		self.backend.block_randomness()
+	}
+
+	fn block_gas_limit(&self) -> U256 {
+		self.backend.block_gas_limit()
+	}
+
+	fn block_base_fee_per_gas(&self) -> U256 {
+		self.backend.block_base_fee_per_gas()
+	}
+
+	fn block_excess_blob_gas(&self) -> U256 {
+		self.backend.block_excess_blob_gas()
+	}
+
+	fn chain_id(&self) -> U256 {
+		self.backend.chain_id()
+	}
+}
+
+impl<H: RuntimeBaseBackend, T> RuntimeBaseBackend for TracedBackend<H, T> {
+	fn balance(&self, address: H160) -> U256 {
+		self.backend.balance(address)
+	}
+
+	fn code_size(&self, address: H160) -> U256 {
+		self.backend.code_size(address)
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		self.backend.code_hash(address)
+	}
+
+	fn code(&self, address: H160) -> Vec<u8> {
+		self.backend.code(address)
+	}
+
+	fn storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.storage(address, index)
+	}
+
+	fn transient_storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.transient_storage(address, index)
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		self.backend.exists(address)
+	}
+
+	fn nonce(&self, address: H160) -> U256 {
+		self.backend.nonce(address)
+	}
+}
+
+impl<H: RuntimeBackend, T: FrameTracer> RuntimeBackend for TracedBackend<H, T> {
+	fn original_storage(&self, address: H160, index: H256) -> H256 {
+		self.backend.original_storage(address, index)
+	}
+
+	fn deleted(&self, address: H160) -> bool {
+		self.backend.deleted(address)
+	}
+
+	fn created_in_transaction(&self, address: H160) -> bool {
+		self.backend.created_in_transaction(address)
+	}
+
+	fn is_cold(&self, address: H160, index: Option<H256>) -> bool {
+		self.backend.is_cold(address, index)
+	}
+
+	fn is_hot(&self, address: H160, index: Option<H256>) -> bool {
+		self.backend.is_hot(address, index)
+	}
+
+	fn mark_hot(&mut self, address: H160, index: Option<H256>) {
+		self.backend.mark_hot(address, index)
+	}
+
+	fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError> {
+		self.backend.set_storage(address, index, value)
+	}
+
+	fn set_transient_storage(
+		&mut self,
+		address: H160,
+		index: H256,
+		value: H256,
+	) -> Result<(), ExitError> {
+		self.backend.set_transient_storage(address, index, value)
+	}
+
+	fn log(&mut self, log: Log) -> Result<(), ExitError> {
+		self.tracer.on_log(&log);
+		self.backend.log(log)
+	}
+
+	fn mark_create(&mut self, address: H160) {
+		self.backend.mark_create(address)
+	}
+
+	fn mark_delete(&mut self, address: H160) {
+		if let Some(transfer) = self.last_transfer.take() {
+			if transfer.source == address {
+				self.tracer
+					.on_selfdestruct(address, transfer.target, transfer.value);
+			}
+		}
+		self.backend.mark_delete(address)
+	}
+
+	fn reset_storage(&mut self, address: H160) {
+		self.backend.reset_storage(address)
+	}
+
+	fn set_code(
+		&mut self,
+		address: H160,
+		code: Vec<u8>,
+		origin: SetCodeOrigin,
+	) -> Result<(), ExitError> {
+		self.backend.set_code(address, code, origin)
+	}
+
+	fn reset_balance(&mut self, address: H160) {
+		self.backend.reset_balance(address)
+	}
+
+	fn deposit(&mut self, target: H160, value: U256) {
+		self.backend.deposit(target, value)
+	}
+
+	fn withdrawal(&mut self, source: H160, value: U256) -> Result<(), ExitError> {
+		self.backend.withdrawal(source, value)
+	}
+
+	fn transfer(&mut self, transfer: Transfer) -> Result<(), ExitError> {
+		self.last_transfer = Some(transfer.clone());
+		self.backend.transfer(transfer)
+	}
+
+	fn inc_nonce(&mut self, address: H160) -> Result<(), ExitError> {
+		self.backend.inc_nonce(address)
+	}
+}
+
+impl<H: TransactionalBackend, T> TransactionalBackend for TracedBackend<H, T> {
+	fn push_substate(&mut self) {
+		self.backend.push_substate()
+	}
+
+	fn pop_substate(&mut self, strategy: MergeStrategy) {
+		self.backend.pop_substate(strategy)
+	}
+}
@@ -0,0 +1,259 @@
+use evm::interpreter::{
+	error::{CallScheme, ExitError},
+	runtime::Log,
+};
+use primitive_types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::{error_message, FrameEnter, FrameExit, FrameKind, FrameTracer};
+
+/// Options of a [CallTracer], as in the `tracerConfig` of geth's
+/// `callTracer`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CallTracerConfig {
+	/// Only trace the transaction call, not its subcalls.
+	pub only_top_call: bool,
+	/// Capture the logs emitted by the calls.
+	pub with_log: bool,
+}
+
+/// A log captured by a [CallTracer].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct CallLog {
+	/// Address emitting the log.
+	pub address: H160,
+	/// Topics of the log.
+	pub topics: Vec<H256>,
+	/// Data of the log.
+	#[serde(with = "hex_bytes")]
+	pub data: Vec<u8>,
+	/// Number of subcalls of the frame made before the log was emitted.
+	pub position: U256,
+}
+
+/// A call frame traced by a [CallTracer], serializing to the format of
+/// geth's `callTracer`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+	/// Address of the caller.
+	pub from: H160,
+	/// Gas available to the call.
+	pub gas: U256,
+	/// Gas used by the call, including its subcalls.
+	pub gas_used: U256,
+	/// Address called, or the created address. `None` for a failed create.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub to: Option<H160>,
+	/// Call data, or init code for a create.
+	#[serde(with = "hex_bytes")]
+	pub input: Vec<u8>,
+	/// Return value, or deployed code for a create. Empty if the call
+	/// failed other than by reverting.
+	#[serde(with = "hex_bytes", skip_serializing_if = "Vec::is_empty")]
+	pub output: Vec<u8>,
+	/// Error of a failed call.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Reason of a call reverted with `Error(string)`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub revert_reason: Option<String>,
+	/// Subcalls, in order.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub calls: Vec<CallFrame>,
+	/// Logs emitted, if enabled with [CallTracerConfig::with_log]. Logs of
+	/// failed calls are removed.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub logs: Vec<CallLog>,
+	/// Value of the call. `None` for `STATICCALL`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub value: Option<U256>,
+	/// Kind of the call: `CALL`, `STATICCALL`, `CREATE2`, etc.
+	#[serde(rename = "type")]
+	pub kind: String,
+}
+
+impl CallFrame {
+	fn clear_logs(&mut self, parent_failed: bool) {
+		let failed = parent_failed || self.error.is_some();
+		if failed {
+			self.logs.clear();
+		}
+		for call in &mut self.calls {
+			call.clear_logs(failed);
+		}
+	}
+}
+
+/// A tracer building the call tree of a transaction, like geth's
+/// `callTracer`.
+#[derive(Clone, Debug, Default)]
+pub struct CallTracer {
+	config: CallTracerConfig,
+	stack: Vec<CallFrame>,
+	/// Depth of the untraced subcalls with only_top_call.
+	skipped: usize,
+	result: Option<CallFrame>,
+}
+
+impl CallTracer {
+	/// Create a new tracer.
+	pub fn new(config: CallTracerConfig) -> Self {
+		Self {
+			config,
+			..Default::default()
+		}
+	}
+
+	/// The traced transaction call, once it is finished.
+	pub fn result(&self) -> Option<&CallFrame> {
+		self.result.as_ref()
+	}
+
+	/// Take the traced transaction call, once it is finished.
+	pub fn into_result(self) -> Option<CallFrame> {
+		self.result
+	}
+}
+
+impl FrameTracer for CallTracer {
+	fn on_enter(&mut self, frame: &FrameEnter<'_>) {
+		if self.skipped > 0 || (self.config.only_top_call && !self.stack.is_empty()) {
+			self.skipped += 1;
+			return;
+		}
+
+		self.stack.push(CallFrame {
+			from: frame.from,
+			gas: frame.gas,
+			gas_used: U256::zero(),
+			to: Some(frame.to),
+			input: frame.input.to_vec(),
+			output: Vec::new(),
+			error: None,
+			revert_reason: None,
+			calls: Vec::new(),
+			logs: Vec::new(),
+			value: frame.value,
+			kind: kind_name(frame.kind).into(),
+		});
+	}
+
+	fn on_exit(&mut self, exit: &FrameExit<'_>) {
+		if self.skipped > 0 {
+			self.skipped -= 1;
+			return;
+		}
+
+		let mut frame = match self.stack.pop() {
+			Some(frame) => frame,
+			None => return,
+		};
+
+		frame.gas_used = frame.gas.saturating_sub(exit.gas_left);
+		match exit.result {
+			Ok(_) => frame.output = exit.output.to_vec(),
+			Err(err) => {
+				frame.error = Some(error_message(err));
+				if frame.kind.starts_with("CREATE") {
+					frame.to = None;
+				}
+				if *err == ExitError::Reverted {
+					frame.output = exit.output.to_vec();
+					frame.revert_reason = revert_reason(exit.output);
+				}
+			}
+		}
+
+		match self.stack.last_mut() {
+			Some(parent) => parent.calls.push(frame),
+			None => {
+				frame.clear_logs(false);
+				self.result = Some(frame);
+			}
+		}
+	}
+
+	fn on_log(&mut self, log: &Log) {
+		if !self.config.with_log || self.skipped > 0 {
+			return;
+		}
+
+		if let Some(frame) = self.stack.last_mut() {
+			frame.logs.push(CallLog {
+				address: log.address,
+				topics: log.topics.clone(),
+				data: log.data.clone(),
+				position: U256::from(frame.calls.len()),
+			});
+		}
+	}
+
+	fn on_selfdestruct(&mut self, address: H160, beneficiary: H160, balance: U256) {
+		if self.skipped > 0 || self.config.only_top_call {
+			return;
+		}
+
+		if let Some(frame) = self.stack.last_mut() {
+			frame.calls.push(CallFrame {
+				from: address,
+				gas: U256::zero(),
+				gas_used: U256::zero(),
+				to: Some(beneficiary),
+				input: Vec::new(),
+				output: Vec::new(),
+				error: None,
+				revert_reason: None,
+				calls: Vec::new(),
+				logs: Vec::new(),
+				value: Some(balance),
+				kind: "SELFDESTRUCT".into(),
+			});
+		}
+	}
+}
+
+fn kind_name(kind: FrameKind) -> &'static str {
+	match kind {
+		FrameKind::Call(CallScheme::Call) => "CALL",
+		FrameKind::Call(CallScheme::CallCode) => "CALLCODE",
+		FrameKind::Call(CallScheme::DelegateCall) => "DELEGATECALL",
+		FrameKind::Call(CallScheme::StaticCall) => "STATICCALL",
+		#[cfg(feature = "eip3074")]
+		FrameKind::Call(CallScheme::AuthCall) => "AUTHCALL",
+		FrameKind::Create => "CREATE",
+		FrameKind::Create2 => "CREATE2",
+	}
+}
+
+/// Decode the reason of a revert with `Error(string)`.
+fn revert_reason(output: &[u8]) -> Option<String> {
+	const SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+	if output.len() < 4 + 64 || output[..4] != SELECTOR {
+		return None;
+	}
+	let data = &output[4..];
+	let offset = U256::from_big_endian(&data[..32]);
+	if offset > U256::from(data.len() - 32) {
+		return None;
+	}
+	let offset = offset.as_usize();
+	let len = U256::from_big_endian(&data[offset..offset + 32]);
+	if len > U256::from(data.len() - offset - 32) {
+		return None;
+	}
+	let start = offset + 32;
+	let reason = &data[start..start + len.as_usize()];
+
+	String::from_utf8(reason.to_vec()).ok()
+}
+
+mod hex_bytes {
+	use serde::Serializer;
+
+	pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&format!("0x{}", hex::encode(value)))
+	}
+}
@@ -0,0 +1,58 @@
+use evm::interpreter::{
+	etable::{Control, EtableSet},
+	machine::Machine,
+	opcode::Opcode,
+};
+
+use crate::{EvalTracer, TracedBackend};
+
+/// An [EtableSet] wrapper notifying the tracer of a [TracedBackend] before
+/// and after every opcode is evaluated.
+pub struct TracingEtable<ES> {
+	etable: ES,
+}
+
+impl<ES> TracingEtable<ES> {
+	/// Wrap an etable.
+	pub const fn new(etable: ES) -> Self {
+		Self { etable }
+	}
+
+	/// Get the inner etable.
+	pub fn inner(&self) -> &ES {
+		&self.etable
+	}
+}
+
+impl<ES, H, T> EtableSet for TracingEtable<ES>
+where
+	ES: EtableSet<Handle = TracedBackend<H, T>>,
+	T: EvalTracer<ES::State, H, ES::Memory>,
+{
+	type State = ES::State;
+	type Handle = TracedBackend<H, T>;
+	type Trap = ES::Trap;
+	type Memory = ES::Memory;
+
+	fn eval(
+		&self,
+		machine: &mut Machine<Self::State, Self::Memory>,
+		handle: &mut Self::Handle,
+		opcode: Opcode,
+		position: usize,
+	) -> Control<Self::Trap> {
+		let (backend, tracer) = handle.parts_mut();
+		tracer.on_eval(machine, backend, opcode, position);
+
+		let control = self.etable.eval(machine, handle, opcode, position);
+
+		let error = match &control {
+			Control::Exit(Err(err)) => Some(err),
+			_ => None,
+		};
+		let (backend, tracer) = handle.parts_mut();
+		tracer.after_eval(machine, backend, error);
+
+		control
+	}
+}
@@ -0,0 +1,233 @@
+use evm::{
+	interpreter::{
+		error::{CallScheme, Capture, CreateScheme, ExitError, ExitResult},
+		runtime::{GasState, RuntimeState},
+		Interpreter,
+	},
+	standard::{InvokerState, SubstackInvoke, TransactArgs, TransactInvoke},
+	CallStackPolicy, Invoker, InvokerControl,
+};
+use primitive_types::U256;
+
+use crate::{FrameEnter, FrameExit, FrameKind, FrameTracer, TracedBackend};
+
+/// A wrapper over a standard invoker notifying the tracer of a
+/// [TracedBackend] when call frames are entered and exited.
+pub struct TracingInvoker<I> {
+	invoker: I,
+}
+
+impl<I> TracingInvoker<I> {
+	/// Wrap an invoker.
+	pub fn new(invoker: I) -> Self {
+		Self { invoker }
+	}
+
+	/// The inner invoker.
+	pub fn inner(&self) -> &I {
+		&self.invoker
+	}
+}
+
+impl<'config, H, T, Tr, I> Invoker<TracedBackend<H, T>, Tr> for TracingInvoker<I>
+where
+	T: FrameTracer,
+	I: Invoker<
+		TracedBackend<H, T>,
+		Tr,
+		TransactArgs = TransactArgs,
+		TransactInvoke = TransactInvoke,
+		SubstackInvoke = SubstackInvoke,
+	>,
+	I::State: InvokerState<'config> + AsRef<RuntimeState>,
+{
+	type State = I::State;
+	type Interpreter = I::Interpreter;
+	type Interrupt = I::Interrupt;
+	type TransactArgs = TransactArgs;
+	type TransactInvoke = TransactInvoke;
+	type TransactValue = I::TransactValue;
+	type SubstackInvoke = SubstackInvoke;
+
+	fn new_transact(
+		&self,
+		args: TransactArgs,
+		handler: &mut TracedBackend<H, T>,
+	) -> Result<
+		(
+			TransactInvoke,
+			InvokerControl<I::Interpreter, (ExitResult, (I::State, Vec<u8>))>,
+		),
+		ExitError,
+	> {
+		let from = args.caller();
+		let value = args.value();
+		let gas = args.gas_limit();
+		let (kind, to, input) = match &args {
+			TransactArgs::Call { address, data, .. } => (
+				FrameKind::Call(CallScheme::Call),
+				Some(*address),
+				data.clone(),
+			),
+			TransactArgs::Create { init_code, .. } => (FrameKind::Create, None, init_code.clone()),
+			#[cfg(feature = "op")]
+			TransactArgs::Deposit(deposit) => match deposit.address {
+				Some(address) => (
+					FrameKind::Call(CallScheme::Call),
+					Some(address),
+					deposit.data.clone(),
+				),
+				None => (FrameKind::Create, None, deposit.data.clone()),
+			},
+		};
+
+		let (invoke, control) = self.invoker.new_transact(args, handler)?;
+
+		handler.tracer_mut().on_enter(&FrameEnter {
+			kind,
+			depth: 0,
+			from,
+			to: to.or(invoke.create_address).unwrap_or_default(),
+			value: Some(value),
+			input: &input,
+			gas,
+		});
+
+		Ok((invoke, control))
+	}
+
+	fn finalize_transact(
+		&self,
+		invoke: &TransactInvoke,
+		exit: ExitResult,
+		machine: (I::State, Vec<u8>),
+		handler: &mut TracedBackend<H, T>,
+	) -> Result<I::TransactValue, ExitError> {
+		let left_gas = machine.0.effective_gas();
+		let code_deposit_gas = match (&exit, invoke.create_address) {
+			(Ok(_), Some(_)) => U256::from(
+				machine.1.len() as u64 * machine.0.config().gas_schedule.gas_code_deposit,
+			),
+			_ => U256::zero(),
+		};
+		let output = machine.1.clone();
+		let succeed = exit.clone();
+
+		let value = self
+			.invoker
+			.finalize_transact(invoke, exit, machine, handler);
+
+		let (result, gas_left) = match &value {
+			Ok(_) => (succeed, left_gas.saturating_sub(code_deposit_gas)),
+			Err(ExitError::Reverted) => (Err(ExitError::Reverted), left_gas),
+			Err(err) => (Err(err.clone()), U256::zero()),
+		};
+
+		handler.tracer_mut().on_exit(&FrameExit {
+			result: &result,
+			output: &output,
+			gas_left,
+		});
+
+		value
+	}
+
+	fn call_stack_policy(&self) -> CallStackPolicy {
+		self.invoker.call_stack_policy()
+	}
+
+	fn enter_substack(
+		&self,
+		trap: Tr,
+		machine: &mut I::Interpreter,
+		handler: &mut TracedBackend<H, T>,
+		depth: usize,
+	) -> Capture<
+		Result<
+			(
+				SubstackInvoke,
+				InvokerControl<I::Interpreter, (ExitResult, (I::State, Vec<u8>))>,
+			),
+			ExitError,
+		>,
+		I::Interrupt,
+	> {
+		let parent_gas = machine.machine().state.gas();
+		let parent_context = machine.machine().state.as_ref().context.clone();
+
+		let entered = self.invoker.enter_substack(trap, machine, handler, depth);
+
+		if let Capture::Exit(Ok((invoke, _))) = &entered {
+			let state = &machine.machine().state;
+			let mut gas = parent_gas.saturating_sub(state.gas());
+
+			let frame = match invoke {
+				SubstackInvoke::Call { trap } => {
+					let value = match trap.scheme {
+						CallScheme::StaticCall => None,
+						CallScheme::DelegateCall => Some(parent_context.apparent_value),
+						_ => Some(trap.transfer.as_ref().map_or(U256::zero(), |t| t.value)),
+					};
+					// The stipend is given to the child on top of the gas
+					// taken from the parent.
+					if trap.has_value() {
+						gas = gas.saturating_add(U256::from(state.config().call_stipend));
+					}
+
+					FrameEnter {
+						kind: FrameKind::Call(trap.scheme),
+						depth,
+						from: parent_context.address,
+						to: trap.target,
+						value,
+						input: &trap.input,
+						gas,
+					}
+				}
+				SubstackInvoke::Create { trap, address } => FrameEnter {
+					kind: match trap.scheme {
+						CreateScheme::Legacy { .. } => FrameKind::Create,
+						CreateScheme::Create2 { .. } => FrameKind::Create2,
+					},
+					depth,
+					from: trap.scheme.caller(),
+					to: *address,
+					value: Some(trap.value),
+					input: &trap.code,
+					gas,
+				},
+			};
+
+			handler.tracer_mut().on_enter(&frame);
+		}
+
+		entered
+	}
+
+	fn exit_substack(
+		&self,
+		result: ExitResult,
+		child: (I::State, Vec<u8>),
+		trap_data: SubstackInvoke,
+		parent: &mut I::Interpreter,
+		handler: &mut TracedBackend<H, T>,
+	) -> Result<(), ExitError> {
+		let parent_gas = parent.machine().state.gas();
+		let exit = result.clone();
+		let output = child.1.clone();
+
+		let feedback = self
+			.invoker
+			.exit_substack(result, child, trap_data, parent, handler);
+
+		handler.tracer_mut().on_exit(&FrameExit {
+			result: &exit,
+			output: &output,
+			// What is left in the child is returned to the parent, unless it
+			// failed.
+			gas_left: parent.machine().state.gas().saturating_sub(parent_gas),
+		});
+
+		feedback
+	}
+}
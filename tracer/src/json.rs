@@ -0,0 +1,231 @@
+use std::io::{self, Write};
+
+use evm::{
+	interpreter::{
+		error::ExitError,
+		opcode::Opcode,
+		runtime::{GasState, RuntimeState},
+	},
+	standard::Machine,
+};
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::{error_message, FrameEnter, FrameExit, FrameTracer};
+
+/// Options of a [JsonLogger], as the `--trace.*` flags of geth's `evm`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct JsonLoggerConfig {
+	/// Capture the memory.
+	pub enable_memory: bool,
+	/// Do not capture the stack.
+	pub disable_stack: bool,
+	/// Capture the return data of the last call.
+	pub enable_return_data: bool,
+}
+
+/// An opcode evaluation written by a [JsonLogger].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonLog {
+	/// Position of the opcode in the code.
+	pub pc: usize,
+	/// Opcode.
+	pub op: u8,
+	/// Gas left before the opcode.
+	pub gas: U256,
+	/// Gas charged by the opcode. For calls and creates, this does not
+	/// include the gas given to the subcall.
+	pub gas_cost: U256,
+	/// Memory before the opcode.
+	#[serde(
+		serialize_with = "serialize_bytes",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub memory: Option<Vec<u8>>,
+	/// Size of the memory before the opcode.
+	pub mem_size: u64,
+	/// Stack before the opcode, from bottom to top.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// Return data of the last call.
+	#[serde(
+		serialize_with = "serialize_bytes",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub return_data: Option<Vec<u8>>,
+	/// Depth of the call frame, one for the transaction.
+	pub depth: usize,
+	/// Gas refund counter before the opcode.
+	pub refund: u64,
+	/// Mnemonic of the opcode.
+	pub op_name: String,
+	/// Error of the opcode, if it failed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+/// Last line written by a [JsonLogger], when the transaction finishes.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonSummary {
+	/// Return value of the transaction, in hex without prefix.
+	pub output: String,
+	/// Gas used by the transaction.
+	pub gas_used: U256,
+	/// Error of the transaction, if it failed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
+/// A tracer writing a JSON line per opcode evaluation as it goes, in the
+/// [EIP-3155](https://eips.ethereum.org/EIPS/eip-3155) format of geth's
+/// `--trace.format=json`, then a [JsonSummary] line.
+///
+/// Write errors stop the tracing, and are returned by
+/// [JsonLogger::into_inner].
+#[derive(Debug)]
+pub struct JsonLogger<W> {
+	writer: W,
+	config: JsonLoggerConfig,
+	/// Opcode being evaluated, written once its cost is known.
+	pending: Option<JsonLog>,
+	depth: usize,
+	gas_limit: U256,
+	/// Refund counter of the parent frames when each frame was entered.
+	refund_bases: Vec<u64>,
+	refund: u64,
+	error: Option<io::Error>,
+}
+
+impl<W: Write> JsonLogger<W> {
+	/// Create a new tracer writing to `writer`.
+	pub fn new(writer: W, config: JsonLoggerConfig) -> Self {
+		Self {
+			writer,
+			config,
+			pending: None,
+			depth: 0,
+			gas_limit: U256::zero(),
+			refund_bases: Vec::new(),
+			refund: 0,
+			error: None,
+		}
+	}
+
+	/// The writer, or the first error writing to it.
+	pub fn into_inner(self) -> io::Result<W> {
+		match self.error {
+			Some(err) => Err(err),
+			None => Ok(self.writer),
+		}
+	}
+
+	fn write_line<T: Serialize>(&mut self, value: &T) {
+		if self.error.is_some() {
+			return;
+		}
+
+		let result = serde_json::to_writer(&mut self.writer, value)
+			.map_err(io::Error::from)
+			.and_then(|()| self.writer.write_all(b"\n"));
+		if let Err(err) = result {
+			self.error = Some(err);
+		}
+	}
+}
+
+impl<H, W: Write> crate::standard::EvalTracer<H> for JsonLogger<W> {
+	fn on_eval(&mut self, machine: &Machine, _handle: &H, opcode: Opcode, position: usize) {
+		self.refund = self.refund_bases.last().copied().unwrap_or_default()
+			+ machine.state.gasometer.refunded_gas();
+
+		let memory = if self.config.enable_memory {
+			let len = machine.memory.effective_len().low_u64() as usize;
+			Some(machine.memory.get(0, len))
+		} else {
+			None
+		};
+
+		let stack = if self.config.disable_stack {
+			None
+		} else {
+			Some(
+				machine
+					.stack
+					.data()
+					.iter()
+					.map(|value| U256::from_big_endian(&value[..]))
+					.collect(),
+			)
+		};
+
+		let return_data = if self.config.enable_return_data {
+			Some(AsRef::<RuntimeState>::as_ref(&machine.state).retbuf.clone())
+		} else {
+			None
+		};
+
+		self.pending = Some(JsonLog {
+			pc: position,
+			op: opcode.as_u8(),
+			gas: machine.state.gas(),
+			gas_cost: U256::zero(),
+			memory,
+			mem_size: machine.memory.effective_len().low_u64(),
+			stack,
+			return_data,
+			depth: self.depth,
+			refund: self.refund,
+			op_name: match opcode.name() {
+				Some(name) => name.into(),
+				None => format!("opcode {:#04x} not defined", opcode.as_u8()),
+			},
+			error: None,
+		});
+	}
+
+	fn after_eval(&mut self, machine: &Machine, _handle: &H, error: Option<&ExitError>) {
+		if let Some(mut log) = self.pending.take() {
+			log.gas_cost = log.gas.saturating_sub(machine.state.gas());
+			log.error = error.map(error_message);
+			self.write_line(&log);
+		}
+	}
+}
+
+impl<W: Write> FrameTracer for JsonLogger<W> {
+	fn on_enter(&mut self, frame: &FrameEnter<'_>) {
+		if self.depth == 0 {
+			self.gas_limit = frame.gas;
+			self.refund = 0;
+		}
+		self.refund_bases.push(self.refund);
+		self.depth += 1;
+	}
+
+	fn on_exit(&mut self, exit: &FrameExit<'_>) {
+		self.refund_bases.pop();
+		self.depth = self.depth.saturating_sub(1);
+		if self.depth == 0 {
+			let summary = JsonSummary {
+				output: hex::encode(exit.output),
+				gas_used: self.gas_limit.saturating_sub(exit.gas_left),
+				error: exit.result.as_ref().err().map(error_message),
+			};
+			self.write_line(&summary);
+			if let Err(err) = self.writer.flush() {
+				self.error.get_or_insert(err);
+			}
+		}
+	}
+}
+
+fn serialize_bytes<S: serde::Serializer>(
+	value: &Option<Vec<u8>>,
+	serializer: S,
+) -> Result<S::Ok, S::Error> {
+	let bytes = value.as_deref().unwrap_or_default();
+	serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+}
@@ -1,7 +1,120 @@
+mod backend;
+mod call;
+mod etable;
+mod invoker;
+mod json;
+mod parity;
+mod prestate;
+mod profile;
 mod standard;
+mod struct_log;
 
-use evm::interpreter::{machine::Machine, opcode::Opcode};
+use evm::interpreter::{
+	error::{CallScheme, ExitError, ExitException, ExitResult},
+	machine::{Machine, Pages},
+	opcode::Opcode,
+	runtime::Log,
+};
+use primitive_types::{H160, U256};
 
-pub trait EvalTracer<S, H> {
-	fn on_eval(&mut self, machine: &Machine<S>, handle: &H, opcode: Opcode, position: usize);
+pub use crate::{
+	backend::TracedBackend,
+	call::{CallFrame, CallLog, CallTracer, CallTracerConfig},
+	etable::TracingEtable,
+	invoker::TracingInvoker,
+	json::{JsonLog, JsonLogger, JsonLoggerConfig, JsonSummary},
+	parity::{
+		ParityAction, ParityCallAction, ParityCreateAction, ParityResult, ParitySuicideAction,
+		ParityTrace, ParityTracer,
+	},
+	prestate::{Prestate, PrestateAccount, PrestateTracer, PrestateTracerConfig},
+	profile::{FrameProfile, GasProfiler, OpcodeProfile},
+	struct_log::{StructLog, StructLogger, StructLoggerConfig, StructLoggerResult},
+};
+
+pub trait EvalTracer<S, H, M = Pages> {
+	fn on_eval(&mut self, machine: &Machine<S, M>, handle: &H, opcode: Opcode, position: usize);
+
+	/// The opcode of the last [EvalTracer::on_eval] is evaluated, failing
+	/// with `error` if set.
+	fn after_eval(&mut self, _machine: &Machine<S, M>, _handle: &H, _error: Option<&ExitError>) {}
+}
+
+/// Kind of a call frame.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FrameKind {
+	/// A message call, and the scheme of the call. A call transaction is a
+	/// [CallScheme::Call].
+	Call(CallScheme),
+	/// `CREATE`, or a create transaction.
+	Create,
+	/// `CREATE2`.
+	Create2,
+}
+
+/// A call frame being entered.
+#[derive(Clone, Debug)]
+pub struct FrameEnter<'a> {
+	/// Kind of the frame.
+	pub kind: FrameKind,
+	/// Depth of the frame, zero for the transaction.
+	pub depth: usize,
+	/// Address of the caller.
+	pub from: H160,
+	/// Address called, or the created address.
+	pub to: H160,
+	/// Value of the call. `None` for `STATICCALL`. For `DELEGATECALL`, this
+	/// is the value of the parent call.
+	pub value: Option<U256>,
+	/// Call data, or init code for a create.
+	pub input: &'a [u8],
+	/// Gas available to the frame.
+	pub gas: U256,
+}
+
+/// A call frame being exited.
+#[derive(Clone, Debug)]
+pub struct FrameExit<'a> {
+	/// Exit result of the frame.
+	pub result: &'a ExitResult,
+	/// Return value, or deployed code for a create.
+	pub output: &'a [u8],
+	/// Gas left and returned to the caller, zero if the frame failed other
+	/// than by reverting. For the transaction, this is after refunds.
+	pub gas_left: U256,
+}
+
+/// Tracer of the call frames of a transaction, notified by a
+/// [TracingInvoker] and a [TracedBackend].
+pub trait FrameTracer {
+	/// A call frame is entered.
+	fn on_enter(&mut self, frame: &FrameEnter<'_>);
+	/// The last entered call frame is exited.
+	fn on_exit(&mut self, exit: &FrameExit<'_>);
+	/// A log is emitted in the last entered call frame.
+	fn on_log(&mut self, _log: &Log) {}
+	/// The contract of the last entered call frame self-destructs, sending
+	/// its balance to `beneficiary`.
+	fn on_selfdestruct(&mut self, _address: H160, _beneficiary: H160, _balance: U256) {}
+}
+
+/// Error message of geth for the common errors.
+pub(crate) fn error_message(err: &ExitError) -> String {
+	match err {
+		ExitError::Reverted => "execution reverted",
+		ExitError::Exception(ExitException::OutOfGas) => "out of gas",
+		ExitError::Exception(ExitException::StackUnderflow) => "stack underflow",
+		ExitError::Exception(ExitException::StackOverflow) => "stack limit reached",
+		ExitError::Exception(ExitException::InvalidJump) => "invalid jump destination",
+		ExitError::Exception(ExitException::InvalidOpcode(_))
+		| ExitError::Exception(ExitException::DesignatedInvalid) => "invalid opcode",
+		ExitError::Exception(ExitException::CallTooDeep) => "max call depth exceeded",
+		ExitError::Exception(ExitException::CreateCollision) => "contract address collision",
+		ExitError::Exception(ExitException::CreateContractLimit) => "max code size exceeded",
+		ExitError::Exception(ExitException::OutOfFund) => "insufficient balance for transfer",
+		ExitError::Exception(ExitException::OutOfOffset) => "return data out of bounds",
+		ExitError::Exception(ExitException::MaxNonce) => "nonce uint64 overflow",
+		_ => return format!("{:?}", err),
+	}
+	.into()
 }
@@ -0,0 +1,283 @@
+use evm::interpreter::error::{CallScheme, ExitError, ExitException};
+use primitive_types::{H160, U256};
+use serde::Serialize;
+
+use crate::{FrameEnter, FrameExit, FrameKind, FrameTracer};
+
+/// Action of a call trace.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityCallAction {
+	/// Scheme of the call: `call`, `callcode`, `delegatecall` or
+	/// `staticcall`.
+	pub call_type: String,
+	/// Address of the caller.
+	pub from: H160,
+	/// Gas available to the call.
+	pub gas: U256,
+	/// Call data.
+	#[serde(serialize_with = "serialize_hex")]
+	pub input: Vec<u8>,
+	/// Address called.
+	pub to: H160,
+	/// Value of the call.
+	pub value: U256,
+}
+
+/// Action of a create trace.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityCreateAction {
+	/// Scheme of the create: `create` or `create2`.
+	pub creation_method: String,
+	/// Address of the creator.
+	pub from: H160,
+	/// Gas available to the create.
+	pub gas: U256,
+	/// Init code.
+	#[serde(serialize_with = "serialize_hex")]
+	pub init: Vec<u8>,
+	/// Value sent to the created contract.
+	pub value: U256,
+}
+
+/// Action of a self-destruct trace.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParitySuicideAction {
+	/// Address of the self-destructed contract.
+	pub address: H160,
+	/// Beneficiary of the balance.
+	pub refund_address: H160,
+	/// Balance sent to the beneficiary.
+	pub balance: U256,
+}
+
+/// Action of a [ParityTrace].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ParityAction {
+	/// A call.
+	Call(ParityCallAction),
+	/// A create.
+	Create(ParityCreateAction),
+	/// A self-destruct.
+	Suicide(ParitySuicideAction),
+}
+
+/// Result of a successful [ParityTrace].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ParityResult {
+	/// Result of a call.
+	Call {
+		/// Gas used by the call, including its subcalls.
+		#[serde(rename = "gasUsed")]
+		gas_used: U256,
+		/// Return value.
+		#[serde(serialize_with = "serialize_hex")]
+		output: Vec<u8>,
+	},
+	/// Result of a create.
+	Create {
+		/// Created address.
+		address: H160,
+		/// Deployed code.
+		#[serde(serialize_with = "serialize_hex")]
+		code: Vec<u8>,
+		/// Gas used by the create, including its subcalls.
+		#[serde(rename = "gasUsed")]
+		gas_used: U256,
+	},
+}
+
+/// A flat trace, in the format of OpenEthereum's `trace_transaction`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityTrace {
+	/// Action traced.
+	pub action: ParityAction,
+	/// Result of the action, `None` if it failed or is a self-destruct.
+	pub result: Option<ParityResult>,
+	/// Error of a failed action.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Number of direct subtraces.
+	pub subtraces: usize,
+	/// Position of the trace in the call tree: the index of the subcall at
+	/// each depth.
+	pub trace_address: Vec<usize>,
+	/// Type of the action: `call`, `create` or `suicide`.
+	#[serde(rename = "type")]
+	pub kind: String,
+}
+
+/// A tracer producing the flat traces of a transaction, in the order the
+/// actions are made, like OpenEthereum's `trace_transaction`.
+///
+/// For the transaction call, the gas includes the intrinsic gas, and the
+/// gas used is after refunds.
+#[derive(Clone, Debug, Default)]
+pub struct ParityTracer {
+	traces: Vec<ParityTrace>,
+	/// Frames entered and not exited.
+	stack: Vec<Frame>,
+}
+
+#[derive(Clone, Debug)]
+struct Frame {
+	/// Index of the trace of the frame.
+	index: usize,
+	to: H160,
+	gas: U256,
+	create: bool,
+}
+
+impl ParityTracer {
+	/// Create a new tracer.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Traces so far.
+	pub fn traces(&self) -> &[ParityTrace] {
+		&self.traces
+	}
+
+	/// Take the traces.
+	pub fn into_traces(self) -> Vec<ParityTrace> {
+		self.traces
+	}
+
+	fn push(&mut self, action: ParityAction, kind: &str) -> usize {
+		let trace_address = match self.stack.last() {
+			Some(parent) => {
+				let parent = &mut self.traces[parent.index];
+				parent.subtraces += 1;
+				let mut trace_address = parent.trace_address.clone();
+				trace_address.push(parent.subtraces - 1);
+				trace_address
+			}
+			None => Vec::new(),
+		};
+
+		self.traces.push(ParityTrace {
+			action,
+			result: None,
+			error: None,
+			subtraces: 0,
+			trace_address,
+			kind: kind.into(),
+		});
+		self.traces.len() - 1
+	}
+}
+
+impl FrameTracer for ParityTracer {
+	fn on_enter(&mut self, frame: &FrameEnter<'_>) {
+		let (action, kind) = match frame.kind {
+			FrameKind::Call(scheme) => (
+				ParityAction::Call(ParityCallAction {
+					call_type: call_type(scheme).into(),
+					from: frame.from,
+					gas: frame.gas,
+					input: frame.input.to_vec(),
+					to: frame.to,
+					value: frame.value.unwrap_or_default(),
+				}),
+				"call",
+			),
+			FrameKind::Create | FrameKind::Create2 => (
+				ParityAction::Create(ParityCreateAction {
+					creation_method: if frame.kind == FrameKind::Create {
+						"create".into()
+					} else {
+						"create2".into()
+					},
+					from: frame.from,
+					gas: frame.gas,
+					init: frame.input.to_vec(),
+					value: frame.value.unwrap_or_default(),
+				}),
+				"create",
+			),
+		};
+
+		let index = self.push(action, kind);
+		self.stack.push(Frame {
+			index,
+			to: frame.to,
+			gas: frame.gas,
+			create: kind == "create",
+		});
+	}
+
+	fn on_exit(&mut self, exit: &FrameExit<'_>) {
+		let frame = match self.stack.pop() {
+			Some(frame) => frame,
+			None => return,
+		};
+		let trace = &mut self.traces[frame.index];
+
+		match exit.result {
+			Ok(_) => {
+				let gas_used = frame.gas.saturating_sub(exit.gas_left);
+				trace.result = Some(if frame.create {
+					ParityResult::Create {
+						address: frame.to,
+						code: exit.output.to_vec(),
+						gas_used,
+					}
+				} else {
+					ParityResult::Call {
+						gas_used,
+						output: exit.output.to_vec(),
+					}
+				});
+			}
+			Err(err) => trace.error = Some(error_message(err)),
+		}
+	}
+
+	fn on_selfdestruct(&mut self, address: H160, beneficiary: H160, balance: U256) {
+		self.push(
+			ParityAction::Suicide(ParitySuicideAction {
+				address,
+				refund_address: beneficiary,
+				balance,
+			}),
+			"suicide",
+		);
+	}
+}
+
+fn call_type(scheme: CallScheme) -> &'static str {
+	match scheme {
+		CallScheme::Call => "call",
+		CallScheme::CallCode => "callcode",
+		CallScheme::DelegateCall => "delegatecall",
+		CallScheme::StaticCall => "staticcall",
+		#[cfg(feature = "eip3074")]
+		CallScheme::AuthCall => "authcall",
+	}
+}
+
+/// Error message of OpenEthereum for the common errors.
+fn error_message(err: &ExitError) -> String {
+	match err {
+		ExitError::Reverted => "Reverted",
+		ExitError::Exception(ExitException::OutOfGas) => "Out of gas",
+		ExitError::Exception(ExitException::StackUnderflow) => "Stack underflow",
+		ExitError::Exception(ExitException::StackOverflow) => "Out of stack",
+		ExitError::Exception(ExitException::InvalidJump) => "Bad jump destination",
+		ExitError::Exception(ExitException::InvalidOpcode(_))
+		| ExitError::Exception(ExitException::DesignatedInvalid) => "Bad instruction",
+		ExitError::Exception(ExitException::OutOfFund) => "Insufficient balance",
+		_ => return format!("{:?}", err),
+	}
+	.into()
+}
+
+fn serialize_hex<S: serde::Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+	serializer.serialize_str(&format!("0x{}", hex::encode(value)))
+}
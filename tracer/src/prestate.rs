@@ -0,0 +1,193 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use evm::{
+	backend::{Delta, OverlayedBackend},
+	interpreter::runtime::{RuntimeBackend, RuntimeBaseBackend},
+};
+use primitive_types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
+
+/// Options of a [PrestateTracer], as in the `tracerConfig` of geth's
+/// `prestateTracer`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrestateTracerConfig {
+	/// Report the changed accounts before and after the execution, instead
+	/// of the accessed accounts before it.
+	pub diff_mode: bool,
+	/// Do not report codes.
+	pub disable_code: bool,
+	/// Do not report storages.
+	pub disable_storage: bool,
+}
+
+/// State of an account reported by a [PrestateTracer]. In the post state of
+/// the diff mode, only the changed fields are set.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct PrestateAccount {
+	/// Balance.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub balance: Option<U256>,
+	/// Nonce. Omitted if zero.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub nonce: Option<u64>,
+	/// Code. Omitted if empty.
+	#[serde(
+		serialize_with = "serialize_code",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub code: Option<Vec<u8>>,
+	/// Storage slots.
+	#[serde(skip_serializing_if = "BTreeMap::is_empty")]
+	pub storage: BTreeMap<H256, H256>,
+}
+
+/// Outcome of a [PrestateTracer].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Prestate {
+	/// Accessed accounts, before the execution.
+	Accounts(BTreeMap<H160, PrestateAccount>),
+	/// Changed accounts, before and after the execution.
+	Diff {
+		/// Changed accounts that existed, before the execution.
+		pre: BTreeMap<H160, PrestateAccount>,
+		/// Changed accounts that still exist, after the execution.
+		post: BTreeMap<H160, PrestateAccount>,
+	},
+}
+
+/// A tracer reporting the state accessed by a transaction before it was
+/// executed, like geth's `prestateTracer`.
+///
+/// It reads what was accessed and changed from the overlay the transaction
+/// was executed in, so it does not hook into the execution: trace the
+/// overlay after the transaction, before deconstructing it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PrestateTracer {
+	config: PrestateTracerConfig,
+}
+
+impl PrestateTracer {
+	/// Create a new tracer.
+	pub fn new(config: PrestateTracerConfig) -> Self {
+		Self { config }
+	}
+
+	/// Trace the transactions executed in `overlay`.
+	pub fn trace<B: RuntimeBaseBackend>(&self, overlay: &OverlayedBackend<B>) -> Prestate {
+		let diff = overlay.state_diff();
+
+		if !self.config.diff_mode {
+			let mut accessed = BTreeMap::<H160, BTreeSet<H256>>::new();
+			for (address, index) in overlay.accessed() {
+				let slots = accessed.entry(*address).or_default();
+				slots.extend(index);
+			}
+			for address in diff.accounts.keys() {
+				accessed.entry(*address).or_default();
+			}
+
+			let accounts = accessed
+				.into_iter()
+				.map(|(address, slots)| {
+					let account = self.account(overlay.inner(), address, slots);
+					(address, account)
+				})
+				.collect();
+
+			return Prestate::Accounts(accounts);
+		}
+
+		let mut pre = BTreeMap::new();
+		let mut post = BTreeMap::new();
+		for (address, account) in diff.accounts {
+			let mut before = PrestateAccount::default();
+			let mut after = PrestateAccount::default();
+			let removed = matches!(account.balance, Delta::Removed(_));
+
+			if let Some((from, to)) = values(account.balance) {
+				before.balance = from;
+				after.balance = to;
+			}
+			if let Some((from, to)) = values(account.nonce) {
+				before.nonce = from
+					.map(|nonce| nonce.low_u64())
+					.filter(|nonce| *nonce != 0);
+				after.nonce = to.map(|nonce| nonce.low_u64()).filter(|nonce| *nonce != 0);
+			}
+			if !self.config.disable_code {
+				if let Some((from, to)) = values(account.code) {
+					before.code = from.filter(|code| !code.is_empty());
+					after.code = to.filter(|code| !code.is_empty());
+				}
+			}
+			if !self.config.disable_storage {
+				for (index, delta) in account.storage {
+					if let Some((from, to)) = values(delta) {
+						before.storage.extend(from.map(|value| (index, value)));
+						after.storage.extend(to.map(|value| (index, value)));
+					}
+				}
+			}
+
+			if overlay.inner().exists(address) {
+				// Unchanged fields of the account are still reported before.
+				let full = self.account(overlay.inner(), address, BTreeSet::new());
+				before.balance = before.balance.or(full.balance);
+				before.nonce = before.nonce.or(full.nonce);
+				before.code = before.code.or(full.code);
+				pre.insert(address, before);
+			}
+			if !removed && !overlay.deleted(address) {
+				post.insert(address, after);
+			}
+		}
+
+		Prestate::Diff { pre, post }
+	}
+
+	fn account<B: RuntimeBaseBackend>(
+		&self,
+		backend: &B,
+		address: H160,
+		slots: BTreeSet<H256>,
+	) -> PrestateAccount {
+		let nonce = backend.nonce(address).low_u64();
+		let code = backend.code(address);
+
+		PrestateAccount {
+			balance: Some(backend.balance(address)),
+			nonce: Some(nonce).filter(|nonce| *nonce != 0),
+			code: Some(code).filter(|code| !self.config.disable_code && !code.is_empty()),
+			storage: if self.config.disable_storage {
+				BTreeMap::new()
+			} else {
+				slots
+					.into_iter()
+					.map(|index| (index, backend.storage(address, index)))
+					.collect()
+			},
+		}
+	}
+}
+
+/// The values before and after a change, if any.
+fn values<T>(delta: Delta<T>) -> Option<(Option<T>, Option<T>)> {
+	match delta {
+		Delta::Unchanged => None,
+		Delta::Added(to) => Some((None, Some(to))),
+		Delta::Removed(from) => Some((Some(from), None)),
+		Delta::Changed { from, to } => Some((Some(from), Some(to))),
+	}
+}
+
+fn serialize_code<S: serde::Serializer>(
+	value: &Option<Vec<u8>>,
+	serializer: S,
+) -> Result<S::Ok, S::Error> {
+	match value {
+		Some(code) => serializer.serialize_str(&format!("0x{}", hex::encode(code))),
+		None => serializer.serialize_none(),
+	}
+}
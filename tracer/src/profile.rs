@@ -0,0 +1,164 @@
+use std::{
+	collections::BTreeMap,
+	io,
+	time::{Duration, Instant},
+};
+
+use evm::{
+	interpreter::{error::ExitError, opcode::Opcode, runtime::GasState},
+	standard::Machine,
+};
+use primitive_types::{H160, U256};
+
+use crate::{FrameEnter, FrameExit, FrameKind, FrameTracer};
+
+/// Gas and time spent in an opcode, by a [GasProfiler].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct OpcodeProfile {
+	/// Number of evaluations.
+	pub count: u64,
+	/// Gas charged in total. For calls and creates, this does not include
+	/// the gas given to the subcalls.
+	pub gas: u64,
+	/// Time spent evaluating in total.
+	pub time: Duration,
+}
+
+/// Gas and time spent in a call frame, by a [GasProfiler].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FrameProfile {
+	/// Kind of the frame.
+	pub kind: FrameKind,
+	/// Address called, or the created address.
+	pub address: H160,
+	/// Depth of the frame, zero for the transaction.
+	pub depth: usize,
+	/// Gas used, including the subcalls.
+	pub gas_used: U256,
+	/// Time spent, including the subcalls.
+	pub time: Duration,
+}
+
+/// A tracer accumulating the gas and time spent per opcode and per call
+/// frame.
+///
+/// The gas charged by the opcodes is also accumulated per call stack, and
+/// can be exported with [GasProfiler::write_collapsed] for flame graph tools
+/// such as `inferno` or `flamegraph.pl`.
+#[derive(Clone, Debug, Default)]
+pub struct GasProfiler {
+	opcodes: BTreeMap<u8, OpcodeProfile>,
+	frames: Vec<FrameProfile>,
+	/// Frames entered and not exited.
+	stack: Vec<EnteredFrame>,
+	collapsed: BTreeMap<String, u64>,
+	/// Opcode being evaluated, its gas before and when it started.
+	current: Option<(Opcode, u64, Instant)>,
+}
+
+#[derive(Clone, Debug)]
+struct EnteredFrame {
+	/// Index in the frame profiles.
+	index: usize,
+	/// Call stack of the frame in the collapsed stacks.
+	stack: String,
+	gas: U256,
+	start: Instant,
+}
+
+impl GasProfiler {
+	/// Create a new profiler.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Profile of the evaluated opcodes.
+	pub fn opcodes(&self) -> impl Iterator<Item = (Opcode, &OpcodeProfile)> {
+		self.opcodes
+			.iter()
+			.map(|(opcode, profile)| (Opcode(*opcode), profile))
+	}
+
+	/// Profile of the call frames, in the order they were entered.
+	pub fn frames(&self) -> &[FrameProfile] {
+		&self.frames
+	}
+
+	/// Write the gas charged per call stack in the collapsed stack format,
+	/// one `frame;frame;OPCODE gas` line per stack.
+	pub fn write_collapsed<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+		for (stack, gas) in &self.collapsed {
+			writeln!(writer, "{} {}", stack, gas)?;
+		}
+		Ok(())
+	}
+}
+
+impl<H> crate::standard::EvalTracer<H> for GasProfiler {
+	fn on_eval(&mut self, machine: &Machine, _handle: &H, opcode: Opcode, _position: usize) {
+		self.current = Some((opcode, machine.state.gas().low_u64(), Instant::now()));
+	}
+
+	fn after_eval(&mut self, machine: &Machine, _handle: &H, _error: Option<&ExitError>) {
+		let (opcode, gas, start) = match self.current.take() {
+			Some(current) => current,
+			None => return,
+		};
+		let time = start.elapsed();
+		let gas = gas.saturating_sub(machine.state.gas().low_u64());
+
+		let profile = self.opcodes.entry(opcode.as_u8()).or_default();
+		profile.count += 1;
+		profile.gas += gas;
+		profile.time += time;
+
+		let name = match opcode.name() {
+			Some(name) => name.into(),
+			None => format!("{:#04x}", opcode.as_u8()),
+		};
+		let stack = match self.stack.last() {
+			Some(frame) => format!("{};{}", frame.stack, name),
+			None => name,
+		};
+		*self.collapsed.entry(stack).or_default() += gas;
+	}
+}
+
+impl FrameTracer for GasProfiler {
+	fn on_enter(&mut self, frame: &FrameEnter<'_>) {
+		let kind = match frame.kind {
+			FrameKind::Call(scheme) => format!("{:?}", scheme).to_uppercase(),
+			FrameKind::Create => "CREATE".into(),
+			FrameKind::Create2 => "CREATE2".into(),
+		};
+		let name = format!("{} {:?}", kind, frame.to);
+		let stack = match self.stack.last() {
+			Some(parent) => format!("{};{}", parent.stack, name),
+			None => name,
+		};
+
+		self.frames.push(FrameProfile {
+			kind: frame.kind,
+			address: frame.to,
+			depth: frame.depth,
+			gas_used: U256::zero(),
+			time: Duration::ZERO,
+		});
+		self.stack.push(EnteredFrame {
+			index: self.frames.len() - 1,
+			stack,
+			gas: frame.gas,
+			start: Instant::now(),
+		});
+	}
+
+	fn on_exit(&mut self, exit: &FrameExit<'_>) {
+		let entered = match self.stack.pop() {
+			Some(entered) => entered,
+			None => return,
+		};
+		let frame = &mut self.frames[entered.index];
+		frame.time = entered.start.elapsed();
+		frame.gas_used = entered.gas.saturating_sub(exit.gas_left);
+	}
+}
@@ -1,14 +1,22 @@
 use evm::{
-	interpreter::opcode::Opcode,
+	interpreter::{error::ExitError, opcode::Opcode},
 	standard::{Machine, State},
 };
 
 pub trait EvalTracer<H> {
 	fn on_eval(&mut self, machine: &Machine, handle: &H, opcode: Opcode, position: usize);
+
+	/// The opcode of the last [EvalTracer::on_eval] is evaluated, failing
+	/// with `error` if set.
+	fn after_eval(&mut self, _machine: &Machine, _handle: &H, _error: Option<&ExitError>) {}
 }
 
 impl<'config, H, T: EvalTracer<H>> crate::EvalTracer<State<'config>, H> for T {
 	fn on_eval(&mut self, machine: &Machine, handle: &H, opcode: Opcode, position: usize) {
 		EvalTracer::<H>::on_eval(self, machine, handle, opcode, position)
 	}
+
+	fn after_eval(&mut self, machine: &Machine, handle: &H, error: Option<&ExitError>) {
+		EvalTracer::<H>::after_eval(self, machine, handle, error)
+	}
 }
@@ -0,0 +1,257 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use evm::{
+	interpreter::{
+		error::ExitError,
+		opcode::Opcode,
+		runtime::{GasState, RuntimeBaseBackend, RuntimeState},
+	},
+	standard::Machine,
+};
+use primitive_types::{H160, H256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::{error_message, FrameEnter, FrameExit, FrameTracer};
+
+/// Options of a [StructLogger], as in the config of geth's struct logger.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StructLoggerConfig {
+	/// Do not capture the memory.
+	pub disable_memory: bool,
+	/// Do not capture the stack.
+	pub disable_stack: bool,
+	/// Do not capture the storage.
+	pub disable_storage: bool,
+	/// Maximum number of entries to capture, zero for no limit.
+	pub limit: usize,
+}
+
+/// An opcode evaluation captured by a [StructLogger].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+	/// Position of the opcode in the code.
+	pub pc: usize,
+	/// Mnemonic of the opcode.
+	pub op: String,
+	/// Gas left before the opcode.
+	pub gas: u64,
+	/// Gas charged by the opcode. For calls and creates, this does not
+	/// include the gas given to the subcall.
+	pub gas_cost: u64,
+	/// Depth of the call frame, one for the transaction.
+	pub depth: usize,
+	/// Error of the opcode, if it failed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Stack before the opcode, from bottom to top.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// Memory before the opcode, shared with the previous log while the
+	/// memory does not change.
+	#[serde(
+		serialize_with = "serialize_memory",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub memory: Option<Arc<[u8]>>,
+	/// Storage of the contract accessed so far, only for `SLOAD` and
+	/// `SSTORE`.
+	#[serde(
+		serialize_with = "serialize_storage",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+/// Outcome of a transaction traced by a [StructLogger], in the format of
+/// geth's `debug_traceTransaction`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLoggerResult {
+	/// Gas used by the transaction.
+	pub gas: u64,
+	/// Whether the transaction failed.
+	pub failed: bool,
+	/// Return value of the transaction.
+	#[serde(serialize_with = "serialize_hex")]
+	pub return_value: Vec<u8>,
+	/// Captured opcode evaluations, in order.
+	pub struct_logs: Vec<StructLog>,
+}
+
+/// A tracer capturing every opcode evaluation, like geth's struct logger.
+#[derive(Clone, Debug, Default)]
+pub struct StructLogger {
+	config: StructLoggerConfig,
+	logs: Vec<StructLog>,
+	/// Whether the opcode being evaluated is captured.
+	capturing: bool,
+	depth: usize,
+	gas_limit: U256,
+	storage: BTreeMap<H160, BTreeMap<H256, H256>>,
+	/// Memory of the current frame last captured, with the version and the
+	/// length it was captured at.
+	memory: Option<(u64, usize, Arc<[u8]>)>,
+	result: Option<(U256, bool, Vec<u8>)>,
+}
+
+impl StructLogger {
+	/// Create a new tracer.
+	pub fn new(config: StructLoggerConfig) -> Self {
+		Self {
+			config,
+			..Default::default()
+		}
+	}
+
+	/// Captured opcode evaluations so far.
+	pub fn logs(&self) -> &[StructLog] {
+		&self.logs
+	}
+
+	/// The outcome of the transaction, once it is finished.
+	pub fn into_result(self) -> Option<StructLoggerResult> {
+		let (gas_left, failed, return_value) = self.result?;
+
+		Some(StructLoggerResult {
+			gas: self.gas_limit.saturating_sub(gas_left).low_u64(),
+			failed,
+			return_value,
+			struct_logs: self.logs,
+		})
+	}
+}
+
+impl<H: RuntimeBaseBackend> crate::standard::EvalTracer<H> for StructLogger {
+	fn on_eval(&mut self, machine: &Machine, handle: &H, opcode: Opcode, position: usize) {
+		self.capturing = self.config.limit == 0 || self.logs.len() < self.config.limit;
+		if !self.capturing {
+			return;
+		}
+
+		let address = AsRef::<RuntimeState>::as_ref(&machine.state)
+			.context
+			.address;
+		let storage = if !self.config.disable_storage
+			&& (opcode == Opcode::SLOAD || opcode == Opcode::SSTORE)
+		{
+			if let Ok(index) = machine.stack.peek(0) {
+				let value = if opcode == Opcode::SLOAD {
+					handle.storage(address, index)
+				} else {
+					machine.stack.peek(1).unwrap_or_default()
+				};
+				self.storage
+					.entry(address)
+					.or_default()
+					.insert(index, value);
+			}
+			self.storage.get(&address).cloned()
+		} else {
+			None
+		};
+
+		let memory = if self.config.disable_memory {
+			None
+		} else {
+			let len = machine.memory.effective_len().low_u64() as usize;
+			let version = machine.memory.version();
+			match &self.memory {
+				Some((last_version, last_len, memory))
+					if *last_version == version && *last_len == len =>
+				{
+					Some(memory.clone())
+				}
+				_ => {
+					let memory = Arc::<[u8]>::from(machine.memory.get(0, len));
+					self.memory = Some((version, len, memory.clone()));
+					Some(memory)
+				}
+			}
+		};
+
+		let stack = if self.config.disable_stack {
+			None
+		} else {
+			Some(
+				machine
+					.stack
+					.data()
+					.iter()
+					.map(|value| U256::from_big_endian(&value[..]))
+					.collect(),
+			)
+		};
+
+		self.logs.push(StructLog {
+			pc: position,
+			op: match opcode.name() {
+				Some(name) => name.into(),
+				None => format!("opcode {:#04x} not defined", opcode.as_u8()),
+			},
+			gas: machine.state.gas().low_u64(),
+			gas_cost: 0,
+			depth: self.depth,
+			error: None,
+			stack,
+			memory,
+			storage,
+		});
+	}
+
+	fn after_eval(&mut self, machine: &Machine, _handle: &H, error: Option<&ExitError>) {
+		if !self.capturing {
+			return;
+		}
+
+		if let Some(log) = self.logs.last_mut() {
+			log.gas_cost = log.gas.saturating_sub(machine.state.gas().low_u64());
+			log.error = error.map(error_message);
+		}
+	}
+}
+
+impl FrameTracer for StructLogger {
+	fn on_enter(&mut self, frame: &FrameEnter<'_>) {
+		if self.depth == 0 {
+			self.gas_limit = frame.gas;
+		}
+		self.depth += 1;
+		self.memory = None;
+	}
+
+	fn on_exit(&mut self, exit: &FrameExit<'_>) {
+		self.depth = self.depth.saturating_sub(1);
+		self.memory = None;
+		if self.depth == 0 {
+			self.result = Some((exit.gas_left, exit.result.is_err(), exit.output.to_vec()));
+		}
+	}
+}
+
+fn serialize_hex<S: serde::Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+	serializer.serialize_str(&hex::encode(value))
+}
+
+fn serialize_memory<S: serde::Serializer>(
+	value: &Option<Arc<[u8]>>,
+	serializer: S,
+) -> Result<S::Ok, S::Error> {
+	let words = value
+		.iter()
+		.flat_map(|memory| memory.chunks(32).map(hex::encode));
+	serializer.collect_seq(words)
+}
+
+fn serialize_storage<S: serde::Serializer>(
+	value: &Option<BTreeMap<H256, H256>>,
+	serializer: S,
+) -> Result<S::Ok, S::Error> {
+	let entries = value.iter().flat_map(|storage| {
+		storage
+			.iter()
+			.map(|(index, value)| (hex::encode(index), hex::encode(value)))
+	});
+	serializer.collect_map(entries)
+}
@@ -0,0 +1,135 @@
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+	standard::{Config, Etable, EtableResolver, Invoker, TransactArgs},
+};
+use evm_tracer::{CallTracer, CallTracerConfig, TracedBackend, TracingInvoker};
+use primitive_types::{H160, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const OUTER: H160 = H160::repeat_byte(0x22);
+const INNER: H160 = H160::repeat_byte(0x33);
+
+fn trace(config: CallTracerConfig) -> CallTracer {
+	let evm_config = Config::shanghai();
+
+	// Store 42, log it and return it.
+	let inner_code = hex::decode("602a60005260206000a060206000f3").unwrap();
+	// Call the inner contract with all gas and return what it returned.
+	let outer_code = hex::decode(format!(
+		"6020600060006000600073{}5af160206000f3",
+		hex::encode(INNER)
+	))
+	.unwrap();
+
+	let backend = InMemoryBackend::from_genesis(
+		InMemoryEnvironment {
+			block_gas_limit: U256::from(30_000_000),
+			..Default::default()
+		},
+		[
+			(
+				CALLER,
+				InMemoryAccount {
+					balance: U256::from(1_000_000_000),
+					..Default::default()
+				},
+			),
+			(
+				OUTER,
+				InMemoryAccount {
+					code: outer_code,
+					..Default::default()
+				},
+			),
+			(
+				INNER,
+				InMemoryAccount {
+					code: inner_code,
+					..Default::default()
+				},
+			),
+		]
+		.into(),
+	);
+
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let resolver = EtableResolver::new(&evm_config, &(), &etable);
+	let invoker = TracingInvoker::new(Invoker::new(&evm_config, &resolver));
+	let args = TransactArgs::Call {
+		caller: CALLER,
+		address: OUTER,
+		value: U256::zero(),
+		data: vec![0xab],
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	};
+
+	let mut traced = TracedBackend::new(backend.overlay(), CallTracer::new(config));
+	evm::transact(args, None, &mut traced, &invoker).unwrap();
+	traced.into_parts().1
+}
+
+#[test]
+fn nested_calls_and_logs() {
+	let tracer = trace(CallTracerConfig {
+		only_top_call: false,
+		with_log: true,
+	});
+	let frame = tracer.result().unwrap();
+
+	assert_eq!(frame.kind, "CALL");
+	assert_eq!(frame.from, CALLER);
+	assert_eq!(frame.to, Some(OUTER));
+	assert_eq!(frame.gas, U256::from(100_000));
+	assert_eq!(frame.input, vec![0xab]);
+	assert_eq!(frame.output, H256_42.to_vec());
+	assert!(frame.logs.is_empty());
+	assert_eq!(frame.calls.len(), 1);
+
+	let call = &frame.calls[0];
+	assert_eq!(call.kind, "CALL");
+	assert_eq!(call.from, OUTER);
+	assert_eq!(call.to, Some(INNER));
+	assert_eq!(call.output, H256_42.to_vec());
+	assert_eq!(call.logs.len(), 1);
+	assert_eq!(call.logs[0].address, INNER);
+	assert_eq!(call.logs[0].position, U256::zero());
+	assert!(call.gas_used > U256::zero() && call.gas_used < call.gas);
+	assert!(frame.gas_used > call.gas_used + U256::from(21_000));
+
+	let json = serde_json::to_value(frame).unwrap();
+	assert_eq!(json["type"], "CALL");
+	assert_eq!(json["input"], "0xab");
+	assert_eq!(json["gas"], "0x186a0");
+	assert_eq!(json["calls"][0]["logs"][0]["position"], "0x0");
+	assert!(json.get("error").is_none());
+}
+
+#[test]
+fn only_top_call() {
+	let tracer = trace(CallTracerConfig {
+		only_top_call: true,
+		with_log: true,
+	});
+	let frame = tracer.result().unwrap();
+
+	assert!(frame.calls.is_empty());
+	assert!(frame.logs.is_empty());
+	assert_eq!(frame.output, H256_42.to_vec());
+}
+
+const H256_42: [u8; 32] = {
+	let mut value = [0; 32];
+	value[31] = 42;
+	value
+};
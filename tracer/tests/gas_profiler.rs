@@ -0,0 +1,105 @@
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+	interpreter::opcode::Opcode,
+	standard::{Config, Etable, EtableResolver, Invoker, TransactArgs},
+};
+use evm_tracer::{GasProfiler, TracedBackend, TracingEtable, TracingInvoker};
+use primitive_types::{H160, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const OUTER: H160 = H160::repeat_byte(0x22);
+const INNER: H160 = H160::repeat_byte(0x33);
+
+#[test]
+fn gas_per_opcode_and_frame() {
+	let config = Config::shanghai();
+
+	// Store 42 and return it.
+	let inner_code = hex::decode("602a60005260206000f3").unwrap();
+	// Call the inner contract with all gas.
+	let outer_code = hex::decode(format!(
+		"6000600060006000600073{}5af100",
+		hex::encode(INNER)
+	))
+	.unwrap();
+
+	let backend = InMemoryBackend::from_genesis(
+		InMemoryEnvironment::default(),
+		[
+			(
+				CALLER,
+				InMemoryAccount {
+					balance: U256::from(1_000_000_000),
+					..Default::default()
+				},
+			),
+			(
+				OUTER,
+				InMemoryAccount {
+					code: outer_code,
+					..Default::default()
+				},
+			),
+			(
+				INNER,
+				InMemoryAccount {
+					code: inner_code,
+					..Default::default()
+				},
+			),
+		]
+		.into(),
+	);
+
+	let etable = TracingEtable::new((
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	));
+	let resolver = EtableResolver::new(&config, &(), &etable);
+	let invoker = TracingInvoker::new(Invoker::new(&config, &resolver));
+	let args = TransactArgs::Call {
+		caller: CALLER,
+		address: OUTER,
+		value: U256::zero(),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	};
+
+	let mut traced = TracedBackend::new(backend.overlay(), GasProfiler::new());
+	evm::transact(args, None, &mut traced, &invoker).unwrap();
+	let profiler = traced.into_parts().1;
+
+	let push1 = profiler
+		.opcodes()
+		.find(|(opcode, _)| *opcode == Opcode::PUSH1)
+		.unwrap()
+		.1;
+	assert_eq!(push1.count, 9);
+	assert_eq!(push1.gas, 27);
+
+	let frames = profiler.frames();
+	assert_eq!(frames.len(), 2);
+	assert_eq!(frames[0].address, OUTER);
+	assert_eq!(frames[1].address, INNER);
+	assert_eq!(frames[1].depth, 1);
+	// Four pushes, MSTORE with memory expansion and RETURN.
+	assert_eq!(frames[1].gas_used, U256::from(4 * 3 + 6));
+
+	let mut collapsed = Vec::new();
+	profiler.write_collapsed(&mut collapsed).unwrap();
+	let collapsed = String::from_utf8(collapsed).unwrap();
+	let stack = format!("CALL {:?};CALL {:?};MSTORE 6", OUTER, INNER);
+	assert!(collapsed.lines().any(|line| line == stack));
+	assert!(collapsed
+		.lines()
+		.any(|line| line.starts_with(&format!("CALL {:?};CALL ", OUTER))
+			&& !line.contains(&format!("{:?}", INNER))));
+}
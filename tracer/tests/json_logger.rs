@@ -0,0 +1,115 @@
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+	standard::{Config, Etable, EtableResolver, Invoker, TransactArgs},
+};
+use evm_tracer::{JsonLogger, JsonLoggerConfig, TracedBackend, TracingEtable, TracingInvoker};
+use primitive_types::{H160, U256};
+use serde_json::Value;
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const CONTRACT: H160 = H160::repeat_byte(0x22);
+
+fn trace(config: JsonLoggerConfig) -> Vec<Value> {
+	let evm_config = Config::shanghai();
+
+	// Store 1 at slot 0, then clear it for a refund.
+	let code = hex::decode("6001600055600060005500").unwrap();
+
+	let backend = InMemoryBackend::from_genesis(
+		InMemoryEnvironment::default(),
+		[
+			(
+				CALLER,
+				InMemoryAccount {
+					balance: U256::from(1_000_000_000),
+					..Default::default()
+				},
+			),
+			(
+				CONTRACT,
+				InMemoryAccount {
+					code,
+					..Default::default()
+				},
+			),
+		]
+		.into(),
+	);
+
+	let etable = TracingEtable::new((
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	));
+	let resolver = EtableResolver::new(&evm_config, &(), &etable);
+	let invoker = TracingInvoker::new(Invoker::new(&evm_config, &resolver));
+	let args = TransactArgs::Call {
+		caller: CALLER,
+		address: CONTRACT,
+		value: U256::zero(),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	};
+
+	let mut traced = TracedBackend::new(backend.overlay(), JsonLogger::new(Vec::new(), config));
+	evm::transact(args, None, &mut traced, &invoker).unwrap();
+	let output = traced.into_parts().1.into_inner().unwrap();
+
+	String::from_utf8(output)
+		.unwrap()
+		.lines()
+		.map(|line| serde_json::from_str(line).unwrap())
+		.collect()
+}
+
+#[test]
+fn opcodes_are_written() {
+	let lines = trace(JsonLoggerConfig::default());
+	assert_eq!(lines.len(), 8);
+
+	let first = &lines[0];
+	assert_eq!(first["pc"], 0);
+	assert_eq!(first["op"], 0x60);
+	assert_eq!(first["opName"], "PUSH1");
+	assert_eq!(first["gas"], "0x13498");
+	assert_eq!(first["gasCost"], "0x3");
+	assert_eq!(first["memSize"], 0);
+	assert_eq!(first["stack"], Value::Array(Vec::new()));
+	assert_eq!(first["depth"], 1);
+	assert_eq!(first["refund"], 0);
+	assert!(first.get("memory").is_none());
+	assert!(first.get("error").is_none());
+
+	let second_sstore = &lines[5];
+	assert_eq!(second_sstore["stack"][0], "0x0");
+	assert_eq!(second_sstore["refund"], 0);
+	let stop = &lines[6];
+	assert_eq!(stop["opName"], "STOP");
+	assert_eq!(stop["refund"], 19_900);
+
+	let summary = &lines[7];
+	assert_eq!(summary["output"], "");
+	assert!(summary["gasUsed"].is_string());
+	assert!(summary.get("error").is_none());
+}
+
+#[test]
+fn capture_options() {
+	let lines = trace(JsonLoggerConfig {
+		enable_memory: true,
+		disable_stack: true,
+		enable_return_data: true,
+	});
+
+	let first = &lines[0];
+	assert_eq!(first["memory"], "0x");
+	assert_eq!(first["returnData"], "0x");
+	assert!(first.get("stack").is_none());
+}
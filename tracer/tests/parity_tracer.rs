@@ -0,0 +1,127 @@
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+	standard::{Config, Etable, EtableResolver, Invoker, TransactArgs},
+};
+use evm_tracer::{ParityAction, ParityResult, ParityTracer, TracedBackend, TracingInvoker};
+use primitive_types::{H160, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const OUTER: H160 = H160::repeat_byte(0x22);
+const INNER: H160 = H160::repeat_byte(0x33);
+const BENEFICIARY: H160 = H160::repeat_byte(0x44);
+
+#[test]
+fn flat_traces() {
+	let config = Config::shanghai();
+
+	// Self-destruct to the beneficiary.
+	let inner_code = hex::decode(format!("73{}ff", hex::encode(BENEFICIARY))).unwrap();
+	// Call the inner contract with all gas.
+	let outer_code = hex::decode(format!(
+		"6000600060006000600073{}5af100",
+		hex::encode(INNER)
+	))
+	.unwrap();
+
+	let backend = InMemoryBackend::from_genesis(
+		InMemoryEnvironment::default(),
+		[
+			(
+				CALLER,
+				InMemoryAccount {
+					balance: U256::from(1_000_000_000),
+					..Default::default()
+				},
+			),
+			(
+				OUTER,
+				InMemoryAccount {
+					code: outer_code,
+					..Default::default()
+				},
+			),
+			(
+				INNER,
+				InMemoryAccount {
+					balance: U256::from(100),
+					code: inner_code,
+					..Default::default()
+				},
+			),
+		]
+		.into(),
+	);
+
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let resolver = EtableResolver::new(&config, &(), &etable);
+	let invoker = TracingInvoker::new(Invoker::new(&config, &resolver));
+	let args = TransactArgs::Call {
+		caller: CALLER,
+		address: OUTER,
+		value: U256::zero(),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	};
+
+	let mut traced = TracedBackend::new(backend.overlay(), ParityTracer::new());
+	evm::transact(args, None, &mut traced, &invoker).unwrap();
+	let traces = traced.into_parts().1.into_traces();
+
+	assert_eq!(traces.len(), 3);
+	assert_eq!(
+		traces
+			.iter()
+			.map(|trace| (
+				trace.kind.as_str(),
+				trace.subtraces,
+				trace.trace_address.clone()
+			))
+			.collect::<Vec<_>>(),
+		[
+			("call", 1, vec![]),
+			("call", 1, vec![0]),
+			("suicide", 0, vec![0, 0]),
+		]
+	);
+
+	let call = match &traces[1].action {
+		ParityAction::Call(call) => call,
+		_ => panic!("not a call"),
+	};
+	assert_eq!(call.call_type, "call");
+	assert_eq!(call.from, OUTER);
+	assert_eq!(call.to, INNER);
+	// The beneficiary is cold and new.
+	assert!(matches!(
+		traces[1].result,
+		Some(ParityResult::Call { gas_used, .. }) if gas_used == U256::from(3 + 5000 + 2600 + 25000)
+	));
+
+	let suicide = match &traces[2].action {
+		ParityAction::Suicide(suicide) => suicide,
+		_ => panic!("not a self-destruct"),
+	};
+	assert_eq!(suicide.address, INNER);
+	assert_eq!(suicide.refund_address, BENEFICIARY);
+	assert_eq!(suicide.balance, U256::from(100));
+
+	let json = serde_json::to_value(&traces).unwrap();
+	assert_eq!(json[0]["traceAddress"], serde_json::json!([]));
+	assert_eq!(json[0]["result"]["output"], "0x");
+	assert_eq!(
+		json[2]["action"]["refundAddress"],
+		format!("{:?}", BENEFICIARY)
+	);
+	assert_eq!(json[2]["result"], serde_json::Value::Null);
+}
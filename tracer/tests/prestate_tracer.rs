@@ -0,0 +1,116 @@
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+	standard::{Config, Etable, EtableResolver, Invoker, TransactArgs},
+};
+use evm_tracer::{Prestate, PrestateTracer, PrestateTracerConfig};
+use primitive_types::{H160, H256, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const CONTRACT: H160 = H160::repeat_byte(0x22);
+
+fn trace(config: PrestateTracerConfig) -> Prestate {
+	let evm_config = Config::shanghai();
+
+	// Load slot 1, and store it plus 2 at slot 2.
+	let code = hex::decode("6001546002016002555f").unwrap();
+
+	let backend = InMemoryBackend::from_genesis(
+		InMemoryEnvironment::default(),
+		[
+			(
+				CALLER,
+				InMemoryAccount {
+					balance: U256::from(1_000_000_000),
+					nonce: U256::from(3),
+					..Default::default()
+				},
+			),
+			(
+				CONTRACT,
+				InMemoryAccount {
+					code: code.clone(),
+					storage: [(H256::from_low_u64_be(1), H256::from_low_u64_be(5))].into(),
+					..Default::default()
+				},
+			),
+		]
+		.into(),
+	);
+
+	let etable = (
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	);
+	let resolver = EtableResolver::new(&evm_config, &(), &etable);
+	let invoker = Invoker::new(&evm_config, &resolver);
+	let args = TransactArgs::Call {
+		caller: CALLER,
+		address: CONTRACT,
+		value: U256::from(10),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	};
+
+	let mut overlay = backend.overlay();
+	evm::transact(args, None, &mut overlay, &invoker).unwrap();
+	PrestateTracer::new(config).trace(&overlay)
+}
+
+#[test]
+fn accessed_accounts_before_execution() {
+	let accounts = match trace(PrestateTracerConfig::default()) {
+		Prestate::Accounts(accounts) => accounts,
+		_ => panic!("not in diff mode"),
+	};
+
+	// The coinbase is accessed too, since Shanghai.
+	assert_eq!(accounts.len(), 3);
+	assert!(accounts.contains_key(&H160::zero()));
+	assert_eq!(accounts[&CALLER].balance, Some(U256::from(1_000_000_000)));
+	assert_eq!(accounts[&CALLER].nonce, Some(3));
+	assert_eq!(accounts[&CONTRACT].balance, Some(U256::zero()));
+	assert_eq!(accounts[&CONTRACT].nonce, None);
+	assert!(accounts[&CONTRACT].code.is_some());
+	assert_eq!(
+		accounts[&CONTRACT].storage,
+		[
+			(H256::from_low_u64_be(1), H256::from_low_u64_be(5)),
+			(H256::from_low_u64_be(2), H256::zero()),
+		]
+		.into()
+	);
+}
+
+#[test]
+fn diff_mode() {
+	let (pre, post) = match trace(PrestateTracerConfig {
+		diff_mode: true,
+		..Default::default()
+	}) {
+		Prestate::Diff { pre, post } => (pre, post),
+		_ => panic!("in diff mode"),
+	};
+
+	assert_eq!(pre[&CALLER].nonce, Some(3));
+	assert_eq!(post[&CALLER].nonce, Some(4));
+	assert_eq!(post[&CALLER].balance, Some(U256::from(1_000_000_000 - 10)));
+	assert_eq!(pre[&CONTRACT].balance, Some(U256::zero()));
+	assert!(pre[&CONTRACT].code.is_some());
+	assert_eq!(post[&CONTRACT].balance, Some(U256::from(10)));
+	assert_eq!(post[&CONTRACT].code, None);
+	assert_eq!(
+		post[&CONTRACT].storage,
+		[(H256::from_low_u64_be(2), H256::from_low_u64_be(7))].into()
+	);
+
+	let json = serde_json::to_value(&post).unwrap();
+	assert_eq!(json[format!("{:?}", CONTRACT)]["balance"], "0xa");
+}
@@ -0,0 +1,133 @@
+use evm::{
+	backend::{InMemoryAccount, InMemoryBackend, InMemoryEnvironment},
+	standard::{Config, Etable, EtableResolver, Invoker, TransactArgs},
+};
+use evm_tracer::{
+	StructLogger, StructLoggerConfig, StructLoggerResult, TracedBackend, TracingEtable,
+	TracingInvoker,
+};
+use primitive_types::{H160, H256, U256};
+
+const CALLER: H160 = H160::repeat_byte(0x11);
+const CONTRACT: H160 = H160::repeat_byte(0x22);
+
+fn trace(config: StructLoggerConfig) -> StructLoggerResult {
+	let evm_config = Config::shanghai();
+
+	// Store 42 at slot 1, then load it and store it in memory.
+	let code = hex::decode("602a600155600154600052").unwrap();
+
+	let backend = InMemoryBackend::from_genesis(
+		InMemoryEnvironment::default(),
+		[
+			(
+				CALLER,
+				InMemoryAccount {
+					balance: U256::from(1_000_000_000),
+					..Default::default()
+				},
+			),
+			(
+				CONTRACT,
+				InMemoryAccount {
+					code,
+					..Default::default()
+				},
+			),
+		]
+		.into(),
+	);
+
+	let etable = TracingEtable::new((
+		Etable::single(evm::standard::eval_gasometer),
+		Etable::runtime(),
+	));
+	let resolver = EtableResolver::new(&evm_config, &(), &etable);
+	let invoker = TracingInvoker::new(Invoker::new(&evm_config, &resolver));
+	let args = TransactArgs::Call {
+		caller: CALLER,
+		address: CONTRACT,
+		value: U256::zero(),
+		data: Vec::new(),
+		nonce: None,
+		fee_payer: None,
+		gas_limit: U256::from(100_000),
+		gas_price: U256::zero(),
+		max_priority_fee_per_gas: None,
+		access_list: Vec::new(),
+		blob_hashes: Vec::new(),
+		max_fee_per_blob_gas: None,
+		overrides: Default::default(),
+	};
+
+	let mut traced = TracedBackend::new(backend.overlay(), StructLogger::new(config));
+	evm::transact(args, None, &mut traced, &invoker).unwrap();
+	traced.into_parts().1.into_result().unwrap()
+}
+
+#[test]
+fn opcodes_are_logged() {
+	let result = trace(StructLoggerConfig::default());
+	let ops = result
+		.struct_logs
+		.iter()
+		.map(|log| log.op.as_str())
+		.collect::<Vec<_>>();
+
+	assert!(!result.failed);
+	assert_eq!(
+		ops,
+		["PUSH1", "PUSH1", "SSTORE", "PUSH1", "SLOAD", "PUSH1", "MSTORE"]
+	);
+
+	let first = &result.struct_logs[0];
+	assert_eq!(first.pc, 0);
+	assert_eq!(first.depth, 1);
+	assert_eq!(first.gas_cost, 3);
+	assert_eq!(first.gas, 100_000 - 21_000);
+	assert_eq!(first.stack, Some(Vec::new()));
+
+	let sstore = &result.struct_logs[2];
+	assert_eq!(sstore.stack, Some(vec![U256::from(42), U256::from(1)]),);
+	assert_eq!(sstore.gas_cost, 22_100);
+	let storage = sstore.storage.as_ref().unwrap();
+	assert_eq!(
+		storage[&H256::from_low_u64_be(1)],
+		H256::from_low_u64_be(42)
+	);
+
+	let mstore = &result.struct_logs[6];
+	assert_eq!(mstore.memory.as_deref(), Some(&[][..]));
+	// The memory did not change, so it is captured once.
+	let first_memory = first.memory.as_ref().unwrap();
+	assert!(result
+		.struct_logs
+		.iter()
+		.all(|log| { std::sync::Arc::ptr_eq(log.memory.as_ref().unwrap(), first_memory) }));
+	assert_eq!(mstore.storage, None);
+	assert_eq!(result.gas, 100_000 - (mstore.gas - mstore.gas_cost));
+
+	let json = serde_json::to_value(&result).unwrap();
+	assert_eq!(json["structLogs"][1]["stack"][0], "0x2a");
+	assert_eq!(
+		json["structLogs"][2]["storage"]
+			["0000000000000000000000000000000000000000000000000000000000000001"],
+		"000000000000000000000000000000000000000000000000000000000000002a"
+	);
+}
+
+#[test]
+fn capture_options() {
+	let result = trace(StructLoggerConfig {
+		disable_memory: true,
+		disable_stack: true,
+		disable_storage: true,
+		limit: 3,
+	});
+
+	assert_eq!(result.struct_logs.len(), 3);
+	assert!(result
+		.struct_logs
+		.iter()
+		.all(|log| log.stack.is_none() && log.memory.is_none() && log.storage.is_none()));
+}